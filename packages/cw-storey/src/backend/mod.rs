@@ -0,0 +1,214 @@
+use std::ops::{Deref, DerefMut};
+
+#[cfg(feature = "iteration")]
+use cosmwasm_std::Order;
+use cosmwasm_std::{CustomQuery, Deps, DepsMut, Storage};
+#[cfg(feature = "iteration")]
+use storey::storage::{IterableStorage, RevIterableStorage};
+use storey::storage::{StorageBackend, StorageBackendMut};
+
+// The parts of the storage interface that differ between *CosmWasm* versions, which are all
+// about iteration. The rest is shared, since the `Storage` trait, `Order` and `Record` are
+// otherwise the same.
+#[cfg(all(
+    feature = "iteration",
+    feature = "cosmwasm-std-1",
+    not(feature = "cosmwasm-std-2")
+))]
+#[path = "v1.rs"]
+mod glue;
+#[cfg(all(feature = "iteration", feature = "cosmwasm-std-2"))]
+#[path = "v2.rs"]
+mod glue;
+
+/// A wrapper around a type implementing [`cosmwasm_std::Storage`] that integrates it with [`storey`].
+///
+/// It's meant to wrap a reference, usually the `&dyn Storage` of [`Deps`] or the
+/// `&mut dyn Storage` of [`DepsMut`]:
+/// - `CwStorage<&S>` allows reading and iterating (in both directions), and can be shared by
+///   any number of readers,
+/// - `CwStorage<&mut S>` allows writing too.
+///
+/// More generally, it can wrap any pointer to a storage: anything dereferencing to one can be
+/// read from, and anything mutably dereferencing to one can be written to. In particular,
+/// `CwStorage<Box<dyn Storage>>` works with the contract storage a `cw-multi-test` `App`
+/// hands out, which lets tests inspect and seed a contract's state with the same containers
+/// the contract uses:
+///
+/// ```
+/// use cw_multi_test::App;
+/// use cw_storey::containers::{Item, Map};
+/// use cw_storey::CwStorage;
+///
+/// const BALANCES: Map<String, Item<u128>> = Map::new(0);
+///
+/// let mut app = App::default();
+/// let contract = app.api().addr_make("contract");
+///
+/// // seed the contract's state
+/// let mut contract_storage = CwStorage(app.contract_storage_mut(&contract));
+/// BALANCES.access(&mut contract_storage).entry_mut("alice").set(&100).unwrap();
+/// drop(contract_storage);
+///
+/// // inspect it
+/// let contract_storage = CwStorage(app.contract_storage(&contract));
+/// let balance = BALANCES.access(&contract_storage).entry("alice").get().unwrap();
+/// assert_eq!(balance, Some(100));
+/// ```
+///
+/// Containers are then accessed through a reference to the wrapper: `&storage` for reading,
+/// `&mut storage` for writing.
+///
+/// Since `CwStorage<&S>` doesn't implement any of the write traits, writing through it
+/// doesn't compile. Converting [`Deps`] with [`From`] gives exactly that, so query handlers
+/// are statically prevented from writing to storage. [`DepsMut`] converts to a writable
+/// `CwStorage<&mut dyn Storage>`.
+///
+/// # Example
+/// ```
+/// use cosmwasm_std::{Deps, DepsMut, StdResult};
+/// use cw_storey::containers::{Item, Map};
+/// use cw_storey::CwStorage;
+/// use storey::containers::IterableAccessor as _;
+///
+/// const BALANCES: Map<String, Item<u128>> = Map::new(0);
+///
+/// fn deposit(deps: DepsMut, who: &str, amount: u128) -> StdResult<()> {
+///     let mut storage = CwStorage::from(deps);
+///     let mut balances = BALANCES.access(&mut storage);
+///
+///     let balance = balances.entry(who).get()?.unwrap_or(0);
+///     balances.entry_mut(who).set(&(balance + amount))?;
+///     Ok(())
+/// }
+///
+/// fn total(deps: Deps) -> StdResult<u128> {
+///     let storage = CwStorage::from(deps);
+///
+///     BALANCES
+///         .access(&storage)
+///         .values()
+///         .map(|balance| balance.map_err(Into::into))
+///         .sum()
+/// }
+/// ```
+///
+pub struct CwStorage<S>(pub S);
+
+impl<'a, C: CustomQuery> From<Deps<'a, C>> for CwStorage<&'a dyn Storage> {
+    fn from(deps: Deps<'a, C>) -> Self {
+        Self(deps.storage)
+    }
+}
+
+impl<'a, C: CustomQuery> From<DepsMut<'a, C>> for CwStorage<&'a mut dyn Storage> {
+    fn from(deps: DepsMut<'a, C>) -> Self {
+        Self(deps.storage)
+    }
+}
+
+impl<T> StorageBackend for CwStorage<T>
+where
+    T: Deref,
+    T::Target: Storage,
+{
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        Storage::get(&*self.0, key)
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        // The host has no existence check. A key-only range scan would avoid loading the
+        // value, but opening an iterator costs more gas than a read, so a read it is.
+        Storage::get(&*self.0, key).is_some()
+    }
+}
+
+impl<T> StorageBackendMut for CwStorage<T>
+where
+    T: DerefMut,
+    T::Target: Storage,
+{
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        Storage::set(&mut *self.0, key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        Storage::remove(&mut *self.0, key)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<T> IterableStorage for CwStorage<T>
+where
+    T: Deref,
+    T::Target: Storage,
+{
+    type KeysIterator<'a>
+        = Box<dyn Iterator<Item = Vec<u8>> + 'a>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = Box<dyn Iterator<Item = Vec<u8>> + 'a>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>
+    where
+        Self: 'a;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        glue::range_keys(&*self.0, start, end, Order::Ascending)
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        glue::range_values(&*self.0, start, end, Order::Ascending)
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        self.0.range(start, end, Order::Ascending)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<T> RevIterableStorage for CwStorage<T>
+where
+    T: Deref,
+    T::Target: Storage,
+{
+    type RevKeysIterator<'a>
+        = Box<dyn Iterator<Item = Vec<u8>> + 'a>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = Box<dyn Iterator<Item = Vec<u8>> + 'a>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        = Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>
+    where
+        Self: 'a;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        glue::range_keys(&*self.0, start, end, Order::Descending)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        glue::range_values(&*self.0, start, end, Order::Descending)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        self.0.range(start, end, Order::Descending)
+    }
+}
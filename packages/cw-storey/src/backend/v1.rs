@@ -0,0 +1,24 @@
+//! The storage glue for *CosmWasm* 1.x.
+//!
+//! The 1.x `Storage` trait can only iterate over key-value pairs, so keys and values are
+//! taken out of those.
+
+use cosmwasm_std::{Order, Storage};
+
+pub(super) fn range_keys<'a, S: Storage + ?Sized>(
+    storage: &'a S,
+    start: Option<&[u8]>,
+    end: Option<&[u8]>,
+    order: Order,
+) -> Box<dyn Iterator<Item = Vec<u8>> + 'a> {
+    Box::new(storage.range(start, end, order).map(|(key, _)| key))
+}
+
+pub(super) fn range_values<'a, S: Storage + ?Sized>(
+    storage: &'a S,
+    start: Option<&[u8]>,
+    end: Option<&[u8]>,
+    order: Order,
+) -> Box<dyn Iterator<Item = Vec<u8>> + 'a> {
+    Box::new(storage.range(start, end, order).map(|(_, value)| value))
+}
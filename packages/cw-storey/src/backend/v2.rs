@@ -0,0 +1,24 @@
+//! The storage glue for *CosmWasm* 2.x.
+//!
+//! The 2.x `Storage` trait iterates over keys and values on their own, which saves the host
+//! from loading what isn't needed.
+
+use cosmwasm_std::{Order, Storage};
+
+pub(super) fn range_keys<'a, S: Storage + ?Sized>(
+    storage: &'a S,
+    start: Option<&[u8]>,
+    end: Option<&[u8]>,
+    order: Order,
+) -> Box<dyn Iterator<Item = Vec<u8>> + 'a> {
+    storage.range_keys(start, end, order)
+}
+
+pub(super) fn range_values<'a, S: Storage + ?Sized>(
+    storage: &'a S,
+    start: Option<&[u8]>,
+    end: Option<&[u8]>,
+    order: Order,
+) -> Box<dyn Iterator<Item = Vec<u8>> + 'a> {
+    storage.range_values(start, end, order)
+}
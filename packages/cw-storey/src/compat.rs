@@ -0,0 +1,596 @@
+//! Reading data written by [*cw-storage-plus*].
+//!
+//! A contract migrating from *cw-storage-plus* to [`storey`] has to read the state the old
+//! code wrote, which uses a different key layout and stores values as JSON. [`PlusMap`] and
+//! [`PlusItem`] reproduce the layout of a *cw-storage-plus* `Map` and `Item`, so that their
+//! data can be read through any [`storey`] backend and written into new containers, usually
+//! in a migration entrypoint.
+//!
+//! [`PlusMap::migrate`] moves a map's entries into new containers a chunk at a time,
+//! removing them from the old layout, and [`PlusItem::migrate_into`] moves an item in one go.
+//! Data that's only read can also be left behind, or removed by raw key later.
+//!
+//! Old keys share the key space of [`storey`] containers: map namespaces shorter than 256
+//! bytes put a `0` byte first, and an item's key starts with the first byte of its namespace.
+//! Their keys can't collide with [`storey`] keys, since the old keys carry the rest of the
+//! namespace after that byte, but clearing or iterating over a container with the same
+//! prefix would run into the old data. Until it's gone, it's best to keep new containers
+//! away from those prefixes.
+//!
+//! # Layout
+//!
+//! An item is stored under its namespace, as-is.
+//!
+//! A map key is the map's namespace and the key components, where every part but the last
+//! is prefixed with its length as a big-endian `u16`. Strings, addresses and byte keys are
+//! stored as-is, unsigned integers as big-endian, and signed integers as big-endian with the
+//! sign bit flipped. Tuple keys are split into one component per element.
+//!
+//! Values are JSON.
+//!
+//! # Example
+//! ```
+//! use cosmwasm_std::testing::MockStorage;
+//! use cosmwasm_std::{Addr, Storage as _, Uint128};
+//! use cw_storey::compat::PlusMap;
+//! use cw_storey::containers::{Item, Map};
+//! use cw_storey::CwStorage;
+//!
+//! // what the old code declared as `Map<&Addr, Uint128> = Map::new("balances")`
+//! const OLD_BALANCES: PlusMap<Addr, Uint128> = PlusMap::new("balances");
+//! const BALANCES: Map<Addr, Item<Uint128>> = Map::new(1);
+//!
+//! let mut raw = MockStorage::new();
+//! raw.set(b"\x00\x08balancesalice", b"\"100\"");
+//! let mut storage = CwStorage(&mut raw);
+//!
+//! let old: Vec<_> = OLD_BALANCES
+//!     .range(&storage, None, None)
+//!     .collect::<Result<_, _>>()
+//!     .unwrap();
+//! for (owner, balance) in old {
+//!     BALANCES.access(&mut storage).entry_mut(&owner).set(&balance).unwrap();
+//! }
+//!
+//! let alice = Addr::unchecked("alice");
+//! let balance = BALANCES.access(&storage).entry(&alice).get().unwrap();
+//! assert_eq!(balance, Some(Uint128::new(100)));
+//! ```
+//!
+//! [*cw-storage-plus*]: https://github.com/CosmWasm/cw-storage-plus
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::marker::PhantomData;
+#[cfg(feature = "iteration")]
+use std::ops::Bound;
+
+use cosmwasm_std::{Addr, StdError};
+use serde::de::DeserializeOwned;
+use storey::containers::Item;
+use storey::encoding::{DecodableWith, EncodableWith, Encoding};
+use storey::error::ErrorFor;
+#[cfg(feature = "iteration")]
+use storey::storage::IterableStorage;
+use storey::storage::{Storage, StorageMut};
+
+/// A view of a *cw-storage-plus* `Item`. See the [module docs](self).
+///
+/// # Example
+/// ```
+/// use cosmwasm_std::testing::MockStorage;
+/// use cosmwasm_std::Storage as _;
+/// use cw_storey::compat::PlusItem;
+/// use cw_storey::containers::Item;
+/// use cw_storey::CwStorage;
+///
+/// // what the old code declared as `Item<String> = Item::new("owner")`
+/// const OLD_OWNER: PlusItem<String> = PlusItem::new("owner");
+/// const OWNER: Item<String> = Item::new(0);
+///
+/// let mut raw = MockStorage::new();
+/// raw.set(b"owner", b"\"alice\"");
+/// let mut storage = CwStorage(&mut raw);
+///
+/// assert_eq!(OLD_OWNER.get(&storage).unwrap(), Some("alice".to_string()));
+///
+/// assert!(OLD_OWNER.migrate_into(&mut storage, &OWNER).unwrap());
+/// assert_eq!(OWNER.access(&storage).get().unwrap(), Some("alice".to_string()));
+/// assert_eq!(OLD_OWNER.get(&storage).unwrap(), None);
+/// ```
+pub struct PlusItem<'a, T> {
+    namespace: &'a str,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> PlusItem<'a, T> {
+    /// Read the item stored under `namespace` - the string the old `Item` was created with.
+    pub const fn new(namespace: &'a str) -> Self {
+        Self {
+            namespace,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Clone for PlusItem<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for PlusItem<'a, T> {}
+
+impl<'a, T> fmt::Debug for PlusItem<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PlusItem")
+            .field("namespace", &self.namespace)
+            .finish()
+    }
+}
+
+impl<'a, T> PlusItem<'a, T>
+where
+    T: DeserializeOwned,
+{
+    /// Get the value of the item.
+    pub fn get<S: Storage>(&self, storage: &S) -> Result<Option<T>, StdError> {
+        storage
+            .get(self.namespace.as_bytes())
+            .map(|value| cosmwasm_std::from_json(value))
+            .transpose()
+    }
+
+    /// Move the value into the [`storey`] `item`, re-encoding it, and remove it from its old
+    /// key.
+    ///
+    /// Returns whether there was a value to move. If there wasn't, `item` is left untouched.
+    pub fn migrate_into<S, E>(
+        &self,
+        storage: &mut S,
+        item: &Item<T, E>,
+    ) -> Result<bool, PlusMigrateError<ErrorFor<E>>>
+    where
+        S: Storage + StorageMut,
+        E: Encoding,
+        T: EncodableWith<E> + DecodableWith<E>,
+    {
+        let Some(value) = self.get(storage).map_err(PlusMigrateError::Value)? else {
+            return Ok(false);
+        };
+
+        item.access(&mut *storage)
+            .set(&value)
+            .map_err(PlusMigrateError::Write)?;
+        storage.remove(self.namespace.as_bytes());
+
+        Ok(true)
+    }
+}
+
+/// A view of a *cw-storage-plus* `Map`. See the [module docs](self).
+///
+/// `K` is the owned form of the old map's key type: `String` for `&str` keys, `Addr` for
+/// `&Addr` keys, `(Addr, String)` for `(&Addr, &str)` keys, and so on.
+pub struct PlusMap<'a, K, V> {
+    namespace: &'a str,
+    phantom: PhantomData<fn() -> (K, V)>,
+}
+
+impl<'a, K, V> PlusMap<'a, K, V> {
+    /// Read the map stored under `namespace` - the string the old `Map` was created with.
+    pub const fn new(namespace: &'a str) -> Self {
+        Self {
+            namespace,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The raw prefix of all the map's keys.
+    fn prefix(&self) -> Vec<u8> {
+        let mut prefix = Vec::new();
+        push_component(&mut prefix, self.namespace.as_bytes());
+        prefix
+    }
+
+    /// The raw key of the entry for `key`.
+    fn raw_key<Q: PlusKey + ?Sized>(&self, key: &Q) -> Vec<u8> {
+        let mut raw = self.prefix();
+        push_key(&mut raw, key);
+        raw
+    }
+}
+
+impl<'a, K, V> Clone for PlusMap<'a, K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, K, V> Copy for PlusMap<'a, K, V> {}
+
+impl<'a, K, V> fmt::Debug for PlusMap<'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PlusMap")
+            .field("namespace", &self.namespace)
+            .finish()
+    }
+}
+
+impl<'a, K, V> PlusMap<'a, K, V>
+where
+    V: DeserializeOwned,
+{
+    /// Get the value for `key`.
+    pub fn get<S, Q>(&self, storage: &S, key: &Q) -> Result<Option<V>, PlusMapError>
+    where
+        S: Storage,
+        K: Borrow<Q>,
+        Q: PlusKey + ?Sized,
+    {
+        storage
+            .get(&self.raw_key(key))
+            .map(|value| decode_value(&value))
+            .transpose()
+    }
+
+    /// Check if there's an entry for `key`.
+    pub fn has<S, Q>(&self, storage: &S, key: &Q) -> bool
+    where
+        S: Storage,
+        K: Borrow<Q>,
+        Q: PlusKey + ?Sized,
+    {
+        storage.has(&self.raw_key(key))
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<'a, K, V> PlusMap<'a, K, V>
+where
+    K: PlusOwnedKey,
+    V: DeserializeOwned,
+{
+    /// Iterate over the entries with keys between `min` and `max`, in the order
+    /// *cw-storage-plus* iterates them (by raw key). `None` leaves a side unbounded.
+    pub fn range<'s, S: IterableStorage>(
+        &self,
+        storage: &'s S,
+        min: Option<Bound<&K>>,
+        max: Option<Bound<&K>>,
+    ) -> impl Iterator<Item = Result<(K, V), PlusMapError>> + 's {
+        let prefix_len = self.prefix().len();
+        let (start, end) = self.bounds(min, max);
+
+        storage
+            .pairs(Some(&start), end.as_deref())
+            .map(move |(key, value)| Ok((K::from_raw(&key[prefix_len..])?, decode_value(&value)?)))
+    }
+
+    /// Iterate over the keys between `min` and `max`. See [`range`](Self::range).
+    pub fn keys<'s, S: IterableStorage>(
+        &self,
+        storage: &'s S,
+        min: Option<Bound<&K>>,
+        max: Option<Bound<&K>>,
+    ) -> impl Iterator<Item = Result<K, PlusMapError>> + 's {
+        let prefix_len = self.prefix().len();
+        let (start, end) = self.bounds(min, max);
+
+        storage
+            .keys(Some(&start), end.as_deref())
+            .map(move |key| Ok(K::from_raw(&key[prefix_len..])?))
+    }
+
+    /// Move up to `limit` entries out of the map.
+    ///
+    /// `migrate` is called with every entry, in order, and writes it into the new containers,
+    /// converting the key and value as needed. Each entry is removed once it's written, so the
+    /// next call picks up where this one stopped. That way a map too big to move in one
+    /// transaction can be moved over several, a chunk at a time.
+    ///
+    /// # Example
+    /// ```
+    /// use cosmwasm_std::testing::MockStorage;
+    /// use cosmwasm_std::{Addr, Storage as _};
+    /// use cw_storey::compat::PlusMap;
+    /// use cw_storey::containers::{Item, Map};
+    /// use cw_storey::CwStorage;
+    ///
+    /// // the old map stored balances as `u64`, the new one as `u128`
+    /// const OLD_BALANCES: PlusMap<Addr, u64> = PlusMap::new("balances");
+    /// const BALANCES: Map<Addr, Item<u128>> = Map::new(1);
+    ///
+    /// let mut raw = MockStorage::new();
+    /// raw.set(b"\x00\x08balancesalice", b"100");
+    /// raw.set(b"\x00\x08balancesbob", b"200");
+    /// let mut storage = CwStorage(&mut raw);
+    ///
+    /// let migrate = |storage: &mut CwStorage<_>, owner: Addr, balance: u64| {
+    ///     BALANCES
+    ///         .access(storage)
+    ///         .entry_mut(&owner)
+    ///         .set(&u128::from(balance))
+    /// };
+    ///
+    /// let progress = OLD_BALANCES.migrate(&mut storage, 1, migrate).unwrap();
+    /// assert_eq!((progress.migrated, progress.done), (1, false));
+    ///
+    /// let progress = OLD_BALANCES.migrate(&mut storage, 1, migrate).unwrap();
+    /// assert_eq!((progress.migrated, progress.done), (1, true));
+    ///
+    /// let bob = Addr::unchecked("bob");
+    /// assert_eq!(BALANCES.access(&storage).entry(&bob).get().unwrap(), Some(200));
+    /// ```
+    pub fn migrate<S, F, E>(
+        &self,
+        storage: &mut S,
+        limit: usize,
+        mut migrate: F,
+    ) -> Result<MigrationProgress, PlusMigrateError<E>>
+    where
+        S: IterableStorage + StorageMut,
+        F: FnMut(&mut S, K, V) -> Result<(), E>,
+    {
+        let prefix_len = self.prefix().len();
+        let (start, end) = self.bounds(None, None);
+
+        // one more than the limit, to tell whether anything is left after this chunk
+        let mut chunk: Vec<_> = storage
+            .pairs(Some(&start), end.as_deref())
+            .take(limit.saturating_add(1))
+            .collect();
+        let done = chunk.len() <= limit;
+        chunk.truncate(limit);
+
+        for (raw_key, value) in &chunk {
+            let key = K::from_raw(&raw_key[prefix_len..])?;
+            let value = cosmwasm_std::from_json(value).map_err(PlusMigrateError::Value)?;
+
+            migrate(storage, key, value).map_err(PlusMigrateError::Write)?;
+            storage.remove(raw_key);
+        }
+
+        Ok(MigrationProgress {
+            migrated: chunk.len(),
+            done,
+        })
+    }
+
+    /// The raw start (inclusive) and end (exclusive) keys of a range.
+    fn bounds(&self, min: Option<Bound<&K>>, max: Option<Bound<&K>>) -> (Vec<u8>, Option<Vec<u8>>) {
+        let start = match min {
+            Some(Bound::Included(key)) => self.raw_key(key),
+            Some(Bound::Excluded(key)) => successor(self.raw_key(key)),
+            Some(Bound::Unbounded) | None => self.prefix(),
+        };
+        let end = match max {
+            Some(Bound::Included(key)) => Some(successor(self.raw_key(key))),
+            Some(Bound::Excluded(key)) => Some(self.raw_key(key)),
+            Some(Bound::Unbounded) | None => prefix_end(&self.prefix()),
+        };
+
+        (start, end)
+    }
+}
+
+fn decode_value<V: DeserializeOwned>(value: &[u8]) -> Result<V, PlusMapError> {
+    cosmwasm_std::from_json(value).map_err(PlusMapError::Value)
+}
+
+/// The smallest key after `key`.
+#[cfg(feature = "iteration")]
+fn successor(mut key: Vec<u8>) -> Vec<u8> {
+    key.push(0);
+    key
+}
+
+/// The smallest key after all the keys starting with `prefix`, if there is one.
+#[cfg(feature = "iteration")]
+fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last < u8::MAX {
+            end.push(last + 1);
+            return Some(end);
+        }
+    }
+    None
+}
+
+fn push_component(buf: &mut Vec<u8>, component: &[u8]) {
+    let len = u16::try_from(component.len()).expect("key components are at most 65535 bytes");
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(component);
+}
+
+fn push_key<Q: PlusKey + ?Sized>(buf: &mut Vec<u8>, key: &Q) {
+    let components = key.components();
+    if let Some((last, init)) = components.split_last() {
+        for component in init {
+            push_component(buf, component);
+        }
+        buf.extend_from_slice(last);
+    }
+}
+
+/// A key type of *cw-storage-plus*, encoded the way it encodes it.
+pub trait PlusKey {
+    /// The components of the key. When stored, every component but the last one is prefixed
+    /// with its length.
+    fn components(&self) -> Vec<Vec<u8>>;
+}
+
+/// A [`PlusKey`] that can be decoded from the part of a raw key after the map's namespace.
+pub trait PlusOwnedKey: PlusKey + Sized {
+    /// Decode the key from `raw` - the encoded components, with all but the last one
+    /// length-prefixed.
+    fn from_raw(raw: &[u8]) -> Result<Self, PlusKeyError>;
+}
+
+impl PlusKey for str {
+    fn components(&self) -> Vec<Vec<u8>> {
+        vec![self.as_bytes().to_vec()]
+    }
+}
+
+impl PlusKey for String {
+    fn components(&self) -> Vec<Vec<u8>> {
+        self.as_str().components()
+    }
+}
+
+impl PlusOwnedKey for String {
+    fn from_raw(raw: &[u8]) -> Result<Self, PlusKeyError> {
+        String::from_utf8(raw.to_vec()).map_err(|_| PlusKeyError::InvalidUtf8)
+    }
+}
+
+impl PlusKey for Addr {
+    fn components(&self) -> Vec<Vec<u8>> {
+        self.as_str().components()
+    }
+}
+
+impl PlusOwnedKey for Addr {
+    fn from_raw(raw: &[u8]) -> Result<Self, PlusKeyError> {
+        String::from_raw(raw).map(Addr::unchecked)
+    }
+}
+
+impl PlusKey for [u8] {
+    fn components(&self) -> Vec<Vec<u8>> {
+        vec![self.to_vec()]
+    }
+}
+
+impl PlusKey for Vec<u8> {
+    fn components(&self) -> Vec<Vec<u8>> {
+        self.as_slice().components()
+    }
+}
+
+impl PlusOwnedKey for Vec<u8> {
+    fn from_raw(raw: &[u8]) -> Result<Self, PlusKeyError> {
+        Ok(raw.to_vec())
+    }
+}
+
+macro_rules! int_key {
+    ($($t:ty $(=> $flip:expr)?),*) => {
+        $(
+            impl PlusKey for $t {
+                fn components(&self) -> Vec<Vec<u8>> {
+                    vec![(*self $(^ $flip)?).to_be_bytes().to_vec()]
+                }
+            }
+
+            impl PlusOwnedKey for $t {
+                fn from_raw(raw: &[u8]) -> Result<Self, PlusKeyError> {
+                    let bytes = raw.try_into().map_err(|_| PlusKeyError::InvalidLength {
+                        expected: std::mem::size_of::<$t>(),
+                        actual: raw.len(),
+                    })?;
+
+                    Ok(<$t>::from_be_bytes(bytes) $(^ $flip)?)
+                }
+            }
+        )*
+    };
+}
+
+// signed integers have their sign bit flipped, so that negative numbers sort first
+int_key!(
+    u8, u16, u32, u64, u128,
+    i8 => i8::MIN, i16 => i16::MIN, i32 => i32::MIN, i64 => i64::MIN, i128 => i128::MIN
+);
+
+impl<A: PlusKey, B: PlusKey> PlusKey for (A, B) {
+    fn components(&self) -> Vec<Vec<u8>> {
+        let mut components = self.0.components();
+        components.extend(self.1.components());
+        components
+    }
+}
+
+impl<A: PlusOwnedKey, B: PlusOwnedKey> PlusOwnedKey for (A, B) {
+    fn from_raw(raw: &[u8]) -> Result<Self, PlusKeyError> {
+        let (a, rest) = split_component(raw)?;
+        Ok((A::from_raw(a)?, B::from_raw(rest)?))
+    }
+}
+
+impl<A: PlusKey, B: PlusKey, C: PlusKey> PlusKey for (A, B, C) {
+    fn components(&self) -> Vec<Vec<u8>> {
+        let mut components = self.0.components();
+        components.extend(self.1.components());
+        components.extend(self.2.components());
+        components
+    }
+}
+
+impl<A: PlusOwnedKey, B: PlusOwnedKey, C: PlusOwnedKey> PlusOwnedKey for (A, B, C) {
+    fn from_raw(raw: &[u8]) -> Result<Self, PlusKeyError> {
+        let (a, rest) = split_component(raw)?;
+        let (b, rest) = split_component(rest)?;
+        Ok((A::from_raw(a)?, B::from_raw(b)?, C::from_raw(rest)?))
+    }
+}
+
+/// Split a length-prefixed component off the front of `raw`.
+fn split_component(raw: &[u8]) -> Result<(&[u8], &[u8]), PlusKeyError> {
+    let (len, rest) = match raw {
+        [hi, lo, rest @ ..] => (u16::from_be_bytes([*hi, *lo]) as usize, rest),
+        _ => return Err(PlusKeyError::InvalidComponent),
+    };
+    if rest.len() < len {
+        return Err(PlusKeyError::InvalidComponent);
+    }
+
+    Ok(rest.split_at(len))
+}
+
+/// An error reading an entry with [`PlusMap`].
+#[derive(Debug, thiserror::Error)]
+pub enum PlusMapError {
+    #[error("invalid key: {0}")]
+    Key(#[from] PlusKeyError),
+
+    #[error("invalid value: {0}")]
+    Value(StdError),
+}
+
+/// How far a [`PlusMap::migrate`] call got.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MigrationProgress {
+    /// The number of entries moved by this call.
+    pub migrated: usize,
+    /// Whether the old map is now empty.
+    pub done: bool,
+}
+
+/// An error moving data with [`PlusItem::migrate_into`] or [`PlusMap::migrate`].
+#[derive(Debug, thiserror::Error)]
+pub enum PlusMigrateError<E> {
+    #[error("invalid key: {0}")]
+    Key(#[from] PlusKeyError),
+
+    #[error("invalid value: {0}")]
+    Value(StdError),
+
+    #[error("failed to write the value: {0}")]
+    Write(E),
+}
+
+/// An error decoding a *cw-storage-plus* key.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum PlusKeyError {
+    #[error("invalid UTF8")]
+    InvalidUtf8,
+
+    #[error("invalid key length, expected {expected} bytes, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+
+    #[error("missing or truncated key component")]
+    InvalidComponent,
+}
@@ -1,17 +1,63 @@
 //! Storage containers for use with [*CosmWasm*] smart contracts.
 //!
+//! The containers here default to the [`CwEncoding`] encoding, so that `Item<u64>` just works.
+//! The encoding can still be overridden per container, e.g. `Item<u64, FixedIntEncoding>`
+//! (see [`storey::encoding`] for the available encodings). Since [`Map`] delegates value
+//! storage to its inner container, the default applies to map values as well.
+//!
+//! Maps can be keyed by [`Addr`](cosmwasm_std::Addr), which is stored as its string form, and
+//! by [`CanonicalAddr`](cosmwasm_std::CanonicalAddr), stored as its bytes. The *CosmWasm*
+//! integers, decimals and [`Timestamp`](cosmwasm_std::Timestamp) work as keys too, and iterate
+//! in numeric order.
+//!
+//! # Example
+//! ```
+//! use cosmwasm_std::Storage as _;
+//! use cw_storey::containers::{Item, Map};
+//! use cw_storey::CwStorage;
+//! use storey::encoding::FixedIntEncoding;
+//!
+//! // uses the default encoding
+//! const CONFIG: Item<String> = Item::new(0);
+//! // uses the default encoding for the map values
+//! const BALANCES: Map<String, Item<u128>> = Map::new(1);
+//! // overrides the encoding
+//! const COUNTER: Item<u64, FixedIntEncoding> = Item::new(2);
+//!
+//! let mut raw_storage = cosmwasm_std::testing::MockStorage::new();
+//! let mut storage = CwStorage(&mut raw_storage);
+//!
+//! CONFIG.access(&mut storage).set(&"admin".to_string()).unwrap();
+//! BALANCES.access(&mut storage).entry_mut("alice").set(&100).unwrap();
+//! COUNTER.access(&mut storage).set(&7).unwrap();
+//!
+//! assert_eq!(raw_storage.get(&[2]), Some(7u64.to_le_bytes().to_vec()));
+//! ```
+//!
 //! [*CosmWasm*]: https://github.com/CosmWasm/cosmwasm
+//! [`CwEncoding`]: crate::CwEncoding
 
-/// The [`storey::containers::Item`] type with the default encoding for [*CosmWasm*] smart
-/// contracts.
-///
-/// [*CosmWasm*]: https://github.com/CosmWasm/cosmwasm
-pub type Item<T> = storey::containers::Item<T, crate::encoding::CwEncoding>;
+use crate::encoding::CwEncoding;
 
-/// The [`storey::containers::Column`] type with the default encoding for [*CosmWasm*] smart
-/// contracts.
+/// Declares an alias of a [`storey`] container with the encoding parameter defaulting to
+/// [`CwEncoding`].
 ///
-/// [*CosmWasm*]: https://github.com/CosmWasm/cosmwasm
-pub type Column<T> = storey::containers::Column<T, crate::encoding::CwEncoding>;
+/// Every [`storey`] container with an encoding parameter should be listed here, so that
+/// contracts never have to name the encoding.
+macro_rules! with_default_encoding {
+    ($($container:ident),* $(,)?) => {
+        $(
+            #[doc = concat!(
+                "The [`storey::containers::", stringify!($container), "`] type with the ",
+                "default encoding for [*CosmWasm*] smart contracts.\n\n",
+                "[*CosmWasm*]: https://github.com/CosmWasm/cosmwasm",
+            )]
+            pub type $container<T, E = CwEncoding> = storey::containers::$container<T, E>;
+        )*
+    };
+}
+
+with_default_encoding!(Item, Column);
 
+// containers without an encoding of their own, which use the encoding of their values
 pub use storey::containers::Map;
@@ -0,0 +1,122 @@
+//! The [*cw2*] contract version, read and written through a [`storey`] backend.
+//!
+//! Every contract is expected to store its name and version under the `contract_info` key,
+//! in the layout of the [*cw2*] crate: the key as-is, and the value as JSON. Tools and
+//! migrations rely on that, so contracts using [`storey`] need to keep writing it. The functions
+//! here do it without depending on *cw-storage-plus*, and mirror the ones of [*cw2*].
+//!
+//! # Example
+//! ```
+//! use cosmwasm_std::testing::MockStorage;
+//! use cw_storey::contract_version::{
+//!     assert_contract_version, get_contract_version, set_contract_version, ContractVersion,
+//! };
+//! use cw_storey::CwStorage;
+//!
+//! let mut raw = MockStorage::new();
+//! let mut storage = CwStorage(&mut raw);
+//!
+//! // in the instantiate entrypoint
+//! set_contract_version(&mut storage, "crates.io:my-contract", "1.0.0").unwrap();
+//!
+//! // in the migrate entrypoint
+//! assert_contract_version(&storage, "crates.io:my-contract", "1.0.0").unwrap();
+//! set_contract_version(&mut storage, "crates.io:my-contract", "1.1.0").unwrap();
+//!
+//! assert_eq!(
+//!     get_contract_version(&storage).unwrap(),
+//!     Some(ContractVersion {
+//!         contract: "crates.io:my-contract".to_string(),
+//!         version: "1.1.0".to_string(),
+//!     })
+//! );
+//! ```
+//!
+//! [*cw2*]: https://crates.io/crates/cw2
+
+use cosmwasm_std::StdError;
+use serde::{Deserialize, Serialize};
+use storey::storage::{Storage, StorageMut};
+
+use crate::compat::PlusItem;
+
+/// The raw key the contract version is stored under.
+pub const CONTRACT_INFO_KEY: &[u8] = b"contract_info";
+
+const CONTRACT: PlusItem<ContractVersion> = PlusItem::new("contract_info");
+
+/// The name and version of a contract, as [*cw2*](https://crates.io/crates/cw2) stores them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ContractVersion {
+    /// The name of the contract, conventionally `crates.io:<crate name>`.
+    pub contract: String,
+    /// The version of the contract, usually the crate version.
+    pub version: String,
+}
+
+/// Get the stored contract version, if there is one.
+pub fn get_contract_version<S: Storage>(storage: &S) -> Result<Option<ContractVersion>, StdError> {
+    CONTRACT.get(storage)
+}
+
+/// Store the contract version.
+pub fn set_contract_version<S: StorageMut>(
+    storage: &mut S,
+    name: impl Into<String>,
+    version: impl Into<String>,
+) -> Result<(), StdError> {
+    let value = cosmwasm_std::to_json_vec(&ContractVersion {
+        contract: name.into(),
+        version: version.into(),
+    })?;
+    storage.set(CONTRACT_INFO_KEY, &value);
+
+    Ok(())
+}
+
+/// Check that the stored contract version is exactly `expected_version` of
+/// `expected_contract`.
+///
+/// Meant for the migrate entrypoint, to make sure the contract being migrated is the one the
+/// migration was written for.
+pub fn assert_contract_version<S: Storage>(
+    storage: &S,
+    expected_contract: &str,
+    expected_version: &str,
+) -> Result<(), VersionError> {
+    let ContractVersion { contract, version } = get_contract_version(storage)
+        .map_err(VersionError::Std)?
+        .ok_or(VersionError::NotFound)?;
+
+    if contract != expected_contract {
+        return Err(VersionError::WrongContract {
+            expected: expected_contract.to_string(),
+            found: contract,
+        });
+    }
+    if version != expected_version {
+        return Err(VersionError::WrongVersion {
+            expected: expected_version.to_string(),
+            found: version,
+        });
+    }
+
+    Ok(())
+}
+
+/// An error checking the contract version with [`assert_contract_version`].
+#[derive(Debug, thiserror::Error)]
+pub enum VersionError {
+    #[error("{0}")]
+    Std(StdError),
+
+    #[error("contract version info not found")]
+    NotFound,
+
+    #[error("wrong contract: expected `{expected}`, found `{found}`")]
+    WrongContract { expected: String, found: String },
+
+    #[error("wrong contract version: expected `{expected}`, found `{found}`")]
+    WrongVersion { expected: String, found: String },
+}
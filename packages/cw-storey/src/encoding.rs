@@ -6,6 +6,11 @@ use storey::encoding::{Cover, DecodableWithImpl, EncodableWithImpl, Encoding};
 /// This type implements the [`Encoding`] trait (see [`storey::encoding`]), which means it can
 /// be used with some of [`storey`]'s containers to encode and decode values.
 ///
+/// Any type implementing [`serde`]'s traits can be stored with it, which includes the
+/// [`cosmwasm_std`] types like `Binary`, `Coin`, `Uint128`, `Decimal`, `Addr` and
+/// `Timestamp`. The bytes stored for those are pinned by tests, so that upgrading
+/// [`cosmwasm_std`] doesn't silently change the layout of existing state.
+///
 /// You're unlikely to need to use this type directly for basic library usage. You might
 /// need it if you're trying to use third-party containers this crate does not provide.
 ///
@@ -16,6 +21,8 @@ pub struct CwEncoding;
 impl Encoding for CwEncoding {
     type DecodeError = StdError;
     type EncodeError = StdError;
+
+    const NAME: &'static str = "msgpack";
 }
 
 impl<T> EncodableWithImpl<CwEncoding> for Cover<&T>
@@ -0,0 +1,297 @@
+//! A description of a contract's storage layout, for indexers.
+//!
+//! Off-chain tools that read a contract's raw state need to know which container lives under
+//! which prefix, and what its keys look like. Rather than leaving them to work that out from
+//! the source, a contract can list its top-level containers with [`storage_layout!`] and
+//! answer a query with [`query_storage_layout`].
+//!
+//! Every container is described by its name, [prefix](storey::containers::Map::prefix_bytes), kind
+//! and key structure, e.g. `Addr / String` for a `Map<Addr, Map<String, Item<_>>>`. With the
+//! `schemars` feature enabled, the description also includes the
+//! [`SchemaDocument`](storey::schema::SchemaDocument) of the container, which needs the key
+//! and value types to implement `JsonSchema`.
+//!
+//! A contract that already describes its containers with a derived
+//! [`StorageSchema`](storey::meta::StorageSchema) doesn't need to list them a second time:
+//! [`StorageLayout::from_schema`] builds the layout from it. The key structure then uses the
+//! type names as written in the struct, and there's no JSON Schema, since the derive only
+//! knows the names of the types.
+//!
+//! # Example
+//! ```
+//! use cosmwasm_std::{Addr, Binary, StdResult};
+//! use cw_storey::containers::{Column, Item, Map};
+//! use cw_storey::layout::{query_storage_layout, StorageLayout};
+//! use cw_storey::storage_layout;
+//!
+//! const OWNER: Item<Addr> = Item::new(0);
+//! const ALLOWANCES: Map<Addr, Map<String, Item<u128>>> = Map::new(1);
+//! const HISTORY: Column<String> = Column::new(2);
+//!
+//! fn layout() -> StorageLayout {
+//!     storage_layout![
+//!         "owner" => OWNER,
+//!         "allowances" => ALLOWANCES,
+//!         "history" => HISTORY,
+//!     ]
+//! }
+//!
+//! // in the query entrypoint
+//! let response: StdResult<Binary> = query_storage_layout(&layout());
+//!
+//! let layout = layout();
+//! assert_eq!(layout.containers[1].prefix, [1]);
+//! assert_eq!(layout.containers[1].key_structure, "Addr / String");
+//! assert_eq!(layout.containers[2].key_structure, "index");
+//! ```
+
+use cosmwasm_std::{Binary, StdResult};
+use serde::Serialize;
+use storey::containers::{Column, Item, Map};
+use storey::meta::{ContainerEntry, ContainerMeta, StorageSchema};
+
+// used by `storage_layout!`, in crates that don't depend on `storey` directly
+#[doc(hidden)]
+pub use storey::containers::assert_unique_prefixes as __assert_unique_prefixes;
+
+/// Lists a contract's top-level containers as a [`StorageLayout`].
+///
+/// Every entry is a name, and a container declared as a `const`. The prefixes of the
+/// containers are checked at compile time: two containers with the same prefix are an error.
+///
+/// ```compile_fail
+/// use cw_storey::containers::Item;
+///
+/// const OWNER: Item<String> = Item::new(0);
+/// const ADMIN: Item<String> = Item::new(0);
+///
+/// let layout = cw_storey::storage_layout!["owner" => OWNER, "admin" => ADMIN];
+/// ```
+///
+/// See the [module docs](crate::layout) for an example.
+#[macro_export]
+macro_rules! storage_layout {
+    ($($name:expr => $container:expr),* $(,)?) => {{
+        const _: () = $crate::layout::__assert_unique_prefixes(&[$($container.prefix_bytes()),*]);
+
+        $crate::layout::StorageLayout {
+            containers: ::std::vec![
+                $($crate::layout::ContainerLayout::new($name, &$container)),*
+            ],
+        }
+    }};
+}
+
+/// Answer a storage layout query, with the layout serialized as JSON.
+pub fn query_storage_layout(layout: &StorageLayout) -> StdResult<Binary> {
+    // JSON Schemas contain floats, which `cosmwasm_std::to_json_binary` can't serialize
+    let json = serde_json::to_vec(layout).expect("the layout is always serializable");
+    Ok(Binary::from(json))
+}
+
+/// The top-level containers of a contract. See the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorageLayout {
+    pub containers: Vec<ContainerLayout>,
+}
+
+impl StorageLayout {
+    /// The layout of the containers listed by `S`, in the order of its fields.
+    ///
+    /// ```
+    /// use cosmwasm_std::Addr;
+    /// use cw_storey::containers::{Item, Map};
+    /// use cw_storey::layout::{ContainerKind, StorageLayout};
+    /// use storey::meta::StorageSchema;
+    ///
+    /// #[derive(StorageSchema)]
+    /// struct State {
+    ///     owner: Item<Addr>,
+    ///     #[storey(prefix = "balances")]
+    ///     balances: Map<Addr, Item<u128>>,
+    /// }
+    ///
+    /// let layout = StorageLayout::from_schema::<State>();
+    /// assert_eq!(layout.containers[1].name, "balances");
+    /// assert_eq!(layout.containers[1].prefix, b"balances");
+    /// assert_eq!(layout.containers[1].kind, ContainerKind::Map);
+    /// assert_eq!(layout.containers[1].key_structure, "Addr");
+    /// ```
+    pub fn from_schema<S: StorageSchema>() -> Self {
+        Self {
+            containers: S::CONTAINERS.iter().map(ContainerLayout::from).collect(),
+        }
+    }
+}
+
+/// The description of a top-level container.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContainerLayout {
+    /// The name the container was registered under.
+    pub name: String,
+
+    /// The prefix of all the container's keys.
+    pub prefix: Vec<u8>,
+
+    pub kind: ContainerKind,
+
+    /// A short description of the key structure: the type of every map key, and `index` for
+    /// a column, separated by ` / `.
+    pub key_structure: String,
+
+    /// The JSON Schema of the keys and values. Missing for layouts built
+    /// [from a `StorageSchema`](StorageLayout::from_schema).
+    #[cfg(feature = "schemars")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<storey::schema::SchemaDocument>,
+}
+
+impl ContainerLayout {
+    /// Describe `container`, under the name `name`.
+    #[cfg(not(feature = "schemars"))]
+    pub fn new<C: Container>(name: impl Into<String>, container: &C) -> Self {
+        Self {
+            name: name.into(),
+            prefix: container.prefix_bytes().to_vec(),
+            kind: C::KIND,
+            key_structure: C::key_structure(),
+        }
+    }
+
+    /// Describe `container`, under the name `name`.
+    #[cfg(feature = "schemars")]
+    pub fn new<C>(name: impl Into<String>, container: &C) -> Self
+    where
+        C: Container + storey::schema::StorableSchema,
+    {
+        Self {
+            name: name.into(),
+            prefix: container.prefix_bytes().to_vec(),
+            kind: C::KIND,
+            key_structure: C::key_structure(),
+            schema: Some(storey::schema::SchemaDocument::new::<C>(
+                container.prefix_bytes(),
+            )),
+        }
+    }
+}
+
+impl From<&ContainerEntry> for ContainerLayout {
+    fn from(entry: &ContainerEntry) -> Self {
+        let mut key_structure: Vec<_> = entry
+            .container
+            .keys()
+            .iter()
+            .map(|key| key.type_name)
+            .collect();
+        if innermost(&entry.container) == ContainerKind::Column {
+            key_structure.push("index");
+        }
+
+        Self {
+            name: entry.name.to_string(),
+            prefix: entry.prefix.to_vec(),
+            kind: ContainerKind::from(&entry.container),
+            key_structure: key_structure.join(" / "),
+            #[cfg(feature = "schemars")]
+            schema: None,
+        }
+    }
+}
+
+/// The kind of the container at the bottom of `meta`, after all the maps.
+fn innermost(meta: &ContainerMeta) -> ContainerKind {
+    match meta {
+        ContainerMeta::Map { value, .. } => innermost(value),
+        meta => ContainerKind::from(meta),
+    }
+}
+
+/// The kind of a container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerKind {
+    Item,
+    Column,
+    Map,
+}
+
+impl From<&ContainerMeta> for ContainerKind {
+    fn from(meta: &ContainerMeta) -> Self {
+        match meta {
+            ContainerMeta::Item { .. } => Self::Item,
+            ContainerMeta::Column { .. } => Self::Column,
+            ContainerMeta::Map { .. } => Self::Map,
+        }
+    }
+}
+
+/// A container that can be listed in a [`StorageLayout`].
+pub trait Container {
+    const KIND: ContainerKind;
+
+    /// The prefix of the container's keys.
+    fn prefix_bytes(&self) -> &[u8];
+
+    /// See [`ContainerLayout::key_structure`].
+    fn key_structure() -> String;
+}
+
+impl<T, E> Container for Item<T, E> {
+    const KIND: ContainerKind = ContainerKind::Item;
+
+    fn prefix_bytes(&self) -> &[u8] {
+        Item::prefix_bytes(self)
+    }
+
+    fn key_structure() -> String {
+        String::new()
+    }
+}
+
+impl<T, E> Container for Column<T, E> {
+    const KIND: ContainerKind = ContainerKind::Column;
+
+    fn prefix_bytes(&self) -> &[u8] {
+        Column::prefix_bytes(self)
+    }
+
+    fn key_structure() -> String {
+        "index".to_string()
+    }
+}
+
+impl<K, V: Container> Container for Map<K, V> {
+    const KIND: ContainerKind = ContainerKind::Map;
+
+    fn prefix_bytes(&self) -> &[u8] {
+        Map::prefix_bytes(self)
+    }
+
+    fn key_structure() -> String {
+        let key = short_type_name(std::any::type_name::<K>());
+        match V::key_structure() {
+            rest if rest.is_empty() => key,
+            rest => format!("{key} / {rest}"),
+        }
+    }
+}
+
+/// `name` without module paths, e.g. `(u32, String)` for `(u32, alloc::string::String)`.
+fn short_type_name(name: &str) -> String {
+    let mut short = String::new();
+    let mut path = String::new();
+
+    for c in name.chars().chain(std::iter::once(' ')) {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            path.push(c);
+        } else {
+            short.push_str(path.rsplit("::").next().unwrap_or_default());
+            path.clear();
+            short.push(c);
+        }
+    }
+    short.pop();
+
+    short
+}
@@ -3,16 +3,76 @@
 //! This crate provides
 //! - a [*CosmWasm*] storage backend for use with [`storey`] collections,
 //! - a [*MessagePack*] encoding integration to be used for serializing and deserializing
-//! values, and
+//!   values, and
 //! - a set of container re-exports that remove the need to manually specify the
-//! encoding, instead relying on the default [*MessagePack*] encoding.
+//!   encoding, instead relying on the default [*MessagePack*] encoding.
+//!
+//! The [`compat`] module reads state written by *cw-storage-plus*, for contracts migrating
+//! to [`storey`], and the [`pagination`] module pages through maps the way list queries
+//! usually do. The [`layout`] module describes a contract's containers to indexers, and
+//! [`contract_version`] keeps the *cw2* contract version. The [`order`] module iterates in the
+//! [`Order`](cosmwasm_std::Order) a query asks for.
+//!
+//! For unit tests, [`MockStorage`](cosmwasm_std::testing::MockStorage) can also be passed to
+//! containers directly, without the [`CwStorage`] wrapper. The [`testing`] module has helpers to
+//! seed it and to check what's in it.
+//!
+//! # Errors
+//!
+//! The containers return a [`storey::Error`], which converts into a
+//! [`StdError`](cosmwasm_std::StdError), so `?` works in contract entry points returning a
+//! `StdResult`. The encoding errors are `StdError`s already and are passed on as they are.
+//!
+//! # *CosmWasm* versions
+//!
+//! The crate works with *CosmWasm* 2.x by default. Contracts still on 1.x can disable the
+//! default features and enable `cosmwasm-std-1` instead:
+//!
+//! ```toml
+//! cw-storey = { version = "0.3", default-features = false, features = ["cosmwasm-std-1"] }
+//! ```
+//!
+//! The stored data is the same with either version, except for `Binary` values: 1.x encodes
+//! them as base64 strings, 2.x as raw bytes. With 1.x, the `compat` and `contract_version`
+//! modules aren't available, the *CosmWasm* types like `Addr` can't be used as map keys, and
+//! container errors don't convert into a `StdError` by themselves. The features are additive:
+//! if both end up enabled, e.g. with `--all-features`, 2.x is used.
+//!
+//! # Iteration
+//!
+//! Iterating over containers needs the default `iteration` feature, and so do the `order` and
+//! `pagination` modules and the range queries of `compat`. A contract that only gets and sets
+//! values can leave it out, which saves compile time but not binary size:
+//!
+//! ```toml
+//! cw-storey = { version = "0.3", default-features = false, features = ["cosmwasm-std-2"] }
+//! ```
 //!
 //! [*CosmWasm*]: https://github.com/CosmWasm/cosmwasm
 //! [*MessagePack*]: https://msgpack.org/
 
+#[cfg(not(any(feature = "cosmwasm-std-1", feature = "cosmwasm-std-2")))]
+compile_error!("either the `cosmwasm-std-1` or the `cosmwasm-std-2` feature has to be enabled");
+
+// The rest of the crate is written against `cosmwasm_std`, whichever version that is. With
+// both features enabled, e.g. by `--all-features`, 2.x is used.
+#[cfg(all(feature = "cosmwasm-std-1", not(feature = "cosmwasm-std-2")))]
+extern crate cosmwasm_std_v1 as cosmwasm_std;
+
 mod backend;
+#[cfg(feature = "cosmwasm-std-2")]
+pub mod compat;
 pub mod containers;
+#[cfg(feature = "cosmwasm-std-2")]
+pub mod contract_version;
 mod encoding;
+pub mod layout;
+#[cfg(feature = "iteration")]
+pub mod order;
+#[cfg(feature = "iteration")]
+pub mod pagination;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod testing;
 
 pub use backend::CwStorage;
 pub use encoding::CwEncoding;
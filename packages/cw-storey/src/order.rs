@@ -0,0 +1,245 @@
+//! Iteration in a [`cosmwasm_std::Order`], with *cw-storage-plus*-style bounds.
+//!
+//! Query messages usually say which way to list entries with an [`Order`]. The
+//! [`CwIterableAccessor`] extension trait takes it directly, and iterates forward or in
+//! reverse accordingly, so handlers don't have to pick between two code paths.
+//!
+//! As with *cw-storage-plus*, the bounds are a `min` and a `max` [`Bound`], not a start and an
+//! end: in descending order, iteration starts at `max` and stops at `min`. Either bound can be
+//! inclusive or exclusive.
+//!
+//! # Example
+//! ```
+//! use cosmwasm_std::testing::MockStorage;
+//! use cosmwasm_std::Order;
+//! use cw_storey::containers::{Item, Map};
+//! use cw_storey::order::{Bound, CwIterableAccessor as _};
+//! use cw_storey::CwStorage;
+//!
+//! const SCORES: Map<u32, Item<u64>> = Map::new(0);
+//!
+//! let mut raw = MockStorage::new();
+//! let mut storage = CwStorage(&mut raw);
+//! for player in 0..10 {
+//!     SCORES.access(&mut storage).entry_mut(&player).set(&(player as u64 * 10)).unwrap();
+//! }
+//!
+//! let access = SCORES.access(&storage);
+//! let players = |order| -> Vec<u32> {
+//!     access
+//!         .keys_cw(order, Some(Bound::inclusive(3)), Some(Bound::exclusive(6)))
+//!         .map(|key| key.unwrap().0)
+//!         .collect()
+//! };
+//!
+//! assert_eq!(players(Order::Ascending), [3, 4, 5]);
+//! assert_eq!(players(Order::Descending), [5, 4, 3]);
+//! ```
+
+use cosmwasm_std::Order;
+use storey::containers::{
+    BoundFor, BoundedIterableAccessor, KVDecodeError, KeyDecodeError, Storable,
+};
+use storey::storage::{IterableStorage as _, RevIterableStorage};
+
+/// A bound of an iteration, like the `Bound` of *cw-storage-plus*.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound<B> {
+    /// The bound itself is included.
+    Inclusive(B),
+    /// The bound itself is excluded.
+    Exclusive(B),
+}
+
+impl<B> Bound<B> {
+    pub fn inclusive(bound: B) -> Self {
+        Self::Inclusive(bound)
+    }
+
+    pub fn exclusive(bound: B) -> Self {
+        Self::Exclusive(bound)
+    }
+
+    /// The raw start of an iteration from this bound up, or `None` if no key can come after
+    /// the bound.
+    fn into_start<S>(self) -> Option<Vec<u8>>
+    where
+        B: BoundFor<S>,
+    {
+        match self {
+            Self::Inclusive(bound) => Some(bound.into_bytes()),
+            Self::Exclusive(bound) => after_prefix(bound.into_bytes()),
+        }
+    }
+
+    /// The raw end of an iteration up to this bound, which is always exclusive, or `None` if
+    /// the iteration can go all the way to the end.
+    fn into_end<S>(self) -> Option<Vec<u8>>
+    where
+        B: BoundFor<S>,
+    {
+        match self {
+            Self::Inclusive(bound) => after_prefix(bound.into_bytes()),
+            Self::Exclusive(bound) => Some(bound.into_bytes()),
+        }
+    }
+}
+
+/// The error of an iteration over the pairs of `S`.
+pub type PairError<S> = KVDecodeError<
+    KeyDecodeError<<S as Storable>::KeyDecodeError>,
+    <S as Storable>::ValueDecodeError,
+>;
+
+type BoxedIter<'a, T> = Box<dyn Iterator<Item = T> + 'a>;
+
+/// A raw start and end, as taken by [`IterableStorage`](storey::storage::IterableStorage).
+type RawRange = (Option<Vec<u8>>, Option<Vec<u8>>);
+
+/// Iteration over a collection in a given [`Order`]. See the [module docs](self).
+///
+/// This is implemented for every accessor that can be iterated over with bounds, if the
+/// storage can also be iterated over in reverse, which is the case for
+/// [`CwStorage`](crate::CwStorage).
+pub trait CwIterableAccessor: BoundedIterableAccessor {
+    /// Iterate over key-value pairs in this collection, in `order`, between `min` and `max`.
+    #[allow(clippy::type_complexity)]
+    fn pairs_cw<'a, B>(
+        &'a self,
+        order: Order,
+        min: Option<Bound<B>>,
+        max: Option<Bound<B>>,
+    ) -> BoxedIter<
+        'a,
+        Result<
+            (
+                <Self::Storable as Storable>::Key,
+                <Self::Storable as Storable>::Value,
+            ),
+            PairError<Self::Storable>,
+        >,
+    >
+    where
+        B: BoundFor<Self::Storable>,
+        Self::Storage: RevIterableStorage,
+        Self::Storable: 'a,
+    {
+        let Some((start, end)) = raw_bounds(min, max) else {
+            return Box::new(std::iter::empty());
+        };
+        let decode = |(key, value): (Vec<u8>, Vec<u8>)| {
+            let key = Self::Storable::decode_key_with_context(&key).map_err(KVDecodeError::Key)?;
+            let value = Self::Storable::decode_value(&value).map_err(KVDecodeError::Value)?;
+            Ok((key, value))
+        };
+
+        let storage = self.storage();
+        match order {
+            Order::Ascending => {
+                Box::new(storage.pairs(start.as_deref(), end.as_deref()).map(decode))
+            }
+            Order::Descending => Box::new(
+                storage
+                    .rev_pairs(start.as_deref(), end.as_deref())
+                    .map(decode),
+            ),
+        }
+    }
+
+    /// Iterate over keys in this collection, in `order`, between `min` and `max`.
+    #[allow(clippy::type_complexity)]
+    fn keys_cw<'a, B>(
+        &'a self,
+        order: Order,
+        min: Option<Bound<B>>,
+        max: Option<Bound<B>>,
+    ) -> BoxedIter<
+        'a,
+        Result<
+            <Self::Storable as Storable>::Key,
+            KeyDecodeError<<Self::Storable as Storable>::KeyDecodeError>,
+        >,
+    >
+    where
+        B: BoundFor<Self::Storable>,
+        Self::Storage: RevIterableStorage,
+        Self::Storable: 'a,
+    {
+        let Some((start, end)) = raw_bounds(min, max) else {
+            return Box::new(std::iter::empty());
+        };
+        let decode = |key: Vec<u8>| Self::Storable::decode_key_with_context(&key);
+
+        let storage = self.storage();
+        match order {
+            Order::Ascending => {
+                Box::new(storage.keys(start.as_deref(), end.as_deref()).map(decode))
+            }
+            Order::Descending => Box::new(
+                storage
+                    .rev_keys(start.as_deref(), end.as_deref())
+                    .map(decode),
+            ),
+        }
+    }
+
+    /// Iterate over values in this collection, in `order`, between `min` and `max`.
+    #[allow(clippy::type_complexity)]
+    fn values_cw<'a, B>(
+        &'a self,
+        order: Order,
+        min: Option<Bound<B>>,
+        max: Option<Bound<B>>,
+    ) -> BoxedIter<
+        'a,
+        Result<<Self::Storable as Storable>::Value, <Self::Storable as Storable>::ValueDecodeError>,
+    >
+    where
+        B: BoundFor<Self::Storable>,
+        Self::Storage: RevIterableStorage,
+        Self::Storable: 'a,
+    {
+        let Some((start, end)) = raw_bounds(min, max) else {
+            return Box::new(std::iter::empty());
+        };
+        let decode = |value: Vec<u8>| Self::Storable::decode_value(&value);
+
+        let storage = self.storage();
+        match order {
+            Order::Ascending => {
+                Box::new(storage.values(start.as_deref(), end.as_deref()).map(decode))
+            }
+            Order::Descending => Box::new(
+                storage
+                    .rev_values(start.as_deref(), end.as_deref())
+                    .map(decode),
+            ),
+        }
+    }
+}
+
+impl<A: BoundedIterableAccessor> CwIterableAccessor for A {}
+
+/// The raw start and end of an iteration between `min` and `max`, or `None` if nothing can be
+/// in between.
+fn raw_bounds<S, B: BoundFor<S>>(min: Option<Bound<B>>, max: Option<Bound<B>>) -> Option<RawRange> {
+    let start = match min {
+        Some(min) => Some(min.into_start()?),
+        None => None,
+    };
+    Some((start, max.and_then(Bound::into_end)))
+}
+
+/// The smallest raw key after every key starting with `prefix`, or `None` if there's none.
+///
+/// Map values can be containers with keys of their own, so the entries of a map key are all
+/// the raw keys starting with it, not just the key itself.
+fn after_prefix(mut prefix: Vec<u8>) -> Option<Vec<u8>> {
+    while let Some(last) = prefix.pop() {
+        if last < u8::MAX {
+            prefix.push(last + 1);
+            return Some(prefix);
+        }
+    }
+    None
+}
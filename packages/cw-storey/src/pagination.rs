@@ -0,0 +1,251 @@
+//! Paginated queries over maps, the way *CosmWasm* contracts usually do them.
+//!
+//! List queries conventionally take a `start_after: Option<X>` (the last key of the previous
+//! page, excluded from the next one) and a `limit: Option<u32>`, which falls back to a default
+//! and is capped at a maximum. The functions here take those arguments as they come in a
+//! query message, so ported handlers keep their shape.
+//!
+//! # Example
+//! ```
+//! use cosmwasm_std::testing::MockStorage;
+//! use cw_storey::containers::{Item, Map};
+//! use cw_storey::pagination::paginate_map;
+//! use cw_storey::CwStorage;
+//!
+//! const BALANCES: Map<String, Item<u128>> = Map::new(0);
+//! const DEFAULT_LIMIT: u32 = 10;
+//! const MAX_LIMIT: u32 = 30;
+//!
+//! let mut raw = MockStorage::new();
+//! let mut storage = CwStorage(&mut raw);
+//! for (owner, balance) in [("ann", 1), ("bob", 2), ("cid", 3)] {
+//!     BALANCES.access(&mut storage).entry_mut(owner).set(&balance).unwrap();
+//! }
+//!
+//! // what a query handler gets in its message
+//! let start_after = Some("ann".to_string());
+//! let limit = Some(1);
+//!
+//! let access = BALANCES.access(&storage);
+//! let page = paginate_map(&access, start_after.as_ref(), limit, DEFAULT_LIMIT, MAX_LIMIT);
+//! assert_eq!(page.unwrap(), [("bob".to_string(), 2)]);
+//! ```
+//!
+//! For list queries, [`map_page`] does the rest of the work too: it maps every entry to the
+//! entry type of the response, and tells whether there's a next page.
+//!
+//! Pages follow the order of the encoded keys, which for variable-length keys like strings is
+//! by length first. Any key type can be paginated, since all that matters is that the order
+//! is stable, but only [`OrderPreservingKey`](storey::containers::OrderPreservingKey)s come
+//! out sorted by value.
+
+use cosmwasm_std::Order;
+use storey::containers::{
+    Item, ItemKeyDecodeError, IterableAccessor as _, KVDecodeError, Key, KeyDecodeError, Map,
+    MapAccess, MapKeyDecodeError, OwnedKey, Storable,
+};
+use storey::encoding::{DecodableWith, EncodableWith, Encoding};
+use storey::storage::{IterableStorage, RevIterableStorage};
+
+/// The error of a paginated query over a `Map<K, Item<T, E>>`.
+pub type PageError<K, T, E> = KVDecodeError<
+    KeyDecodeError<<Map<K, Item<T, E>> as Storable>::KeyDecodeError>,
+    <Map<K, Item<T, E>> as Storable>::ValueDecodeError,
+>;
+
+/// A page of the entries after `start_after`, in ascending order.
+///
+/// At most `limit` entries are returned, or `default` if there's no limit, but never more
+/// than `max`.
+pub fn paginate_map<K, T, E, S>(
+    access: &MapAccess<K, Item<T, E>, S>,
+    start_after: Option<&K>,
+    limit: Option<u32>,
+    default: u32,
+    max: u32,
+) -> Result<Vec<(K, T)>, PageError<K, T, E>>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage,
+    MapKeyDecodeError<ItemKeyDecodeError>: From<K::Error>,
+{
+    let start = start_after.map(after);
+    let pairs = access.storage().pairs(start.as_deref(), None);
+
+    decode_page::<K, T, E>(pairs, clamp(limit, default, max))
+}
+
+/// Like [`paginate_map`], but only returns the values.
+pub fn paginate_map_values<K, T, E, S>(
+    access: &MapAccess<K, Item<T, E>, S>,
+    start_after: Option<&K>,
+    limit: Option<u32>,
+    default: u32,
+    max: u32,
+) -> Result<Vec<T>, PageError<K, T, E>>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage,
+    MapKeyDecodeError<ItemKeyDecodeError>: From<K::Error>,
+{
+    let start = start_after.map(after);
+
+    access
+        .storage()
+        .values(start.as_deref(), None)
+        .take(clamp(limit, default, max))
+        .map(|value| Map::<K, Item<T, E>>::decode_value(&value).map_err(KVDecodeError::Value))
+        .collect()
+}
+
+/// Like [`paginate_map`], but in descending order: the page holds the entries before
+/// `start_after`, starting with the last one.
+pub fn paginate_map_rev<K, T, E, S>(
+    access: &MapAccess<K, Item<T, E>, S>,
+    start_after: Option<&K>,
+    limit: Option<u32>,
+    default: u32,
+    max: u32,
+) -> Result<Vec<(K, T)>, PageError<K, T, E>>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage + RevIterableStorage,
+    MapKeyDecodeError<ItemKeyDecodeError>: From<K::Error>,
+{
+    let end = start_after.map(|key| storey::key!(key));
+    let pairs = access.storage().rev_pairs(None, end.as_deref());
+
+    decode_page::<K, T, E>(pairs, clamp(limit, default, max))
+}
+
+/// One page of a list query, see [`map_page`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<K, R> {
+    /// The entries of the page, mapped to the response type.
+    pub entries: Vec<R>,
+    /// The key to pass as `start_after` to get the next page, if there is one.
+    pub next_key: Option<K>,
+}
+
+/// A page of the entries after `start_after`, in the given `order`, each mapped with `entry`.
+///
+/// The limits work like for [`paginate_map`]. `next_key` is set if there are more entries
+/// after this page.
+///
+/// # Example
+/// ```
+/// use cosmwasm_std::testing::MockStorage;
+/// use cosmwasm_std::Order;
+/// use cw_storey::containers::{Item, Map};
+/// use cw_storey::pagination::map_page;
+/// use cw_storey::CwStorage;
+///
+/// const SCORES: Map<u32, Item<u64>> = Map::new(0);
+///
+/// struct ScoreResponse {
+///     player: u32,
+///     score: u64,
+/// }
+///
+/// let mut raw = MockStorage::new();
+/// let mut storage = CwStorage(&mut raw);
+/// for player in 0..5 {
+///     SCORES.access(&mut storage).entry_mut(&player).set(&10).unwrap();
+/// }
+///
+/// let access = SCORES.access(&storage);
+/// let page = map_page(&access, None, Some(3), 10, 30, Order::Descending, |player, score| {
+///     ScoreResponse { player, score }
+/// })
+/// .unwrap();
+///
+/// assert_eq!(page.entries.len(), 3);
+/// assert_eq!(page.entries[0].player, 4);
+/// assert_eq!(page.next_key, Some(2));
+/// ```
+pub fn map_page<K, T, E, S, R>(
+    access: &MapAccess<K, Item<T, E>, S>,
+    start_after: Option<&K>,
+    limit: Option<u32>,
+    default: u32,
+    max: u32,
+    order: Order,
+    mut entry: impl FnMut(K, T) -> R,
+) -> Result<Page<K, R>, PageError<K, T, E>>
+where
+    K: OwnedKey + Clone,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage + RevIterableStorage,
+    MapKeyDecodeError<ItemKeyDecodeError>: From<K::Error>,
+{
+    let limit = clamp(limit, default, max);
+
+    // one more than the limit, to tell whether there's a next page
+    let mut pairs = match order {
+        Order::Ascending => {
+            let start = start_after.map(after);
+            let pairs = access.storage().pairs(start.as_deref(), None);
+            decode_page::<K, T, E>(pairs, limit.saturating_add(1))?
+        }
+        Order::Descending => {
+            let end = start_after.map(|key| storey::key!(key));
+            let pairs = access.storage().rev_pairs(None, end.as_deref());
+            decode_page::<K, T, E>(pairs, limit.saturating_add(1))?
+        }
+    };
+
+    let next_key = if pairs.len() > limit {
+        pairs.truncate(limit);
+        pairs.last().map(|(key, _)| key.clone())
+    } else {
+        None
+    };
+
+    Ok(Page {
+        entries: pairs
+            .into_iter()
+            .map(|(key, value)| entry(key, value))
+            .collect(),
+        next_key,
+    })
+}
+
+/// The limit a query asked for, or `default`, capped at `max`.
+fn clamp(limit: Option<u32>, default: u32, max: u32) -> usize {
+    limit.unwrap_or(default).min(max) as usize
+}
+
+/// The smallest raw key after the entry for `key`.
+fn after<K: Key>(key: &K) -> Vec<u8> {
+    let mut raw = storey::key!(key);
+    raw.push(0);
+    raw
+}
+
+fn decode_page<K, T, E>(
+    pairs: impl Iterator<Item = (Vec<u8>, Vec<u8>)>,
+    limit: usize,
+) -> Result<Vec<(K, T)>, PageError<K, T, E>>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    MapKeyDecodeError<ItemKeyDecodeError>: From<K::Error>,
+{
+    pairs
+        .take(limit)
+        .map(|(key, value)| {
+            let (key, ()) =
+                Map::<K, Item<T, E>>::decode_key_with_context(&key).map_err(KVDecodeError::Key)?;
+            let value = Map::<K, Item<T, E>>::decode_value(&value).map_err(KVDecodeError::Value)?;
+            Ok((key, value))
+        })
+        .collect()
+}
@@ -0,0 +1,155 @@
+//! Helpers for unit-testing contracts, against
+//! [`MockStorage`](cosmwasm_std::testing::MockStorage) or the storage of
+//! [`mock_dependencies`](cosmwasm_std::testing::mock_dependencies).
+//!
+//! [`seed`] fills a storage through the same containers the contract uses, and
+//! [`assert_item_eq`] and [`assert_map_contains`] check what's in it afterwards. The assertions
+//! panic with the raw keys involved, which helps telling apart a wrong value from a value
+//! stored under the wrong key.
+//!
+//! Like [`cosmwasm_std::testing`], this module isn't available when compiling to wasm.
+//!
+//! # Example
+//! ```
+//! use cosmwasm_std::testing::mock_dependencies;
+//! use cw_storey::containers::{Item, Map};
+//! use cw_storey::testing::{assert_item_eq, assert_map_contains, seed};
+//! use cw_storey::CwStorage;
+//!
+//! const OWNER: Item<String> = Item::new(0);
+//! const BALANCES: Map<String, Item<u128>> = Map::new(1);
+//!
+//! let mut deps = mock_dependencies();
+//! seed(deps.as_mut().storage, |s| {
+//!     OWNER.access(&mut *s).set(&"alice".to_string())?;
+//!     BALANCES.access(&mut *s).entry_mut("alice").set(&100)?;
+//!     Ok(())
+//! });
+//!
+//! // what an execute handler would do
+//! let mut storage = CwStorage(deps.as_mut().storage);
+//! BALANCES.access(&mut storage).entry_mut("bob").set(&20).unwrap();
+//!
+//! assert_item_eq(&deps.storage, &OWNER, &"alice".to_string());
+//! assert_map_contains(
+//!     &deps.storage,
+//!     &BALANCES,
+//!     &[("alice".to_string(), 100), ("bob".to_string(), 20)],
+//! );
+//! ```
+
+use std::fmt::{Debug, Display, Write as _};
+
+use cosmwasm_std::{StdResult, Storage};
+use storey::containers::{Item, ItemKeyDecodeError, Map, MapKeyDecodeError, OwnedKey};
+use storey::encoding::{DecodableWith, EncodableWith, Encoding};
+
+use crate::CwStorage;
+
+/// Fill `storage` with whatever `f` writes to it.
+///
+/// # Panics
+/// If `f` fails.
+#[track_caller]
+pub fn seed(
+    storage: &mut dyn Storage,
+    f: impl FnOnce(&mut CwStorage<&mut dyn Storage>) -> StdResult<()>,
+) {
+    if let Err(e) = f(&mut CwStorage(storage)) {
+        panic!("seeding the storage failed: {e}");
+    }
+}
+
+/// Assert that `item` holds `expected`.
+///
+/// # Panics
+/// If the item is empty, holds another value, or can't be decoded.
+#[track_caller]
+pub fn assert_item_eq<T, E>(storage: &dyn Storage, item: &Item<T, E>, expected: &T)
+where
+    E: Encoding,
+    E::DecodeError: Display,
+    T: EncodableWith<E> + DecodableWith<E> + PartialEq + Debug,
+{
+    let raw_key = item.prefix_bytes();
+    let found = item.access(&CwStorage(storage)).get();
+
+    if let Some(mismatch) = mismatch(storage, raw_key, found, expected) {
+        panic!("item at raw key {}: {mismatch}", hex(raw_key));
+    }
+}
+
+/// Assert that `map` has all the `expected` entries. Other entries are ignored.
+///
+/// # Panics
+/// If any of the entries is missing, has another value, or can't be decoded. All of the
+/// entries that don't match are listed.
+#[track_caller]
+pub fn assert_map_contains<K, T, E>(
+    storage: &dyn Storage,
+    map: &Map<K, Item<T, E>>,
+    expected: &[(K, T)],
+) where
+    K: OwnedKey + Debug,
+    E: Encoding,
+    E::DecodeError: Display,
+    T: EncodableWith<E> + DecodableWith<E> + PartialEq + Debug,
+    MapKeyDecodeError<ItemKeyDecodeError>: From<K::Error>,
+{
+    let cw_storage = CwStorage(storage);
+    let access = map.access(&cw_storage);
+
+    let mut report = String::new();
+    for (key, value) in expected {
+        let raw_key = [map.prefix_bytes(), &storey::key!(key)].concat();
+        let found = access.entry(key).get();
+
+        if let Some(mismatch) = mismatch(storage, &raw_key, found, value) {
+            let _ = write!(
+                report,
+                "\n  {key:?} (raw key {}): {mismatch}",
+                hex(&raw_key)
+            );
+        }
+    }
+
+    if !report.is_empty() {
+        panic!(
+            "map with prefix {} is missing expected entries:{report}",
+            hex(map.prefix_bytes())
+        );
+    }
+}
+
+/// What's wrong with the value `found` under `raw_key`, if anything.
+fn mismatch<T, D>(
+    storage: &dyn Storage,
+    raw_key: &[u8],
+    found: Result<Option<T>, D>,
+    expected: &T,
+) -> Option<String>
+where
+    T: PartialEq + Debug,
+    D: Display,
+{
+    match found {
+        Ok(Some(found)) if found == *expected => None,
+        Ok(Some(found)) => Some(format!("expected {expected:?}, found {found:?}")),
+        Ok(None) => Some(format!("expected {expected:?}, found nothing")),
+        Err(e) => {
+            let raw_value = storage.get(raw_key).unwrap_or_default();
+            Some(format!(
+                "expected {expected:?}, found a value that can't be decoded ({e}): {}",
+                hex(&raw_value)
+            ))
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut hex = String::from("0x");
+    for byte in bytes {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
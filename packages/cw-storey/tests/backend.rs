@@ -0,0 +1,113 @@
+// The storage backend, with whichever `cosmwasm-std` version is enabled. Both versions have to
+// store exactly the same bytes, and iterate over them the same way.
+//
+// The 1.x backend is checked by the CI run of
+// `cargo test -p cw-storey --tests --no-default-features --features cosmwasm-std-1,iteration`.
+
+#[cfg(all(feature = "cosmwasm-std-1", not(feature = "cosmwasm-std-2")))]
+extern crate cosmwasm_std_v1 as cosmwasm_std;
+
+use cosmwasm_std::testing::MockStorage;
+use cosmwasm_std::{Order, Storage};
+use cw_storey::containers::{Column, Item, Map};
+use cw_storey::CwStorage;
+#[cfg(feature = "iteration")]
+use storey::storage::{IterableStorage as _, RevIterableStorage as _};
+
+fn populated() -> MockStorage {
+    let mut raw_storage = MockStorage::new();
+    let mut storage = CwStorage(&mut raw_storage);
+
+    Item::<u64>::new(0).access(&mut storage).set(&42).unwrap();
+
+    let map = Map::<String, Item<u32>>::new(1);
+    map.access(&mut storage).entry_mut("foo").set(&1).unwrap();
+    map.access(&mut storage).entry_mut("bar").set(&2).unwrap();
+
+    let column = Column::<String>::new(2);
+    column.access(&mut storage).push(&"a".to_string()).unwrap();
+    column.access(&mut storage).push(&"b".to_string()).unwrap();
+
+    raw_storage
+}
+
+#[test]
+fn raw_layout() {
+    let raw_storage = populated();
+    let raw: Vec<_> = raw_storage.range(None, None, Order::Ascending).collect();
+
+    assert_eq!(raw, fixture());
+}
+
+#[test]
+#[cfg(feature = "iteration")]
+fn iteration() {
+    let raw_storage = populated();
+    let storage = CwStorage(&raw_storage);
+    let fixture = fixture();
+
+    let pairs: Vec<_> = storage.pairs(None, None).collect();
+    assert_eq!(pairs, fixture);
+    let keys: Vec<_> = storage.keys(None, None).collect();
+    assert_eq!(
+        keys,
+        fixture.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>()
+    );
+    let values: Vec<_> = storage.values(None, None).collect();
+    assert_eq!(
+        values,
+        fixture.iter().map(|(_, v)| v.clone()).collect::<Vec<_>>()
+    );
+
+    let pairs: Vec<_> = storage.rev_pairs(None, None).collect();
+    assert_eq!(pairs, fixture.iter().rev().cloned().collect::<Vec<_>>());
+    let keys: Vec<_> = storage.rev_keys(None, None).collect();
+    assert_eq!(
+        keys,
+        fixture
+            .iter()
+            .rev()
+            .map(|(k, _)| k.clone())
+            .collect::<Vec<_>>()
+    );
+    let values: Vec<_> = storage.rev_values(None, None).collect();
+    assert_eq!(
+        values,
+        fixture
+            .iter()
+            .rev()
+            .map(|(_, v)| v.clone())
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+#[cfg(feature = "iteration")]
+fn bounded_iteration() {
+    let raw_storage = populated();
+    let storage = CwStorage(&raw_storage);
+
+    // the map entries only
+    let keys: Vec<_> = storage.keys(Some(&[1]), Some(&[2])).collect();
+    assert_eq!(keys, [b"\x01\x03bar".to_vec(), b"\x01\x03foo".to_vec()]);
+    let values: Vec<_> = storage.rev_values(Some(&[1]), Some(&[2])).collect();
+    assert_eq!(values, [vec![1], vec![2]]);
+}
+
+/// The raw contents of [`populated`], in order.
+fn fixture() -> Vec<(Vec<u8>, Vec<u8>)> {
+    let fixture: &[(&[u8], &[u8])] = &[
+        (&[0], &[42]),
+        (b"\x01\x03bar", &[2]),
+        (b"\x01\x03foo", &[1]),
+        (&[2, 0, 0, 0, 0], b"\xa1a"),
+        (&[2, 0, 0, 0, 1], b"\xa1b"),
+        // the column's metadata: the last index, then the length
+        (&[255, 2, 0], &[0, 0, 0, 1]),
+        (&[255, 2, 1], &[0, 0, 0, 2]),
+    ];
+    fixture
+        .iter()
+        .map(|(k, v)| (k.to_vec(), v.to_vec()))
+        .collect()
+}
@@ -0,0 +1,459 @@
+// Reading maps written by cw-storage-plus through the compat layer.
+//
+// Every fixture is written with the real cw-storage-plus, then read back with `PlusMap`, so
+// these tests break if the two layouts ever drift apart.
+
+#![cfg(feature = "cosmwasm-std-2")]
+
+use std::ops::Bound;
+
+use cosmwasm_std::testing::MockStorage;
+use cosmwasm_std::{Addr, Order, Uint128};
+use cw_storage_plus as plus;
+use cw_storey::compat::{
+    MigrationProgress, PlusItem, PlusKeyError, PlusMap, PlusMapError, PlusMigrateError,
+};
+use cw_storey::containers::{Item, Map};
+use cw_storey::CwStorage;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+struct Config {
+    owner: Addr,
+    fee: Uint128,
+    paused: bool,
+}
+
+#[test]
+fn string_keys() {
+    const OLD: plus::Map<&str, Config> = plus::Map::new("config");
+    const COMPAT: PlusMap<String, Config> = PlusMap::new("config");
+
+    let config = |owner: &str, fee| Config {
+        owner: Addr::unchecked(owner),
+        fee: Uint128::new(fee),
+        paused: false,
+    };
+
+    let mut raw = MockStorage::new();
+    OLD.save(&mut raw, "b", &config("bob", 2)).unwrap();
+    OLD.save(&mut raw, "aa", &config("alice", 1)).unwrap();
+    OLD.save(&mut raw, "", &config("nobody", 0)).unwrap();
+
+    let storage = CwStorage(&raw);
+    assert_eq!(COMPAT.get(&storage, "b").unwrap(), Some(config("bob", 2)));
+    assert_eq!(COMPAT.get(&storage, "").unwrap(), Some(config("nobody", 0)));
+    assert_eq!(COMPAT.get(&storage, "c").unwrap(), None);
+    assert!(COMPAT.has(&storage, "aa"));
+    assert!(!COMPAT.has(&storage, "a"));
+
+    assert_same_entries(&raw, &OLD, &COMPAT);
+}
+
+#[test]
+fn addr_keys() {
+    const OLD: plus::Map<&Addr, Uint128> = plus::Map::new("balances");
+    const COMPAT: PlusMap<Addr, Uint128> = PlusMap::new("balances");
+
+    let mut raw = MockStorage::new();
+    for (name, balance) in [("carol", 3), ("alice", 1), ("bob", 2)] {
+        let addr = Addr::unchecked(name);
+        OLD.save(&mut raw, &addr, &Uint128::new(balance)).unwrap();
+    }
+
+    let storage = CwStorage(&raw);
+    assert_eq!(
+        COMPAT.get(&storage, &Addr::unchecked("bob")).unwrap(),
+        Some(Uint128::new(2))
+    );
+
+    assert_same_entries(&raw, &OLD, &COMPAT);
+}
+
+#[test]
+fn int_keys() {
+    const OLD_UNSIGNED: plus::Map<u32, u64> = plus::Map::new("unsigned");
+    const COMPAT_UNSIGNED: PlusMap<u32, u64> = PlusMap::new("unsigned");
+    const OLD_SIGNED: plus::Map<i64, String> = plus::Map::new("signed");
+    const COMPAT_SIGNED: PlusMap<i64, String> = PlusMap::new("signed");
+
+    let mut raw = MockStorage::new();
+    for key in [0, 1, 256, u32::MAX, 7] {
+        OLD_UNSIGNED.save(&mut raw, key, &u64::from(key)).unwrap();
+    }
+    for key in [i64::MIN, -1, 0, 1, i64::MAX, -300] {
+        OLD_SIGNED.save(&mut raw, key, &key.to_string()).unwrap();
+    }
+
+    let storage = CwStorage(&raw);
+    assert_eq!(COMPAT_UNSIGNED.get(&storage, &256).unwrap(), Some(256));
+    assert_eq!(
+        COMPAT_SIGNED.get(&storage, &-300).unwrap(),
+        Some("-300".to_string())
+    );
+
+    assert_same_entries(&raw, &OLD_UNSIGNED, &COMPAT_UNSIGNED);
+    assert_same_entries(&raw, &OLD_SIGNED, &COMPAT_SIGNED);
+
+    // negative numbers come first
+    let keys: Vec<_> = COMPAT_SIGNED
+        .keys(&storage, None, None)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(keys, [i64::MIN, -300, -1, 0, 1, i64::MAX]);
+}
+
+#[test]
+fn byte_keys() {
+    const OLD: plus::Map<&[u8], bool> = plus::Map::new("bytes");
+    const COMPAT: PlusMap<Vec<u8>, bool> = PlusMap::new("bytes");
+
+    let mut raw = MockStorage::new();
+    OLD.save(&mut raw, &[0xff, 0], &true).unwrap();
+    OLD.save(&mut raw, &[], &false).unwrap();
+    OLD.save(&mut raw, b"key", &true).unwrap();
+
+    let storage = CwStorage(&raw);
+    assert_eq!(COMPAT.get(&storage, &[0xff, 0][..]).unwrap(), Some(true));
+
+    assert_same_entries(&raw, &OLD, &COMPAT);
+}
+
+#[test]
+fn composite_keys() {
+    const OLD_PAIR: plus::Map<(&Addr, &str), u64> = plus::Map::new("allowances");
+    const COMPAT_PAIR: PlusMap<(Addr, String), u64> = PlusMap::new("allowances");
+    const OLD_TRIPLE: plus::Map<(u64, &str, i32), bool> = plus::Map::new("triples");
+    const COMPAT_TRIPLE: PlusMap<(u64, String, i32), bool> = PlusMap::new("triples");
+
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut raw = MockStorage::new();
+    OLD_PAIR.save(&mut raw, (&alice, "uatom"), &1).unwrap();
+    OLD_PAIR.save(&mut raw, (&alice, "uosmo"), &2).unwrap();
+    OLD_PAIR.save(&mut raw, (&bob, "uatom"), &3).unwrap();
+    OLD_PAIR.save(&mut raw, (&bob, ""), &4).unwrap();
+
+    OLD_TRIPLE.save(&mut raw, (1, "a", -1), &true).unwrap();
+    OLD_TRIPLE.save(&mut raw, (1, "a", 5), &false).unwrap();
+    OLD_TRIPLE.save(&mut raw, (0, "bc", 0), &true).unwrap();
+    OLD_TRIPLE.save(&mut raw, (1, "", i32::MIN), &true).unwrap();
+
+    let storage = CwStorage(&raw);
+    assert_eq!(
+        COMPAT_PAIR
+            .get(&storage, &(bob.clone(), "uatom".to_string()))
+            .unwrap(),
+        Some(3)
+    );
+    assert_eq!(
+        COMPAT_TRIPLE
+            .get(&storage, &(1, "a".to_string(), 5))
+            .unwrap(),
+        Some(false)
+    );
+
+    assert_same_entries(&raw, &OLD_PAIR, &COMPAT_PAIR);
+    assert_same_entries(&raw, &OLD_TRIPLE, &COMPAT_TRIPLE);
+
+    // the maps don't see each other's entries
+    assert_eq!(COMPAT_PAIR.keys(&storage, None, None).count(), 4);
+}
+
+#[test]
+fn bounds_match() {
+    const OLD: plus::Map<u32, u32> = plus::Map::new("bounded");
+    const COMPAT: PlusMap<u32, u32> = PlusMap::new("bounded");
+
+    let mut raw = MockStorage::new();
+    for key in [1, 3, 5, 7, 9] {
+        OLD.save(&mut raw, key, &(key * 10)).unwrap();
+    }
+    let storage = CwStorage(&raw);
+
+    let bounds = [
+        (None, None),
+        (Some(Bound::Included(3)), None),
+        (Some(Bound::Excluded(3)), None),
+        (None, Some(Bound::Included(7))),
+        (None, Some(Bound::Excluded(7))),
+        (Some(Bound::Included(4)), Some(Bound::Excluded(9))),
+        (Some(Bound::Excluded(1)), Some(Bound::Included(2))),
+        (Some(Bound::Included(9)), Some(Bound::Excluded(1))),
+    ];
+
+    for (min, max) in bounds {
+        let old: Vec<_> = OLD
+            .range(
+                &raw,
+                min.map(plus_bound),
+                max.map(plus_bound),
+                Order::Ascending,
+            )
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let compat: Vec<_> = COMPAT
+            .range(&storage, min.as_ref().map(as_ref), max.as_ref().map(as_ref))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(compat, old, "bounds {min:?}..{max:?}");
+    }
+}
+
+#[test]
+fn invalid_data() {
+    const COMPAT_STRING: PlusMap<String, u64> = PlusMap::new("strings");
+    const COMPAT_INT: PlusMap<u32, u64> = PlusMap::new("ints");
+    const COMPAT_PAIR: PlusMap<(String, u32), u64> = PlusMap::new("pairs");
+
+    let mut raw = MockStorage::new();
+    plus::Map::<&str, String>::new("strings")
+        .save(&mut raw, "key", &"not a number".to_string())
+        .unwrap();
+    plus::Map::<&[u8], u64>::new("strings")
+        .save(&mut raw, &[0xff], &1)
+        .unwrap();
+    plus::Map::<&[u8], u64>::new("ints")
+        .save(&mut raw, &[1, 2], &1)
+        .unwrap();
+    plus::Map::<&[u8], u64>::new("pairs")
+        .save(&mut raw, &[0, 5, b'a'], &1)
+        .unwrap();
+
+    let storage = CwStorage(&raw);
+    assert!(matches!(
+        COMPAT_STRING.get(&storage, "key"),
+        Err(PlusMapError::Value(_))
+    ));
+    assert!(matches!(
+        COMPAT_STRING.keys(&storage, None, None).last(),
+        Some(Err(PlusMapError::Key(PlusKeyError::InvalidUtf8)))
+    ));
+    assert!(matches!(
+        COMPAT_INT.keys(&storage, None, None).next(),
+        Some(Err(PlusMapError::Key(PlusKeyError::InvalidLength {
+            expected: 4,
+            actual: 2
+        })))
+    ));
+    assert!(matches!(
+        COMPAT_PAIR.keys(&storage, None, None).next(),
+        Some(Err(PlusMapError::Key(PlusKeyError::InvalidComponent)))
+    ));
+}
+
+/// Check that `compat` iterates over exactly the entries `old` does, in the same order.
+fn assert_same_entries<'a, K, V, CK>(
+    raw: &MockStorage,
+    old: &plus::Map<K, V>,
+    compat: &PlusMap<CK, V>,
+) where
+    K: plus::PrimaryKey<'a> + plus::KeyDeserialize,
+    K::Output: PartialEq<CK> + std::fmt::Debug + 'static,
+    V: Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+    CK: cw_storey::compat::PlusOwnedKey + std::fmt::Debug,
+{
+    let old: Vec<(K::Output, V)> = old
+        .range(raw, None, None, Order::Ascending)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let compat: Vec<(CK, V)> = compat
+        .range(&CwStorage(raw), None, None)
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(old.len(), compat.len());
+    for ((old_key, old_value), (key, value)) in old.iter().zip(&compat) {
+        assert_eq!(old_key, key);
+        assert_eq!(old_value, value);
+    }
+}
+
+fn plus_bound(bound: Bound<u32>) -> plus::Bound<'static, u32> {
+    match bound {
+        Bound::Included(key) => plus::Bound::inclusive(key),
+        Bound::Excluded(key) => plus::Bound::exclusive(key),
+        Bound::Unbounded => unreachable!("unbounded sides are None"),
+    }
+}
+
+fn as_ref(bound: &Bound<u32>) -> Bound<&u32> {
+    bound.as_ref()
+}
+
+#[test]
+fn items() {
+    const OLD_CONFIG: plus::Item<Config> = plus::Item::new("config");
+    const COMPAT_CONFIG: PlusItem<Config> = PlusItem::new("config");
+    const OLD_EMPTY: plus::Item<u64> = plus::Item::new("");
+    const COMPAT_EMPTY: PlusItem<u64> = PlusItem::new("");
+
+    let config = Config {
+        owner: Addr::unchecked("owner"),
+        fee: Uint128::new(5),
+        paused: true,
+    };
+
+    let mut raw = MockStorage::new();
+    let storage = CwStorage(&raw);
+    assert_eq!(COMPAT_CONFIG.get(&storage).unwrap(), None);
+
+    OLD_CONFIG.save(&mut raw, &config).unwrap();
+    OLD_EMPTY.save(&mut raw, &7).unwrap();
+
+    let storage = CwStorage(&raw);
+    assert_eq!(COMPAT_CONFIG.get(&storage).unwrap(), Some(config));
+    assert_eq!(COMPAT_EMPTY.get(&storage).unwrap(), Some(7));
+
+    // the wrong type
+    assert!(PlusItem::<bool>::new("config").get(&storage).is_err());
+}
+
+#[test]
+fn item_with_long_namespace() {
+    // map namespaces this long get a different length prefix, item keys don't change
+    let namespace = "n".repeat(300);
+    let old = plus::Item::<String>::new_dyn(namespace.clone());
+
+    let mut raw = MockStorage::new();
+    old.save(&mut raw, &"value".to_string()).unwrap();
+
+    let storage = CwStorage(&raw);
+    assert_eq!(
+        PlusItem::<String>::new(&namespace).get(&storage).unwrap(),
+        Some("value".to_string())
+    );
+}
+
+#[test]
+fn migrate_item() {
+    const OLD: plus::Item<Uint128> = plus::Item::new("total");
+    const COMPAT: PlusItem<Uint128> = PlusItem::new("total");
+    const TOTAL: Item<Uint128> = Item::new(0);
+
+    let mut raw = MockStorage::new();
+    OLD.save(&mut raw, &Uint128::new(1000)).unwrap();
+
+    let mut storage = CwStorage(&mut raw);
+    assert!(COMPAT.migrate_into(&mut storage, &TOTAL).unwrap());
+    assert_eq!(
+        TOTAL.access(&storage).get().unwrap(),
+        Some(Uint128::new(1000))
+    );
+
+    // the old key is gone, so a second migration doesn't overwrite the new value
+    TOTAL.access(&mut storage).set(&Uint128::new(1)).unwrap();
+    assert!(!COMPAT.migrate_into(&mut storage, &TOTAL).unwrap());
+    assert_eq!(TOTAL.access(&storage).get().unwrap(), Some(Uint128::new(1)));
+
+    assert_eq!(OLD.may_load(&raw).unwrap(), None);
+}
+
+#[test]
+fn migrate_map_in_chunks() {
+    const OLD: plus::Map<(&Addr, &str), Uint128> = plus::Map::new("allowances");
+    const COMPAT: PlusMap<(Addr, String), Uint128> = PlusMap::new("allowances");
+    const ALLOWANCES: Map<Addr, Map<String, Item<u128>>> = Map::new(1);
+
+    const ENTRIES: u128 = 3000;
+    let owner = |i: u128| Addr::unchecked(format!("owner{}", i % 100));
+    let denom = |i: u128| format!("denom{i}");
+
+    let mut raw = MockStorage::new();
+    for i in 0..ENTRIES {
+        OLD.save(&mut raw, (&owner(i), &denom(i)), &Uint128::new(i))
+            .unwrap();
+    }
+
+    let mut storage = CwStorage(&mut raw);
+    let migrate = |storage: &mut CwStorage<_>, (owner, denom): (Addr, String), amount: Uint128| {
+        ALLOWANCES
+            .access(storage)
+            .entry_mut(&owner)
+            .entry_mut(&denom)
+            .set(&amount.u128())
+    };
+
+    let mut progress = vec![];
+    for _ in 0..3 {
+        progress.push(COMPAT.migrate(&mut storage, 1000, migrate).unwrap());
+        // entries migrated so far, and only those, are gone from the old map
+        let migrated: usize = progress.iter().map(|progress| progress.migrated).sum();
+        assert_eq!(
+            COMPAT.keys(&storage, None, None).count(),
+            ENTRIES as usize - migrated
+        );
+    }
+    assert_eq!(
+        progress,
+        [
+            MigrationProgress {
+                migrated: 1000,
+                done: false
+            },
+            MigrationProgress {
+                migrated: 1000,
+                done: false
+            },
+            MigrationProgress {
+                migrated: 1000,
+                done: true
+            },
+        ]
+    );
+
+    // nothing left to do
+    let progress = COMPAT.migrate(&mut storage, 1000, migrate).unwrap();
+    assert_eq!(progress.migrated, 0);
+    assert!(progress.done);
+
+    for i in 0..ENTRIES {
+        let amount = ALLOWANCES
+            .access(&storage)
+            .entry(&owner(i))
+            .entry(&denom(i))
+            .get()
+            .unwrap();
+        assert_eq!(amount, Some(i));
+    }
+    assert_eq!(OLD.keys(&raw, None, None, Order::Ascending).count(), 0);
+}
+
+#[test]
+fn failed_migration_keeps_entry() {
+    const OLD: plus::Map<u32, u32> = plus::Map::new("numbers");
+    const COMPAT: PlusMap<u32, u32> = PlusMap::new("numbers");
+
+    let mut raw = MockStorage::new();
+    for i in 0..3 {
+        OLD.save(&mut raw, i, &i).unwrap();
+    }
+
+    let mut storage = CwStorage(&mut raw);
+    let err = COMPAT
+        .migrate(&mut storage, 10, |_, key, _| match key {
+            1 => Err("nope"),
+            _ => Ok(()),
+        })
+        .unwrap_err();
+    assert!(matches!(err, PlusMigrateError::Write("nope")));
+
+    // the entry before the failing one was moved, the rest wasn't
+    let keys: Vec<_> = COMPAT
+        .keys(&storage, None, None)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(keys, [1, 2]);
+}
+
+#[test]
+fn debug() {
+    // only the namespace is printed, not the value types
+    let item = PlusItem::<Config>::new("config");
+    let map = PlusMap::<String, Uint128>::new("balances");
+
+    assert_eq!(format!("{item:?}"), r#"PlusItem { namespace: "config" }"#);
+    assert_eq!(format!("{map:?}"), r#"PlusMap { namespace: "balances" }"#);
+}
@@ -0,0 +1,476 @@
+// The container test-suite, run against `cosmwasm_std::testing::MockStorage` through the
+// `CwStorage` adapter.
+
+#![cfg(feature = "cosmwasm-std-2")]
+
+use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
+use cosmwasm_std::{Addr, CanonicalAddr, Storage, Timestamp};
+use cw_storey::containers::{Column, Item, Map};
+use cw_storey::CwStorage;
+
+use storey::containers::{BoundedIterableAccessor as _, IterableAccessor as _};
+use storey::storage::{Metered, RevIterableStorage as _, StorageBranch};
+use storey_testing::storey_container_tests;
+
+/// A fresh `MockStorage`, owned by the adapter.
+fn mock_storage() -> CwStorage<Box<dyn Storage>> {
+    CwStorage(Box::new(MockStorage::new()))
+}
+
+storey_container_tests! {
+    item item: Item<u64>,
+    value: |i| u64::from(i) * 1337,
+    storage: mock_storage(),
+}
+
+storey_container_tests! {
+    column column: Column<u64>,
+    value: |i| u64::from(i) + 9000,
+    storage: mock_storage(),
+}
+
+storey_container_tests! {
+    map string_keys: Map<String, Item<u64>>,
+    key: |i| format!("key{i}").repeat(i as usize % 3 + 1),
+    value: u64::from,
+    storage: mock_storage(),
+}
+
+storey_container_tests! {
+    ordered_map int_keys: Map<u32, Item<u64>>,
+    key: |i| i * i * 100,
+    value: |i| u64::from(i) * 10,
+    storage: mock_storage(),
+}
+
+#[test]
+fn aliases_default_to_cw_encoding() {
+    use cw_storey::CwEncoding;
+    use storey::encoding::RawEncoding;
+
+    let _: storey::containers::Item<u64, CwEncoding> = Item::<u64>::new(0);
+    let _: storey::containers::Column<u64, CwEncoding> = Column::<u64>::new(0);
+
+    // the encoding can still be picked per container
+    let mut raw = MockStorage::new();
+    let mut storage = CwStorage(&mut raw);
+
+    let names = Map::<Addr, Item<String, RawEncoding>>::new(0);
+    let owner = Addr::unchecked("owner");
+    names
+        .access(&mut storage)
+        .entry_mut(&owner)
+        .set(&"alice".to_string())
+        .unwrap();
+
+    let mut key = vec![0, 5];
+    key.extend_from_slice(b"owner");
+    assert_eq!(raw.get(&key), Some(b"alice".to_vec()));
+}
+
+#[test]
+fn nested_map_iteration() {
+    let mut raw = MockStorage::new();
+    let mut storage = CwStorage(&mut raw);
+
+    let map = Map::<String, Map<String, Item<u64>>>::new(0);
+    let mut access = map.access(&mut storage);
+
+    access.entry_mut("foo").entry_mut("bar").set(&1337).unwrap();
+    access.entry_mut("foo").entry_mut("baz").set(&42).unwrap();
+    access
+        .entry_mut("qux")
+        .entry_mut("quux")
+        .set(&9001)
+        .unwrap();
+
+    assert_eq!(
+        access.pairs().collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![
+            (("foo".to_string(), ("bar".to_string(), ())), 1337),
+            (("foo".to_string(), ("baz".to_string(), ())), 42),
+            (("qux".to_string(), ("quux".to_string(), ())), 9001)
+        ]
+    );
+    assert_eq!(
+        access
+            .entry("foo")
+            .keys()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        vec![("bar".to_string(), ()), ("baz".to_string(), ())]
+    );
+    assert_eq!(
+        access
+            .entry("qux")
+            .values()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        vec![9001]
+    );
+}
+
+#[test]
+fn addr_keys() {
+    let mut raw = MockStorage::new();
+    let mut storage = CwStorage(&mut raw);
+
+    let api = MockApi::default();
+    let addrs: Vec<Addr> = ["alice", "bob", "carol", "dave"]
+        .into_iter()
+        .map(|name| api.addr_make(name))
+        .collect();
+
+    let map = Map::<Addr, Item<u64>>::new(0);
+    let mut access = map.access(&mut storage);
+    for (i, addr) in addrs.iter().enumerate() {
+        access.entry_mut(addr).set(&(i as u64)).unwrap();
+    }
+
+    // the addresses have the same length, so they come out sorted by their string form
+    let mut sorted = addrs.clone();
+    sorted.sort();
+    assert_eq!(
+        access
+            .keys()
+            .map(|key| key.map(|(addr, ())| addr))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        sorted
+    );
+
+    // lookups with an address that hasn't been validated
+    assert_eq!(
+        access
+            .entry(&Addr::unchecked(addrs[1].as_str()))
+            .get()
+            .unwrap(),
+        Some(1)
+    );
+    assert_eq!(
+        access.entry(&Addr::unchecked("nobody")).get().unwrap(),
+        None
+    );
+
+    // the raw key is the address string
+    let mut key = vec![0, addrs[0].as_str().len() as u8];
+    key.extend_from_slice(addrs[0].as_bytes());
+    assert!(raw.get(&key).is_some());
+
+    // keys that aren't valid UTF-8 fail to decode
+    raw.set(&[0, 2, 0xff, 0xfe], &[0]);
+    let storage = CwStorage(&raw);
+    assert!(map.access(&storage).keys().any(|key| key.is_err()));
+}
+
+#[test]
+fn addr_keyed_map_of_map() {
+    let mut raw = MockStorage::new();
+    let mut storage = CwStorage(&mut raw);
+
+    let api = MockApi::default();
+    let alice = api.addr_make("alice");
+    let bob = api.addr_make("bob");
+
+    let map = Map::<Addr, Map<String, Item<u64>>>::new(0);
+    let mut access = map.access(&mut storage);
+    access.entry_mut(&alice).entry_mut("atom").set(&10).unwrap();
+    access.entry_mut(&alice).entry_mut("osmo").set(&20).unwrap();
+    access.entry_mut(&bob).entry_mut("atom").set(&30).unwrap();
+
+    assert_eq!(
+        access
+            .entry(&alice)
+            .pairs()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        vec![
+            (("atom".to_string(), ()), 10),
+            (("osmo".to_string(), ()), 20)
+        ]
+    );
+
+    let mut expected = vec![
+        ((alice.clone(), ("atom".to_string(), ())), 10),
+        ((alice.clone(), ("osmo".to_string(), ())), 20),
+        ((bob.clone(), ("atom".to_string(), ())), 30),
+    ];
+    expected.sort_by(|a, b| a.0 .0.cmp(&b.0 .0));
+    assert_eq!(
+        access.pairs().collect::<Result<Vec<_>, _>>().unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn canonical_addr_keys() {
+    let mut raw = MockStorage::new();
+    let mut storage = CwStorage(&mut raw);
+
+    let map = Map::<CanonicalAddr, Item<u64>>::new(0);
+    let mut access = map.access(&mut storage);
+
+    // 20 bytes, like most chains use, and 32 bytes, like contract addresses on some
+    let short: Vec<CanonicalAddr> = [[3; 20], [1; 20], [2; 20]]
+        .into_iter()
+        .map(|bytes| CanonicalAddr::from(&bytes[..]))
+        .collect();
+    let long = CanonicalAddr::from(vec![0; 32]);
+
+    for (i, addr) in short.iter().chain([&long]).enumerate() {
+        access.entry_mut(addr).set(&(i as u64)).unwrap();
+    }
+
+    for (i, addr) in short.iter().chain([&long]).enumerate() {
+        assert_eq!(access.entry(addr).get().unwrap(), Some(i as u64));
+    }
+
+    // sorted by their bytes, the shorter ones first
+    let keys = access
+        .keys()
+        .map(|key| key.map(|(addr, ())| addr))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        keys,
+        [
+            short[1].clone(),
+            short[2].clone(),
+            short[0].clone(),
+            long.clone()
+        ]
+    );
+
+    // the raw key is the address bytes
+    let mut key = vec![0, 32];
+    key.extend_from_slice(long.as_slice());
+    assert!(raw.get(&key).is_some());
+}
+
+#[test]
+fn expiring_before_block_time() {
+    let mut raw = MockStorage::new();
+    let mut storage = CwStorage(&mut raw);
+
+    let env = mock_env();
+    let now = env.block.time;
+
+    // expiration -> claim id
+    let expirations = Map::<Timestamp, Map<u64, Item<()>>>::new(0);
+    let mut access = expirations.access(&mut storage);
+    let claims = [
+        (now.minus_seconds(3600), 1),
+        (now.plus_seconds(1), 2),
+        (now.minus_nanos(1), 3),
+        (now, 4),
+        (now.minus_seconds(3600), 5),
+        (Timestamp::from_nanos(0), 6),
+    ];
+    for (expiration, id) in claims {
+        access
+            .entry_mut(&expiration)
+            .entry_mut(&id)
+            .set(&())
+            .unwrap();
+    }
+
+    let expired: Vec<_> = access
+        .bounded_keys(None::<Timestamp>, Some(now))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        expired,
+        [
+            (Timestamp::from_nanos(0), (6, ())),
+            (now.minus_seconds(3600), (1, ())),
+            (now.minus_seconds(3600), (5, ())),
+            (now.minus_nanos(1), (3, ())),
+        ]
+    );
+}
+
+// What a query handler gets through `Deps`: a shared reference, which is enough to iterate,
+// and can be used by several readers at once.
+#[test]
+fn shared_iteration() {
+    let mut raw = MockStorage::new();
+
+    let map = Map::<String, Item<u64>>::new(0);
+    {
+        let mut storage = CwStorage(&mut raw as &mut dyn Storage);
+        map.access(&mut storage).entry_mut("a").set(&1).unwrap();
+        map.access(&mut storage).entry_mut("b").set(&2).unwrap();
+    }
+
+    let shared: &dyn Storage = &raw;
+    let first = CwStorage(shared);
+    let second = CwStorage(shared);
+
+    let first_access = map.access(&first);
+    let second_access = map.access(&second);
+
+    let mut keys = first_access.keys();
+    let values = second_access
+        .values()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(keys.next().unwrap().unwrap().0, "a");
+    assert_eq!(values, vec![1, 2]);
+    assert_eq!(keys.next().unwrap().unwrap().0, "b");
+    assert!(keys.next().is_none());
+}
+
+#[test]
+fn reverse_iteration() {
+    let mut raw = MockStorage::new();
+    let mut storage = CwStorage(&mut raw);
+
+    let map = Map::<String, Item<u64, storey::encoding::FixedIntEncoding>>::new(0);
+    for (key, value) in [("a", 1u64), ("b", 2), ("c", 3)] {
+        map.access(&mut storage).entry_mut(key).set(&value).unwrap();
+    }
+
+    let branch = StorageBranch::new(&storage, vec![0]);
+    let key = |k: &str| vec![1, k.as_bytes()[0]];
+
+    assert_eq!(
+        branch.rev_keys(None, None).collect::<Vec<_>>(),
+        vec![key("c"), key("b"), key("a")]
+    );
+    assert_eq!(
+        branch.rev_values(Some(&key("b")), None).collect::<Vec<_>>(),
+        vec![3u64.to_le_bytes().to_vec(), 2u64.to_le_bytes().to_vec()]
+    );
+    assert_eq!(
+        branch.rev_pairs(None, Some(&key("b"))).collect::<Vec<_>>(),
+        vec![(key("a"), 1u64.to_le_bytes().to_vec())]
+    );
+}
+
+// `MockStorage` can be used directly, and must agree byte for byte with the adapter.
+#[test]
+fn direct_and_adapter_agree() {
+    let map = Map::<String, Column<u64>>::new(0);
+
+    let mut through_adapter = MockStorage::new();
+    {
+        let mut storage = CwStorage(&mut through_adapter as &mut dyn Storage);
+        let mut access = map.access(&mut storage);
+        access.entry_mut("foo").push(&1337).unwrap();
+        access.entry_mut("foo").push(&42).unwrap();
+        access.entry_mut("bar").push(&9001).unwrap();
+    }
+
+    let mut direct = MockStorage::new();
+    {
+        let mut access = map.access(&mut direct);
+        access.entry_mut("foo").push(&1337).unwrap();
+        access.entry_mut("foo").push(&42).unwrap();
+        access.entry_mut("bar").push(&9001).unwrap();
+    }
+
+    let dump = |storage: &MockStorage| {
+        storage
+            .range(None, None, cosmwasm_std::Order::Ascending)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(dump(&through_adapter), dump(&direct));
+
+    // written through the adapter, read directly
+    assert_eq!(
+        map.access(&through_adapter)
+            .pairs()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        vec![
+            (("bar".to_string(), 0), 9001),
+            (("foo".to_string(), 0), 1337),
+            (("foo".to_string(), 1), 42)
+        ]
+    );
+    assert_eq!(map.access(&through_adapter).entry("foo").len().unwrap(), 2);
+
+    // written directly, read through the adapter
+    let storage = CwStorage(&direct as &dyn Storage);
+    assert_eq!(
+        map.access(&storage)
+            .entry("foo")
+            .values()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        vec![1337, 42]
+    );
+    assert_eq!(
+        StorageBranch::new(&direct, vec![0])
+            .rev_keys(None, None)
+            .collect::<Vec<_>>(),
+        StorageBranch::new(&storage, vec![0])
+            .rev_keys(None, None)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn from_deps() {
+    let mut deps = cosmwasm_std::testing::mock_dependencies();
+    let map = Map::<String, Item<u64>>::new(0);
+
+    let mut storage = CwStorage::from(deps.as_mut());
+    map.access(&mut storage)
+        .entry_mut("foo")
+        .set(&1337)
+        .unwrap();
+
+    let storage = CwStorage::from(deps.as_ref());
+    assert_eq!(map.access(&storage).entry("foo").get().unwrap(), Some(1337));
+    assert_eq!(map.access(&storage).keys().count(), 1);
+}
+
+// The same contract code, metered.
+#[test]
+fn metered() {
+    fn execute<S>(storage: &mut S)
+    where
+        S: storey::storage::StorageMut + storey::storage::Storage,
+    {
+        let map = Map::<String, Item<u64>>::new(0);
+        let counter = Item::<u64>::new(1);
+
+        map.access(&mut *storage).entry_mut("foo").set(&1).unwrap();
+        let count = counter.access(&mut *storage).get().unwrap().unwrap_or(0);
+        counter.access(&mut *storage).set(&(count + 1)).unwrap();
+    }
+
+    let mut raw = MockStorage::new();
+    let mut metered = Metered::with_prefixes(CwStorage(&mut raw as &mut dyn Storage), [0, 1]);
+    execute(&mut metered);
+
+    let report = metered.report();
+    assert_eq!(report.total.reads, 1);
+    assert_eq!(report.total.writes, 2);
+    assert_eq!(report.prefixes[&0].writes, 1);
+    assert_eq!(report.prefixes[&1].reads, 1);
+}
+
+#[test]
+fn errors_into_std_error() {
+    fn bump(storage: &mut CwStorage<Box<dyn Storage>>, ix: u32) -> cosmwasm_std::StdResult<u64> {
+        let column = Column::<u64>::new(0);
+        let value = column.access(&mut *storage).get(ix)?.unwrap_or(0) + 1;
+        column.access(&mut *storage).update(ix, &value)?;
+        Ok(value)
+    }
+
+    let mut storage = mock_storage();
+    Column::<u64>::new(0).access(&mut storage).push(&1).unwrap();
+
+    assert_eq!(bump(&mut storage, 0).unwrap(), 2);
+    assert!(bump(&mut storage, 1)
+        .unwrap_err()
+        .to_string()
+        .contains("not found"));
+
+    storage.0.set(&[0, 0, 0, 0, 0], &[0xc1]);
+    assert!(bump(&mut storage, 0).is_err());
+}
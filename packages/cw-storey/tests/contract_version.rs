@@ -0,0 +1,98 @@
+// The contract version, checked against the `cw2` crate itself.
+
+#![cfg(feature = "cosmwasm-std-2")]
+
+use cosmwasm_std::testing::MockStorage;
+use cosmwasm_std::Storage as _;
+use cw_storey::contract_version::{
+    assert_contract_version, get_contract_version, set_contract_version, ContractVersion,
+    VersionError, CONTRACT_INFO_KEY,
+};
+use cw_storey::CwStorage;
+
+#[test]
+fn layout() {
+    let mut raw = MockStorage::new();
+    set_contract_version(&mut CwStorage(&mut raw), "crates.io:my-contract", "1.2.3").unwrap();
+
+    assert_eq!(CONTRACT_INFO_KEY, b"contract_info");
+    assert_eq!(
+        raw.get(b"contract_info").unwrap(),
+        br#"{"contract":"crates.io:my-contract","version":"1.2.3"}"#
+    );
+}
+
+#[test]
+fn written_by_cw2() {
+    let mut raw = MockStorage::new();
+    cw2::set_contract_version(&mut raw, "crates.io:my-contract", "0.9.0").unwrap();
+
+    assert_eq!(
+        get_contract_version(&CwStorage(&raw)).unwrap(),
+        Some(ContractVersion {
+            contract: "crates.io:my-contract".to_string(),
+            version: "0.9.0".to_string(),
+        })
+    );
+}
+
+#[test]
+fn read_by_cw2() {
+    let mut raw = MockStorage::new();
+    set_contract_version(&mut CwStorage(&mut raw), "crates.io:my-contract", "2.0.0").unwrap();
+
+    let version = cw2::get_contract_version(&raw).unwrap();
+    assert_eq!(version.contract, "crates.io:my-contract");
+    assert_eq!(version.version, "2.0.0");
+    cw2::assert_contract_version(&raw, "crates.io:my-contract", "2.0.0").unwrap();
+}
+
+#[test]
+fn missing() {
+    let raw = MockStorage::new();
+    let storage = CwStorage(&raw);
+
+    assert_eq!(get_contract_version(&storage).unwrap(), None);
+    assert!(matches!(
+        assert_contract_version(&storage, "crates.io:my-contract", "1.0.0"),
+        Err(VersionError::NotFound)
+    ));
+}
+
+#[test]
+fn assert_version() {
+    let mut raw = MockStorage::new();
+    let mut storage = CwStorage(&mut raw);
+    set_contract_version(&mut storage, "crates.io:my-contract", "1.0.0").unwrap();
+
+    assert_contract_version(&storage, "crates.io:my-contract", "1.0.0").unwrap();
+
+    match assert_contract_version(&storage, "crates.io:other-contract", "1.0.0") {
+        Err(VersionError::WrongContract { expected, found }) => {
+            assert_eq!(expected, "crates.io:other-contract");
+            assert_eq!(found, "crates.io:my-contract");
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    match assert_contract_version(&storage, "crates.io:my-contract", "1.1.0") {
+        Err(VersionError::WrongVersion { expected, found }) => {
+            assert_eq!(expected, "1.1.0");
+            assert_eq!(found, "1.0.0");
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn invalid_value() {
+    let mut raw = MockStorage::new();
+    raw.set(b"contract_info", br#"{"contract":"x"}"#);
+    let storage = CwStorage(&raw);
+
+    assert!(get_contract_version(&storage).is_err());
+    assert!(matches!(
+        assert_contract_version(&storage, "x", "1.0.0"),
+        Err(VersionError::Std(_))
+    ));
+}
@@ -0,0 +1,162 @@
+//! A contract exposing its storage layout to indexers.
+
+#![cfg(feature = "cosmwasm-std-2")]
+
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{Addr, Binary, Deps, Env, StdResult, Uint128};
+use cw_storey::containers::{Column, Item, Map};
+use cw_storey::layout::{query_storage_layout, ContainerKind, StorageLayout};
+use cw_storey::storage_layout;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use storey::meta::StorageSchema;
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+struct Config {
+    owner: Addr,
+    fee: Uint128,
+}
+
+const CONFIG: Item<Config> = Item::new(0);
+const BALANCES: Map<Addr, Item<Uint128>> = Map::new(1);
+const ALLOWANCES: Map<Addr, Map<(u32, String), Item<Uint128>>> = Map::new(2);
+const EVENTS: Map<u64, Column<String>> = Map::new(7);
+
+fn layout() -> StorageLayout {
+    storage_layout![
+        "config" => CONFIG,
+        "balances" => BALANCES,
+        "allowances" => ALLOWANCES,
+        "events" => EVENTS,
+    ]
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum QueryMsg {
+    StorageLayout {},
+}
+
+fn query(_: Deps, _: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::StorageLayout {} => query_storage_layout(&layout()),
+    }
+}
+
+#[test]
+fn layout_query() {
+    let deps = mock_dependencies();
+    let response = query(deps.as_ref(), mock_env(), QueryMsg::StorageLayout {}).unwrap();
+
+    let mut document: Value = serde_json::from_slice(&response).unwrap();
+
+    // the schemas are only there with the `schemars` feature, see `schemas` below
+    for container in document["containers"].as_array_mut().unwrap() {
+        let schema = container.as_object_mut().unwrap().remove("schema");
+        assert_eq!(schema.is_some(), cfg!(feature = "schemars"));
+    }
+
+    assert_eq!(
+        document,
+        json!({
+            "containers": [
+                {
+                    "name": "config",
+                    "prefix": [0],
+                    "kind": "item",
+                    "key_structure": "",
+                },
+                {
+                    "name": "balances",
+                    "prefix": [1],
+                    "kind": "map",
+                    "key_structure": "Addr",
+                },
+                {
+                    "name": "allowances",
+                    "prefix": [2],
+                    "kind": "map",
+                    "key_structure": "Addr / (u32, String)",
+                },
+                {
+                    "name": "events",
+                    "prefix": [7],
+                    "kind": "map",
+                    "key_structure": "u64 / index",
+                },
+            ]
+        })
+    );
+}
+
+#[test]
+fn containers() {
+    let layout = layout();
+
+    let names: Vec<_> = layout.containers.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["config", "balances", "allowances", "events"]);
+    assert_eq!(layout.containers[0].kind, ContainerKind::Item);
+    assert_eq!(layout.containers[3].prefix, EVENTS.prefix_bytes());
+}
+
+#[cfg(feature = "schemars")]
+#[test]
+fn schemas() {
+    let layout = layout();
+
+    let config = layout.containers[0].schema.as_ref().unwrap();
+    assert_eq!(config.prefix, [0]);
+    assert!(config.definitions.contains_key("Config"));
+
+    let allowances = serde_json::to_value(layout.containers[2].schema.as_ref().unwrap()).unwrap();
+    assert_eq!(allowances["container"]["container"], "map");
+    assert_eq!(allowances["container"]["value"]["container"], json!("map"));
+}
+
+#[test]
+fn named_prefixes() {
+    const OWNER: Item<Addr> = Item::new_str("owner");
+    const VOTES: Map<u64, Item<Uint128>> = Map::new_str("votes");
+    const LOG: Column<String> = Column::new_str("log");
+
+    let layout = storage_layout!["owner" => OWNER, "votes" => VOTES, "log" => LOG];
+
+    let prefixes: Vec<_> = layout.containers.iter().map(|c| &c.prefix[..]).collect();
+    assert_eq!(prefixes, [&b"owner"[..], b"votes", b"log"]);
+    #[cfg(feature = "schemars")]
+    assert_eq!(
+        layout.containers[1].schema.as_ref().unwrap().prefix,
+        b"votes"
+    );
+}
+
+#[test]
+fn from_schema() {
+    #[allow(dead_code)]
+    #[derive(StorageSchema)]
+    struct State {
+        config: Item<Config>,
+        balances: Map<Addr, Item<Uint128>>,
+        allowances: Map<Addr, Map<(u32, String), Item<Uint128>>>,
+        #[storey(prefix = 7)]
+        events: Map<u64, Column<String>>,
+    }
+
+    // the same as listing the containers, apart from the schemas
+    let expected = layout();
+    #[cfg(feature = "schemars")]
+    let mut expected = expected;
+    #[cfg(feature = "schemars")]
+    for container in &mut expected.containers {
+        container.schema = None;
+    }
+    assert_eq!(StorageLayout::from_schema::<State>(), expected);
+
+    let document: Value = serde_json::from_slice(
+        &query_storage_layout(&StorageLayout::from_schema::<State>()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(document["containers"][3]["key_structure"], "u64 / index");
+    assert_eq!(document["containers"][3].get("schema"), None);
+}
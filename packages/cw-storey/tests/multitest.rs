@@ -0,0 +1,133 @@
+//! Seeding and inspecting the state of a contract running in `cw-multi-test`, through the same
+//! containers the contract uses.
+
+#![cfg(feature = "cosmwasm-std-2")]
+
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError,
+    StdResult,
+};
+use cw_multi_test::{App, ContractWrapper, Executor as _};
+use cw_storey::containers::{Item, Map};
+use cw_storey::CwStorage;
+use serde::{Deserialize, Serialize};
+use storey::containers::IterableAccessor as _;
+
+const OWNER: Item<String> = Item::new(0);
+const BALANCES: Map<String, Item<u128>> = Map::new(1);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Transfer {
+    to: String,
+    amount: u128,
+}
+
+fn instantiate(deps: DepsMut, _: Env, info: MessageInfo, _: Empty) -> StdResult<Response> {
+    let mut storage = CwStorage::from(deps);
+    OWNER.access(&mut storage).set(&info.sender.to_string())?;
+    Ok(Response::new())
+}
+
+fn execute(deps: DepsMut, _: Env, info: MessageInfo, msg: Transfer) -> StdResult<Response> {
+    let mut storage = CwStorage::from(deps);
+    let mut balances = BALANCES.access(&mut storage);
+
+    let from = info.sender.to_string();
+    let balance = balances.entry(&from).get()?.unwrap_or(0);
+    let remaining = balance
+        .checked_sub(msg.amount)
+        .ok_or_else(|| StdError::generic_err("insufficient balance"))?;
+    balances.entry_mut(&from).set(&remaining)?;
+
+    let received = balances.entry(&msg.to).get()?.unwrap_or(0);
+    balances.entry_mut(&msg.to).set(&(received + msg.amount))?;
+
+    Ok(Response::new())
+}
+
+fn query(deps: Deps, _: Env, _: Empty) -> StdResult<Binary> {
+    let storage = CwStorage::from(deps);
+    let total = BALANCES
+        .access(&storage)
+        .values()
+        .map(|balance| balance.map_err(Into::into))
+        .sum::<StdResult<u128>>()?;
+    to_json_binary(&total)
+}
+
+fn setup() -> (App, Addr, Addr) {
+    let mut app = App::default();
+    let owner = app.api().addr_make("owner");
+
+    let code_id = app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query)));
+    let contract = app
+        .instantiate_contract(code_id, owner.clone(), &Empty {}, &[], "bank", None)
+        .unwrap();
+
+    (app, owner, contract)
+}
+
+#[test]
+fn inspect_contract_state() {
+    let (app, owner, contract) = setup();
+
+    let storage = CwStorage(app.contract_storage(&contract));
+    assert_eq!(
+        OWNER.access(&storage).get().unwrap(),
+        Some(owner.to_string())
+    );
+    assert_eq!(BALANCES.access(&storage).keys().count(), 0);
+}
+
+#[test]
+fn seed_then_execute() {
+    let (mut app, owner, contract) = setup();
+    let alice = app.api().addr_make("alice");
+    let bob = app.api().addr_make("bob");
+
+    {
+        let mut storage = CwStorage(app.contract_storage_mut(&contract));
+        BALANCES
+            .access(&mut storage)
+            .entry_mut(alice.as_str())
+            .set(&100)
+            .unwrap();
+    }
+
+    app.execute_contract(
+        alice.clone(),
+        contract.clone(),
+        &Transfer {
+            to: bob.to_string(),
+            amount: 30,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the contract saw the seeded balance, and its writes are visible from here
+    {
+        let storage = CwStorage(app.contract_storage(&contract));
+        let balances = BALANCES.access(&storage);
+        assert_eq!(balances.entry(alice.as_str()).get().unwrap(), Some(70));
+        assert_eq!(balances.entry(bob.as_str()).get().unwrap(), Some(30));
+    }
+
+    let total: u128 = app.wrap().query_wasm_smart(&contract, &Empty {}).unwrap();
+    assert_eq!(total, 100);
+
+    // a transfer over the balance fails, and leaves the state as it was
+    app.execute_contract(
+        owner,
+        contract.clone(),
+        &Transfer {
+            to: bob.to_string(),
+            amount: 1,
+        },
+        &[],
+    )
+    .unwrap_err();
+
+    let storage = CwStorage(app.contract_storage(&contract));
+    assert_eq!(BALANCES.access(&storage).keys().count(), 2);
+}
@@ -0,0 +1,195 @@
+// Iteration in a `cosmwasm_std::Order`, with min/max bounds.
+
+#[cfg(all(feature = "cosmwasm-std-1", not(feature = "cosmwasm-std-2")))]
+extern crate cosmwasm_std_v1 as cosmwasm_std;
+
+use cosmwasm_std::testing::MockStorage;
+use cosmwasm_std::Order;
+use cw_storey::containers::{Column, Item, Map};
+use cw_storey::order::{Bound, CwIterableAccessor as _};
+use cw_storey::CwStorage;
+
+// the even numbers from 0 to 18, so that odd bounds aren't in the map
+fn seeded(map: &Map<u32, Item<u64>>) -> MockStorage {
+    let mut raw = MockStorage::new();
+    let mut storage = CwStorage(&mut raw);
+    for i in (0..20u32).step_by(2) {
+        map.access(&mut storage)
+            .entry_mut(&i)
+            .set(&u64::from(i * 10))
+            .unwrap();
+    }
+    raw
+}
+
+#[test]
+fn unbounded() {
+    const MAP: Map<u32, Item<u64>> = Map::new(0);
+    let raw = seeded(&MAP);
+    let storage = CwStorage(&raw);
+    let access = MAP.access(&storage);
+
+    let keys = |order| -> Vec<u32> {
+        access
+            .keys_cw::<u32>(order, None, None)
+            .map(|key| key.unwrap().0)
+            .collect()
+    };
+    assert_eq!(keys(Order::Ascending), [0, 2, 4, 6, 8, 10, 12, 14, 16, 18]);
+    assert_eq!(keys(Order::Descending), [18, 16, 14, 12, 10, 8, 6, 4, 2, 0]);
+
+    let pairs: Vec<_> = access
+        .pairs_cw::<u32>(Order::Descending, None, None)
+        .take(2)
+        .map(|pair| {
+            let ((key, ()), value) = pair.unwrap();
+            (key, value)
+        })
+        .collect();
+    assert_eq!(pairs, [(18, 180), (16, 160)]);
+}
+
+#[test]
+fn bounds_at_existing_keys() {
+    const MAP: Map<u32, Item<u64>> = Map::new(0);
+    let raw = seeded(&MAP);
+    let storage = CwStorage(&raw);
+    let access = MAP.access(&storage);
+
+    let keys = |order, min, max| -> Vec<u32> {
+        access
+            .keys_cw(order, min, max)
+            .map(|key| key.unwrap().0)
+            .collect()
+    };
+
+    use Order::*;
+    let (incl, excl) = (Bound::inclusive, Bound::exclusive);
+
+    assert_eq!(
+        keys(Ascending, Some(incl(4)), Some(incl(10))),
+        [4, 6, 8, 10]
+    );
+    assert_eq!(keys(Ascending, Some(excl(4)), Some(excl(10))), [6, 8]);
+    assert_eq!(
+        keys(Ascending, Some(excl(4)), None),
+        [6, 8, 10, 12, 14, 16, 18]
+    );
+    assert_eq!(keys(Ascending, None, Some(incl(4))), [0, 2, 4]);
+
+    // the bounds are still the lower and the upper one in descending order
+    assert_eq!(
+        keys(Descending, Some(incl(4)), Some(incl(10))),
+        [10, 8, 6, 4]
+    );
+    assert_eq!(keys(Descending, Some(excl(4)), Some(excl(10))), [8, 6]);
+    assert_eq!(keys(Descending, None, Some(excl(4))), [2, 0]);
+    assert_eq!(keys(Descending, Some(incl(14)), None), [18, 16, 14]);
+
+    assert!(keys(Ascending, Some(excl(4)), Some(excl(6))).is_empty());
+    assert_eq!(keys(Descending, Some(incl(8)), Some(incl(8))), [8]);
+    assert!(keys(Descending, Some(incl(10)), Some(incl(8))).is_empty());
+}
+
+#[test]
+fn bounds_at_missing_keys() {
+    const MAP: Map<u32, Item<u64>> = Map::new(0);
+    let raw = seeded(&MAP);
+    let storage = CwStorage(&raw);
+    let access = MAP.access(&storage);
+
+    let keys = |order, min, max| -> Vec<u32> {
+        access
+            .keys_cw(order, min, max)
+            .map(|key| key.unwrap().0)
+            .collect()
+    };
+
+    use Order::*;
+    let (incl, excl) = (Bound::inclusive, Bound::exclusive);
+
+    // whether a missing bound is inclusive doesn't matter
+    for (min, max) in [(incl(3), incl(9)), (excl(3), excl(9))] {
+        assert_eq!(keys(Ascending, Some(min), Some(max)), [4, 6, 8]);
+        assert_eq!(keys(Descending, Some(min), Some(max)), [8, 6, 4]);
+    }
+
+    assert_eq!(keys(Ascending, Some(excl(17)), None), [18]);
+    assert_eq!(keys(Descending, None, Some(incl(1))), [0]);
+    assert!(keys(Ascending, Some(incl(19)), None).is_empty());
+    assert!(keys(Descending, Some(incl(100)), Some(incl(200))).is_empty());
+
+    let values: Vec<_> = access
+        .values_cw(Descending, Some(excl(11)), Some(excl(15)))
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(values, [140, 120]);
+}
+
+#[test]
+fn nested_maps() {
+    // an exclusive bound skips all the entries under the key, not just the first
+    const MAP: Map<u32, Map<u32, Item<u64>>> = Map::new(0);
+
+    let mut raw = MockStorage::new();
+    let mut storage = CwStorage(&mut raw);
+    for outer in 0..4u32 {
+        for inner in 0..3u32 {
+            MAP.access(&mut storage)
+                .entry_mut(&outer)
+                .entry_mut(&inner)
+                .set(&u64::from(outer * 10 + inner))
+                .unwrap();
+        }
+    }
+
+    let access = MAP.access(&storage);
+    let keys = |order, min, max| -> Vec<(u32, u32)> {
+        access
+            .keys_cw(order, min, max)
+            .map(|key| {
+                let (outer, (inner, ())) = key.unwrap();
+                (outer, inner)
+            })
+            .collect()
+    };
+
+    assert_eq!(
+        keys(
+            Order::Ascending,
+            Some(Bound::exclusive(1)),
+            Some(Bound::exclusive(3))
+        ),
+        [(2, 0), (2, 1), (2, 2)]
+    );
+    assert_eq!(
+        keys(
+            Order::Descending,
+            Some(Bound::exclusive(1)),
+            Some(Bound::inclusive(3))
+        ),
+        [(3, 2), (3, 1), (3, 0), (2, 2), (2, 1), (2, 0)]
+    );
+}
+
+#[test]
+fn columns() {
+    const COLUMN: Column<String> = Column::new(0);
+
+    let mut raw = MockStorage::new();
+    let mut storage = CwStorage(&mut raw);
+    for name in ["a", "b", "c", "d"] {
+        COLUMN.access(&mut storage).push(&name.to_string()).unwrap();
+    }
+
+    let access = COLUMN.access(&storage);
+    let pairs: Vec<_> = access
+        .pairs_cw(
+            Order::Descending,
+            Some(Bound::exclusive(0)),
+            Some(Bound::exclusive(3)),
+        )
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(pairs, [(2, "c".to_string()), (1, "b".to_string())]);
+}
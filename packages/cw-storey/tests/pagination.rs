@@ -0,0 +1,316 @@
+#![cfg(feature = "cosmwasm-std-2")]
+
+use cosmwasm_std::testing::MockStorage;
+use cw_storey::containers::{Item, Map};
+use cw_storey::pagination::{paginate_map, paginate_map_rev, paginate_map_values};
+use cw_storey::CwStorage;
+
+const DEFAULT_LIMIT: u32 = 3;
+const MAX_LIMIT: u32 = 5;
+
+fn seeded(map: &Map<u32, Item<u64>>) -> MockStorage {
+    let mut raw = MockStorage::new();
+    let mut storage = CwStorage(&mut raw);
+    for i in 0..10u32 {
+        map.access(&mut storage)
+            .entry_mut(&i)
+            .set(&u64::from(i * 10))
+            .unwrap();
+    }
+    raw
+}
+
+#[test]
+fn pages_through_everything() {
+    const MAP: Map<u32, Item<u64>> = Map::new(0);
+    let raw = seeded(&MAP);
+    let storage = CwStorage(&raw);
+    let access = MAP.access(&storage);
+
+    let mut start_after = None;
+    let mut seen = vec![];
+    loop {
+        let page = paginate_map(
+            &access,
+            start_after.as_ref(),
+            None,
+            DEFAULT_LIMIT,
+            MAX_LIMIT,
+        )
+        .unwrap();
+        let Some(&(last, _)) = page.last() else {
+            break;
+        };
+        assert!(page.len() <= DEFAULT_LIMIT as usize);
+        seen.extend(page);
+        start_after = Some(last);
+    }
+
+    let expected: Vec<_> = (0..10).map(|i| (i, u64::from(i * 10))).collect();
+    assert_eq!(seen, expected);
+}
+
+#[test]
+fn start_after_is_exclusive() {
+    const MAP: Map<u32, Item<u64>> = Map::new(0);
+    let raw = seeded(&MAP);
+    let storage = CwStorage(&raw);
+    let access = MAP.access(&storage);
+
+    let page = paginate_map(&access, Some(&4), Some(2), DEFAULT_LIMIT, MAX_LIMIT).unwrap();
+    assert_eq!(page, [(5, 50), (6, 60)]);
+
+    // a key that isn't in the map works too
+    let page = paginate_map(&access, Some(&100), None, DEFAULT_LIMIT, MAX_LIMIT).unwrap();
+    assert_eq!(page, []);
+}
+
+#[test]
+fn limit_is_clamped() {
+    const MAP: Map<u32, Item<u64>> = Map::new(0);
+    let raw = seeded(&MAP);
+    let storage = CwStorage(&raw);
+    let access = MAP.access(&storage);
+
+    let page = |limit| paginate_map(&access, None, limit, DEFAULT_LIMIT, MAX_LIMIT).unwrap();
+    assert_eq!(page(None).len(), DEFAULT_LIMIT as usize);
+    assert_eq!(page(Some(1)).len(), 1);
+    assert_eq!(page(Some(0)).len(), 0);
+    assert_eq!(page(Some(u32::MAX)).len(), MAX_LIMIT as usize);
+}
+
+#[test]
+fn values_only() {
+    const MAP: Map<u32, Item<u64>> = Map::new(0);
+    let raw = seeded(&MAP);
+    let storage = CwStorage(&raw);
+    let access = MAP.access(&storage);
+
+    let page = paginate_map_values(&access, Some(&7), None, DEFAULT_LIMIT, MAX_LIMIT).unwrap();
+    assert_eq!(page, [80, 90]);
+}
+
+#[test]
+fn descending() {
+    const MAP: Map<u32, Item<u64>> = Map::new(0);
+    let raw = seeded(&MAP);
+    let storage = CwStorage(&raw);
+    let access = MAP.access(&storage);
+
+    let page = paginate_map_rev(&access, None, None, DEFAULT_LIMIT, MAX_LIMIT).unwrap();
+    assert_eq!(page, [(9, 90), (8, 80), (7, 70)]);
+
+    let page = paginate_map_rev(&access, Some(&7), Some(10), DEFAULT_LIMIT, MAX_LIMIT).unwrap();
+    assert_eq!(page, [(6, 60), (5, 50), (4, 40), (3, 30), (2, 20)]);
+
+    let page = paginate_map_rev(&access, Some(&0), None, DEFAULT_LIMIT, MAX_LIMIT).unwrap();
+    assert_eq!(page, []);
+}
+
+#[test]
+fn string_keys_in_a_shared_prefix() {
+    // entries of other containers, and of nested entries sharing a key prefix, stay out
+    const NAMES: Map<String, Item<u32>> = Map::new(0);
+    const OTHER: Item<u32> = Item::new(1);
+
+    let mut raw = MockStorage::new();
+    let mut storage = CwStorage(&mut raw);
+    for (i, name) in ["a", "ab", "b", "abc"].into_iter().enumerate() {
+        NAMES
+            .access(&mut storage)
+            .entry_mut(name)
+            .set(&(i as u32))
+            .unwrap();
+    }
+    OTHER.access(&mut storage).set(&99).unwrap();
+
+    let access = NAMES.access(&storage);
+    let page = paginate_map(&access, Some(&"a".to_string()), None, 10, 10).unwrap();
+    assert_eq!(
+        page,
+        [
+            ("b".to_string(), 2),
+            ("ab".to_string(), 1),
+            ("abc".to_string(), 3)
+        ]
+    );
+}
+
+// A contract with two list queries over differently-shaped maps.
+mod contract {
+    use cosmwasm_std::{to_json_binary, Addr, Binary, Deps, Order, StdError, StdResult, Uint128};
+    use cw_storey::containers::{Item, Map};
+    use cw_storey::pagination::map_page;
+    use cw_storey::CwStorage;
+    use serde::{Deserialize, Serialize};
+
+    pub const BALANCES: Map<Addr, Item<Uint128>> = Map::new(0);
+    pub const PROPOSALS: Map<u64, Item<Proposal>> = Map::new(1);
+
+    const DEFAULT_LIMIT: u32 = 2;
+    const MAX_LIMIT: u32 = 10;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct Proposal {
+        pub title: String,
+        pub passed: bool,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum QueryMsg {
+        Balances {
+            start_after: Option<Addr>,
+            limit: Option<u32>,
+        },
+        // newest first
+        Proposals {
+            start_after: Option<u64>,
+            limit: Option<u32>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct BalancesResponse {
+        pub balances: Vec<BalanceEntry>,
+        pub next_key: Option<Addr>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct BalanceEntry {
+        pub address: Addr,
+        pub amount: Uint128,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct ProposalsResponse {
+        pub proposals: Vec<(u64, String)>,
+        pub next_key: Option<u64>,
+    }
+
+    pub fn query(deps: Deps, msg: QueryMsg) -> StdResult<Binary> {
+        let storage = CwStorage::from(deps);
+
+        match msg {
+            QueryMsg::Balances { start_after, limit } => {
+                let access = BALANCES.access(&storage);
+                let page = map_page(
+                    &access,
+                    start_after.as_ref(),
+                    limit,
+                    DEFAULT_LIMIT,
+                    MAX_LIMIT,
+                    Order::Ascending,
+                    |address, amount| BalanceEntry { address, amount },
+                )
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+                to_json_binary(&BalancesResponse {
+                    balances: page.entries,
+                    next_key: page.next_key,
+                })
+            }
+            QueryMsg::Proposals { start_after, limit } => {
+                let access = PROPOSALS.access(&storage);
+                let page = map_page(
+                    &access,
+                    start_after.as_ref(),
+                    limit,
+                    DEFAULT_LIMIT,
+                    MAX_LIMIT,
+                    Order::Descending,
+                    |id, proposal| (id, proposal.title),
+                )
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+                to_json_binary(&ProposalsResponse {
+                    proposals: page.entries,
+                    next_key: page.next_key,
+                })
+            }
+        }
+    }
+}
+
+#[test]
+fn list_queries() {
+    use contract::*;
+    use cosmwasm_std::testing::mock_dependencies;
+    use cosmwasm_std::{from_json, Addr, Uint128};
+
+    let mut deps = mock_dependencies();
+    let mut storage = CwStorage(deps.as_mut().storage);
+
+    let addrs: Vec<_> = ["a1", "a2", "a3"]
+        .into_iter()
+        .map(Addr::unchecked)
+        .collect();
+    for (i, addr) in addrs.iter().enumerate() {
+        BALANCES
+            .access(&mut storage)
+            .entry_mut(addr)
+            .set(&Uint128::new(i as u128))
+            .unwrap();
+    }
+    for id in 1..=5 {
+        let proposal = Proposal {
+            title: format!("proposal {id}"),
+            passed: id % 2 == 0,
+        };
+        PROPOSALS
+            .access(&mut storage)
+            .entry_mut(&id)
+            .set(&proposal)
+            .unwrap();
+    }
+
+    let balances = |start_after, limit| -> BalancesResponse {
+        let msg = QueryMsg::Balances { start_after, limit };
+        from_json(query(deps.as_ref(), msg).unwrap()).unwrap()
+    };
+
+    let first = balances(None, None);
+    assert_eq!(
+        first.balances,
+        [
+            BalanceEntry {
+                address: addrs[0].clone(),
+                amount: Uint128::new(0)
+            },
+            BalanceEntry {
+                address: addrs[1].clone(),
+                amount: Uint128::new(1)
+            },
+        ]
+    );
+    assert_eq!(first.next_key, Some(addrs[1].clone()));
+
+    let second = balances(first.next_key, None);
+    assert_eq!(second.balances.len(), 1);
+    assert_eq!(second.balances[0].address, addrs[2]);
+    assert_eq!(second.next_key, None);
+
+    let proposals = |start_after, limit| -> ProposalsResponse {
+        let msg = QueryMsg::Proposals { start_after, limit };
+        from_json(query(deps.as_ref(), msg).unwrap()).unwrap()
+    };
+
+    let first = proposals(None, Some(3));
+    assert_eq!(
+        first.proposals,
+        [
+            (5, "proposal 5".to_string()),
+            (4, "proposal 4".to_string()),
+            (3, "proposal 3".to_string()),
+        ]
+    );
+    assert_eq!(first.next_key, Some(3));
+
+    // the last page is exactly full, and there's nothing after it
+    let second = proposals(first.next_key, Some(2));
+    assert_eq!(second.proposals.len(), 2);
+    assert_eq!(second.next_key, None);
+
+    // limits over the maximum are clamped
+    assert_eq!(proposals(None, Some(1000)).proposals.len(), 5);
+}
@@ -1,3 +1,6 @@
+#[cfg(all(feature = "cosmwasm-std-1", not(feature = "cosmwasm-std-2")))]
+extern crate cosmwasm_std_v1 as cosmwasm_std;
+
 use cw_storey::{containers::Item, CwStorage};
 
 use storey::containers::{IterableAccessor as _, Map};
@@ -47,3 +50,27 @@ fn iteration() {
     assert_eq!(iter.next().unwrap().unwrap().0, "foo");
     assert!(iter.next().is_none());
 }
+
+#[test]
+fn encoding_override() {
+    use cosmwasm_std::Storage as _;
+    use storey::encoding::FixedIntEncoding;
+
+    let mut raw_storage = cosmwasm_std::testing::MockStorage::new();
+    let mut storage = CwStorage(&mut raw_storage);
+
+    let default = Item::<u64>::new(0);
+    let overridden = Item::<u64, FixedIntEncoding>::new(1);
+    let map = Map::<String, Item<u32, FixedIntEncoding>>::new(2);
+
+    default.access(&mut storage).set(&42).unwrap();
+    overridden.access(&mut storage).set(&42).unwrap();
+    map.access(&mut storage).entry_mut("foo").set(&42).unwrap();
+
+    assert_eq!(storage.0.get(&[0]), Some(vec![42]));
+    assert_eq!(storage.0.get(&[1]), Some(42u64.to_le_bytes().to_vec()));
+    assert_eq!(
+        storage.0.get(&[2, 3, b'f', b'o', b'o']),
+        Some(42u32.to_le_bytes().to_vec())
+    );
+}
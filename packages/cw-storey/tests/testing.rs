@@ -0,0 +1,165 @@
+// The unit-test helpers, and what their failures look like.
+
+#![cfg(feature = "cosmwasm-std-2")]
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use cosmwasm_std::testing::{mock_dependencies, MockStorage};
+use cosmwasm_std::{StdError, Storage as _};
+use cw_storey::containers::{Item, Map};
+use cw_storey::testing::{assert_item_eq, assert_map_contains, seed};
+
+const OWNER: Item<String> = Item::new(0);
+const BALANCES: Map<String, Item<u64>> = Map::new(1);
+
+fn seeded() -> MockStorage {
+    let mut storage = MockStorage::new();
+    seed(&mut storage, |s| {
+        OWNER.access(&mut *s).set(&"alice".to_string())?;
+        BALANCES.access(&mut *s).entry_mut("alice").set(&100)?;
+        BALANCES.access(&mut *s).entry_mut("bob").set(&7)?;
+        Ok(())
+    });
+    storage
+}
+
+/// The message `f` panics with.
+fn panic_message(f: impl FnOnce()) -> String {
+    let payload = catch_unwind(AssertUnwindSafe(f)).expect_err("expected a panic");
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().unwrap().to_string(),
+    }
+}
+
+#[test]
+fn matching_state() {
+    let storage = seeded();
+
+    assert_item_eq(&storage, &OWNER, &"alice".to_string());
+    assert_map_contains(&storage, &BALANCES, &[("bob".to_string(), 7)]);
+    assert_map_contains(
+        &storage,
+        &BALANCES,
+        &[("alice".to_string(), 100), ("bob".to_string(), 7)],
+    );
+    assert_map_contains(&storage, &BALANCES, &[]);
+}
+
+#[test]
+fn with_mock_dependencies() {
+    let mut deps = mock_dependencies();
+    seed(deps.as_mut().storage, |s| {
+        OWNER.access(s).set(&"bob".to_string())?;
+        Ok(())
+    });
+
+    assert_item_eq(deps.as_ref().storage, &OWNER, &"bob".to_string());
+}
+
+#[test]
+fn failed_seeding() {
+    let message = panic_message(|| {
+        seed(&mut MockStorage::new(), |_| {
+            Err(StdError::generic_err("no funds"))
+        })
+    });
+    assert!(message.starts_with("seeding the storage failed: "));
+    assert!(message.contains("no funds"));
+}
+
+#[test]
+fn item_mismatch() {
+    let storage = seeded();
+
+    let message = panic_message(|| assert_item_eq(&storage, &OWNER, &"bob".to_string()));
+    assert_eq!(
+        message,
+        r#"item at raw key 0x00: expected "bob", found "alice""#
+    );
+
+    let message = panic_message(|| {
+        assert_item_eq(&MockStorage::new(), &OWNER, &"bob".to_string());
+    });
+    assert_eq!(
+        message,
+        r#"item at raw key 0x00: expected "bob", found nothing"#
+    );
+}
+
+#[test]
+fn undecodable_item() {
+    let mut storage = MockStorage::new();
+    storage.set(&[0], &[0xc1]);
+
+    let message = panic_message(|| assert_item_eq(&storage, &OWNER, &"bob".to_string()));
+    assert!(
+        message.starts_with(
+            r#"item at raw key 0x00: expected "bob", found a value that can't be decoded ("#
+        ),
+        "{message}"
+    );
+    assert!(message.ends_with("): 0xc1"), "{message}");
+}
+
+#[test]
+fn map_mismatches() {
+    let storage = seeded();
+
+    // every wrong entry is listed, and the right ones aren't
+    let message = panic_message(|| {
+        assert_map_contains(
+            &storage,
+            &BALANCES,
+            &[
+                ("alice".to_string(), 99),
+                ("bob".to_string(), 7),
+                ("carol".to_string(), 1),
+            ],
+        )
+    });
+    assert_eq!(
+        message,
+        [
+            "map with prefix 0x01 is missing expected entries:",
+            r#"  "alice" (raw key 0x0105616c696365): expected 99, found 100"#,
+            r#"  "carol" (raw key 0x01056361726f6c): expected 1, found nothing"#,
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn named_prefixes() {
+    const CONFIG: Item<String> = Item::new_str("config");
+    const VOTES: Map<String, Item<u64>> = Map::new_str("votes");
+
+    let mut storage = MockStorage::new();
+    seed(&mut storage, |s| {
+        CONFIG.access(&mut *s).set(&"open".to_string())?;
+        VOTES.access(&mut *s).entry_mut("alice").set(&3)?;
+        Ok(())
+    });
+
+    assert_item_eq(&storage, &CONFIG, &"open".to_string());
+    assert_map_contains(&storage, &VOTES, &[("alice".to_string(), 3)]);
+
+    // the raw keys start with the whole prefix
+    let message = panic_message(|| assert_item_eq(&storage, &CONFIG, &"closed".to_string()));
+    assert_eq!(
+        message,
+        r#"item at raw key 0x636f6e666967: expected "closed", found "open""#
+    );
+
+    let message = panic_message(|| {
+        assert_map_contains(&storage, &VOTES, &[("alice".to_string(), 4)]);
+    });
+    assert_eq!(
+        message,
+        [
+            "map with prefix 0x766f746573 is missing expected entries:",
+            r#"  "alice" (raw key 0x766f74657305616c696365): expected 4, found 3"#,
+        ]
+        .join("\n")
+    );
+}
@@ -0,0 +1,102 @@
+// The bytes `CwEncoding` stores for common *CosmWasm* types.
+//
+// These pin the state layout: if an upgrade of `cosmwasm-std` or of the serializer changes
+// how any of these types is encoded, contracts would no longer be able to read their existing
+// state, so these bytes must never change.
+
+#[cfg(all(feature = "cosmwasm-std-1", not(feature = "cosmwasm-std-2")))]
+extern crate cosmwasm_std_v1 as cosmwasm_std;
+
+use std::fmt::Debug;
+
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Timestamp, Uint128};
+use cw_storey::CwEncoding;
+use storey::encoding::{DecodableWith, EncodableWith};
+
+/// Check that `value` encodes to exactly `bytes`, and that `bytes` decode back to `value`.
+fn check<T>(value: T, bytes: &[u8])
+where
+    T: EncodableWith<CwEncoding> + DecodableWith<CwEncoding> + PartialEq + Debug,
+{
+    assert_eq!(value.encode().unwrap(), bytes, "encoding {value:?}");
+    assert_eq!(T::decode(bytes).unwrap(), value);
+}
+
+#[test]
+#[cfg(feature = "cosmwasm-std-2")]
+fn binary() {
+    // raw bytes - MessagePack isn't human-readable, so no base64
+    check(Binary::from(vec![1, 2, 3]), &[0xc4, 3, 1, 2, 3]);
+    check(Binary::default(), &[0xc4, 0]);
+}
+
+#[test]
+#[cfg(not(feature = "cosmwasm-std-2"))]
+fn binary() {
+    // 1.x always encodes it as a base64 string
+    check(Binary::from(vec![1, 2, 3]), b"\xa4AQID");
+    check(Binary::default(), &[0xa0]);
+}
+
+#[test]
+fn uint128() {
+    // decimal, as a string
+    check(Uint128::new(100), &[0xa3, b'1', b'0', b'0']);
+    check(Uint128::zero(), &[0xa1, b'0']);
+
+    // too long for the short string format
+    let mut max = vec![0xd9, 39];
+    max.extend_from_slice(u128::MAX.to_string().as_bytes());
+    check(Uint128::MAX, &max);
+}
+
+#[test]
+fn decimal() {
+    check(Decimal::percent(150), &[0xa3, b'1', b'.', b'5']);
+    check(Decimal::one(), &[0xa1, b'1']);
+    check(Decimal::zero(), &[0xa1, b'0']);
+}
+
+#[test]
+fn addr() {
+    let mut bytes = vec![0xac];
+    bytes.extend_from_slice(b"cosmwasm1xyz");
+    check(Addr::unchecked("cosmwasm1xyz"), &bytes);
+}
+
+#[test]
+fn timestamp() {
+    // nanoseconds, in decimal, as a string
+    let mut bytes = vec![0xaa];
+    bytes.extend_from_slice(b"1000000000");
+    check(Timestamp::from_seconds(1), &bytes);
+}
+
+#[test]
+fn coin() {
+    // a map with the field names as keys
+    let mut bytes = vec![0x82];
+    bytes.extend_from_slice(&[0xa5, b'd', b'e', b'n', b'o', b'm']);
+    bytes.extend_from_slice(&[0xa5, b'u', b'a', b't', b'o', b'm']);
+    bytes.extend_from_slice(&[0xa6, b'a', b'm', b'o', b'u', b'n', b't']);
+    bytes.extend_from_slice(&[0xa3, b'1', b'0', b'0']);
+
+    check(Coin::new(100u128, "uatom"), &bytes);
+}
+
+#[test]
+fn coins() {
+    let coin = |denom: &str, amount: u128| Coin::new(amount, denom);
+
+    // the encoding is named, since the serde encodings of `storey` can encode these types too
+    let encoded = EncodableWith::<CwEncoding>::encode(&vec![coin("a", 1), coin("b", 2)]).unwrap();
+
+    // an array of two coin maps
+    assert_eq!(encoded[0], 0x92);
+    let single = EncodableWith::<CwEncoding>::encode(&coin("a", 1)).unwrap();
+    assert_eq!(encoded[1..].len(), 2 * single.len());
+    assert_eq!(
+        <Vec<Coin> as DecodableWith<CwEncoding>>::decode(&encoded).unwrap(),
+        [coin("a", 1), coin("b", 2)]
+    );
+}
@@ -0,0 +1,90 @@
+//! Encode/decode timings for every in-tree encoding, over the shapes defined in the library.
+//!
+//! Output sizes don't need a benchmark run: `cargo run -p storey-bench --bin sizes`.
+
+use std::hint::black_box;
+
+use criterion::measurement::WallTime;
+use criterion::{criterion_group, criterion_main, BenchmarkGroup, Criterion};
+use serde::{de::DeserializeOwned, Serialize};
+use storey::encoding::{
+    BincodeEncoding, CanonicalCborEncoding, CborEncoding, DecodableWith, EncodableWith, Encoding,
+    FixedIntEncoding, JsonEncoding, MsgPackEncoding, PostcardEncoding, ProstEncoding,
+};
+use storey_bench::{event_log, proto, Profile, Registry, SMALL_INT};
+
+/// The benchmarks for one shape, named `<shape>/encode/<encoding>` and
+/// `<shape>/decode/<encoding>`.
+struct Shape<'c>(BenchmarkGroup<'c, WallTime>);
+
+impl<'c> Shape<'c> {
+    fn new(c: &'c mut Criterion, shape: &str) -> Self {
+        Self(c.benchmark_group(shape))
+    }
+
+    fn bench<E, T>(&mut self, name: &str, value: &T)
+    where
+        E: Encoding,
+        T: EncodableWith<E> + DecodableWith<E>,
+    {
+        let bytes = value.encode().unwrap();
+
+        self.0.bench_function(format!("encode/{name}"), |b| {
+            b.iter(|| black_box(value).encode().unwrap())
+        });
+        self.0.bench_function(format!("decode/{name}"), |b| {
+            b.iter(|| T::decode(black_box(&bytes)).unwrap())
+        });
+    }
+
+    fn bench_serde<T: Serialize + DeserializeOwned>(&mut self, value: &T) {
+        self.bench::<BincodeEncoding, T>("bincode", value);
+        self.bench::<CborEncoding, T>("cbor", value);
+        self.bench::<CanonicalCborEncoding, T>("canonical cbor", value);
+        self.bench::<JsonEncoding, T>("json", value);
+        self.bench::<MsgPackEncoding, T>("msgpack", value);
+        self.bench::<PostcardEncoding, T>("postcard", value);
+    }
+
+    fn finish(self) {
+        self.0.finish();
+    }
+}
+
+fn small_int(c: &mut Criterion) {
+    let mut shape = Shape::new(c, "small_int");
+    shape.bench_serde(&SMALL_INT);
+    shape.bench::<ProstEncoding, _>("prost", &proto::SmallInt::from(SMALL_INT));
+    shape.bench::<FixedIntEncoding, _>("fixed int", &SMALL_INT);
+    shape.finish();
+}
+
+fn profile(c: &mut Criterion) {
+    let profile = Profile::sample(7);
+
+    let mut shape = Shape::new(c, "profile");
+    shape.bench_serde(&profile);
+    shape.bench::<ProstEncoding, _>("prost", &proto::Profile::from(&profile));
+    shape.finish();
+}
+
+fn registry(c: &mut Criterion) {
+    let registry = Registry::sample();
+
+    let mut shape = Shape::new(c, "registry");
+    shape.bench_serde(&registry);
+    shape.bench::<ProstEncoding, _>("prost", &proto::Registry::from(&registry));
+    shape.finish();
+}
+
+fn events(c: &mut Criterion) {
+    let events = event_log();
+
+    let mut shape = Shape::new(c, "events");
+    shape.bench_serde(&events);
+    shape.bench::<ProstEncoding, _>("prost", &proto::EventLog::from(&events[..]));
+    shape.finish();
+}
+
+criterion_group!(benches, small_int, profile, registry, events);
+criterion_main!(benches);
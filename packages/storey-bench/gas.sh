@@ -0,0 +1,31 @@
+#!/bin/sh
+# Builds the contract in `gas/` once per encoding, runs every build through the workloads in a
+# CosmWasm VM, and writes the gas they took to `gas-report.json`.
+#
+# Requires a nightly toolchain with the rust-src component, `rustup toolchain add nightly
+# --component rust-src`: the standard library is rebuilt along with the contract, and
+# cosmwasm-vm 2.3 needs rustc 1.96.
+set -e
+
+cd "$(dirname "$0")/gas"
+
+wasm=target/wasm32-unknown-unknown/release/storey_gas_contract.wasm
+mkdir -p artifacts
+
+for encoding in bincode cbor canonical-cbor json msgpack postcard; do
+    # recent compilers emit wasm features the VM doesn't accept by default, and so does the
+    # prebuilt standard library, hence rebuilding it; the VM provides the `db_*` imports
+    RUSTFLAGS="-C target-cpu=mvp -C link-arg=--allow-undefined" cargo +nightly build --quiet \
+        --release --target wasm32-unknown-unknown -p storey-gas-contract --features "$encoding" \
+        -Z build-std=std,panic_abort
+
+    # shrink it the way contracts are usually shrunk before being uploaded, if possible
+    if command -v wasm-opt > /dev/null; then
+        wasm-opt -Os --signext-lowering "$wasm" -o "artifacts/$encoding.wasm"
+    else
+        cp "$wasm" "artifacts/$encoding.wasm"
+    fi
+done
+
+cargo +nightly run --quiet --release -p storey-gas-harness -- artifacts/*.wasm > ../gas-report.json
+echo "gas report written to packages/storey-bench/gas-report.json"
@@ -0,0 +1,136 @@
+use cosmwasm_std::{
+    entry_point, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult, Storage,
+};
+use cw_storey::containers::{Column, Item, Map};
+use cw_storey::CwStorage;
+use storey::containers::IterableAccessor as _;
+use storey::encoding::{DecodableWith, EncodableWith};
+
+use crate::{Container, ExecuteMsg, Op, Profile, Shape, Workload};
+
+#[cfg(feature = "bincode")]
+type E = storey::encoding::BincodeEncoding;
+#[cfg(feature = "cbor")]
+type E = storey::encoding::CborEncoding;
+#[cfg(feature = "canonical-cbor")]
+type E = storey::encoding::CanonicalCborEncoding;
+#[cfg(feature = "json")]
+type E = storey::encoding::JsonEncoding;
+#[cfg(feature = "msgpack")]
+type E = storey::encoding::MsgPackEncoding;
+#[cfg(feature = "postcard")]
+type E = storey::encoding::PostcardEncoding;
+
+#[entry_point]
+pub fn instantiate(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+#[entry_point]
+pub fn execute(deps: DepsMut, _: Env, _: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    let ExecuteMsg::Run(workload) = msg;
+    let mut storage = CwStorage(deps.storage);
+
+    // every shape gets containers of its own
+    let touched = match workload.shape {
+        Shape::SmallInt => run(&mut storage, 0, workload, |i| u64::from(i) * 1_000_000)?,
+        Shape::Profile => run(&mut storage, 3, workload, Profile::sample)?,
+    };
+
+    Ok(Response::new().add_attribute("touched", touched.to_string()))
+}
+
+/// Run `workload` on the containers from `prefix` on, and return how many values it touched.
+fn run<T>(
+    storage: &mut CwStorage<&mut dyn Storage>,
+    prefix: u8,
+    workload: Workload,
+    sample: impl Fn(u32) -> T,
+) -> StdResult<u32>
+where
+    T: EncodableWith<E> + DecodableWith<E>,
+{
+    let item = Item::<T, E>::new(prefix);
+    let map = Map::<u32, Item<T, E>>::new(prefix + 1);
+    let column = Column::<T, E>::new(prefix + 2);
+
+    let count = workload.count;
+    let mut touched = 0;
+
+    match (workload.container, workload.op) {
+        (Container::Item, Op::Write) => {
+            for i in 0..count {
+                item.access(&mut *storage)
+                    .set(&sample(i))
+                    .map_err(std_err)?;
+                touched += 1;
+            }
+        }
+        (Container::Item, Op::Read) => {
+            for _ in 0..count {
+                if item.access(&*storage).get().map_err(std_err)?.is_some() {
+                    touched += 1;
+                }
+            }
+        }
+        (Container::Map, Op::Write) => {
+            for i in 0..count {
+                map.access(&mut *storage)
+                    .entry_mut(&i)
+                    .set(&sample(i))
+                    .map_err(std_err)?;
+                touched += 1;
+            }
+        }
+        (Container::Map, Op::Read) => {
+            for i in 0..count {
+                if map
+                    .access(&*storage)
+                    .entry(&i)
+                    .get()
+                    .map_err(std_err)?
+                    .is_some()
+                {
+                    touched += 1;
+                }
+            }
+        }
+        (Container::Map, Op::Iterate) => {
+            for value in map.access(&*storage).values().take(count as usize) {
+                value.map_err(std_err)?;
+                touched += 1;
+            }
+        }
+        (Container::Column, Op::Write) => {
+            for i in 0..count {
+                column
+                    .access(&mut *storage)
+                    .push(&sample(i))
+                    .map_err(std_err)?;
+                touched += 1;
+            }
+        }
+        (Container::Column, Op::Read) => {
+            for i in 0..count {
+                if column.access(&*storage).get(i).map_err(std_err)?.is_some() {
+                    touched += 1;
+                }
+            }
+        }
+        (Container::Column, Op::Iterate) => {
+            for value in column.access(&*storage).values().take(count as usize) {
+                value.map_err(std_err)?;
+                touched += 1;
+            }
+        }
+        (Container::Item, Op::Iterate) => {
+            return Err(StdError::generic_err("items can't be iterated over"));
+        }
+    }
+
+    Ok(touched)
+}
+
+fn std_err(e: impl std::fmt::Display) -> StdError {
+    StdError::generic_err(e.to_string())
+}
@@ -0,0 +1,103 @@
+//! A contract running storage workloads, with the encoding of its containers picked by
+//! feature.
+//!
+//! Every execute message is one [`Workload`]: `count` operations of one kind on one container,
+//! with values of one shape. The harness measures the gas every message takes.
+//!
+//! The message types are always available, so that the harness can use them. The entry points
+//! need exactly one encoding feature.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    Run(Workload),
+}
+
+/// `count` operations of the kind `op`, on a `container` holding values of the given `shape`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Workload {
+    pub shape: Shape,
+    pub container: Container,
+    pub op: Op,
+    pub count: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Shape {
+    /// A `u64`, like a counter or a config value.
+    SmallInt,
+    /// A [`Profile`].
+    Profile,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Container {
+    Item,
+    /// A `Map<u32, Item<_>>`.
+    Map,
+    Column,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Op {
+    /// Write `count` values: the same item over and over, or `count` entries of a map or
+    /// column.
+    Write,
+    /// Read the values written with [`Op::Write`] one by one.
+    Read,
+    /// Iterate over the first `count` values. Items can't be iterated over.
+    Iterate,
+}
+
+/// A struct with a few strings and vectors, the same as the `profile` shape of the native
+/// benchmarks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub bio: String,
+    pub tags: Vec<String>,
+    pub scores: Vec<u32>,
+    pub active: bool,
+}
+
+impl Profile {
+    pub fn sample(seed: u32) -> Self {
+        Self {
+            name: format!("user-{seed}"),
+            bio: format!("Profile #{seed}, stored to see how encodings deal with text."),
+            tags: (0..4).map(|i| format!("tag-{}", (seed + i) % 7)).collect(),
+            scores: (0..16)
+                .map(|i| seed.wrapping_mul(2654435761) >> i)
+                .collect(),
+            active: !seed.is_multiple_of(3),
+        }
+    }
+}
+
+#[cfg(any(
+    feature = "bincode",
+    feature = "cbor",
+    feature = "canonical-cbor",
+    feature = "json",
+    feature = "msgpack",
+    feature = "postcard",
+))]
+pub mod contract;
+
+#[cfg(all(
+    target_arch = "wasm32",
+    not(any(
+        feature = "bincode",
+        feature = "cbor",
+        feature = "canonical-cbor",
+        feature = "json",
+        feature = "msgpack",
+        feature = "postcard",
+    ))
+))]
+compile_error!("the contract needs an encoding feature, e.g. `--features msgpack`");
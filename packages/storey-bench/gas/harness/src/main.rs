@@ -0,0 +1,123 @@
+//! Measures the gas the benchmark contract takes for every workload, once per encoding, and
+//! prints the results as JSON.
+//!
+//! The arguments are builds of the contract, each named after its encoding, e.g.
+//! `artifacts/msgpack.wasm`. Every build runs in a fresh instance, through the workloads in
+//! order, so that the reads find what the writes stored. See `gas.sh`.
+
+use std::error::Error;
+use std::path::Path;
+
+use cosmwasm_std::testing::{message_info, mock_env};
+use cosmwasm_std::{Addr, Empty};
+use cosmwasm_vm::testing::{mock_instance_with_gas_limit, MockApi, MockQuerier, MockStorage};
+use cosmwasm_vm::{call_execute, call_instantiate, Instance};
+use serde::Serialize;
+use storey_gas_contract::{Container, ExecuteMsg, Op, Shape, Workload};
+
+/// High enough for any workload, so that the limit never gets in the way.
+const GAS_LIMIT: u64 = 1_000_000_000_000_000;
+
+/// How many values every workload touches.
+const COUNT: u32 = 100;
+
+/// The workloads, in the order they run in.
+fn workloads() -> Vec<Workload> {
+    let containers: [(Container, &[Op]); 3] = [
+        (Container::Item, &[Op::Write, Op::Read]),
+        (Container::Map, &[Op::Write, Op::Read, Op::Iterate]),
+        (Container::Column, &[Op::Write, Op::Read, Op::Iterate]),
+    ];
+
+    let mut workloads = Vec::new();
+    for shape in [Shape::SmallInt, Shape::Profile] {
+        for (container, ops) in containers {
+            for &op in ops {
+                workloads.push(Workload {
+                    shape,
+                    container,
+                    op,
+                    count: COUNT,
+                });
+            }
+        }
+    }
+    workloads
+}
+
+#[derive(Serialize)]
+struct Report {
+    results: Vec<Measurement>,
+}
+
+/// The gas a workload took, in *CosmWasm* gas units (not *Cosmos SDK* gas).
+#[derive(Serialize)]
+struct Measurement {
+    encoding: String,
+    #[serde(flatten)]
+    workload: Workload,
+    /// All the gas the workload took.
+    gas: u64,
+    /// The gas of running the contract code, which is where encodings differ the most.
+    gas_wasm: u64,
+    /// The gas charged by the backend, e.g. for storage access.
+    gas_backend: u64,
+    gas_per_op: u64,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let builds: Vec<String> = std::env::args().skip(1).collect();
+    if builds.is_empty() {
+        return Err("usage: storey-gas-harness <encoding>.wasm...".into());
+    }
+
+    let mut results = Vec::new();
+    for build in builds {
+        let encoding = Path::new(&build)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| format!("can't tell the encoding of `{build}`"))?
+            .to_string();
+        let wasm = std::fs::read(&build)?;
+
+        let mut instance = mock_instance_with_gas_limit(&wasm, GAS_LIMIT);
+        let info = message_info(&Addr::unchecked("bench"), &[]);
+        call_instantiate::<_, _, _, Empty>(&mut instance, &mock_env(), &info, b"{}")?
+            .into_result()?;
+
+        for workload in workloads() {
+            let (gas_wasm, gas_backend) = measure(&mut instance, workload)?;
+            results.push(Measurement {
+                encoding: encoding.clone(),
+                workload,
+                gas: gas_wasm + gas_backend,
+                gas_wasm,
+                gas_backend,
+                gas_per_op: (gas_wasm + gas_backend) / u64::from(workload.count),
+            });
+        }
+    }
+
+    println!("{}", serde_json::to_string_pretty(&Report { results })?);
+    Ok(())
+}
+
+/// Run `workload`, and return the gas used by the contract code and by the backend.
+fn measure(
+    instance: &mut Instance<MockApi, MockStorage, MockQuerier>,
+    workload: Workload,
+) -> Result<(u64, u64), Box<dyn Error>> {
+    let msg = serde_json::to_vec(&ExecuteMsg::Run(workload))?;
+    let info = message_info(&Addr::unchecked("bench"), &[]);
+
+    let before = instance.create_gas_report();
+    call_execute::<_, _, _, Empty>(instance, &mock_env(), &info, &msg)?
+        .into_result()
+        .map_err(|e| format!("{workload:?} failed: {e}"))?;
+    let after = instance.create_gas_report();
+
+    Ok((
+        after.used_internally - before.used_internally,
+        after.used_externally - before.used_externally,
+    ))
+}
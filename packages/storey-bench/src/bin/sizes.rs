@@ -0,0 +1,58 @@
+//! Prints the encoded size of every benchmark shape with every in-tree encoding, as a markdown
+//! table.
+
+use storey::encoding::FixedIntEncoding;
+use storey_bench::{
+    encoded_size, event_log, prost_size, proto, serde_sizes, Profile, Registry, SERDE_ENCODINGS,
+    SMALL_INT,
+};
+
+fn main() {
+    let profile = Profile::sample(7);
+    let registry = Registry::sample();
+    let events = event_log();
+
+    let rows = [
+        (
+            "small int",
+            serde_sizes(&SMALL_INT),
+            prost_size(&proto::SmallInt::from(SMALL_INT)),
+        ),
+        (
+            "profile",
+            serde_sizes(&profile),
+            prost_size(&proto::Profile::from(&profile)),
+        ),
+        (
+            "registry",
+            serde_sizes(&registry),
+            prost_size(&proto::Registry::from(&registry)),
+        ),
+        (
+            "events",
+            serde_sizes(&events),
+            prost_size(&proto::EventLog::from(&events[..])),
+        ),
+    ];
+
+    print!("| shape |");
+    for name in SERDE_ENCODINGS {
+        print!(" {name} |");
+    }
+    println!(" prost |");
+    println!("{}", "|---".repeat(SERDE_ENCODINGS.len() + 2) + "|");
+
+    for (shape, sizes, prost) in rows {
+        print!("| {shape} |");
+        for size in sizes {
+            print!(" {size} |");
+        }
+        println!(" {prost} |");
+    }
+
+    println!();
+    println!(
+        "small int with fixed int encoding: {} bytes",
+        encoded_size::<FixedIntEncoding, u64>(&SMALL_INT)
+    );
+}
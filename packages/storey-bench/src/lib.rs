@@ -0,0 +1,154 @@
+//! Value shapes and helpers shared by the encoding benchmarks.
+//!
+//! Every shape is a plain `serde` type, which is what contracts usually store. [`proto`] mirrors
+//! them as `prost` messages, since Protocol Buffers need types of their own.
+//!
+//! See the README for how to run the benchmarks and the latest results.
+
+use std::collections::BTreeMap;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use storey::encoding::{
+    BincodeEncoding, CanonicalCborEncoding, CborEncoding, DecodableWith, EncodableWith, Encoding,
+    JsonEncoding, MsgPackEncoding, PostcardEncoding, ProstEncoding,
+};
+
+pub mod proto;
+
+/// A small integer, e.g. a counter or a config flag.
+pub const SMALL_INT: u64 = 1_000_000;
+
+/// A mid-size struct with strings and vectors, e.g. a user profile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub bio: String,
+    pub tags: Vec<String>,
+    pub scores: Vec<u32>,
+    pub active: bool,
+}
+
+impl Profile {
+    pub fn sample(seed: u32) -> Self {
+        Self {
+            name: format!("user-{seed}"),
+            bio: format!("Profile #{seed}, stored to see how encodings deal with text."),
+            tags: (0..4).map(|i| format!("tag-{}", (seed + i) % 7)).collect(),
+            scores: (0..16)
+                .map(|i| seed.wrapping_mul(2654435761) >> i)
+                .collect(),
+            active: !seed.is_multiple_of(3),
+        }
+    }
+}
+
+/// A large nested struct, e.g. the whole state of a small registry contract.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Registry {
+    pub owner: String,
+    pub profiles: Vec<Profile>,
+    pub metadata: BTreeMap<String, String>,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub height: u64,
+    pub hash: Vec<u8>,
+    pub signers: Vec<String>,
+}
+
+impl Registry {
+    pub fn sample() -> Self {
+        Self {
+            owner: "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu".to_string(),
+            profiles: (0..50).map(Profile::sample).collect(),
+            metadata: (0..20)
+                .map(|i| (format!("key-{i:02}"), format!("value number {i}")))
+                .collect(),
+            checkpoints: (0..20)
+                .map(|i| Checkpoint {
+                    height: 1_000_000 + i * 100,
+                    hash: (0..32).map(|b| (b as u64 * 31 + i) as u8).collect(),
+                    signers: (0..3)
+                        .map(|s| format!("validator-{}", (i + s) % 10))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// An enum-heavy value, e.g. a log of events.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Event {
+    Transfer {
+        from: String,
+        to: String,
+        amount: u64,
+    },
+    Mint {
+        to: String,
+        amount: u64,
+    },
+    Burn(u64),
+    Pause,
+    Unpause,
+}
+
+pub fn event_log() -> Vec<Event> {
+    (0..100u64)
+        .map(|i| match i % 5 {
+            0 => Event::Transfer {
+                from: format!("addr-{}", i % 13),
+                to: format!("addr-{}", (i + 1) % 13),
+                amount: i * 1_000,
+            },
+            1 => Event::Mint {
+                to: format!("addr-{}", i % 13),
+                amount: i * 10,
+            },
+            2 => Event::Burn(i),
+            3 => Event::Pause,
+            _ => Event::Unpause,
+        })
+        .collect()
+}
+
+/// The names of the `serde`-based encodings, in the order [`serde_sizes`] reports them.
+pub const SERDE_ENCODINGS: [&str; 6] = [
+    "bincode",
+    "cbor",
+    "canonical cbor",
+    "json",
+    "msgpack",
+    "postcard",
+];
+
+/// The encoded size of `value` with each of the [`SERDE_ENCODINGS`].
+pub fn serde_sizes<T: Serialize + DeserializeOwned>(value: &T) -> [usize; 6] {
+    [
+        encoded_size::<BincodeEncoding, T>(value),
+        encoded_size::<CborEncoding, T>(value),
+        encoded_size::<CanonicalCborEncoding, T>(value),
+        encoded_size::<JsonEncoding, T>(value),
+        encoded_size::<MsgPackEncoding, T>(value),
+        encoded_size::<PostcardEncoding, T>(value),
+    ]
+}
+
+/// The encoded size of the `prost` mirror of a value.
+pub fn prost_size<P: prost::Message + Default>(value: &P) -> usize {
+    encoded_size::<ProstEncoding, P>(value)
+}
+
+/// Encode `value` with `E`, check it decodes back, and return the encoded size.
+pub fn encoded_size<E, T>(value: &T) -> usize
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+{
+    let bytes = value.encode().unwrap();
+    T::decode(&bytes).unwrap();
+    bytes.len()
+}
@@ -0,0 +1,162 @@
+//! `prost` mirrors of the benchmark shapes.
+//!
+//! The field layout follows the `serde` types as closely as Protocol Buffers allow. A lone
+//! integer can't be a message, so [`SmallInt`] wraps it in a single field.
+
+use prost::{Message, Oneof};
+
+#[derive(Clone, PartialEq, Message)]
+pub struct SmallInt {
+    #[prost(uint64, tag = "1")]
+    pub value: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Profile {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub bio: String,
+    #[prost(string, repeated, tag = "3")]
+    pub tags: Vec<String>,
+    #[prost(uint32, repeated, tag = "4")]
+    pub scores: Vec<u32>,
+    #[prost(bool, tag = "5")]
+    pub active: bool,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Registry {
+    #[prost(string, tag = "1")]
+    pub owner: String,
+    #[prost(message, repeated, tag = "2")]
+    pub profiles: Vec<Profile>,
+    #[prost(btree_map = "string, string", tag = "3")]
+    pub metadata: std::collections::BTreeMap<String, String>,
+    #[prost(message, repeated, tag = "4")]
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Checkpoint {
+    #[prost(uint64, tag = "1")]
+    pub height: u64,
+    #[prost(bytes = "vec", tag = "2")]
+    pub hash: Vec<u8>,
+    #[prost(string, repeated, tag = "3")]
+    pub signers: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct EventLog {
+    #[prost(message, repeated, tag = "1")]
+    pub events: Vec<Event>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Event {
+    #[prost(oneof = "EventKind", tags = "1, 2, 3, 4, 5")]
+    pub kind: Option<EventKind>,
+}
+
+#[derive(Clone, PartialEq, Oneof)]
+pub enum EventKind {
+    #[prost(message, tag = "1")]
+    Transfer(Transfer),
+    #[prost(message, tag = "2")]
+    Mint(Mint),
+    #[prost(uint64, tag = "3")]
+    Burn(u64),
+    #[prost(message, tag = "4")]
+    Pause(Unit),
+    #[prost(message, tag = "5")]
+    Unpause(Unit),
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Transfer {
+    #[prost(string, tag = "1")]
+    pub from: String,
+    #[prost(string, tag = "2")]
+    pub to: String,
+    #[prost(uint64, tag = "3")]
+    pub amount: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Mint {
+    #[prost(string, tag = "1")]
+    pub to: String,
+    #[prost(uint64, tag = "2")]
+    pub amount: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Unit {}
+
+impl From<u64> for SmallInt {
+    fn from(value: u64) -> Self {
+        Self { value }
+    }
+}
+
+impl From<&super::Profile> for Profile {
+    fn from(p: &super::Profile) -> Self {
+        Self {
+            name: p.name.clone(),
+            bio: p.bio.clone(),
+            tags: p.tags.clone(),
+            scores: p.scores.clone(),
+            active: p.active,
+        }
+    }
+}
+
+impl From<&super::Registry> for Registry {
+    fn from(r: &super::Registry) -> Self {
+        Self {
+            owner: r.owner.clone(),
+            profiles: r.profiles.iter().map(Into::into).collect(),
+            metadata: r.metadata.clone(),
+            checkpoints: r
+                .checkpoints
+                .iter()
+                .map(|c| Checkpoint {
+                    height: c.height,
+                    hash: c.hash.clone(),
+                    signers: c.signers.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&[super::Event]> for EventLog {
+    fn from(events: &[super::Event]) -> Self {
+        use super::Event as E;
+
+        let events = events
+            .iter()
+            .map(|event| {
+                let kind = match event {
+                    E::Transfer { from, to, amount } => EventKind::Transfer(Transfer {
+                        from: from.clone(),
+                        to: to.clone(),
+                        amount: *amount,
+                    }),
+                    E::Mint { to, amount } => EventKind::Mint(Mint {
+                        to: to.clone(),
+                        amount: *amount,
+                    }),
+                    E::Burn(amount) => EventKind::Burn(*amount),
+                    E::Pause => EventKind::Pause(Unit {}),
+                    E::Unpause => EventKind::Unpause(Unit {}),
+                };
+
+                Event { kind: Some(kind) }
+            })
+            .collect();
+
+        Self { events }
+    }
+}
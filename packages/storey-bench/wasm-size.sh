@@ -0,0 +1,34 @@
+#!/bin/sh
+# Builds a minimal wasm module per encoding and prints its size, optimized with `wasm-opt -Os`
+# if that's installed.
+#
+# Requires the wasm32-unknown-unknown target: `rustup target add wasm32-unknown-unknown`.
+set -e
+
+cd "$(dirname "$0")/wasm-size"
+
+wasm=target/wasm32-unknown-unknown/release/storey_wasm_size.wasm
+
+printf '| encoding | size (bytes) | after wasm-opt -Os (bytes) |\n'
+printf '|---|---|---|\n'
+
+for encoding in none bincode cbor json msgpack postcard prost; do
+    if [ "$encoding" = none ]; then
+        features=""
+    else
+        features="--features $encoding"
+    fi
+
+    # shellcheck disable=SC2086
+    cargo build --quiet --release --target wasm32-unknown-unknown $features
+
+    size=$(wc -c < "$wasm")
+    if command -v wasm-opt > /dev/null; then
+        wasm-opt -Os "$wasm" -o "$wasm.opt"
+        opt=$(wc -c < "$wasm.opt")
+    else
+        opt="-"
+    fi
+
+    printf '| %s | %s | %s |\n' "$encoding" "$size" "$opt"
+done
@@ -0,0 +1,81 @@
+//! Decodes a value from memory and encodes it back, with the encoding picked by feature.
+//!
+//! Without any encoding feature, the value is just copied, which gives the baseline size of
+//! the module.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "prost", derive(prost::Message))]
+pub struct Profile {
+    #[cfg_attr(feature = "prost", prost(string, tag = "1"))]
+    pub name: String,
+    #[cfg_attr(feature = "prost", prost(string, repeated, tag = "2"))]
+    pub tags: Vec<String>,
+    #[cfg_attr(feature = "prost", prost(uint64, tag = "3"))]
+    pub balance: u64,
+    #[cfg_attr(feature = "prost", prost(bool, tag = "4"))]
+    pub active: bool,
+}
+
+#[cfg(feature = "bincode")]
+type E = storey::encoding::BincodeEncoding;
+#[cfg(feature = "cbor")]
+type E = storey::encoding::CborEncoding;
+#[cfg(feature = "json")]
+type E = storey::encoding::JsonEncoding;
+#[cfg(feature = "msgpack")]
+type E = storey::encoding::MsgPackEncoding;
+#[cfg(feature = "postcard")]
+type E = storey::encoding::PostcardEncoding;
+#[cfg(feature = "prost")]
+type E = storey::encoding::ProstEncoding;
+
+#[cfg(any(
+    feature = "bincode",
+    feature = "cbor",
+    feature = "json",
+    feature = "msgpack",
+    feature = "postcard",
+    feature = "prost",
+))]
+fn roundtrip(input: &[u8]) -> Vec<u8> {
+    use storey::encoding::{DecodableWith, EncodableWith};
+
+    match <Profile as DecodableWith<E>>::decode(input) {
+        Ok(profile) => EncodableWith::<E>::encode(&profile).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
+#[cfg(not(any(
+    feature = "bincode",
+    feature = "cbor",
+    feature = "json",
+    feature = "msgpack",
+    feature = "postcard",
+    feature = "prost",
+)))]
+fn roundtrip(input: &[u8]) -> Vec<u8> {
+    input.to_vec()
+}
+
+#[no_mangle]
+pub extern "C" fn allocate(len: usize) -> *mut u8 {
+    let mut buf = Vec::<u8>::with_capacity(len);
+    let ptr = buf.as_mut_ptr();
+    std::mem::forget(buf);
+    ptr
+}
+
+/// # Safety
+///
+/// `ptr` must point to `len` initialized bytes obtained from [`allocate`].
+#[no_mangle]
+pub unsafe extern "C" fn execute(ptr: *mut u8, len: usize) -> usize {
+    let input = Vec::from_raw_parts(ptr, len, len);
+    let output = roundtrip(&input);
+    let output_len = output.len();
+    std::mem::forget(output);
+    output_len
+}
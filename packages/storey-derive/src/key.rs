@@ -0,0 +1,286 @@
+//! The derives of the key traits, for structs encoded as the concatenation of their fields.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Error, Fields, Member, Type};
+
+/// A field of a key struct.
+struct KeyField {
+    member: Member,
+    name: String,
+    ty: Type,
+}
+
+/// The fields of a key struct, and whether the last one is of variable width.
+struct KeyFields {
+    fields: Vec<KeyField>,
+    variable: bool,
+}
+
+impl KeyFields {
+    fn parse(input: &DeriveInput, derive: &str) -> Result<Self, Error> {
+        let fields = match &input.data {
+            Data::Struct(data) => match &data.fields {
+                Fields::Named(fields) => &fields.named,
+                Fields::Unnamed(fields) => &fields.unnamed,
+                Fields::Unit => return Err(no_fields(input, derive)),
+            },
+            _ => {
+                return Err(Error::new(
+                    input.ident.span(),
+                    format!("`{derive}` can only be derived for structs"),
+                ))
+            }
+        };
+        if fields.is_empty() {
+            return Err(no_fields(input, derive));
+        }
+
+        let mut variable = false;
+        let mut key_fields = Vec::new();
+        for (i, field) in fields.iter().enumerate() {
+            if is_variable(field)? {
+                if i + 1 < fields.len() {
+                    return Err(Error::new(
+                        field.span(),
+                        "only the last field can be of variable width",
+                    ));
+                }
+                variable = true;
+            }
+
+            let (member, name) = match &field.ident {
+                Some(ident) => (Member::Named(ident.clone()), ident.to_string()),
+                None => (Member::Unnamed(i.into()), i.to_string()),
+            };
+            key_fields.push(KeyField {
+                member,
+                name,
+                ty: field.ty.clone(),
+            });
+        }
+
+        Ok(Self {
+            fields: key_fields,
+            variable,
+        })
+    }
+
+    /// The fields of fixed width, i.e. all of them but a variable-width last one.
+    fn fixed(&self) -> &[KeyField] {
+        match self.variable {
+            true => &self.fields[..self.fields.len() - 1],
+            false => &self.fields,
+        }
+    }
+
+    /// The sum of the sizes of the fixed-width fields, as a const expression.
+    fn fixed_size(&self) -> TokenStream2 {
+        let tys = self.fixed().iter().map(|field| &field.ty);
+        quote!(0 #(+ <#tys as ::storey::containers::FixedSizeKey>::SIZE)*)
+    }
+}
+
+fn no_fields(input: &DeriveInput, derive: &str) -> Error {
+    Error::new(
+        input.ident.span(),
+        format!("`{derive}` needs a struct with at least one field"),
+    )
+}
+
+/// Whether `field` is marked with `#[storey(variable)]`.
+fn is_variable(field: &syn::Field) -> Result<bool, Error> {
+    let mut variable = false;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("storey") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("variable") {
+                variable = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown `storey` attribute, expected `variable`"))
+            }
+        })?;
+    }
+    Ok(variable)
+}
+
+pub fn key(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = KeyFields::parse(&input, "Key")?;
+    let members: Vec<_> = fields.fields.iter().map(|field| &field.member).collect();
+    let tys = fields.fields.iter().map(|field| &field.ty);
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut where_clause = where_clause
+        .cloned()
+        .unwrap_or_else(|| syn::parse_quote!(where));
+    for ty in tys {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#ty: ::storey::containers::Key));
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::storey::containers::Key for #ident #ty_generics #where_clause {
+            fn encode(&self, buf: &mut ::storey::__private::Vec<u8>) {
+                #(::storey::containers::Key::encode(&self.#members, buf);)*
+            }
+
+            fn size_hint(&self) -> usize {
+                0 #(+ ::storey::containers::Key::size_hint(&self.#members))*
+            }
+        }
+    })
+}
+
+pub fn owned_key(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = KeyFields::parse(&input, "OwnedKey")?;
+    let fixed_size = fields.fixed_size();
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut where_clause = where_clause
+        .cloned()
+        .unwrap_or_else(|| syn::parse_quote!(where));
+    for field in &fields.fields {
+        let ty = &field.ty;
+        where_clause.predicates.push(syn::parse_quote! {
+            ::storey::containers::MapKeyDecodeError<::core::convert::Infallible>:
+                ::core::convert::From<<#ty as ::storey::containers::OwnedKey>::Error>
+        });
+    }
+    for field in fields.fixed() {
+        let ty = &field.ty;
+        where_clause
+            .predicates
+            .push(syn::parse_quote_spanned! {ty.span()=>
+                #ty: ::storey::containers::FixedSizeKey
+            });
+    }
+    let last = &fields.fields.last().unwrap().ty;
+    where_clause
+        .predicates
+        .push(syn::parse_quote!(#last: ::storey::containers::OwnedKey));
+
+    // split off the fixed-width fields one after the other, the variable-width one (if any)
+    // getting the rest
+    let decode = fields.fields.iter().enumerate().map(|(i, field)| {
+        let KeyField { member, name, ty } = field;
+        let bytes = match fields.variable && i + 1 == fields.fields.len() {
+            true => quote!(bytes),
+            false => quote! {{
+                let (field, rest) =
+                    bytes.split_at(<#ty as ::storey::containers::FixedSizeKey>::SIZE);
+                bytes = rest;
+                field
+            }},
+        };
+        quote! {
+            #member: <#ty as ::storey::containers::OwnedKey>::from_bytes(#bytes).map_err(|e| {
+                ::storey::containers::StructKeyDecodeError::__field(#name, e)
+            })?
+        }
+    });
+
+    let (width, length_check, fixed_size_impl) = if fields.variable {
+        (
+            quote! {
+                match <#last as ::storey::containers::OwnedKey>::WIDTH {
+                    ::core::option::Option::Some(width) => {
+                        ::core::option::Option::Some(#fixed_size + width)
+                    }
+                    ::core::option::Option::None => ::core::option::Option::None,
+                }
+            },
+            quote! {
+                if bytes.len() < #fixed_size {
+                    return ::core::result::Result::Err(
+                        ::storey::containers::StructKeyDecodeError::TooShort {
+                            expected: #fixed_size,
+                            actual: bytes.len(),
+                        },
+                    );
+                }
+            },
+            quote!(),
+        )
+    } else {
+        (
+            quote!(::core::option::Option::Some(#fixed_size)),
+            quote! {
+                if bytes.len() != #fixed_size {
+                    return ::core::result::Result::Err(
+                        ::storey::containers::StructKeyDecodeError::InvalidLength {
+                            expected: #fixed_size,
+                            actual: bytes.len(),
+                        },
+                    );
+                }
+            },
+            quote! {
+                impl #impl_generics ::storey::containers::FixedSizeKey for #ident #ty_generics
+                    #where_clause
+                {
+                    const SIZE: usize = #fixed_size;
+                }
+            },
+        )
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::storey::containers::OwnedKey for #ident #ty_generics #where_clause {
+            type Error = ::storey::containers::StructKeyDecodeError;
+
+            const WIDTH: ::core::option::Option<usize> = #width;
+
+            #[allow(unused_mut)]
+            fn from_bytes(mut bytes: &[u8]) -> ::core::result::Result<Self, Self::Error>
+            where
+                Self: Sized,
+            {
+                #length_check
+
+                ::core::result::Result::Ok(Self {
+                    #(#decode,)*
+                })
+            }
+        }
+
+        #fixed_size_impl
+    })
+}
+
+pub fn order_preserving_key(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = KeyFields::parse(&input, "OrderPreservingKey")?;
+    if fields.variable {
+        let last = fields.fields.last().unwrap();
+        return Err(Error::new(
+            last.ty.span(),
+            "an `OrderPreservingKey` can't have a variable-width field, since the length of the \
+             key would come first",
+        ));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut where_clause = where_clause
+        .cloned()
+        .unwrap_or_else(|| syn::parse_quote!(where));
+    for field in &fields.fields {
+        let ty = &field.ty;
+        where_clause.predicates.push(syn::parse_quote! {
+            #ty: ::storey::containers::OrderPreservingKey + ::storey::containers::FixedSizeKey
+        });
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::storey::containers::OrderPreservingKey for #ident #ty_generics
+            #where_clause
+        {
+        }
+    })
+}
@@ -0,0 +1,284 @@
+//! Derive macros for [`storey`](https://docs.rs/storey). Use them through `storey`, with its
+//! `derive` feature enabled, rather than depending on this crate directly.
+
+mod key;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Fields, GenericArgument, Lit, PathArguments, Type,
+};
+
+/// See `storey::meta::StorageSchema`.
+#[proc_macro_derive(StorageSchema, attributes(storey))]
+pub fn derive_storage_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    storage_schema(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// See `storey::containers::Key`.
+#[proc_macro_derive(Key, attributes(storey))]
+pub fn derive_key(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    key::key(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// See `storey::containers::OwnedKey`.
+#[proc_macro_derive(OwnedKey, attributes(storey))]
+pub fn derive_owned_key(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    key::owned_key(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// See `storey::containers::OrderPreservingKey`.
+#[proc_macro_derive(OrderPreservingKey, attributes(storey))]
+pub fn derive_order_preserving_key(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    key::order_preserving_key(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// See `storey::meta::container_meta`.
+#[proc_macro]
+pub fn container_meta(input: TokenStream) -> TokenStream {
+    let ty = parse_macro_input!(input as Type);
+    container(&ty)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn storage_schema(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    input.ident.span(),
+                    "`StorageSchema` needs a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "`StorageSchema` can only be derived for structs",
+            ))
+        }
+    };
+
+    // like the discriminants of an enum: the previous prefix plus one, unless given
+    let mut next_prefix = Some(0u16);
+    let mut prefixes = Vec::new();
+    let mut entries = Vec::new();
+    for field in fields {
+        let prefix = match explicit_prefix(field)? {
+            Some(ExplicitPrefix::Byte(byte)) => {
+                next_prefix = Some(u16::from(byte) + 1);
+                vec![byte]
+            }
+            Some(ExplicitPrefix::Name(name)) => {
+                next_prefix = None;
+                name.into_bytes()
+            }
+            None => {
+                let next = next_prefix.ok_or_else(|| {
+                    Error::new(
+                        field.span(),
+                        "a container after one with a named prefix needs a prefix of its own",
+                    )
+                })?;
+                let byte = u8::try_from(next).map_err(|_| {
+                    Error::new(field.span(), "no prefix left for this container, past 255")
+                })?;
+                next_prefix = Some(next + 1);
+                vec![byte]
+            }
+        };
+
+        let prefix = Literal::byte_string(&prefix);
+        let name = field.ident.as_ref().unwrap().to_string();
+        let container = container(&field.ty)?;
+        entries.push(quote! {
+            ::storey::meta::ContainerEntry {
+                name: #name,
+                prefix: #prefix,
+                container: #container,
+            }
+        });
+        prefixes.push(prefix);
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::storey::meta::StorageSchema for #ident #ty_generics #where_clause {
+            const CONTAINERS: &'static [::storey::meta::ContainerEntry] = &[#(#entries),*];
+        }
+
+        const _: () = ::storey::containers::assert_unique_prefixes(&[#(#prefixes),*]);
+    })
+}
+
+/// A prefix given with `#[storey(prefix = ..)]`.
+enum ExplicitPrefix {
+    /// `prefix = N`, a single byte.
+    Byte(u8),
+    /// `prefix = "name"`, the bytes of the name, like `new_str`.
+    Name(String),
+}
+
+/// The prefix given with `#[storey(prefix = ..)]`, if any.
+fn explicit_prefix(field: &syn::Field) -> Result<Option<ExplicitPrefix>, Error> {
+    let mut prefix = None;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("storey") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("prefix") {
+                prefix = Some(match meta.value()?.parse::<Lit>()? {
+                    Lit::Int(int) => ExplicitPrefix::Byte(int.base10_parse::<u8>()?),
+                    Lit::Str(name) if name.value().is_empty() => {
+                        return Err(Error::new(name.span(), "a prefix can't be empty"))
+                    }
+                    Lit::Str(name) => ExplicitPrefix::Name(name.value()),
+                    lit => {
+                        return Err(Error::new(
+                            lit.span(),
+                            "expected a byte like `3`, or a name like `\"balances\"`",
+                        ))
+                    }
+                });
+                Ok(())
+            } else {
+                Err(meta.error("unknown `storey` attribute, expected `prefix`"))
+            }
+        })?;
+    }
+    Ok(prefix)
+}
+
+/// The `ContainerMeta` of a container type, as a const expression.
+fn container(ty: &Type) -> Result<TokenStream2, Error> {
+    let unsupported = || {
+        Error::new(
+            ty.span(),
+            "expected an `Item`, `Map` or `Column`, written out with its type arguments",
+        )
+    };
+
+    let Type::Path(path) = ty else {
+        return Err(unsupported());
+    };
+    let segment = path.path.segments.last().ok_or_else(unsupported)?;
+    let args: Vec<&Type> = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => return Err(unsupported()),
+    };
+
+    match (segment.ident.to_string().as_str(), args.as_slice()) {
+        ("Item", [value, ..]) => {
+            let value = value_meta(ty, value);
+            Ok(quote!(::storey::meta::ContainerMeta::Item { value: #value }))
+        }
+        ("Column", [value, ..]) => {
+            let value = value_meta(ty, value);
+            Ok(quote!(::storey::meta::ContainerMeta::Column { value: #value }))
+        }
+        ("Map", [key, value]) => {
+            let key_name = type_name(key);
+            let value = container(value)?;
+            Ok(quote! {
+                ::storey::meta::ContainerMeta::Map {
+                    key: ::storey::meta::KeyMeta {
+                        type_name: #key_name,
+                        width: <#key as ::storey::containers::OwnedKey>::WIDTH,
+                    },
+                    value: &#value,
+                }
+            })
+        }
+        _ => Err(unsupported()),
+    }
+}
+
+/// The `ValueMeta` of the values of `container`, an `Item` or a `Column`. The encoding comes
+/// from the type system, since it's often a default type argument.
+fn value_meta(container: &Type, value: &Type) -> TokenStream2 {
+    let value_name = type_name(value);
+    quote! {
+        ::storey::meta::ValueMeta {
+            type_name: #value_name,
+            encoding: <#container as ::storey::meta::ContainerEncoding>::ENCODING,
+        }
+    }
+}
+
+/// `ty` as it's usually written, e.g. `Map<String, Item<u64>>` rather than the
+/// `Map < String , Item < u64 > >` of its tokens.
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            let mut name = String::new();
+            if path.path.leading_colon.is_some() {
+                name.push_str("::");
+            }
+            for (i, segment) in path.path.segments.iter().enumerate() {
+                if i > 0 {
+                    name.push_str("::");
+                }
+                name.push_str(&segment.ident.to_string());
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    let args: Vec<String> = args
+                        .args
+                        .iter()
+                        .map(|arg| match arg {
+                            GenericArgument::Type(ty) => type_name(ty),
+                            arg => compact(arg.to_token_stream()),
+                        })
+                        .collect();
+                    name.push_str(&format!("<{}>", args.join(", ")));
+                }
+            }
+            name
+        }
+        Type::Tuple(tuple) => {
+            let elems: Vec<String> = tuple.elems.iter().map(type_name).collect();
+            match elems.as_slice() {
+                [elem] => format!("({elem},)"),
+                elems => format!("({})", elems.join(", ")),
+            }
+        }
+        Type::Array(array) => format!(
+            "[{}; {}]",
+            type_name(&array.elem),
+            compact(array.len.to_token_stream())
+        ),
+        Type::Slice(slice) => format!("[{}]", type_name(&slice.elem)),
+        Type::Reference(reference) => format!("&{}", type_name(&reference.elem)),
+        Type::Paren(paren) => type_name(&paren.elem),
+        Type::Group(group) => type_name(&group.elem),
+        ty => compact(ty.to_token_stream()),
+    }
+}
+
+/// Tokens without the spaces `to_string` puts between all of them.
+fn compact(tokens: TokenStream2) -> String {
+    tokens.to_string().replace(' ', "")
+}
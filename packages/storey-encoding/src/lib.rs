@@ -1,26 +1,74 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use core::error::Error;
+#[cfg(feature = "std")]
+use std::error::Error;
+
 pub trait Encoding {
     /// The error type returned when encoding fails.
-    type EncodeError;
+    ///
+    /// This has to be a proper error type so that it can be propagated with `?` into
+    /// `thiserror` enums, `anyhow::Error` and the like.
+    type EncodeError: Error + Send + Sync + 'static;
 
     /// The error type returned when decoding fails.
-    type DecodeError;
+    ///
+    /// The same requirements as for [`EncodeError`](Encoding::EncodeError) apply.
+    type DecodeError: Error + Send + Sync + 'static;
+
+    /// A short name for the encoding, like `msgpack`, for tools describing what's in storage.
+    ///
+    /// Encodings wrapping another one name only themselves. Encodings that don't set a name
+    /// are described as `custom`.
+    const NAME: &'static str = "custom";
 }
 
 pub trait EncodableWith<E: Encoding>: sealed::SealedE<E> {
     fn encode(&self) -> Result<Vec<u8>, E::EncodeError>;
+
+    /// Encode the value, appending the result to `buf`.
+    ///
+    /// This allows reusing a single buffer for encoding many values. If encoding fails,
+    /// `buf` may have been partially written to.
+    fn encode_to(&self, buf: &mut Vec<u8>) -> Result<(), E::EncodeError>;
 }
 
 pub trait EncodableWithImpl<E: Encoding> {
     fn encode_impl(self) -> Result<Vec<u8>, E::EncodeError>;
+
+    /// Encode the value, appending the result to `buf`.
+    ///
+    /// The default implementation appends the result of [`encode_impl`]. Encodings whose
+    /// serializer can write into an existing buffer should override it to avoid the extra
+    /// allocation.
+    ///
+    /// [`encode_impl`]: EncodableWithImpl::encode_impl
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), E::EncodeError>
+    where
+        Self: Sized,
+    {
+        buf.extend_from_slice(&self.encode_impl()?);
+        Ok(())
+    }
 }
 
-impl<E: Encoding, T> EncodableWith<E> for T
+impl<E: Encoding, T: ?Sized> EncodableWith<E> for T
 where
     for<'a> Cover<&'a T>: EncodableWithImpl<E>,
 {
     fn encode(&self) -> Result<Vec<u8>, <E as Encoding>::EncodeError> {
         Cover(self).encode_impl()
     }
+
+    fn encode_to(&self, buf: &mut Vec<u8>) -> Result<(), <E as Encoding>::EncodeError> {
+        Cover(self).encode_to_impl(buf)
+    }
 }
 
 pub trait DecodableWith<E: Encoding>: Sized + sealed::SealedD<E> {
@@ -41,6 +89,43 @@ where
     }
 }
 
+/// A value type with a borrowed counterpart, which can be decoded while borrowing from the
+/// encoded bytes instead of copying out of them.
+pub trait Borrowable {
+    type Borrowed<'a>;
+}
+
+impl Borrowable for String {
+    type Borrowed<'a> = &'a str;
+}
+
+impl Borrowable for Vec<u8> {
+    type Borrowed<'a> = &'a [u8];
+}
+
+pub trait BorrowDecodableWith<E: Encoding>: sealed::SealedB<E> {
+    type Borrowed<'a>;
+
+    fn decode_borrowed(data: &[u8]) -> Result<Self::Borrowed<'_>, E::DecodeError>;
+}
+
+pub trait BorrowDecodableWithImpl<E: Encoding> {
+    type Borrowed<'a>;
+
+    fn decode_borrowed_impl(data: &[u8]) -> Result<Self::Borrowed<'_>, E::DecodeError>;
+}
+
+impl<E: Encoding, T> BorrowDecodableWith<E> for T
+where
+    Cover<T>: BorrowDecodableWithImpl<E>,
+{
+    type Borrowed<'a> = <Cover<T> as BorrowDecodableWithImpl<E>>::Borrowed<'a>;
+
+    fn decode_borrowed(data: &[u8]) -> Result<Self::Borrowed<'_>, <E as Encoding>::DecodeError> {
+        <Cover<T>>::decode_borrowed_impl(data)
+    }
+}
+
 mod sealed {
     // This module is private to the crate. It's used to seal the `EncodableWith` and
     // `DecodableWith` traits, so that the only way they can be implemented outside
@@ -54,9 +139,11 @@ mod sealed {
 
     pub trait SealedE<E> {}
     pub trait SealedD<E> {}
+    pub trait SealedB<E> {}
 
-    impl<E: Encoding, T> SealedE<E> for T where for<'a> Cover<&'a T>: EncodableWithImpl<E> {}
+    impl<E: Encoding, T: ?Sized> SealedE<E> for T where for<'a> Cover<&'a T>: EncodableWithImpl<E> {}
     impl<E: Encoding, T> SealedD<E> for T where Cover<T>: DecodableWithImpl<E> {}
+    impl<E: Encoding, T> SealedB<E> for T where Cover<T>: BorrowDecodableWithImpl<E> {}
 }
 
 pub struct Cover<T>(pub T);
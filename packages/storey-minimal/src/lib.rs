@@ -0,0 +1,89 @@
+//! A small name registry stored with `storey`, without iteration. See the README.
+
+use storey::containers::{Column, Item, Map};
+use storey::encoding::{FixedIntDecodeError, FixedIntEncoding};
+use storey::storage::Transaction;
+
+/// The storage backend, an in-memory map of bytes.
+pub type Storage = std::collections::BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// The error of the registry operations.
+pub type Error = storey::Error<FixedIntDecodeError>;
+
+const COUNT: Item<u64, FixedIntEncoding> = Item::new(0);
+const OWNERS: Map<String, Item<u64, FixedIntEncoding>> = Map::new(1);
+const GRANTS: Map<u64, Map<String, Item<u64, FixedIntEncoding>>> = Map::new(2);
+const EXPIRIES: Column<u64, FixedIntEncoding> = Column::new(3);
+
+/// Registers `name` for `owner` until `expiry`, unless it's taken. Returns the index of the
+/// registration.
+pub fn register(
+    storage: &mut Storage,
+    name: &str,
+    owner: u64,
+    expiry: u64,
+) -> Result<Option<u32>, Error> {
+    let mut tx = Transaction::new(&mut *storage);
+
+    let mut owners = OWNERS.access(&mut tx);
+    if owners.entry(name).get()?.is_some() {
+        return Ok(None);
+    }
+    owners.entry_mut(name).set(&owner)?;
+
+    let count = COUNT.access(&tx).get()?.unwrap_or(0);
+    COUNT.access(&mut tx).set(&(count + 1))?;
+    let index = EXPIRIES.access(&mut tx).push(&expiry)?;
+
+    tx.commit();
+    Ok(Some(index))
+}
+
+/// Lets `grantee` use `name` until `expiry`, if `owner` owns it. Returns whether it did.
+pub fn grant(
+    storage: &mut Storage,
+    name: &str,
+    owner: u64,
+    grantee: u64,
+    expiry: u64,
+) -> Result<bool, Error> {
+    if OWNERS.access(&*storage).entry(name).get()? != Some(owner) {
+        return Ok(false);
+    }
+
+    GRANTS
+        .access(storage)
+        .entry_mut(&grantee)
+        .entry_mut(name)
+        .set(&expiry)?;
+    Ok(true)
+}
+
+/// Takes `name` back from `grantee`.
+pub fn revoke(storage: &mut Storage, name: &str, grantee: u64) {
+    GRANTS
+        .access(storage)
+        .entry_mut(&grantee)
+        .entry_mut(name)
+        .remove();
+}
+
+/// The owner of `name`, if it's registered.
+pub fn owner(storage: &Storage, name: &str) -> Result<Option<u64>, Error> {
+    OWNERS.access(storage).entry(name).get()
+}
+
+/// Until when `grantee` may use `name`, if at all.
+pub fn grant_expiry(storage: &Storage, name: &str, grantee: u64) -> Result<Option<u64>, Error> {
+    GRANTS.access(storage).entry(&grantee).entry(name).get()
+}
+
+/// The expiry of the registration at `index`.
+pub fn expiry(storage: &Storage, index: u32) -> Result<Option<u64>, Error> {
+    EXPIRIES.access(storage).get(index)
+}
+
+/// The number of names registered.
+pub fn count(storage: &Storage) -> Result<u64, Error> {
+    Ok(COUNT.access(storage).get()?.unwrap_or(0))
+}
@@ -0,0 +1,35 @@
+use storey_minimal::{count, expiry, grant, grant_expiry, owner, register, revoke, Storage};
+
+#[test]
+fn registry() {
+    let mut storage = Storage::new();
+
+    assert_eq!(register(&mut storage, "alice", 1, 100), Ok(Some(0)));
+    assert_eq!(register(&mut storage, "bob", 2, 200), Ok(Some(1)));
+    assert_eq!(register(&mut storage, "alice", 3, 300), Ok(None));
+
+    assert_eq!(owner(&storage, "alice"), Ok(Some(1)));
+    assert_eq!(owner(&storage, "carol"), Ok(None));
+    assert_eq!(expiry(&storage, 1), Ok(Some(200)));
+    assert_eq!(expiry(&storage, 2), Ok(None));
+    assert_eq!(count(&storage), Ok(2));
+
+    assert_eq!(grant(&mut storage, "alice", 2, 5, 50), Ok(false));
+    assert_eq!(grant(&mut storage, "alice", 1, 5, 50), Ok(true));
+    assert_eq!(grant_expiry(&storage, "alice", 5), Ok(Some(50)));
+    assert_eq!(grant_expiry(&storage, "bob", 5), Ok(None));
+
+    revoke(&mut storage, "alice", 5);
+    assert_eq!(grant_expiry(&storage, "alice", 5), Ok(None));
+}
+
+#[test]
+fn layout() {
+    let mut storage = Storage::new();
+    register(&mut storage, "alice", 1, 100).unwrap();
+    grant(&mut storage, "alice", 1, 5, 50).unwrap();
+
+    // the keys don't depend on the feature: length-prefixed outer keys, then the raw inner one
+    assert!(storage.contains_key(&[&[1, 5][..], b"alice"].concat()[..]));
+    assert!(storage.contains_key(&[&[2, 8][..], &5u64.to_be_bytes(), &[5], b"alice"].concat()[..]));
+}
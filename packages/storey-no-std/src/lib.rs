@@ -0,0 +1,107 @@
+//! A small ledger stored with `storey`, without `std`. See the README.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use storey::containers::{Column, Item, IterableAccessor as _, Map};
+use storey::encoding::{FixedIntDecodeError, FixedIntEncoding};
+use storey::storage::Transaction;
+
+/// The storage backend, an in-memory map of bytes.
+pub type Storage = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// The error of the ledger operations.
+pub type Error = storey::Error<FixedIntDecodeError>;
+
+/// A transfer, keyed by the block it happened in and its position there.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, storey::containers::Key, storey::containers::OwnedKey,
+)]
+pub struct TransferId {
+    pub height: u64,
+    pub index: u32,
+}
+
+const TOTAL: Item<u64, FixedIntEncoding> = Item::new(0);
+const BALANCES: Map<String, Item<u64, FixedIntEncoding>> =
+    Map::new_with_prefix(storey::prefix!("balances"));
+const AMOUNTS: Map<TransferId, Item<u64, FixedIntEncoding>> = Map::new(2);
+const HISTORY: Column<u64, FixedIntEncoding> = Column::new(3);
+
+/// Credits `amount` to `account`.
+pub fn mint(storage: &mut Storage, account: &str, amount: u64) -> Result<u64, Error> {
+    let mut balances = BALANCES.access(&mut *storage);
+    let balance = balances.entry(account).get()?.unwrap_or(0) + amount;
+    balances.entry_mut(account).set(&balance)?;
+
+    let total = TOTAL.access(&*storage).get()?.unwrap_or(0);
+    TOTAL.access(&mut *storage).set(&(total + amount))?;
+    Ok(balance)
+}
+
+/// Moves `amount` from `from` to `to`, all or nothing. Returns whether `from` had enough.
+pub fn transfer(
+    storage: &mut Storage,
+    id: TransferId,
+    from: &str,
+    to: &str,
+    amount: u64,
+) -> Result<bool, Error> {
+    let mut tx = Transaction::new(&mut *storage);
+
+    let mut balances = BALANCES.access(&mut tx);
+    let Some(remaining) = balances.entry(from).get()?.unwrap_or(0).checked_sub(amount) else {
+        return Ok(false);
+    };
+    balances.entry_mut(from).set(&remaining)?;
+    let received = balances.entry(to).get()?.unwrap_or(0) + amount;
+    balances.entry_mut(to).set(&received)?;
+
+    AMOUNTS.access(&mut tx).entry_mut(&id).set(&amount)?;
+    HISTORY.access(&mut tx).push(&amount)?;
+
+    tx.commit();
+    Ok(true)
+}
+
+/// The balances of all the accounts, by account.
+pub fn balances(storage: &Storage) -> Result<Vec<(String, u64)>, Error> {
+    BALANCES
+        .access(storage)
+        .pairs()
+        .map(|pair| {
+            let ((account, ()), balance) = pair?;
+            Ok((account, balance))
+        })
+        .collect()
+}
+
+/// The transfers and their amounts, by height and index.
+pub fn transfers(storage: &Storage) -> Result<Vec<(TransferId, u64)>, Error> {
+    AMOUNTS
+        .access(storage)
+        .pairs()
+        .map(|pair| {
+            let ((id, ()), amount) = pair?;
+            Ok((id, amount))
+        })
+        .collect()
+}
+
+/// The key a transfer is stored under, after the prefix of the map.
+pub fn transfer_key(id: TransferId) -> Vec<u8> {
+    storey::key!(id)
+}
+
+/// The number of transfers made, and the total supply.
+pub fn stats(storage: &Storage) -> Result<(u32, u64), Error> {
+    Ok((
+        HISTORY.access(storage).len()?,
+        TOTAL.access(storage).get()?.unwrap_or(0),
+    ))
+}
@@ -0,0 +1,44 @@
+// The `no_std` crate, linked into a test binary with `std`.
+
+use storey_no_std::{
+    balances, mint, stats, transfer, transfer_key, transfers, Storage, TransferId,
+};
+
+#[test]
+fn ledger() {
+    let mut storage = Storage::new();
+
+    assert_eq!(mint(&mut storage, "alice", 100), Ok(100));
+    assert_eq!(mint(&mut storage, "bob", 5), Ok(5));
+
+    let first = TransferId {
+        height: 7,
+        index: 0,
+    };
+    let second = TransferId {
+        height: 7,
+        index: 1,
+    };
+    assert_eq!(transfer(&mut storage, first, "alice", "bob", 30), Ok(true));
+    assert_eq!(
+        transfer(&mut storage, second, "bob", "carol", 50),
+        Ok(false)
+    );
+    assert_eq!(transfer(&mut storage, second, "bob", "carol", 35), Ok(true));
+
+    // string keys are length-prefixed, so shorter ones come first
+    assert_eq!(
+        balances(&storage).unwrap(),
+        [
+            ("bob".to_string(), 0),
+            ("alice".to_string(), 70),
+            ("carol".to_string(), 35)
+        ]
+    );
+    assert_eq!(transfers(&storage).unwrap(), [(first, 30), (second, 35)]);
+    assert_eq!(stats(&storage), Ok((2, 105)));
+
+    let key = [&[2][..], &transfer_key(second)].concat();
+    assert_eq!(key, [2, 12, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0, 1]);
+    assert!(storage.contains_key(&key));
+}
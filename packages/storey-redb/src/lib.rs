@@ -0,0 +1,356 @@
+//! A [*redb*] storage backend for [`storey`] containers, for off-chain services that want an
+//! embedded, pure-Rust database with ACID transactions.
+//!
+//! [`RedbStorage`] keeps everything in one table of a [`redb::Database`], with byte keys and
+//! values, and implements the reading, writing and iteration traits (in both directions) over
+//! it.
+//!
+//! # Transactions
+//!
+//! By default, every write is committed in its own write transaction, and every read goes
+//! through its own read transaction. A write batch is committed in a single transaction.
+//!
+//! [`begin`](RedbStorage::begin) opens a write transaction held by the storage, so that the
+//! writes made until [`commit`](RedbStorage::commit) are committed together. Reads see those
+//! writes in the meantime. [`abort`](RedbStorage::abort), or dropping the storage, discards
+//! them. This pairs with [`Transaction`]: buffer a unit of work in a `Transaction`, then
+//! commit it into an open *redb* transaction along with others.
+//!
+//! # Crash consistency
+//!
+//! *redb* transactions are committed with immediate durability: once a commit returns, the
+//! transaction survives the process being killed (or the machine losing power). After a crash,
+//! the table holds exactly the transactions committed before it. That means every write made
+//! outside of [`begin`](RedbStorage::begin)/[`commit`](RedbStorage::commit), since each is its
+//! own transaction, and none of the writes of a transaction that wasn't committed. Containers
+//! write several keys for some operations (like pushing to a column, which writes the value
+//! and the length), so an operation interrupted halfway leaves part of it behind, unless it
+//! runs inside an explicit transaction.
+//!
+//! # Errors
+//!
+//! The storage traits are infallible, so [`RedbStorage`] panics on any *redb* error met through
+//! them, with the error in the message. Those are I/O errors or a corrupted database, which
+//! can't be recovered from by carrying on. [`commit`](RedbStorage::commit) and
+//! [`abort`](RedbStorage::abort) return *redb*'s errors instead, since the caller may want to
+//! retry the unit of work.
+//!
+//! # Example
+//! ```
+//! use storey::containers::{Column, IterableAccessor as _};
+//! use storey_redb::RedbStorage;
+//! # use storey_testing::encoding::TestEncoding;
+//!
+//! const HISTORY: Column<u64, TestEncoding> = Column::new(0);
+//!
+//! # let dir = tempfile::tempdir().unwrap();
+//! # let path = dir.path().join("db.redb");
+//! let db = redb::Database::create(path).unwrap();
+//! let mut storage = RedbStorage::new(db, "state");
+//!
+//! storage.begin();
+//! HISTORY.access(&mut storage).push(&1).unwrap();
+//! HISTORY.access(&mut storage).push(&2).unwrap();
+//! storage.commit().unwrap();
+//!
+//! assert_eq!(
+//!     HISTORY.access(&storage).values().collect::<Result<Vec<_>, _>>(),
+//!     Ok(vec![1, 2])
+//! );
+//! ```
+//!
+//! [*redb*]: https://docs.rs/redb
+//! [`storey`]: https://docs.rs/storey
+//! [`Transaction`]: https://docs.rs/storey/latest/storey/storage/struct.Transaction.html
+
+use std::iter::{Map, Rev};
+use std::ops::Bound;
+use std::sync::Arc;
+
+use redb::{Database, ReadableTable as _, TableDefinition, TableError, WriteTransaction};
+use storey_storage::{IterableStorage, RevIterableStorage, StorageBackend, StorageBackendMut};
+
+type Table = TableDefinition<'static, &'static [u8], &'static [u8]>;
+type Bounds<'a> = (Bound<&'a [u8]>, Bound<&'a [u8]>);
+
+/// A storage backend over a table of a [`redb::Database`].
+///
+/// See the [crate docs](crate) for how transactions are handled, and for the error policy.
+pub struct RedbStorage {
+    db: Arc<Database>,
+    table: Table,
+    txn: Option<WriteTransaction>,
+}
+
+impl RedbStorage {
+    /// Use the table called `table` in `db` as a storage backend. The table is created on the
+    /// first write if it doesn't exist.
+    ///
+    /// Several tables of the same database can be used as separate backends, by passing the
+    /// database in an [`Arc`].
+    pub fn new(db: impl Into<Arc<Database>>, table: &'static str) -> Self {
+        Self {
+            db: db.into(),
+            table: TableDefinition::new(table),
+            txn: None,
+        }
+    }
+
+    /// Get the database.
+    pub fn database(&self) -> &Arc<Database> {
+        &self.db
+    }
+
+    /// Open a write transaction, which the writes go to until [`commit`](Self::commit) or
+    /// [`abort`](Self::abort).
+    ///
+    /// This waits for any other write transaction on the database to finish.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a transaction is already open, or on a *redb* error (which can only come from
+    /// the storage, like for the other operations).
+    pub fn begin(&mut self) {
+        assert!(self.txn.is_none(), "a transaction is already open");
+        self.txn = Some(self.db.begin_write().expect(ERROR));
+    }
+
+    /// Whether a transaction opened by [`begin`](Self::begin) is in progress.
+    pub fn in_transaction(&self) -> bool {
+        self.txn.is_some()
+    }
+
+    /// Commit the open transaction, if any.
+    pub fn commit(&mut self) -> Result<(), redb::CommitError> {
+        match self.txn.take() {
+            Some(txn) => txn.commit(),
+            None => Ok(()),
+        }
+    }
+
+    /// Discard the writes of the open transaction, if any.
+    pub fn abort(&mut self) -> Result<(), redb::StorageError> {
+        match self.txn.take() {
+            Some(txn) => txn.abort(),
+            None => Ok(()),
+        }
+    }
+
+    /// Run `f` with the table open for writing, in the open transaction or in a new one
+    /// committed afterwards.
+    fn write<R>(&mut self, f: impl FnOnce(&mut redb::Table<&[u8], &[u8]>) -> R) -> R {
+        match &self.txn {
+            Some(txn) => f(&mut txn.open_table(self.table).expect(ERROR)),
+            None => {
+                let txn = self.db.begin_write().expect(ERROR);
+                let result = f(&mut txn.open_table(self.table).expect(ERROR));
+                txn.commit().expect(ERROR);
+                result
+            }
+        }
+    }
+
+    fn entries(&self, start: Option<&[u8]>, end: Option<&[u8]>) -> Entries {
+        let Some(bounds) = bounds(start, end) else {
+            return Entries::empty();
+        };
+
+        match &self.txn {
+            // the table borrows the transaction, so the entries have to be copied out
+            Some(txn) => {
+                let table = txn.open_table(self.table).expect(ERROR);
+                let entries: Vec<_> = table
+                    .range::<&[u8]>(bounds)
+                    .expect(ERROR)
+                    .map(copy)
+                    .collect();
+                Entries(Inner::Buffered(entries.into_iter()))
+            }
+            None => {
+                let txn = self.db.begin_read().expect(ERROR);
+                match txn.open_table(self.table) {
+                    Ok(table) => Entries(Inner::Table(table.range::<&[u8]>(bounds).expect(ERROR))),
+                    Err(TableError::TableDoesNotExist(_)) => Entries::empty(),
+                    Err(err) => panic!("{ERROR}: {err}"),
+                }
+            }
+        }
+    }
+}
+
+impl StorageBackend for RedbStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match &self.txn {
+            Some(txn) => {
+                let table = txn.open_table(self.table).expect(ERROR);
+                let value = table.get(key).expect(ERROR);
+                value.map(|value| value.value().to_vec())
+            }
+            None => {
+                let txn = self.db.begin_read().expect(ERROR);
+                match txn.open_table(self.table) {
+                    Ok(table) => table
+                        .get(key)
+                        .expect(ERROR)
+                        .map(|value| value.value().to_vec()),
+                    Err(TableError::TableDoesNotExist(_)) => None,
+                    Err(err) => panic!("{ERROR}: {err}"),
+                }
+            }
+        }
+    }
+}
+
+impl StorageBackendMut for RedbStorage {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.write(|table| {
+            table.insert(key, value).expect(ERROR);
+        })
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.write(|table| {
+            table.remove(key).expect(ERROR);
+        })
+    }
+
+    /// Commits the batch as a single transaction, unless a transaction is already open.
+    fn write_batch<I>(&mut self, batch: I)
+    where
+        I: IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
+    {
+        self.write(|table| {
+            for (key, value) in batch {
+                match value {
+                    Some(value) => table.insert(key.as_slice(), value.as_slice()),
+                    None => table.remove(key.as_slice()),
+                }
+                .expect(ERROR);
+            }
+        })
+    }
+
+    fn remove_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>) {
+        if let Some(bounds) = bounds(start, end) {
+            self.write(|table| {
+                table
+                    .retain_in::<&[u8], _>(bounds, |_, _| false)
+                    .expect(ERROR);
+            })
+        }
+    }
+}
+
+impl IterableStorage for RedbStorage {
+    type KeysIterator<'a> = Map<Entries, fn((Vec<u8>, Vec<u8>)) -> Vec<u8>>;
+    type ValuesIterator<'a> = Map<Entries, fn((Vec<u8>, Vec<u8>)) -> Vec<u8>>;
+    type PairsIterator<'a> = Entries;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        self.entries(start, end).map(|(k, _)| k)
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        self.entries(start, end).map(|(_, v)| v)
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        self.entries(start, end)
+    }
+}
+
+impl RevIterableStorage for RedbStorage {
+    type RevKeysIterator<'a> = Rev<<Self as IterableStorage>::KeysIterator<'a>>;
+    type RevValuesIterator<'a> = Rev<<Self as IterableStorage>::ValuesIterator<'a>>;
+    type RevPairsIterator<'a> = Rev<Entries>;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        self.keys(start, end).rev()
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        self.values(start, end).rev()
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        self.pairs(start, end).rev()
+    }
+}
+
+/// An iterator over the entries of a [`RedbStorage`].
+///
+/// Outside of a transaction, it reads from a read transaction of its own, which it keeps open
+/// until dropped. Inside one, the entries are copied out when the iterator is created.
+pub struct Entries(Inner);
+
+enum Inner {
+    Table(redb::Range<'static, &'static [u8], &'static [u8]>),
+    Buffered(std::vec::IntoIter<(Vec<u8>, Vec<u8>)>),
+}
+
+impl Entries {
+    fn empty() -> Self {
+        Self(Inner::Buffered(Vec::new().into_iter()))
+    }
+}
+
+impl Iterator for Entries {
+    type Item = (Vec<u8>, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            Inner::Table(range) => range.next().map(copy),
+            Inner::Buffered(entries) => entries.next(),
+        }
+    }
+}
+
+impl DoubleEndedIterator for Entries {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            Inner::Table(range) => range.next_back().map(copy),
+            Inner::Buffered(entries) => entries.next_back(),
+        }
+    }
+}
+
+type Entry<'a> = Result<
+    (
+        redb::AccessGuard<'a, &'static [u8]>,
+        redb::AccessGuard<'a, &'static [u8]>,
+    ),
+    redb::StorageError,
+>;
+
+fn copy(entry: Entry<'_>) -> (Vec<u8>, Vec<u8>) {
+    let (key, value) = entry.expect(ERROR);
+    (key.value().to_vec(), value.value().to_vec())
+}
+
+/// The bounds of a range in *redb*'s terms, or `None` if the range is inverted (and so empty).
+fn bounds<'a>(start: Option<&'a [u8]>, end: Option<&'a [u8]>) -> Option<Bounds<'a>> {
+    if let (Some(start), Some(end)) = (start, end) {
+        if start > end {
+            return None;
+        }
+    }
+
+    Some((
+        start.map_or(Bound::Unbounded, Bound::Included),
+        end.map_or(Bound::Unbounded, Bound::Excluded),
+    ))
+}
+
+const ERROR: &str = "redb storage error";
@@ -0,0 +1,143 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use redb::Database;
+use storey::containers::{Column, IterableAccessor as _};
+use storey::storage::{StorageBackend as _, StorageBackendMut, Transaction};
+use storey_redb::RedbStorage;
+use storey_testing::conformance::{check_get_many, check_iteration, check_remove_range};
+use storey_testing::encoding::TestEncoding;
+
+const COLUMN: Column<u64, TestEncoding> = Column::new(0);
+
+fn open(path: &Path) -> RedbStorage {
+    RedbStorage::new(Database::create(path.join("db.redb")).unwrap(), "test")
+}
+
+#[test]
+fn conformance() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut storage = open(dir.path());
+
+    check_iteration(&mut storage);
+    check_remove_range(&mut storage);
+    check_get_many(&mut storage);
+}
+
+#[test]
+fn conformance_in_transaction() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut storage = open(dir.path());
+
+    storage.begin();
+    check_iteration(&mut storage);
+    check_remove_range(&mut storage);
+    check_get_many(&mut storage);
+    storage.commit().unwrap();
+}
+
+#[test]
+fn persistence() {
+    let dir = tempfile::tempdir().unwrap();
+
+    {
+        let mut storage = open(dir.path());
+        COLUMN.access(&mut storage).push(&1).unwrap();
+        COLUMN.access(&mut storage).push(&2).unwrap();
+    }
+
+    let storage = open(dir.path());
+    assert_eq!(
+        COLUMN
+            .access(&storage)
+            .values()
+            .collect::<Result<Vec<_>, _>>(),
+        Ok(vec![1, 2])
+    );
+}
+
+#[test]
+fn explicit_transactions() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(Database::create(dir.path().join("db.redb")).unwrap());
+    let mut storage = RedbStorage::new(db.clone(), "test");
+    let other = RedbStorage::new(db, "test");
+
+    storage.begin();
+    COLUMN.access(&mut storage).push(&1).unwrap();
+    // visible inside the transaction, but not outside of it
+    assert_eq!(COLUMN.access(&storage).len(), Ok(1));
+    assert_eq!(COLUMN.access(&other).len(), Ok(0));
+    storage.commit().unwrap();
+    assert_eq!(COLUMN.access(&other).len(), Ok(1));
+
+    storage.begin();
+    COLUMN.access(&mut storage).push(&2).unwrap();
+    storage.abort().unwrap();
+    assert!(!storage.in_transaction());
+    assert_eq!(COLUMN.access(&storage).len(), Ok(1));
+
+    // dropping the storage aborts the transaction too
+    storage.begin();
+    COLUMN.access(&mut storage).push(&3).unwrap();
+    drop(storage);
+    assert_eq!(COLUMN.access(&other).len(), Ok(1));
+}
+
+#[test]
+fn with_transaction_overlay() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut storage = open(dir.path());
+
+    storage.begin();
+    for i in 0..3 {
+        let mut tx = Transaction::new(&mut storage);
+        COLUMN.access(&mut tx).push(&i).unwrap();
+        if i != 1 {
+            tx.commit();
+        }
+    }
+    storage.commit().unwrap();
+
+    assert_eq!(
+        COLUMN
+            .access(&storage)
+            .values()
+            .collect::<Result<Vec<_>, _>>(),
+        Ok(vec![0, 2])
+    );
+}
+
+#[test]
+fn tables_are_separate() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(Database::create(dir.path().join("db.redb")).unwrap());
+    let mut a = RedbStorage::new(db.clone(), "a");
+    let b = RedbStorage::new(db, "b");
+
+    a.set(b"key", b"value");
+    assert_eq!(a.get(b"key"), Some(b"value".to_vec()));
+    assert_eq!(b.get(b"key"), None);
+}
+
+#[test]
+fn batch_through_reference() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut storage = open(dir.path());
+
+    // the batch fails halfway, so none of it may land
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let batch = (0..4u8).map(|i| {
+            assert!(i < 2, "batch interrupted");
+            (vec![i], Some(vec![i]))
+        });
+        <&mut RedbStorage as StorageBackendMut>::write_batch(&mut &mut storage, batch);
+    }));
+    assert!(result.is_err());
+    assert_eq!(storage.get(&[0]), None);
+
+    let batch = [(vec![0], Some(vec![0])), (vec![1], Some(vec![1]))];
+    <&mut RedbStorage as StorageBackendMut>::write_batch(&mut &mut storage, batch);
+    assert_eq!(storage.get(&[0]), Some(vec![0]));
+    assert_eq!(storage.get(&[1]), Some(vec![1]));
+}
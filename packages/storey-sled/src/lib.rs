@@ -0,0 +1,194 @@
+//! A [*sled*] storage backend for [`storey`] containers, for off-chain services (like
+//! indexers) that need their state to persist.
+//!
+//! [`SledStorage`] wraps a [`sled::Tree`], and implements the reading, writing and iteration
+//! traits (in both directions) over it.
+//!
+//! # Errors
+//!
+//! The storage traits are infallible: containers expect a read to return the value or
+//! nothing, and a write to succeed. *sled* operations can fail, but only on I/O errors or when
+//! the database is corrupted, and neither can be recovered from by retrying the operation or
+//! by carrying on without it. So [`SledStorage`] panics on any *sled* error, with the error in
+//! the message. Services that must shut down cleanly in that case can catch the panic at their
+//! top level.
+//!
+//! Durability is *sled*'s: writes are flushed in the background, and
+//! [`flush`](SledStorage::flush) waits for them to be on disk (and returns the errors as a
+//! `Result`, since it's called directly rather than through the storage traits).
+//!
+//! # Example
+//! ```
+//! use storey::containers::{Item, Map};
+//! use storey_sled::SledStorage;
+//! # use storey_testing::encoding::TestEncoding;
+//!
+//! const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new(0);
+//!
+//! let db = sled::Config::new().temporary(true).open().unwrap();
+//! let mut storage = SledStorage::from(db);
+//!
+//! BALANCES.access(&mut storage).entry_mut("alice").set(&100).unwrap();
+//! storage.flush().unwrap();
+//!
+//! assert_eq!(BALANCES.access(&storage).entry("alice").get(), Ok(Some(100)));
+//! ```
+//!
+//! [*sled*]: https://docs.rs/sled
+//! [`storey`]: https://docs.rs/storey
+
+use std::iter::{Map, Rev};
+use std::ops::Bound;
+
+use storey_storage::{IterableStorage, RevIterableStorage, StorageBackend, StorageBackendMut};
+
+type Entry = sled::Result<(sled::IVec, sled::IVec)>;
+
+/// A storage backend over a [`sled::Tree`].
+///
+/// Panics on *sled* errors (see the [crate docs](crate#errors)).
+#[derive(Clone)]
+pub struct SledStorage {
+    tree: sled::Tree,
+}
+
+impl SledStorage {
+    /// Use `tree` as a storage backend.
+    pub fn new(tree: sled::Tree) -> Self {
+        Self { tree }
+    }
+
+    /// Get the wrapped tree.
+    pub fn tree(&self) -> &sled::Tree {
+        &self.tree
+    }
+
+    /// Get the wrapped tree back.
+    pub fn into_inner(self) -> sled::Tree {
+        self.tree
+    }
+
+    /// Wait for all the writes so far to be on disk, returning the number of bytes flushed.
+    pub fn flush(&self) -> sled::Result<usize> {
+        self.tree.flush()
+    }
+
+    fn range(&self, start: Option<&[u8]>, end: Option<&[u8]>) -> sled::Iter {
+        let start = start.map_or(Bound::Unbounded, Bound::Included);
+        let end = end.map_or(Bound::Unbounded, Bound::Excluded);
+
+        // inverted bounds make an empty range
+        if let (Bound::Included(start), Bound::Excluded(end)) = (start, end) {
+            if start > end {
+                return self
+                    .tree
+                    .range::<&[u8], _>((Bound::Included(start), Bound::Excluded(start)));
+            }
+        }
+
+        self.tree.range::<&[u8], _>((start, end))
+    }
+}
+
+impl From<sled::Tree> for SledStorage {
+    fn from(tree: sled::Tree) -> Self {
+        Self::new(tree)
+    }
+}
+
+/// Uses the default tree of the database.
+impl From<sled::Db> for SledStorage {
+    fn from(db: sled::Db) -> Self {
+        Self::new(sled::Tree::clone(&db))
+    }
+}
+
+impl StorageBackend for SledStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.tree.get(key).expect(ERROR).map(|value| value.to_vec())
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        self.tree.contains_key(key).expect(ERROR)
+    }
+}
+
+impl StorageBackendMut for SledStorage {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.tree.insert(key, value).expect(ERROR);
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.tree.remove(key).expect(ERROR);
+    }
+
+    /// Applies the batch atomically.
+    fn write_batch<I>(&mut self, batch: I)
+    where
+        I: IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
+    {
+        let mut sled_batch = sled::Batch::default();
+        for (key, value) in batch {
+            match value {
+                Some(value) => sled_batch.insert(key, value),
+                None => sled_batch.remove(key),
+            }
+        }
+        self.tree.apply_batch(sled_batch).expect(ERROR);
+    }
+}
+
+impl IterableStorage for SledStorage {
+    type KeysIterator<'a> = Map<sled::Iter, fn(Entry) -> Vec<u8>>;
+    type ValuesIterator<'a> = Map<sled::Iter, fn(Entry) -> Vec<u8>>;
+    type PairsIterator<'a> = Map<sled::Iter, fn(Entry) -> (Vec<u8>, Vec<u8>)>;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        self.range(start, end)
+            .map(|entry| entry.expect(ERROR).0.to_vec())
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        self.range(start, end)
+            .map(|entry| entry.expect(ERROR).1.to_vec())
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        self.range(start, end).map(|entry| {
+            let (key, value) = entry.expect(ERROR);
+            (key.to_vec(), value.to_vec())
+        })
+    }
+}
+
+impl RevIterableStorage for SledStorage {
+    type RevKeysIterator<'a> = Rev<<Self as IterableStorage>::KeysIterator<'a>>;
+    type RevValuesIterator<'a> = Rev<<Self as IterableStorage>::ValuesIterator<'a>>;
+    type RevPairsIterator<'a> = Rev<<Self as IterableStorage>::PairsIterator<'a>>;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        self.keys(start, end).rev()
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        self.values(start, end).rev()
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        self.pairs(start, end).rev()
+    }
+}
+
+const ERROR: &str = "sled storage error";
@@ -0,0 +1,64 @@
+use storey::containers::{Column, IterableAccessor as _};
+use storey_sled::SledStorage;
+use storey_testing::conformance::{check_get_many, check_iteration, check_remove_range};
+use storey_testing::encoding::TestEncoding;
+
+fn temporary() -> SledStorage {
+    SledStorage::from(sled::Config::new().temporary(true).open().unwrap())
+}
+
+#[test]
+fn conformance() {
+    check_iteration(&mut temporary());
+    check_remove_range(&mut temporary());
+    check_get_many(&mut temporary());
+}
+
+#[test]
+fn persistence() {
+    const COLUMN: Column<u64, TestEncoding> = Column::new(0);
+
+    let dir = tempfile::tempdir().unwrap();
+
+    {
+        let mut storage = SledStorage::from(sled::open(dir.path()).unwrap());
+        COLUMN.access(&mut storage).push(&1).unwrap();
+        COLUMN.access(&mut storage).push(&2).unwrap();
+        storage.flush().unwrap();
+    }
+
+    let storage = SledStorage::from(reopen(dir.path()));
+    let access = COLUMN.access(&storage);
+    assert_eq!(access.len(), Ok(2));
+    assert_eq!(
+        access.values().collect::<Result<Vec<_>, _>>(),
+        Ok(vec![1, 2])
+    );
+}
+
+/// Open the database at `path` again, once the previous handle let go of it.
+///
+/// *sled*'s background threads may hold the database (and its file lock) for a moment after
+/// the last handle is dropped.
+fn reopen(path: &std::path::Path) -> sled::Db {
+    for _ in 0..50 {
+        match sled::open(path) {
+            Ok(db) => return db,
+            Err(_) => std::thread::sleep(std::time::Duration::from_millis(20)),
+        }
+    }
+    sled::open(path).unwrap()
+}
+
+#[test]
+fn named_trees_are_separate() {
+    const COLUMN: Column<u64, TestEncoding> = Column::new(0);
+
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let mut a = SledStorage::new(db.open_tree("a").unwrap());
+    let b = SledStorage::new(db.open_tree("b").unwrap());
+
+    COLUMN.access(&mut a).push(&1).unwrap();
+    assert_eq!(COLUMN.access(&a).len(), Ok(1));
+    assert_eq!(COLUMN.access(&b).len(), Ok(0));
+}
@@ -1,4 +1,8 @@
+use super::key::ComposedKey;
+#[cfg(feature = "iteration")]
+use super::storage::{IterableStorage, RemoveRangeStorage};
 use super::storage::{Storage, StorageMut};
+use alloc::vec::Vec;
 
 /// A trait for immutably accessing a storage backend.
 ///
@@ -13,6 +17,17 @@ pub trait StorageBackend {
     fn has(&self, key: &[u8]) -> bool {
         self.get(key).is_some()
     }
+
+    /// Get the values associated with each of the given keys.
+    ///
+    /// The results are in the same order as `keys`, with one result per key (even if a key is
+    /// repeated).
+    ///
+    /// The default implementation calls [`get`](Self::get) for each key. Backends that can
+    /// answer many reads at once should override it.
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
 }
 
 /// A trait for mutably accessing a storage backend.
@@ -20,12 +35,142 @@ pub trait StorageBackend {
 /// A collection of basic write operations that can be performed on a storage backend.
 ///
 /// You should only have to interact with this trait if you are implementing a custom storage backend.
+///
+/// # Interior mutability
+///
+/// Writes take `&mut self`. Backends that can be written through a shared reference (a handle
+/// to a host's storage, or a backend behind a lock or a cell) don't need a `&self` variant of
+/// this trait: implement it for the shared reference instead, and pass `&backend` wherever a
+/// mutable backend is expected.
+///
+/// ```
+/// use core::cell::RefCell;
+/// use std::collections::BTreeMap;
+///
+/// use storey_storage::{StorageBackend, StorageBackendMut};
+///
+/// struct Host {
+///     data: RefCell<BTreeMap<Vec<u8>, Vec<u8>>>,
+/// }
+///
+/// impl StorageBackend for Host {
+///     fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+///         self.data.borrow().get(key).cloned()
+///     }
+/// }
+///
+/// impl StorageBackendMut for &Host {
+///     fn set(&mut self, key: &[u8], value: &[u8]) {
+///         self.data.borrow_mut().insert(key.to_vec(), value.to_vec());
+///     }
+///
+///     fn remove(&mut self, key: &[u8]) {
+///         self.data.borrow_mut().remove(key);
+///     }
+/// }
+///
+/// let host = Host { data: RefCell::default() };
+/// (&host).set(b"key", b"value");
+/// assert_eq!(host.get(b"key"), Some(b"value".to_vec()));
+/// ```
 pub trait StorageBackendMut {
     /// Set the value associated with the given key.
     fn set(&mut self, key: &[u8], value: &[u8]);
 
     /// Remove the value associated with the given key.
     fn remove(&mut self, key: &[u8]);
+
+    /// Apply a batch of writes. A `None` value removes the key.
+    ///
+    /// The default implementation applies the writes one by one, in order. Backends with a
+    /// native way of writing batches should override it.
+    fn write_batch<I>(&mut self, batch: I)
+    where
+        I: IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
+        Self: Sized,
+    {
+        for (key, value) in batch {
+            match value {
+                Some(value) => self.set(&key, &value),
+                None => self.remove(&key),
+            }
+        }
+    }
+
+    /// Remove all the keys between `start` (inclusive) and `end` (exclusive).
+    ///
+    /// The bounds mean exactly what they mean for iteration (see [`IterableStorage::keys`]),
+    /// so this removes the keys iteration with the same bounds would yield.
+    ///
+    /// The default implementation collects the keys in the range, then removes them one by
+    /// one. Backends with a native way of removing a range should override it.
+    #[cfg(feature = "iteration")]
+    fn remove_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>)
+    where
+        Self: IterableStorage,
+    {
+        let keys: Vec<_> = self.keys(start, end).collect();
+        for key in keys {
+            self.remove(&key);
+        }
+    }
+}
+
+impl<B: StorageBackend + ?Sized> StorageBackend for &B {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        (**self).get(key)
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        (**self).has(key)
+    }
+
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        (**self).get_many(keys)
+    }
+}
+
+impl<B: StorageBackend + ?Sized> StorageBackend for &mut B {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        (**self).get(key)
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        (**self).has(key)
+    }
+
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        (**self).get_many(keys)
+    }
+}
+
+impl<B: StorageBackendMut> StorageBackendMut for &mut B {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        (**self).set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        (**self).remove(key)
+    }
+
+    fn write_batch<I>(&mut self, batch: I)
+    where
+        I: IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
+    {
+        (**self).write_batch(batch)
+    }
+
+    #[cfg(feature = "iteration")]
+    fn remove_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>)
+    where
+        Self: IterableStorage,
+    {
+        // `B`'s own `remove_range` can't be called here, since `&mut B: IterableStorage` doesn't
+        // prove `B: IterableStorage`; removing the range as one batch still reaches `B`'s
+        // native batch writes
+        let keys: Vec<_> = self.keys(start, end).collect();
+        (**self).write_batch(keys.into_iter().map(|key| (key, None)))
+    }
 }
 
 impl<B> Storage for B
@@ -40,6 +185,10 @@ where
         StorageBackend::has(self, key)
     }
 
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        StorageBackend::get_many(self, keys)
+    }
+
     fn get_meta(&self, key: &[u8]) -> Option<Vec<u8>> {
         StorageBackend::get(self, &meta_key(key))
     }
@@ -47,6 +196,16 @@ where
     fn has_meta(&self, key: &[u8]) -> bool {
         StorageBackend::has(self, &meta_key(key))
     }
+
+    fn get_meta_composed(&self, key: &ComposedKey) -> Option<Vec<u8>> {
+        key.with_prefix(&[255])
+            .with_bytes(|key| StorageBackend::get(self, key))
+    }
+
+    fn has_meta_composed(&self, key: &ComposedKey) -> bool {
+        key.with_prefix(&[255])
+            .with_bytes(|key| StorageBackend::has(self, key))
+    }
 }
 
 impl<B> StorageMut for B
@@ -68,6 +227,34 @@ where
     fn remove_meta(&mut self, key: &[u8]) {
         StorageBackendMut::remove(self, &meta_key(key))
     }
+
+    fn set_meta_composed(&mut self, key: &ComposedKey, value: &[u8]) {
+        key.with_prefix(&[255])
+            .with_bytes(|key| StorageBackendMut::set(self, key, value))
+    }
+
+    fn remove_meta_composed(&mut self, key: &ComposedKey) {
+        key.with_prefix(&[255])
+            .with_bytes(|key| StorageBackendMut::remove(self, key))
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<B> RemoveRangeStorage for B
+where
+    B: StorageBackendMut + IterableStorage,
+{
+    fn remove_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>) {
+        StorageBackendMut::remove_range(self, start, end)
+    }
+
+    fn remove_meta_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>) {
+        // the metadata namespace is the end of the key space, so an open end stays open
+        let start = meta_key(start.unwrap_or_default());
+        let end = end.map(meta_key);
+
+        StorageBackendMut::remove_range(self, Some(&start), end.as_deref())
+    }
 }
 
 fn meta_key(key: &[u8]) -> Vec<u8> {
@@ -0,0 +1,145 @@
+#[cfg(feature = "iteration")]
+use alloc::collections::btree_map;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+#[cfg(feature = "iteration")]
+use core::iter::{Map, Rev};
+#[cfg(feature = "iteration")]
+use core::ops::Bound;
+#[cfg(feature = "std")]
+use std::{collections::HashMap, hash::BuildHasher};
+
+use super::backend::{StorageBackend, StorageBackendMut};
+#[cfg(feature = "iteration")]
+use super::storage::{IterableStorage, RevIterableStorage};
+
+#[cfg(feature = "iteration")]
+type Entry<'a> = (&'a Vec<u8>, &'a Vec<u8>);
+#[cfg(feature = "iteration")]
+type Range<'a> = btree_map::Range<'a, Vec<u8>, Vec<u8>>;
+
+/// A `BTreeMap` of bytes is a complete backend, with ordered iteration in both directions.
+impl StorageBackend for BTreeMap<Vec<u8>, Vec<u8>> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        BTreeMap::get(self, key).cloned()
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        self.contains_key(key)
+    }
+}
+
+impl StorageBackendMut for BTreeMap<Vec<u8>, Vec<u8>> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.insert(key.to_vec(), value.to_vec());
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        BTreeMap::remove(self, key);
+    }
+
+    #[cfg(feature = "iteration")]
+    fn remove_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>) {
+        // split the range (and everything after it) off, then put back what's after it
+        let mut range = match start {
+            Some(start) => self.split_off(start),
+            None => core::mem::take(self),
+        };
+        if let Some(end) = end {
+            self.append(&mut range.split_off(end));
+        }
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl IterableStorage for BTreeMap<Vec<u8>, Vec<u8>> {
+    type KeysIterator<'a> = Map<Range<'a>, fn(Entry<'a>) -> Vec<u8>>;
+    type ValuesIterator<'a> = Map<Range<'a>, fn(Entry<'a>) -> Vec<u8>>;
+    type PairsIterator<'a> = Map<Range<'a>, fn(Entry<'a>) -> (Vec<u8>, Vec<u8>)>;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        range(self, start, end).map(|(k, _)| k.clone())
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        range(self, start, end).map(|(_, v)| v.clone())
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        range(self, start, end).map(|(k, v)| (k.clone(), v.clone()))
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl RevIterableStorage for BTreeMap<Vec<u8>, Vec<u8>> {
+    type RevKeysIterator<'a> = Rev<<Self as IterableStorage>::KeysIterator<'a>>;
+    type RevValuesIterator<'a> = Rev<<Self as IterableStorage>::ValuesIterator<'a>>;
+    type RevPairsIterator<'a> = Rev<<Self as IterableStorage>::PairsIterator<'a>>;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        IterableStorage::keys(self, start, end).rev()
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        IterableStorage::values(self, start, end).rev()
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        IterableStorage::pairs(self, start, end).rev()
+    }
+}
+
+#[cfg(feature = "iteration")]
+fn range<'a>(
+    map: &'a BTreeMap<Vec<u8>, Vec<u8>>,
+    start: Option<&[u8]>,
+    end: Option<&[u8]>,
+) -> Range<'a> {
+    let start = start.map_or(Bound::Unbounded, Bound::Included);
+    let end = end.map_or(Bound::Unbounded, Bound::Excluded);
+
+    // `BTreeMap::range` panics on inverted bounds, while storage iteration is just empty
+    if let (Bound::Included(start), Bound::Excluded(end)) = (start, end) {
+        if start > end {
+            return map.range::<[u8], _>((Bound::Included(start), Bound::Excluded(start)));
+        }
+    }
+
+    map.range::<[u8], _>((start, end))
+}
+
+/// A `HashMap` of bytes can be read from and written to, but it has no order to iterate in,
+/// so it only supports containers that don't need iteration.
+#[cfg(feature = "std")]
+impl<H: BuildHasher> StorageBackend for HashMap<Vec<u8>, Vec<u8>, H> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        HashMap::get(self, key).cloned()
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        self.contains_key(key)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<H: BuildHasher> StorageBackendMut for HashMap<Vec<u8>, Vec<u8>, H> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.insert(key.to_vec(), value.to_vec());
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        HashMap::remove(self, key);
+    }
+}
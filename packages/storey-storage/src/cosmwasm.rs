@@ -0,0 +1,79 @@
+//! Backend implementations for the in-memory storage of [`cosmwasm_std`].
+//!
+//! These let contract unit tests hand a [`MemoryStorage`] (or a `MockStorage`, which is the same
+//! type) straight to a container, without wrapping it in an adapter first. They behave exactly
+//! like `cw_storey::CwStorage`, so both produce identical bytes.
+
+use cosmwasm_std::MemoryStorage;
+#[cfg(feature = "iteration")]
+use cosmwasm_std::{Order, Storage as _};
+
+#[cfg(feature = "iteration")]
+use crate::{IterableStorage, RevIterableStorage};
+use crate::{StorageBackend, StorageBackendMut};
+
+impl StorageBackend for MemoryStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        cosmwasm_std::Storage::get(self, key)
+    }
+}
+
+impl StorageBackendMut for MemoryStorage {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        cosmwasm_std::Storage::set(self, key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        cosmwasm_std::Storage::remove(self, key)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl IterableStorage for MemoryStorage {
+    type KeysIterator<'a> = Box<dyn Iterator<Item = Vec<u8>> + 'a>;
+    type ValuesIterator<'a> = Box<dyn Iterator<Item = Vec<u8>> + 'a>;
+    type PairsIterator<'a> = Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        self.range_keys(start, end, Order::Ascending)
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        self.range_values(start, end, Order::Ascending)
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        self.range(start, end, Order::Ascending)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl RevIterableStorage for MemoryStorage {
+    type RevKeysIterator<'a> = Box<dyn Iterator<Item = Vec<u8>> + 'a>;
+    type RevValuesIterator<'a> = Box<dyn Iterator<Item = Vec<u8>> + 'a>;
+    type RevPairsIterator<'a> = Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        self.range_keys(start, end, Order::Descending)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        self.range_values(start, end, Order::Descending)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        self.range(start, end, Order::Descending)
+    }
+}
@@ -0,0 +1,291 @@
+use super::backend::{StorageBackend, StorageBackendMut};
+use super::storage::{IterableStorage, RevIterableStorage};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// A dyn-compatible version of [`IterableStorage`].
+///
+/// The iteration traits return their iterators as associated types, which makes them
+/// impossible to use as trait objects. This trait returns boxed iterators instead. It's
+/// implemented for every [`IterableStorage`], so you shouldn't need to implement it yourself.
+pub trait DynIterableStorage {
+    /// Get an iterator over keys. See [`IterableStorage::keys`].
+    fn dyn_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Box<dyn Iterator<Item = Vec<u8>> + 'a>;
+
+    /// Get an iterator over values. See [`IterableStorage::values`].
+    fn dyn_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Box<dyn Iterator<Item = Vec<u8>> + 'a>;
+
+    /// Get an iterator over key-value pairs. See [`IterableStorage::pairs`].
+    fn dyn_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>;
+}
+
+impl<S: IterableStorage> DynIterableStorage for S {
+    fn dyn_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Box<dyn Iterator<Item = Vec<u8>> + 'a> {
+        Box::new(self.keys(start, end))
+    }
+
+    fn dyn_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Box<dyn Iterator<Item = Vec<u8>> + 'a> {
+        Box::new(self.values(start, end))
+    }
+
+    fn dyn_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a> {
+        Box::new(self.pairs(start, end))
+    }
+}
+
+/// A dyn-compatible version of [`RevIterableStorage`].
+///
+/// This is to [`RevIterableStorage`] what [`DynIterableStorage`] is to [`IterableStorage`].
+pub trait DynRevIterableStorage {
+    /// Get a reverse iterator over keys. See [`RevIterableStorage::rev_keys`].
+    fn dyn_rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Box<dyn Iterator<Item = Vec<u8>> + 'a>;
+
+    /// Get a reverse iterator over values. See [`RevIterableStorage::rev_values`].
+    fn dyn_rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Box<dyn Iterator<Item = Vec<u8>> + 'a>;
+
+    /// Get a reverse iterator over key-value pairs. See [`RevIterableStorage::rev_pairs`].
+    fn dyn_rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>;
+}
+
+impl<S: RevIterableStorage> DynRevIterableStorage for S {
+    fn dyn_rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Box<dyn Iterator<Item = Vec<u8>> + 'a> {
+        Box::new(self.rev_keys(start, end))
+    }
+
+    fn dyn_rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Box<dyn Iterator<Item = Vec<u8>> + 'a> {
+        Box::new(self.rev_values(start, end))
+    }
+
+    fn dyn_rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a> {
+        Box::new(self.rev_pairs(start, end))
+    }
+}
+
+/// A dyn-compatible storage backend, supporting reads, writes and iteration in both
+/// directions.
+///
+/// This makes it possible to choose a backend at runtime, and keep it as a
+/// `Box<dyn DynStorage>`. The box implements [`StorageBackend`], [`StorageBackendMut`],
+/// [`IterableStorage`] and [`RevIterableStorage`] again, so containers can be used with it
+/// like with any other backend.
+///
+/// This trait is implemented for every backend implementing all of the above, so you
+/// shouldn't need to implement it yourself.
+///
+/// # Example
+/// ```
+/// use storey_storage::{DynStorage, IterableStorage as _, StorageBackendMut as _};
+///
+/// // the generic traits are available on the box, whatever the backend behind it
+/// fn reset(storage: &mut Box<dyn DynStorage>) -> usize {
+///     let keys: Vec<_> = storage.keys(None, None).collect();
+///     for key in &keys {
+///         storage.remove(key);
+///     }
+///     keys.len()
+/// }
+/// ```
+pub trait DynStorage: DynIterableStorage + DynRevIterableStorage {
+    /// See [`StorageBackend::get`].
+    fn dyn_get(&self, key: &[u8]) -> Option<Vec<u8>>;
+
+    /// See [`StorageBackend::has`].
+    fn dyn_has(&self, key: &[u8]) -> bool;
+
+    /// See [`StorageBackend::get_many`].
+    fn dyn_get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>>;
+
+    /// See [`StorageBackendMut::set`].
+    fn dyn_set(&mut self, key: &[u8], value: &[u8]);
+
+    /// See [`StorageBackendMut::remove`].
+    fn dyn_remove(&mut self, key: &[u8]);
+
+    /// See [`StorageBackendMut::write_batch`].
+    fn dyn_write_batch(&mut self, batch: Vec<(Vec<u8>, Option<Vec<u8>>)>);
+
+    /// See [`StorageBackendMut::remove_range`].
+    fn dyn_remove_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>);
+}
+
+impl<S> DynStorage for S
+where
+    S: StorageBackend + StorageBackendMut + IterableStorage + RevIterableStorage,
+{
+    fn dyn_get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.get(key)
+    }
+
+    fn dyn_has(&self, key: &[u8]) -> bool {
+        self.has(key)
+    }
+
+    fn dyn_get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        self.get_many(keys)
+    }
+
+    fn dyn_set(&mut self, key: &[u8], value: &[u8]) {
+        self.set(key, value)
+    }
+
+    fn dyn_remove(&mut self, key: &[u8]) {
+        self.remove(key)
+    }
+
+    fn dyn_write_batch(&mut self, batch: Vec<(Vec<u8>, Option<Vec<u8>>)>) {
+        self.write_batch(batch)
+    }
+
+    fn dyn_remove_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>) {
+        self.remove_range(start, end)
+    }
+}
+
+impl StorageBackend for Box<dyn DynStorage + '_> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        (**self).dyn_get(key)
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        (**self).dyn_has(key)
+    }
+
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        (**self).dyn_get_many(keys)
+    }
+}
+
+impl StorageBackendMut for Box<dyn DynStorage + '_> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        (**self).dyn_set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        (**self).dyn_remove(key)
+    }
+
+    fn write_batch<I>(&mut self, batch: I)
+    where
+        I: IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
+    {
+        (**self).dyn_write_batch(batch.into_iter().collect())
+    }
+
+    fn remove_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>) {
+        (**self).dyn_remove_range(start, end)
+    }
+}
+
+impl IterableStorage for Box<dyn DynStorage + '_> {
+    type KeysIterator<'a>
+        = Box<dyn Iterator<Item = Vec<u8>> + 'a>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = Box<dyn Iterator<Item = Vec<u8>> + 'a>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>
+    where
+        Self: 'a;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        (**self).dyn_keys(start, end)
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        (**self).dyn_values(start, end)
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        (**self).dyn_pairs(start, end)
+    }
+}
+
+impl RevIterableStorage for Box<dyn DynStorage + '_> {
+    type RevKeysIterator<'a>
+        = Box<dyn Iterator<Item = Vec<u8>> + 'a>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = Box<dyn Iterator<Item = Vec<u8>> + 'a>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        = Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>
+    where
+        Self: 'a;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        (**self).dyn_rev_keys(start, end)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        (**self).dyn_rev_values(start, end)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        (**self).dyn_rev_pairs(start, end)
+    }
+}
@@ -0,0 +1,107 @@
+use alloc::vec::Vec;
+
+/// A key made of several parts, concatenated only when it's needed in one piece.
+///
+/// Storage namespaces (like `storey`'s `StorageBranch`) prepend their prefix to every key
+/// that goes through them. Done by concatenation, nested namespaces would allocate a new key
+/// at every level. Instead, each level wraps the key it got in a `ComposedKey` with its prefix
+/// in front, which borrows the parts without copying them, and the backend puts the result
+/// together once (see [`Storage::get_composed`] and friends), on the stack if it's short.
+///
+/// [`Storage::get_composed`]: crate::Storage::get_composed
+///
+/// # Example
+/// ```
+/// use storey_storage::ComposedKey;
+///
+/// let key = ComposedKey::new(b"key");
+/// let key = key.with_prefix(b"inner/");
+/// let key = key.with_prefix(b"outer/");
+///
+/// assert_eq!(key.len(), 15);
+/// assert_eq!(key.to_vec(), b"outer/inner/key");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ComposedKey<'a> {
+    head: &'a [u8],
+    tail: Option<&'a ComposedKey<'a>>,
+}
+
+impl<'a> ComposedKey<'a> {
+    /// A key made of a single part.
+    pub fn new(key: &'a [u8]) -> Self {
+        Self {
+            head: key,
+            tail: None,
+        }
+    }
+
+    /// This key, with `prefix` in front of it.
+    pub fn with_prefix<'b>(&'b self, prefix: &'b [u8]) -> ComposedKey<'b> {
+        ComposedKey {
+            head: prefix,
+            tail: Some(self),
+        }
+    }
+
+    /// The parts of the key, in order.
+    pub fn parts(&self) -> impl Iterator<Item = &'a [u8]> {
+        let mut next = Some(*self);
+        core::iter::from_fn(move || {
+            let key = next?;
+            next = key.tail.copied();
+            Some(key.head)
+        })
+    }
+
+    /// The length of the whole key, in bytes.
+    pub fn len(&self) -> usize {
+        self.parts().map(<[u8]>::len).sum()
+    }
+
+    /// Whether the whole key is empty.
+    pub fn is_empty(&self) -> bool {
+        self.parts().all(<[u8]>::is_empty)
+    }
+
+    /// Append the whole key to `buf`.
+    pub fn write_to(&self, buf: &mut Vec<u8>) {
+        for part in self.parts() {
+            buf.extend_from_slice(part);
+        }
+    }
+
+    /// Call `f` with the whole key.
+    ///
+    /// Keys up to 64 bytes long are put together on the stack, so this doesn't allocate for
+    /// them.
+    pub fn with_bytes<R>(&self, f: impl FnOnce(&[u8]) -> R) -> R {
+        let len = self.len();
+        if len > STACK_KEY_LEN {
+            return f(&self.to_vec());
+        }
+
+        let mut buf = [0; STACK_KEY_LEN];
+        let mut pos = 0;
+        for part in self.parts() {
+            buf[pos..pos + part.len()].copy_from_slice(part);
+            pos += part.len();
+        }
+        f(&buf[..len])
+    }
+
+    /// The whole key, in one allocation.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.len());
+        self.write_to(&mut buf);
+        buf
+    }
+}
+
+const STACK_KEY_LEN: usize = 64;
+
+impl<'a> From<&'a [u8]> for ComposedKey<'a> {
+    fn from(key: &'a [u8]) -> Self {
+        Self::new(key)
+    }
+}
@@ -1,5 +1,20 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 mod backend;
+mod collections;
+#[cfg(feature = "cosmwasm-std")]
+mod cosmwasm;
+#[cfg(feature = "iteration")]
+mod dynamic;
+mod key;
 mod storage;
 
 pub use backend::{StorageBackend, StorageBackendMut};
-pub use storage::{IterableStorage, RevIterableStorage, Storage, StorageMut};
+#[cfg(feature = "iteration")]
+pub use dynamic::{DynIterableStorage, DynRevIterableStorage, DynStorage};
+pub use key::ComposedKey;
+#[cfg(feature = "iteration")]
+pub use storage::{IterableStorage, RemoveRangeStorage, RevIterableStorage};
+pub use storage::{Storage, StorageMut};
@@ -1,3 +1,6 @@
+use crate::ComposedKey;
+use alloc::vec::Vec;
+
 /// A read interface for binary key-value storage.
 pub trait Storage {
     /// Get the value of the key.
@@ -8,6 +11,13 @@ pub trait Storage {
         self.get(key).is_some()
     }
 
+    /// Get the values of each of the keys, in the same order as `keys`.
+    ///
+    /// There's one result per key, even if a key is repeated.
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
+
     /// Get the value of the key in the metadata namespace.
     fn get_meta(&self, _key: &[u8]) -> Option<Vec<u8>>;
 
@@ -15,6 +25,41 @@ pub trait Storage {
     fn has_meta(&self, key: &[u8]) -> bool {
         self.get_meta(key).is_some()
     }
+
+    /// Get the value of a key made of parts. See [`ComposedKey`].
+    ///
+    /// The default implementation puts the key together (see [`ComposedKey::with_bytes`]) and
+    /// calls [`get`](Self::get). Storage namespaces override it to add their prefix and pass
+    /// the key on without putting it together.
+    fn get_composed(&self, key: &ComposedKey) -> Option<Vec<u8>> {
+        key.with_bytes(|key| self.get(key))
+    }
+
+    /// Check if a key made of parts exists. See [`get_composed`](Self::get_composed).
+    fn has_composed(&self, key: &ComposedKey) -> bool {
+        key.with_bytes(|key| self.has(key))
+    }
+
+    /// Get the value of a key made of parts in the metadata namespace. See
+    /// [`get_composed`](Self::get_composed).
+    fn get_meta_composed(&self, key: &ComposedKey) -> Option<Vec<u8>> {
+        key.with_bytes(|key| self.get_meta(key))
+    }
+
+    /// Check if a key made of parts exists in the metadata namespace. See
+    /// [`get_composed`](Self::get_composed).
+    fn has_meta_composed(&self, key: &ComposedKey) -> bool {
+        key.with_bytes(|key| self.has_meta(key))
+    }
+
+    /// The prefix this storage puts before keys on their way to the backend.
+    ///
+    /// It's empty for a backend. Storage namespaces return their own prefix after the one of
+    /// the storage they're nested in. It's meant for debugging: the namespaces don't use it to
+    /// build keys.
+    fn key_prefix(&self) -> Vec<u8> {
+        Vec::new()
+    }
 }
 
 /// A write interface for binary key-value storage.
@@ -30,11 +75,52 @@ pub trait StorageMut {
 
     /// Remove the key in the metadata namespace.
     fn remove_meta(&mut self, _key: &[u8]);
+
+    /// Set the value of a key made of parts. See [`Storage::get_composed`].
+    fn set_composed(&mut self, key: &ComposedKey, value: &[u8]) {
+        key.with_bytes(|key| self.set(key, value))
+    }
+
+    /// Remove a key made of parts. See [`Storage::get_composed`].
+    fn remove_composed(&mut self, key: &ComposedKey) {
+        key.with_bytes(|key| self.remove(key))
+    }
+
+    /// Set the value of a key made of parts in the metadata namespace. See
+    /// [`Storage::get_composed`].
+    fn set_meta_composed(&mut self, key: &ComposedKey, value: &[u8]) {
+        key.with_bytes(|key| self.set_meta(key, value))
+    }
+
+    /// Remove a key made of parts in the metadata namespace. See [`Storage::get_composed`].
+    fn remove_meta_composed(&mut self, key: &ComposedKey) {
+        key.with_bytes(|key| self.remove_meta(key))
+    }
+}
+
+/// Range removal interface for binary key-value storage.
+///
+/// This is implemented for every storage backend that can iterate (through
+/// [`StorageBackendMut::remove_range`], so that backends can provide a native implementation),
+/// and for storage branches over a storage implementing it.
+///
+/// [`StorageBackendMut::remove_range`]: crate::StorageBackendMut::remove_range
+#[cfg(feature = "iteration")]
+pub trait RemoveRangeStorage: StorageMut + IterableStorage {
+    /// Remove all the keys between `start` (inclusive) and `end` (exclusive).
+    ///
+    /// The bounds mean exactly what they mean for iteration (see [`IterableStorage::keys`]).
+    fn remove_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>);
+
+    /// Remove all the keys between `start` (inclusive) and `end` (exclusive) in the metadata
+    /// namespace.
+    fn remove_meta_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>);
 }
 
 /// Iteration interface for binary key-value storage.
 ///
 /// The iterator should iterate over key-value pairs in lexicographical order of keys.
+#[cfg(feature = "iteration")]
 pub trait IterableStorage {
     /// The type of the iterator returned by [`keys`](Self::keys).
     type KeysIterator<'a>: Iterator<Item = Vec<u8>>
@@ -85,10 +171,20 @@ pub trait IterableStorage {
     fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a>;
 }
 
+#[cfg(feature = "iteration")]
 impl<T: IterableStorage> IterableStorage for &T {
-    type KeysIterator<'a> = T::KeysIterator<'a> where Self: 'a;
-    type ValuesIterator<'a> = T::ValuesIterator<'a> where Self: 'a;
-    type PairsIterator<'a> = T::PairsIterator<'a> where Self: 'a;
+    type KeysIterator<'a>
+        = T::KeysIterator<'a>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = T::ValuesIterator<'a>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = T::PairsIterator<'a>
+    where
+        Self: 'a;
 
     /// Get an iterator over keys.
     ///
@@ -130,10 +226,20 @@ impl<T: IterableStorage> IterableStorage for &T {
     }
 }
 
+#[cfg(feature = "iteration")]
 impl<T: IterableStorage> IterableStorage for &mut T {
-    type KeysIterator<'a> = T::KeysIterator<'a> where Self: 'a;
-    type ValuesIterator<'a> = T::ValuesIterator<'a> where Self: 'a;
-    type PairsIterator<'a> = T::PairsIterator<'a> where Self: 'a;
+    type KeysIterator<'a>
+        = T::KeysIterator<'a>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = T::ValuesIterator<'a>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = T::PairsIterator<'a>
+    where
+        Self: 'a;
 
     fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
         (**self).keys(start, end)
@@ -151,6 +257,7 @@ impl<T: IterableStorage> IterableStorage for &mut T {
 /// Iteration interface for binary key-value storage in reverse order.
 ///
 /// The iterator should iterate over key-value pairs in reverse lexicographical order of keys.
+#[cfg(feature = "iteration")]
 pub trait RevIterableStorage {
     /// The type of the iterator returned by [`rev_keys`](Self::rev_keys).
     type RevKeysIterator<'a>: Iterator<Item = Vec<u8>>
@@ -183,3 +290,83 @@ pub trait RevIterableStorage {
         end: Option<&[u8]>,
     ) -> Self::RevPairsIterator<'a>;
 }
+
+#[cfg(feature = "iteration")]
+impl<T: RevIterableStorage> RevIterableStorage for &T {
+    type RevKeysIterator<'a>
+        = T::RevKeysIterator<'a>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = T::RevValuesIterator<'a>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        = T::RevPairsIterator<'a>
+    where
+        Self: 'a;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        (**self).rev_keys(start, end)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        (**self).rev_values(start, end)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        (**self).rev_pairs(start, end)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<T: RevIterableStorage> RevIterableStorage for &mut T {
+    type RevKeysIterator<'a>
+        = T::RevKeysIterator<'a>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = T::RevValuesIterator<'a>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        = T::RevPairsIterator<'a>
+    where
+        Self: 'a;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        (**self).rev_keys(start, end)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        (**self).rev_values(start, end)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        (**self).rev_pairs(start, end)
+    }
+}
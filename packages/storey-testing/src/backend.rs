@@ -0,0 +1,472 @@
+//! An in-memory storage backend.
+
+use std::{
+    cell::{Cell, UnsafeCell},
+    collections::{btree_map, BTreeMap},
+    ops::Bound,
+};
+
+use storey_storage::{IterableStorage, RevIterableStorage, StorageBackend, StorageBackendMut};
+
+// `UnsafeCell` is needed here to implement interior mutability.
+// https://doc.rust-lang.org/book/ch15-05-interior-mutability.html
+//
+// We could play it safe and use `RefCell` instead, but we really don't need
+// the extra bookkeeping. We're not trying to push borrow rules for the inner
+// `BTreeMap` to runtime; we guarantee memory safety around aliasing at compile
+// time.
+
+/// An in-memory storage backend, backed by a [`BTreeMap`].
+///
+/// It supports reading, writing and iterating in both directions, so it can be used with
+/// any container.
+///
+/// # Example
+/// ```
+/// use storey_storage::{IterableStorage as _, StorageBackendMut as _};
+/// use storey_testing::backend::TestStorage;
+///
+/// let mut storage = TestStorage::new();
+/// storage.set(&[1], b"bar");
+/// storage.set(&[0], b"foo");
+///
+/// assert_eq!(storage.keys(None, None).collect::<Vec<_>>(), vec![vec![0], vec![1]]);
+/// ```
+pub struct TestStorage {
+    data: UnsafeCell<BTreeMap<Vec<u8>, Vec<u8>>>,
+    fail_next_get: Cell<bool>,
+}
+
+impl TestStorage {
+    /// Create an empty storage.
+    pub fn new() -> Self {
+        Self {
+            data: UnsafeCell::new(BTreeMap::new()),
+            fail_next_get: Cell::new(false),
+        }
+    }
+
+    /// Make the next read return corrupted data.
+    ///
+    /// The next call to [`get`](StorageBackend::get) returns an empty value, whether the key
+    /// exists or not. Decoding an empty value fails with most encodings (including
+    /// [`TestEncoding`](crate::encoding::TestEncoding)), which makes this a way to test how
+    /// corrupted storage is handled. Reads after that one behave normally again.
+    ///
+    /// Metadata reads (like the length of a column) count as reads too.
+    ///
+    /// # Example
+    /// ```
+    /// use storey_storage::{StorageBackend as _, StorageBackendMut as _};
+    /// use storey_testing::backend::TestStorage;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// storage.set(&[0], b"foo");
+    ///
+    /// storage.fail_next_get();
+    /// assert_eq!(storage.get(&[0]), Some(vec![]));
+    /// assert_eq!(storage.get(&[0]), Some(b"foo".to_vec()));
+    /// ```
+    pub fn fail_next_get(&self) {
+        self.fail_next_get.set(true);
+    }
+}
+
+impl Default for TestStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Safety: the BTreeMap is only ever mutated through `&mut self`, so shared references to it
+// obtained through `&self` (including the ones held by iterators, which borrow `self`) can't
+// coexist with a mutation.
+//
+// Moreover, we can further guarantee that the dereference is valid because the data
+// is always initialized during construction.
+
+impl StorageBackend for TestStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        if self.fail_next_get.replace(false) {
+            return Some(Vec::new());
+        }
+
+        // Safety: see above
+        unsafe { (*self.data.get()).get(key).cloned() }
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        // Safety: see above
+        unsafe { (*self.data.get()).contains_key(key) }
+    }
+}
+
+impl StorageBackendMut for TestStorage {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.data.get_mut().insert(key.to_vec(), value.to_vec());
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.data.get_mut().remove(key);
+    }
+
+    fn remove_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>) {
+        let data = self.data.get_mut();
+
+        // split the range (and everything after it) off, then put back what's after it
+        let mut range = match start {
+            Some(start) => data.split_off(start),
+            None => std::mem::take(data),
+        };
+        if let Some(end) = end {
+            data.append(&mut range.split_off(end));
+        }
+    }
+}
+
+impl TestStorage {
+    /// The entries between `start` (inclusive) and `end` (exclusive), in order.
+    ///
+    /// The entries are borrowed from the map, so only the ones actually iterated over get
+    /// copied out.
+    fn range<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> btree_map::Range<'a, Vec<u8>, Vec<u8>> {
+        let start = start.map_or(Bound::Unbounded, Bound::Included);
+        let mut end = end.map_or(Bound::Unbounded, Bound::Excluded);
+
+        // `BTreeMap::range` panics on inverted bounds, while storage iteration is just empty
+        if let (Bound::Included(start), Bound::Excluded(inner_end)) = (start, end) {
+            if start > inner_end {
+                end = Bound::Excluded(start);
+            }
+        }
+
+        // Safety: see above
+        unsafe { &*self.data.get() }.range::<[u8], _>((start, end))
+    }
+}
+
+impl IterableStorage for TestStorage {
+    type KeysIterator<'a> = Box<dyn DoubleEndedIterator<Item = Vec<u8>> + 'a>;
+    type ValuesIterator<'a> = Box<dyn DoubleEndedIterator<Item = Vec<u8>> + 'a>;
+    type PairsIterator<'a> = Box<dyn DoubleEndedIterator<Item = (Vec<u8>, Vec<u8>)> + 'a>;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        Box::new(self.range(start, end).map(|(k, _)| k.clone()))
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        Box::new(self.range(start, end).map(|(_, v)| v.clone()))
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        Box::new(self.range(start, end).map(|(k, v)| (k.clone(), v.clone())))
+    }
+}
+
+// Reverse iteration covers the same range as forward iteration - `start` is still the
+// inclusive lower bound and `end` the exclusive upper bound - just walked from the end.
+impl RevIterableStorage for TestStorage {
+    type RevKeysIterator<'a> = Box<dyn Iterator<Item = Vec<u8>> + 'a>;
+    type RevValuesIterator<'a> = Box<dyn Iterator<Item = Vec<u8>> + 'a>;
+    type RevPairsIterator<'a> = Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        Box::new(self.keys(start, end).rev())
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        Box::new(self.values(start, end).rev())
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        Box::new(self.pairs(start, end).rev())
+    }
+}
+
+/// A [`TestStorage`] counting the reads made to it.
+///
+/// Point reads ([`get`](StorageBackend::get)), batched reads
+/// ([`get_many`](StorageBackend::get_many)) and existence checks
+/// ([`has`](StorageBackend::has)) are counted separately, so that tests can check a cheaper
+/// path is actually taken. Everything else is passed through as-is.
+///
+/// # Example
+/// ```
+/// use storey_storage::{StorageBackend as _, StorageBackendMut as _};
+/// use storey_testing::backend::CountingStorage;
+///
+/// let mut storage = CountingStorage::new();
+/// storage.set(&[0], b"foo");
+///
+/// assert_eq!(storage.get_many(&[&[0], &[1], &[0]]).len(), 3);
+/// assert_eq!(storage.gets(), 0);
+/// assert_eq!(storage.batches(), 1);
+/// ```
+#[derive(Default)]
+pub struct CountingStorage {
+    inner: TestStorage,
+    gets: Cell<usize>,
+    batches: Cell<usize>,
+    checks: Cell<usize>,
+}
+
+impl CountingStorage {
+    /// Create an empty storage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of calls to [`get`](StorageBackend::get) so far.
+    pub fn gets(&self) -> usize {
+        self.gets.get()
+    }
+
+    /// The number of calls to [`get_many`](StorageBackend::get_many) so far.
+    pub fn batches(&self) -> usize {
+        self.batches.get()
+    }
+
+    /// The number of calls to [`has`](StorageBackend::has) so far.
+    pub fn checks(&self) -> usize {
+        self.checks.get()
+    }
+
+    /// Reset all the counters.
+    pub fn reset(&self) {
+        self.gets.set(0);
+        self.batches.set(0);
+        self.checks.set(0);
+    }
+}
+
+impl StorageBackend for CountingStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.gets.set(self.gets.get() + 1);
+        self.inner.get(key)
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        self.checks.set(self.checks.get() + 1);
+        self.inner.has(key)
+    }
+
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        self.batches.set(self.batches.get() + 1);
+        self.inner.get_many(keys)
+    }
+}
+
+impl StorageBackendMut for CountingStorage {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.inner.set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.inner.remove(key)
+    }
+
+    fn remove_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>) {
+        self.inner.remove_range(start, end)
+    }
+}
+
+impl IterableStorage for CountingStorage {
+    type KeysIterator<'a> = <TestStorage as IterableStorage>::KeysIterator<'a>;
+    type ValuesIterator<'a> = <TestStorage as IterableStorage>::ValuesIterator<'a>;
+    type PairsIterator<'a> = <TestStorage as IterableStorage>::PairsIterator<'a>;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        self.inner.keys(start, end)
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        self.inner.values(start, end)
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        self.inner.pairs(start, end)
+    }
+}
+
+impl RevIterableStorage for CountingStorage {
+    type RevKeysIterator<'a> = <TestStorage as RevIterableStorage>::RevKeysIterator<'a>;
+    type RevValuesIterator<'a> = <TestStorage as RevIterableStorage>::RevValuesIterator<'a>;
+    type RevPairsIterator<'a> = <TestStorage as RevIterableStorage>::RevPairsIterator<'a>;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        self.inner.rev_keys(start, end)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        self.inner.rev_values(start, end)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        self.inner.rev_pairs(start, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storage_backend() {
+        // TODO: split this into smaller tests?
+
+        let mut storage = TestStorage::new();
+
+        storage.set(&[0], b"bar");
+        storage.set(&[1], b"baz");
+        storage.set(&[1, 0], b"qux");
+        storage.set(&[1, 1], b"quux");
+        storage.set(&[2], b"qux");
+
+        let keys: Vec<_> = storage.keys(None, None).collect();
+        assert_eq!(
+            keys,
+            vec![vec![0], vec![1], vec![1, 0], vec![1, 1], vec![2]]
+        );
+
+        let some_keys: Vec<_> = storage.keys(Some(&[1]), Some(&[2])).collect();
+        assert_eq!(some_keys, vec![vec![1], vec![1, 0], vec![1, 1]]);
+
+        let values: Vec<_> = storage.values(None, None).collect();
+        assert_eq!(
+            values.iter().collect::<Vec<_>>(),
+            vec![&b"bar"[..], b"baz", b"qux", b"quux", b"qux"]
+        );
+
+        let some_values: Vec<_> = storage.values(Some(&[1]), Some(&[2])).collect();
+        assert_eq!(
+            some_values.iter().collect::<Vec<_>>(),
+            vec![&b"baz"[..], b"qux", b"quux"]
+        );
+
+        let pairs: Vec<_> = storage.pairs(None, None).collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (vec![0], b"bar".to_vec()),
+                (vec![1], b"baz".to_vec()),
+                (vec![1, 0], b"qux".to_vec()),
+                (vec![1, 1], b"quux".to_vec()),
+                (vec![2], b"qux".to_vec()),
+            ]
+        );
+
+        let some_pairs: Vec<_> = storage.pairs(Some(&[1]), Some(&[2])).collect();
+        assert_eq!(
+            some_pairs,
+            vec![
+                (vec![1], b"baz".to_vec()),
+                (vec![1, 0], b"qux".to_vec()),
+                (vec![1, 1], b"quux".to_vec()),
+            ]
+        );
+
+        let rev_keys: Vec<_> = storage.rev_keys(None, None).collect();
+        assert_eq!(
+            rev_keys,
+            vec![vec![2], vec![1, 1], vec![1, 0], vec![1], vec![0]]
+        );
+
+        let some_rev_keys: Vec<_> = storage.rev_keys(Some(&[1]), Some(&[2])).collect();
+        assert_eq!(some_rev_keys, vec![vec![1, 1], vec![1, 0], vec![1]]);
+
+        let rev_values: Vec<_> = storage.rev_values(None, None).collect();
+        assert_eq!(
+            rev_values.iter().collect::<Vec<_>>(),
+            vec![&b"qux"[..], b"quux", b"qux", b"baz", b"bar"]
+        );
+
+        let some_rev_values: Vec<_> = storage.rev_values(Some(&[1]), Some(&[2])).collect();
+        assert_eq!(
+            some_rev_values.iter().collect::<Vec<_>>(),
+            vec![&b"quux"[..], b"qux", b"baz"]
+        );
+
+        let rev_pairs: Vec<_> = storage.rev_pairs(None, None).collect();
+        assert_eq!(
+            rev_pairs,
+            vec![
+                (vec![2], b"qux".to_vec()),
+                (vec![1, 1], b"quux".to_vec()),
+                (vec![1, 0], b"qux".to_vec()),
+                (vec![1], b"baz".to_vec()),
+                (vec![0], b"bar".to_vec()),
+            ]
+        );
+
+        let some_rev_pairs: Vec<_> = storage.rev_pairs(Some(&[1]), Some(&[2])).collect();
+        assert_eq!(
+            some_rev_pairs,
+            vec![
+                (vec![1, 1], b"quux".to_vec()),
+                (vec![1, 0], b"qux".to_vec()),
+                (vec![1], b"baz".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn fail_next_get() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0], b"foo");
+
+        storage.fail_next_get();
+        assert_eq!(StorageBackend::get(&storage, &[1]), Some(vec![]));
+        assert_eq!(StorageBackend::get(&storage, &[1]), None);
+        assert_eq!(StorageBackend::get(&storage, &[0]), Some(b"foo".to_vec()));
+    }
+
+    #[test]
+    fn metadata() {
+        use storey_storage::StorageMut as _;
+
+        let mut storage = TestStorage::new();
+        storage.set_meta(&[0], b"meta");
+
+        assert_eq!(StorageBackend::get(&storage, &[0]), None);
+        assert_eq!(
+            StorageBackend::get(&storage, &[255, 0]),
+            Some(b"meta".to_vec())
+        );
+    }
+
+    #[test]
+    fn conformance() {
+        crate::conformance::check_iteration(&mut TestStorage::new());
+        crate::conformance::check_remove_range(&mut TestStorage::new());
+        crate::conformance::check_get_many(&mut TestStorage::new());
+        crate::conformance::check_get_many(&mut CountingStorage::new());
+    }
+}
@@ -0,0 +1,201 @@
+//! Checks that a storage backend behaves like the storage traits require.
+//!
+//! These are meant to be run against every backend (and backend wrapper) in the test suite of
+//! the crate implementing it. They panic on the first deviation found.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use storey_storage::{
+    IterableStorage, RemoveRangeStorage, RevIterableStorage, StorageBackend, StorageBackendMut,
+};
+
+type Pairs = Vec<(Vec<u8>, Vec<u8>)>;
+
+// keys chosen to have prefixes of each other, and to sit on both sides of the bounds below
+const KEYS: &[&[u8]] = &[
+    &[0],
+    &[1],
+    &[1, 0],
+    &[1, 0, 0],
+    &[1, 1],
+    &[1, 255],
+    &[2],
+    &[2, 0],
+    &[254, 255],
+    &[255],
+    &[255, 255],
+];
+
+const BOUNDS: &[Option<&[u8]>] = &[
+    None,
+    Some(&[]),
+    Some(&[0]),
+    Some(&[1]),
+    Some(&[1, 0]),
+    Some(&[1, 0, 0, 0]),
+    Some(&[1, 128]),
+    Some(&[2]),
+    Some(&[3]),
+    Some(&[255]),
+    Some(&[255, 255, 255]),
+];
+
+/// Check forward and reverse iteration over every combination of a set of bounds.
+///
+/// `storage` must be empty. It's populated with a fixed set of keys, and every combination of
+/// bounds (including inverted and empty ranges) is checked against a [`BTreeMap`]. For each
+/// range, [`keys`](IterableStorage::keys), [`values`](IterableStorage::values) and
+/// [`pairs`](IterableStorage::pairs) must agree, and the reverse methods must yield the same
+/// entries in the opposite order. The start bound is inclusive and the end bound exclusive, in
+/// both directions.
+///
+/// # Example
+/// ```
+/// use storey_testing::backend::TestStorage;
+/// use storey_testing::conformance::check_iteration;
+///
+/// check_iteration(&mut TestStorage::new());
+/// ```
+pub fn check_iteration<S>(storage: &mut S)
+where
+    S: StorageBackendMut + IterableStorage + RevIterableStorage,
+{
+    let mut model = BTreeMap::new();
+    for (i, key) in KEYS.iter().enumerate() {
+        let value = vec![i as u8];
+        storage.set(key, &value);
+        model.insert(key.to_vec(), value);
+    }
+
+    for &start in BOUNDS {
+        for &end in BOUNDS {
+            let expected: Pairs = model
+                .iter()
+                .filter(|(k, _)| in_range(k, start, end))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+
+            let context = format!("start: {start:?}, end: {end:?}");
+            check(&context, storage.pairs(start, end).collect(), &expected);
+            check(
+                &context,
+                storage.keys(start, end).collect(),
+                &expected.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>(),
+            );
+            check(
+                &context,
+                storage.values(start, end).collect(),
+                &expected.iter().map(|(_, v)| v.clone()).collect::<Vec<_>>(),
+            );
+
+            let reversed: Pairs = expected.into_iter().rev().collect();
+            check(&context, storage.rev_pairs(start, end).collect(), &reversed);
+            check(
+                &context,
+                storage.rev_keys(start, end).collect(),
+                &reversed.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>(),
+            );
+            check(
+                &context,
+                storage.rev_values(start, end).collect(),
+                &reversed.iter().map(|(_, v)| v.clone()).collect::<Vec<_>>(),
+            );
+        }
+    }
+}
+
+/// Check range removal over every combination of a set of bounds.
+///
+/// `storage` must be empty. For each range, it's populated with a fixed set of keys, the range
+/// is removed, and the keys left are checked against a [`BTreeMap`] - a key must be removed if
+/// and only if iteration with the same bounds would yield it. The storage is emptied again
+/// afterwards.
+///
+/// # Example
+/// ```
+/// use storey_testing::backend::TestStorage;
+/// use storey_testing::conformance::check_remove_range;
+///
+/// check_remove_range(&mut TestStorage::new());
+/// ```
+pub fn check_remove_range<S: RemoveRangeStorage>(storage: &mut S) {
+    for &start in BOUNDS {
+        for &end in BOUNDS {
+            for (i, key) in KEYS.iter().enumerate() {
+                storage.set(key, &[i as u8]);
+            }
+
+            let expected: Vec<Vec<u8>> = KEYS
+                .iter()
+                .filter(|k| !in_range(k, start, end))
+                .map(|k| k.to_vec())
+                .collect();
+
+            let context = format!("start: {start:?}, end: {end:?}");
+            storage.remove_range(start, end);
+            check(&context, storage.keys(None, None).collect(), &expected);
+
+            storage.remove_range(None, None);
+            check(&context, storage.keys(None, None).collect(), &[]);
+        }
+    }
+}
+
+/// Check that [`get_many`](storey_storage::StorageBackend::get_many) agrees with point reads.
+///
+/// `storage` must be empty. It's populated with a fixed set of keys, then read in batches
+/// mixing present and missing keys, in varying orders and with duplicates. There must be one
+/// result per key, in the order of the keys.
+///
+/// # Example
+/// ```
+/// use storey_testing::backend::TestStorage;
+/// use storey_testing::conformance::check_get_many;
+///
+/// check_get_many(&mut TestStorage::new());
+/// ```
+pub fn check_get_many<S: StorageBackendMut + StorageBackend>(storage: &mut S) {
+    for (i, key) in KEYS.iter().enumerate() {
+        storage.set(key, &[i as u8]);
+    }
+
+    let batches: &[&[&[u8]]] = &[
+        &[],
+        KEYS,
+        &[&[1], &[3], &[1, 0], &[3], &[1]],
+        &[&[255, 255], &[0], &[255, 255], &[4], &[0]],
+        &[&[9], &[9], &[9]],
+    ];
+
+    for batch in batches {
+        let expected: Vec<_> = batch.iter().map(|key| storage.get(key)).collect();
+        check(
+            &format!("keys: {batch:?}"),
+            storage.get_many(batch),
+            &expected,
+        );
+    }
+
+    for key in KEYS {
+        storage.remove(key);
+    }
+}
+
+fn in_range(key: &[u8], start: Option<&[u8]>, end: Option<&[u8]>) -> bool {
+    if let Some(start) = start {
+        if key < start {
+            return false;
+        }
+    }
+    if let Some(end) = end {
+        if key >= end {
+            return false;
+        }
+    }
+    true
+}
+
+fn check<T: PartialEq + Debug>(context: &str, actual: Vec<T>, expected: &[T]) {
+    assert_eq!(actual, expected, "{context}");
+}
@@ -0,0 +1,143 @@
+//! A simple encoding for tests.
+
+use std::cell::Cell;
+use std::convert::Infallible;
+
+use storey_encoding::{Cover, DecodableWithImpl, EncodableWithImpl, Encoding};
+
+// An implementation of an encoding used for tests.
+//
+// In a real-life scenario, implementers of `EncodableWith` and `DecodableWith`
+// will usually provide a blanket implementation that delegates to some third-party
+// serialization/deserialization trait. We're imitating this a little here to make
+// sure this process works.
+
+/// An encoding for tests, storing `u64`s as 8 little-endian bytes.
+///
+/// # Example
+/// ```
+/// use storey_encoding::{DecodableWith as _, EncodableWith as _};
+/// use storey_testing::encoding::TestDecodeError;
+///
+/// assert_eq!(1u64.encode(), Ok(vec![1, 0, 0, 0, 0, 0, 0, 0]));
+/// assert_eq!(u64::decode(&[1, 2, 3]), Err(TestDecodeError::InvalidLength(3)));
+/// ```
+pub struct TestEncoding;
+
+impl Encoding for TestEncoding {
+    type DecodeError = TestDecodeError;
+    type EncodeError = Infallible;
+
+    const NAME: &'static str = "test";
+}
+
+/// An error decoding a value with [`TestEncoding`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum TestDecodeError {
+    #[error("invalid length, expected 8 bytes, got {0}")]
+    InvalidLength(usize),
+
+    #[error("injected decode failure")]
+    Injected,
+}
+
+thread_local! {
+    static FAIL_DECODE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Make the next decode with [`TestEncoding`] on the current thread fail.
+///
+/// The next value decoded fails with [`TestDecodeError::Injected`], no matter what the stored
+/// bytes are. Decoding after that behaves normally again.
+///
+/// # Example
+/// ```
+/// use storey_encoding::{DecodableWith as _, EncodableWith as _};
+/// use storey_testing::encoding::{fail_decode, TestDecodeError};
+///
+/// let bytes = 1u64.encode().unwrap();
+///
+/// fail_decode();
+/// assert_eq!(u64::decode(&bytes), Err(TestDecodeError::Injected));
+/// assert_eq!(u64::decode(&bytes), Ok(1));
+/// ```
+pub fn fail_decode() {
+    FAIL_DECODE.with(|fail| fail.set(true));
+}
+
+// This is how we would implement `EncodableWith` and `DecodableWith` for
+// `MyEncoding`, through a blanket implementation.
+
+impl<T> EncodableWithImpl<TestEncoding> for Cover<&T>
+where
+    T: MyTestEncoding,
+{
+    fn encode_impl(self) -> Result<Vec<u8>, <TestEncoding as Encoding>::EncodeError> {
+        self.0.my_encode()
+    }
+}
+
+impl<T> DecodableWithImpl<TestEncoding> for Cover<T>
+where
+    T: MyTestEncoding,
+{
+    fn decode_impl(data: &[u8]) -> Result<Self, <TestEncoding as Encoding>::DecodeError> {
+        if FAIL_DECODE.with(|fail| fail.replace(false)) {
+            return Err(TestDecodeError::Injected);
+        }
+
+        let value = T::my_decode(data)?;
+        Ok(Cover(value))
+    }
+}
+
+// Imagine `MyTestEncoding` is a third-party trait that we don't control.
+
+trait MyTestEncoding: Sized {
+    fn my_encode(&self) -> Result<Vec<u8>, Infallible>;
+    fn my_decode(data: &[u8]) -> Result<Self, TestDecodeError>;
+}
+
+impl MyTestEncoding for u64 {
+    fn my_encode(&self) -> Result<Vec<u8>, Infallible> {
+        Ok(self.to_le_bytes().to_vec())
+    }
+
+    fn my_decode(data: &[u8]) -> Result<Self, TestDecodeError> {
+        let bytes = data
+            .try_into()
+            .map_err(|_| TestDecodeError::InvalidLength(data.len()))?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use storey_encoding::{DecodableWith as _, EncodableWith as _};
+
+    use super::{fail_decode, TestDecodeError};
+
+    #[test]
+    fn encoding() {
+        assert_eq!(12u64.encode(), Ok(12u64.to_le_bytes().to_vec()));
+    }
+
+    #[test]
+    fn decoding() {
+        assert_eq!(<u64>::decode(&12u64.to_le_bytes()), Ok(12));
+        assert_eq!(
+            <u64>::decode(&[1, 2, 3]),
+            Err(TestDecodeError::InvalidLength(3))
+        );
+    }
+
+    #[test]
+    fn injected_failure() {
+        fail_decode();
+        assert_eq!(
+            <u64>::decode(&12u64.to_le_bytes()),
+            Err(TestDecodeError::Injected)
+        );
+        assert_eq!(<u64>::decode(&12u64.to_le_bytes()), Ok(12));
+    }
+}
@@ -0,0 +1,23 @@
+//! Test helpers for [`storey`](https://docs.rs/storey) and the crates built on it.
+//!
+//! This crate provides
+//! - [`TestStorage`](backend::TestStorage), an in-memory storage backend with forward and
+//!   reverse iteration, along with [`CountingStorage`](backend::CountingStorage), a variant
+//!   counting the reads made to it,
+//! - [`TestEncoding`](encoding::TestEncoding), a minimal encoding for `u64` values,
+//! - [`conformance`] checks that a backend implements the storage traits correctly, and
+//! - [`storey_container_tests!`], generating the tests every container should pass.
+//!
+//! The backend and the encoding can inject failures
+//! ([`TestStorage::fail_next_get`](backend::TestStorage::fail_next_get) and
+//! [`fail_decode`](encoding::fail_decode)), so that error paths can be tested too.
+//!
+//! It's meant to be used as a dev-dependency.
+
+pub mod backend;
+pub mod conformance;
+pub mod encoding;
+mod suite;
+
+#[doc(hidden)]
+pub use suite::__samples;
@@ -0,0 +1,451 @@
+/// Generates the tests every container type should pass, for one container.
+///
+/// Every invocation describes a container type, with functions generating sample keys and
+/// values from an index, and expands to a module of tests writing those samples to storage
+/// and checking what comes back: reads after writes, removal, iteration order, bounds, the raw
+/// keys the container uses, and the container nested in a map. The container is placed under
+/// the prefix `1`, with other data under `0` and `2` that it mustn't touch.
+///
+/// The kinds of containers are
+/// - `item`, for an `Item<V, E>`,
+/// - `column`, for a `Column<V, E>`,
+/// - `map`, for a `Map<K, Item<V, E>>`, and
+/// - `ordered_map`, for a `Map<K, Item<V, E>>` with an
+///   [`OrderPreservingKey`](https://docs.rs/storey/latest/storey/containers/trait.OrderPreservingKey.html),
+///   which adds tests of bounded iteration. The keys must then compare like their samples
+///   are ordered: `key(i) < key(j)` for `i < j`.
+///
+/// The key and value functions take a `u32` index and must return different samples for
+/// different indexes. The tests run against a fresh
+/// [`TestStorage`](crate::backend::TestStorage), or the storage given with `storage: ...`,
+/// evaluated once per test.
+///
+/// The generated code refers to `storey` by name, so the crate using the macro needs to depend
+/// on it. Custom containers mimicking the API of the `storey` ones can be tested the same way.
+///
+/// # Example
+/// ```
+/// use storey::containers::{Column, Item, Map};
+/// use storey_testing::encoding::TestEncoding;
+/// use storey_testing::storey_container_tests;
+///
+/// storey_container_tests!(item counter: Item<u64, TestEncoding>, value: u64::from);
+/// storey_container_tests!(column log: Column<u64, TestEncoding>, value: |i| u64::from(i) * 3);
+/// storey_container_tests! {
+///     map string_keys: Map<String, Item<u64, TestEncoding>>,
+///     key: |i| format!("key-{i}"),
+///     value: |i| u64::from(i) + 100,
+/// }
+/// storey_container_tests! {
+///     ordered_map int_keys: Map<u32, Item<u64, TestEncoding>>,
+///     key: |i| i * 1000,
+///     value: u64::from,
+///     storage: storey_testing::backend::TestStorage::new(),
+/// }
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! storey_container_tests {
+    (item $name:ident: $ty:ty, value: $value:expr $(, storage: $storage:expr)? $(,)?) => {
+        #[allow(unused_imports)]
+        mod $name {
+            use super::*;
+            use ::storey::storage::{IterableStorage as _, Storage as _, StorageMut as _};
+
+            #[test]
+            fn round_trip() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let value = $crate::__samples($value);
+                let item = <$ty>::new(1);
+
+                assert_eq!(item.access(&storage).get().unwrap(), None);
+                assert!(!item.access(&storage).exists());
+
+                item.access(&mut storage).set(&value(0)).unwrap();
+                assert_eq!(item.access(&storage).get().unwrap(), Some(value(0)));
+                assert!(item.access(&storage).exists());
+
+                item.access(&mut storage).set(&value(1)).unwrap();
+                assert_eq!(item.access(&storage).get().unwrap(), Some(value(1)));
+            }
+
+            #[test]
+            fn layout() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let value = $crate::__samples($value);
+                let item = <$ty>::new(1);
+
+                storage.set(&[0], b"before");
+                storage.set(&[2], b"after");
+                item.access(&mut storage).set(&value(0)).unwrap();
+
+                assert_eq!(
+                    storage.keys(None, None).collect::<::std::vec::Vec<_>>(),
+                    [vec![0], vec![1], vec![2]]
+                );
+                assert_eq!(storage.get(&[0]), Some(b"before".to_vec()));
+                assert_eq!(storage.get(&[2]), Some(b"after".to_vec()));
+            }
+
+            #[test]
+            fn nested() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let value = $crate::__samples($value);
+                let map = ::storey::containers::Map::<::std::string::String, $ty>::new(1);
+
+                map.access(&mut storage).entry_mut("a").set(&value(0)).unwrap();
+                map.access(&mut storage).entry_mut("b").set(&value(1)).unwrap();
+
+                assert_eq!(map.access(&storage).entry("a").get().unwrap(), Some(value(0)));
+                assert_eq!(map.access(&storage).entry("b").get().unwrap(), Some(value(1)));
+                assert_eq!(map.access(&storage).entry("c").get().unwrap(), None);
+                assert!(storage.has(&[&[1][..], &::storey::key!("a")].concat()));
+            }
+        }
+    };
+
+    (column $name:ident: $ty:ty, value: $value:expr $(, storage: $storage:expr)? $(,)?) => {
+        #[allow(unused_imports)]
+        mod $name {
+            use super::*;
+            use ::storey::containers::{BoundedIterableAccessor as _, IterableAccessor as _};
+            use ::storey::storage::{IterableStorage as _, Storage as _, StorageMut as _};
+
+            const SAMPLES: u32 = 8;
+
+            #[test]
+            fn round_trip() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let value = $crate::__samples($value);
+                let column = <$ty>::new(1);
+
+                assert!(column.access(&storage).is_empty().unwrap());
+                for i in 0..SAMPLES {
+                    assert_eq!(column.access(&mut storage).push(&value(i)).unwrap(), i);
+                }
+                assert_eq!(column.access(&storage).len().unwrap(), SAMPLES);
+                for i in 0..SAMPLES {
+                    assert_eq!(column.access(&storage).get(i).unwrap(), Some(value(i)));
+                }
+                assert_eq!(column.access(&storage).get(SAMPLES).unwrap(), None);
+
+                column.access(&mut storage).update(0, &value(SAMPLES)).unwrap();
+                assert_eq!(column.access(&storage).get(0).unwrap(), Some(value(SAMPLES)));
+            }
+
+            #[test]
+            fn remove() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let value = $crate::__samples($value);
+                let column = <$ty>::new(1);
+
+                for i in 0..SAMPLES {
+                    column.access(&mut storage).push(&value(i)).unwrap();
+                }
+                column.access(&mut storage).remove(1).unwrap();
+                column.access(&mut storage).remove(SAMPLES - 1).unwrap();
+
+                assert_eq!(column.access(&storage).get(1).unwrap(), None);
+                assert_eq!(column.access(&storage).len().unwrap(), SAMPLES - 2);
+
+                // indexes aren't reused
+                assert_eq!(column.access(&mut storage).push(&value(0)).unwrap(), SAMPLES);
+            }
+
+            #[test]
+            fn iteration() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let value = $crate::__samples($value);
+                let column = <$ty>::new(1);
+
+                for i in 0..SAMPLES {
+                    column.access(&mut storage).push(&value(i)).unwrap();
+                }
+                column.access(&mut storage).remove(2).unwrap();
+
+                let expected: ::std::vec::Vec<_> =
+                    (0..SAMPLES).filter(|&i| i != 2).map(|i| (i, value(i))).collect();
+                let access = column.access(&storage);
+                assert_eq!(access.pairs().collect::<Result<Vec<_>, _>>().unwrap(), expected);
+                assert_eq!(
+                    access.keys().collect::<Result<Vec<_>, _>>().unwrap(),
+                    expected.iter().map(|(i, _)| *i).collect::<Vec<_>>()
+                );
+                assert_eq!(
+                    access.values().collect::<Result<Vec<_>, _>>().unwrap(),
+                    expected.into_iter().map(|(_, v)| v).collect::<Vec<_>>()
+                );
+            }
+
+            #[test]
+            fn bounds() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let value = $crate::__samples($value);
+                let column = <$ty>::new(1);
+
+                for i in 0..SAMPLES {
+                    column.access(&mut storage).push(&value(i)).unwrap();
+                }
+
+                let access = column.access(&storage);
+                assert_eq!(
+                    access
+                        .bounded_keys(Some(2u32), Some(5u32))
+                        .collect::<Result<Vec<_>, _>>()
+                        .unwrap(),
+                    [2, 3, 4]
+                );
+                assert_eq!(
+                    access
+                        .bounded_values(Some(SAMPLES - 1), None::<u32>)
+                        .collect::<Result<Vec<_>, _>>()
+                        .unwrap(),
+                    [value(SAMPLES - 1)]
+                );
+                assert_eq!(access.bounded_keys(Some(5u32), Some(5u32)).count(), 0);
+            }
+
+            #[test]
+            fn layout() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let value = $crate::__samples($value);
+                let column = <$ty>::new(1);
+
+                storage.set(&[0], b"before");
+                storage.set(&[2], b"after");
+                for i in 0..SAMPLES {
+                    column.access(&mut storage).push(&value(i)).unwrap();
+                }
+
+                // entries under their big-endian index, the metadata being kept apart
+                for i in 0..SAMPLES {
+                    assert!(storage.has(&[&[1][..], &i.to_be_bytes()].concat()));
+                }
+                assert!(storage
+                    .keys(Some(&[1]), Some(&[2]))
+                    .all(|key| key.len() == 5));
+                assert_eq!(storage.get(&[0]), Some(b"before".to_vec()));
+                assert_eq!(storage.get(&[2]), Some(b"after".to_vec()));
+            }
+
+            #[test]
+            fn nested() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let value = $crate::__samples($value);
+                let map = ::storey::containers::Map::<::std::string::String, $ty>::new(1);
+
+                map.access(&mut storage).entry_mut("a").push(&value(0)).unwrap();
+                map.access(&mut storage).entry_mut("a").push(&value(1)).unwrap();
+                map.access(&mut storage).entry_mut("b").push(&value(2)).unwrap();
+
+                assert_eq!(map.access(&storage).entry("a").len().unwrap(), 2);
+                assert_eq!(map.access(&storage).entry("b").len().unwrap(), 1);
+                assert_eq!(
+                    map.access(&storage).pairs().collect::<Result<Vec<_>, _>>().unwrap(),
+                    [
+                        (("a".to_string(), 0), value(0)),
+                        (("a".to_string(), 1), value(1)),
+                        (("b".to_string(), 0), value(2)),
+                    ]
+                );
+            }
+        }
+    };
+
+    (map $name:ident: $ty:ty, key: $key:expr, value: $value:expr $(, storage: $storage:expr)? $(,)?) => {
+        $crate::__map_tests!($name: $ty, key: $key, value: $value, storage: [$($storage)?], {});
+    };
+
+    (ordered_map $name:ident: $ty:ty, key: $key:expr, value: $value:expr $(, storage: $storage:expr)? $(,)?) => {
+        $crate::__map_tests!($name: $ty, key: $key, value: $value, storage: [$($storage)?], {
+            #[test]
+            fn bounds() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let (key, value) = ($crate::__samples($key), $crate::__samples($value));
+                let map = <$ty>::new(1);
+
+                for i in 0..SAMPLES {
+                    map.access(&mut storage).entry_mut(&key(i)).set(&value(i)).unwrap();
+                }
+
+                let access = map.access(&storage);
+                let keys = |start: Option<u32>, end: Option<u32>| {
+                    access
+                        .bounded_keys(start.map(&key), end.map(&key))
+                        .map(|k| k.map(|(k, ())| k))
+                        .collect::<Result<Vec<_>, _>>()
+                        .unwrap()
+                };
+                assert_eq!(keys(Some(2), Some(5)), [key(2), key(3), key(4)]);
+                assert_eq!(keys(None, Some(2)), [key(0), key(1)]);
+                assert_eq!(keys(Some(SAMPLES - 2), None), [key(SAMPLES - 2), key(SAMPLES - 1)]);
+                assert!(keys(Some(3), Some(3)).is_empty());
+                assert!(keys(Some(5), Some(3)).is_empty());
+
+                assert_eq!(
+                    access
+                        .bounded_values(Some(key(1)), Some(key(3)))
+                        .collect::<Result<Vec<_>, _>>()
+                        .unwrap(),
+                    [value(1), value(2)]
+                );
+            }
+        });
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __map_tests {
+    ($name:ident: $ty:ty, key: $key:expr, value: $value:expr, storage: [$($storage:expr)?], {
+        $($extra:tt)*
+    }) => {
+        #[allow(unused_imports)]
+        mod $name {
+            use super::*;
+            use ::storey::containers::{BoundedIterableAccessor as _, IterableAccessor as _};
+            use ::storey::storage::{IterableStorage as _, Storage as _, StorageMut as _};
+
+            const SAMPLES: u32 = 8;
+
+            #[test]
+            fn round_trip() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let (key, value) = ($crate::__samples($key), $crate::__samples($value));
+                let map = <$ty>::new(1);
+
+                for i in 0..SAMPLES {
+                    assert_eq!(map.access(&storage).entry(&key(i)).get().unwrap(), None);
+                    map.access(&mut storage).entry_mut(&key(i)).set(&value(i)).unwrap();
+                }
+                for i in 0..SAMPLES {
+                    assert_eq!(map.access(&storage).entry(&key(i)).get().unwrap(), Some(value(i)));
+                }
+                assert_eq!(map.access(&storage).entry(&key(SAMPLES)).get().unwrap(), None);
+
+                map.access(&mut storage).entry_mut(&key(0)).set(&value(SAMPLES)).unwrap();
+                assert_eq!(
+                    map.access(&storage).entry(&key(0)).get().unwrap(),
+                    Some(value(SAMPLES))
+                );
+                assert_eq!(map.access(&storage).entry(&key(1)).get().unwrap(), Some(value(1)));
+            }
+
+            #[test]
+            fn remove() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let (key, value) = ($crate::__samples($key), $crate::__samples($value));
+                let map = <$ty>::new(1);
+
+                storage.set(&[0], b"before");
+                storage.set(&[2], b"after");
+                for i in 0..SAMPLES {
+                    map.access(&mut storage).entry_mut(&key(i)).set(&value(i)).unwrap();
+                }
+                map.access(&mut storage).clear();
+
+                for i in 0..SAMPLES {
+                    assert_eq!(map.access(&storage).entry(&key(i)).get().unwrap(), None);
+                }
+                assert_eq!(map.access(&storage).keys().count(), 0);
+                assert_eq!(storage.get(&[0]), Some(b"before".to_vec()));
+                assert_eq!(storage.get(&[2]), Some(b"after".to_vec()));
+            }
+
+            #[test]
+            fn iteration() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let (key, value) = ($crate::__samples($key), $crate::__samples($value));
+                let map = <$ty>::new(1);
+
+                for i in (0..SAMPLES).rev() {
+                    map.access(&mut storage).entry_mut(&key(i)).set(&value(i)).unwrap();
+                }
+
+                // in the order of the raw keys, whatever the order of the writes
+                let mut order: ::std::vec::Vec<u32> = (0..SAMPLES).collect();
+                order.sort_by_key(|&i| ::storey::key!(&key(i)));
+
+                let access = map.access(&storage);
+                assert_eq!(
+                    access.pairs().collect::<Result<Vec<_>, _>>().unwrap(),
+                    order.iter().map(|&i| ((key(i), ()), value(i))).collect::<Vec<_>>()
+                );
+                assert_eq!(
+                    access.keys().collect::<Result<Vec<_>, _>>().unwrap(),
+                    order.iter().map(|&i| (key(i), ())).collect::<Vec<_>>()
+                );
+                assert_eq!(
+                    access.values().collect::<Result<Vec<_>, _>>().unwrap(),
+                    order.iter().map(|&i| value(i)).collect::<Vec<_>>()
+                );
+            }
+
+            #[test]
+            fn layout() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let (key, value) = ($crate::__samples($key), $crate::__samples($value));
+                let map = <$ty>::new(1);
+
+                for i in 0..SAMPLES {
+                    map.access(&mut storage).entry_mut(&key(i)).set(&value(i)).unwrap();
+                }
+
+                let mut expected: ::std::vec::Vec<_> = (0..SAMPLES)
+                    .map(|i| [&[1][..], &::storey::key!(&key(i))].concat())
+                    .collect();
+                expected.sort();
+                assert_eq!(storage.keys(None, None).collect::<Vec<_>>(), expected);
+            }
+
+            #[test]
+            fn nested() {
+                let mut storage = $crate::__storage!($($storage)?);
+                let (key, value) = ($crate::__samples($key), $crate::__samples($value));
+                let map = ::storey::containers::Map::<::std::string::String, $ty>::new(1);
+
+                for i in 0..SAMPLES {
+                    let outer = if i % 2 == 0 { "even" } else { "odd" };
+                    map.access(&mut storage)
+                        .entry_mut(outer)
+                        .entry_mut(&key(i))
+                        .set(&value(i))
+                        .unwrap();
+                }
+
+                for i in 0..SAMPLES {
+                    let (outer, other) = if i % 2 == 0 { ("even", "odd") } else { ("odd", "even") };
+                    let access = map.access(&storage);
+                    assert_eq!(access.entry(outer).entry(&key(i)).get().unwrap(), Some(value(i)));
+                    assert_eq!(access.entry(other).entry(&key(i)).get().unwrap(), None);
+                    assert!(storage.has(
+                        &[&[1][..], &::storey::key!(outer), &::storey::key!(&key(i))].concat()
+                    ));
+                }
+                assert_eq!(
+                    map.access(&storage).entry("odd").keys().count(),
+                    SAMPLES as usize / 2
+                );
+            }
+
+            $($extra)*
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __storage {
+    () => {
+        $crate::backend::TestStorage::new()
+    };
+    ($storage:expr) => {
+        $storage
+    };
+}
+
+/// Pins down the argument type of a sample generator, for closures like `|i| i * 2`.
+#[doc(hidden)]
+pub fn __samples<T>(f: impl Fn(u32) -> T) -> impl Fn(u32) -> T {
+    f
+}
@@ -0,0 +1,86 @@
+//! Iterating over a `TestStorage` only copies out the entries that are actually iterated over.
+//!
+//! This counts the bytes allocated on the test thread while taking a few entries out of a
+//! large map. Copying the whole map (or the whole range) up front would allocate megabytes.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use storey_storage::{IterableStorage as _, RevIterableStorage as _, StorageBackendMut as _};
+use storey_testing::backend::TestStorage;
+
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.with(|allocated| allocated.set(allocated.get() + layout.size()));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// The number of bytes allocated on this thread while running `f`.
+fn allocated_by<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATED.with(Cell::get);
+    let result = f();
+    (result, ALLOCATED.with(Cell::get) - before)
+}
+
+const ENTRIES: u32 = 100_000;
+const VALUE_LEN: usize = 256;
+
+// Ten entries take up ten keys and values, plus the vector they're collected into. Anything
+// proportional to the size of the map is orders of magnitude above this.
+const BUDGET: usize = 16 * 1024;
+
+#[test]
+fn only_the_requested_slice_is_copied() {
+    let mut storage = TestStorage::new();
+    for i in 0..ENTRIES {
+        storage.set(&i.to_be_bytes(), &[i as u8; VALUE_LEN]);
+    }
+
+    let middle = (ENTRIES / 2).to_be_bytes();
+
+    let (pairs, allocated) = allocated_by(|| {
+        storage
+            .pairs(Some(&middle), None)
+            .take(10)
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(pairs.len(), 10);
+    assert_eq!(pairs[0].0, middle);
+    assert!(allocated < BUDGET, "allocated {allocated} bytes");
+
+    let (keys, allocated) = allocated_by(|| storage.keys(None, None).take(10).collect::<Vec<_>>());
+    assert_eq!(keys[9], 9u32.to_be_bytes());
+    assert!(allocated < BUDGET, "allocated {allocated} bytes");
+
+    let (values, allocated) = allocated_by(|| {
+        storage
+            .rev_values(None, Some(&middle))
+            .take(10)
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(values[0], [(ENTRIES / 2 - 1) as u8; VALUE_LEN]);
+    assert!(allocated < BUDGET, "allocated {allocated} bytes");
+
+    let (rev_pairs, allocated) =
+        allocated_by(|| storage.rev_pairs(None, None).take(10).collect::<Vec<_>>());
+    assert_eq!(rev_pairs[0].0, (ENTRIES - 1).to_be_bytes());
+    assert!(allocated < BUDGET, "allocated {allocated} bytes");
+
+    // an iterator that's created, but never advanced, doesn't copy anything
+    let (_, allocated) = allocated_by(|| drop(storage.pairs(None, None)));
+    assert!(allocated < 64, "allocated {allocated} bytes");
+}
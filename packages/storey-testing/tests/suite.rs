@@ -0,0 +1,29 @@
+//! The generated container tests, run against the `storey` containers.
+
+use storey::containers::{Column, Item, Map};
+use storey_testing::backend::TestStorage;
+use storey_testing::encoding::TestEncoding;
+use storey_testing::storey_container_tests;
+
+storey_container_tests!(item item: Item<u64, TestEncoding>, value: |i| u64::from(i) + 1);
+
+storey_container_tests!(column column: Column<u64, TestEncoding>, value: |i| u64::from(i) * 7);
+
+storey_container_tests! {
+    map string_keys: Map<String, Item<u64, TestEncoding>>,
+    key: |i| "k".repeat(i as usize + 1),
+    value: |i| u64::from(i) << 32,
+}
+
+storey_container_tests! {
+    ordered_map int_keys: Map<u32, Item<u64, TestEncoding>>,
+    key: |i| i * 300,
+    value: u64::from,
+}
+
+storey_container_tests! {
+    ordered_map signed_keys: Map<i64, Item<u64, TestEncoding>>,
+    key: |i| i64::from(i) - 4,
+    value: u64::from,
+    storage: TestStorage::new(),
+}
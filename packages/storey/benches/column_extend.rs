@@ -0,0 +1,94 @@
+//! Compares appending many values to a column one by one with `push` against doing it in a
+//! single `extend` call.
+//!
+//! Besides timing both, this prints the number of heap allocations each approach makes, since
+//! reusing the encoding buffer is what `extend` is about.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use storey::containers::Column;
+use storey::encoding::RawEncoding;
+use storey_testing::backend::TestStorage;
+
+const ENTRIES: usize = 10_000;
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+type Rows = Column<Vec<u8>, RawEncoding>;
+
+fn values() -> Vec<Vec<u8>> {
+    (0..ENTRIES)
+        .map(|i| format!("row #{i:05} of the benchmark column").into_bytes())
+        .collect()
+}
+
+fn push_all(storage: &mut TestStorage, values: &[Vec<u8>]) {
+    let column = Rows::new(0);
+    let mut access = column.access(storage);
+
+    for value in values {
+        access.push(value).unwrap();
+    }
+}
+
+fn extend_all(storage: &mut TestStorage, values: &[Vec<u8>]) {
+    let column = Rows::new(0);
+    column.access(storage).extend(values).unwrap();
+}
+
+fn count_allocations(f: impl FnOnce(&mut TestStorage, &[Vec<u8>])) -> usize {
+    let mut storage = TestStorage::new();
+    let values = values();
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f(&mut storage, &values);
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn column_extend(c: &mut Criterion) {
+    println!(
+        "allocations for {ENTRIES} entries: push {}, extend {}",
+        count_allocations(push_all),
+        count_allocations(extend_all)
+    );
+
+    let mut group = c.benchmark_group("column_10k");
+
+    group.bench_function("push", |b| {
+        b.iter_batched(
+            || (TestStorage::new(), values()),
+            |(mut storage, values)| push_all(&mut storage, &values),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("extend", |b| {
+        b.iter_batched(
+            || (TestStorage::new(), values()),
+            |(mut storage, values)| extend_all(&mut storage, &values),
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, column_extend);
+criterion_main!(benches);
@@ -0,0 +1,105 @@
+//! Reads (and writes) through maps nested three levels deep.
+//!
+//! Every level of nesting is a storage branch over the one above it. This prints the number
+//! of heap allocations a single read and a single write make, since composing the full key
+//! at every level would cost one allocation per level.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use storey::containers::{Item, Map};
+use storey::encoding::FixedIntEncoding;
+use storey_testing::backend::TestStorage;
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+type Nested = Map<u32, Map<u32, Map<u32, Item<u64, FixedIntEncoding>>>>;
+
+const NESTED: Nested = Map::new(0);
+
+fn seeded() -> TestStorage {
+    let mut storage = TestStorage::new();
+    for a in 0..10 {
+        for b in 0..10 {
+            for c in 0..10 {
+                NESTED
+                    .access(&mut storage)
+                    .entry_mut(&a)
+                    .entry_mut(&b)
+                    .entry_mut(&c)
+                    .set(&u64::from(a * 100 + b * 10 + c))
+                    .unwrap();
+            }
+        }
+    }
+    storage
+}
+
+fn read(storage: &TestStorage, a: u32, b: u32, c: u32) -> Option<u64> {
+    NESTED
+        .access(storage)
+        .entry(&a)
+        .entry(&b)
+        .entry(&c)
+        .get()
+        .unwrap()
+}
+
+fn write(storage: &mut TestStorage, a: u32, b: u32, c: u32) {
+    NESTED
+        .access(storage)
+        .entry_mut(&a)
+        .entry_mut(&b)
+        .entry_mut(&c)
+        .set(&7)
+        .unwrap();
+}
+
+fn count_allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn nested_read(c: &mut Criterion) {
+    let mut storage = seeded();
+
+    println!(
+        "allocations for a read 3 maps deep: {}, for a write: {}",
+        count_allocations(|| {
+            black_box(read(&storage, 3, 4, 5));
+        }),
+        count_allocations(|| write(&mut storage, 3, 4, 5)),
+    );
+
+    let mut group = c.benchmark_group("nested_maps");
+
+    group.bench_function("read", |bench| {
+        bench.iter(|| read(&storage, black_box(3), black_box(4), black_box(5)))
+    });
+    group.bench_function("write", |bench| {
+        bench.iter(|| write(&mut storage, black_box(3), black_box(4), black_box(5)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, nested_read);
+criterion_main!(benches);
@@ -0,0 +1,107 @@
+//! Stored values never make the built-in encodings panic, whatever the bytes.
+
+#![no_main]
+
+use std::collections::BTreeMap;
+
+use libfuzzer_sys::fuzz_target;
+use serde::{Deserialize, Serialize};
+use storey::encoding::{
+    BincodeEncoding, BorrowDecodableWith, CanonicalCborEncoding, CborEncoding, Checksummed, Cipher,
+    Compressed, DecodableWith, Encoding, Encrypted, FixedIntEncoding, JsonEncoding,
+    MsgPackEncoding, PostcardEncoding, ProstEncoding, RawEncoding, Versioned, VersionedDecodeError,
+    VersionedValue,
+};
+
+#[derive(Serialize, Deserialize)]
+struct Record {
+    owner: String,
+    balances: BTreeMap<String, u128>,
+    tags: Vec<Option<i16>>,
+    frozen: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordV1 {
+    owner: String,
+}
+
+impl VersionedValue<MsgPackEncoding> for Record {
+    const VERSION: u8 = 2;
+
+    fn upgrade(
+        version: u8,
+        payload: &[u8],
+    ) -> Result<Self, VersionedDecodeError<<MsgPackEncoding as Encoding>::DecodeError>> {
+        match version {
+            1 => {
+                let old: RecordV1 = DecodableWith::<MsgPackEncoding>::decode(payload)
+                    .map_err(|error| VersionedDecodeError::Decode { version, error })?;
+                Ok(Record {
+                    owner: old.owner,
+                    balances: BTreeMap::new(),
+                    tags: Vec::new(),
+                    frozen: false,
+                })
+            }
+            _ => Err(VersionedDecodeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+/// A stand-in cipher, so that the framing of encrypted values gets fuzzed.
+struct Plain;
+
+impl Cipher for Plain {
+    const NONCE_LEN: usize = 12;
+    const ASSOCIATED_DATA: &'static [u8] = b"fuzz";
+
+    fn encrypt(_: &[u8], _: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        plaintext.to_vec()
+    }
+
+    fn decrypt(_: &[u8], _: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>> {
+        Some(ciphertext.to_vec())
+    }
+}
+
+/// Decode `data` as a `T`, error message included.
+fn decode<E: Encoding, T: DecodableWith<E>>(data: &[u8]) {
+    if let Err(err) = T::decode(data) {
+        let _ = err.to_string();
+    }
+}
+
+fn decode_borrowed<E: Encoding, T: BorrowDecodableWith<E>>(data: &[u8]) {
+    if let Err(err) = T::decode_borrowed(data) {
+        let _ = err.to_string();
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    decode::<FixedIntEncoding, u64>(data);
+    decode::<FixedIntEncoding, (u8, String, Option<i32>)>(data);
+    decode::<FixedIntEncoding, Vec<u8>>(data);
+    decode::<FixedIntEncoding, bool>(data);
+    decode::<RawEncoding, String>(data);
+    decode::<RawEncoding, Vec<u8>>(data);
+
+    decode::<Checksummed<FixedIntEncoding>, u64>(data);
+    decode::<Compressed<RawEncoding, 64>, String>(data);
+    decode::<Compressed<MsgPackEncoding, 64>, Record>(data);
+    decode::<Encrypted<FixedIntEncoding, Plain>, (u32, String)>(data);
+    decode::<Versioned<MsgPackEncoding>, Record>(data);
+
+    decode::<JsonEncoding, Record>(data);
+    decode::<MsgPackEncoding, Record>(data);
+    decode::<CborEncoding, Record>(data);
+    decode::<CanonicalCborEncoding, Record>(data);
+    decode::<BincodeEncoding, Record>(data);
+    decode::<PostcardEncoding, Record>(data);
+    decode::<ProstEncoding, String>(data);
+    decode::<ProstEncoding, u64>(data);
+
+    decode_borrowed::<JsonEncoding, String>(data);
+    decode_borrowed::<MsgPackEncoding, String>(data);
+    decode_borrowed::<BincodeEncoding, Vec<u8>>(data);
+});
@@ -0,0 +1,22 @@
+//! Raw keys of items and columns never make decoding panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use storey::containers::{Column, Item, Storable};
+use storey::encoding::FixedIntEncoding;
+
+/// Decode `key` the way iteration does, error message included.
+fn decode<S: Storable>(key: &[u8])
+where
+    S::KeyDecodeError: core::fmt::Display,
+{
+    if let Err(err) = S::decode_key_with_context(key) {
+        let _ = err.to_string();
+    }
+}
+
+fuzz_target!(|key: &[u8]| {
+    decode::<Item<u64, FixedIntEncoding>>(key);
+    decode::<Column<u64, FixedIntEncoding>>(key);
+});
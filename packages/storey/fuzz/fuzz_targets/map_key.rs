@@ -0,0 +1,45 @@
+//! Raw keys of maps, at several nesting depths and with the built-in key types, never make
+//! decoding panic: it's fed whatever bytes are in the storage when iterating.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use storey::containers::{Column, Hashed, Item, Key, Map, OwnedKey, Sha256, Storable};
+use storey::encoding::FixedIntEncoding;
+
+type Value = Item<u64, FixedIntEncoding>;
+
+#[derive(Key, OwnedKey)]
+struct Named {
+    _owner: u32,
+    #[storey(variable)]
+    _name: String,
+}
+
+/// Decode `key` the way iteration does, error message included.
+fn decode<S: Storable>(key: &[u8])
+where
+    S::KeyDecodeError: core::fmt::Display,
+{
+    if let Err(err) = S::decode_key_with_context(key) {
+        let _ = err.to_string();
+    }
+}
+
+fuzz_target!(|key: &[u8]| {
+    decode::<Map<String, Value>>(key);
+    decode::<Map<Vec<u8>, Value>>(key);
+    decode::<Map<u32, Value>>(key);
+    decode::<Map<i64, Value>>(key);
+    decode::<Map<usize, Value>>(key);
+    decode::<Map<[u16; 3], Value>>(key);
+    decode::<Map<(u8, String), Value>>(key);
+    decode::<Map<(u32, i16, Vec<u8>), Value>>(key);
+    decode::<Map<Hashed<str, Sha256>, Value>>(key);
+    decode::<Map<Named, Value>>(key);
+
+    decode::<Map<String, Map<u64, Value>>>(key);
+    decode::<Map<String, Column<u64, FixedIntEncoding>>>(key);
+    decode::<Map<u8, Map<String, Map<(u16, String), Value>>>>(key);
+    decode::<Map<Vec<u8>, Map<Vec<u8>, Map<Vec<u8>, Map<Vec<u8>, Value>>>>>(key);
+});
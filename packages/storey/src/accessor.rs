@@ -0,0 +1,331 @@
+//! Domain-named wrappers around accessors, with [`accessor!`].
+//!
+//! Contract code going through the generic accessor API ends up with `entry_mut(..).set(..)`
+//! chains all over the place. [`accessor!`] declares a newtype around the accessor of a
+//! top-level container, with methods named after what they mean to the contract and delegating
+//! to the accessor. The methods are plain `#[inline]` calls, so the wrapper costs nothing over
+//! using the accessor directly.
+//!
+//! Methods that need more than a delegation can go in an `impl` block of their own, built on
+//! the generated ones.
+//!
+//! # Example
+//! ```
+//! # use core::convert::Infallible;
+//! # use storey_testing::backend::TestStorage;
+//! # use storey_testing::encoding::{TestDecodeError, TestEncoding};
+//! use storey::containers::{Item, Map};
+//!
+//! const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new(0);
+//!
+//! storey::accessor! {
+//!     /// The token balances, by owner.
+//!     pub struct Balances(BALANCES: Map<String, Item<u64, TestEncoding>>) {
+//!         /// The balance of `owner`, if they ever held any tokens.
+//!         pub fn of = get;
+//!         pub fn holds = has;
+//!         fn adjust = update;
+//!     }
+//! }
+//!
+//! impl<S> Balances<&mut S>
+//! where
+//!     S: storey::storage::Storage + storey::storage::StorageMut,
+//! {
+//!     pub fn credit(
+//!         &mut self,
+//!         owner: &str,
+//!         amount: u64,
+//!     ) -> Result<u64, storey::Error<TestDecodeError, Infallible>> {
+//!         self.adjust(owner, |balance| balance.unwrap_or(0) + amount)
+//!     }
+//! }
+//!
+//! let mut storage = TestStorage::new();
+//!
+//! Balances::new(&mut storage).credit("alice", 5).unwrap();
+//! Balances::new(&mut storage).credit("alice", 2).unwrap();
+//!
+//! let balances = Balances::new(&storage);
+//! assert_eq!(balances.of("alice").unwrap(), Some(7));
+//! assert!(!balances.holds("bob"));
+//! ```
+//!
+//! [`accessor!`]: crate::accessor!
+
+use crate::containers::Item;
+use crate::encoding::Encoding;
+
+/// Declares a wrapper around the accessor of a top-level container, with methods delegating
+/// to it under other names. See the [module docs](mod@crate::accessor).
+///
+/// The wrapper is generic over the storage, like the accessor, and is created with `new`, from
+/// anything the container's `access` method takes. Every method is given as
+/// `fn name = operation;`, optionally with attributes and a visibility. The operations are
+/// those of the accessor:
+///
+/// | container        | operation | generated method                                         |
+/// |------------------|-----------|----------------------------------------------------------|
+/// | `Item<T, E>`     | `get`     | `fn(&self) -> Result<Option<T>, _>`                      |
+/// |                  | `exists`  | `fn(&self) -> bool`                                      |
+/// |                  | `set`     | `fn(&mut self, &T) -> Result<(), _>`                     |
+/// |                  | `update`  | `fn(&mut self, impl FnOnce(Option<T>) -> T) -> Result<T, _>` |
+/// | `Map<K, Item<T, E>>` | `get` | `fn(&self, &K) -> Result<Option<T>, _>`                  |
+/// |                  | `has`     | `fn(&self, &K) -> bool`                                  |
+/// |                  | `set`     | `fn(&mut self, &K, &T) -> Result<(), _>`                 |
+/// |                  | `update`  | `fn(&mut self, &K, impl FnOnce(Option<T>) -> T) -> Result<T, _>` |
+///
+/// Map keys are taken by reference to anything the key type can be borrowed as, like
+/// [`MapAccess::entry`](crate::containers::MapAccess::entry) does.
+///
+/// Since the macro only sees tokens, maps have to be written as `Map<..>`, and not through a
+/// path or an alias of their own. Any other type is taken to be an [`Item`].
+///
+/// An unknown operation doesn't compile:
+///
+/// ```compile_fail
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::Item;
+///
+/// const OWNER: Item<u64, TestEncoding> = Item::new(0);
+///
+/// storey::accessor! {
+///     struct Owner(OWNER: Item<u64, TestEncoding>) {
+///         fn clear = remove;
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! accessor {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident($container:path: Map<$key:ty, $item:ty>) {
+            $($(#[$method_attr:meta])* $method_vis:vis fn $method:ident = $op:ident;)*
+        }
+    ) => {
+        $crate::accessor!(@struct [$(#[$attr])*] $vis $name, $container, Map<$key, $item>);
+
+        $(
+            $crate::accessor!(@map $name [$key] [$item]
+                $(#[$method_attr])* $method_vis fn $method = $op
+            );
+        )*
+    };
+
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident($container:path: $item:ty) {
+            $($(#[$method_attr:meta])* $method_vis:vis fn $method:ident = $op:ident;)*
+        }
+    ) => {
+        $crate::accessor!(@struct [$(#[$attr])*] $vis $name, $container, $item);
+
+        $(
+            $crate::accessor!(@item $name [$item]
+                $(#[$method_attr])* $method_vis fn $method = $op
+            );
+        )*
+    };
+
+    (@struct [$($attr:tt)*] $vis:vis $name:ident, $container:path, $ty:ty) => {
+        $($attr)*
+        $vis struct $name<S>(
+            <$ty as $crate::containers::Storable>::Accessor<$crate::storage::StorageBranch<S>>,
+        );
+
+        impl<S> $name<S> {
+            /// Access the container through `storage`.
+            #[inline]
+            $vis fn new(storage: S) -> Self {
+                Self($container.access(storage))
+            }
+        }
+    };
+
+    (@item $name:ident [$item:ty] $(#[$attr:meta])* $vis:vis fn $method:ident = get) => {
+        impl<S> $name<S>
+        where
+            $crate::storage::StorageBranch<S>: $crate::storage::Storage,
+        {
+            $(#[$attr])*
+            #[inline]
+            $vis fn $method(
+                &self,
+            ) -> ::core::result::Result<
+                ::core::option::Option<$crate::accessor::Value<$item>>,
+                $crate::accessor::Error<$item>,
+            > {
+                self.0.get()
+            }
+        }
+    };
+
+    (@item $name:ident [$item:ty] $(#[$attr:meta])* $vis:vis fn $method:ident = exists) => {
+        impl<S> $name<S>
+        where
+            $crate::storage::StorageBranch<S>: $crate::storage::Storage,
+        {
+            $(#[$attr])*
+            #[inline]
+            $vis fn $method(&self) -> bool {
+                self.0.exists()
+            }
+        }
+    };
+
+    (@item $name:ident [$item:ty] $(#[$attr:meta])* $vis:vis fn $method:ident = set) => {
+        impl<S> $name<S>
+        where
+            $crate::storage::StorageBranch<S>: $crate::storage::StorageMut,
+        {
+            $(#[$attr])*
+            #[inline]
+            $vis fn $method(
+                &mut self,
+                value: &$crate::accessor::Value<$item>,
+            ) -> ::core::result::Result<(), $crate::accessor::Error<$item>> {
+                self.0.set(value)
+            }
+        }
+    };
+
+    (@item $name:ident [$item:ty] $(#[$attr:meta])* $vis:vis fn $method:ident = update) => {
+        impl<S> $name<S>
+        where
+            $crate::storage::StorageBranch<S>: $crate::storage::Storage + $crate::storage::StorageMut,
+        {
+            $(#[$attr])*
+            #[inline]
+            $vis fn $method(
+                &mut self,
+                f: impl ::core::ops::FnOnce(
+                    ::core::option::Option<$crate::accessor::Value<$item>>,
+                ) -> $crate::accessor::Value<$item>,
+            ) -> ::core::result::Result<
+                $crate::accessor::Value<$item>,
+                $crate::accessor::Error<$item>,
+            > {
+                self.0.update(f)
+            }
+        }
+    };
+
+    (@map $name:ident [$key:ty] [$item:ty] $(#[$attr:meta])* $vis:vis fn $method:ident = get) => {
+        impl<S> $name<S>
+        where
+            $crate::storage::StorageBranch<S>: $crate::storage::Storage,
+        {
+            $(#[$attr])*
+            #[inline]
+            $vis fn $method<Q>(
+                &self,
+                key: &Q,
+            ) -> ::core::result::Result<
+                ::core::option::Option<$crate::accessor::Value<$item>>,
+                $crate::accessor::Error<$item>,
+            >
+            where
+                $key: ::core::borrow::Borrow<Q>,
+                Q: $crate::containers::Key + ?Sized,
+            {
+                self.0.entry(key).get()
+            }
+        }
+    };
+
+    (@map $name:ident [$key:ty] [$item:ty] $(#[$attr:meta])* $vis:vis fn $method:ident = has) => {
+        impl<S> $name<S>
+        where
+            $crate::storage::StorageBranch<S>: $crate::storage::Storage,
+        {
+            $(#[$attr])*
+            #[inline]
+            $vis fn $method<Q>(&self, key: &Q) -> bool
+            where
+                $key: ::core::borrow::Borrow<Q>,
+                Q: $crate::containers::Key + ?Sized,
+            {
+                self.0.has(key)
+            }
+        }
+    };
+
+    (@map $name:ident [$key:ty] [$item:ty] $(#[$attr:meta])* $vis:vis fn $method:ident = set) => {
+        impl<S> $name<S>
+        where
+            $crate::storage::StorageBranch<S>: $crate::storage::StorageMut,
+        {
+            $(#[$attr])*
+            #[inline]
+            $vis fn $method<Q>(
+                &mut self,
+                key: &Q,
+                value: &$crate::accessor::Value<$item>,
+            ) -> ::core::result::Result<(), $crate::accessor::Error<$item>>
+            where
+                $key: ::core::borrow::Borrow<Q>,
+                Q: $crate::containers::Key + ?Sized,
+            {
+                self.0.entry_mut(key).set(value)
+            }
+        }
+    };
+
+    (@map $name:ident [$key:ty] [$item:ty] $(#[$attr:meta])* $vis:vis fn $method:ident = update) => {
+        impl<S> $name<S>
+        where
+            $crate::storage::StorageBranch<S>: $crate::storage::Storage + $crate::storage::StorageMut,
+        {
+            $(#[$attr])*
+            #[inline]
+            $vis fn $method<Q>(
+                &mut self,
+                key: &Q,
+                f: impl ::core::ops::FnOnce(
+                    ::core::option::Option<$crate::accessor::Value<$item>>,
+                ) -> $crate::accessor::Value<$item>,
+            ) -> ::core::result::Result<
+                $crate::accessor::Value<$item>,
+                $crate::accessor::Error<$item>,
+            >
+            where
+                $key: ::core::borrow::Borrow<Q>,
+                Q: $crate::containers::Key + ?Sized,
+            {
+                self.0.entry_mut(key).update(f)
+            }
+        }
+    };
+
+    (@item $name:ident [$item:ty] $(#[$attr:meta])* $vis:vis fn $method:ident = $op:ident) => {
+        ::core::compile_error!(::core::concat!(
+            "unknown operation `", ::core::stringify!($op), "` for an item, expected `get`, ",
+            "`exists`, `set` or `update`"
+        ));
+    };
+
+    (@map $name:ident [$key:ty] [$item:ty] $(#[$attr:meta])* $vis:vis fn $method:ident = $op:ident) => {
+        ::core::compile_error!(::core::concat!(
+            "unknown operation `", ::core::stringify!($op), "` for a map, expected `get`, ",
+            "`has`, `set` or `update`"
+        ));
+    };
+}
+
+/// The parts of an [`Item`] type, for the signatures generated by [`accessor!`].
+#[doc(hidden)]
+pub trait ItemParts {
+    type Value;
+    type Encoding: Encoding;
+}
+
+impl<T, E: Encoding> ItemParts for Item<T, E> {
+    type Value = T;
+    type Encoding = E;
+}
+
+#[doc(hidden)]
+pub type Value<I> = <I as ItemParts>::Value;
+
+#[doc(hidden)]
+pub type Error<I> = crate::error::ErrorFor<<I as ItemParts>::Encoding>;
@@ -0,0 +1,148 @@
+//! Catching top-level containers that share their prefix, at runtime.
+//!
+//! [`root!`](crate::root!) and [`declare_containers!`](crate::declare_containers!) reject
+//! duplicate prefixes at compile time, but only among the containers declared together.
+//! Containers declared in different modules or crates, or with other tools, can still end up
+//! sharing their keys. With the `prefix-audit` feature, every top-level [`Item`], [`Map`] and
+//! [`Column`] records its prefix and type in a global registry when it's accessed, and
+//! [`assert_no_prefix_collisions`] checks the registry at the end of a test run. Other code
+//! writing to storage directly (like another storage library) can [`register`] its prefixes
+//! too.
+//!
+//! Two prefixes collide if they're equal, or if one of them is a prefix of the other: the
+//! containers then write to keys the other one reads. A container accessed several times with
+//! the same prefix is only recorded once, so two containers of the same type with the same
+//! prefix can't be told apart, and don't count as a collision.
+//!
+//! Without the `prefix-audit` feature (and outside of this crate's own tests), nothing is
+//! recorded, and all the functions here do nothing.
+//!
+//! # Example
+//! ```
+//! # use storey_testing::backend::TestStorage;
+//! mod config {
+//!     # use storey_testing::encoding::TestEncoding;
+//!     use storey::containers::Item;
+//!
+//!     pub const CONFIG: Item<u64, TestEncoding> = Item::new(0);
+//! }
+//!
+//! mod balances {
+//!     # use storey_testing::encoding::TestEncoding;
+//!     use storey::containers::{Item, Map};
+//!
+//!     // the same prefix as `CONFIG`, by mistake
+//!     pub const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new(0);
+//! }
+//!
+//! let mut storage = TestStorage::new();
+//! config::CONFIG.access(&mut storage).set(&1).unwrap();
+//! balances::BALANCES.access(&mut storage).entry_mut("alice").set(&5).unwrap();
+//!
+//! # #[cfg(feature = "prefix-audit")]
+//! let result = std::panic::catch_unwind(storey::audit::assert_no_prefix_collisions);
+//! # #[cfg(feature = "prefix-audit")]
+//! assert!(result.is_err());
+//! ```
+//!
+//! [`Item`]: crate::containers::Item
+//! [`Map`]: crate::containers::Map
+//! [`Column`]: crate::containers::Column
+
+/// Record that the container `container` keeps its data under `prefix`.
+///
+/// The containers of this crate call this themselves. The caller's location is recorded, to
+/// tell where the container is used.
+#[cfg_attr(any(test, feature = "prefix-audit"), track_caller)]
+#[inline(always)]
+pub fn register(prefix: &[u8], container: &'static str) {
+    #[cfg(any(test, feature = "prefix-audit"))]
+    registry::register(prefix, container, std::panic::Location::caller());
+
+    #[cfg(not(any(test, feature = "prefix-audit")))]
+    let _ = (prefix, container);
+}
+
+/// Panic if two of the containers accessed so far have overlapping prefixes.
+///
+/// # Panics
+/// With a report listing every pair of colliding containers, with their prefixes and where
+/// they were first accessed.
+#[inline(always)]
+pub fn assert_no_prefix_collisions() {
+    #[cfg(any(test, feature = "prefix-audit"))]
+    if let Some(report) = registry::collisions() {
+        panic!("{report}");
+    }
+}
+
+/// Forget all the containers recorded so far, e.g. between tests of unrelated contracts.
+#[inline(always)]
+pub fn clear() {
+    #[cfg(any(test, feature = "prefix-audit"))]
+    registry::clear();
+}
+
+#[cfg(any(test, feature = "prefix-audit"))]
+mod registry {
+    use core::fmt::Write as _;
+    use std::panic::Location;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    struct Entry {
+        prefix: Vec<u8>,
+        container: &'static str,
+        location: &'static Location<'static>,
+    }
+
+    static REGISTRY: Mutex<Vec<Entry>> = Mutex::new(Vec::new());
+
+    fn registry() -> MutexGuard<'static, Vec<Entry>> {
+        // the registry stays consistent if a thread panics while holding it
+        REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn register(prefix: &[u8], container: &'static str, location: &'static Location<'static>) {
+        let mut registry = registry();
+        let known = registry
+            .iter()
+            .any(|entry| entry.prefix == prefix && entry.container == container);
+
+        if !known {
+            registry.push(Entry {
+                prefix: prefix.to_vec(),
+                container,
+                location,
+            });
+        }
+    }
+
+    /// A report of the colliding containers, if there are any.
+    pub fn collisions() -> Option<String> {
+        let mut registry = registry();
+        registry.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+
+        let mut report = String::new();
+        for (i, a) in registry.iter().enumerate() {
+            // sorted, so a prefix of `a`'s prefix can only come after it
+            for b in registry[i + 1..].iter() {
+                if b.prefix.starts_with(&a.prefix) {
+                    let _ = write!(report, "\n  {} and {}", describe(a), describe(b));
+                }
+            }
+        }
+
+        (!report.is_empty()).then(|| format!("containers with overlapping prefixes:{report}"))
+    }
+
+    fn describe(entry: &Entry) -> String {
+        format!(
+            "`{}` under {:?} (accessed at {})",
+            entry.container, entry.prefix, entry.location
+        )
+    }
+
+    pub fn clear() {
+        registry().clear();
+    }
+}
@@ -1,13 +1,19 @@
-use std::marker::PhantomData;
-
-use thiserror::Error;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
 
 use crate::encoding::Encoding;
 use crate::encoding::{DecodableWith, EncodableWith};
-use crate::storage::{IterableStorage, StorageBranch};
+use crate::error::{Error, ErrorFor};
+use crate::prefix::Prefix;
+#[cfg(feature = "iteration")]
+use crate::storage::IterableStorage;
+use crate::storage::StorageBranch;
 use crate::storage::{Storage, StorageMut};
 
-use super::{BoundFor, BoundedIterableAccessor, IterableAccessor, Storable};
+use super::Storable;
+#[cfg(feature = "iteration")]
+use super::{BoundFor, BoundedIterableAccessor, IterableAccessor};
 
 const META_LAST_IX: &[u8] = &[0];
 const META_LEN: &[u8] = &[1];
@@ -19,8 +25,8 @@ const META_LEN: &[u8] = &[1];
 ///
 /// # Example
 /// ```
-/// # use mocks::encoding::TestEncoding;
-/// # use mocks::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
 /// use storey::containers::Column;
 ///
 /// let mut storage = TestStorage::new();
@@ -35,8 +41,8 @@ const META_LEN: &[u8] = &[1];
 /// assert_eq!(access.get(2).unwrap(), None);
 /// ```
 pub struct Column<T, E> {
-    prefix: u8,
-    phantom: PhantomData<(T, E)>,
+    prefix: Prefix,
+    phantom: PhantomData<fn() -> (T, E)>,
 }
 
 impl<T, E> Column<T, E>
@@ -52,17 +58,71 @@ where
     /// The key provided here is used as a prefix for all keys the column itself might generate.
     pub const fn new(prefix: u8) -> Self {
         Self {
-            prefix,
+            prefix: Prefix::Byte(prefix),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Create a new column with a multi-byte prefix, typically built with
+    /// [`prefix!`](crate::prefix!).
+    ///
+    /// Like with [`new`](Self::new), it is the responsibility of the caller to ensure that the
+    /// prefix doesn't conflict with other keys in the storage, and in particular that it
+    /// doesn't start with the prefix of another container. See the [`prefix`](mod@crate::prefix)
+    /// module.
+    ///
+    /// A one-byte prefix makes the same column as [`new`](Self::new) does:
+    /// `Column::new_with_prefix(&[3])` and `Column::new(3)` are stored under the same keys.
+    ///
+    /// # Panics
+    /// If `prefix` is empty or starts with `255`, which is reserved for metadata. In a `const`,
+    /// that's a compile error.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// use storey::containers::{Column, Item};
+    ///
+    /// const HISTORY: Column<u64, TestEncoding> =
+    ///     Column::new_with_prefix(storey::prefix!("history"));
+    /// ```
+    pub const fn new_with_prefix(prefix: &'static [u8]) -> Self {
+        Self {
+            prefix: Prefix::bytes(prefix),
             phantom: PhantomData,
         }
     }
 
+    /// Create a column stored under the UTF-8 bytes of `prefix`, like the namespaces of
+    /// `cw-storage-plus`. This is the same as [`new_with_prefix`](Self::new_with_prefix) with
+    /// a [`prefix!`](crate::prefix!) built from the name, and the same caveats apply.
+    ///
+    /// # Panics
+    /// If `prefix` is empty. In a `const`, that's a compile error.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// use storey::containers::Column;
+    ///
+    /// const HISTORY: Column<u64, TestEncoding> = Column::new_str("history");
+    /// ```
+    pub const fn new_str(prefix: &'static str) -> Self {
+        Self::new_with_prefix(crate::prefix::named(prefix))
+    }
+
+    /// Create a column with a multi-byte prefix.
+    #[deprecated(note = "renamed to `new_with_prefix`")]
+    pub const fn with_prefix(prefix: &'static [u8]) -> Self {
+        Self::new_with_prefix(prefix)
+    }
+
     /// Acquire an accessor for this column.
     ///
     /// # Example
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::Column;
     ///
     /// // immutable accessor
@@ -75,8 +135,58 @@ where
     /// let column = Column::<u64, TestEncoding>::new(0);
     /// let mut access = column.access(&mut storage);
     /// ```
+    #[cfg_attr(any(test, feature = "prefix-audit"), track_caller)]
     pub fn access<S>(&self, storage: S) -> ColumnAccess<E, T, StorageBranch<S>> {
-        Self::access_impl(StorageBranch::new(storage, vec![self.prefix]))
+        crate::audit::register(self.prefix.as_slice(), core::any::type_name::<Self>());
+        Self::access_impl(StorageBranch::new(storage, self.prefix.as_slice().to_vec()))
+    }
+}
+
+impl<T, E> Column<T, E> {
+    /// The prefix the column is stored under.
+    ///
+    /// # Panics
+    /// If the column has a prefix longer than one byte. Use
+    /// [`prefix_bytes`](Self::prefix_bytes) for those.
+    pub const fn prefix(&self) -> u8 {
+        self.prefix.byte()
+    }
+
+    /// The bytes of the prefix the column is stored under, whichever constructor was used.
+    pub const fn prefix_bytes(&self) -> &[u8] {
+        self.prefix.as_slice()
+    }
+}
+
+impl<T, E> Clone for Column<T, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, E> Copy for Column<T, E> {}
+
+impl<T, E> PartialEq for Column<T, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.prefix_bytes() == other.prefix_bytes()
+    }
+}
+
+impl<T, E> Eq for Column<T, E> {}
+
+impl<T, E> fmt::Debug for Column<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Column")
+            .field("prefix", &self.prefix_bytes())
+            .finish()
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl<T: schemars::JsonSchema, E> Column<T, E> {
+    /// Describe the column as JSON Schema. See the [`schema`](crate::schema) module.
+    pub fn schema(&self) -> crate::schema::SchemaDocument {
+        crate::schema::SchemaDocument::new::<Self>(self.prefix_bytes())
     }
 }
 
@@ -118,9 +228,18 @@ pub struct ColumnKeyDecodeError;
 /// This type provides methods for interacting with the column in storage.
 pub struct ColumnAccess<E, T, S> {
     storage: S,
-    phantom: PhantomData<(E, T)>,
+    phantom: PhantomData<fn() -> (E, T)>,
+}
+
+impl<E, T, S: Storage> fmt::Debug for ColumnAccess<E, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ColumnAccess")
+            .field("prefix", &self.storage.key_prefix())
+            .finish()
+    }
 }
 
+#[cfg(feature = "iteration")]
 impl<E, T, S> IterableAccessor for ColumnAccess<E, T, S>
 where
     E: Encoding,
@@ -135,6 +254,7 @@ where
     }
 }
 
+#[cfg(feature = "iteration")]
 impl<E, T, S> BoundedIterableAccessor for ColumnAccess<E, T, S>
 where
     E: Encoding,
@@ -143,6 +263,7 @@ where
 {
 }
 
+#[cfg(feature = "iteration")]
 impl<T, E> BoundFor<Column<T, E>> for u32 {
     fn into_bytes(self) -> Vec<u8> {
         self.to_be_bytes().to_vec()
@@ -159,8 +280,8 @@ where
     ///
     /// # Example
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::Column;
     ///
     /// let mut storage = TestStorage::new();
@@ -171,10 +292,10 @@ where
     /// assert_eq!(access.get(0).unwrap(), Some(1337));
     /// assert_eq!(access.get(1).unwrap(), None);
     /// ```
-    pub fn get(&self, key: u32) -> Result<Option<T>, E::DecodeError> {
+    pub fn get(&self, key: u32) -> Result<Option<T>, ErrorFor<E>> {
         self.storage
             .get(&encode_ix(key))
-            .map(|bytes| T::decode(&bytes))
+            .map(|bytes| T::decode(&bytes).map_err(Error::Decode))
             .transpose()
     }
 
@@ -183,8 +304,8 @@ where
     ///
     /// # Example
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::Column;
     ///
     /// let mut storage = TestStorage::new();
@@ -197,14 +318,14 @@ where
     ///
     /// assert_eq!(access.len().unwrap(), 1);
     /// ```
-    pub fn len(&self) -> Result<u32, LenError> {
+    pub fn len(&self) -> Result<u32, ErrorFor<E>> {
         // TODO: bounds check + error handlinge
 
         self.storage
             .get_meta(META_LEN)
             .map(|bytes| {
                 if bytes.len() != 4 {
-                    Err(LenError::InconsistentState)
+                    Err(Error::InconsistentState)
                 } else {
                     Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
                 }
@@ -216,8 +337,8 @@ where
     ///
     /// # Example
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::Column;
     ///
     /// let mut storage = TestStorage::new();
@@ -230,7 +351,7 @@ where
     ///
     /// assert_eq!(access.is_empty().unwrap(), false);
     /// ```
-    pub fn is_empty(&self) -> Result<bool, LenError> {
+    pub fn is_empty(&self) -> Result<bool, ErrorFor<E>> {
         self.len().map(|len| len == 0)
     }
 }
@@ -259,8 +380,8 @@ where
     ///
     /// # Example
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::Column;
     ///
     /// let mut storage = TestStorage::new();
@@ -270,15 +391,15 @@ where
     /// access.push(&1337).unwrap();
     /// access.push(&42).unwrap();
     /// ```
-    pub fn push(&mut self, value: &T) -> Result<u32, PushError<E::EncodeError>> {
-        let bytes = value.encode()?;
+    pub fn push(&mut self, value: &T) -> Result<u32, ErrorFor<E>> {
+        let bytes = value.encode().map_err(Error::Encode)?;
 
         let ix = match self
             .storage
             .get_meta(META_LAST_IX)
             .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
         {
-            Some(last_ix) => last_ix.checked_add(1).ok_or(PushError::IndexOverflow)?,
+            Some(last_ix) => last_ix.checked_add(1).ok_or(Error::Overflow)?,
             None => 0,
         };
 
@@ -295,12 +416,77 @@ where
         Ok(ix)
     }
 
+    /// Append all values from an iterator to the end of the column.
+    ///
+    /// This is equivalent to calling [`push`](Self::push) for each value, but cheaper: a
+    /// single buffer is reused for encoding all of them, and the column's metadata is only
+    /// updated once.
+    ///
+    /// If encoding one of the values fails, the ones before it stay pushed.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
+    /// use storey::containers::Column;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let column = Column::<u64, TestEncoding>::new(0);
+    /// let mut access = column.access(&mut storage);
+    ///
+    /// access.push(&1337).unwrap();
+    /// access.extend(&[42, 9001]).unwrap();
+    ///
+    /// assert_eq!(access.get(2).unwrap(), Some(9001));
+    /// assert_eq!(access.len().unwrap(), 3);
+    /// ```
+    pub fn extend<'v, I>(&mut self, values: I) -> Result<(), ErrorFor<E>>
+    where
+        I: IntoIterator<Item = &'v T>,
+        T: 'v,
+    {
+        let last_ix = self
+            .storage
+            .get_meta(META_LAST_IX)
+            .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+        let len = self
+            .storage
+            .get_meta(META_LEN)
+            .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .unwrap_or(0);
+
+        let mut buf = Vec::new();
+        let mut next_ix = last_ix.map_or(Some(0), |ix| ix.checked_add(1));
+        let mut pushed = 0;
+
+        let result = values.into_iter().try_for_each(|value| {
+            let ix = next_ix.ok_or(Error::Overflow)?;
+
+            buf.clear();
+            value.encode_to(&mut buf).map_err(Error::Encode)?;
+            self.storage.set(&encode_ix(ix), &buf);
+
+            next_ix = ix.checked_add(1);
+            pushed += 1;
+            Ok(())
+        });
+
+        if pushed > 0 {
+            let last_ix = last_ix.map_or(pushed - 1, |ix| ix + pushed);
+            self.storage.set_meta(META_LAST_IX, &last_ix.to_be_bytes());
+            self.storage
+                .set_meta(META_LEN, &(len + pushed).to_be_bytes());
+        }
+
+        result
+    }
+
     /// Update the value associated with the given key.
     ///
     /// # Example
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::Column;
     ///
     /// let mut storage = TestStorage::new();
@@ -313,12 +499,10 @@ where
     /// access.update(0, &9001).unwrap();
     /// assert_eq!(access.get(0).unwrap(), Some(9001));
     /// ```
-    pub fn update(&mut self, key: u32, value: &T) -> Result<(), UpdateError<E::EncodeError>> {
-        self.storage
-            .get(&encode_ix(key))
-            .ok_or(UpdateError::NotFound)?;
+    pub fn update(&mut self, key: u32, value: &T) -> Result<(), ErrorFor<E>> {
+        self.storage.get(&encode_ix(key)).ok_or(Error::NotFound)?;
 
-        let bytes = value.encode()?;
+        let bytes = value.encode().map_err(Error::Encode)?;
 
         self.storage.set(&encode_ix(key), &bytes);
 
@@ -331,8 +515,8 @@ where
     ///
     /// # Example
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::Column;
     ///
     /// let mut storage = TestStorage::new();
@@ -345,66 +529,26 @@ where
     /// access.remove(0).unwrap();
     /// assert_eq!(access.get(0).unwrap(), None);
     /// ```
-    pub fn remove(&mut self, key: u32) -> Result<(), RemoveError> {
+    pub fn remove(&mut self, key: u32) -> Result<(), ErrorFor<E>> {
         self.storage.remove(&encode_ix(key));
 
         let len = self
             .storage
             .get_meta(META_LEN)
             .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
-            .ok_or(RemoveError::InconsistentState)?;
+            .ok_or(Error::InconsistentState)?;
         self.storage.set_meta(META_LEN, &(len - 1).to_be_bytes());
 
         Ok(())
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
-pub enum PushError<E> {
-    #[error("index overflow")]
-    IndexOverflow,
-    #[error("{0}")]
-    EncodingError(E),
-}
-
-impl<E> From<E> for PushError<E> {
-    fn from(e: E) -> Self {
-        PushError::EncodingError(e)
-    }
-}
-
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
-pub enum UpdateError<E> {
-    #[error("not found")]
-    NotFound,
-    #[error("{0}")]
-    EncodingError(E),
-}
-
-impl<E> From<E> for UpdateError<E> {
-    fn from(e: E) -> Self {
-        UpdateError::EncodingError(e)
-    }
-}
-
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
-pub enum RemoveError {
-    #[error("inconsistent state")]
-    InconsistentState,
-}
-
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
-pub enum LenError {
-    #[error("inconsistent state")]
-    InconsistentState,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use mocks::backend::TestStorage;
-    use mocks::encoding::TestEncoding;
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
 
     #[test]
     fn basic() {
@@ -422,7 +566,7 @@ mod tests {
         assert_eq!(access.len().unwrap(), 2);
 
         access.remove(0).unwrap();
-        assert_eq!(access.update(0, &9001), Err(UpdateError::NotFound));
+        assert_eq!(access.update(0, &9001), Err(Error::NotFound));
         access.update(1, &9001).unwrap();
 
         assert_eq!(access.get(0).unwrap(), None);
@@ -430,6 +574,28 @@ mod tests {
         assert_eq!(access.len().unwrap(), 1);
     }
 
+    #[test]
+    fn extend() {
+        let mut storage = TestStorage::new();
+
+        let column = Column::<u64, TestEncoding>::new(0);
+        let mut access = column.access(&mut storage);
+
+        access.extend(&[]).unwrap();
+        assert_eq!(access.len().unwrap(), 0);
+
+        access.extend(&[1, 2]).unwrap();
+        access.remove(1).unwrap();
+        access.extend([3, 4].iter()).unwrap();
+        assert_eq!(access.push(&5).unwrap(), 4);
+
+        assert_eq!(
+            access.pairs().collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![(0, 1), (2, 3), (3, 4), (4, 5)]
+        );
+        assert_eq!(access.len().unwrap(), 4);
+    }
+
     #[test]
     fn iteration() {
         let mut storage = TestStorage::new();
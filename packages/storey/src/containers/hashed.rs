@@ -0,0 +1,209 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+use super::map::{InvalidKeyLength, Key, OwnedKey};
+use super::KeyPhantom;
+
+/// A hash function producing 32-byte digests, used by [`Hashed`] keys.
+///
+/// The output is written to storage, so it must be deterministic and must never change for
+/// a given input. Swapping the hasher of an existing map makes all of its entries unreachable.
+pub trait KeyHasher {
+    fn hash(bytes: &[u8]) -> [u8; 32];
+}
+
+/// The SHA-256 [`KeyHasher`].
+#[cfg(any(test, feature = "sha2"))]
+pub struct Sha256;
+
+#[cfg(any(test, feature = "sha2"))]
+impl KeyHasher for Sha256 {
+    fn hash(bytes: &[u8]) -> [u8; 32] {
+        use sha2::Digest as _;
+
+        sha2::Sha256::digest(bytes).into()
+    }
+}
+
+/// A map key that stores the hash of another key rather than the key itself.
+///
+/// Map keys are length-prefixed with a single byte, which limits them to 255 bytes. Wrapping
+/// the key type in `Hashed` lifts that limit: whatever the key encodes to is hashed with `H`,
+/// and only the 32-byte digest ends up in storage.
+///
+/// Lookups work because hashing is deterministic. The flip side is that the original key
+/// can't be recovered from storage - iterating over a map with hashed keys yields `Hashed`
+/// values that only carry the [`digest`](Hashed::digest).
+///
+/// # Collisions
+///
+/// Two keys that hash to the same digest share a storage slot, silently overwriting each
+/// other. With a cryptographic hash function such as SHA-256 this is not a practical
+/// concern, even if an adversary gets to choose the keys. Do not use a non-cryptographic
+/// hasher for keys that come from untrusted input.
+///
+/// # Example
+/// ```
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
+/// use storey::containers::{Hashed, Item, KeyHasher, Map};
+///
+/// // Any 32-byte hash function will do. This one is not collision resistant!
+/// struct Xor;
+///
+/// impl KeyHasher for Xor {
+///     fn hash(bytes: &[u8]) -> [u8; 32] {
+///         let mut digest = [0; 32];
+///         for (i, b) in bytes.iter().enumerate() {
+///             digest[i % 32] ^= b;
+///         }
+///         digest
+///     }
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// let map = Map::<Hashed<str, Xor>, Item<u64, TestEncoding>>::new(0);
+/// let mut access = map.access(&mut storage);
+///
+/// let long_key = "a".repeat(1000);
+///
+/// access.entry_mut(&Hashed::new(&*long_key)).set(&1337).unwrap();
+/// assert_eq!(access.entry(&Hashed::new(&*long_key)).get().unwrap(), Some(1337));
+/// ```
+pub struct Hashed<K: ?Sized, H> {
+    digest: [u8; 32],
+    phantom: KeyPhantom<K, H>,
+}
+
+impl<K, H> Hashed<K, H>
+where
+    K: Key + ?Sized,
+    H: KeyHasher,
+{
+    /// Hashes the given key.
+    pub fn new(key: &K) -> Self {
+        let mut buf = Vec::with_capacity(key.size_hint());
+        key.encode(&mut buf);
+
+        Self::from_digest(H::hash(&buf))
+    }
+}
+
+impl<K: ?Sized, H> Hashed<K, H> {
+    /// Wraps an already computed digest.
+    pub const fn from_digest(digest: [u8; 32]) -> Self {
+        Self {
+            digest,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The digest stored in place of the original key.
+    pub const fn digest(&self) -> &[u8; 32] {
+        &self.digest
+    }
+}
+
+impl<K: ?Sized, H> Key for Hashed<K, H> {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.digest);
+    }
+
+    fn size_hint(&self) -> usize {
+        32
+    }
+}
+
+impl<K: ?Sized, H> OwnedKey for Hashed<K, H> {
+    type Error = InvalidKeyLength;
+
+    const WIDTH: Option<usize> = Some(32);
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        let digest = bytes.try_into().map_err(|_| InvalidKeyLength {
+            expected: 32,
+            actual: bytes.len(),
+        })?;
+
+        Ok(Self::from_digest(digest))
+    }
+}
+
+impl<K: ?Sized, H> Clone for Hashed<K, H> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K: ?Sized, H> Copy for Hashed<K, H> {}
+
+impl<K: ?Sized, H> PartialEq for Hashed<K, H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.digest == other.digest
+    }
+}
+
+impl<K: ?Sized, H> Eq for Hashed<K, H> {}
+
+impl<K: ?Sized, H> fmt::Debug for Hashed<K, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Hashed").field(&self.digest).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::{Item, IterableAccessor as _, Map};
+
+    use storey_storage::Storage as _;
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
+
+    #[test]
+    fn long_keys_get_distinct_slots() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<Hashed<String, Sha256>, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        let a = "x".repeat(10_000);
+        let b = format!("{}y", "x".repeat(9_999));
+
+        access.entry_mut(&Hashed::new(&a)).set(&1).unwrap();
+        access.entry_mut(&Hashed::new(&b)).set(&2).unwrap();
+
+        assert_eq!(access.entry(&Hashed::new(&a)).get().unwrap(), Some(1));
+        assert_eq!(access.entry(&Hashed::new(&b)).get().unwrap(), Some(2));
+
+        let raw_a = [&[0, 32][..], &Sha256::hash(a.as_bytes())].concat();
+        let raw_b = [&[0, 32][..], &Sha256::hash(b.as_bytes())].concat();
+        assert_ne!(raw_a, raw_b);
+        assert_eq!(storage.get(&raw_a), Some(1u64.to_le_bytes().to_vec()));
+        assert_eq!(storage.get(&raw_b), Some(2u64.to_le_bytes().to_vec()));
+    }
+
+    #[test]
+    fn iteration_yields_digests() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<Hashed<u32, Sha256>, Item<u64, TestEncoding>>::new(0);
+        map.access(&mut storage)
+            .entry_mut(&Hashed::new(&7))
+            .set(&1)
+            .unwrap();
+
+        let keys = map
+            .access(&storage)
+            .keys()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(keys, vec![(Hashed::new(&7), ())]);
+        assert_eq!(keys[0].0.digest(), &Sha256::hash(&[0, 0, 0, 7]));
+    }
+}
@@ -1,6 +1,9 @@
-use std::marker::PhantomData;
+use core::fmt;
+use core::marker::PhantomData;
 
-use crate::encoding::{DecodableWith, EncodableWith, Encoding};
+use crate::encoding::{BorrowDecodableWith, DecodableWith, EncodableWith, Encoding};
+use crate::error::{Error, ErrorFor};
+use crate::prefix::Prefix;
 use crate::storage::StorageBranch;
 use crate::storage::{Storage, StorageMut};
 
@@ -13,8 +16,8 @@ use super::Storable;
 ///
 /// # Example
 /// ```
-/// # use mocks::encoding::TestEncoding;
-/// # use mocks::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
 /// use storey::containers::Item;
 ///
 /// let mut storage = TestStorage::new();
@@ -24,8 +27,8 @@ use super::Storable;
 /// assert_eq!(item.access(&storage).get().unwrap(), Some(42));
 /// ```
 pub struct Item<T, E> {
-    key: u8,
-    phantom: PhantomData<(T, E)>,
+    key: Prefix,
+    phantom: PhantomData<fn() -> (T, E)>,
 }
 
 impl<T, E> Item<T, E>
@@ -38,17 +41,70 @@ where
     /// It is the responsibility of the caller to ensure that the key is unique.
     pub const fn new(key: u8) -> Self {
         Self {
-            key,
+            key: Prefix::Byte(key),
             phantom: PhantomData,
         }
     }
 
+    /// Create a new item with a multi-byte prefix, typically built with
+    /// [`prefix!`](crate::prefix!).
+    ///
+    /// Like with [`new`](Self::new), it is the responsibility of the caller to ensure that the
+    /// prefix doesn't conflict with other keys in the storage, and in particular that it
+    /// doesn't start with the prefix of another container. See the [`prefix`](mod@crate::prefix)
+    /// module.
+    ///
+    /// A one-byte prefix makes the same item as [`new`](Self::new) does:
+    /// `Item::new_with_prefix(&[3])` and `Item::new(3)` are stored under the same keys.
+    ///
+    /// # Panics
+    /// If `prefix` is empty or starts with `255`, which is reserved for metadata. In a `const`,
+    /// that's a compile error.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// use storey::containers::Item;
+    ///
+    /// const CONFIG: Item<u64, TestEncoding> = Item::new_with_prefix(storey::prefix!("config"));
+    /// ```
+    pub const fn new_with_prefix(prefix: &'static [u8]) -> Self {
+        Self {
+            key: Prefix::bytes(prefix),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Create an item stored under the UTF-8 bytes of `prefix`, like the namespaces of
+    /// `cw-storage-plus`. This is the same as [`new_with_prefix`](Self::new_with_prefix) with
+    /// a [`prefix!`](crate::prefix!) built from the name, and the same caveats apply.
+    ///
+    /// # Panics
+    /// If `prefix` is empty. In a `const`, that's a compile error.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// use storey::containers::Item;
+    ///
+    /// const CONFIG: Item<u64, TestEncoding> = Item::new_str("config");
+    /// ```
+    pub const fn new_str(prefix: &'static str) -> Self {
+        Self::new_with_prefix(crate::prefix::named(prefix))
+    }
+
+    /// Create an item with a multi-byte prefix.
+    #[deprecated(note = "renamed to `new_with_prefix`")]
+    pub const fn with_prefix(prefix: &'static [u8]) -> Self {
+        Self::new_with_prefix(prefix)
+    }
+
     /// Acquire an accessor to the item.
     ///
     /// # Example
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::Item;
     ///
     /// // immutable accessor
@@ -60,8 +116,58 @@ where
     /// let mut storage = TestStorage::new();
     /// let item = Item::<u64, TestEncoding>::new(0);
     /// let mut access = item.access(&mut storage);
+    #[cfg_attr(any(test, feature = "prefix-audit"), track_caller)]
     pub fn access<S>(&self, storage: S) -> ItemAccess<E, T, StorageBranch<S>> {
-        Self::access_impl(StorageBranch::new(storage, vec![self.key]))
+        crate::audit::register(self.key.as_slice(), core::any::type_name::<Self>());
+        Self::access_impl(StorageBranch::new(storage, self.key.as_slice().to_vec()))
+    }
+}
+
+impl<T, E> Item<T, E> {
+    /// The key the item is stored under.
+    ///
+    /// # Panics
+    /// If the item has a prefix longer than one byte. Use
+    /// [`prefix_bytes`](Self::prefix_bytes) for those.
+    pub const fn prefix(&self) -> u8 {
+        self.key.byte()
+    }
+
+    /// The bytes of the prefix the item is stored under, whichever constructor was used.
+    pub const fn prefix_bytes(&self) -> &[u8] {
+        self.key.as_slice()
+    }
+}
+
+impl<T, E> Clone for Item<T, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, E> Copy for Item<T, E> {}
+
+impl<T, E> PartialEq for Item<T, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.prefix_bytes() == other.prefix_bytes()
+    }
+}
+
+impl<T, E> Eq for Item<T, E> {}
+
+impl<T, E> fmt::Debug for Item<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Item")
+            .field("prefix", &self.prefix_bytes())
+            .finish()
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl<T: schemars::JsonSchema, E> Item<T, E> {
+    /// Describe the item as JSON Schema. See the [`schema`](crate::schema) module.
+    pub fn schema(&self) -> crate::schema::SchemaDocument {
+        crate::schema::SchemaDocument::new::<Self>(self.prefix_bytes())
     }
 }
 
@@ -105,7 +211,15 @@ pub struct ItemKeyDecodeError;
 /// This type provides methods to get and set the value of the item.
 pub struct ItemAccess<E, T, S> {
     storage: S,
-    phantom: PhantomData<(E, T)>,
+    phantom: PhantomData<fn() -> (E, T)>,
+}
+
+impl<E, T, S: Storage> fmt::Debug for ItemAccess<E, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ItemAccess")
+            .field("prefix", &self.storage.key_prefix())
+            .finish()
+    }
 }
 
 impl<E, T, S> ItemAccess<E, T, S>
@@ -120,8 +234,8 @@ where
     ///
     /// # Examples
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::Item;
     ///
     /// let storage = TestStorage::new();
@@ -132,8 +246,8 @@ where
     /// ```
     ///
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::Item;
     ///
     /// let mut storage = TestStorage::new();
@@ -142,10 +256,73 @@ where
     /// item.access(&mut storage).set(&42).unwrap();
     /// assert_eq!(item.access(&storage).get().unwrap(), Some(42));
     /// ```
-    pub fn get(&self) -> Result<Option<T>, E::DecodeError> {
+    pub fn get(&self) -> Result<Option<T>, ErrorFor<E>> {
         self.storage
             .get(&[])
-            .map(|bytes| T::decode(&bytes))
+            .map(|bytes| T::decode(&bytes).map_err(Error::Decode))
+            .transpose()
+    }
+
+    /// Check if the item exists (has been set).
+    ///
+    /// This goes through [`Storage::has`], so backends that can check a key without reading
+    /// its value don't read it.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
+    /// use storey::containers::Item;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    ///
+    /// assert!(!item.access(&storage).exists());
+    /// item.access(&mut storage).set(&42).unwrap();
+    /// assert!(item.access(&storage).exists());
+    /// ```
+    pub fn exists(&self) -> bool {
+        self.storage.has(&[])
+    }
+}
+
+impl<E, T, S> ItemAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E> + BorrowDecodableWith<E>,
+    S: Storage,
+{
+    /// Decode the value of the item into its borrowed form (see [`Borrowable`]) and pass it to
+    /// `f`, returning the result.
+    ///
+    /// Unlike [`get`](Self::get), this doesn't copy strings or byte arrays out of the raw
+    /// value, which makes it cheaper when only part of a large value is needed.
+    ///
+    /// Returns `None` if the item doesn't exist (has not been set yet).
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::backend::TestStorage;
+    /// use storey::containers::Item;
+    /// use storey::encoding::RawEncoding;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<String, RawEncoding>::new(0);
+    ///
+    /// item.access(&mut storage).set(&"hello world".to_string()).unwrap();
+    ///
+    /// let first_word = item.access(&storage).get_with(|s| s.split(' ').next().map(str::len));
+    /// assert_eq!(first_word, Ok(Some(Some(5))));
+    /// ```
+    ///
+    /// [`Borrowable`]: crate::encoding::Borrowable
+    pub fn get_with<R>(
+        &self,
+        f: impl FnOnce(<T as BorrowDecodableWith<E>>::Borrowed<'_>) -> R,
+    ) -> Result<Option<R>, ErrorFor<E>> {
+        self.storage
+            .get(&[])
+            .map(|bytes| T::decode_borrowed(&bytes).map(f).map_err(Error::Decode))
             .transpose()
     }
 }
@@ -160,8 +337,8 @@ where
     ///
     /// # Example
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::Item;
     ///
     /// let mut storage = TestStorage::new();
@@ -170,19 +347,67 @@ where
     /// item.access(&mut storage).set(&42).unwrap();
     /// assert_eq!(item.access(&storage).get().unwrap(), Some(42));
     /// ```
-    pub fn set(&mut self, value: &T) -> Result<(), E::EncodeError> {
-        let bytes = value.encode()?;
+    pub fn set(&mut self, value: &T) -> Result<(), ErrorFor<E>> {
+        let bytes = value.encode().map_err(Error::Encode)?;
         self.storage.set(&[], &bytes);
         Ok(())
     }
+
+    /// Remove the value of the item. Removing an item that isn't set does nothing.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
+    /// use storey::containers::Item;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    ///
+    /// item.access(&mut storage).set(&42).unwrap();
+    /// item.access(&mut storage).remove();
+    /// assert_eq!(item.access(&storage).get().unwrap(), None);
+    /// ```
+    pub fn remove(&mut self) {
+        self.storage.remove(&[]);
+    }
+}
+
+impl<E, T, S> ItemAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + StorageMut,
+{
+    /// Replace the value of the item with what `f` makes of the current one (`None` if the
+    /// item isn't set), and return the new value.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
+    /// use storey::containers::Item;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    /// let mut access = item.access(&mut storage);
+    ///
+    /// assert_eq!(access.update(|n| n.unwrap_or(0) + 1).unwrap(), 1);
+    /// assert_eq!(access.update(|n| n.unwrap_or(0) + 1).unwrap(), 2);
+    /// ```
+    pub fn update(&mut self, f: impl FnOnce(Option<T>) -> T) -> Result<T, ErrorFor<E>> {
+        let value = f(self.get()?);
+        self.set(&value)?;
+        Ok(value)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use mocks::backend::TestStorage;
-    use mocks::encoding::TestEncoding;
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
 
     #[test]
     fn basic() {
@@ -199,4 +424,19 @@ mod tests {
         assert_eq!(access1.get().unwrap(), None);
         assert_eq!(storage.get(&[1]), None);
     }
+
+    #[test]
+    fn exists_without_reading() {
+        use storey_testing::backend::CountingStorage;
+
+        let mut storage = CountingStorage::new();
+        let item = Item::<u64, TestEncoding>::new(0);
+
+        assert!(!item.access(&storage).exists());
+        item.access(&mut storage).set(&42).unwrap();
+        assert!(item.access(&storage).exists());
+
+        assert_eq!(storage.gets(), 0);
+        assert_eq!(storage.checks(), 2);
+    }
 }
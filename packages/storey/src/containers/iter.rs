@@ -0,0 +1,398 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+use super::{KeyDecodeError, Storable};
+use crate::storage::IterableStorage;
+
+/// A key-value pair decoding error.
+///
+/// This is the error type of [`StorableIter`]. It implements [`std::error::Error`], so it can
+/// be propagated with `?` like any other error.
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum KVDecodeError<K, V> {
+    #[error("failed to decode key: {0}")]
+    Key(K),
+    #[error("failed to decode value: {0}")]
+    Value(V),
+}
+
+/// A trait for collection accessors (see [`Storable::AccessorT`]) that provide iteration over
+/// their contents.
+pub trait IterableAccessor: Sized {
+    /// The [`Storable`] type this accessor is associated with.
+    type Storable: Storable;
+
+    /// The [`Storage`] type this accessor is associated with.
+    ///
+    /// [`Storage`]: crate::storage::Storage
+    type Storage: IterableStorage;
+
+    /// Get a reference to the storage this accessor is associated with.
+    fn storage(&self) -> &Self::Storage;
+
+    /// Iterate over key-value pairs in this collection.
+    fn pairs(&self) -> StorableIter<'_, Self::Storable, Self::Storage> {
+        StorableIter {
+            inner: self.storage().pairs(None, None),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Iterate over keys in this collection.
+    fn keys(&self) -> StorableKeys<'_, Self::Storable, Self::Storage> {
+        StorableKeys {
+            inner: self.storage().keys(None, None),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Iterate over values in this collection.
+    fn values(&self) -> StorableValues<'_, Self::Storable, Self::Storage> {
+        StorableValues {
+            inner: self.storage().values(None, None),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Count the entries in this collection, and the bytes they take up.
+    ///
+    /// Keys are measured without the prefix of the collection itself. See
+    /// [`stats::measure`](crate::stats::measure).
+    fn stats(&self) -> crate::stats::PrefixStats {
+        crate::stats::measure(self.storage(), &[])
+    }
+
+    /// Render every entry in this collection, one per line, in iteration order.
+    ///
+    /// Each line is a key and a value rendered by [`dump_entries`](Self::dump_entries), like
+    /// `"alice" / 7 => 100`. The output only depends on what's stored, so it can be compared
+    /// against a golden string or a snapshot in tests.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// use storey::containers::{IterableAccessor as _, Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+    ///
+    /// let mut access = map.access(&mut storage);
+    /// access.entry_mut("alice").entry_mut(&7).set(&100).unwrap();
+    /// access.entry_mut("bob").entry_mut(&1).set(&5).unwrap();
+    ///
+    /// assert_eq!(
+    ///     map.access(&storage).dump(),
+    ///     "\"bob\" / 1 => 5\n\"alice\" / 7 => 100\n"
+    /// );
+    /// ```
+    fn dump(&self) -> String
+    where
+        <Self::Storable as Storable>::Key: DumpKey,
+        <Self::Storable as Storable>::Value: fmt::Debug,
+    {
+        let mut out = String::new();
+        for (key, value) in self.dump_entries() {
+            out.push_str(&key);
+            out.push_str(" => ");
+            out.push_str(&value);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Render every entry in this collection as a key and a value, in iteration order.
+    ///
+    /// Keys of nested containers are rendered one component per container, outermost first,
+    /// separated by ` / ` (see [`DumpKey`]). Values are rendered with their `Debug` impl.
+    ///
+    /// Entries that don't decode are rendered rather than reported as errors: a key or value
+    /// that fails to decode is replaced with the error between angle brackets, with the raw
+    /// bytes in hex. The other half of the entry is still decoded.
+    fn dump_entries(&self) -> Vec<(String, String)>
+    where
+        <Self::Storable as Storable>::Key: DumpKey,
+        <Self::Storable as Storable>::Value: fmt::Debug,
+    {
+        self.storage()
+            .pairs(None, None)
+            .map(|(key, value)| {
+                let key = match Self::Storable::decode_key_with_context(&key) {
+                    Ok(key) => {
+                        let mut components = Vec::new();
+                        key.components(&mut components);
+                        components.join(" / ")
+                    }
+                    Err(e) => format!("<{}>", e),
+                };
+                let value = match Self::Storable::decode_value(&value) {
+                    Ok(value) => format!("{:?}", value),
+                    Err(e) => format!("<invalid value {}: {}>", crate::hex::encode(&value), e),
+                };
+                (key, value)
+            })
+            .collect()
+    }
+}
+
+/// A container key that [`IterableAccessor::dump`] can render, one component per container.
+///
+/// This is implemented for the keys of the built-in containers: `()` for an
+/// [`Item`](super::Item), which adds no component, the index of a
+/// [`Column`](super::Column), and a pair of a [`Map`](super::Map) key, rendered with its
+/// `Debug` impl, and the key of the container nested in it. A custom container nesting
+/// others can use a pair the same way.
+pub trait DumpKey {
+    /// Append the rendered components of this key to `out`, outermost first.
+    fn components(&self, out: &mut Vec<String>);
+}
+
+impl DumpKey for () {
+    fn components(&self, _: &mut Vec<String>) {}
+}
+
+impl DumpKey for u32 {
+    fn components(&self, out: &mut Vec<String>) {
+        out.push(format!("{}", self));
+    }
+}
+
+impl<K: fmt::Debug, R: DumpKey> DumpKey for (K, R) {
+    fn components(&self, out: &mut Vec<String>) {
+        out.push(format!("{:?}", self.0));
+        self.1.components(out);
+    }
+}
+
+/// A trait for collection accessors that provide iteration over a range of their contents.
+///
+/// The bounds are compared against raw keys, so this is only implemented where the byte order
+/// of keys matches their natural order. For [`Map`](super::Map) that means the key type has to
+/// implement [`OrderPreservingKey`](super::OrderPreservingKey).
+pub trait BoundedIterableAccessor: IterableAccessor {
+    /// Iterate over key-value pairs in this collection, respecting the given bounds.
+    fn bounded_pairs<S, E>(
+        &self,
+        start: Option<S>,
+        end: Option<E>,
+    ) -> StorableIter<'_, Self::Storable, Self::Storage>
+    where
+        S: BoundFor<Self::Storable>,
+        E: BoundFor<Self::Storable>,
+    {
+        let start = start.map(|b| b.into_bytes());
+        let end = end.map(|b| b.into_bytes());
+
+        StorableIter {
+            inner: self.storage().pairs(start.as_deref(), end.as_deref()),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Iterate over keys in this collection, respecting the given bounds.
+    fn bounded_keys<S, E>(
+        &self,
+        start: Option<S>,
+        end: Option<E>,
+    ) -> StorableKeys<'_, Self::Storable, Self::Storage>
+    where
+        S: BoundFor<Self::Storable>,
+        E: BoundFor<Self::Storable>,
+    {
+        let start = start.map(|b| b.into_bytes());
+        let end = end.map(|b| b.into_bytes());
+
+        StorableKeys {
+            inner: self.storage().keys(start.as_deref(), end.as_deref()),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Iterate over values in this collection, respecting the given bounds.
+    fn bounded_values<S, E>(
+        &self,
+        start: Option<S>,
+        end: Option<E>,
+    ) -> StorableValues<'_, Self::Storable, Self::Storage>
+    where
+        S: BoundFor<Self::Storable>,
+        E: BoundFor<Self::Storable>,
+    {
+        let start = start.map(|b| b.into_bytes());
+        let end = end.map(|b| b.into_bytes());
+
+        StorableValues {
+            inner: self.storage().values(start.as_deref(), end.as_deref()),
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// A type that can be used as bounds for iteration over a given collection.
+///
+/// As an example, a collection `Foo` with string-y keys can accept both `String` and
+/// `&str` bounds by providing these impls:
+/// - `impl BoundFor<Foo> for &str`
+/// - `impl BoundFor<Foo> for String`
+pub trait BoundFor<T> {
+    fn into_bytes(self) -> Vec<u8>;
+}
+
+/// The iterator over key-value pairs in a collection.
+pub struct StorableIter<'i, S, B>
+where
+    S: Storable,
+    B: IterableStorage + 'i,
+{
+    inner: B::PairsIterator<'i>,
+    phantom: PhantomData<fn() -> S>,
+}
+
+impl<'i, S, B> Clone for StorableIter<'i, S, B>
+where
+    S: Storable,
+    B: IterableStorage + 'i,
+    B::PairsIterator<'i>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'i, S, B> fmt::Debug for StorableIter<'i, S, B>
+where
+    S: Storable,
+    B: IterableStorage + 'i,
+    B::PairsIterator<'i>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StorableIter")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<'i, S, B> Iterator for StorableIter<'i, S, B>
+where
+    S: Storable,
+    B: IterableStorage + 'i,
+{
+    type Item = Result<
+        (S::Key, S::Value),
+        KVDecodeError<KeyDecodeError<S::KeyDecodeError>, S::ValueDecodeError>,
+    >;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| -> Self::Item {
+            match (S::decode_key_with_context(&k), S::decode_value(&v)) {
+                (Err(e), _) => Err(KVDecodeError::Key(e)),
+                (_, Err(e)) => Err(KVDecodeError::Value(e)),
+                (Ok(k), Ok(v)) => Ok((k, v)),
+            }
+        })
+    }
+}
+
+/// The iterator over keys in a collection.
+pub struct StorableKeys<'i, S, B>
+where
+    S: Storable,
+    B: IterableStorage + 'i,
+{
+    inner: B::KeysIterator<'i>,
+    phantom: PhantomData<fn() -> S>,
+}
+
+impl<'i, S, B> Clone for StorableKeys<'i, S, B>
+where
+    S: Storable,
+    B: IterableStorage + 'i,
+    B::KeysIterator<'i>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'i, S, B> fmt::Debug for StorableKeys<'i, S, B>
+where
+    S: Storable,
+    B: IterableStorage + 'i,
+    B::KeysIterator<'i>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StorableKeys")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<'i, S, B> Iterator for StorableKeys<'i, S, B>
+where
+    S: Storable,
+    B: IterableStorage + 'i,
+{
+    type Item = Result<S::Key, KeyDecodeError<S::KeyDecodeError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|k| S::decode_key_with_context(&k))
+    }
+}
+
+/// The iterator over values in a collection.
+pub struct StorableValues<'i, S, B>
+where
+    S: Storable,
+    B: IterableStorage + 'i,
+{
+    inner: B::ValuesIterator<'i>,
+    phantom: PhantomData<fn() -> S>,
+}
+
+impl<'i, S, B> Clone for StorableValues<'i, S, B>
+where
+    S: Storable,
+    B: IterableStorage + 'i,
+    B::ValuesIterator<'i>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'i, S, B> fmt::Debug for StorableValues<'i, S, B>
+where
+    S: Storable,
+    B: IterableStorage + 'i,
+    B::ValuesIterator<'i>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StorableValues")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<'i, S, B> Iterator for StorableValues<'i, S, B>
+where
+    S: Storable,
+    B: IterableStorage + 'i,
+{
+    type Item = Result<S::Value, S::ValueDecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|v| S::decode_value(&v))
+    }
+}
@@ -1,10 +1,22 @@
-use std::{borrow::Borrow, marker::PhantomData};
+use alloc::borrow::{Borrow, Cow};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::fmt;
+use core::marker::PhantomData;
 
-use crate::storage::IterableStorage;
+use crate::encoding::{DecodableWith, Encoding};
+use crate::error::{Error, ErrorFor};
+use crate::prefix::Prefix;
+use crate::storage::Storage;
 use crate::storage::StorageBranch;
+#[cfg(feature = "iteration")]
+use crate::storage::{IterableStorage, RemoveRangeStorage};
 
-use super::IterableAccessor;
 use super::Storable;
+#[cfg(feature = "iteration")]
+use super::{BoundFor, BoundedIterableAccessor, IterableAccessor};
+use super::{Item, KeyPhantom};
 
 /// A map that stores values of type `V` under keys of type `K`.
 ///
@@ -16,8 +28,8 @@ use super::Storable;
 /// # Examples
 ///
 /// ```
-/// # use mocks::encoding::TestEncoding;
-/// # use mocks::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
 /// use storey::containers::{Item, Map};
 ///
 /// let mut storage = TestStorage::new();
@@ -30,8 +42,8 @@ use super::Storable;
 /// ```
 ///
 /// ```
-/// # use mocks::encoding::TestEncoding;
-/// # use mocks::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
 /// use storey::containers::{Item, Map};
 ///
 /// let mut storage = TestStorage::new();
@@ -43,15 +55,15 @@ use super::Storable;
 /// assert_eq!(access.entry("foo").entry("baz").get().unwrap(), None);
 /// ```
 pub struct Map<K: ?Sized, V> {
-    prefix: u8,
-    phantom: PhantomData<(*const K, V)>,
+    prefix: Prefix,
+    phantom: KeyPhantom<K, V>,
 }
 
 impl<K, V> Map<K, V>
 where
     K: OwnedKey,
     V: Storable,
-    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    MapKeyDecodeError<V::KeyDecodeError>: From<K::Error>,
 {
     /// Creates a new map with the given prefix.
     ///
@@ -61,17 +73,71 @@ where
     /// The key provided here is used as a prefix for all keys managed by the map.
     pub const fn new(prefix: u8) -> Self {
         Self {
-            prefix,
+            prefix: Prefix::Byte(prefix),
             phantom: PhantomData,
         }
     }
 
+    /// Creates a new map with a multi-byte prefix, typically built with
+    /// [`prefix!`](crate::prefix!).
+    ///
+    /// Like with [`new`](Self::new), it is the responsibility of the caller to ensure that the
+    /// prefix doesn't conflict with other keys in the storage, and in particular that it
+    /// doesn't start with the prefix of another container. See the [`prefix`](mod@crate::prefix)
+    /// module.
+    ///
+    /// A one-byte prefix makes the same map as [`new`](Self::new) does:
+    /// `Map::new_with_prefix(&[3])` and `Map::new(3)` are stored under the same keys.
+    ///
+    /// # Panics
+    /// If `prefix` is empty or starts with `255`, which is reserved for metadata. In a `const`,
+    /// that's a compile error.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// const BALANCES: Map<String, Item<u64, TestEncoding>> =
+    ///     Map::new_with_prefix(storey::prefix!("balances"));
+    /// ```
+    pub const fn new_with_prefix(prefix: &'static [u8]) -> Self {
+        Self {
+            prefix: Prefix::bytes(prefix),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates a map stored under the UTF-8 bytes of `prefix`, like the namespaces of
+    /// `cw-storage-plus`. This is the same as [`new_with_prefix`](Self::new_with_prefix) with
+    /// a [`prefix!`](crate::prefix!) built from the name, and the same caveats apply.
+    ///
+    /// # Panics
+    /// If `prefix` is empty. In a `const`, that's a compile error.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new_str("balances");
+    /// ```
+    pub const fn new_str(prefix: &'static str) -> Self {
+        Self::new_with_prefix(crate::prefix::named(prefix))
+    }
+
+    /// Creates a map with a multi-byte prefix.
+    #[deprecated(note = "renamed to `new_with_prefix`")]
+    pub const fn with_prefix(prefix: &'static [u8]) -> Self {
+        Self::new_with_prefix(prefix)
+    }
+
     /// Acquires an accessor for the map.
     ///
     /// # Example
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::{Item, Map};
     ///
     /// // immutable access
@@ -84,8 +150,63 @@ where
     /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
     /// let mut access = map.access(&mut storage);
     /// ```
+    #[cfg_attr(any(test, feature = "prefix-audit"), track_caller)]
     pub fn access<S>(&self, storage: S) -> MapAccess<K, V, StorageBranch<S>> {
-        Self::access_impl(StorageBranch::new(storage, vec![self.prefix]))
+        crate::audit::register(self.prefix.as_slice(), core::any::type_name::<Self>());
+        Self::access_impl(StorageBranch::new(storage, self.prefix.as_slice().to_vec()))
+    }
+}
+
+impl<K: ?Sized, V> Map<K, V> {
+    /// The prefix the map is stored under.
+    ///
+    /// # Panics
+    /// If the map has a prefix longer than one byte. Use
+    /// [`prefix_bytes`](Self::prefix_bytes) for those.
+    pub const fn prefix(&self) -> u8 {
+        self.prefix.byte()
+    }
+
+    /// The bytes of the prefix the map is stored under, whichever constructor was used.
+    pub const fn prefix_bytes(&self) -> &[u8] {
+        self.prefix.as_slice()
+    }
+}
+
+impl<K: ?Sized, V> Clone for Map<K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K: ?Sized, V> Copy for Map<K, V> {}
+
+impl<K: ?Sized, V> PartialEq for Map<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.prefix_bytes() == other.prefix_bytes()
+    }
+}
+
+impl<K: ?Sized, V> Eq for Map<K, V> {}
+
+impl<K: ?Sized, V> fmt::Debug for Map<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Map")
+            .field("prefix", &self.prefix_bytes())
+            .finish()
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl<K, V> Map<K, V>
+where
+    K: schemars::JsonSchema,
+    V: crate::schema::StorableSchema,
+{
+    /// Describe the map and the containers nested in it as JSON Schema. See the
+    /// [`schema`](crate::schema) module.
+    pub fn schema(&self) -> crate::schema::SchemaDocument {
+        crate::schema::SchemaDocument::new::<Self>(self.prefix_bytes())
     }
 }
 
@@ -93,7 +214,7 @@ impl<K, V> Storable for Map<K, V>
 where
     K: OwnedKey,
     V: Storable,
-    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    MapKeyDecodeError<V::KeyDecodeError>: From<K::Error>,
 {
     type Accessor<S> = MapAccess<K, V, S>;
     type Key = (K, V::Key);
@@ -115,13 +236,25 @@ where
             return Err(MapKeyDecodeError::KeyTooShort(len));
         }
 
-        let map_key =
-            K::from_bytes(&key[1..len + 1]).map_err(|_| MapKeyDecodeError::InvalidUtf8)?;
+        let map_key = K::from_bytes(&key[1..len + 1]).map_err(MapKeyDecodeError::from)?;
         let rest = V::decode_key(&key[len + 1..]).map_err(MapKeyDecodeError::Inner)?;
 
         Ok((map_key, rest))
     }
 
+    fn locate_key_error(key: &[u8], error: &Self::KeyDecodeError) -> (usize, usize) {
+        match error {
+            MapKeyDecodeError::Inner(inner) => {
+                // the nested key follows the length prefix and this map's key
+                let start = key.first().map_or(0, |&len| len as usize + 1);
+                let nested = key.get(start..).unwrap_or_default();
+                let (offset, component) = V::locate_key_error(nested, inner);
+                (start + offset, component + 1)
+            }
+            _ => (0, 0),
+        }
+    }
+
     fn decode_value(value: &[u8]) -> Result<Self::Value, Self::ValueDecodeError> {
         V::decode_value(value)
     }
@@ -129,7 +262,7 @@ where
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
 #[error("invalid key length, expected empty key")]
-pub enum MapKeyDecodeError<I: std::fmt::Display> {
+pub enum MapKeyDecodeError<I: core::fmt::Display> {
     #[error("empty key, expected length prefix (1 byte)")]
     EmptyKey,
 
@@ -139,16 +272,60 @@ pub enum MapKeyDecodeError<I: std::fmt::Display> {
     #[error("invalid UTF8")]
     InvalidUtf8,
 
+    #[error("invalid key length, expected {expected} bytes, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+
+    #[error("key out of range for this platform")]
+    OutOfRange,
+
     #[error("sub key decode error: {0}")]
     Inner(I),
 }
 
+impl<I: core::fmt::Display> From<InvalidUtf8> for MapKeyDecodeError<I> {
+    fn from(_: InvalidUtf8) -> Self {
+        Self::InvalidUtf8
+    }
+}
+
+impl<I: core::fmt::Display> From<InvalidKeyLength> for MapKeyDecodeError<I> {
+    fn from(e: InvalidKeyLength) -> Self {
+        Self::InvalidLength {
+            expected: e.expected,
+            actual: e.actual,
+        }
+    }
+}
+
+impl<I: core::fmt::Display> From<SizeKeyDecodeError> for MapKeyDecodeError<I> {
+    fn from(e: SizeKeyDecodeError) -> Self {
+        match e {
+            SizeKeyDecodeError::InvalidLength(e) => e.into(),
+            SizeKeyDecodeError::OutOfRange => Self::OutOfRange,
+        }
+    }
+}
+
+impl<I: core::fmt::Display> From<Infallible> for MapKeyDecodeError<I> {
+    fn from(e: Infallible) -> Self {
+        match e {}
+    }
+}
+
 /// An accessor for a map.
 ///
 /// The accessor provides methods for interacting with the map in storage.
 pub struct MapAccess<K: ?Sized, V, S> {
     storage: S,
-    phantom: PhantomData<(*const K, V)>,
+    phantom: KeyPhantom<K, V>,
+}
+
+impl<K: ?Sized, V, S: Storage> fmt::Debug for MapAccess<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapAccess")
+            .field("prefix", &self.storage.key_prefix())
+            .finish()
+    }
 }
 
 impl<K, V, S> MapAccess<K, V, S>
@@ -161,8 +338,8 @@ where
     /// # Examples
     ///
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::{Item, Map};
     ///
     /// let storage = TestStorage::new();
@@ -173,8 +350,8 @@ where
     /// ```
     ///
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::{Item, Map};
     ///
     /// let storage = TestStorage::new();
@@ -183,6 +360,11 @@ where
     ///
     /// assert_eq!(access.entry("foo").entry("bar").get().unwrap(), None);
     /// ```
+    ///
+    /// # Panics
+    /// If the encoded key is longer than [`MAX_KEY_LEN`] bytes. Use
+    /// [`try_entry`](Self::try_entry) for keys that can be that long.
+    #[track_caller]
     pub fn entry<Q>(&self, key: &Q) -> V::Accessor<StorageBranch<&S>>
     where
         K: Borrow<Q>,
@@ -193,13 +375,42 @@ where
         V::access_impl(StorageBranch::new(&self.storage, key))
     }
 
+    /// Like [`entry`](Self::entry), but returns an error instead of panicking if the encoded
+    /// key is longer than [`MAX_KEY_LEN`] bytes.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
+    /// use storey::containers::{Item, KeyTooLong, Map};
+    ///
+    /// let storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let access = map.access(&storage);
+    ///
+    /// assert_eq!(access.try_entry("foo").unwrap().get().unwrap(), None);
+    /// assert_eq!(
+    ///     access.try_entry(&"a".repeat(300)).err(),
+    ///     Some(KeyTooLong { len: 300, max: 255 })
+    /// );
+    /// ```
+    pub fn try_entry<Q>(&self, key: &Q) -> Result<V::Accessor<StorageBranch<&S>>, KeyTooLong>
+    where
+        K: Borrow<Q>,
+        Q: Key + ?Sized,
+    {
+        let key = try_length_prefixed_key(key)?;
+
+        Ok(V::access_impl(StorageBranch::new(&self.storage, key)))
+    }
+
     /// Returns a mutable accessor for the inner container of this map.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::{Item, Map};
     ///
     /// let mut storage = TestStorage::new();
@@ -211,8 +422,8 @@ where
     /// ```
     ///
     /// ```
-    /// # use mocks::encoding::TestEncoding;
-    /// # use mocks::backend::TestStorage;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
     /// use storey::containers::{Item, Map};
     ///
     /// let mut storage = TestStorage::new();
@@ -222,38 +433,301 @@ where
     /// access.entry_mut("foo").entry_mut("bar").set(&1337).unwrap();
     /// assert_eq!(access.entry("foo").entry("bar").get().unwrap(), Some(1337));
     /// ```
+    ///
+    /// # Panics
+    /// If the encoded key is longer than [`MAX_KEY_LEN`] bytes. Use
+    /// [`try_entry_mut`](Self::try_entry_mut) for keys that can be that long.
+    #[track_caller]
     pub fn entry_mut<Q>(&mut self, key: &Q) -> V::Accessor<StorageBranch<&mut S>>
     where
         K: Borrow<Q>,
         Q: Key + ?Sized,
     {
-        let len = key.bytes().len();
-        let bytes = key.bytes();
-        let mut key = Vec::with_capacity(len + 1);
-
-        key.push(len as u8);
-        key.extend_from_slice(bytes);
+        let key = length_prefixed_key(key);
 
         V::access_impl(StorageBranch::new(&mut self.storage, key))
     }
+
+    /// Like [`entry_mut`](Self::entry_mut), but returns an error instead of panicking if the
+    /// encoded key is longer than [`MAX_KEY_LEN`] bytes.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.try_entry_mut("foo").unwrap().set(&1337).unwrap();
+    /// assert!(access.try_entry_mut(&"a".repeat(300)).is_err());
+    /// ```
+    pub fn try_entry_mut<Q>(
+        &mut self,
+        key: &Q,
+    ) -> Result<V::Accessor<StorageBranch<&mut S>>, KeyTooLong>
+    where
+        K: Borrow<Q>,
+        Q: Key + ?Sized,
+    {
+        let key = try_length_prefixed_key(key)?;
+
+        Ok(V::access_impl(StorageBranch::new(&mut self.storage, key)))
+    }
+}
+
+impl<K, E, T, S> MapAccess<K, Item<T, E>, S>
+where
+    K: Key,
+    E: Encoding,
+    T: crate::encoding::EncodableWith<E> + DecodableWith<E>,
+    S: Storage,
+{
+    /// Gets the values of several entries at once.
+    ///
+    /// The values are returned in the order of `keys`, with `None` for entries that don't
+    /// exist. A key can appear more than once. All the entries are read with a single call to
+    /// [`Storage::get_many`], which a backend can serve in one round trip.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("foo").set(&1337).unwrap();
+    /// access.entry_mut("bar").set(&42).unwrap();
+    ///
+    /// assert_eq!(
+    ///     access.get_many(&["bar", "baz", "foo", "bar"]).unwrap(),
+    ///     [Some(42), None, Some(1337), Some(42)]
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    /// If one of the encoded keys is longer than [`MAX_KEY_LEN`] bytes.
+    pub fn get_many<Q>(&self, keys: &[&Q]) -> Result<Vec<Option<T>>, ErrorFor<E>>
+    where
+        K: Borrow<Q>,
+        Q: Key + ?Sized,
+    {
+        let keys: Vec<_> = keys.iter().map(|key| length_prefixed_key(*key)).collect();
+        let keys: Vec<_> = keys.iter().map(Vec::as_slice).collect();
+
+        self.storage
+            .get_many(&keys)
+            .into_iter()
+            .map(|bytes| {
+                bytes
+                    .map(|bytes| T::decode(&bytes).map_err(Error::Decode))
+                    .transpose()
+            })
+            .collect()
+    }
+
+    /// Checks if the map has an entry for `key`.
+    ///
+    /// This goes through [`Storage::has`], so backends that can check a key without reading
+    /// its value don't read it.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("foo").set(&1337).unwrap();
+    ///
+    /// assert!(access.has("foo"));
+    /// assert!(!access.has("bar"));
+    /// ```
+    ///
+    /// # Panics
+    /// If the encoded key is longer than [`MAX_KEY_LEN`] bytes.
+    #[track_caller]
+    pub fn has<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Key + ?Sized,
+    {
+        self.storage.has(&length_prefixed_key(key))
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<K, V, S> MapAccess<K, V, S>
+where
+    K: Key,
+    V: Storable,
+    S: RemoveRangeStorage,
+{
+    /// Removes all the entries of the map, along with everything stored in the containers
+    /// nested in them (including their metadata).
+    ///
+    /// This goes through [`RemoveRangeStorage`], so a backend that can remove a range of keys
+    /// natively doesn't need to be iterated over.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
+    /// use storey::containers::{Column, IterableAccessor as _, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Column<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("foo").push(&1337).unwrap();
+    /// access.entry_mut("bar").push(&42).unwrap();
+    ///
+    /// access.clear();
+    /// assert_eq!(access.keys().count(), 0);
+    /// assert_eq!(access.entry("foo").len().unwrap(), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        self.storage.remove_range(None, None);
+        self.storage.remove_meta_range(None, None);
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<K, V, S> MapAccess<K, V, S>
+where
+    K: OrderPreservingKey,
+    V: Storable,
+    S: RemoveRangeStorage,
+{
+    /// Removes the entries with keys between `start` (inclusive) and `end` (exclusive), along
+    /// with everything stored in the containers nested in them.
+    ///
+    /// The bounds work the same way as for [bounded iteration](BoundedIterableAccessor), so
+    /// this removes exactly the entries bounded iteration would yield.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::encoding::TestEncoding;
+    /// # use storey_testing::backend::TestStorage;
+    /// use storey::containers::{Item, IterableAccessor as _, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// for height in 0..10 {
+    ///     access.entry_mut(&height).set(&u64::from(height)).unwrap();
+    /// }
+    ///
+    /// // prune everything below height 7
+    /// access.remove_range(None::<u32>, Some(7));
+    /// assert_eq!(
+    ///     access.keys().collect::<Result<Vec<_>, _>>().unwrap(),
+    ///     [(7, ()), (8, ()), (9, ())]
+    /// );
+    /// ```
+    pub fn remove_range<B, E>(&mut self, start: Option<B>, end: Option<E>)
+    where
+        B: BoundFor<Map<K, V>>,
+        E: BoundFor<Map<K, V>>,
+    {
+        let start = start.map(|b| b.into_bytes());
+        let end = end.map(|b| b.into_bytes());
+
+        self.storage.remove_range(start.as_deref(), end.as_deref());
+        self.storage
+            .remove_meta_range(start.as_deref(), end.as_deref());
+    }
 }
 
+#[track_caller]
 fn length_prefixed_key<K: Key + ?Sized>(key: &K) -> Vec<u8> {
-    let len = key.bytes().len();
-    let bytes = key.bytes();
-    let mut key = Vec::with_capacity(len + 1);
+    let mut buf = Vec::with_capacity(1 + key.size_hint());
+    key.__encode_length_prefixed(&mut buf);
+
+    buf
+}
 
-    key.push(len as u8);
-    key.extend_from_slice(bytes);
+fn try_length_prefixed_key<K: Key + ?Sized>(key: &K) -> Result<Vec<u8>, KeyTooLong> {
+    let mut buf = Vec::with_capacity(1 + key.size_hint());
+    key.__try_encode_length_prefixed(&mut buf)?;
 
-    key
+    Ok(buf)
 }
 
+/// The error returned when an encoded map key is longer than its one-byte length prefix can
+/// describe.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+#[error("map key of {len} bytes is too long, expected at most {max} bytes")]
+pub struct KeyTooLong {
+    /// The length of the encoded key.
+    pub len: usize,
+    /// The longest key a map supports, [`MAX_KEY_LEN`].
+    pub max: usize,
+}
+
+/// The longest encoded key a map supports, in bytes.
+///
+/// Map keys are stored after a one-byte length prefix, so longer keys can't be told apart
+/// from shorter ones. Map methods taking a key panic on longer keys;
+/// [`try_entry`](MapAccess::try_entry) and [`try_entry_mut`](MapAccess::try_entry_mut) return
+/// a [`KeyTooLong`] error instead.
+pub const MAX_KEY_LEN: usize = u8::MAX as usize;
+
+/// Appends a key to a buffer, length-prefixed the way maps lay out their entries.
+///
+/// This is an implementation detail of [`key!`](crate::key!), kept as a method so that the
+/// macro can rely on auto-referencing/dereferencing for its arguments.
+#[doc(hidden)]
+pub trait LengthPrefixed: Key {
+    #[track_caller]
+    fn __encode_length_prefixed(&self, buf: &mut Vec<u8>) {
+        if let Err(e) = self.__try_encode_length_prefixed(buf) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Leaves `buf` as it was if the key is too long.
+    fn __try_encode_length_prefixed(&self, buf: &mut Vec<u8>) -> Result<(), KeyTooLong> {
+        let start = buf.len();
+
+        // reserve the length byte, encode in place, then patch the length in
+        buf.push(0);
+        self.encode(buf);
+
+        let len = buf.len() - start - 1;
+        match u8::try_from(len) {
+            Ok(len) => {
+                buf[start] = len;
+                Ok(())
+            }
+            Err(_) => {
+                buf.truncate(start);
+                Err(KeyTooLong {
+                    len,
+                    max: MAX_KEY_LEN,
+                })
+            }
+        }
+    }
+}
+
+impl<K: Key + ?Sized> LengthPrefixed for K {}
+
+#[cfg(feature = "iteration")]
 impl<K, V, S> IterableAccessor for MapAccess<K, V, S>
 where
     K: OwnedKey,
     V: Storable,
-    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    MapKeyDecodeError<V::KeyDecodeError>: From<K::Error>,
     S: IterableStorage,
 {
     type Storable = Map<K, V>;
@@ -264,74 +738,1619 @@ where
     }
 }
 
+#[cfg(feature = "iteration")]
+impl<K, V, S> BoundedIterableAccessor for MapAccess<K, V, S>
+where
+    K: OwnedKey + OrderPreservingKey,
+    V: Storable,
+    MapKeyDecodeError<V::KeyDecodeError>: From<K::Error>,
+    S: IterableStorage,
+{
+}
+
+#[cfg(feature = "iteration")]
+impl<K, V> BoundFor<Map<K, V>> for K
+where
+    K: OrderPreservingKey,
+{
+    fn into_bytes(self) -> Vec<u8> {
+        length_prefixed_key(&self)
+    }
+}
+
+/// A type that can be used as a map key.
+///
+/// The key is encoded by appending its byte representation to a caller-provided buffer. The
+/// map takes care of length-prefixing it, so implementations only need to write the key
+/// itself.
+///
+/// Types that already own a contiguous byte representation (strings, byte slices) should
+/// implement [`ByteKey`] instead and get this trait for free.
+///
+/// The encoding doesn't have to be prefix-free: since every map level stores its key after a
+/// length prefix, any key can be used at any level of nested maps. Composite keys (tuples,
+/// arrays and derived struct keys) concatenate their components without framing, though, so
+/// every component but the last has to be a [`FixedSizeKey`]. That's checked at compile time.
 pub trait Key {
-    fn bytes(&self) -> &[u8];
+    /// Append the encoded key to `buf`.
+    fn encode(&self, buf: &mut Vec<u8>);
+
+    /// The number of bytes [`encode`](Key::encode) is expected to append.
+    ///
+    /// This is only used to reserve space up front, so it's fine for it to be inaccurate.
+    fn size_hint(&self) -> usize {
+        0
+    }
 }
 
 pub trait OwnedKey: Key {
-    type Error;
+    /// The error returned when the bytes aren't a valid encoding of the key.
+    type Error: crate::StdError + Send + Sync + 'static;
+
+    /// The length of every encoded key, if they all have the same length.
+    ///
+    /// This describes the storage layout to tools, e.g. in [`meta`](crate::meta). It isn't used
+    /// for decoding, so a variable-width key can leave it at `None`.
+    const WIDTH: Option<usize> = None;
 
+    /// Decode the key from its byte representation.
+    ///
+    /// `bytes` is exactly the encoded key - no more, no less. Implementations must reject
+    /// input they would not have produced themselves rather than decoding a prefix of it.
     fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
     where
         Self: Sized;
 }
 
-impl Key for String {
-    fn bytes(&self) -> &[u8] {
-        self.as_bytes()
+/// A key that is already a contiguous byte slice and is stored as-is.
+///
+/// Every `ByteKey` is a [`Key`].
+pub trait ByteKey {
+    fn bytes(&self) -> &[u8];
+}
+
+impl<T: ByteKey + ?Sized> Key for T {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.bytes());
+    }
+
+    fn size_hint(&self) -> usize {
+        self.bytes().len()
     }
 }
 
-impl Key for str {
+impl ByteKey for String {
     fn bytes(&self) -> &[u8] {
         self.as_bytes()
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
-#[error("invalid UTF8")]
-pub struct InvalidUtf8;
-
-impl OwnedKey for String {
-    type Error = InvalidUtf8;
-
-    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
-    where
-        Self: Sized,
-    {
-        std::str::from_utf8(bytes)
-            .map(String::from)
-            .map_err(|_| InvalidUtf8)
+impl ByteKey for str {
+    fn bytes(&self) -> &[u8] {
+        self.as_bytes()
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    use crate::containers::Item;
+/// Allows keys that may or may not be owned to be used directly.
+///
+/// When the map is declared with [`String`] keys, the borrowed form can be passed to
+/// [`MapAccess::entry`] and friends by dereferencing the `Cow` to a `str`.
+///
+/// # Example
+/// ```
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
+/// use std::borrow::Cow;
+///
+/// use storey::containers::{Item, Map};
+///
+/// const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new(0);
+///
+/// fn balance<'k>(storage: &TestStorage, owner: impl Into<Cow<'k, str>>) -> Option<u64> {
+///     let owner = owner.into();
+///     BALANCES.access(storage).entry(owner.as_ref()).get().unwrap()
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// BALANCES.access(&mut storage).entry_mut("alice").set(&100).unwrap();
+///
+/// assert_eq!(balance(&storage, "alice"), Some(100));
+/// assert_eq!(balance(&storage, String::from("alice")), Some(100));
+/// assert_eq!(balance(&storage, format!("{}b", "bo")), None);
+/// ```
+impl ByteKey for Cow<'_, str> {
+    fn bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl ByteKey for [u8] {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+/// Like `Cow<str>`, for maps with [`Vec<u8>`] keys.
+impl ByteKey for Cow<'_, [u8]> {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+impl ByteKey for Vec<u8> {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+#[error("invalid UTF8")]
+pub struct InvalidUtf8;
+
+impl OwnedKey for String {
+    type Error = InvalidUtf8;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        core::str::from_utf8(bytes)
+            .map(String::from)
+            .map_err(|_| InvalidUtf8)
+    }
+}
+
+impl OwnedKey for Vec<u8> {
+    type Error = Infallible;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Decodes to an owned `Cow`, so that maps can be declared with `Cow<[u8]>` keys, and take
+/// either form of it.
+impl OwnedKey for Cow<'_, [u8]> {
+    type Error = Infallible;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        Ok(Cow::Owned(bytes.to_vec()))
+    }
+}
+
+/// Addresses are stored as their string form, exactly like [`String`] keys.
+///
+/// Decoding only checks that the key is valid UTF-8. It doesn't validate the address, which
+/// was validated before it was stored.
+///
+/// Lookups take an `&Addr`. To look up an address that hasn't been validated (say, straight
+/// from a query message), wrap it with [`Addr::unchecked`](cosmwasm_std::Addr::unchecked).
+/// Nothing is checked then, but an invalid address can't have been stored in the first place,
+/// so the lookup simply finds nothing.
+///
+/// # Example
+/// ```
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
+/// use cosmwasm_std::Addr;
+/// use storey::containers::{Item, Map};
+///
+/// const BALANCES: Map<Addr, Item<u64, TestEncoding>> = Map::new(0);
+///
+/// let mut storage = TestStorage::new();
+/// let alice = Addr::unchecked("cosmwasm1alice");
+/// BALANCES.access(&mut storage).entry_mut(&alice).set(&100).unwrap();
+///
+/// let unvalidated = "cosmwasm1alice";
+/// let balance = BALANCES.access(&storage).entry(&Addr::unchecked(unvalidated)).get();
+/// assert_eq!(balance, Ok(Some(100)));
+/// ```
+#[cfg(feature = "cosmwasm-std")]
+impl ByteKey for cosmwasm_std::Addr {
+    fn bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[cfg(feature = "cosmwasm-std")]
+impl OwnedKey for cosmwasm_std::Addr {
+    type Error = InvalidUtf8;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        String::from_bytes(bytes).map(cosmwasm_std::Addr::unchecked)
+    }
+}
+
+/// Canonical addresses are stored as their raw bytes, exactly like `Vec<u8>` keys.
+///
+/// Like any variable-length key, a canonical address is length-prefixed, so map keys are
+/// ordered by length first, and only then by their bytes. Addresses of the same length (which
+/// is usually all of them) come out sorted by their bytes.
+///
+/// For the same reason, a canonical address can't be a non-final element of a tuple key, since
+/// those need to have a fixed size. To key by an address and something else, nest one map in
+/// another, which length-prefixes every level.
+///
+/// # Example
+/// ```
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
+/// use cosmwasm_std::CanonicalAddr;
+/// use storey::containers::{Item, Map};
+///
+/// const ALLOWANCES: Map<CanonicalAddr, Map<String, Item<u64, TestEncoding>>> = Map::new(0);
+///
+/// let mut storage = TestStorage::new();
+/// let owner = CanonicalAddr::from(vec![7; 20]);
+/// ALLOWANCES
+///     .access(&mut storage)
+///     .entry_mut(&owner)
+///     .entry_mut("uatom")
+///     .set(&100)
+///     .unwrap();
+///
+/// let allowance = ALLOWANCES.access(&storage).entry(&owner).entry("uatom").get();
+/// assert_eq!(allowance, Ok(Some(100)));
+/// ```
+#[cfg(feature = "cosmwasm-std")]
+impl ByteKey for cosmwasm_std::CanonicalAddr {
+    fn bytes(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+#[cfg(feature = "cosmwasm-std")]
+impl OwnedKey for cosmwasm_std::CanonicalAddr {
+    type Error = Infallible;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        Ok(cosmwasm_std::CanonicalAddr::from(bytes))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+#[error("invalid key length, expected {expected} bytes, got {actual}")]
+pub struct InvalidKeyLength {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+/// Unsigned integers are encoded as fixed-width big-endian, so that byte order matches
+/// numeric order.
+macro_rules! unsigned_int_key {
+    ($($t:ty),*) => {
+        $(
+            impl Key for $t {
+                fn encode(&self, buf: &mut Vec<u8>) {
+                    buf.extend_from_slice(&self.to_be_bytes());
+                }
+
+                fn size_hint(&self) -> usize {
+                    core::mem::size_of::<$t>()
+                }
+            }
+
+            impl OwnedKey for $t {
+                type Error = InvalidKeyLength;
+
+                const WIDTH: Option<usize> = Some(core::mem::size_of::<$t>());
+
+                fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+                where
+                    Self: Sized,
+                {
+                    let bytes = bytes.try_into().map_err(|_| InvalidKeyLength {
+                        expected: core::mem::size_of::<$t>(),
+                        actual: bytes.len(),
+                    })?;
+
+                    Ok(<$t>::from_be_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+/// Signed integers are encoded as fixed-width big-endian with the sign bit flipped, so that
+/// negative numbers sort before positive ones.
+macro_rules! signed_int_key {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl Key for $t {
+                fn encode(&self, buf: &mut Vec<u8>) {
+                    let flipped = (*self as $u) ^ (1 << (<$u>::BITS - 1));
+                    buf.extend_from_slice(&flipped.to_be_bytes());
+                }
+
+                fn size_hint(&self) -> usize {
+                    core::mem::size_of::<$t>()
+                }
+            }
+
+            impl OwnedKey for $t {
+                type Error = InvalidKeyLength;
+
+                const WIDTH: Option<usize> = Some(core::mem::size_of::<$t>());
+
+                fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+                where
+                    Self: Sized,
+                {
+                    let flipped = <$u>::from_bytes(bytes)?;
+
+                    Ok((flipped ^ (1 << (<$u>::BITS - 1))) as $t)
+                }
+            }
+        )*
+    };
+}
+
+unsigned_int_key!(u8, u16, u32, u64, u128);
+signed_int_key!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128);
+
+/// A key that always encodes to exactly [`SIZE`](FixedSizeKey::SIZE) bytes.
+///
+/// Fixed-width keys can be concatenated without any framing and still be split apart
+/// unambiguously, which is what array, tuple and derived struct keys rely on. An
+/// implementation whose [`encode`](Key::encode) doesn't always write exactly `SIZE` bytes
+/// makes the components of those keys bleed into each other.
+pub trait FixedSizeKey: OwnedKey {
+    /// The length of the encoded key.
+    const SIZE: usize;
+}
+
+macro_rules! fixed_size_key {
+    ($($t:ty),*) => {
+        $(
+            impl FixedSizeKey for $t {
+                const SIZE: usize = core::mem::size_of::<$t>();
+            }
+        )*
+    };
+}
+
+fixed_size_key!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+/// Arrays of fixed-width keys are encoded as the concatenation of their elements.
+///
+/// For integer elements, the encoding sorts the same way arrays compare: element by element.
+///
+/// # Example
+/// ```
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
+/// use storey::containers::{Item, Map};
+///
+/// let mut storage = TestStorage::new();
+/// // (epoch, slot)
+/// let map = Map::<[u64; 2], Item<u64, TestEncoding>>::new(0);
+/// let mut access = map.access(&mut storage);
+///
+/// access.entry_mut(&[3, 14]).set(&1337).unwrap();
+/// assert_eq!(access.entry(&[3, 14]).get().unwrap(), Some(1337));
+/// assert_eq!(access.entry(&[14, 3]).get().unwrap(), None);
+/// ```
+impl<T: FixedSizeKey, const N: usize> Key for [T; N] {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        for element in self {
+            element.encode(buf);
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        T::SIZE * N
+    }
+}
+
+impl<T, const N: usize> OwnedKey for [T; N]
+where
+    T: FixedSizeKey,
+    T::Error: From<InvalidKeyLength>,
+{
+    type Error = T::Error;
+
+    const WIDTH: Option<usize> = Some(T::SIZE * N);
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        if bytes.len() != T::SIZE * N {
+            return Err(InvalidKeyLength {
+                expected: T::SIZE * N,
+                actual: bytes.len(),
+            }
+            .into());
+        }
+
+        let elements = bytes
+            .chunks_exact(T::SIZE)
+            .map(T::from_bytes)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // the length check above guarantees exactly N elements
+        Ok(elements
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("array key has the wrong number of elements")))
+    }
+}
+
+impl<T, const N: usize> FixedSizeKey for [T; N]
+where
+    T: FixedSizeKey,
+    T::Error: From<InvalidKeyLength>,
+{
+    const SIZE: usize = T::SIZE * N;
+}
+
+/// An error decoding a tuple key, identifying the component that failed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum TupleKeyDecodeError<A, B, C = Infallible> {
+    #[error("first key component: {0}")]
+    First(A),
+
+    #[error("second key component: {0}")]
+    Second(B),
+
+    #[error("third key component: {0}")]
+    Third(C),
+}
+
+impl<I, A, B, C> From<TupleKeyDecodeError<A, B, C>> for MapKeyDecodeError<I>
+where
+    I: core::fmt::Display,
+    MapKeyDecodeError<I>: From<A> + From<B> + From<C>,
+{
+    fn from(e: TupleKeyDecodeError<A, B, C>) -> Self {
+        match e {
+            TupleKeyDecodeError::First(e) => e.into(),
+            TupleKeyDecodeError::Second(e) => e.into(),
+            TupleKeyDecodeError::Third(e) => e.into(),
+        }
+    }
+}
+
+/// An error decoding a key derived with `#[derive(OwnedKey)]`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum StructKeyDecodeError {
+    #[error("invalid key length, expected {expected} bytes, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+
+    #[error("key too short, expected at least {expected} bytes, got {actual}")]
+    TooShort { expected: usize, actual: usize },
+
+    #[error("field `{field}`: {source}")]
+    Field {
+        field: &'static str,
+        source: MapKeyDecodeError<Infallible>,
+    },
+}
+
+impl StructKeyDecodeError {
+    /// The error decoding `field`, for the derived impls.
+    #[doc(hidden)]
+    pub fn __field<E>(field: &'static str, e: E) -> Self
+    where
+        MapKeyDecodeError<Infallible>: From<E>,
+    {
+        Self::Field {
+            field,
+            source: e.into(),
+        }
+    }
+}
+
+impl<I: core::fmt::Display> From<StructKeyDecodeError> for MapKeyDecodeError<I> {
+    fn from(e: StructKeyDecodeError) -> Self {
+        match e {
+            StructKeyDecodeError::InvalidLength { expected, actual } => {
+                Self::InvalidLength { expected, actual }
+            }
+            StructKeyDecodeError::TooShort { expected, .. } => Self::KeyTooShort(expected),
+            StructKeyDecodeError::Field { source, .. } => match source {
+                MapKeyDecodeError::EmptyKey => Self::EmptyKey,
+                MapKeyDecodeError::KeyTooShort(len) => Self::KeyTooShort(len),
+                MapKeyDecodeError::InvalidUtf8 => Self::InvalidUtf8,
+                MapKeyDecodeError::InvalidLength { expected, actual } => {
+                    Self::InvalidLength { expected, actual }
+                }
+                MapKeyDecodeError::OutOfRange => Self::OutOfRange,
+                MapKeyDecodeError::Inner(e) => match e {},
+            },
+        }
+    }
+}
+
+/// Tuple keys are encoded as the concatenation of their components.
+///
+/// Only the last component may be of variable width. All the others have to be
+/// [`FixedSizeKey`]s, so that decoding knows where each of them ends.
+///
+/// # Example
+/// ```
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
+/// use storey::containers::{Item, Map};
+///
+/// let mut storage = TestStorage::new();
+/// let map = Map::<(u32, String), Item<u64, TestEncoding>>::new(0);
+/// let mut access = map.access(&mut storage);
+///
+/// access.entry_mut(&(7, "foo".to_string())).set(&1337).unwrap();
+/// assert_eq!(access.entry(&(7, "foo".to_string())).get().unwrap(), Some(1337));
+/// ```
+impl<A, B> Key for (A, B)
+where
+    A: FixedSizeKey,
+    B: Key,
+{
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.0.encode(buf);
+        self.1.encode(buf);
+    }
+
+    fn size_hint(&self) -> usize {
+        A::SIZE + self.1.size_hint()
+    }
+}
+
+impl<A, B> OwnedKey for (A, B)
+where
+    A: FixedSizeKey,
+    B: OwnedKey,
+{
+    type Error = TupleKeyDecodeError<A::Error, B::Error>;
+
+    const WIDTH: Option<usize> = match B::WIDTH {
+        Some(width) => Some(A::SIZE + width),
+        None => None,
+    };
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        let (a, b) = bytes.split_at(A::SIZE.min(bytes.len()));
+
+        Ok((
+            A::from_bytes(a).map_err(TupleKeyDecodeError::First)?,
+            B::from_bytes(b).map_err(TupleKeyDecodeError::Second)?,
+        ))
+    }
+}
+
+impl<A, B, C> Key for (A, B, C)
+where
+    A: FixedSizeKey,
+    B: FixedSizeKey,
+    C: Key,
+{
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.0.encode(buf);
+        self.1.encode(buf);
+        self.2.encode(buf);
+    }
+
+    fn size_hint(&self) -> usize {
+        A::SIZE + B::SIZE + self.2.size_hint()
+    }
+}
+
+impl<A, B, C> OwnedKey for (A, B, C)
+where
+    A: FixedSizeKey,
+    B: FixedSizeKey,
+    C: OwnedKey,
+{
+    type Error = TupleKeyDecodeError<A::Error, B::Error, C::Error>;
+
+    const WIDTH: Option<usize> = match C::WIDTH {
+        Some(width) => Some(A::SIZE + B::SIZE + width),
+        None => None,
+    };
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        let (a, rest) = bytes.split_at(A::SIZE.min(bytes.len()));
+        let (b, c) = rest.split_at(B::SIZE.min(rest.len()));
+
+        Ok((
+            A::from_bytes(a).map_err(TupleKeyDecodeError::First)?,
+            B::from_bytes(b).map_err(TupleKeyDecodeError::Second)?,
+            C::from_bytes(c).map_err(TupleKeyDecodeError::Third)?,
+        ))
+    }
+}
+
+/// A marker for keys whose encoding sorts the same way as the keys themselves.
+///
+/// Bounded iteration over a [`Map`] (see [`BoundedIterableAccessor`]) compares raw keys
+/// byte-wise. The result is only meaningful if, for any two keys `a < b`, the stored form of
+/// `a` (length prefix included) sorts before the stored form of `b`. This trait marks the key
+/// types for which that holds.
+///
+/// Fixed-width integers qualify. Strings do not: the length prefix means `"b"` is stored
+/// before `"aa"`. Neither do [`Hashed`](super::Hashed) keys.
+///
+/// Implementing this for a type that doesn't uphold the property won't cause memory
+/// unsafety, but bounded iteration will silently return the wrong entries.
+pub trait OrderPreservingKey: Key {}
+
+macro_rules! order_preserving_key {
+    ($($t:ty),*) => {
+        $(
+            impl OrderPreservingKey for $t {}
+        )*
+    };
+}
+
+order_preserving_key!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, usize, isize);
+
+impl<T, const N: usize> OrderPreservingKey for [T; N] where T: OrderPreservingKey + FixedSizeKey {}
+
+impl<A, B> OrderPreservingKey for (A, B)
+where
+    A: OrderPreservingKey + FixedSizeKey,
+    B: OrderPreservingKey,
+{
+}
+
+impl<A, B, C> OrderPreservingKey for (A, B, C)
+where
+    A: OrderPreservingKey + FixedSizeKey,
+    B: OrderPreservingKey + FixedSizeKey,
+    C: OrderPreservingKey,
+{
+}
+
+/// An error decoding a [`usize`] or [`isize`] key.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum SizeKeyDecodeError {
+    #[error(transparent)]
+    InvalidLength(#[from] InvalidKeyLength),
+
+    #[error("key out of range for this platform's pointer width")]
+    OutOfRange,
+}
+
+/// `usize` and `isize` keys are always encoded as 8 bytes - the same way as [`u64`] and
+/// [`i64`] - regardless of the platform. This keeps the storage layout identical between
+/// native code (tests, off-chain tooling) and 32-bit wasm.
+///
+/// The flip side is that on 32-bit platforms, decoding fails with
+/// [`SizeKeyDecodeError::OutOfRange`] for keys that were written by a 64-bit platform and
+/// don't fit.
+macro_rules! size_key {
+    ($($t:ty => $wide:ty),*) => {
+        $(
+            impl Key for $t {
+                fn encode(&self, buf: &mut Vec<u8>) {
+                    (*self as $wide).encode(buf)
+                }
 
-    use mocks::backend::TestStorage;
-    use mocks::encoding::TestEncoding;
+                fn size_hint(&self) -> usize {
+                    8
+                }
+            }
+
+            impl OwnedKey for $t {
+                type Error = SizeKeyDecodeError;
+
+                const WIDTH: Option<usize> = Some(8);
+
+                fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+                where
+                    Self: Sized,
+                {
+                    narrow(<$wide>::from_bytes(bytes)?)
+                }
+            }
+        )*
+    };
+}
+
+size_key!(usize => u64, isize => i64);
+
+impl FixedSizeKey for usize {
+    const SIZE: usize = 8;
+}
+
+impl FixedSizeKey for isize {
+    const SIZE: usize = 8;
+}
+
+fn narrow<W, T: TryFrom<W>>(wide: W) -> Result<T, SizeKeyDecodeError> {
+    T::try_from(wide).map_err(|_| SizeKeyDecodeError::OutOfRange)
+}
+
+/// The *CosmWasm* unsigned integers are encoded as fixed-width big-endian, like the primitive
+/// ones, so that byte order matches numeric order. `Uint64` and `Uint128` are stored exactly
+/// like [`u64`] and [`u128`].
+///
+/// # Example
+/// ```
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
+/// use cosmwasm_std::Uint128;
+/// use storey::containers::{BoundedIterableAccessor as _, Item, Map};
+///
+/// // orders by price level
+/// const ORDERS: Map<Uint128, Item<u64, TestEncoding>> = Map::new(0);
+///
+/// let mut storage = TestStorage::new();
+/// let mut access = ORDERS.access(&mut storage);
+/// for price in [300u128, 20, 1000] {
+///     access.entry_mut(&Uint128::new(price)).set(&1).unwrap();
+/// }
+///
+/// let cheap: Vec<_> = access
+///     .bounded_keys(None::<Uint128>, Some(Uint128::new(500)))
+///     .map(|key| key.unwrap().0)
+///     .collect();
+/// assert_eq!(cheap, [Uint128::new(20), Uint128::new(300)]);
+/// ```
+#[cfg(feature = "cosmwasm-std")]
+macro_rules! cosmwasm_uint_key {
+    ($($t:ident => $prim:ty),*) => {
+        $(
+            impl Key for cosmwasm_std::$t {
+                fn encode(&self, buf: &mut Vec<u8>) {
+                    <$prim>::from(*self).encode(buf)
+                }
+
+                fn size_hint(&self) -> usize {
+                    core::mem::size_of::<$prim>()
+                }
+            }
+
+            impl OwnedKey for cosmwasm_std::$t {
+                type Error = InvalidKeyLength;
+
+                const WIDTH: Option<usize> = Some(core::mem::size_of::<$prim>());
+
+                fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+                where
+                    Self: Sized,
+                {
+                    <$prim>::from_bytes(bytes).map(Self::from)
+                }
+            }
+
+            impl FixedSizeKey for cosmwasm_std::$t {
+                const SIZE: usize = core::mem::size_of::<$prim>();
+            }
+
+            impl OrderPreservingKey for cosmwasm_std::$t {}
+        )*
+    };
+}
+
+#[cfg(feature = "cosmwasm-std")]
+cosmwasm_uint_key!(Uint64 => u64, Uint128 => u128);
+
+#[cfg(feature = "cosmwasm-std")]
+impl Key for cosmwasm_std::Uint256 {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_be_bytes());
+    }
+
+    fn size_hint(&self) -> usize {
+        32
+    }
+}
+
+#[cfg(feature = "cosmwasm-std")]
+impl OwnedKey for cosmwasm_std::Uint256 {
+    type Error = InvalidKeyLength;
+
+    const WIDTH: Option<usize> = Some(32);
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        let bytes = bytes.try_into().map_err(|_| InvalidKeyLength {
+            expected: 32,
+            actual: bytes.len(),
+        })?;
+
+        Ok(Self::from_be_bytes(bytes))
+    }
+}
+
+#[cfg(feature = "cosmwasm-std")]
+impl FixedSizeKey for cosmwasm_std::Uint256 {
+    const SIZE: usize = 32;
+}
+
+#[cfg(feature = "cosmwasm-std")]
+impl OrderPreservingKey for cosmwasm_std::Uint256 {}
+
+/// Timestamps are stored as their number of nanoseconds, exactly like a [`u64`] key, so they
+/// iterate chronologically.
+///
+/// Timestamps can be passed as bounds directly, which makes scanning for everything up to a
+/// point in time a bounded iteration.
+///
+/// # Example
+/// ```
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
+/// use cosmwasm_std::Timestamp;
+/// use storey::containers::{BoundedIterableAccessor as _, Item, Map};
+///
+/// // expiration -> claim id
+/// const EXPIRATIONS: Map<Timestamp, Item<u64, TestEncoding>> = Map::new(0);
+///
+/// let mut storage = TestStorage::new();
+/// let mut access = EXPIRATIONS.access(&mut storage);
+/// access.entry_mut(&Timestamp::from_seconds(300)).set(&1).unwrap();
+/// access.entry_mut(&Timestamp::from_seconds(100)).set(&2).unwrap();
+///
+/// // everything expiring before `now`
+/// let now = Timestamp::from_seconds(200);
+/// let expired: Vec<_> = access
+///     .bounded_values(None::<Timestamp>, Some(now))
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(expired, [2]);
+/// ```
+#[cfg(feature = "cosmwasm-std")]
+impl Key for cosmwasm_std::Timestamp {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.nanos().encode(buf)
+    }
+
+    fn size_hint(&self) -> usize {
+        8
+    }
+}
+
+#[cfg(feature = "cosmwasm-std")]
+impl OwnedKey for cosmwasm_std::Timestamp {
+    type Error = InvalidKeyLength;
+
+    const WIDTH: Option<usize> = Some(8);
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        u64::from_bytes(bytes).map(Self::from_nanos)
+    }
+}
+
+#[cfg(feature = "cosmwasm-std")]
+impl FixedSizeKey for cosmwasm_std::Timestamp {
+    const SIZE: usize = 8;
+}
+
+#[cfg(feature = "cosmwasm-std")]
+impl OrderPreservingKey for cosmwasm_std::Timestamp {}
+
+/// Decimals are stored as their atomics, the fixed-point integer behind them: a `Decimal` like
+/// a [`Uint128`](cosmwasm_std::Uint128) key, and a `Decimal256` like a
+/// [`Uint256`](cosmwasm_std::Uint256) key.
+///
+/// The number of decimal places is fixed, so the atomics of two decimals compare the same way
+/// as the decimals do, and iteration is in numeric order. That makes decimals a good fit for
+/// price levels.
+///
+/// # Example
+/// ```
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
+/// use core::str::FromStr;
+///
+/// use cosmwasm_std::Decimal;
+/// use storey::containers::{BoundedIterableAccessor as _, Item, Map};
+///
+/// // price -> amount asked at that price
+/// const ASKS: Map<Decimal, Item<u64, TestEncoding>> = Map::new(0);
+///
+/// let mut storage = TestStorage::new();
+/// let mut access = ASKS.access(&mut storage);
+/// for (price, amount) in [("1.5", 10), ("0.25", 20), ("12", 30), ("1.05", 40)] {
+///     let price = Decimal::from_str(price).unwrap();
+///     access.entry_mut(&price).set(&amount).unwrap();
+/// }
+///
+/// // the best asks, up to a price of 2
+/// let limit = Decimal::from_str("2").unwrap();
+/// let asks: Vec<_> = access
+///     .bounded_pairs(None::<Decimal>, Some(limit))
+///     .map(|entry| entry.map(|((price, ()), amount)| (price.to_string(), amount)))
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(
+///     asks,
+///     [("0.25".to_string(), 20), ("1.05".to_string(), 40), ("1.5".to_string(), 10)]
+/// );
+/// ```
+#[cfg(feature = "cosmwasm-std")]
+macro_rules! cosmwasm_decimal_key {
+    ($($t:ident => $atomics:ident),*) => {
+        $(
+            impl Key for cosmwasm_std::$t {
+                fn encode(&self, buf: &mut Vec<u8>) {
+                    self.atomics().encode(buf)
+                }
+
+                fn size_hint(&self) -> usize {
+                    <cosmwasm_std::$atomics as FixedSizeKey>::SIZE
+                }
+            }
+
+            impl OwnedKey for cosmwasm_std::$t {
+                type Error = InvalidKeyLength;
+
+                const WIDTH: Option<usize> = Some(<cosmwasm_std::$atomics as FixedSizeKey>::SIZE);
+
+                fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+                where
+                    Self: Sized,
+                {
+                    cosmwasm_std::$atomics::from_bytes(bytes).map(Self::new)
+                }
+            }
+
+            impl FixedSizeKey for cosmwasm_std::$t {
+                const SIZE: usize = <cosmwasm_std::$atomics as FixedSizeKey>::SIZE;
+            }
+
+            impl OrderPreservingKey for cosmwasm_std::$t {}
+        )*
+    };
+}
+
+#[cfg(feature = "cosmwasm-std")]
+cosmwasm_decimal_key!(Decimal => Uint128, Decimal256 => Uint256);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::column::ColumnKeyDecodeError;
+    use crate::containers::item::ItemKeyDecodeError;
+    use crate::containers::{Column, Item};
+
+    use proptest::prelude::*;
     use storey_storage::Storage as _;
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
+
+    #[test]
+    fn map() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+
+        map.access(&mut storage)
+            .entry_mut("foo")
+            .set(&1337)
+            .unwrap();
+
+        assert_eq!(map.access(&storage).entry("foo").get().unwrap(), Some(1337));
+        assert_eq!(
+            storage.get(&[0, 3, 102, 111, 111]),
+            Some(1337u64.to_le_bytes().to_vec())
+        );
+        assert_eq!(map.access(&storage).entry("bar").get().unwrap(), None);
+    }
+
+    #[test]
+    fn cow_keys() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        let borrowed: Cow<str> = Cow::Borrowed("foo");
+        let owned: Cow<str> = Cow::Owned("bar".to_string());
+
+        access.entry_mut(&*borrowed).set(&1337).unwrap();
+        access.entry_mut(&*owned).set(&42).unwrap();
+
+        assert_eq!(access.entry("foo").get().unwrap(), Some(1337));
+        assert_eq!(access.entry(owned.as_ref()).get().unwrap(), Some(42));
+        assert_eq!(ByteKey::bytes(&borrowed), ByteKey::bytes("foo"));
+        assert_eq!(
+            storage.get(&[0, 3, 98, 97, 114]),
+            Some(42u64.to_le_bytes().to_vec())
+        );
+    }
+
+    #[test]
+    fn cow_byte_keys() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<Cow<[u8]>, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        let borrowed: Cow<[u8]> = Cow::Borrowed(&[1, 2, 3]);
+        let owned: Cow<[u8]> = Cow::Owned(vec![4, 5]);
+
+        access.entry_mut(&borrowed).set(&1337).unwrap();
+        access.entry_mut(&owned).set(&42).unwrap();
+
+        assert_eq!(access.entry(&borrowed).get().unwrap(), Some(1337));
+        assert_eq!(
+            access.entry(&Cow::Borrowed(&[4, 5][..])).get().unwrap(),
+            Some(42)
+        );
+        // the length comes first, so the shorter key does too
+        assert_eq!(
+            access.pairs().collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![((owned, ()), 42), ((borrowed, ()), 1337)]
+        );
+        assert_eq!(
+            storage.get(&[0, 2, 4, 5]),
+            Some(42u64.to_le_bytes().to_vec())
+        );
+        // the same entries as with `Vec<u8>` keys
+        let map = Map::<Vec<u8>, Item<u64, TestEncoding>>::new(0);
+        assert_eq!(
+            map.access(&storage).entry(&[1, 2, 3][..]).get().unwrap(),
+            Some(1337)
+        );
+    }
+
+    #[test]
+    fn longest_key() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        let key = "a".repeat(MAX_KEY_LEN);
+        access.entry_mut(&key).set(&1337).unwrap();
+        access.try_entry_mut(&key).unwrap().set(&42).unwrap();
+
+        assert_eq!(access.entry(&key).get().unwrap(), Some(42));
+        assert_eq!(
+            access.keys().collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![(key.clone(), ())]
+        );
+        assert_eq!(
+            storage.get(&[&[0, 255][..], key.as_bytes()].concat()),
+            Some(42u64.to_le_bytes().to_vec())
+        );
+    }
 
     #[test]
-    fn map() {
+    fn key_too_long() {
         let mut storage = TestStorage::new();
 
+        let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        let key = "a".repeat(MAX_KEY_LEN + 1);
+        let err = KeyTooLong { len: 256, max: 255 };
+        assert_eq!(access.try_entry(&key).err(), Some(err));
+        assert_eq!(access.try_entry_mut(&key).err(), Some(err));
+        assert_eq!(
+            access
+                .try_entry_mut("foo")
+                .unwrap()
+                .try_entry_mut(&key)
+                .err(),
+            Some(err)
+        );
+        assert_eq!(access.keys().count(), 0);
+
+        let mut buf = vec![1, 2];
+        assert_eq!(key.__try_encode_length_prefixed(&mut buf), Err(err));
+        assert_eq!(buf, [1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "map key of 256 bytes is too long, expected at most 255 bytes")]
+    fn key_too_long_panics() {
+        let storage = TestStorage::new();
+
         let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        map.access(&storage).entry(&"a".repeat(256));
+    }
+
+    #[test]
+    #[should_panic(expected = "map key of 300 bytes is too long")]
+    fn nested_key_too_long_panics() {
+        let mut storage = TestStorage::new();
 
+        let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
         map.access(&mut storage)
             .entry_mut("foo")
+            .entry_mut(&"a".repeat(300))
+            .set(&1)
+            .unwrap();
+    }
+
+    #[test]
+    fn int_keys() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut(&0x01020304).set(&1337).unwrap();
+        access.entry_mut(&7).set(&42).unwrap();
+
+        assert_eq!(access.entry(&0x01020304).get().unwrap(), Some(1337));
+        assert_eq!(access.entry(&8).get().unwrap(), None);
+        assert_eq!(
+            storage.get(&[0, 4, 1, 2, 3, 4]),
+            Some(1337u64.to_le_bytes().to_vec())
+        );
+
+        let keys = map
+            .access(&storage)
+            .keys()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(keys, vec![(7, ()), (0x01020304, ())]);
+    }
+
+    #[test]
+    fn signed_int_keys_sort_numerically() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<i16, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        for k in [0, -1, i16::MAX, 1, i16::MIN, -300] {
+            access.entry_mut(&k).set(&0).unwrap();
+        }
+
+        assert_eq!(storage.get(&[0, 2, 0x7f, 0xff]), Some(vec![0; 8]));
+        assert_eq!(storage.get(&[0, 2, 0x80, 0x00]), Some(vec![0; 8]));
+
+        let keys = map
+            .access(&storage)
+            .keys()
+            .map(|k| k.map(|(k, ())| k))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(keys, vec![i16::MIN, -300, -1, 0, 1, i16::MAX]);
+    }
+
+    #[test]
+    fn int_keys_roundtrip() {
+        fn roundtrip<K: OwnedKey + PartialEq + core::fmt::Debug>(key: K) {
+            let mut buf = Vec::new();
+            key.encode(&mut buf);
+            assert_eq!(buf.len(), key.size_hint());
+            assert_eq!(K::from_bytes(&buf).unwrap(), key);
+        }
+
+        roundtrip(u8::MAX);
+        roundtrip(0u16);
+        roundtrip(u64::MAX);
+        roundtrip(u128::MAX - 1);
+        roundtrip(i8::MIN);
+        roundtrip(-1i32);
+        roundtrip(i64::MAX);
+        roundtrip(i128::MIN + 1);
+
+        assert_eq!(
+            u32::from_bytes(&[0, 0, 1]),
+            Err(InvalidKeyLength {
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert_eq!(
+            Map::<i64, Item<u64, TestEncoding>>::decode_key(&[2, 0, 0]),
+            Err(MapKeyDecodeError::InvalidLength {
+                expected: 8,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn size_keys() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<usize, Map<isize, Item<u64, TestEncoding>>>::new(0);
+        map.access(&mut storage)
+            .entry_mut(&3)
+            .entry_mut(&-1)
             .set(&1337)
             .unwrap();
 
-        assert_eq!(map.access(&storage).entry("foo").get().unwrap(), Some(1337));
+        // same layout as u64/i64 on every platform
         assert_eq!(
-            storage.get(&[0, 3, 102, 111, 111]),
+            storage.get(&[0, 8, 0, 0, 0, 0, 0, 0, 0, 3, 8, 127, 255, 255, 255, 255, 255, 255, 255]),
+            Some(1337u64.to_le_bytes().to_vec())
+        );
+
+        let keys = map
+            .access(&storage)
+            .keys()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(keys, vec![(3, (-1, ()))]);
+
+        assert_eq!(
+            usize::from_bytes(&[0, 0, 0, 3]),
+            Err(SizeKeyDecodeError::InvalidLength(InvalidKeyLength {
+                expected: 8,
+                actual: 4
+            }))
+        );
+    }
+
+    #[test]
+    fn size_keys_out_of_range() {
+        // what decoding a 64-bit key looks like on a 32-bit platform
+        assert_eq!(narrow::<u64, u32>(u32::MAX as u64), Ok(u32::MAX));
+        assert_eq!(
+            narrow::<u64, u32>(u32::MAX as u64 + 1),
+            Err(SizeKeyDecodeError::OutOfRange)
+        );
+        assert_eq!(
+            narrow::<i64, i32>(i32::MIN as i64 - 1),
+            Err(SizeKeyDecodeError::OutOfRange)
+        );
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "32")]
+    fn size_keys_out_of_range_native() {
+        assert_eq!(
+            usize::from_bytes(&(u32::MAX as u64 + 1).to_be_bytes()),
+            Err(SizeKeyDecodeError::OutOfRange)
+        );
+        assert_eq!(
+            Map::<isize, Item<u64, TestEncoding>>::decode_key(&[8, 0, 0, 0, 0, 0, 0, 0, 0]),
+            Err(MapKeyDecodeError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn array_keys() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<[u32; 2], Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut(&[1, 2]).set(&1337).unwrap();
+
+        assert_eq!(access.entry(&[1, 2]).get().unwrap(), Some(1337));
+        assert_eq!(
+            storage.get(&[0, 8, 0, 0, 0, 1, 0, 0, 0, 2]),
+            Some(1337u64.to_le_bytes().to_vec())
+        );
+
+        assert_eq!(
+            <[u32; 2]>::from_bytes(&[0, 0, 0, 1, 0, 0, 0]),
+            Err(InvalidKeyLength {
+                expected: 8,
+                actual: 7
+            })
+        );
+        assert_eq!(
+            <[usize; 2]>::from_bytes(&[0; 12]),
+            Err(SizeKeyDecodeError::InvalidLength(InvalidKeyLength {
+                expected: 16,
+                actual: 12
+            }))
+        );
+        assert_eq!(<[[i8; 2]; 2]>::from_bytes(&[128; 4]), Ok([[0; 2]; 2]));
+        assert_eq!(<[u64; 0]>::from_bytes(&[]), Ok([]));
+    }
+
+    #[test]
+    fn tuple_keys() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<(u8, i16, String), Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access
+            .entry_mut(&(1, -1, "foo".to_string()))
+            .set(&1337)
+            .unwrap();
+
+        assert_eq!(
+            storage.get(&[0, 6, 1, 127, 255, 102, 111, 111]),
+            Some(1337u64.to_le_bytes().to_vec())
+        );
+
+        let keys = map
+            .access(&storage)
+            .keys()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(keys, vec![((1, -1, "foo".to_string()), ())]);
+
+        assert_eq!(
+            <(u32, String)>::from_bytes(&[0, 0, 1]),
+            Err(TupleKeyDecodeError::First(InvalidKeyLength {
+                expected: 4,
+                actual: 3
+            }))
+        );
+        assert_eq!(
+            <(u8, i16, String)>::from_bytes(&[1, 127, 255, 255]),
+            Err(TupleKeyDecodeError::Third(InvalidUtf8))
+        );
+        assert_eq!(
+            Map::<(u8, u8), Item<u64, TestEncoding>>::decode_key(&[1, 0]),
+            Err(MapKeyDecodeError::InvalidLength {
+                expected: 1,
+                actual: 0
+            })
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn array_keys_roundtrip(key: [u64; 2]) {
+            let mut buf = Vec::new();
+            key.encode(&mut buf);
+
+            prop_assert_eq!(buf.len(), <[u64; 2]>::SIZE);
+            prop_assert_eq!(<[u64; 2]>::from_bytes(&buf), Ok(key));
+        }
+
+        #[test]
+        fn array_keys_preserve_order(a: [i32; 4], b: [i32; 4]) {
+            let (mut a_buf, mut b_buf) = (Vec::new(), Vec::new());
+            a.encode(&mut a_buf);
+            b.encode(&mut b_buf);
+
+            prop_assert_eq!(a.cmp(&b), a_buf.cmp(&b_buf));
+        }
+    }
+
+    #[test]
+    fn bounded_iteration() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<i32, Map<String, Item<u64, TestEncoding>>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        for k in [-300, -2, 0, 5, 256] {
+            access.entry_mut(&k).entry_mut("a").set(&0).unwrap();
+            access.entry_mut(&k).entry_mut("b").set(&1).unwrap();
+        }
+
+        let keys = access
+            .bounded_keys(Some(-2), Some(256))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            keys,
+            vec![
+                (-2, ("a".to_string(), ())),
+                (-2, ("b".to_string(), ())),
+                (0, ("a".to_string(), ())),
+                (0, ("b".to_string(), ())),
+                (5, ("a".to_string(), ())),
+                (5, ("b".to_string(), ())),
+            ]
+        );
+
+        let values = access
+            .bounded_values(None::<i32>, Some(0))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, vec![0, 1, 0, 1]);
+
+        let pairs = access
+            .bounded_pairs(Some(6), None::<i32>)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                ((256, ("a".to_string(), ())), 0),
+                ((256, ("b".to_string(), ())), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn remove_range() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<i32, Map<String, Column<u64, TestEncoding>>>::new(0);
+        let other = Map::<i32, Item<u64, TestEncoding>>::new(1);
+
+        for k in [-300, -2, 0, 5, 256] {
+            let mut access = map.access(&mut storage);
+            access.entry_mut(&k).entry_mut("a").push(&0).unwrap();
+            access.entry_mut(&k).entry_mut("b").push(&1).unwrap();
+            other.access(&mut storage).entry_mut(&k).set(&2).unwrap();
+        }
+
+        let mut access = map.access(&mut storage);
+        let bounded = access
+            .bounded_keys(Some(-2), Some(256))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let all = access.keys().collect::<Result<Vec<_>, _>>().unwrap();
+
+        access.remove_range(Some(-2), Some(256));
+
+        // exactly what bounded iteration yields is gone
+        let left = access.keys().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            left,
+            all.into_iter()
+                .filter(|k| !bounded.contains(k))
+                .collect::<Vec<_>>()
+        );
+
+        // including the metadata of the nested columns
+        assert_eq!(access.entry(&0).entry("a").len(), Ok(0));
+        assert_eq!(access.entry(&-300).entry("a").len(), Ok(1));
+
+        access.clear();
+        assert_eq!(access.keys().count(), 0);
+        assert_eq!(access.entry(&256).entry("b").len(), Ok(0));
+        assert_eq!(access.entry_mut(&256).entry_mut("b").push(&3), Ok(0));
+
+        assert_eq!(other.access(&storage).values().count(), 5);
+    }
+
+    #[test]
+    fn get_many() {
+        use storey_testing::backend::CountingStorage;
+
+        let mut storage = CountingStorage::new();
+        let map = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+
+        let mut access = map.access(&mut storage);
+        access.entry_mut("foo").entry_mut(&1).set(&10).unwrap();
+        access.entry_mut("foo").entry_mut(&2).set(&20).unwrap();
+        access.entry_mut("bar").entry_mut(&1).set(&30).unwrap();
+
+        let access = map.access(&storage);
+        assert_eq!(
+            access.entry("foo").get_many(&[&2, &3, &1, &2]),
+            Ok(vec![Some(20), None, Some(10), Some(20)])
+        );
+        assert_eq!(access.entry("bar").get_many(&[]), Ok(vec![]));
+        assert_eq!(access.entry("baz").get_many(&[&1]), Ok(vec![None]));
+
+        // every lookup went through a single batch
+        assert_eq!(storage.gets(), 0);
+        assert_eq!(storage.batches(), 3);
+    }
+
+    #[test]
+    fn has_without_reading() {
+        use storey_testing::backend::CountingStorage;
+
+        let mut storage = CountingStorage::new();
+        let map = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+
+        map.access(&mut storage)
+            .entry_mut("foo")
+            .entry_mut(&1)
+            .set(&10)
+            .unwrap();
+
+        let access = map.access(&storage);
+        assert!(access.entry("foo").has(&1));
+        assert!(!access.entry("foo").has(&2));
+        assert!(!access.entry("bar").has(&1));
+
+        assert_eq!(storage.gets(), 0);
+        assert_eq!(storage.checks(), 3);
+    }
+
+    #[test]
+    fn remove_range_reaches_backend() {
+        use storey_storage::{StorageBackend, StorageBackendMut};
+
+        type Bounds = (Option<Vec<u8>>, Option<Vec<u8>>);
+
+        /// Records the range removals reaching it.
+        #[derive(Default)]
+        struct Native {
+            inner: TestStorage,
+            removals: Vec<Bounds>,
+        }
+
+        impl StorageBackend for Native {
+            fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+                StorageBackend::get(&self.inner, key)
+            }
+        }
+
+        impl StorageBackendMut for Native {
+            fn set(&mut self, key: &[u8], value: &[u8]) {
+                StorageBackendMut::set(&mut self.inner, key, value)
+            }
+
+            fn remove(&mut self, key: &[u8]) {
+                StorageBackendMut::remove(&mut self.inner, key)
+            }
+
+            fn remove_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>) {
+                self.removals
+                    .push((start.map(<[u8]>::to_vec), end.map(<[u8]>::to_vec)));
+                StorageBackendMut::remove_range(&mut self.inner, start, end)
+            }
+        }
+
+        impl IterableStorage for Native {
+            type KeysIterator<'a> = <TestStorage as IterableStorage>::KeysIterator<'a>;
+            type ValuesIterator<'a> = <TestStorage as IterableStorage>::ValuesIterator<'a>;
+            type PairsIterator<'a> = <TestStorage as IterableStorage>::PairsIterator<'a>;
+
+            fn keys<'a>(&'a self, s: Option<&[u8]>, e: Option<&[u8]>) -> Self::KeysIterator<'a> {
+                self.inner.keys(s, e)
+            }
+
+            fn values<'a>(
+                &'a self,
+                s: Option<&[u8]>,
+                e: Option<&[u8]>,
+            ) -> Self::ValuesIterator<'a> {
+                self.inner.values(s, e)
+            }
+
+            fn pairs<'a>(&'a self, s: Option<&[u8]>, e: Option<&[u8]>) -> Self::PairsIterator<'a> {
+                self.inner.pairs(s, e)
+            }
+        }
+
+        let mut storage = Native::default();
+        let map = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(3);
+
+        let mut access = map.access(&mut storage);
+        access.entry_mut("foo").remove_range(Some(1), None::<u32>);
+        access.clear();
+
+        // the entry's own range ends where the next possible entry ("fop") starts
+        let foo = [&[3][..], &crate::key!("foo", 1u32)].concat();
+        let fop = [&[3][..], &crate::key!("fop")].concat();
+        assert_eq!(
+            storage.removals,
+            [
+                (Some(foo.clone()), Some(fop.clone())),
+                (
+                    Some([&[255][..], &foo].concat()),
+                    Some([&[255][..], &fop].concat())
+                ),
+                (Some(vec![3]), Some(vec![4])),
+                (Some(vec![255, 3]), Some(vec![255, 4])),
+            ]
+        );
+    }
+
+    #[test]
+    fn byte_keys() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<Vec<u8>, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut([1u8, 2].as_slice()).set(&1337).unwrap();
+
+        assert_eq!(access.entry(&vec![1, 2]).get().unwrap(), Some(1337));
+        assert_eq!(
+            storage.get(&[0, 2, 1, 2]),
             Some(1337u64.to_le_bytes().to_vec())
         );
-        assert_eq!(map.access(&storage).entry("bar").get().unwrap(), None);
     }
 
     #[test]
@@ -368,6 +2387,84 @@ mod tests {
         assert_eq!(keys, vec![("bar".to_string(), ()), ("foo".to_string(), ())])
     }
 
+    #[test]
+    fn decode_key_roundtrip() {
+        type Nested = Map<String, Map<String, Item<u64, TestEncoding>>>;
+
+        assert_eq!(
+            Nested::decode_key(&[3, 102, 111, 111, 3, 98, 97, 114]),
+            Ok(("foo".to_string(), ("bar".to_string(), ())))
+        );
+        assert_eq!(
+            Nested::decode_key(&[0, 0]),
+            Ok((String::new(), (String::new(), ())))
+        );
+    }
+
+    #[test]
+    fn decode_key_malformed() {
+        type Flat = Map<String, Item<u64, TestEncoding>>;
+        type Nested = Map<String, Map<String, Item<u64, TestEncoding>>>;
+        type OfColumn = Map<String, Column<u64, TestEncoding>>;
+
+        // no length byte
+        assert_eq!(Flat::decode_key(&[]), Err(MapKeyDecodeError::EmptyKey));
+
+        // length byte claims more than there is
+        assert_eq!(
+            Flat::decode_key(&[4, 102, 111, 111]),
+            Err(MapKeyDecodeError::KeyTooShort(4))
+        );
+
+        // length byte claims less than there is - the leftover byte must not be ignored
+        assert_eq!(
+            Flat::decode_key(&[2, 102, 111, 111]),
+            Err(MapKeyDecodeError::Inner(ItemKeyDecodeError))
+        );
+
+        // trailing garbage
+        assert_eq!(
+            Flat::decode_key(&[3, 102, 111, 111, 0]),
+            Err(MapKeyDecodeError::Inner(ItemKeyDecodeError))
+        );
+
+        // invalid key bytes
+        assert_eq!(
+            Flat::decode_key(&[1, 255]),
+            Err(MapKeyDecodeError::InvalidUtf8)
+        );
+
+        // nested: inner length byte missing
+        assert_eq!(
+            Nested::decode_key(&[3, 102, 111, 111]),
+            Err(MapKeyDecodeError::Inner(MapKeyDecodeError::EmptyKey))
+        );
+
+        // nested: truncated tail
+        assert_eq!(
+            Nested::decode_key(&[3, 102, 111, 111, 3, 98, 97]),
+            Err(MapKeyDecodeError::Inner(MapKeyDecodeError::KeyTooShort(3)))
+        );
+
+        // nested: extra bytes after the innermost key
+        assert_eq!(
+            Nested::decode_key(&[3, 102, 111, 111, 3, 98, 97, 114, 0]),
+            Err(MapKeyDecodeError::Inner(MapKeyDecodeError::Inner(
+                ItemKeyDecodeError
+            )))
+        );
+
+        // column index not exactly 4 bytes
+        assert_eq!(
+            OfColumn::decode_key(&[3, 102, 111, 111, 0, 0, 0]),
+            Err(MapKeyDecodeError::Inner(ColumnKeyDecodeError))
+        );
+        assert_eq!(
+            OfColumn::decode_key(&[3, 102, 111, 111, 0, 0, 0, 0, 0]),
+            Err(MapKeyDecodeError::Inner(ColumnKeyDecodeError))
+        );
+    }
+
     #[test]
     fn values() {
         let mut storage = TestStorage::new();
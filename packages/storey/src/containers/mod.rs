@@ -1,17 +1,177 @@
 //! This module contains both the traits for implementing collections/containers, as well as a
 //! few fundamental collections/containers themselves.
+//!
+//! # Threads
+//! Containers only hold a prefix, so they are `Send` and `Sync` whatever their key, value and
+//! encoding types are. Accessors and the iterators over them hold the storage they were
+//! created with, so they are `Send` and `Sync` when that storage is: an accessor over
+//! `&S` is `Send` if `S` is `Sync`, for example. This lets accessors be kept across `.await`
+//! points in tasks that move between threads.
 
 mod column;
+mod hashed;
 mod item;
+#[cfg(feature = "iteration")]
+mod iter;
 mod map;
 
-use std::marker::PhantomData;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+pub use column::{Column, ColumnAccess, ColumnKeyDecodeError};
+#[cfg(feature = "sha2")]
+pub use hashed::Sha256;
+pub use hashed::{Hashed, KeyHasher};
+pub use item::{Item, ItemAccess, ItemKeyDecodeError};
+#[cfg(feature = "iteration")]
+pub use iter::{
+    BoundFor, BoundedIterableAccessor, DumpKey, IterableAccessor, KVDecodeError, StorableIter,
+    StorableKeys, StorableValues,
+};
+pub use map::{
+    ByteKey, FixedSizeKey, InvalidKeyLength, InvalidUtf8, Key, KeyTooLong, Map, MapAccess,
+    MapKeyDecodeError, OrderPreservingKey, OwnedKey, SizeKeyDecodeError, StructKeyDecodeError,
+    TupleKeyDecodeError, MAX_KEY_LEN,
+};
+
+#[doc(hidden)]
+pub use map::LengthPrefixed;
+
+/// Derives [`Key`] for a struct, encoding it as the concatenation of its fields, in order.
+///
+/// This goes with [`OwnedKey`](derive@OwnedKey), which has the details.
+#[cfg(feature = "derive")]
+pub use storey_derive::Key;
 
-pub use column::{Column, ColumnAccess};
-pub use item::{Item, ItemAccess};
-pub use map::{Map, MapAccess};
+/// Derives [`OwnedKey`] for a struct of fixed-width fields, like a tuple key with names.
+///
+/// The key is encoded as the concatenation of the fields, in declaration order. All of them
+/// have to be [`FixedSizeKey`]s, so that decoding knows where each one ends. The struct then
+/// gets a [`FixedSizeKey`] impl too, and keys of any other length are rejected.
+///
+/// Like the last component of a tuple key, the last field can be of variable width, if marked
+/// with `#[storey(variable)]`. It then takes up whatever follows the other fields.
+///
+/// Decoding errors are [`StructKeyDecodeError`]s, naming the field that failed.
+///
+/// # Ordering
+/// With [`OrderPreservingKey`](derive@OrderPreservingKey) derived as well, raw keys sort by
+/// the first field, then by the second, and so on: the order `#[derive(PartialOrd, Ord)]`
+/// gives the struct. That takes every field to be an [`OrderPreservingKey`], and none of
+/// them to be variable-width.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::{BoundedIterableAccessor as _, Item, Key, Map, OrderPreservingKey, OwnedKey};
+///
+/// #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Key, OwnedKey, OrderPreservingKey)]
+/// struct PoolKey {
+///     denom_a_id: u32,
+///     denom_b_id: u32,
+/// }
+///
+/// #[derive(Debug, PartialEq, Key, OwnedKey)]
+/// struct Name {
+///     owner_id: u64,
+///     #[storey(variable)]
+///     name: String,
+/// }
+///
+/// let pool = |denom_a_id, denom_b_id| PoolKey { denom_a_id, denom_b_id };
+/// assert_eq!(storey::key!(pool(1, 2)), [8, 0, 0, 0, 1, 0, 0, 0, 2]);
+///
+/// let mut storage = TestStorage::new();
+/// let pools = Map::<PoolKey, Item<u64, TestEncoding>>::new(0);
+/// for (a, b) in [(1, 2), (1, 3), (2, 1)] {
+///     pools.access(&mut storage).entry_mut(&pool(a, b)).set(&0).unwrap();
+/// }
+/// let from_1_3 = pools
+///     .access(&storage)
+///     .bounded_keys(Some(pool(1, 3)), None::<PoolKey>)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(from_1_3, [(pool(1, 3), ()), (pool(2, 1), ())]);
+///
+/// let name = Name { owner_id: 7, name: "main".to_string() };
+/// let bytes = storey::key!(name)[1..].to_vec();
+/// assert_eq!(Name::from_bytes(&bytes), Ok(name));
+/// ```
+#[cfg(feature = "derive")]
+pub use storey_derive::OwnedKey;
+
+/// Derives [`OrderPreservingKey`] for a struct deriving [`OwnedKey`](derive@OwnedKey), whose
+/// fields are all fixed-width [`OrderPreservingKey`]s. Raw keys then sort like the fields, in
+/// declaration order.
+#[cfg(feature = "derive")]
+pub use storey_derive::OrderPreservingKey;
+
+/// Fail if two of `prefixes` overlap: if they're the same, or one starts with the other.
+///
+/// Top-level containers with overlapping prefixes silently share keys, so their data gets
+/// mixed up. With multi-byte prefixes that's not only the same prefix twice: a container
+/// under `b"ab"` has its keys among those of a container under `b"a"`. Called in a `const`
+/// item, this turns that mistake into a compile error:
+///
+/// ```compile_fail
+/// use storey::containers::{assert_unique_prefixes, Item, Map};
+/// # use storey_testing::encoding::TestEncoding;
+///
+/// const CONFIG: Item<u64, TestEncoding> = Item::new(0);
+/// const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new(0);
+///
+/// const _: () = assert_unique_prefixes(&[CONFIG.prefix_bytes(), BALANCES.prefix_bytes()]);
+/// ```
+///
+/// ```compile_fail
+/// use storey::containers::{assert_unique_prefixes, Item, Map};
+/// # use storey_testing::encoding::TestEncoding;
+///
+/// const CONFIG: Item<u64, TestEncoding> = Item::new(b'b');
+/// const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new_str("balances");
+///
+/// const _: () = assert_unique_prefixes(&[CONFIG.prefix_bytes(), BALANCES.prefix_bytes()]);
+/// ```
+///
+/// The [`declare_containers!`](crate::declare_containers) macro declares the containers and
+/// does the check in one go.
+///
+/// # Panics
+/// If two prefixes overlap.
+pub const fn assert_unique_prefixes(prefixes: &[&[u8]]) {
+    let mut i = 0;
+    while i < prefixes.len() {
+        let mut j = i + 1;
+        while j < prefixes.len() {
+            let (a, b) = (prefixes[i], prefixes[j]);
+            if a.len() == b.len() && starts_with(a, b) {
+                panic!("two containers have the same prefix");
+            }
+            if starts_with(a, b) || starts_with(b, a) {
+                panic!("the prefix of a container starts with the prefix of another one");
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+}
 
-use crate::storage::IterableStorage;
+/// Whether `bytes` starts with `prefix`, in a `const fn`.
+const fn starts_with(bytes: &[u8], prefix: &[u8]) -> bool {
+    if prefix.len() > bytes.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < prefix.len() {
+        if bytes[i] != prefix[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
 
 /// The fundamental trait every collection/container should implement.
 pub trait Storable {
@@ -32,14 +192,14 @@ pub trait Storable {
     type Key;
 
     /// The error type for decoding keys.
-    type KeyDecodeError;
+    type KeyDecodeError: crate::StdError + Send + Sync + 'static;
 
     /// The Value type for this collection/container. This is the type that will be used for
     /// value iteration.
     type Value;
 
     /// The error type for decoding values.
-    type ValueDecodeError;
+    type ValueDecodeError: crate::StdError + Send + Sync + 'static;
 
     /// Create an accessor for this collection/container, given a [`Storage`] implementation.
     ///
@@ -50,198 +210,103 @@ pub trait Storable {
     ///
     /// This method is used in key iteration to provide a typed key rather than raw bytes
     /// to the user.
-    fn decode_key(key: &[u8]) -> Result<Self::Key, Self::KeyDecodeError>;
-
-    /// Decode a value from a byte slice.
     ///
-    /// This method is used in value iteration to provide a typed value rather than raw bytes
-    /// to the user.
-    fn decode_value(value: &[u8]) -> Result<Self::Value, Self::ValueDecodeError>;
-}
-
-/// A key-value pair decoding error.
-#[derive(Debug, PartialEq)]
-pub enum KVDecodeError<K, V> {
-    Key(K),
-    Value(V),
-}
-
-/// A trait for collection accessors (see [`Storable::AccessorT`]) that provide iteration over
-/// their contents.
-pub trait IterableAccessor: Sized {
-    /// The [`Storable`] type this accessor is associated with.
-    type Storable: Storable;
+    /// The slice passed in is exactly the part of the raw key that belongs to this
+    /// collection/container (i.e. with any parent prefixes already stripped). Implementations
+    /// must consume all of it: trailing bytes that weren't accounted for, as well as input
+    /// that's too short, must result in an error rather than being silently ignored. In other
+    /// words, decoding has to be the exact inverse of how the container composes keys, since
+    /// iteration relies on that.
+    fn decode_key(key: &[u8]) -> Result<Self::Key, Self::KeyDecodeError>;
 
-    /// The [`Storage`] type this accessor is associated with.
+    /// Where in `key` decoding failed with `error`, as returned by
+    /// [`decode_key`](Self::decode_key).
     ///
-    /// [`Storage`]: crate::storage::Storage
-    type Storage: IterableStorage;
-
-    /// Get a reference to the storage this accessor is associated with.
-    fn storage(&self) -> &Self::Storage;
-
-    /// Iterate over key-value pairs in this collection.
-    fn pairs(&self) -> StorableIter<'_, Self::Storable, Self::Storage> {
-        StorableIter {
-            inner: self.storage().pairs(None, None),
-            phantom: PhantomData,
-        }
-    }
-
-    /// Iterate over keys in this collection.
-    fn keys(&self) -> StorableKeys<'_, Self::Storable, Self::Storage> {
-        StorableKeys {
-            inner: self.storage().keys(None, None),
-            phantom: PhantomData,
-        }
-    }
-
-    /// Iterate over values in this collection.
-    fn values(&self) -> StorableValues<'_, Self::Storable, Self::Storage> {
-        StorableValues {
-            inner: self.storage().values(None, None),
-            phantom: PhantomData,
-        }
-    }
-}
-
-pub trait BoundedIterableAccessor: IterableAccessor {
-    /// Iterate over key-value pairs in this collection, respecting the given bounds.
-    fn bounded_pairs<S, E>(
-        &self,
-        start: Option<S>,
-        end: Option<E>,
-    ) -> StorableIter<'_, Self::Storable, Self::Storage>
-    where
-        S: BoundFor<Self::Storable>,
-        E: BoundFor<Self::Storable>,
-    {
-        let start = start.map(|b| b.into_bytes());
-        let end = end.map(|b| b.into_bytes());
-
-        StorableIter {
-            inner: self.storage().pairs(start.as_deref(), end.as_deref()),
-            phantom: PhantomData,
-        }
-    }
-
-    /// Iterate over keys in this collection, respecting the given bounds.
-    fn bounded_keys<S, E>(
-        &self,
-        start: Option<S>,
-        end: Option<E>,
-    ) -> StorableKeys<'_, Self::Storable, Self::Storage>
-    where
-        S: BoundFor<Self::Storable>,
-        E: BoundFor<Self::Storable>,
-    {
-        let start = start.map(|b| b.into_bytes());
-        let end = end.map(|b| b.into_bytes());
-
-        StorableKeys {
-            inner: self.storage().keys(start.as_deref(), end.as_deref()),
-            phantom: PhantomData,
-        }
+    /// This is the byte offset in `key` of the component that failed to decode, and the index
+    /// of that component: `0` for this container's own part of the key, `1` for the part
+    /// belonging to a container nested in it, and so on. Containers nesting others should
+    /// delegate to the nested container for its part of the key. The default, for containers
+    /// that don't nest others, is the start of the key.
+    fn locate_key_error(key: &[u8], error: &Self::KeyDecodeError) -> (usize, usize) {
+        let _ = (key, error);
+        (0, 0)
     }
 
-    /// Iterate over values in this collection, respecting the given bounds.
-    fn bounded_values<S, E>(
-        &self,
-        start: Option<S>,
-        end: Option<E>,
-    ) -> StorableValues<'_, Self::Storable, Self::Storage>
-    where
-        S: BoundFor<Self::Storable>,
-        E: BoundFor<Self::Storable>,
-    {
-        let start = start.map(|b| b.into_bytes());
-        let end = end.map(|b| b.into_bytes());
-
-        StorableValues {
-            inner: self.storage().values(start.as_deref(), end.as_deref()),
-            phantom: PhantomData,
-        }
-    }
-}
-
-/// A type that can be used as bounds for iteration over a given collection.
-///
-/// As an example, a collection `Foo` with string-y keys can accept both `String` and
-/// `&str` bounds by providing these impls:
-/// - `impl BoundFor<Foo> for &str`
-/// - `impl BoundFor<Foo> for String`
-pub trait BoundFor<T> {
-    fn into_bytes(self) -> Vec<u8>;
-}
-
-/// The iterator over key-value pairs in a collection.
-pub struct StorableIter<'i, S, B>
-where
-    S: Storable,
-    B: IterableStorage + 'i,
-{
-    inner: B::PairsIterator<'i>,
-    phantom: PhantomData<S>,
-}
-
-impl<'i, S, B> Iterator for StorableIter<'i, S, B>
-where
-    S: Storable,
-    B: IterableStorage + 'i,
-{
-    type Item = Result<(S::Key, S::Value), KVDecodeError<S::KeyDecodeError, S::ValueDecodeError>>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|(k, v)| -> Self::Item {
-            match (S::decode_key(&k), S::decode_value(&v)) {
-                (Err(e), _) => Err(KVDecodeError::Key(e)),
-                (_, Err(e)) => Err(KVDecodeError::Value(e)),
-                (Ok(k), Ok(v)) => Ok((k, v)),
+    /// Decode a key like [`decode_key`](Self::decode_key) does, reporting failures with the
+    /// raw key and where in it decoding failed. This is what iteration uses.
+    fn decode_key_with_context(
+        key: &[u8],
+    ) -> Result<Self::Key, KeyDecodeError<Self::KeyDecodeError>> {
+        Self::decode_key(key).map_err(|source| {
+            let (offset, component) = Self::locate_key_error(key, &source);
+            KeyDecodeError {
+                key: key.to_vec(),
+                offset,
+                component,
+                source,
             }
         })
     }
-}
-
-/// The iterator over keys in a collection.
-pub struct StorableKeys<'i, S, B>
-where
-    S: Storable,
-    B: IterableStorage + 'i,
-{
-    inner: B::KeysIterator<'i>,
-    phantom: PhantomData<S>,
-}
-
-impl<'i, S, B> Iterator for StorableKeys<'i, S, B>
-where
-    S: Storable,
-    B: IterableStorage + 'i,
-{
-    type Item = Result<S::Key, S::KeyDecodeError>;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|k| S::decode_key(&k))
-    }
+    /// Decode a value from a byte slice.
+    ///
+    /// This method is used in value iteration to provide a typed value rather than raw bytes
+    /// to the user.
+    fn decode_value(value: &[u8]) -> Result<Self::Value, Self::ValueDecodeError>;
 }
 
-/// The iterator over values in a collection.
-pub struct StorableValues<'i, S, B>
-where
-    S: Storable,
-    B: IterableStorage + 'i,
-{
-    inner: B::ValuesIterator<'i>,
-    phantom: PhantomData<S>,
+/// An error decoding a raw key in iteration, with the key and where in it decoding failed.
+///
+/// Keys of nested containers are made of components, one per container: the key of a
+/// `Map<String, Map<u32, Item<_, _>>>` is the length-prefixed string, followed by the
+/// length-prefixed number, followed by nothing for the item. The error points at the
+/// component that failed to decode, as located by [`Storable::locate_key_error`].
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::{IterableAccessor as _, Item, Map, MapKeyDecodeError};
+/// use storey::storage::StorageMut as _;
+///
+/// let mut storage = TestStorage::new();
+/// let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
+///
+/// // "foo", then a nested key that's not UTF-8
+/// storage.set(&[0, 3, b'f', b'o', b'o', 1, 0xff], &[]);
+///
+/// let err = map.access(&storage).keys().next().unwrap().unwrap_err();
+/// assert_eq!(err.key, [3, b'f', b'o', b'o', 1, 0xff]);
+/// assert_eq!(err.offset, 4);
+/// assert_eq!(err.component, 1);
+/// assert_eq!(err.source, MapKeyDecodeError::Inner(MapKeyDecodeError::InvalidUtf8));
+/// assert_eq!(
+///     err.to_string(),
+///     "invalid key 03666f6f01ff at byte 4 (component 1): sub key decode error: invalid UTF8"
+/// );
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
+#[error(
+    "invalid key {} at byte {offset} (component {component}): {source}",
+    crate::hex::encode(key)
+)]
+pub struct KeyDecodeError<E> {
+    /// The raw key, without the prefix of the container being iterated over.
+    pub key: Vec<u8>,
+
+    /// The byte offset in `key` of the component that failed to decode.
+    pub offset: usize,
+
+    /// The index of the component that failed to decode, `0` being the key of the container
+    /// being iterated over.
+    pub component: usize,
+
+    /// The error of the container the component belongs to.
+    pub source: E,
 }
 
-impl<'i, S, B> Iterator for StorableValues<'i, S, B>
-where
-    S: Storable,
-    B: IterableStorage + 'i,
-{
-    type Item = Result<S::Value, S::ValueDecodeError>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|v| S::decode_value(&v))
-    }
-}
+/// The marker of a type with a key type `K`, which can be unsized, and another type `V`.
+///
+/// Unlike `PhantomData<(*const K, V)>`, this is `Send` and `Sync` whatever `K` and `V` are, and
+/// doesn't imply owning a `K` or a `V`. It is covariant in both.
+pub(crate) type KeyPhantom<K, V> = PhantomData<(fn() -> *const K, fn() -> V)>;
@@ -0,0 +1,314 @@
+//! Exporting the contents of a backend, and importing them into another one.
+//!
+//! [`export`] copies every key-value pair under a set of prefixes into a [`Dump`], and
+//! [`import`] writes a dump into a backend. The backends don't need to be of the same type,
+//! so this can be used to seed test fixtures from real chain state, or to move data from one
+//! backend implementation to another.
+//!
+//! Entries are raw key-value pairs, so a dump taken with the empty prefix includes container
+//! metadata (like the length of a [`Column`](crate::containers::Column)) too.
+//!
+//! For states too large to hold in memory at once, [`export_iter`] and [`import_stream`] work
+//! on one entry at a time. With the `serde` and `json` features enabled, [`write_json_lines`]
+//! and [`read_json_lines`] write and read the entries as JSON Lines, one entry per line.
+//!
+//! # Format
+//!
+//! With the `serde` feature enabled, a [`Dump`] can be serialized. Keys and values are lowercase
+//! hex strings, so in JSON a dump looks like this:
+//!
+//! ```json
+//! {"entries":[{"key":"00616c696365","value":"2a"}]}
+//! ```
+//!
+//! # Existing keys
+//!
+//! What happens to keys that are already in the target backend is up to the [`OnExisting`]
+//! policy passed in: either the import fails, or the imported values overwrite the existing
+//! ones. Keys in the backend that aren't in the dump are never touched.
+//!
+//! # Example
+//! ```
+//! # use std::collections::BTreeMap;
+//! # use storey_testing::backend::TestStorage;
+//! # use storey_testing::encoding::TestEncoding;
+//! use storey::containers::{Item, Map};
+//! use storey::dump::{export, import, OnExisting};
+//!
+//! const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new(0);
+//!
+//! let mut source = TestStorage::new();
+//! BALANCES.access(&mut source).entry_mut("alice").set(&100).unwrap();
+//!
+//! let dump = export(&source, &[&[0]]);
+//!
+//! let mut target = BTreeMap::new();
+//! import(&mut target, &dump, OnExisting::Error).unwrap();
+//!
+//! assert_eq!(BALANCES.access(&target).entry("alice").get(), Ok(Some(100)));
+//!
+//! // the keys are there now, so importing again fails unless told to overwrite them
+//! assert!(import(&mut target, &dump, OnExisting::Error).is_err());
+//! ```
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+pub use crate::snapshot::Entry;
+use crate::storage::{prefix_end, IterableStorage, Storage, StorageMut};
+
+/// Copy every key-value pair under `prefixes` out of `storage`.
+///
+/// The prefixes are raw key prefixes. They may overlap, and the empty prefix exports the whole
+/// storage. The entries are sorted by key.
+pub fn export<S: IterableStorage>(storage: &S, prefixes: &[&[u8]]) -> Dump {
+    Dump {
+        entries: export_iter(storage, prefixes).collect(),
+    }
+}
+
+/// Iterate over every key-value pair under `prefixes` in `storage`, without collecting them.
+///
+/// This yields the same entries, in the same order, as [`export`].
+pub fn export_iter<'a, S: IterableStorage>(
+    storage: &'a S,
+    prefixes: &[&[u8]],
+) -> impl Iterator<Item = Entry> + 'a {
+    // Once sorted, a prefix comes right after any other prefix it starts with, so skipping
+    // those leaves prefixes covering disjoint, ordered ranges.
+    let mut sorted = prefixes.to_vec();
+    sorted.sort();
+    let mut prefixes: Vec<Vec<u8>> = Vec::with_capacity(sorted.len());
+    for prefix in sorted {
+        if !matches!(prefixes.last(), Some(last) if prefix.starts_with(last)) {
+            prefixes.push(prefix.to_vec());
+        }
+    }
+
+    prefixes.into_iter().flat_map(move |prefix| {
+        let end = prefix_end(&prefix);
+        storage
+            .pairs(Some(&prefix), end.as_deref())
+            .map(|(key, value)| Entry { key, value })
+    })
+}
+
+/// Write the entries of `dump` into `storage`.
+///
+/// With [`OnExisting::Error`], every key is checked before anything is written, so if the
+/// import fails, `storage` is left as it was.
+pub fn import<S>(storage: &mut S, dump: &Dump, policy: OnExisting) -> Result<(), ImportError>
+where
+    S: Storage + StorageMut,
+{
+    if policy == OnExisting::Error {
+        if let Some(entry) = dump.entries.iter().find(|entry| storage.has(&entry.key)) {
+            return Err(ImportError::Exists {
+                key: entry.key.clone(),
+            });
+        }
+    }
+
+    for entry in &dump.entries {
+        storage.set(&entry.key, &entry.value);
+    }
+
+    Ok(())
+}
+
+/// Write entries into `storage` as they come, returning the number of entries written.
+///
+/// This is the streaming counterpart of [`import`]. The entries are results, so that errors
+/// reading them (e.g. from a file, with [`read_json_lines`]) stop the import. Entries that
+/// are infallible can be passed in with `.map(Ok::<_, Infallible>)`.
+///
+/// Entries are written one by one, so when the import fails (because of an error from
+/// `entries`, or an existing key with [`OnExisting::Error`]), the entries before the one that
+/// failed are already written. To import all or nothing, import into a
+/// [`Transaction`](crate::storage::Transaction) and only commit it if the import succeeds.
+pub fn import_stream<S, I, E>(
+    storage: &mut S,
+    entries: I,
+    policy: OnExisting,
+) -> Result<usize, ImportError<E>>
+where
+    S: Storage + StorageMut,
+    I: IntoIterator<Item = Result<Entry, E>>,
+{
+    let mut written = 0;
+
+    for entry in entries {
+        let entry = entry.map_err(ImportError::Source)?;
+
+        if policy == OnExisting::Error && storage.has(&entry.key) {
+            return Err(ImportError::Exists { key: entry.key });
+        }
+
+        storage.set(&entry.key, &entry.value);
+        written += 1;
+    }
+
+    Ok(written)
+}
+
+/// Write `entries` to `writer` as JSON Lines, returning the number of entries written.
+///
+/// Each line is an [`Entry`] serialized as JSON, like `{"key":"0001","value":"2a"}`.
+#[cfg(all(feature = "serde", feature = "json"))]
+pub fn write_json_lines<W, I>(mut writer: W, entries: I) -> std::io::Result<usize>
+where
+    W: std::io::Write,
+    I: IntoIterator<Item = Entry>,
+{
+    let mut written = 0;
+
+    for entry in entries {
+        serde_json::to_writer(&mut writer, &entry)?;
+        writer.write_all(b"\n")?;
+        written += 1;
+    }
+
+    writer.flush()?;
+    Ok(written)
+}
+
+/// Read entries written by [`write_json_lines`] from `reader`, one line at a time.
+///
+/// Blank lines are skipped. Lines that aren't valid entries are reported as
+/// [`InvalidData`](std::io::ErrorKind::InvalidData) errors.
+#[cfg(all(feature = "serde", feature = "json"))]
+pub fn read_json_lines<R: std::io::BufRead>(
+    reader: R,
+) -> impl Iterator<Item = std::io::Result<Entry>> {
+    reader.lines().filter_map(|line| match line {
+        Ok(line) if line.trim().is_empty() => None,
+        Ok(line) => Some(serde_json::from_str(&line).map_err(std::io::Error::from)),
+        Err(err) => Some(Err(err)),
+    })
+}
+
+/// Raw key-value pairs exported from a backend. See [`export`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dump {
+    /// The entries, sorted by key if the dump comes from [`export`].
+    pub entries: Vec<Entry>,
+}
+
+/// What [`import`] does with keys that already exist in the target backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnExisting {
+    /// Fail with [`ImportError::Exists`].
+    Error,
+
+    /// Replace the existing value with the imported one.
+    Overwrite,
+}
+
+/// An error importing entries with [`import`] or [`import_stream`].
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
+pub enum ImportError<E = Infallible> {
+    #[error("raw key {key:?} already exists")]
+    Exists { key: Vec<u8> },
+
+    #[error("failed to read an entry: {0}")]
+    Source(E),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::collections::BTreeMap;
+
+    use storey_testing::backend::TestStorage;
+
+    fn entry(key: &[u8], value: &[u8]) -> Entry {
+        Entry {
+            key: key.to_vec(),
+            value: value.to_vec(),
+        }
+    }
+
+    fn source() -> TestStorage {
+        let mut storage = TestStorage::new();
+        storage.set(&[0, 1], &[1]);
+        storage.set(&[1, 1], &[2]);
+        storage.set(&[1, 2], &[3]);
+        storage.set(&[2, 1], &[4]);
+        storage
+    }
+
+    #[test]
+    fn export_under_prefixes() {
+        let storage = source();
+
+        assert_eq!(
+            export(&storage, &[&[2], &[1, 2], &[0], &[1]]).entries,
+            [
+                entry(&[0, 1], &[1]),
+                entry(&[1, 1], &[2]),
+                entry(&[1, 2], &[3]),
+                entry(&[2, 1], &[4]),
+            ]
+        );
+        assert_eq!(
+            export(&storage, &[&[1, 2], &[2]]).entries,
+            [entry(&[1, 2], &[3]), entry(&[2, 1], &[4])]
+        );
+        assert_eq!(export(&storage, &[&[]]).entries.len(), 4);
+        assert!(export(&storage, &[]).entries.is_empty());
+
+        // the streaming variant yields the same entries
+        assert_eq!(
+            export_iter(&storage, &[&[1], &[0]]).collect::<Vec<_>>(),
+            export(&storage, &[&[1], &[0]]).entries
+        );
+    }
+
+    #[test]
+    fn import_with_existing_keys() {
+        let dump = export(&source(), &[&[1]]);
+
+        let mut target = BTreeMap::new();
+        target.insert(vec![1, 2], vec![30]);
+        target.insert(vec![3], vec![5]);
+
+        assert_eq!(
+            import(&mut target, &dump, OnExisting::Error),
+            Err(ImportError::Exists { key: vec![1, 2] })
+        );
+        // nothing was written
+        assert_eq!(target.len(), 2);
+
+        import(&mut target, &dump, OnExisting::Overwrite).unwrap();
+        assert_eq!(
+            target,
+            BTreeMap::from([
+                (vec![1, 1], vec![2]),
+                (vec![1, 2], vec![3]),
+                (vec![3], vec![5]),
+            ])
+        );
+    }
+
+    #[test]
+    fn import_stream_stops_at_the_first_error() {
+        let entries = vec![Ok(entry(&[1], &[1])), Err("broken"), Ok(entry(&[2], &[2]))];
+
+        let mut target = BTreeMap::new();
+        assert_eq!(
+            import_stream(&mut target, entries, OnExisting::Error),
+            Err(ImportError::Source("broken"))
+        );
+        assert_eq!(target, BTreeMap::from([(vec![1], vec![1])]));
+
+        let source = source();
+        let entries = export_iter(&source, &[&[]]).map(Ok::<_, Infallible>);
+        assert_eq!(
+            import_stream(&mut target, entries, OnExisting::Overwrite),
+            Ok(4)
+        );
+        assert_eq!(target.len(), 5);
+    }
+}
@@ -0,0 +1,219 @@
+use bincode::config::{Configuration, Fixint, Limit, LittleEndian, NoLimit};
+use bincode::error::{DecodeError, EncodeError};
+
+use super::{
+    BorrowDecodableWithImpl, Borrowable, Cover, DecodableWithImpl, EncodableWithImpl, Encoding,
+};
+
+/// The [bincode] configuration used by [`BincodeEncoding`].
+///
+/// This is pinned explicitly rather than relying on bincode's defaults, so that the bytes in
+/// storage don't change if those defaults ever do:
+/// - integers are little-endian,
+/// - integers are fixed-width (no varint encoding),
+/// - there's no size limit.
+///
+/// Additionally, [`BincodeEncoding`] rejects trailing bytes on decode, and stops decoding a
+/// value claiming more than 64 MiB. Without that limit, a corrupted length prefix could make
+/// bincode try to allocate any amount of memory, and panic. The limit doesn't change the
+/// encoded bytes.
+///
+/// [bincode]: https://docs.rs/bincode
+pub const BINCODE_CONFIG: Configuration<LittleEndian, Fixint, NoLimit> =
+    bincode::config::standard()
+        .with_little_endian()
+        .with_fixed_int_encoding()
+        .with_no_limit();
+
+/// The configuration values are decoded with. See [`BINCODE_CONFIG`].
+const DECODE_CONFIG: Configuration<LittleEndian, Fixint, Limit<{ 64 << 20 }>> =
+    BINCODE_CONFIG.with_limit();
+
+/// The [bincode] encoding, for any type implementing [`serde`]'s traits.
+///
+/// Bincode is fast and compact, but not self-describing: the stored bytes can only be read
+/// back into the exact same type. It's a good fit for off-chain services that value speed and
+/// size. The configuration is pinned by [`BINCODE_CONFIG`].
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// use storey::containers::Item;
+/// use storey::encoding::BincodeEncoding;
+///
+/// #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+/// struct Checkpoint {
+///     height: u64,
+///     hash: [u8; 4],
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// let item = Item::<Checkpoint, BincodeEncoding>::new(0);
+///
+/// let checkpoint = Checkpoint { height: 7, hash: [1, 2, 3, 4] };
+/// item.access(&mut storage).set(&checkpoint).unwrap();
+/// assert_eq!(item.access(&storage).get().unwrap(), Some(checkpoint));
+/// ```
+///
+/// [bincode]: https://docs.rs/bincode
+pub struct BincodeEncoding;
+
+impl Encoding for BincodeEncoding {
+    type DecodeError = BincodeDecodeError;
+    type EncodeError = EncodeError;
+
+    const NAME: &'static str = "bincode";
+}
+
+/// An error decoding a value with [`BincodeEncoding`].
+#[derive(Debug, thiserror::Error)]
+pub enum BincodeDecodeError {
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+
+    #[error("{0} unexpected trailing bytes")]
+    TrailingBytes(usize),
+}
+
+impl<T> EncodableWithImpl<BincodeEncoding> for Cover<&T>
+where
+    T: serde::Serialize,
+{
+    fn encode_impl(self) -> Result<Vec<u8>, EncodeError> {
+        bincode::serde::encode_to_vec(self.0, BINCODE_CONFIG)
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), EncodeError> {
+        bincode::serde::encode_into_std_write(self.0, buf, BINCODE_CONFIG).map(|_| ())
+    }
+}
+
+impl<T> DecodableWithImpl<BincodeEncoding> for Cover<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn decode_impl(data: &[u8]) -> Result<Self, BincodeDecodeError> {
+        let (value, read) = bincode::serde::decode_from_slice(data, DECODE_CONFIG)?;
+
+        if read != data.len() {
+            return Err(BincodeDecodeError::TrailingBytes(data.len() - read));
+        }
+
+        Ok(Cover(value))
+    }
+}
+
+impl<T> BorrowDecodableWithImpl<BincodeEncoding> for Cover<T>
+where
+    T: Borrowable,
+    for<'a> T::Borrowed<'a>: serde::Deserialize<'a>,
+{
+    type Borrowed<'a> = T::Borrowed<'a>;
+
+    fn decode_borrowed_impl(data: &[u8]) -> Result<T::Borrowed<'_>, BincodeDecodeError> {
+        let (value, read) = bincode::serde::borrow_decode_from_slice(data, DECODE_CONFIG)?;
+
+        if read != data.len() {
+            return Err(BincodeDecodeError::TrailingBytes(data.len() - read));
+        }
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::containers::Item;
+    use crate::encoding::{DecodableWith, EncodableWith};
+
+    use storey_storage::Storage as _;
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Checkpoint {
+        height: u64,
+        label: String,
+        parent: Option<u16>,
+    }
+
+    fn encode<T: EncodableWith<BincodeEncoding>>(value: &T) -> Vec<u8> {
+        value.encode().unwrap()
+    }
+
+    #[test]
+    fn fixtures() {
+        // these bytes must never change
+        assert_eq!(encode(&1u32), [1, 0, 0, 0]);
+        assert_eq!(encode(&-2i16), [0xfe, 0xff]);
+        assert_eq!(encode(&true), [1]);
+        assert_eq!(encode(&"ab"), [2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b']);
+        assert_eq!(
+            encode(&Checkpoint {
+                height: 258,
+                label: "x".to_string(),
+                parent: Some(3),
+            }),
+            [
+                2, 1, 0, 0, 0, 0, 0, 0, // height
+                1, 0, 0, 0, 0, 0, 0, 0, b'x', // label
+                1, 3, 0, // parent
+            ]
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let checkpoint = Checkpoint {
+            height: u64::MAX,
+            label: "genesis".to_string(),
+            parent: None,
+        };
+
+        let bytes = encode(&checkpoint);
+        assert_eq!(
+            <Checkpoint as DecodableWith<BincodeEncoding>>::decode(&bytes).unwrap(),
+            checkpoint
+        );
+    }
+
+    #[test]
+    fn strict_decoding() {
+        assert!(matches!(
+            <u32 as DecodableWith<BincodeEncoding>>::decode(&[1, 0, 0, 0, 0]),
+            Err(BincodeDecodeError::TrailingBytes(1))
+        ));
+        assert!(matches!(
+            <u32 as DecodableWith<BincodeEncoding>>::decode(&[1, 0, 0]),
+            Err(BincodeDecodeError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn huge_length_prefix() {
+        // found by the `decode_value` fuzz target: a string claiming ~2^64 bytes
+        let data = [0x8a, 0xa8, 0xc1, 0x41, 0xff, 0xff, 0xff, 0xff];
+        assert!(matches!(
+            <String as DecodableWith<BincodeEncoding>>::decode(&data),
+            Err(BincodeDecodeError::Decode(DecodeError::LimitExceeded))
+        ));
+    }
+
+    #[test]
+    fn coexists_with_other_encodings() {
+        let mut storage = TestStorage::new();
+
+        let bincode_item = Item::<u64, BincodeEncoding>::new(0);
+        let test_item = Item::<u64, TestEncoding>::new(1);
+
+        bincode_item.access(&mut storage).set(&1337).unwrap();
+        test_item.access(&mut storage).set(&42).unwrap();
+
+        assert_eq!(bincode_item.access(&storage).get().unwrap(), Some(1337));
+        assert_eq!(test_item.access(&storage).get().unwrap(), Some(42));
+        assert_eq!(storage.get(&[0]), Some(1337u64.to_le_bytes().to_vec()));
+    }
+}
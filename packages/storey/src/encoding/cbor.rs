@@ -0,0 +1,299 @@
+use ciborium::Value;
+
+use super::{Cover, DecodableWithImpl, EncodableWithImpl, Encoding};
+
+type EncodeError = ciborium::ser::Error<std::io::Error>;
+type DecodeError = ciborium::de::Error<std::io::Error>;
+
+/// The [CBOR] encoding, for any type implementing [`serde`]'s traits.
+///
+/// The output is whatever the serializer produces. In particular, maps are written in their
+/// iteration order, so a `HashMap` may encode differently every time. If stored bytes must
+/// not depend on that (e.g. because they're part of consensus state), use
+/// [`CanonicalCborEncoding`].
+///
+/// Decoding rejects trailing bytes after the value.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// use storey::containers::Item;
+/// use storey::encoding::CborEncoding;
+///
+/// #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+/// struct Channel {
+///     port: String,
+///     sequence: u64,
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// let item = Item::<Channel, CborEncoding>::new(0);
+///
+/// let channel = Channel { port: "transfer".to_string(), sequence: 7 };
+/// item.access(&mut storage).set(&channel).unwrap();
+/// assert_eq!(item.access(&storage).get().unwrap(), Some(channel));
+/// ```
+///
+/// [CBOR]: https://cbor.io/
+pub struct CborEncoding;
+
+impl Encoding for CborEncoding {
+    type DecodeError = DecodeError;
+    type EncodeError = EncodeError;
+
+    const NAME: &'static str = "cbor";
+}
+
+impl<T> EncodableWithImpl<CborEncoding> for Cover<&T>
+where
+    T: serde::Serialize,
+{
+    fn encode_impl(self) -> Result<Vec<u8>, EncodeError> {
+        let mut buf = Vec::new();
+        EncodableWithImpl::<CborEncoding>::encode_to_impl(self, &mut buf)?;
+        Ok(buf)
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), EncodeError> {
+        ciborium::into_writer(self.0, buf)
+    }
+}
+
+impl<T> DecodableWithImpl<CborEncoding> for Cover<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn decode_impl(data: &[u8]) -> Result<Self, DecodeError> {
+        decode_strict(data).map(Cover)
+    }
+}
+
+/// The [CBOR] encoding with [deterministic encoding] as defined by RFC 8949.
+///
+/// Equal values always produce identical bytes:
+/// - integers and lengths use the shortest possible form,
+/// - floats use the shortest form that preserves their value,
+/// - only definite-length items are produced,
+/// - map entries are sorted by the bytewise lexicographic order of their encoded keys.
+///
+/// This costs an intermediate representation of the value on every write, so prefer
+/// [`CborEncoding`] if you don't need the guarantee. The two share the same data model, so
+/// either can decode what the other wrote.
+///
+/// [CBOR]: https://cbor.io/
+/// [deterministic encoding]: https://www.rfc-editor.org/rfc/rfc8949.html#name-core-deterministic-encoding
+pub struct CanonicalCborEncoding;
+
+impl Encoding for CanonicalCborEncoding {
+    type DecodeError = DecodeError;
+    type EncodeError = EncodeError;
+
+    const NAME: &'static str = "canonical-cbor";
+}
+
+impl<T> EncodableWithImpl<CanonicalCborEncoding> for Cover<&T>
+where
+    T: serde::Serialize,
+{
+    fn encode_impl(self) -> Result<Vec<u8>, EncodeError> {
+        let value =
+            Value::serialized(self.0).map_err(|e| ciborium::ser::Error::Value(e.to_string()))?;
+
+        let mut buf = Vec::new();
+        ciborium::into_writer(&canonicalize(value)?, &mut buf)?;
+        Ok(buf)
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), EncodeError> {
+        let value =
+            Value::serialized(self.0).map_err(|e| ciborium::ser::Error::Value(e.to_string()))?;
+
+        ciborium::into_writer(&canonicalize(value)?, buf)
+    }
+}
+
+impl<T> DecodableWithImpl<CanonicalCborEncoding> for Cover<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn decode_impl(data: &[u8]) -> Result<Self, DecodeError> {
+        decode_strict(data).map(Cover)
+    }
+}
+
+fn decode_strict<T: serde::de::DeserializeOwned>(data: &[u8]) -> Result<T, DecodeError> {
+    let mut reader = data;
+    let value = ciborium::from_reader(&mut reader)?;
+
+    if !reader.is_empty() {
+        return Err(ciborium::de::Error::semantic(
+            data.len() - reader.len(),
+            "unexpected trailing bytes",
+        ));
+    }
+
+    Ok(value)
+}
+
+/// Sorts map entries recursively by their encoded keys.
+///
+/// Everything else the deterministic encoding requires is already how `ciborium` writes
+/// values.
+fn canonicalize(value: Value) -> Result<Value, EncodeError> {
+    Ok(match value {
+        Value::Map(entries) => {
+            let mut entries = entries
+                .into_iter()
+                .map(|(k, v)| {
+                    let k = canonicalize(k)?;
+                    let mut key_bytes = Vec::new();
+                    ciborium::into_writer(&k, &mut key_bytes)?;
+
+                    Ok((key_bytes, k, canonicalize(v)?))
+                })
+                .collect::<Result<Vec<_>, EncodeError>>()?;
+            entries.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+
+            Value::Map(entries.into_iter().map(|(_, k, v)| (k, v)).collect())
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(canonicalize)
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Tag(tag, inner) => Value::Tag(tag, Box::new(canonicalize(*inner)?)),
+        other => other,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::encoding::{DecodableWith, EncodableWith};
+
+    fn encode<E: Encoding, T: EncodableWith<E>>(value: &T) -> Vec<u8> {
+        value.encode().unwrap()
+    }
+
+    fn decode<E: Encoding, T: DecodableWith<E>>(data: &[u8]) -> T {
+        T::decode(data).unwrap()
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Packet {
+        sequence: u64,
+        data: Vec<String>,
+        timeout: Option<u32>,
+    }
+
+    #[test]
+    fn roundtrip() {
+        let packet = Packet {
+            sequence: 1 << 40,
+            data: vec!["hello".to_string()],
+            timeout: None,
+        };
+
+        for bytes in [
+            encode::<CborEncoding, _>(&packet),
+            encode::<CanonicalCborEncoding, _>(&packet),
+        ] {
+            assert_eq!(decode::<CborEncoding, Packet>(&bytes), packet);
+            assert_eq!(decode::<CanonicalCborEncoding, Packet>(&bytes), packet);
+        }
+    }
+
+    /// Test vectors from RFC 8949, Appendix A.
+    #[test]
+    fn rfc_8949_vectors() {
+        fn check<T>(value: T, expected: &str)
+        where
+            T: Serialize + serde::de::DeserializeOwned + PartialEq + core::fmt::Debug,
+        {
+            let expected = hex(expected);
+            assert_eq!(encode::<CborEncoding, _>(&value), expected);
+            assert_eq!(encode::<CanonicalCborEncoding, _>(&value), expected);
+            assert_eq!(decode::<CborEncoding, T>(&expected), value);
+        }
+
+        check(0u8, "00");
+        check(23u8, "17");
+        check(24u8, "1818");
+        check(1000u32, "1903e8");
+        check(1000000u64, "1a000f4240");
+        check(1000000000000u64, "1b000000e8d4a51000");
+        check(-1i8, "20");
+        check(-1000i32, "3903e7");
+        check(1.5f64, "f93e00");
+        check(100000.0f64, "fa47c35000");
+        check(1.1f64, "fb3ff199999999999a");
+        check(false, "f4");
+        check(true, "f5");
+        check(None::<u8>, "f6");
+        check("IETF".to_string(), "6449455446");
+        check("\u{00fc}".to_string(), "62c3bc");
+        check(Vec::<u8>::new(), "80");
+        check((1u8, vec![2u8, 3], vec![4u8, 5]), "8301820203820405");
+        check(
+            BTreeMap::from([("a".to_string(), 1u8), ("b".to_string(), 2)]),
+            "a2616101616202",
+        );
+    }
+
+    #[test]
+    fn canonical_map_order() {
+        // RFC 8949, section 4.2.1: keys are sorted by their encoded form, so integers come
+        // before strings and shorter strings before longer ones
+        let value = Value::Map(vec![
+            (Value::Text("aa".to_string()), Value::from(0)),
+            (Value::Text("z".to_string()), Value::from(1)),
+            (Value::from(-1), Value::from(2)),
+            (Value::from(100), Value::from(3)),
+            (Value::from(10), Value::from(4)),
+        ]);
+
+        assert_eq!(
+            encode::<CanonicalCborEncoding, _>(&value),
+            hex("a50a041864032002617a0162616100")
+        );
+    }
+
+    #[test]
+    fn canonical_is_deterministic() {
+        let expected = encode::<CanonicalCborEncoding, _>(&BTreeMap::from([
+            ("aa".to_string(), vec![BTreeMap::from([(3u8, 0u8), (1, 0)])]),
+            ("b".to_string(), vec![]),
+        ]));
+
+        for _ in 0..16 {
+            let value: HashMap<String, Vec<HashMap<u8, u8>>> = HashMap::from([
+                ("b".to_string(), vec![]),
+                ("aa".to_string(), vec![HashMap::from([(1, 0), (3, 0)])]),
+            ]);
+
+            assert_eq!(encode::<CanonicalCborEncoding, _>(&value), expected);
+        }
+
+        // shorter key first, even though "aa" < "b" as strings
+        assert_eq!(&expected[..4], [0xa2, 0x61, b'b', 0x80]);
+    }
+
+    #[test]
+    fn strict_decoding() {
+        assert!(<u8 as DecodableWith<CborEncoding>>::decode(&hex("0000")).is_err());
+        assert!(<u8 as DecodableWith<CanonicalCborEncoding>>::decode(&hex("0000")).is_err());
+        assert!(<u32 as DecodableWith<CborEncoding>>::decode(&hex("1a000f")).is_err());
+    }
+}
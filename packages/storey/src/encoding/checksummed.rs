@@ -0,0 +1,295 @@
+use core::marker::PhantomData;
+
+use super::{Cover, DecodableWith, DecodableWithImpl, EncodableWith, EncodableWithImpl, Encoding};
+
+const CHECKSUM_LEN: usize = 4;
+
+/// An encoding adapter that appends a [CRC32] checksum to the output of `E`, and verifies it
+/// when decoding.
+///
+/// This is meant for deployments where the storage backend itself can't be fully trusted to
+/// hand back what was written (e.g. off-chain databases on unreliable disks). A corrupted
+/// value results in a [`ChecksumMismatch`] error rather than garbage, or a confusing error
+/// from `E`.
+///
+/// The checksum is stored as a little-endian `u32` after the payload.
+///
+/// # Unchecked reads
+///
+/// By default, values without a valid checksum are rejected, including ones written by plain
+/// `E` before the container switched to `Checksummed<E>`. Setting `ALLOW_UNCHECKED_READS` to
+/// `true` makes decoding fall back to reading the whole value as plain `E` when the checksum
+/// doesn't match. That allows reading such legacy values, at the cost of no longer detecting
+/// corruption reliably: a corrupted value is only reported if it also fails to decode as
+/// plain `E`. Writes always include the checksum.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// use storey::containers::Item;
+/// use storey::encoding::{Checksummed, ChecksummedDecodeError, FixedIntEncoding};
+/// use storey::storage::{Storage as _, StorageMut as _};
+///
+/// let mut storage = TestStorage::new();
+/// let item = Item::<u64, Checksummed<FixedIntEncoding>>::new(0);
+///
+/// item.access(&mut storage).set(&42).unwrap();
+/// assert_eq!(item.access(&storage).get().unwrap(), Some(42));
+///
+/// // the disk flips a bit
+/// let mut raw = storage.get(&[0]).unwrap();
+/// raw[0] ^= 0b100;
+/// storage.set(&[0], &raw);
+///
+/// assert!(matches!(
+///     item.access(&storage).get(),
+///     Err(storey::Error::Decode(ChecksummedDecodeError::ChecksumMismatch { .. }))
+/// ));
+/// ```
+///
+/// [CRC32]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+/// [`ChecksumMismatch`]: ChecksummedDecodeError::ChecksumMismatch
+pub struct Checksummed<E, const ALLOW_UNCHECKED_READS: bool = false>(PhantomData<E>);
+
+impl<E: Encoding, const ALLOW_UNCHECKED_READS: bool> Encoding
+    for Checksummed<E, ALLOW_UNCHECKED_READS>
+{
+    type DecodeError = ChecksummedDecodeError<E::DecodeError>;
+    type EncodeError = E::EncodeError;
+
+    const NAME: &'static str = "checksummed";
+}
+
+/// An error decoding a value with the [`Checksummed`] encoding.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum ChecksummedDecodeError<D> {
+    #[error("value of {len} bytes is too short to hold a checksum")]
+    MissingChecksum { len: usize },
+
+    #[error("checksum mismatch for value of {len} bytes: stored {expected:#010x}, computed {actual:#010x}")]
+    ChecksumMismatch {
+        expected: u32,
+        actual: u32,
+        len: usize,
+    },
+
+    #[error("decoding payload: {0}")]
+    Decode(D),
+}
+
+impl<T, E, const ALLOW_UNCHECKED_READS: bool>
+    EncodableWithImpl<Checksummed<E, ALLOW_UNCHECKED_READS>> for Cover<&T>
+where
+    T: EncodableWith<E> + ?Sized,
+    E: Encoding,
+{
+    fn encode_impl(self) -> Result<Vec<u8>, E::EncodeError> {
+        let mut bytes = Vec::new();
+        EncodableWithImpl::<Checksummed<E, ALLOW_UNCHECKED_READS>>::encode_to_impl(
+            self, &mut bytes,
+        )?;
+        Ok(bytes)
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), E::EncodeError> {
+        let start = buf.len();
+        self.0.encode_to(buf)?;
+
+        let checksum = crc32fast::hash(&buf[start..]);
+        buf.extend_from_slice(&checksum.to_le_bytes());
+
+        Ok(())
+    }
+}
+
+impl<T, E, const ALLOW_UNCHECKED_READS: bool>
+    DecodableWithImpl<Checksummed<E, ALLOW_UNCHECKED_READS>> for Cover<T>
+where
+    T: DecodableWith<E>,
+    E: Encoding,
+{
+    fn decode_impl(data: &[u8]) -> Result<Self, ChecksummedDecodeError<E::DecodeError>> {
+        match verify(data) {
+            Ok(payload) => T::decode(payload)
+                .map(Cover)
+                .map_err(ChecksummedDecodeError::Decode),
+            // The checksum error is the more useful one to report, unless the value turns out
+            // to be a legacy one.
+            Err(err) if ALLOW_UNCHECKED_READS => T::decode(data).map(Cover).map_err(|_| err),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Check the trailing checksum of `data`, and return the payload it covers.
+fn verify<D>(data: &[u8]) -> Result<&[u8], ChecksummedDecodeError<D>> {
+    let len = data.len();
+    let split = len
+        .checked_sub(CHECKSUM_LEN)
+        .ok_or(ChecksummedDecodeError::MissingChecksum { len })?;
+    let (payload, checksum) = data.split_at(split);
+
+    let expected = u32::from_le_bytes(checksum.try_into().unwrap());
+    let actual = crc32fast::hash(payload);
+
+    if expected != actual {
+        return Err(ChecksummedDecodeError::ChecksumMismatch {
+            expected,
+            actual,
+            len,
+        });
+    }
+
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::{Item, Map};
+    use crate::encoding::{FixedIntDecodeError, FixedIntEncoding};
+
+    use storey_storage::{Storage as _, StorageMut as _};
+    use storey_testing::backend::TestStorage;
+
+    type Error = ChecksummedDecodeError<FixedIntDecodeError>;
+
+    fn flip_byte(storage: &mut TestStorage, key: &[u8], index: usize) {
+        let mut raw = storage.get(key).unwrap();
+        raw[index] ^= 0xff;
+        storage.set(key, &raw);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut storage = TestStorage::new();
+        let item = Item::<u32, Checksummed<FixedIntEncoding>>::new(0);
+
+        item.access(&mut storage).set(&0xdead_beef).unwrap();
+        assert_eq!(
+            storage.get(&[0]).unwrap(),
+            [
+                &0xdead_beefu32.to_le_bytes()[..],
+                &crc32fast::hash(&0xdead_beefu32.to_le_bytes()).to_le_bytes()
+            ]
+            .concat()
+        );
+        assert_eq!(item.access(&storage).get().unwrap(), Some(0xdead_beef));
+    }
+
+    #[test]
+    fn encode_to_appends() {
+        let mut buf = vec![9];
+        EncodableWith::<Checksummed<FixedIntEncoding>>::encode_to(&7u32, &mut buf).unwrap();
+
+        // the checksum only covers the value itself
+        assert_eq!(buf[0], 9);
+        assert_eq!(
+            DecodableWith::<Checksummed<FixedIntEncoding>>::decode(&buf[1..]),
+            Ok(7u32)
+        );
+    }
+
+    #[test]
+    fn corrupted_payload() {
+        let mut storage = TestStorage::new();
+        let map = Map::<String, Item<u64, Checksummed<FixedIntEncoding>>>::new(0);
+
+        map.access(&mut storage).entry_mut("foo").set(&42).unwrap();
+        flip_byte(&mut storage, b"\x00\x03foo", 3);
+
+        let expected = crc32fast::hash(&42u64.to_le_bytes());
+        let mut corrupted = 42u64.to_le_bytes();
+        corrupted[3] ^= 0xff;
+
+        assert_eq!(
+            map.access(&storage).entry("foo").get(),
+            Err(crate::Error::Decode(Error::ChecksumMismatch {
+                expected,
+                actual: crc32fast::hash(&corrupted),
+                len: 12,
+            }))
+        );
+    }
+
+    #[test]
+    fn corrupted_checksum() {
+        let mut storage = TestStorage::new();
+        let item = Item::<u64, Checksummed<FixedIntEncoding>>::new(0);
+
+        item.access(&mut storage).set(&42).unwrap();
+        flip_byte(&mut storage, &[0], 8);
+
+        let err = item.access(&storage).get().unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Decode(Error::ChecksumMismatch { actual, len: 12, .. })
+                if actual == crc32fast::hash(&42u64.to_le_bytes())
+        ));
+    }
+
+    #[test]
+    fn too_short() {
+        assert_eq!(
+            DecodableWith::<Checksummed<FixedIntEncoding>>::decode(&[1, 2, 3]).map(|v: u8| v),
+            Err(Error::MissingChecksum { len: 3 })
+        );
+    }
+
+    #[test]
+    fn invalid_payload() {
+        // the checksum is fine, but the payload isn't a valid `u16`
+        let data = [&[1][..], &crc32fast::hash(&[1]).to_le_bytes()].concat();
+        assert_eq!(
+            DecodableWith::<Checksummed<FixedIntEncoding>>::decode(&data).map(|v: u16| v),
+            Err(Error::Decode(FixedIntDecodeError::InvalidLength {
+                expected: 2,
+                actual: 1
+            }))
+        );
+    }
+
+    #[test]
+    fn legacy_values_rejected_by_default() {
+        let mut storage = TestStorage::new();
+
+        Item::<u64, FixedIntEncoding>::new(0)
+            .access(&mut storage)
+            .set(&42)
+            .unwrap();
+
+        let item = Item::<u64, Checksummed<FixedIntEncoding>>::new(0);
+        assert!(matches!(
+            item.access(&storage).get(),
+            Err(crate::Error::Decode(Error::ChecksumMismatch { len: 8, .. }))
+        ));
+    }
+
+    #[test]
+    fn unchecked_reads() {
+        let mut storage = TestStorage::new();
+
+        Item::<u64, FixedIntEncoding>::new(0)
+            .access(&mut storage)
+            .set(&42)
+            .unwrap();
+
+        let item = Item::<u64, Checksummed<FixedIntEncoding, true>>::new(0);
+        assert_eq!(item.access(&storage).get().unwrap(), Some(42));
+
+        // writes are checksummed
+        item.access(&mut storage).set(&43).unwrap();
+        assert_eq!(storage.get(&[0]).unwrap().len(), 12);
+        assert_eq!(item.access(&storage).get().unwrap(), Some(43));
+
+        // corruption that doesn't look like a legacy value is still caught
+        flip_byte(&mut storage, &[0], 0);
+        assert!(matches!(
+            item.access(&storage).get(),
+            Err(crate::Error::Decode(Error::ChecksumMismatch {
+                len: 12,
+                ..
+            }))
+        ));
+    }
+}
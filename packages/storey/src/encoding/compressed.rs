@@ -0,0 +1,297 @@
+use core::marker::PhantomData;
+
+use lz4_flex::block::DecompressError;
+
+use super::{Cover, DecodableWith, DecodableWithImpl, EncodableWith, EncodableWithImpl, Encoding};
+
+const PLAIN: u8 = 0;
+const LZ4: u8 = 1;
+
+/// LZ4 can't expand data by more than a factor of 255, so a size header claiming more than
+/// that is corrupt. Checking it up front avoids allocating whatever the header says.
+const MAX_RATIO: usize = 255;
+
+/// An encoding adapter that compresses the output of `E` with [LZ4] once it gets larger than
+/// `THRESHOLD` bytes.
+///
+/// Every value is prefixed with a marker byte: `0` means the rest is the plain output of `E`,
+/// `1` means the rest is the LZ4 block of it, preceded by the uncompressed size as a
+/// little-endian `u32`. Values up to `THRESHOLD` bytes are never compressed, since the
+/// overhead usually outweighs the gain for them. Neither are values that don't actually get
+/// smaller.
+///
+/// # Reading legacy values
+///
+/// Decoding also accepts values written by plain `E`, so an existing container can be
+/// switched over to `Compressed<E>` without migrating its data. Old values are rewritten in
+/// the new format whenever they're next set.
+///
+/// The two formats can't be told apart with certainty, though. If a value doesn't start with
+/// a known marker, or doesn't decode when interpreted as one, it's decoded as a plain `E`
+/// value instead. That cuts both ways:
+/// - a legacy value which starts with `0x00` or `0x01` *and* happens to decode after
+///   stripping that byte is misread,
+/// - a corrupted compressed value which happens to be a valid plain `E` value is read as one
+///   rather than reported as an error.
+///
+/// How likely that is depends on `E`. Formats that validate their input, like JSON or
+/// MessagePack, practically never accept the other interpretation. Formats that accept any
+/// input, like [`RawEncoding`](super::RawEncoding) for `Vec<u8>`, always do. If that's a
+/// concern, migrate the data instead of relying on the fallback.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// use storey::containers::Item;
+/// use storey::encoding::{Compressed, RawEncoding};
+///
+/// let mut storage = TestStorage::new();
+/// let item = Item::<Vec<u8>, Compressed<RawEncoding, 64>>::new(0);
+///
+/// let log = b"all good\n".repeat(100);
+/// item.access(&mut storage).set(&log).unwrap();
+/// assert_eq!(item.access(&storage).get().unwrap(), Some(log));
+/// ```
+///
+/// [LZ4]: https://lz4.org/
+pub struct Compressed<E, const THRESHOLD: usize = 128>(PhantomData<E>);
+
+impl<E: Encoding, const THRESHOLD: usize> Encoding for Compressed<E, THRESHOLD> {
+    type DecodeError = CompressedDecodeError<E::DecodeError>;
+    type EncodeError = E::EncodeError;
+
+    const NAME: &'static str = "compressed";
+}
+
+/// An error decoding a value with the [`Compressed`] encoding.
+#[derive(Debug, thiserror::Error)]
+pub enum CompressedDecodeError<D> {
+    #[error("compressed payload of {len} bytes is missing its size header")]
+    MissingSize { len: usize },
+
+    #[error("compressed payload of {len} bytes claims an implausible uncompressed size of {size}")]
+    InvalidSize { size: usize, len: usize },
+
+    #[error("decompressing {len}-byte payload: {source}")]
+    Decompress {
+        len: usize,
+        #[source]
+        source: DecompressError,
+    },
+
+    #[error("payload decompressed to {actual} bytes, expected {expected}")]
+    SizeMismatch { expected: usize, actual: usize },
+
+    #[error("decoding payload: {0}")]
+    Decode(D),
+}
+
+impl<T, E, const THRESHOLD: usize> EncodableWithImpl<Compressed<E, THRESHOLD>> for Cover<&T>
+where
+    T: EncodableWith<E> + ?Sized,
+    E: Encoding,
+{
+    fn encode_impl(self) -> Result<Vec<u8>, E::EncodeError> {
+        let mut bytes = Vec::new();
+        EncodableWithImpl::<Compressed<E, THRESHOLD>>::encode_to_impl(self, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), E::EncodeError> {
+        let start = buf.len();
+        buf.push(PLAIN);
+        self.0.encode_to(buf)?;
+
+        let payload = &buf[start + 1..];
+        if payload.len() > THRESHOLD && u32::try_from(payload.len()).is_ok() {
+            let compressed = lz4_flex::block::compress_prepend_size(payload);
+
+            if compressed.len() < payload.len() {
+                buf.truncate(start);
+                buf.push(LZ4);
+                buf.extend_from_slice(&compressed);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T, E, const THRESHOLD: usize> DecodableWithImpl<Compressed<E, THRESHOLD>> for Cover<T>
+where
+    T: DecodableWith<E>,
+    E: Encoding,
+{
+    fn decode_impl(data: &[u8]) -> Result<Self, CompressedDecodeError<E::DecodeError>> {
+        let result = match data.split_first() {
+            Some((&PLAIN, payload)) => T::decode(payload).map_err(CompressedDecodeError::Decode),
+            Some((&LZ4, payload)) => decompress(payload)
+                .and_then(|payload| T::decode(&payload).map_err(CompressedDecodeError::Decode)),
+            _ => {
+                return T::decode(data)
+                    .map(Cover)
+                    .map_err(CompressedDecodeError::Decode)
+            }
+        };
+
+        // The error from the marked interpretation is the more useful one to report, unless
+        // the value turns out to be a legacy one.
+        result
+            .or_else(|err| T::decode(data).map_err(|_| err))
+            .map(Cover)
+    }
+}
+
+fn decompress<D>(payload: &[u8]) -> Result<Vec<u8>, CompressedDecodeError<D>> {
+    let len = payload.len();
+    let (size, block) = lz4_flex::block::uncompressed_size(payload)
+        .map_err(|_| CompressedDecodeError::MissingSize { len })?;
+
+    if size > block.len().saturating_mul(MAX_RATIO) {
+        return Err(CompressedDecodeError::InvalidSize { size, len });
+    }
+
+    let bytes = lz4_flex::block::decompress(block, size)
+        .map_err(|source| CompressedDecodeError::Decompress { len, source })?;
+
+    if bytes.len() != size {
+        return Err(CompressedDecodeError::SizeMismatch {
+            expected: size,
+            actual: bytes.len(),
+        });
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::Item;
+    use crate::encoding::{FixedIntEncoding, RawEncoding};
+
+    use storey_storage::{Storage as _, StorageMut as _};
+    use storey_testing::backend::TestStorage;
+
+    type Lz4 = Compressed<RawEncoding, 64>;
+
+    fn encode(value: &[u8]) -> Vec<u8> {
+        EncodableWith::<Lz4>::encode(value).unwrap()
+    }
+
+    fn decode(data: &[u8]) -> Result<Vec<u8>, CompressedDecodeError<core::str::Utf8Error>> {
+        DecodableWith::<Lz4>::decode(data)
+    }
+
+    // Unlike `Vec<u8>`, not every byte string is a valid `String`, so corrupted payloads can't
+    // be mistaken for legacy values.
+    fn decode_str(data: &[u8]) -> Result<String, CompressedDecodeError<core::str::Utf8Error>> {
+        DecodableWith::<Lz4>::decode(data)
+    }
+
+    #[test]
+    fn small_values_are_stored_plain() {
+        assert_eq!(encode(b"hello"), b"\x00hello");
+        assert_eq!(encode(&[7; 64]), [&[0][..], &[7; 64]].concat());
+        assert_eq!(decode(b"\x00hello").unwrap(), b"hello");
+    }
+
+    #[test]
+    fn compresses_repetitive_payload() {
+        let payload = b"{\"owner\":\"alice\",\"amount\":100}".repeat(200);
+
+        let encoded = encode(&payload);
+        assert_eq!(encoded[0], LZ4);
+        assert!(
+            encoded.len() * 10 < payload.len(),
+            "expected at least 10x reduction, got {} -> {} bytes",
+            payload.len(),
+            encoded.len()
+        );
+
+        assert_eq!(decode(&encoded).unwrap(), payload);
+    }
+
+    #[test]
+    fn encode_to_appends() {
+        let payload = b"abcdefgh".repeat(100);
+
+        for value in [&b"hello"[..], &payload] {
+            let mut buf = vec![9];
+            EncodableWith::<Lz4>::encode_to(value, &mut buf).unwrap();
+
+            assert_eq!(buf[0], 9);
+            assert_eq!(buf[1..], encode(value));
+        }
+    }
+
+    #[test]
+    fn incompressible_values_are_stored_plain() {
+        // a sequence with no repetitions LZ4 could make use of
+        let payload: Vec<u8> = (0..=255u8).map(|b| b.wrapping_mul(167)).collect();
+
+        let encoded = encode(&payload);
+        assert_eq!(encoded[0], PLAIN);
+        assert_eq!(decode(&encoded).unwrap(), payload);
+    }
+
+    #[test]
+    fn reads_legacy_values() {
+        let mut storage = TestStorage::new();
+
+        let legacy = Item::<u64, FixedIntEncoding>::new(0);
+        let item = Item::<u64, Compressed<FixedIntEncoding>>::new(0);
+
+        legacy.access(&mut storage).set(&0x0102).unwrap();
+        assert_eq!(item.access(&storage).get().unwrap(), Some(0x0102));
+
+        item.access(&mut storage).set(&0x0102).unwrap();
+        assert_eq!(storage.get(&[0]).unwrap()[0], PLAIN);
+        assert_eq!(item.access(&storage).get().unwrap(), Some(0x0102));
+
+        // starts with a marker byte, but the rest is too short to be a marked value
+        storage.set(&[0], &1u64.to_le_bytes());
+        assert_eq!(item.access(&storage).get().unwrap(), Some(1));
+    }
+
+    #[test]
+    fn corrupted_payloads_are_errors() {
+        let payload = "abcdefgh".repeat(100);
+        let encoded = encode(payload.as_bytes());
+
+        // truncated
+        let err = decode_str(&encoded[..encoded.len() - 10]).unwrap_err();
+        assert!(matches!(err, CompressedDecodeError::Decompress { .. }));
+        assert!(err.to_string().starts_with("decompressing"));
+
+        // no room for the size header
+        let err = decode_str(&[LZ4, 0xff, 0xff]).unwrap_err();
+        assert!(matches!(err, CompressedDecodeError::MissingSize { len: 2 }));
+
+        // a size header claiming way more than the block could hold
+        let mut bogus = encoded.clone();
+        bogus[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = decode_str(&bogus).unwrap_err();
+        assert!(matches!(err, CompressedDecodeError::InvalidSize { .. }));
+
+        // a size header claiming more than the block actually holds
+        let mut short = encoded.clone();
+        short[1..5].copy_from_slice(&(payload.len() as u32 + 1).to_le_bytes());
+        let err = decode_str(&short).unwrap_err();
+        assert!(matches!(
+            err,
+            CompressedDecodeError::SizeMismatch {
+                expected: 801,
+                actual: 800
+            }
+        ));
+    }
+
+    #[test]
+    fn inner_decode_errors_are_wrapped() {
+        let err = DecodableWith::<Compressed<FixedIntEncoding>>::decode(&[PLAIN, 1, 2])
+            .map(|v: u64| v)
+            .unwrap_err();
+        assert!(matches!(err, CompressedDecodeError::Decode(_)));
+    }
+}
@@ -0,0 +1,370 @@
+use core::marker::PhantomData;
+
+use super::{Cover, DecodableWith, DecodableWithImpl, EncodableWith, EncodableWithImpl, Encoding};
+
+/// An encoding adapter that encrypts the output of `E` with an [AEAD] cipher.
+///
+/// **This is not meant for on-chain use.** Everything a contract stores is public, and so is
+/// everything it executes, key included. This adapter is for off-chain deployments of `storey`
+/// that keep sensitive data (e.g. personal information) in a database that shouldn't be able to
+/// read it.
+///
+/// The cipher and its key are provided by the caller through the [`Cipher`] trait. This crate
+/// doesn't implement any cryptography itself, and doesn't get involved in key management.
+///
+/// Values are stored as a random nonce followed by whatever [`Cipher::encrypt`] returns (the
+/// ciphertext and the authentication tag, for the usual AEAD constructions).
+///
+/// # Associated data
+///
+/// Every value is authenticated together with [`Cipher::ASSOCIATED_DATA`]. Set it to something
+/// unique to the container the cipher is used for, like its prefix: then a value copied over
+/// from another container fails to decrypt rather than being accepted.
+///
+/// Encodings don't know which key a value is stored under, so the associated data can't
+/// identify a single entry. Within one container (e.g. between two entries of a [`Map`]),
+/// values can still be swapped without being detected. If that matters, store the key in the
+/// value too and check it after reading.
+///
+/// A value that fails authentication (wrong key, tampering, another container's data) results
+/// in [`EncryptedDecodeError::Decrypt`], which is distinct from errors decoding the decrypted
+/// payload.
+///
+/// # Example
+///
+/// An implementation of [`Cipher`] on top of the `aes-gcm` crate could look like this:
+///
+/// ```ignore
+/// use aes_gcm::aead::{Aead, KeyInit, Payload};
+/// use aes_gcm::{Aes256Gcm, Nonce};
+/// use storey::containers::{Item, Map};
+/// use storey::encoding::{Cipher, Encrypted, MsgPackEncoding};
+///
+/// struct ProfilesCipher;
+///
+/// impl Cipher for ProfilesCipher {
+///     const NONCE_LEN: usize = 12;
+///     const ASSOCIATED_DATA: &'static [u8] = b"profiles";
+///
+///     fn encrypt(nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+///         let payload = Payload { msg: plaintext, aad: associated_data };
+///         cipher().encrypt(Nonce::from_slice(nonce), payload).expect("value too large")
+///     }
+///
+///     fn decrypt(nonce: &[u8], associated_data: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>> {
+///         let payload = Payload { msg: ciphertext, aad: associated_data };
+///         cipher().decrypt(Nonce::from_slice(nonce), payload).ok()
+///     }
+/// }
+///
+/// fn cipher() -> Aes256Gcm {
+///     // however the application manages its keys
+///     Aes256Gcm::new(&load_key())
+/// }
+///
+/// const PROFILES: Map<String, Item<Profile, Encrypted<MsgPackEncoding, ProfilesCipher>>> =
+///     Map::new(0);
+/// ```
+///
+/// [AEAD]: https://en.wikipedia.org/wiki/Authenticated_encryption
+/// [`Map`]: crate::containers::Map
+pub struct Encrypted<E, C>(PhantomData<(E, C)>);
+
+impl<E: Encoding, C: Cipher> Encoding for Encrypted<E, C> {
+    type DecodeError = EncryptedDecodeError<E::DecodeError>;
+    type EncodeError = E::EncodeError;
+
+    const NAME: &'static str = "encrypted";
+}
+
+/// An [AEAD] cipher, with its key, for the [`Encrypted`] encoding adapter.
+///
+/// Encodings have no state, so neither does this trait: the implementation has to get hold of
+/// the key on its own, from wherever the application keeps it.
+///
+/// [AEAD]: https://en.wikipedia.org/wiki/Authenticated_encryption
+pub trait Cipher {
+    /// The length of the nonce in bytes.
+    const NONCE_LEN: usize;
+
+    /// The associated data every value is authenticated with. See
+    /// [the `Encrypted` docs](Encrypted#associated-data).
+    const ASSOCIATED_DATA: &'static [u8];
+
+    /// Fill `nonce` with a fresh nonce.
+    ///
+    /// The default implementation uses the operating system's random number generator, which
+    /// is the right choice for ciphers with nonces long enough to be picked at random (96 bits
+    /// or more). A nonce must never be used twice with the same key.
+    ///
+    /// # Panics
+    ///
+    /// The default implementation panics if the random number generator fails.
+    fn generate_nonce(nonce: &mut [u8]) {
+        getrandom::getrandom(nonce).expect("failed to generate a random nonce");
+    }
+
+    /// Encrypt and authenticate `plaintext`, also authenticating `associated_data`.
+    ///
+    /// The result is stored after the nonce.
+    fn encrypt(nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> Vec<u8>;
+
+    /// Decrypt `ciphertext` (as returned by [`encrypt`](Cipher::encrypt)).
+    ///
+    /// Returns `None` if the ciphertext or the associated data fail authentication.
+    fn decrypt(nonce: &[u8], associated_data: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// An error decoding a value with the [`Encrypted`] encoding.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum EncryptedDecodeError<D> {
+    #[error("value of {len} bytes is too short to hold a nonce")]
+    MissingNonce { len: usize },
+
+    #[error("decryption failed: wrong key, or the value was tampered with or moved")]
+    Decrypt,
+
+    #[error("decoding payload: {0}")]
+    Decode(D),
+}
+
+impl<T, E, C> EncodableWithImpl<Encrypted<E, C>> for Cover<&T>
+where
+    T: EncodableWith<E> + ?Sized,
+    E: Encoding,
+    C: Cipher,
+{
+    fn encode_impl(self) -> Result<Vec<u8>, E::EncodeError> {
+        let plaintext = self.0.encode()?;
+
+        let mut bytes = vec![0; C::NONCE_LEN];
+        C::generate_nonce(&mut bytes);
+        let ciphertext = C::encrypt(&bytes, C::ASSOCIATED_DATA, &plaintext);
+        bytes.extend_from_slice(&ciphertext);
+
+        Ok(bytes)
+    }
+}
+
+impl<T, E, C> DecodableWithImpl<Encrypted<E, C>> for Cover<T>
+where
+    T: DecodableWith<E>,
+    E: Encoding,
+    C: Cipher,
+{
+    fn decode_impl(data: &[u8]) -> Result<Self, EncryptedDecodeError<E::DecodeError>> {
+        if data.len() < C::NONCE_LEN {
+            return Err(EncryptedDecodeError::MissingNonce { len: data.len() });
+        }
+
+        let (nonce, ciphertext) = data.split_at(C::NONCE_LEN);
+        let plaintext = C::decrypt(nonce, C::ASSOCIATED_DATA, ciphertext)
+            .ok_or(EncryptedDecodeError::Decrypt)?;
+
+        T::decode(&plaintext)
+            .map(Cover)
+            .map_err(EncryptedDecodeError::Decode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    use super::*;
+    use crate::containers::{Item, Map};
+    use crate::encoding::{FixedIntDecodeError, FixedIntEncoding};
+    use crate::storage::{Storage as _, StorageMut as _};
+
+    use storey_testing::backend::TestStorage;
+
+    // A test-only AEAD: HMAC-SHA256 in counter mode for encryption, and a truncated
+    // HMAC-SHA256 over the associated data, nonce and ciphertext for authentication.
+
+    const ENC_KEY: [u8; 32] = [0x11; 32];
+    const MAC_KEY: [u8; 32] = [0x22; 32];
+    const TAG_LEN: usize = 16;
+
+    fn hmac(key: &[u8]) -> Hmac<Sha256> {
+        Hmac::new_from_slice(key).unwrap()
+    }
+
+    fn apply_keystream(nonce: &[u8], data: &mut [u8]) {
+        for (i, chunk) in data.chunks_mut(32).enumerate() {
+            let mut mac = hmac(&ENC_KEY);
+            mac.update(nonce);
+            mac.update(&(i as u32).to_be_bytes());
+            let block = mac.finalize().into_bytes();
+
+            for (byte, key) in chunk.iter_mut().zip(block) {
+                *byte ^= key;
+            }
+        }
+    }
+
+    fn tag(nonce: &[u8], associated_data: &[u8], ciphertext: &[u8]) -> Hmac<Sha256> {
+        let mut mac = hmac(&MAC_KEY);
+        mac.update(&(associated_data.len() as u64).to_be_bytes());
+        mac.update(associated_data);
+        mac.update(nonce);
+        mac.update(ciphertext);
+        mac
+    }
+
+    macro_rules! test_cipher {
+        ($name:ident, $ad:expr $(, $nonce:expr)?) => {
+            struct $name;
+
+            impl Cipher for $name {
+                const NONCE_LEN: usize = 12;
+                const ASSOCIATED_DATA: &'static [u8] = $ad;
+
+                $(
+                    fn generate_nonce(nonce: &mut [u8]) {
+                        nonce.copy_from_slice(&$nonce);
+                    }
+                )?
+
+                fn encrypt(nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+                    let mut ciphertext = plaintext.to_vec();
+                    apply_keystream(nonce, &mut ciphertext);
+
+                    let tag = tag(nonce, associated_data, &ciphertext).finalize().into_bytes();
+                    ciphertext.extend_from_slice(&tag[..TAG_LEN]);
+                    ciphertext
+                }
+
+                fn decrypt(
+                    nonce: &[u8],
+                    associated_data: &[u8],
+                    ciphertext: &[u8],
+                ) -> Option<Vec<u8>> {
+                    let split = ciphertext.len().checked_sub(TAG_LEN)?;
+                    let (ciphertext, expected) = ciphertext.split_at(split);
+                    tag(nonce, associated_data, ciphertext)
+                        .verify_truncated_left(expected)
+                        .ok()?;
+
+                    let mut plaintext = ciphertext.to_vec();
+                    apply_keystream(nonce, &mut plaintext);
+                    Some(plaintext)
+                }
+            }
+        };
+    }
+
+    test_cipher!(Balances, b"balances", [7; 12]);
+    test_cipher!(Profiles, b"profiles", [7; 12]);
+    test_cipher!(Random, b"balances");
+
+    type Enc<C> = Encrypted<FixedIntEncoding, C>;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    // Computed independently with Python's `hmac` module: nonce, then the ciphertext, then the
+    // truncated tag.
+    #[test]
+    fn vectors() {
+        let encoded = EncodableWith::<Enc<Balances>>::encode(&0x0102030405060708u64).unwrap();
+        assert_eq!(
+            hex(&encoded),
+            "070707070707070707070707840692985ee6bb9324fd8144ec6498c6aea981ba795b9195"
+        );
+
+        let encoded = EncodableWith::<Enc<Balances>>::encode(&"hi".to_string()).unwrap();
+        assert_eq!(
+            hex(&encoded),
+            "070707070707070707070707e4681825ac1c1d8bd7a03b089326142f63aa"
+        );
+
+        assert_eq!(
+            <u64 as DecodableWith<Enc<Balances>>>::decode(
+                &EncodableWith::<Enc<Balances>>::encode(&42u64).unwrap()
+            ),
+            Ok(42)
+        );
+    }
+
+    #[test]
+    fn random_nonces() {
+        let a = EncodableWith::<Enc<Random>>::encode(&42u64).unwrap();
+        let b = EncodableWith::<Enc<Random>>::encode(&42u64).unwrap();
+        assert_ne!(a[..12], b[..12]);
+
+        assert_eq!(<u64 as DecodableWith<Enc<Random>>>::decode(&a), Ok(42));
+        assert_eq!(<u64 as DecodableWith<Enc<Random>>>::decode(&b), Ok(42));
+
+        // same key and associated data
+        assert_eq!(<u64 as DecodableWith<Enc<Balances>>>::decode(&a), Ok(42));
+    }
+
+    #[test]
+    fn tampering() {
+        let mut storage = TestStorage::new();
+        let item = Item::<u64, Enc<Balances>>::new(0);
+        item.access(&mut storage).set(&42).unwrap();
+
+        let raw = storage.get(&[0]).unwrap();
+        for i in 0..raw.len() {
+            let mut tampered = raw.clone();
+            tampered[i] ^= 1;
+            storage.set(&[0], &tampered);
+
+            assert_eq!(
+                item.access(&storage).get(),
+                Err(crate::Error::Decode(EncryptedDecodeError::Decrypt))
+            );
+        }
+
+        storage.set(&[0], &raw[..5]);
+        assert_eq!(
+            item.access(&storage).get(),
+            Err(crate::Error::Decode(EncryptedDecodeError::MissingNonce {
+                len: 5
+            }))
+        );
+    }
+
+    #[test]
+    fn transplanted_value() {
+        let mut storage = TestStorage::new();
+
+        let balances = Map::<String, Item<u64, Enc<Balances>>>::new(0);
+        let profiles = Map::<String, Item<u64, Enc<Profiles>>>::new(1);
+
+        balances
+            .access(&mut storage)
+            .entry_mut("alice")
+            .set(&1000)
+            .unwrap();
+
+        let raw = storage
+            .get(&[&[0][..], &crate::key!("alice")].concat())
+            .unwrap();
+        storage.set(&[&[1][..], &crate::key!("alice")].concat(), &raw);
+
+        assert_eq!(
+            profiles.access(&storage).entry("alice").get(),
+            Err(crate::Error::Decode(EncryptedDecodeError::Decrypt))
+        );
+    }
+
+    #[test]
+    fn inner_decode_error() {
+        // authentic, but not a `u64`
+        let encoded = EncodableWith::<Enc<Balances>>::encode(&7u8).unwrap();
+
+        assert_eq!(
+            <u64 as DecodableWith<Enc<Balances>>>::decode(&encoded),
+            Err(EncryptedDecodeError::Decode(
+                FixedIntDecodeError::InvalidLength {
+                    expected: 8,
+                    actual: 1
+                }
+            ))
+        );
+    }
+}
@@ -0,0 +1,507 @@
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::str::Utf8Error;
+
+use super::{Cover, DecodableWithImpl, EncodableWithImpl, Encoding};
+
+/// A dependency-free encoding for integers, booleans, strings, byte vectors, and options and
+/// small tuples of those.
+///
+/// This covers the basic state of most contracts without pulling in `serde` or any other
+/// serialization framework. The layout is:
+///
+/// | Type                          | Layout                                                   |
+/// |-------------------------------|----------------------------------------------------------|
+/// | `u8`..`u128`, `i8`..`i128`    | fixed-width little-endian bytes                          |
+/// | `bool`                        | one byte, `0` or `1`                                     |
+/// | [`String`], [`Vec<u8>`]       | the bytes themselves, length-prefixed if not last        |
+/// | [`Option<T>`]                 | `0` for `None`, `1` followed by `T` for `Some`           |
+/// | `(A, B)`, `(A, B, C)`, ...    | the components one after another (up to four of them)    |
+///
+/// A string or byte vector that isn't the last thing in a value (e.g. the first component of a
+/// `(String, u64)`) is prefixed with its length as a little-endian `u32`, so that decoding
+/// knows where it ends. Everywhere else it takes up the rest of the value, which means a lone
+/// `String` or `Vec<u8>` is stored exactly like with [`RawEncoding`](super::RawEncoding).
+///
+/// `usize` and `isize` are deliberately not supported, since their width depends on the
+/// platform.
+///
+/// Decoding is strict: the input has to have exactly the right length, a boolean has to be
+/// `0` or `1`, and strings have to be valid UTF-8.
+///
+/// # Panics
+///
+/// Encoding panics if a string or byte vector that has to be length-prefixed is longer than
+/// `u32::MAX` bytes.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// use storey::containers::Item;
+/// use storey::encoding::FixedIntEncoding;
+/// use storey::storage::Storage as _;
+///
+/// let mut storage = TestStorage::new();
+/// let item = Item::<u32, FixedIntEncoding>::new(0);
+///
+/// item.access(&mut storage).set(&0x01020304).unwrap();
+/// assert_eq!(storage.get(&[0]), Some(vec![4, 3, 2, 1]));
+///
+/// let item = Item::<(String, Option<u16>), FixedIntEncoding>::new(1);
+///
+/// item.access(&mut storage).set(&("ab".to_string(), Some(7))).unwrap();
+/// assert_eq!(storage.get(&[1]), Some(vec![2, 0, 0, 0, b'a', b'b', 1, 7, 0]));
+/// ```
+pub struct FixedIntEncoding;
+
+impl Encoding for FixedIntEncoding {
+    type DecodeError = FixedIntDecodeError;
+    type EncodeError = Infallible;
+
+    const NAME: &'static str = "fixed-int";
+}
+
+/// An error decoding a value with [`FixedIntEncoding`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum FixedIntDecodeError {
+    #[error("invalid length, expected {expected} bytes, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+
+    #[error("invalid boolean byte {0}, expected 0 or 1")]
+    InvalidBool(u8),
+
+    #[error("invalid option tag {0}, expected 0 or 1")]
+    InvalidOptionTag(u8),
+
+    #[error("invalid UTF8: {0}")]
+    InvalidUtf8(#[from] Utf8Error),
+}
+
+/// A type with a [`FixedIntEncoding`] layout.
+///
+/// The `*_last` methods handle a value that takes up the rest of the data, the others one that
+/// might be followed by more data and so has to be self-delimiting. They're the same for
+/// fixed-width types.
+trait Field: Sized {
+    fn encode_field(&self, buf: &mut Vec<u8>);
+
+    fn encode_last(&self, buf: &mut Vec<u8>) {
+        self.encode_field(buf)
+    }
+
+    /// Decode a value from the front of `data`, advancing it past the value.
+    fn decode_field(data: &mut &[u8]) -> Result<Self, FixedIntDecodeError>;
+
+    fn decode_last(data: &[u8]) -> Result<Self, FixedIntDecodeError> {
+        let mut rest = data;
+        let value = Self::decode_field(&mut rest)?;
+
+        if !rest.is_empty() {
+            return Err(FixedIntDecodeError::InvalidLength {
+                expected: data.len() - rest.len(),
+                actual: data.len(),
+            });
+        }
+
+        Ok(value)
+    }
+}
+
+/// Split `len` bytes off the front of `data`.
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], FixedIntDecodeError> {
+    if data.len() < len {
+        return Err(FixedIntDecodeError::InvalidLength {
+            expected: len,
+            actual: data.len(),
+        });
+    }
+
+    let (head, rest) = data.split_at(len);
+    *data = rest;
+    Ok(head)
+}
+
+impl<T: Field> EncodableWithImpl<FixedIntEncoding> for Cover<&T> {
+    fn encode_impl(self) -> Result<Vec<u8>, Infallible> {
+        let mut buf = Vec::new();
+        self.0.encode_last(&mut buf);
+        Ok(buf)
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), Infallible> {
+        self.0.encode_last(buf);
+        Ok(())
+    }
+}
+
+impl<T: Field> DecodableWithImpl<FixedIntEncoding> for Cover<T> {
+    fn decode_impl(data: &[u8]) -> Result<Self, FixedIntDecodeError> {
+        T::decode_last(data).map(Cover)
+    }
+}
+
+macro_rules! fixed_int {
+    ($($t:ty),*) => {
+        $(
+            impl Field for $t {
+                fn encode_field(&self, buf: &mut Vec<u8>) {
+                    buf.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode_field(data: &mut &[u8]) -> Result<Self, FixedIntDecodeError> {
+                    let bytes = take(data, core::mem::size_of::<$t>())?;
+                    Ok(<$t>::from_le_bytes(bytes.try_into().unwrap()))
+                }
+
+                fn decode_last(data: &[u8]) -> Result<Self, FixedIntDecodeError> {
+                    let bytes = data.try_into().map_err(|_| FixedIntDecodeError::InvalidLength {
+                        expected: core::mem::size_of::<$t>(),
+                        actual: data.len(),
+                    })?;
+
+                    Ok(<$t>::from_le_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+fixed_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl Field for bool {
+    fn encode_field(&self, buf: &mut Vec<u8>) {
+        buf.push(*self as u8);
+    }
+
+    fn decode_field(data: &mut &[u8]) -> Result<Self, FixedIntDecodeError> {
+        match take(data, 1)? {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            [b] => Err(FixedIntDecodeError::InvalidBool(*b)),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn encode_len(len: usize, buf: &mut Vec<u8>) {
+    let len = u32::try_from(len).expect("length-prefixed value longer than u32::MAX bytes");
+    buf.extend_from_slice(&len.to_le_bytes());
+}
+
+fn decode_len(data: &mut &[u8]) -> Result<usize, FixedIntDecodeError> {
+    u32::decode_field(data).map(|len| len as usize)
+}
+
+impl Field for Vec<u8> {
+    fn encode_field(&self, buf: &mut Vec<u8>) {
+        encode_len(self.len(), buf);
+        buf.extend_from_slice(self);
+    }
+
+    fn encode_last(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self);
+    }
+
+    fn decode_field(data: &mut &[u8]) -> Result<Self, FixedIntDecodeError> {
+        let len = decode_len(data)?;
+        Ok(take(data, len)?.to_vec())
+    }
+
+    fn decode_last(data: &[u8]) -> Result<Self, FixedIntDecodeError> {
+        Ok(data.to_vec())
+    }
+}
+
+impl Field for String {
+    fn encode_field(&self, buf: &mut Vec<u8>) {
+        encode_len(self.len(), buf);
+        buf.extend_from_slice(self.as_bytes());
+    }
+
+    fn encode_last(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode_field(data: &mut &[u8]) -> Result<Self, FixedIntDecodeError> {
+        let len = decode_len(data)?;
+        Self::decode_last(take(data, len)?)
+    }
+
+    fn decode_last(data: &[u8]) -> Result<Self, FixedIntDecodeError> {
+        Ok(core::str::from_utf8(data)?.to_string())
+    }
+}
+
+impl<T: Field> Field for Option<T> {
+    fn encode_field(&self, buf: &mut Vec<u8>) {
+        match self {
+            None => buf.push(0),
+            Some(value) => {
+                buf.push(1);
+                value.encode_field(buf);
+            }
+        }
+    }
+
+    fn encode_last(&self, buf: &mut Vec<u8>) {
+        match self {
+            None => buf.push(0),
+            Some(value) => {
+                buf.push(1);
+                value.encode_last(buf);
+            }
+        }
+    }
+
+    fn decode_field(data: &mut &[u8]) -> Result<Self, FixedIntDecodeError> {
+        match take(data, 1)? {
+            [0] => Ok(None),
+            [1] => T::decode_field(data).map(Some),
+            [b] => Err(FixedIntDecodeError::InvalidOptionTag(*b)),
+            _ => unreachable!(),
+        }
+    }
+
+    fn decode_last(data: &[u8]) -> Result<Self, FixedIntDecodeError> {
+        match data.split_first() {
+            Some((0, [])) => Ok(None),
+            Some((1, rest)) => T::decode_last(rest).map(Some),
+            Some((0, _)) => Err(FixedIntDecodeError::InvalidLength {
+                expected: 1,
+                actual: data.len(),
+            }),
+            Some((b, _)) => Err(FixedIntDecodeError::InvalidOptionTag(*b)),
+            None => Err(FixedIntDecodeError::InvalidLength {
+                expected: 1,
+                actual: 0,
+            }),
+        }
+    }
+}
+
+macro_rules! tuple {
+    ($($t:ident $i:tt),*; $last:ident $l:tt) => {
+        impl<$($t: Field,)* $last: Field> Field for ($($t,)* $last,) {
+            fn encode_field(&self, buf: &mut Vec<u8>) {
+                $(self.$i.encode_field(buf);)*
+                self.$l.encode_field(buf);
+            }
+
+            fn encode_last(&self, buf: &mut Vec<u8>) {
+                $(self.$i.encode_field(buf);)*
+                self.$l.encode_last(buf);
+            }
+
+            fn decode_field(data: &mut &[u8]) -> Result<Self, FixedIntDecodeError> {
+                Ok(($($t::decode_field(data)?,)* $last::decode_field(data)?,))
+            }
+
+            fn decode_last(mut data: &[u8]) -> Result<Self, FixedIntDecodeError> {
+                Ok(($($t::decode_field(&mut data)?,)* $last::decode_last(data)?,))
+            }
+        }
+    };
+}
+
+tuple!(A 0; B 1);
+tuple!(A 0, B 1; C 2);
+tuple!(A 0, B 1, C 2; D 3);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::{Item, Map};
+    use crate::encoding::{DecodableWith, EncodableWith};
+
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
+
+    fn encode<T: EncodableWith<FixedIntEncoding>>(value: &T) -> Vec<u8> {
+        value.encode().unwrap()
+    }
+
+    fn decode<T: DecodableWith<FixedIntEncoding>>(data: &[u8]) -> Result<T, FixedIntDecodeError> {
+        T::decode(data)
+    }
+
+    #[test]
+    fn little_endian() {
+        assert_eq!(encode(&1u16), [1, 0]);
+        assert_eq!(encode(&-2i32), [0xfe, 0xff, 0xff, 0xff]);
+        assert_eq!(encode(&u128::MAX), [0xff; 16]);
+        assert_eq!(encode(&true), [1]);
+        assert_eq!(encode(&false), [0]);
+    }
+
+    #[test]
+    fn encode_to_appends() {
+        let mut buf = vec![9];
+        EncodableWith::<FixedIntEncoding>::encode_to(&1u16, &mut buf).unwrap();
+        EncodableWith::<FixedIntEncoding>::encode_to(&true, &mut buf).unwrap();
+        assert_eq!(buf, [9, 1, 0, 1]);
+    }
+
+    #[test]
+    fn roundtrip() {
+        assert_eq!(decode(&encode(&i64::MIN)), Ok(i64::MIN));
+        assert_eq!(decode(&encode(&42u8)), Ok(42u8));
+        assert_eq!(decode(&encode(&true)), Ok(true));
+    }
+
+    #[test]
+    fn strict_decoding() {
+        assert_eq!(
+            decode::<u32>(&[1, 0, 0]),
+            Err(FixedIntDecodeError::InvalidLength {
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert_eq!(
+            decode::<u8>(&[1, 0]),
+            Err(FixedIntDecodeError::InvalidLength {
+                expected: 1,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            decode::<bool>(&[2]),
+            Err(FixedIntDecodeError::InvalidBool(2))
+        );
+        assert_eq!(
+            decode::<bool>(&[]),
+            Err(FixedIntDecodeError::InvalidLength {
+                expected: 1,
+                actual: 0
+            })
+        );
+    }
+
+    #[test]
+    fn strings_and_bytes() {
+        assert_eq!(encode(&"foo".to_string()), b"foo");
+        assert_eq!(encode(&vec![1u8, 2]), [1, 2]);
+        assert!(encode(&String::new()).is_empty());
+
+        assert_eq!(decode(b"foo"), Ok("foo".to_string()));
+        assert_eq!(decode(&[1, 2]), Ok(vec![1u8, 2]));
+        assert!(matches!(
+            decode::<String>(&[0xff]),
+            Err(FixedIntDecodeError::InvalidUtf8(_))
+        ));
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(encode(&None::<u16>), [0]);
+        assert_eq!(encode(&Some(5u16)), [1, 5, 0]);
+        assert_eq!(encode(&Some("ab".to_string())), [1, b'a', b'b']);
+        assert_eq!(encode(&Some(None::<bool>)), [1, 0]);
+
+        assert_eq!(decode(&[0]), Ok(None::<u16>));
+        assert_eq!(decode(&[1, 5, 0]), Ok(Some(5u16)));
+        assert_eq!(decode(&[1, b'a', b'b']), Ok(Some("ab".to_string())));
+        assert_eq!(decode(&[1, 0]), Ok(Some(None::<bool>)));
+
+        assert_eq!(
+            decode::<Option<u8>>(&[2, 5]),
+            Err(FixedIntDecodeError::InvalidOptionTag(2))
+        );
+        assert_eq!(
+            decode::<Option<u8>>(&[0, 5]),
+            Err(FixedIntDecodeError::InvalidLength {
+                expected: 1,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            decode::<Option<u8>>(&[]),
+            Err(FixedIntDecodeError::InvalidLength {
+                expected: 1,
+                actual: 0
+            })
+        );
+    }
+
+    #[test]
+    fn tuples() {
+        assert_eq!(encode(&(1u8, true)), [1, 1]);
+        assert_eq!(
+            encode(&("ab".to_string(), 3u32)),
+            [2, 0, 0, 0, b'a', b'b', 3, 0, 0, 0]
+        );
+        assert_eq!(
+            encode(&(7i16, "ab".to_string(), vec![9u8])),
+            [7, 0, 2, 0, 0, 0, b'a', b'b', 9]
+        );
+        assert_eq!(
+            encode(&(Some(vec![9u8]), None::<u8>, false, Some("x".to_string()))),
+            [1, 1, 0, 0, 0, 9, 0, 0, 1, b'x']
+        );
+
+        let value = (
+            "owner".to_string(),
+            Some((u128::MAX, vec![1u8, 2, 3])),
+            -1i64,
+        );
+        assert_eq!(decode(&encode(&value)), Ok(value));
+    }
+
+    #[test]
+    fn strict_tuple_decoding() {
+        assert_eq!(
+            decode::<(u8, u8)>(&[1, 2, 3]),
+            Err(FixedIntDecodeError::InvalidLength {
+                expected: 1,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            decode::<(String, u8)>(&[5, 0, 0, 0, b'a']),
+            Err(FixedIntDecodeError::InvalidLength {
+                expected: 5,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            decode::<(Option<u8>, u8)>(&[3, 1]),
+            Err(FixedIntDecodeError::InvalidOptionTag(3))
+        );
+    }
+
+    #[test]
+    fn map_of_tuples() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<(String, u64), FixedIntEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access
+            .entry_mut("alice")
+            .set(&("admin".to_string(), 100))
+            .unwrap();
+
+        assert_eq!(
+            access.entry("alice").get(),
+            Ok(Some(("admin".to_string(), 100)))
+        );
+        assert_eq!(access.entry("bob").get(), Ok(None));
+    }
+
+    #[test]
+    fn reads_test_encoding_data() {
+        let mut storage = TestStorage::new();
+
+        Item::<u64, TestEncoding>::new(0)
+            .access(&mut storage)
+            .set(&1337)
+            .unwrap();
+
+        assert_eq!(
+            Item::<u64, FixedIntEncoding>::new(0).access(&storage).get(),
+            Ok(Some(1337))
+        );
+    }
+}
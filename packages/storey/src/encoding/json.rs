@@ -0,0 +1,222 @@
+use serde_json::{Map, Value};
+
+use super::{
+    BorrowDecodableWithImpl, Borrowable, Cover, DecodableWithImpl, EncodableWithImpl, Encoding,
+};
+
+/// The [JSON] encoding, for any type implementing [`serde`]'s traits.
+///
+/// JSON is bigger and slower than the binary formats, but values stay human-readable in raw
+/// state dumps and block explorers.
+///
+/// # Determinism
+///
+/// Encoding is deterministic: equal values always produce identical bytes. In particular,
+/// object keys are sorted, so a `HashMap` encodes the same way no matter its iteration order.
+/// Keys are compared as the strings they end up as in JSON, which means numeric map keys are
+/// sorted lexicographically (`"10"` before `"2"`).
+///
+/// # Large integers
+///
+/// Integers are encoded as JSON numbers. Values that don't fit in a [`u64`] or [`i64`] (e.g.
+/// a large [`u128`]) fail to encode. If you need the full 128-bit range, use a type that
+/// serializes to a string instead, such as `cosmwasm_std::Uint128`.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// use storey::containers::Item;
+/// use storey::encoding::JsonEncoding;
+/// use storey::storage::Storage as _;
+///
+/// #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+/// struct Config {
+///     admin: String,
+///     fee: Option<u32>,
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// let item = Item::<Config, JsonEncoding>::new(0);
+///
+/// let config = Config { admin: "alice".to_string(), fee: None };
+/// item.access(&mut storage).set(&config).unwrap();
+///
+/// assert_eq!(item.access(&storage).get().unwrap(), Some(config));
+/// assert_eq!(storage.get(&[0]).unwrap(), br#"{"admin":"alice","fee":null}"#);
+/// ```
+///
+/// [JSON]: https://www.json.org/
+pub struct JsonEncoding;
+
+impl Encoding for JsonEncoding {
+    type DecodeError = serde_json::Error;
+    type EncodeError = serde_json::Error;
+
+    const NAME: &'static str = "json";
+}
+
+impl<T> EncodableWithImpl<JsonEncoding> for Cover<&T>
+where
+    T: serde::Serialize,
+{
+    fn encode_impl(self) -> Result<Vec<u8>, serde_json::Error> {
+        let value = serde_json::to_value(self.0)?;
+        serde_json::to_vec(&canonicalize(value))
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), serde_json::Error> {
+        let value = serde_json::to_value(self.0)?;
+        serde_json::to_writer(buf, &canonicalize(value))
+    }
+}
+
+impl<T> DecodableWithImpl<JsonEncoding> for Cover<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn decode_impl(data: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(data).map(Cover)
+    }
+}
+
+impl<T> BorrowDecodableWithImpl<JsonEncoding> for Cover<T>
+where
+    T: Borrowable,
+    for<'a> T::Borrowed<'a>: serde::Deserialize<'a>,
+{
+    type Borrowed<'a> = T::Borrowed<'a>;
+
+    fn decode_borrowed_impl(data: &[u8]) -> Result<T::Borrowed<'_>, serde_json::Error> {
+        serde_json::from_slice(data)
+    }
+}
+
+/// Sorts object keys recursively.
+///
+/// `serde_json` keeps objects sorted by default, but not if its `preserve_order` feature is
+/// enabled somewhere in the dependency graph. This makes sure the output doesn't depend on that.
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut entries: Vec<_> = object.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, canonicalize(v)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(array) => Value::Array(array.into_iter().map(canonicalize).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::encoding::{DecodableWith, EncodableWith};
+
+    fn encode<T: EncodableWith<JsonEncoding>>(value: &T) -> Vec<u8> {
+        value.encode().unwrap()
+    }
+
+    fn decode<T: DecodableWith<JsonEncoding>>(data: &[u8]) -> T {
+        T::decode(data).unwrap()
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Position {
+        owner: String,
+        shares: HashMap<String, u64>,
+    }
+
+    #[test]
+    fn roundtrip() {
+        let position = Position {
+            owner: "alice".to_string(),
+            shares: HashMap::from([("atom".to_string(), 5), ("osmo".to_string(), 7)]),
+        };
+
+        assert_eq!(decode::<Position>(&encode(&position)), position);
+    }
+
+    #[test]
+    fn deterministic_map_order() {
+        let expected = br#"{"a":1,"b":2,"c":3,"d":4,"e":5,"f":6,"g":7,"h":8}"#;
+
+        // insert in different orders - `HashMap` iteration order differs between instances
+        for _ in 0..16 {
+            let map: HashMap<String, u32> = ["h", "a", "g", "b", "f", "c", "e", "d"]
+                .iter()
+                .map(|k| (k.to_string(), (k.as_bytes()[0] - b'a' + 1) as u32))
+                .collect();
+
+            assert_eq!(encode(&map), expected);
+        }
+    }
+
+    #[test]
+    fn nested_objects_are_sorted() {
+        let value: HashMap<&str, HashMap<&str, u8>> = HashMap::from([
+            ("z", HashMap::from([("y", 1), ("x", 2)])),
+            ("a", HashMap::new()),
+        ]);
+
+        assert_eq!(encode(&value), br#"{"a":{},"z":{"x":2,"y":1}}"#);
+    }
+
+    #[test]
+    fn non_string_map_keys() {
+        let map = HashMap::from([(10u32, "ten"), (2, "two"), (1, "one")]);
+
+        // keys become strings and are sorted as such
+        assert_eq!(encode(&map), br#"{"1":"one","10":"ten","2":"two"}"#);
+        assert_eq!(decode::<BTreeMap<u32, String>>(&encode(&map)).len(), 3);
+    }
+
+    #[test]
+    fn large_integers() {
+        let fits = u64::MAX as u128;
+        assert_eq!(encode(&fits), b"18446744073709551615");
+        assert_eq!(decode::<u128>(&encode(&fits)), fits);
+
+        let too_big = u64::MAX as u128 + 1;
+        assert!(EncodableWith::<JsonEncoding>::encode(&too_big).is_err());
+
+        assert_eq!(encode(&i64::MIN), b"-9223372036854775808");
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct PositionRef<'a> {
+        #[serde(borrow)]
+        owner: alloc::borrow::Cow<'a, str>,
+    }
+
+    impl Borrowable for Position {
+        type Borrowed<'a> = PositionRef<'a>;
+    }
+
+    #[test]
+    fn borrowed() {
+        use crate::encoding::BorrowDecodableWith;
+        use alloc::borrow::Cow;
+
+        let decode = <Position as BorrowDecodableWith<JsonEncoding>>::decode_borrowed;
+
+        let data = br#"{"owner":"alice","shares":{}}"#;
+        assert!(matches!(
+            decode(data).unwrap().owner,
+            Cow::Borrowed("alice")
+        ));
+
+        // strings with escapes can't be borrowed, so they're copied
+        let data = br#"{"owner":"al\"ice","shares":{}}"#;
+        assert!(matches!(decode(data).unwrap().owner, Cow::Owned(s) if s == "al\"ice"));
+    }
+}
@@ -0,0 +1,367 @@
+//! A set of traits for encoding and decoding data.
+//!
+//! # Overview
+//!
+//! The [`Encoding`] trait is for types that serve as "markers" for a particular encoding.
+//!
+//! The [`EncodableWith`] and [`DecodableWith`] traits are for types that can be encoded
+//! and decoded with a particular encoding, respectively.
+//!
+//! [`NonNullItem`]: crate::containers::NonNullItem
+//!
+//! # Implementing an encoding
+//!
+//! To implement an encoding, you need to provide a type that implements [`Encoding`].
+//! These types are generally zero-sized unit structs. Their error types have to implement
+//! [`std::error::Error`] (`core::error::Error` without the `std` feature) and be
+//! `Send + Sync + 'static`, so that users can propagate them with `?`.
+//!
+//! You must also provide blanket implementations for encodable/decodable types.
+//!
+//! The [`EncodableWith`] and [`DecodableWith`] traits are [sealed], so you can't implement
+//! them directly. Instead, what you want to do is implement [`EncodableWithImpl`] and
+//! [`DecodableWithImpl`], using [`Cover<&T>`] and [`Cover<T>`] to [cover] the type
+//! parameters.
+//!
+//! [cover]: https://doc.rust-lang.org/reference/glossary.html#uncovered-type
+//! [sealed]: https://rust-lang.github.io/api-guidelines/future-proofing.html#sealed-traits-protect-against-downstream-implementations-c-sealed
+//!
+//! ## Encoding example
+//!
+//! ```
+//! use storey::encoding::{EncodableWithImpl, Encoding, Cover};
+//!
+//! // - Implementation -
+//!
+//! struct DisplayEncoding;
+//!
+//! #[derive(Debug, PartialEq, thiserror::Error)]
+//! #[error("not a valid value")]
+//! struct InvalidValue;
+//!
+//! impl Encoding for DisplayEncoding {
+//!     type DecodeError = InvalidValue;
+//!     type EncodeError = core::convert::Infallible;
+//! }
+//!
+//! impl<T> EncodableWithImpl<DisplayEncoding> for Cover<&T,>
+//! where
+//!     T: core::fmt::Display,
+//! {
+//!     fn encode_impl(self) -> Result<Vec<u8>, core::convert::Infallible> {
+//!         Ok(format!("{}", self.0).into_bytes())
+//!     }
+//! }
+//!
+//! // - Usage -
+//!
+//! use storey::encoding::EncodableWith;
+//!
+//! // If there's only one encoding present for `u64`, we can use `encode` directly.
+//! // Otherwise (e.g. with one of the serde-based encodings of this crate enabled),
+//! // we need to disambiguate.
+//!
+//! assert_eq!(
+//!     EncodableWith::<DisplayEncoding>::encode(&12u64),
+//!     Ok("12".as_bytes().to_vec())
+//! );
+//! ```
+//!
+//! ## Decoding example
+//!
+//! ```
+//! use storey::encoding::{DecodableWithImpl, Encoding, Cover};
+//!
+//! // - Implementation -
+//!
+//! struct DisplayEncoding;
+//!
+//! #[derive(Debug, PartialEq, thiserror::Error)]
+//! #[error("not a valid value")]
+//! struct InvalidValue;
+//!
+//! impl Encoding for DisplayEncoding {
+//!    type DecodeError = InvalidValue;
+//!    type EncodeError = core::convert::Infallible;
+//! }
+//!
+//! impl<T> DecodableWithImpl<DisplayEncoding> for Cover<T>
+//! where
+//!     T: core::str::FromStr,
+//! {
+//!     fn decode_impl(data: &[u8]) -> Result<Self, InvalidValue> {
+//!         let string = String::from_utf8(data.to_vec()).map_err(|_| InvalidValue)?;
+//!         let value = string.parse().map_err(|_| InvalidValue)?;
+//!         Ok(Cover(value))
+//!     }
+//! }
+//!
+//! // - Usage -
+//!
+//! use storey::encoding::DecodableWith;
+//!
+//! // If there's only one encoding present for `u64`, we can use `decode` directly.
+//! // Otherwise (e.g. with one of the serde-based encodings of this crate enabled),
+//! // we need to disambiguate.
+//!
+//! assert_eq!(
+//!     <u64 as DecodableWith<DisplayEncoding>>::decode("12".as_bytes()),
+//!     Ok(12)
+//! );
+//! ```
+//!
+//! # Provided encodings
+//!
+//! Some encodings ship with this crate. Those that need third-party dependencies are behind a
+//! feature flag, so that you only pull in what you actually use:
+//!
+//! | Encoding                  | Feature    | Format                           |
+//! |---------------------------|------------|----------------------------------|
+//! | [`FixedIntEncoding`]      |            | primitives, options and tuples   |
+//! | [`RawEncoding`]           |            | the bytes themselves             |
+//! | [`BincodeEncoding`]       | `bincode`  | [bincode] (v2)                   |
+//! | [`CborEncoding`]          | `cbor`     | [CBOR] via `ciborium`            |
+//! | [`CanonicalCborEncoding`] | `cbor`     | deterministically encoded [CBOR] |
+//! | [`JsonEncoding`]          | `json`     | [JSON] via `serde_json`          |
+//! | [`MsgPackEncoding`]       | `msgpack`  | [MessagePack] via `rmp-serde`    |
+//! | [`PostcardEncoding`]      | `postcard` | [postcard]                       |
+//! | [`ProstEncoding`]         | `prost`    | [Protocol Buffers] via `prost`   |
+//!
+//! On top of that, a few adapters wrap any of them:
+//! - [`Versioned`] supports reading older representations of a value after its type changes,
+//! - [`Compressed`] (feature `lz4`) compresses large values with [LZ4],
+//! - [`Checksummed`] (feature `crc32`) detects corrupted values with a CRC32 checksum,
+//! - [`Encrypted`] (feature `encrypted`) encrypts values with a caller-provided cipher, for
+//!   off-chain use only.
+//!
+//! Any number of them can be enabled at the same time. Every container picks its own encoding,
+//! so a single program can read and write containers declared with different encodings.
+//!
+//! ## `serde` types
+//!
+//! The `serde`-based encodings (bincode, CBOR, JSON, MessagePack, postcard) come with blanket
+//! implementations of [`EncodableWithImpl`] and [`DecodableWithImpl`] for any type implementing
+//! `Serialize` and `DeserializeOwned`. A `serde` type works with all of them out of the box,
+//! without any code of its own:
+//!
+//! ```
+//! # #[cfg(all(feature = "json", feature = "msgpack"))]
+//! # fn main() {
+//! # use storey_testing::backend::TestStorage;
+//! use serde::{Deserialize, Serialize};
+//! use storey::containers::Item;
+//! use storey::encoding::{JsonEncoding, MsgPackEncoding};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Config {
+//!     admin: String,
+//! }
+//!
+//! let mut storage = TestStorage::new();
+//! let config = Config { admin: "alice".to_string() };
+//!
+//! Item::<Config, JsonEncoding>::new(0).access(&mut storage).set(&config).unwrap();
+//! Item::<Config, MsgPackEncoding>::new(1).access(&mut storage).set(&config).unwrap();
+//! # }
+//! # #[cfg(not(all(feature = "json", feature = "msgpack")))]
+//! # fn main() {}
+//! ```
+//!
+//! Each blanket implementation only covers its own encoding marker, so they don't get in the
+//! way of implementing other encodings (your own, or e.g. [`FixedIntEncoding`]) for the same
+//! types. What they rule out is a type-specific implementation for one of these encodings
+//! alongside the blanket one. To customize how a type is stored with them, customize its
+//! `Serialize` and `Deserialize` implementations instead.
+//!
+//! [bincode]: https://docs.rs/bincode
+//! [CBOR]: https://cbor.io/
+//! [JSON]: https://www.json.org/
+//! [LZ4]: https://lz4.org/
+//! [MessagePack]: https://msgpack.org/
+//! [postcard]: https://docs.rs/postcard
+//! [Protocol Buffers]: https://protobuf.dev/
+
+mod fixed_int;
+mod raw;
+mod versioned;
+
+#[cfg(feature = "bincode")]
+mod bincode;
+#[cfg(feature = "cbor")]
+mod cbor;
+#[cfg(feature = "crc32")]
+mod checksummed;
+#[cfg(feature = "lz4")]
+mod compressed;
+#[cfg(feature = "encrypted")]
+mod encrypted;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+#[cfg(feature = "postcard")]
+mod postcard;
+#[cfg(feature = "prost")]
+mod prost;
+
+pub use fixed_int::{FixedIntDecodeError, FixedIntEncoding};
+pub use raw::RawEncoding;
+pub use versioned::{decode_versioned, Versioned, VersionedDecodeError, VersionedValue};
+
+#[cfg(feature = "bincode")]
+pub use self::bincode::{BincodeDecodeError, BincodeEncoding, BINCODE_CONFIG};
+#[cfg(feature = "postcard")]
+pub use self::postcard::{PostcardDecodeError, PostcardEncoding};
+#[cfg(feature = "prost")]
+pub use self::prost::ProstEncoding;
+#[cfg(feature = "cbor")]
+pub use cbor::{CanonicalCborEncoding, CborEncoding};
+#[cfg(feature = "crc32")]
+pub use checksummed::{Checksummed, ChecksummedDecodeError};
+#[cfg(feature = "lz4")]
+pub use compressed::{Compressed, CompressedDecodeError};
+#[cfg(feature = "encrypted")]
+pub use encrypted::{Cipher, Encrypted, EncryptedDecodeError};
+#[cfg(feature = "json")]
+pub use json::JsonEncoding;
+#[cfg(feature = "msgpack")]
+pub use msgpack::MsgPackEncoding;
+
+/// A trait for types that serve as "markers" for a particular encoding.
+/// These types are expected to be empty structs.
+pub use storey_encoding::Encoding;
+
+/// A trait for types that can be encoded with a particular encoding.
+///
+/// # Implementing `EncodableWith`
+///
+/// The trait is [sealed], so you can't implement it directly. Instead of implementing
+/// [`EncodableWith`] for `T`, you should implement [`EncodableWithImpl`] for [`Cover<&T>`].
+///
+/// [See the module-level documentation for an example.](self)
+///
+/// [sealed]: https://rust-lang.github.io/api-guidelines/future-proofing.html#sealed-traits-protect-against-downstream-implementations-c-sealed
+pub use storey_encoding::EncodableWith;
+
+/// A trait for implementing [`EncodableWith`] for a particular encoding.
+///
+/// This trait exists to allow blanket implementations of [`EncodableWith`] for
+/// third-party types. To provide an implementation of [`EncodableWith`] for
+/// `MyEncoding` for a type `T`, you should implement [`EncodableWithImpl`] for
+/// [`Cover<&T>`]. The reason for this quirky complication are subtleties in Rust's orphan
+/// rules.
+///
+/// [See the module-level documentation for usage.](self)
+pub use storey_encoding::EncodableWithImpl;
+
+/// A trait for types that can be decoded with a particular encoding.
+///
+/// # Implementing `DecodableWith`
+///
+/// The trait is [sealed], so you can't implement it directly. Instead of implementing
+/// [`DecodableWith`] for `T`, you should implement [`DecodableWithImpl`] for [`Cover<T>`].
+///
+/// [See the module-level documentation for an example.](self)
+///
+/// [sealed]: https://rust-lang.github.io/api-guidelines/future-proofing.html#sealed-traits-protect-against-downstream-implementations-c-sealed
+pub use storey_encoding::DecodableWith;
+
+/// A trait for implementing [`DecodableWith`] for a particular encoding.
+///
+/// This trait exists to allow blanket implementations of [`DecodableWith`] for
+/// third-party types. To provide an implementation of [`DecodableWith`] for
+/// `MyEncoding` for a type `T`, you should implement [`DecodableWithImpl`] for
+/// [`Cover<T>`]. The reason for this quirky complication are subtleties in Rust's orphan
+/// rules.
+///
+/// [See the module-level documentation for usage.](self)
+pub use storey_encoding::DecodableWithImpl;
+
+/// A value type with a borrowed counterpart, for decoding without copying.
+///
+/// Many value types own their data (`String`, `Vec<T>`, structs with such fields), and
+/// decoding them means copying that data out of the raw bytes. When a value is read only to
+/// inspect part of it, that copy is wasted work. Implementing this trait declares a
+/// *borrowed* version of the type, which the encodings can decode into while borrowing from the
+/// raw bytes instead. For `serde`-based encodings, that's a type deserializing with
+/// `#[serde(borrow)]` fields.
+///
+/// Borrowed values can be read with [`ItemAccess::get_with`].
+///
+/// Implemented for [`String`] (as `&str`) and [`Vec<u8>`] (as `&[u8]`).
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "msgpack")]
+/// # fn main() {
+/// # use storey_testing::backend::TestStorage;
+/// use serde::{Deserialize, Serialize};
+/// use storey::containers::Item;
+/// use storey::encoding::{Borrowable, MsgPackEncoding};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Profile {
+///     name: String,
+///     bio: String,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct ProfileRef<'a> {
+///     name: &'a str,
+///     bio: &'a str,
+/// }
+///
+/// impl Borrowable for Profile {
+///     type Borrowed<'a> = ProfileRef<'a>;
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// let item = Item::<Profile, MsgPackEncoding>::new(0);
+///
+/// let profile = Profile { name: "alice".to_string(), bio: "likes trains".repeat(100) };
+/// item.access(&mut storage).set(&profile).unwrap();
+///
+/// // no copy of the bio is made
+/// let name_len = item.access(&storage).get_with(|p| p.name.len()).unwrap();
+/// assert_eq!(name_len, Some(5));
+/// # }
+/// # #[cfg(not(feature = "msgpack"))]
+/// # fn main() {}
+/// ```
+///
+/// [`ItemAccess::get_with`]: crate::containers::ItemAccess::get_with
+pub use storey_encoding::Borrowable;
+
+/// A trait for types that can be decoded with a particular encoding into their borrowed
+/// counterpart (see [`Borrowable`]).
+///
+/// # Implementing `BorrowDecodableWith`
+///
+/// The trait is [sealed], so you can't implement it directly. Instead of implementing
+/// [`BorrowDecodableWith`] for `T`, you should implement [`BorrowDecodableWithImpl`] for
+/// [`Cover<T>`].
+///
+/// [sealed]: https://rust-lang.github.io/api-guidelines/future-proofing.html#sealed-traits-protect-against-downstream-implementations-c-sealed
+pub use storey_encoding::BorrowDecodableWith;
+
+/// A trait for implementing [`BorrowDecodableWith`] for a particular encoding.
+///
+/// This works just like [`DecodableWithImpl`]. Implementations for `serde`-based encodings
+/// typically cover every `T` implementing [`Borrowable`], with `T::Borrowed` deserialized
+/// while borrowing from the input.
+pub use storey_encoding::BorrowDecodableWithImpl;
+
+/// A wrapper type used to [cover] type arguments when providing blanket implementations of
+/// [`EncodableWithImpl`] and [`DecodableWithImpl`].
+///
+/// Due to [orphan rules], it is impossible for downstream crates to provide a blanket
+/// implementation of [`EncodableWith`] or [`DecodableWith`] for a type parameter `T`.
+/// Instead, they should provide a blanket implementation of [`EncodableWithImpl`] or
+/// [`DecodableWithImpl`] for `Cover<&T>` or `Cover<T>`, respectively. This ensures that
+/// the `T` types are covered and orphan rules are not violated.
+///
+/// [See the module-level documentation for usage.](self)
+///
+/// [orphan rules]: https://doc.rust-lang.org/reference/items/implementations.html#orphan-rules
+/// [cover]: https://doc.rust-lang.org/reference/glossary.html#uncovered-type
+pub use storey_encoding::Cover;
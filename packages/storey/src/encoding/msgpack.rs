@@ -0,0 +1,217 @@
+use super::{
+    BorrowDecodableWithImpl, Borrowable, Cover, DecodableWithImpl, EncodableWithImpl, Encoding,
+};
+
+/// The [*MessagePack*] encoding, for any type implementing [`serde`]'s traits.
+///
+/// Structs are encoded as maps keyed by field name rather than as arrays. That costs a few
+/// bytes, but keeps stored values self-describing and lets fields be added or reordered
+/// without breaking data that's already in storage.
+///
+/// MessagePack has a single `nil` value, so nested options don't survive a round trip:
+/// `Some(None::<T>)` decodes as `None`.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// use storey::containers::Item;
+/// use storey::encoding::MsgPackEncoding;
+///
+/// #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+/// struct Config {
+///     admin: String,
+///     fee: Option<u32>,
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// let item = Item::<Config, MsgPackEncoding>::new(0);
+///
+/// let config = Config { admin: "alice".to_string(), fee: None };
+/// item.access(&mut storage).set(&config).unwrap();
+/// assert_eq!(item.access(&storage).get().unwrap(), Some(config));
+/// ```
+///
+/// [*MessagePack*]: https://msgpack.org/
+pub struct MsgPackEncoding;
+
+impl Encoding for MsgPackEncoding {
+    type DecodeError = rmp_serde::decode::Error;
+    type EncodeError = rmp_serde::encode::Error;
+
+    const NAME: &'static str = "msgpack";
+}
+
+impl<T> EncodableWithImpl<MsgPackEncoding> for Cover<&T>
+where
+    T: serde::Serialize,
+{
+    fn encode_impl(self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        rmp_serde::to_vec_named(self.0)
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), rmp_serde::encode::Error> {
+        rmp_serde::encode::write_named(buf, self.0)
+    }
+}
+
+impl<T> DecodableWithImpl<MsgPackEncoding> for Cover<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn decode_impl(data: &[u8]) -> Result<Self, rmp_serde::decode::Error> {
+        rmp_serde::from_slice(data).map(Cover)
+    }
+}
+
+impl<T> BorrowDecodableWithImpl<MsgPackEncoding> for Cover<T>
+where
+    T: Borrowable,
+    for<'a> T::Borrowed<'a>: serde::Deserialize<'a>,
+{
+    type Borrowed<'a> = T::Borrowed<'a>;
+
+    fn decode_borrowed_impl(data: &[u8]) -> Result<T::Borrowed<'_>, rmp_serde::decode::Error> {
+        rmp_serde::from_slice(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::Cow;
+    use alloc::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::encoding::{DecodableWith, EncodableWith};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Account {
+        owner: String,
+        balance: u128,
+        tags: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Status {
+        Active,
+        Frozen { reason: String },
+        Migrated(u64),
+    }
+
+    fn roundtrip<T>(value: T) -> T
+    where
+        T: EncodableWith<MsgPackEncoding> + DecodableWith<MsgPackEncoding>,
+    {
+        T::decode(&value.encode().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn structs() {
+        let account = Account {
+            owner: "alice".to_string(),
+            balance: u128::MAX,
+            tags: vec!["vip".to_string()],
+        };
+
+        let bytes = EncodableWith::<MsgPackEncoding>::encode(&account).unwrap();
+
+        // fields are keyed by name
+        assert_eq!(&bytes[..7], [0x83, 0xa5, b'o', b'w', b'n', b'e', b'r']);
+        assert_eq!(
+            <Account as DecodableWith<MsgPackEncoding>>::decode(&bytes).unwrap(),
+            account
+        );
+    }
+
+    #[test]
+    fn enums() {
+        for status in [
+            Status::Active,
+            Status::Frozen {
+                reason: "audit".to_string(),
+            },
+            Status::Migrated(7),
+        ] {
+            let bytes = EncodableWith::<MsgPackEncoding>::encode(&status).unwrap();
+            assert_eq!(
+                <Status as DecodableWith<MsgPackEncoding>>::decode(&bytes).unwrap(),
+                status
+            );
+        }
+    }
+
+    #[test]
+    fn maps() {
+        let map = BTreeMap::from([(1u32, "one".to_string()), (2, "two".to_string())]);
+        assert_eq!(roundtrip(map.clone()), map);
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(roundtrip(Some(42u64)), Some(42));
+        assert_eq!(roundtrip(None::<u64>), None);
+
+        // MessagePack has a single `nil`, so nested options collapse
+        assert_eq!(roundtrip(Some(None::<u64>)), None);
+    }
+
+    #[test]
+    fn invalid_data() {
+        let err = <Account as DecodableWith<MsgPackEncoding>>::decode(&[0xc1]).unwrap_err();
+        let _: &dyn std::error::Error = &err;
+    }
+
+    #[derive(Deserialize)]
+    struct AccountRef<'a> {
+        #[serde(borrow)]
+        owner: Cow<'a, str>,
+        tags: Vec<&'a str>,
+    }
+
+    impl Borrowable for Account {
+        type Borrowed<'a> = AccountRef<'a>;
+    }
+
+    #[test]
+    fn borrowed() {
+        use crate::containers::Item;
+        use crate::encoding::BorrowDecodableWith;
+        use storey_testing::backend::TestStorage;
+
+        let account = Account {
+            owner: "alice".to_string(),
+            balance: 5,
+            tags: vec!["vip".to_string(), "early".to_string()],
+        };
+        let bytes = EncodableWith::<MsgPackEncoding>::encode(&account).unwrap();
+
+        let borrowed =
+            <Account as BorrowDecodableWith<MsgPackEncoding>>::decode_borrowed(&bytes).unwrap();
+        assert_eq!(borrowed.owner, "alice");
+        assert_eq!(borrowed.tags, ["vip", "early"]);
+
+        // the strings point into the encoded bytes
+        let range = bytes.as_ptr_range();
+        assert!(matches!(borrowed.owner, Cow::Borrowed(s) if range.contains(&s.as_ptr())));
+        assert!(borrowed
+            .tags
+            .iter()
+            .all(|tag| range.contains(&tag.as_ptr())));
+
+        let mut storage = TestStorage::new();
+        let item = Item::<Account, MsgPackEncoding>::new(0);
+        assert_eq!(
+            item.access(&storage).get_with(|a| a.tags.len()).unwrap(),
+            None
+        );
+
+        item.access(&mut storage).set(&account).unwrap();
+        assert_eq!(
+            item.access(&storage)
+                .get_with(|a| a.owner.to_uppercase())
+                .unwrap(),
+            Some("ALICE".to_string())
+        );
+    }
+}
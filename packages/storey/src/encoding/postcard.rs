@@ -0,0 +1,164 @@
+use super::{
+    BorrowDecodableWithImpl, Borrowable, Cover, DecodableWithImpl, EncodableWithImpl, Encoding,
+};
+
+/// The [postcard] encoding, for any type implementing [`serde`]'s traits.
+///
+/// Postcard is a compact, non-self-describing binary format: integers are varint-encoded and
+/// struct field names are not stored. The implementation is small, which keeps contract
+/// binaries lean compared to the JSON or MessagePack encodings.
+///
+/// Like bincode, the stored bytes can only be read back into the exact same type. Decoding
+/// rejects trailing bytes after the value.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// use storey::containers::Item;
+/// use storey::encoding::PostcardEncoding;
+///
+/// #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+/// struct Counter {
+///     value: u64,
+///     owner: String,
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// let item = Item::<Counter, PostcardEncoding>::new(0);
+///
+/// let counter = Counter { value: 1, owner: "alice".to_string() };
+/// item.access(&mut storage).set(&counter).unwrap();
+/// assert_eq!(item.access(&storage).get().unwrap(), Some(counter));
+/// ```
+///
+/// [postcard]: https://docs.rs/postcard
+pub struct PostcardEncoding;
+
+impl Encoding for PostcardEncoding {
+    type DecodeError = PostcardDecodeError;
+    type EncodeError = postcard::Error;
+
+    const NAME: &'static str = "postcard";
+}
+
+/// An error decoding a value with [`PostcardEncoding`].
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum PostcardDecodeError {
+    #[error(transparent)]
+    Decode(#[from] postcard::Error),
+
+    #[error("{0} unexpected trailing bytes")]
+    TrailingBytes(usize),
+}
+
+impl<T> EncodableWithImpl<PostcardEncoding> for Cover<&T>
+where
+    T: serde::Serialize,
+{
+    fn encode_impl(self) -> Result<Vec<u8>, postcard::Error> {
+        postcard::to_allocvec(self.0)
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), postcard::Error> {
+        postcard::to_io(self.0, buf).map(|_| ())
+    }
+}
+
+impl<T> DecodableWithImpl<PostcardEncoding> for Cover<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn decode_impl(data: &[u8]) -> Result<Self, PostcardDecodeError> {
+        let (value, rest) = postcard::take_from_bytes(data)?;
+
+        if !rest.is_empty() {
+            return Err(PostcardDecodeError::TrailingBytes(rest.len()));
+        }
+
+        Ok(Cover(value))
+    }
+}
+
+impl<T> BorrowDecodableWithImpl<PostcardEncoding> for Cover<T>
+where
+    T: Borrowable,
+    for<'a> T::Borrowed<'a>: serde::Deserialize<'a>,
+{
+    type Borrowed<'a> = T::Borrowed<'a>;
+
+    fn decode_borrowed_impl(data: &[u8]) -> Result<T::Borrowed<'_>, PostcardDecodeError> {
+        let (value, rest) = postcard::take_from_bytes(data)?;
+
+        if !rest.is_empty() {
+            return Err(PostcardDecodeError::TrailingBytes(rest.len()));
+        }
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::encoding::{DecodableWith, EncodableWith};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Counter {
+        value: u64,
+        delta: i32,
+        owner: String,
+        last: Option<u8>,
+    }
+
+    fn encode<T: EncodableWith<PostcardEncoding>>(value: &T) -> Vec<u8> {
+        value.encode().unwrap()
+    }
+
+    #[test]
+    fn fixtures() {
+        // these bytes must never change
+        assert_eq!(encode(&300u64), [0xac, 0x02]);
+        assert_eq!(encode(&-2i32), [0x03]);
+        assert_eq!(encode(&"ab"), [2, b'a', b'b']);
+        assert_eq!(
+            encode(&Counter {
+                value: 1,
+                delta: -1,
+                owner: "x".to_string(),
+                last: Some(7),
+            }),
+            [1, 1, 1, b'x', 1, 7]
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let counter = Counter {
+            value: u64::MAX,
+            delta: i32::MIN,
+            owner: "alice".to_string(),
+            last: None,
+        };
+
+        assert_eq!(
+            <Counter as DecodableWith<PostcardEncoding>>::decode(&encode(&counter)),
+            Ok(counter)
+        );
+    }
+
+    #[test]
+    fn strict_decoding() {
+        assert_eq!(
+            <u8 as DecodableWith<PostcardEncoding>>::decode(&[1, 0]),
+            Err(PostcardDecodeError::TrailingBytes(1))
+        );
+        assert_eq!(
+            <String as DecodableWith<PostcardEncoding>>::decode(&[2, b'a']),
+            Err(PostcardDecodeError::Decode(
+                postcard::Error::DeserializeUnexpectedEnd
+            ))
+        );
+    }
+}
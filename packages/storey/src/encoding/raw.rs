@@ -0,0 +1,185 @@
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::str::Utf8Error;
+
+use super::{BorrowDecodableWithImpl, Cover, DecodableWithImpl, EncodableWithImpl, Encoding};
+
+/// An encoding that stores byte-like values as-is.
+///
+/// Sometimes a value already is a sequence of bytes (an opaque blob, a pre-serialized message,
+/// a string), and running it through a serialization format only adds overhead. With this
+/// encoding, the stored bytes are exactly the value's bytes.
+///
+/// Implemented for [`Vec<u8>`], [`[u8]`](slice) and [`String`], [`str`]. Strings are stored as
+/// UTF-8, and decoding them fails on invalid UTF-8 rather than converting lossily. Values can
+/// also be decoded without copying, as `&[u8]` and `&str` respectively (see
+/// [`ItemAccess::get_with`](crate::containers::ItemAccess::get_with)).
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// use storey::containers::Item;
+/// use storey::encoding::RawEncoding;
+/// use storey::storage::Storage as _;
+///
+/// let mut storage = TestStorage::new();
+/// let item = Item::<Vec<u8>, RawEncoding>::new(0);
+///
+/// item.access(&mut storage).set(&vec![1, 2, 3]).unwrap();
+/// assert_eq!(storage.get(&[0]), Some(vec![1, 2, 3]));
+/// ```
+pub struct RawEncoding;
+
+impl Encoding for RawEncoding {
+    type DecodeError = Utf8Error;
+    type EncodeError = Infallible;
+
+    const NAME: &'static str = "raw";
+}
+
+impl EncodableWithImpl<RawEncoding> for Cover<&[u8]> {
+    fn encode_impl(self) -> Result<Vec<u8>, Infallible> {
+        Ok(self.0.to_vec())
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), Infallible> {
+        buf.extend_from_slice(self.0);
+        Ok(())
+    }
+}
+
+impl EncodableWithImpl<RawEncoding> for Cover<&Vec<u8>> {
+    fn encode_impl(self) -> Result<Vec<u8>, Infallible> {
+        Ok(self.0.clone())
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), Infallible> {
+        buf.extend_from_slice(self.0);
+        Ok(())
+    }
+}
+
+impl EncodableWithImpl<RawEncoding> for Cover<&str> {
+    fn encode_impl(self) -> Result<Vec<u8>, Infallible> {
+        Ok(self.0.as_bytes().to_vec())
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), Infallible> {
+        buf.extend_from_slice(self.0.as_bytes());
+        Ok(())
+    }
+}
+
+impl EncodableWithImpl<RawEncoding> for Cover<&String> {
+    fn encode_impl(self) -> Result<Vec<u8>, Infallible> {
+        Ok(self.0.as_bytes().to_vec())
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), Infallible> {
+        buf.extend_from_slice(self.0.as_bytes());
+        Ok(())
+    }
+}
+
+impl DecodableWithImpl<RawEncoding> for Cover<Vec<u8>> {
+    fn decode_impl(data: &[u8]) -> Result<Self, Utf8Error> {
+        Ok(Cover(data.to_vec()))
+    }
+}
+
+impl DecodableWithImpl<RawEncoding> for Cover<String> {
+    fn decode_impl(data: &[u8]) -> Result<Self, Utf8Error> {
+        core::str::from_utf8(data).map(|s| Cover(s.to_string()))
+    }
+}
+
+impl BorrowDecodableWithImpl<RawEncoding> for Cover<Vec<u8>> {
+    type Borrowed<'a> = &'a [u8];
+
+    fn decode_borrowed_impl(data: &[u8]) -> Result<&[u8], Utf8Error> {
+        Ok(data)
+    }
+}
+
+impl BorrowDecodableWithImpl<RawEncoding> for Cover<String> {
+    type Borrowed<'a> = &'a str;
+
+    fn decode_borrowed_impl(data: &[u8]) -> Result<&str, Utf8Error> {
+        core::str::from_utf8(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::Item;
+    use crate::encoding::{DecodableWith, EncodableWith};
+
+    use storey_storage::{Storage as _, StorageMut as _};
+    use storey_testing::backend::TestStorage;
+
+    #[test]
+    fn stores_bytes_as_is() {
+        let mut storage = TestStorage::new();
+
+        let bytes = Item::<Vec<u8>, RawEncoding>::new(0);
+        let string = Item::<String, RawEncoding>::new(1);
+
+        bytes.access(&mut storage).set(&vec![0, 255]).unwrap();
+        string
+            .access(&mut storage)
+            .set(&"héllo".to_string())
+            .unwrap();
+
+        assert_eq!(storage.get(&[0]), Some(vec![0, 255]));
+        assert_eq!(storage.get(&[1]), Some("héllo".as_bytes().to_vec()));
+        assert_eq!(bytes.access(&storage).get(), Ok(Some(vec![0, 255])));
+        assert_eq!(string.access(&storage).get(), Ok(Some("héllo".to_string())));
+    }
+
+    #[test]
+    fn unsized_values() {
+        assert_eq!(
+            EncodableWith::<RawEncoding>::encode(&[1u8, 2][..]),
+            Ok(vec![1, 2])
+        );
+        assert_eq!(
+            EncodableWith::<RawEncoding>::encode("ab"),
+            Ok(vec![b'a', b'b'])
+        );
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        assert!(<String as DecodableWith<RawEncoding>>::decode(&[0x66, 0xff]).is_err());
+        assert_eq!(
+            <Vec<u8> as DecodableWith<RawEncoding>>::decode(&[0x66, 0xff]),
+            Ok(vec![0x66, 0xff])
+        );
+    }
+
+    #[test]
+    fn borrowed() {
+        let mut storage = TestStorage::new();
+
+        let bytes = Item::<Vec<u8>, RawEncoding>::new(0);
+        let string = Item::<String, RawEncoding>::new(1);
+
+        bytes.access(&mut storage).set(&vec![0, 255]).unwrap();
+        string
+            .access(&mut storage)
+            .set(&"héllo".to_string())
+            .unwrap();
+
+        assert_eq!(bytes.access(&storage).get_with(|b| b.len()), Ok(Some(2)));
+        assert_eq!(
+            string.access(&storage).get_with(|s| s.chars().count()),
+            Ok(Some(5))
+        );
+
+        storage.set(&[1], &[0x66, 0xff]);
+        assert!(string.access(&storage).get_with(|s| s.len()).is_err());
+    }
+}
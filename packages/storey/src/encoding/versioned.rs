@@ -0,0 +1,282 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use super::{Cover, DecodableWith, DecodableWithImpl, EncodableWith, EncodableWithImpl, Encoding};
+
+/// An encoding adapter that tags every value with a version, so that old representations
+/// can still be read after the value type evolves.
+///
+/// Values are stored as a single version byte followed by the payload produced by `E`. Writes
+/// always use the latest version ([`VersionedValue::VERSION`]). When reading, payloads of the
+/// latest version are decoded directly, and older ones are handed to
+/// [`VersionedValue::upgrade`], so that getters always return the current type no matter when
+/// the value was written.
+///
+/// See [`VersionedValue`] for how to register an upgrade chain.
+pub struct Versioned<E>(PhantomData<E>);
+
+impl<E: Encoding> Encoding for Versioned<E> {
+    type DecodeError = VersionedDecodeError<E::DecodeError>;
+    type EncodeError = E::EncodeError;
+
+    const NAME: &'static str = "versioned";
+}
+
+/// An error decoding a value with the [`Versioned`] encoding.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum VersionedDecodeError<D> {
+    #[error("empty value, expected a version byte")]
+    MissingVersion,
+
+    #[error("unsupported version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("decoding version {version}: {error}")]
+    Decode { version: u8, error: D },
+}
+
+/// A value type stored with the [`Versioned`] encoding.
+///
+/// The current representation is the type itself, stored with version [`VERSION`]. Previous
+/// representations are registered by implementing [`upgrade`], which decodes an old payload
+/// and converts it to the current type. Typically, each previous representation is a type of
+/// its own that implements `VersionedValue` too. Then [`decode_versioned`] decodes a payload
+/// as that type (recursively upgrading it as needed), and all that's left is a pure
+/// conversion function from one version to the next.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "msgpack")]
+/// # fn main() {
+/// # use storey_testing::backend::TestStorage;
+/// use serde::{Deserialize, Serialize};
+/// use storey::containers::{Item, Map};
+/// use storey::encoding::{
+///     decode_versioned, Encoding, MsgPackEncoding, Versioned, VersionedDecodeError,
+///     VersionedValue,
+/// };
+/// use storey::storage::StorageMut as _;
+///
+/// type Error = VersionedDecodeError<<MsgPackEncoding as Encoding>::DecodeError>;
+///
+/// // How accounts used to be stored.
+/// #[derive(Serialize, Deserialize)]
+/// struct AccountV1 {
+///     balance: u64,
+/// }
+///
+/// impl VersionedValue<MsgPackEncoding> for AccountV1 {
+///     const VERSION: u8 = 1;
+/// }
+///
+/// // How they're stored now.
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Account {
+///     balance: u64,
+///     frozen: bool,
+/// }
+///
+/// impl VersionedValue<MsgPackEncoding> for Account {
+///     const VERSION: u8 = 2;
+///
+///     fn upgrade(version: u8, payload: &[u8]) -> Result<Self, Error> {
+///         let v1: AccountV1 = decode_versioned(version, payload)?;
+///         Ok(Account { balance: v1.balance, frozen: false })
+///     }
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// let accounts = Map::<String, Item<Account, Versioned<MsgPackEncoding>>>::new(0);
+///
+/// // an account written back when it was version 1
+/// let v1 = rmp_serde::to_vec_named(&AccountV1 { balance: 7 }).unwrap();
+/// storage.set(&[0, 5, b'a', b'l', b'i', b'c', b'e'], &[&[1][..], &v1].concat());
+///
+/// let mut access = accounts.access(&mut storage);
+/// assert_eq!(
+///     access.entry("alice").get().unwrap(),
+///     Some(Account { balance: 7, frozen: false })
+/// );
+///
+/// // writes use the latest version
+/// access.entry_mut("bob").set(&Account { balance: 5, frozen: true }).unwrap();
+/// # }
+/// # #[cfg(not(feature = "msgpack"))]
+/// # fn main() {}
+/// ```
+///
+/// [`VERSION`]: VersionedValue::VERSION
+/// [`upgrade`]: VersionedValue::upgrade
+pub trait VersionedValue<E: Encoding>: EncodableWith<E> + DecodableWith<E> {
+    /// The version this type is stored as.
+    const VERSION: u8;
+
+    /// Decode a payload written with an older `version` and convert it to the current type.
+    ///
+    /// This is only called for versions other than [`VERSION`](VersionedValue::VERSION).
+    /// The default implementation doesn't know any previous versions.
+    fn upgrade(version: u8, payload: &[u8]) -> Result<Self, VersionedDecodeError<E::DecodeError>> {
+        let _ = payload;
+        Err(VersionedDecodeError::UnsupportedVersion(version))
+    }
+}
+
+/// Decode a payload written with `version` as `T`, upgrading it if it's an older version.
+///
+/// Versions newer than [`T::VERSION`](VersionedValue::VERSION) are rejected.
+pub fn decode_versioned<T, E>(
+    version: u8,
+    payload: &[u8],
+) -> Result<T, VersionedDecodeError<E::DecodeError>>
+where
+    T: VersionedValue<E>,
+    E: Encoding,
+{
+    use core::cmp::Ordering;
+
+    match version.cmp(&T::VERSION) {
+        Ordering::Equal => {
+            T::decode(payload).map_err(|error| VersionedDecodeError::Decode { version, error })
+        }
+        Ordering::Less => T::upgrade(version, payload),
+        Ordering::Greater => Err(VersionedDecodeError::UnsupportedVersion(version)),
+    }
+}
+
+impl<T, E> EncodableWithImpl<Versioned<E>> for Cover<&T>
+where
+    T: VersionedValue<E>,
+    E: Encoding,
+{
+    fn encode_impl(self) -> Result<Vec<u8>, E::EncodeError> {
+        let mut bytes = Vec::new();
+        EncodableWithImpl::<Versioned<E>>::encode_to_impl(self, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn encode_to_impl(self, buf: &mut Vec<u8>) -> Result<(), E::EncodeError> {
+        buf.push(T::VERSION);
+        self.0.encode_to(buf)
+    }
+}
+
+impl<T, E> DecodableWithImpl<Versioned<E>> for Cover<T>
+where
+    T: VersionedValue<E>,
+    E: Encoding,
+{
+    fn decode_impl(data: &[u8]) -> Result<Self, VersionedDecodeError<E::DecodeError>> {
+        let (version, payload) = data
+            .split_first()
+            .ok_or(VersionedDecodeError::MissingVersion)?;
+
+        decode_versioned(*version, payload).map(Cover)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::{Item, Map};
+    use crate::encoding::{FixedIntDecodeError, FixedIntEncoding};
+
+    use storey_storage::{Storage as _, StorageMut as _};
+    use storey_testing::backend::TestStorage;
+
+    type Error = VersionedDecodeError<FixedIntDecodeError>;
+
+    // v1: a signed 16-bit score
+    impl VersionedValue<FixedIntEncoding> for i16 {
+        const VERSION: u8 = 1;
+    }
+
+    // v2: scores can't be negative anymore, so negative ones were clamped to zero
+    impl VersionedValue<FixedIntEncoding> for u32 {
+        const VERSION: u8 = 2;
+
+        fn upgrade(version: u8, payload: &[u8]) -> Result<Self, Error> {
+            let v1: i16 = decode_versioned(version, payload)?;
+            Ok(v1.max(0) as u32)
+        }
+    }
+
+    // v3: scores are scaled by 100
+    impl VersionedValue<FixedIntEncoding> for u64 {
+        const VERSION: u8 = 3;
+
+        fn upgrade(version: u8, payload: &[u8]) -> Result<Self, Error> {
+            let v2: u32 = decode_versioned(version, payload)?;
+            Ok(v2 as u64 * 100)
+        }
+    }
+
+    #[test]
+    fn upgrade_chain() {
+        let mut storage = TestStorage::new();
+        let scores = Map::<String, Item<u64, Versioned<FixedIntEncoding>>>::new(0);
+
+        // written by hand, as older versions of the contract would have
+        storage.set(&[0, 1, b'a'], &[1, 0xff, 0xff]); // v1: -1
+        storage.set(&[0, 1, b'b'], &[1, 7, 0]); // v1: 7
+        storage.set(&[0, 1, b'c'], &[2, 9, 0, 0, 0]); // v2: 9
+
+        let mut access = scores.access(&mut storage);
+        assert_eq!(access.entry("a").get(), Ok(Some(0)));
+        assert_eq!(access.entry("b").get(), Ok(Some(700)));
+        assert_eq!(access.entry("c").get(), Ok(Some(900)));
+
+        access.entry_mut("d").set(&42).unwrap();
+        assert_eq!(access.entry("d").get(), Ok(Some(42)));
+        assert_eq!(
+            storage.get(&[0, 1, b'd']),
+            Some([&[3][..], &42u64.to_le_bytes()].concat())
+        );
+    }
+
+    #[test]
+    fn encode_to_appends() {
+        let mut buf = vec![9];
+        EncodableWith::<Versioned<FixedIntEncoding>>::encode_to(&7u32, &mut buf).unwrap();
+        assert_eq!(buf, [9, 2, 7, 0, 0, 0]);
+    }
+
+    #[test]
+    fn invalid_data() {
+        let mut storage = TestStorage::new();
+        let item = Item::<u64, Versioned<FixedIntEncoding>>::new(0);
+
+        storage.set(&[0], &[]);
+        assert_eq!(
+            item.access(&storage).get(),
+            Err(crate::Error::Decode(VersionedDecodeError::MissingVersion))
+        );
+
+        storage.set(&[0], &[4, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            item.access(&storage).get(),
+            Err(crate::Error::Decode(
+                VersionedDecodeError::UnsupportedVersion(4)
+            ))
+        );
+
+        storage.set(&[0], &[0, 0]);
+        assert_eq!(
+            item.access(&storage).get(),
+            Err(crate::Error::Decode(
+                VersionedDecodeError::UnsupportedVersion(0)
+            ))
+        );
+
+        storage.set(&[0], &[1, 0]);
+        assert_eq!(
+            item.access(&storage).get(),
+            Err(crate::Error::Decode(VersionedDecodeError::Decode {
+                version: 1,
+                error: FixedIntDecodeError::InvalidLength {
+                    expected: 2,
+                    actual: 1
+                }
+            }))
+        );
+    }
+}
@@ -0,0 +1,206 @@
+//! The error type of the container operations.
+
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use core::convert::Infallible;
+
+#[cfg(feature = "iteration")]
+use crate::containers::KVDecodeError;
+use crate::containers::KeyDecodeError;
+use crate::encoding::Encoding;
+
+/// A key decoding error of any container, as kept by [`Error::KeyDecode`].
+pub type BoxedKeyDecodeError = KeyDecodeError<Box<dyn crate::StdError + Send + Sync>>;
+
+/// The [`Error`] of the operations on containers with values of encoding `E`.
+pub type ErrorFor<E> = Error<<E as Encoding>::DecodeError, <E as Encoding>::EncodeError>;
+
+/// The error of the operations of the containers in this crate.
+///
+/// `D` and `E` are the decoding and encoding errors of the value encoding, which are kept as
+/// they are. Every operation on a container with values of encoding `Enc` returns an
+/// [`ErrorFor<Enc>`], i.e. an `Error<Enc::DecodeError, Enc::EncodeError>`, even if it can't
+/// fail all these ways, so errors of operations on containers with the same encoding can be
+/// propagated with `?` into the same type.
+///
+/// Errors of iteration convert into this type too.
+///
+/// With the `cosmwasm-std` feature, an `Error<StdError, StdError>`, which is what the
+/// encoding of `cw-storey` gives, converts into a [`StdError`]. The encoding errors are
+/// returned as they are.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::{TestDecodeError, TestEncoding};
+/// use core::convert::Infallible;
+///
+/// use storey::containers::{Column, Item, IterableAccessor as _};
+/// use storey::Error;
+///
+/// const TOTAL: Item<u64, TestEncoding> = Item::new(0);
+/// const HISTORY: Column<u64, TestEncoding> = Column::new(1);
+///
+/// type StoreError = Error<TestDecodeError, Infallible>;
+///
+/// fn record(storage: &mut TestStorage, amount: u64) -> Result<u64, StoreError> {
+///     HISTORY.access(&mut *storage).push(&amount)?;
+///     let total = TOTAL.access(&*storage).get()?.unwrap_or(0) + amount;
+///     TOTAL.access(&mut *storage).set(&total)?;
+///     Ok(total)
+/// }
+///
+/// fn recount(storage: &TestStorage) -> Result<u64, StoreError> {
+///     let mut total = 0;
+///     for pair in HISTORY.access(storage).pairs() {
+///         total += pair?.1;
+///     }
+///     Ok(total)
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// record(&mut storage, 5).unwrap();
+/// assert_eq!(record(&mut storage, 2), Ok(7));
+/// assert_eq!(recount(&storage), Ok(7));
+/// ```
+///
+/// [`StdError`]: https://docs.rs/cosmwasm-std/latest/cosmwasm_std/enum.StdError.html
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error<D = Infallible, E = Infallible> {
+    /// A value failed to decode.
+    #[error("failed to decode value: {0}")]
+    Decode(D),
+
+    /// A value failed to encode.
+    #[error("failed to encode value: {0}")]
+    Encode(E),
+
+    /// A key failed to decode, in iteration.
+    #[error("failed to decode key: {0}")]
+    KeyDecode(BoxedKeyDecodeError),
+
+    /// The entry to update doesn't exist.
+    #[error("not found")]
+    NotFound,
+
+    /// The container is full, e.g. a [`Column`](crate::containers::Column) with `u32::MAX`
+    /// entries.
+    #[error("index overflow")]
+    Overflow,
+
+    /// The metadata of the container doesn't match its contents.
+    #[error("inconsistent state")]
+    InconsistentState,
+}
+
+/// Key decoding errors are compared by their key and position, and the message of their
+/// source.
+impl<D: PartialEq, E: PartialEq> PartialEq for Error<D, E> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Decode(a), Self::Decode(b)) => a == b,
+            (Self::Encode(a), Self::Encode(b)) => a == b,
+            (Self::KeyDecode(a), Self::KeyDecode(b)) => {
+                a.key == b.key
+                    && a.offset == b.offset
+                    && a.component == b.component
+                    && a.source.to_string() == b.source.to_string()
+            }
+            (Self::NotFound, Self::NotFound)
+            | (Self::Overflow, Self::Overflow)
+            | (Self::InconsistentState, Self::InconsistentState) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<D: Eq, E: Eq> Eq for Error<D, E> {}
+
+impl<D, E, K> From<KeyDecodeError<K>> for Error<D, E>
+where
+    K: crate::StdError + Send + Sync + 'static,
+{
+    fn from(e: KeyDecodeError<K>) -> Self {
+        Self::KeyDecode(KeyDecodeError {
+            key: e.key,
+            offset: e.offset,
+            component: e.component,
+            source: Box::new(e.source),
+        })
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<D, E, K> From<KVDecodeError<KeyDecodeError<K>, D>> for Error<D, E>
+where
+    K: crate::StdError + Send + Sync + 'static,
+{
+    fn from(e: KVDecodeError<KeyDecodeError<K>, D>) -> Self {
+        match e {
+            KVDecodeError::Key(e) => e.into(),
+            KVDecodeError::Value(e) => Self::Decode(e),
+        }
+    }
+}
+
+#[cfg(feature = "cosmwasm-std")]
+impl From<Error<cosmwasm_std::StdError, cosmwasm_std::StdError>> for cosmwasm_std::StdError {
+    fn from(e: Error<cosmwasm_std::StdError, cosmwasm_std::StdError>) -> Self {
+        match e {
+            Error::Decode(e) | Error::Encode(e) => e,
+            e => cosmwasm_std::StdError::generic_err(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::{
+        Item, ItemKeyDecodeError, IterableAccessor as _, Map, MapKeyDecodeError,
+    };
+    use crate::storage::StorageMut as _;
+
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::{TestDecodeError, TestEncoding};
+
+    #[test]
+    fn iteration_errors() {
+        let mut storage = TestStorage::new();
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+
+        storage.set(&[0, 3, b'f', b'o', b'o'], &[1, 2]);
+        let err: Error<TestDecodeError> = map
+            .access(&storage)
+            .pairs()
+            .next()
+            .unwrap()
+            .unwrap_err()
+            .into();
+        assert_eq!(err, Error::Decode(TestDecodeError::InvalidLength(2)));
+
+        storage.set(&[0, 1, 0xff], &[0; 8]);
+        let err: Error<TestDecodeError> = map
+            .access(&storage)
+            .keys()
+            .next()
+            .unwrap()
+            .unwrap_err()
+            .into();
+        assert_eq!(
+            err.to_string(),
+            "failed to decode key: invalid key 01ff at byte 0 (component 0): invalid UTF8"
+        );
+        assert_eq!(
+            err,
+            Error::from(KeyDecodeError {
+                key: vec![1, 0xff],
+                offset: 0,
+                component: 0,
+                source: MapKeyDecodeError::<ItemKeyDecodeError>::InvalidUtf8,
+            })
+        );
+    }
+}
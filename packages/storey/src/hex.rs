@@ -0,0 +1,75 @@
+//! Bytes as lowercase hex strings, for error messages and, with the `serde` and `iteration`
+//! features, for use with `#[serde(with = "crate::hex")]` in dumps and snapshots.
+
+use alloc::string::String;
+#[cfg(all(feature = "serde", feature = "iteration"))]
+use alloc::vec::Vec;
+
+#[cfg(all(feature = "serde", feature = "iteration"))]
+use serde::de::{Error as _, Unexpected};
+#[cfg(all(feature = "serde", feature = "iteration"))]
+use serde::{Deserialize, Deserializer, Serializer};
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        out.push(DIGITS[(byte >> 4) as usize] as char);
+        out.push(DIGITS[(byte & 0xf) as usize] as char);
+    }
+    out
+}
+
+#[cfg(all(feature = "serde", feature = "iteration"))]
+pub(crate) fn decode(hex: &str) -> Option<Vec<u8>> {
+    fn digit(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?))
+        .collect()
+}
+
+#[cfg(all(feature = "serde", feature = "iteration"))]
+pub(crate) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&encode(bytes))
+}
+
+#[cfg(all(feature = "serde", feature = "iteration"))]
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    decode(&hex).ok_or_else(|| D::Error::invalid_value(Unexpected::Str(&hex), &"a hex string"))
+}
+
+#[cfg(all(test, feature = "serde", feature = "iteration"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let bytes = [0x00, 0x01, 0x7f, 0x80, 0xab, 0xff];
+        assert_eq!(encode(&bytes), "00017f80abff");
+        assert_eq!(decode("00017f80abff").unwrap(), bytes);
+        assert_eq!(decode("00017F80ABFF").unwrap(), bytes);
+        assert_eq!(decode("").unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(decode("0"), None);
+        assert_eq!(decode("0g"), None);
+        assert_eq!(decode("zz"), None);
+    }
+}
@@ -13,7 +13,160 @@
 //!
 //! Similarly, the storage backend is pluggable. The [`storage`] module provides traits
 //! for that.
+//!
+//! # `no_std`
+//! With the default `std` feature disabled, the crate is `no_std` and only needs `alloc`. The
+//! containers, keys, storage wrappers and the encodings that don't need a feature work the
+//! same, except for `storage::Shared`, which needs threads. The optional features need `std`
+//! and enable it. Without `std`, errors implement `core::error::Error`, which needs Rust 1.81.
+//!
+//! # Iteration
+//! Everything that iterates over storage is behind the default `iteration` feature: the
+//! [`IterableAccessor`](containers::IterableAccessor) methods, clearing maps, the iterable
+//! backend traits, and the modules built on them. Contracts that only get and set values can
+//! disable it, which leaves out that code and the backend implementations it needs.
+//!
+//! That only saves compile time: it doesn't make a contract's wasm binary any smaller, since
+//! code a contract never calls is dropped when it's linked anyway. What disabling the feature
+//! does guarantee is that nothing in the contract iterates over storage.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod accessor;
+pub mod audit;
 pub mod containers;
+#[cfg(feature = "iteration")]
+pub mod dump;
 pub mod encoding;
+pub mod error;
+mod hex;
+pub mod meta;
+pub mod migrate;
+pub mod prefix;
+pub mod root;
+#[cfg(feature = "schemars")]
+pub mod schema;
+#[cfg(feature = "iteration")]
+pub mod snapshot;
+#[cfg(feature = "iteration")]
+pub mod stats;
 pub mod storage;
+
+pub use error::Error;
+pub use storage::StorageBranch;
+
+// `std::error::Error` is a re-export of `core::error::Error` since Rust 1.81, which is only
+// required without `std`
+#[cfg(not(feature = "std"))]
+pub(crate) use core::error::Error as StdError;
+#[cfg(feature = "std")]
+pub(crate) use std::error::Error as StdError;
+
+// what the macros need from `alloc`, which the crates using them might not have in scope
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec::Vec;
+}
+
+/// Builds the raw key a chain of [`Map`] entries would produce.
+///
+/// Each component is a value implementing [`Key`]. Components are encoded and
+/// length-prefixed in order, exactly as nested [`MapAccess::entry`] calls would lay them out,
+/// so the result can be used wherever a raw key (or key suffix) is needed without
+/// duplicating the layout by hand. The top-level map's prefix is not included.
+///
+/// The result is a `Vec<u8>`, so the macro can't be used in const contexts. Like
+/// [`MapAccess::entry`], it panics if a component is longer than
+/// [`MAX_KEY_LEN`](crate::containers::MAX_KEY_LEN) bytes once encoded.
+///
+/// # Example
+/// ```
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey_testing::backend::TestStorage;
+/// use storey::containers::{Item, Map};
+/// use storey::key;
+/// use storey::storage::Storage as _;
+///
+/// let mut storage = TestStorage::new();
+/// let map = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+///
+/// map.access(&mut storage).entry_mut("foo").entry_mut(&7).set(&1337).unwrap();
+///
+/// assert_eq!(key!("foo", 7u32), [3, 102, 111, 111, 4, 0, 0, 0, 7]);
+///
+/// let raw = [&[0][..], &key!("foo", 7u32)].concat();
+/// assert!(storage.has(&raw));
+/// ```
+///
+/// [`Map`]: crate::containers::Map
+/// [`Key`]: crate::containers::Key
+/// [`MapAccess::entry`]: crate::containers::MapAccess::entry
+#[macro_export]
+macro_rules! key {
+    ($($component:expr),* $(,)?) => {{
+        #[allow(unused_imports)]
+        use $crate::containers::LengthPrefixed as _;
+
+        #[allow(unused_mut)]
+        let mut buf = $crate::__private::Vec::<u8>::new();
+        $(
+            ($component).__encode_length_prefixed(&mut buf);
+        )*
+        buf
+    }};
+}
+
+/// Declares top-level containers as `const`s, and checks at compile time that no two of them
+/// have the same prefix.
+///
+/// Every entry is the name of a container, its type, and its prefix. Attributes like doc
+/// comments and a visibility can go before the name. The types need a `const fn new(prefix)`,
+/// which [`Item`], [`Map`] and [`Column`] all have.
+///
+/// Declaring containers by hand works just as well; the macro only adds the check, which
+/// [`assert_unique_prefixes`] does on its own too.
+///
+/// # Example
+/// ```
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::{Column, Item, Map};
+///
+/// storey::declare_containers! {
+///     /// The contract's configuration.
+///     pub CONFIG: Item<u64, TestEncoding> = 0,
+///     BALANCES: Map<String, Item<u64, TestEncoding>> = 1,
+///     HISTORY: Column<u64, TestEncoding> = 2,
+/// }
+///
+/// assert_eq!(BALANCES.prefix(), 1);
+/// ```
+///
+/// Two containers with the same prefix don't compile:
+///
+/// ```compile_fail
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::{Item, Map};
+///
+/// storey::declare_containers! {
+///     CONFIG: Item<u64, TestEncoding> = 0,
+///     BALANCES: Map<String, Item<u64, TestEncoding>> = 0,
+/// }
+/// ```
+///
+/// [`Item`]: crate::containers::Item
+/// [`Map`]: crate::containers::Map
+/// [`Column`]: crate::containers::Column
+/// [`assert_unique_prefixes`]: crate::containers::assert_unique_prefixes
+#[macro_export]
+macro_rules! declare_containers {
+    ($($(#[$attr:meta])* $vis:vis $name:ident: $ty:ty = $prefix:expr),* $(,)?) => {
+        $(
+            $(#[$attr])*
+            $vis const $name: $ty = <$ty>::new($prefix);
+        )*
+
+        const _: () = $crate::containers::assert_unique_prefixes(&[$($name.prefix_bytes()),*]);
+    };
+}
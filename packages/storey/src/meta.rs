@@ -0,0 +1,176 @@
+//! Machine-readable descriptions of containers, as plain constants.
+//!
+//! A [`ContainerMeta`] describes what a container type puts in storage: its kind, the type and
+//! width of every map key, and the type and encoding of the values. [`StorageSchema`] lists
+//! the top-level containers of a contract with their prefixes. Both are made of arrays and
+//! structs, so they can be used in `const` contexts, and serialized with the `serde` feature,
+//! e.g. to compare the storage layouts of two versions of a contract.
+//!
+//! With the `derive` feature, [`StorageSchema`] can be derived for a struct with a field per
+//! top-level container, and [`container_meta!`] describes a single container type. Prefixes are
+//! assigned like the discriminants of an enum, the first field getting `0` and every other one
+//! the prefix after the one before it, unless given with `#[storey(prefix = N)]`. A name, as in
+//! `#[storey(prefix = "balances")]`, gives the prefix of `new_str("balances")`; the field after
+//! it then needs a prefix of its own. Overlapping prefixes don't compile, as with
+//! [`assert_unique_prefixes`](crate::containers::assert_unique_prefixes).
+//!
+//! Type names are taken from the source, as written. Since the derive only sees the tokens, the
+//! containers have to be written out as `Item<..>`, `Map<..>` or `Column<..>`, and not through
+//! aliases of their own.
+//!
+//! # Example
+//! ```
+//! # #[cfg(feature = "derive")]
+//! # {
+//! # use storey_testing::encoding::TestEncoding;
+//! use storey::containers::{Column, Item, Map};
+//! use storey::meta::{ContainerMeta, StorageSchema};
+//!
+//! #[derive(StorageSchema)]
+//! struct State {
+//!     config: Item<u64, TestEncoding>,
+//!     #[storey(prefix = 5)]
+//!     balances: Map<String, Item<u64, TestEncoding>>,
+//!     history: Column<u64, TestEncoding>,
+//!     #[storey(prefix = "claims")]
+//!     claims: Map<u32, Item<u64, TestEncoding>>,
+//! }
+//!
+//! let [config, balances, history, claims] = State::CONTAINERS else { unreachable!() };
+//! assert_eq!((config.name, config.prefix), ("config", &[0][..]));
+//! assert_eq!((balances.name, balances.prefix), ("balances", &[5][..]));
+//! assert_eq!((history.name, history.prefix), ("history", &[6][..]));
+//! assert_eq!((claims.name, claims.prefix), ("claims", &b"claims"[..]));
+//!
+//! let ContainerMeta::Map { key, value } = balances.container else { unreachable!() };
+//! assert_eq!(key.type_name, "String");
+//! assert_eq!(key.width, None);
+//! assert_eq!(value.value().unwrap().encoding, "test");
+//! # }
+//! ```
+
+use crate::containers::{Column, Item};
+use crate::encoding::Encoding;
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(feature = "derive")]
+pub use storey_derive::StorageSchema;
+
+/// Describes a container type as a [`ContainerMeta`] constant.
+///
+/// ```
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::{Item, Map};
+/// use storey::meta::{container_meta, ContainerMeta};
+///
+/// const META: ContainerMeta = container_meta!(Map<u32, Item<u64, TestEncoding>>);
+///
+/// let ContainerMeta::Map { key, .. } = META else { unreachable!() };
+/// assert_eq!(key.type_name, "u32");
+/// assert_eq!(key.width, Some(4));
+/// ```
+#[cfg(feature = "derive")]
+pub use storey_derive::container_meta;
+
+/// A struct listing the top-level containers of a contract, one per field.
+///
+/// This is meant to be derived. See the [module docs](self).
+pub trait StorageSchema {
+    /// The containers, in the order of the fields.
+    const CONTAINERS: &'static [ContainerEntry];
+}
+
+/// A top-level container in a [`StorageSchema`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ContainerEntry {
+    /// The name of the field.
+    pub name: &'static str,
+
+    /// The prefix of all the container's keys.
+    pub prefix: &'static [u8],
+
+    pub container: ContainerMeta,
+}
+
+/// The description of a container type, and of the containers nested in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(tag = "kind", rename_all = "snake_case")
+)]
+pub enum ContainerMeta {
+    /// A single value, stored under the container's own key.
+    Item { value: ValueMeta },
+
+    /// A list of values, keyed by their `u32` index.
+    Column { value: ValueMeta },
+
+    /// Entries keyed by `key`, each of them a container of its own.
+    Map {
+        key: KeyMeta,
+        value: &'static ContainerMeta,
+    },
+}
+
+impl ContainerMeta {
+    /// The values at the bottom of the container, after going through all the map keys.
+    pub fn value(&self) -> Option<&ValueMeta> {
+        match self {
+            Self::Item { value } | Self::Column { value } => Some(value),
+            Self::Map { value, .. } => value.value(),
+        }
+    }
+
+    /// The keys of the container, outermost first. A column's index isn't included.
+    pub fn keys(&self) -> Vec<&KeyMeta> {
+        match self {
+            Self::Item { .. } | Self::Column { .. } => Vec::new(),
+            Self::Map { key, value } => {
+                let mut keys = vec![key];
+                keys.extend(value.keys());
+                keys
+            }
+        }
+    }
+}
+
+/// A map key in a [`ContainerMeta`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KeyMeta {
+    /// The key type, as written in the source.
+    pub type_name: &'static str,
+
+    /// The length of every encoded key, if they all have the same length. See
+    /// [`OwnedKey::WIDTH`](crate::containers::OwnedKey::WIDTH).
+    pub width: Option<usize>,
+}
+
+/// The values of an [`Item`] or a [`Column`] in a [`ContainerMeta`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ValueMeta {
+    /// The value type, as written in the source.
+    pub type_name: &'static str,
+
+    /// The [name](Encoding::NAME) of the encoding.
+    pub encoding: &'static str,
+}
+
+/// A container storing values with an encoding. Used by the derive to find out the encoding of
+/// a container whose encoding isn't written out.
+pub trait ContainerEncoding {
+    /// The [name](Encoding::NAME) of the encoding.
+    const ENCODING: &'static str;
+}
+
+impl<T, E: Encoding> ContainerEncoding for Item<T, E> {
+    const ENCODING: &'static str = E::NAME;
+}
+
+impl<T, E: Encoding> ContainerEncoding for Column<T, E> {
+    const ENCODING: &'static str = E::NAME;
+}
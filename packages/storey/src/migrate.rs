@@ -0,0 +1,523 @@
+//! Helpers for migrating stored data between contract versions.
+//!
+//! [`migration!`](crate::migration) declares the steps taking the storage from one version to
+//! the next, and generates a driver running the ones needed, in order. [`reencode`] is a
+//! building block for such steps.
+
+use crate::containers::Item;
+#[cfg(feature = "iteration")]
+use crate::encoding::{DecodableWith, EncodableWith, Encoding};
+use crate::encoding::{FixedIntDecodeError, FixedIntEncoding};
+#[cfg(feature = "iteration")]
+use crate::storage::IterableStorage;
+use crate::storage::{Storage, StorageBranch, StorageMut};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Re-encode every value stored under `raw_prefix` from the encoding `EOld` to `ENew`.
+///
+/// This works on raw storage: every value whose key starts with `raw_prefix` is decoded as a
+/// `T` with `EOld`, encoded again with `ENew` and written back under the same key. Keys are
+/// left untouched, so no container type is needed - just the bytes the old container was stored
+/// under. That's handy when the container definition itself changed between contract versions.
+/// Metadata (like the length of a [`Column`](crate::containers::Column)) isn't touched either.
+///
+/// At most `limit` values are rewritten per call, so that large collections can be migrated
+/// over several transactions. If there's more to do, the returned cursor is the raw key of the
+/// last value that was rewritten, and passing it back as `cursor` picks up right after it.
+/// `None` means every value under the prefix has been re-encoded.
+///
+/// A batch is only written once all of its values were re-encoded successfully. If one of them
+/// fails to decode or encode, the error reports its raw key and nothing from that batch is
+/// written.
+///
+/// # Panics
+///
+/// Panics if `limit` is 0.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::{Item, Map};
+/// use storey::encoding::FixedIntEncoding;
+/// use storey::migrate::reencode;
+///
+/// let mut storage = TestStorage::new();
+///
+/// let old = Map::<String, Item<u64, TestEncoding>>::new(0);
+/// for (i, name) in ["alice", "bob", "carol"].into_iter().enumerate() {
+///     old.access(&mut storage).entry_mut(name).set(&(i as u64)).unwrap();
+/// }
+///
+/// let mut cursor = None;
+/// loop {
+///     cursor = reencode::<u64, TestEncoding, FixedIntEncoding, _>(
+///         &mut storage,
+///         &[0],
+///         2,
+///         cursor.as_deref(),
+///     )
+///     .unwrap();
+///
+///     if cursor.is_none() {
+///         break;
+///     }
+/// }
+///
+/// let new = Map::<String, Item<u64, FixedIntEncoding>>::new(0);
+/// assert_eq!(new.access(&storage).entry("carol").get().unwrap(), Some(2));
+/// ```
+#[cfg(feature = "iteration")]
+pub fn reencode<T, EOld, ENew, S>(
+    storage: &mut S,
+    raw_prefix: &[u8],
+    limit: usize,
+    cursor: Option<&[u8]>,
+) -> Result<Option<Cursor>, ReencodeError<EOld::DecodeError, ENew::EncodeError>>
+where
+    T: DecodableWith<EOld> + EncodableWith<ENew>,
+    EOld: Encoding,
+    ENew: Encoding,
+    S: IterableStorage + StorageMut,
+{
+    assert!(limit > 0, "limit must be at least 1");
+
+    // Resume right after the cursor. Appending a zero byte gives the smallest key greater
+    // than it.
+    let start = cursor.map(|cursor| {
+        let mut start = cursor
+            .strip_prefix(raw_prefix)
+            .expect("cursor is not under the given prefix")
+            .to_vec();
+        start.push(0);
+        start
+    });
+
+    let mut branch = StorageBranch::new(&mut *storage, raw_prefix.to_vec());
+    let mut pairs: Vec<_> = branch
+        .pairs(start.as_deref(), None)
+        .take(limit + 1)
+        .collect();
+    let done = pairs.len() <= limit;
+    pairs.truncate(limit);
+
+    let raw_key = |key: &[u8]| [raw_prefix, key].concat();
+
+    let mut batch = Vec::with_capacity(pairs.len());
+    for (key, value) in pairs {
+        let value = T::decode(&value).map_err(|source| ReencodeError::Decode {
+            key: raw_key(&key),
+            source,
+        })?;
+        let value = value.encode().map_err(|source| ReencodeError::Encode {
+            key: raw_key(&key),
+            source,
+        })?;
+
+        batch.push((key, value));
+    }
+
+    for (key, value) in &batch {
+        branch.set(key, value);
+    }
+
+    Ok(match (done, batch.last()) {
+        (false, Some((key, _))) => Some(raw_key(key)),
+        _ => None,
+    })
+}
+
+/// The raw key [`reencode`] stopped at.
+pub type Cursor = Vec<u8>;
+
+/// An error re-encoding a value with [`reencode`].
+#[cfg(feature = "iteration")]
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
+pub enum ReencodeError<D, E> {
+    #[error("failed to decode the value under raw key {key:?}: {source}")]
+    Decode { key: Vec<u8>, source: D },
+
+    #[error("failed to encode the value under raw key {key:?}: {source}")]
+    Encode { key: Vec<u8>, source: E },
+}
+
+/// Where [`migration!`](crate::migration) records the version of the storage.
+///
+/// The prefix `254` is reserved for it: contracts using [`migration!`](crate::migration) must
+/// not put containers of their own there. Storage without a recorded version is at version
+/// `0`.
+pub const STORAGE_VERSION: Item<u32, FixedIntEncoding> = Item::new(254);
+
+/// Where the cursor of a step that's in progress is kept, in the metadata of
+/// [`STORAGE_VERSION`].
+const CURSOR_META: &[u8] = b"cursor";
+
+/// Declares the steps migrating the storage from one version to the next, and generates a
+/// `run_migrations` function running them.
+///
+/// Every step is written `from -> to: function`, in order, each one starting at the version
+/// the one before it ends at. The functions take the storage and the cursor a previous call
+/// left off at (`None` when starting a step), and return a [`StepOutcome`]. A step that has
+/// too much to do in one go returns [`StepOutcome::InProgress`] with a cursor, and is
+/// called again with that cursor on the next call to `run_migrations`.
+///
+/// The generated items are
+/// - `pub fn run_migrations(storage, target: u32) -> Result<MigrationOutcome, MigrationError<E>>`,
+///   running the steps from the version recorded in [`STORAGE_VERSION`] up to `target`, and
+///   recording the new version after every step, and
+/// - `pub const LATEST_VERSION: u32`, the version the last step ends at, which a freshly
+///   instantiated contract should record with [`STORAGE_VERSION`].
+///
+/// Nothing is run unless all the steps between the current version and `target` are
+/// declared. Steps that don't chain up don't compile:
+///
+/// ```compile_fail
+/// # use storey_testing::backend::TestStorage;
+/// use storey::migrate::StepOutcome;
+///
+/// fn step(_: &mut TestStorage, _: Option<&[u8]>) -> Result<StepOutcome, ()> {
+///     Ok(StepOutcome::Done)
+/// }
+///
+/// storey::migration! {
+///     storage: TestStorage,
+///     error: (),
+///     steps: [
+///         1 -> 2: step,
+///         3 -> 4: step,
+///     ],
+/// }
+/// ```
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::{Item, Map};
+/// use storey::migrate::{MigrationOutcome, StepOutcome, STORAGE_VERSION};
+///
+/// const OWNER: Item<u64, TestEncoding> = Item::new(0);
+/// const ADMINS: Map<u64, Item<u64, TestEncoding>> = Map::new(1);
+///
+/// /// Version 2 keeps the owner among the admins.
+/// fn owner_is_admin(
+///     storage: &mut TestStorage,
+///     _cursor: Option<&[u8]>,
+/// ) -> Result<StepOutcome, ()> {
+///     if let Some(owner) = OWNER.access(&*storage).get().map_err(|_| ())? {
+///         ADMINS.access(storage).entry_mut(&owner).set(&1).unwrap();
+///     }
+///     Ok(StepOutcome::Done)
+/// }
+///
+/// storey::migration! {
+///     storage: TestStorage,
+///     error: (),
+///     steps: [
+///         1 -> 2: owner_is_admin,
+///     ],
+/// }
+///
+/// // a contract instantiated at version 1
+/// let mut storage = TestStorage::new();
+/// STORAGE_VERSION.access(&mut storage).set(&1).unwrap();
+/// OWNER.access(&mut storage).set(&7).unwrap();
+///
+/// assert_eq!(run_migrations(&mut storage, LATEST_VERSION), Ok(MigrationOutcome::UpToDate(2)));
+/// assert_eq!(ADMINS.access(&storage).entry(&7).get(), Ok(Some(1)));
+/// assert_eq!(STORAGE_VERSION.access(&storage).get(), Ok(Some(2)));
+///
+/// // running it again is a no-op
+/// assert_eq!(run_migrations(&mut storage, LATEST_VERSION), Ok(MigrationOutcome::UpToDate(2)));
+/// ```
+#[macro_export]
+macro_rules! migration {
+    (
+        storage: $storage:ty,
+        error: $error:ty,
+        steps: [$($from:literal -> $to:literal: $step:path),+ $(,)?] $(,)?
+    ) => {
+        /// The version the storage is at once all the migrations ran.
+        pub const LATEST_VERSION: u32 =
+            $crate::migrate::latest_version(&[$(($from, $to)),+]);
+
+        /// Runs the migrations needed to bring the storage to version `target`.
+        pub fn run_migrations(
+            storage: &mut $storage,
+            target: u32,
+        ) -> ::core::result::Result<
+            $crate::migrate::MigrationOutcome,
+            $crate::migrate::MigrationError<$error>,
+        > {
+            let steps: &[$crate::migrate::MigrationStep<$storage, $error>] = &[$(
+                $crate::migrate::MigrationStep { from: $from, to: $to, run: $step },
+            )+];
+            $crate::migrate::run_migrations(storage, steps, target)
+        }
+    };
+}
+
+/// A step declared with [`migration!`](crate::migration).
+#[doc(hidden)]
+pub struct MigrationStep<S, E> {
+    pub from: u32,
+    pub to: u32,
+    pub run: fn(&mut S, Option<&[u8]>) -> Result<StepOutcome, E>,
+}
+
+/// What a migration step did.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum StepOutcome {
+    /// The step is over.
+    Done,
+
+    /// The step has more to do, starting from this cursor.
+    InProgress(Cursor),
+}
+
+/// Where the storage stands after running `run_migrations`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MigrationOutcome {
+    /// The storage is at the target version.
+    UpToDate(u32),
+
+    /// The step from `from` to `to` is in progress, and the next call picks it up where it
+    /// stopped. The storage is still at version `from`.
+    InProgress { from: u32, to: u32 },
+}
+
+/// An error running migrations.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum MigrationError<E> {
+    #[error("the storage version can't be read or written: {0}")]
+    InvalidVersion(crate::Error<FixedIntDecodeError>),
+
+    #[error("the storage is at version {current}, past the target version {target}")]
+    Downgrade { current: u32, target: u32 },
+
+    #[error("no migration path from version {current} to version {target}")]
+    NoPath { current: u32, target: u32 },
+
+    #[error("migrating from version {from} to version {to} failed: {source}")]
+    Step { from: u32, to: u32, source: E },
+}
+
+/// The driver behind the `run_migrations` functions generated by
+/// [`migration!`](crate::migration).
+#[doc(hidden)]
+pub fn run_migrations<S, E>(
+    storage: &mut S,
+    steps: &[MigrationStep<S, E>],
+    target: u32,
+) -> Result<MigrationOutcome, MigrationError<E>>
+where
+    S: Storage + StorageMut,
+{
+    let current = STORAGE_VERSION
+        .access(&*storage)
+        .get()
+        .map_err(MigrationError::InvalidVersion)?
+        .unwrap_or(0);
+
+    if current > target {
+        return Err(MigrationError::Downgrade { current, target });
+    }
+
+    // find all the steps first, so that nothing runs if some are missing
+    let mut path = Vec::new();
+    let mut version = current;
+    while version < target {
+        match steps.iter().find(|step| step.from == version) {
+            Some(step) if step.to <= target => {
+                path.push(step);
+                version = step.to;
+            }
+            _ => return Err(MigrationError::NoPath { current, target }),
+        }
+    }
+
+    for step in path {
+        let cursor =
+            StorageBranch::new(&*storage, vec![STORAGE_VERSION.prefix()]).get_meta(CURSOR_META);
+
+        let outcome =
+            (step.run)(storage, cursor.as_deref()).map_err(|source| MigrationError::Step {
+                from: step.from,
+                to: step.to,
+                source,
+            })?;
+
+        let mut branch = StorageBranch::new(&mut *storage, vec![STORAGE_VERSION.prefix()]);
+        match outcome {
+            StepOutcome::Done => branch.remove_meta(CURSOR_META),
+            StepOutcome::InProgress(cursor) => {
+                branch.set_meta(CURSOR_META, &cursor);
+                return Ok(MigrationOutcome::InProgress {
+                    from: step.from,
+                    to: step.to,
+                });
+            }
+        }
+
+        STORAGE_VERSION
+            .access(&mut *storage)
+            .set(&step.to)
+            .map_err(MigrationError::InvalidVersion)?;
+    }
+
+    Ok(MigrationOutcome::UpToDate(target))
+}
+
+/// The version the last of the `steps` ends at.
+///
+/// # Panics
+/// If a step doesn't go to a later version, or doesn't start where the one before it ends.
+#[doc(hidden)]
+pub const fn latest_version(steps: &[(u32, u32)]) -> u32 {
+    let mut i = 0;
+    while i < steps.len() {
+        if steps[i].1 <= steps[i].0 {
+            panic!("a migration step doesn't go to a later version");
+        }
+        if i > 0 && steps[i].0 != steps[i - 1].1 {
+            panic!("a migration step doesn't start where the one before it ends");
+        }
+        i += 1;
+    }
+    steps[steps.len() - 1].1
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use super::*;
+    use crate::containers::{Column, Item, Map};
+    use crate::encoding::{Cover, DecodableWithImpl, EncodableWithImpl, FixedIntDecodeError};
+
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::{TestDecodeError, TestEncoding};
+
+    /// Big-endian `u64`s, so that re-encoding from `TestEncoding` actually changes the bytes.
+    struct BigEndian;
+
+    impl Encoding for BigEndian {
+        type DecodeError = FixedIntDecodeError;
+        type EncodeError = Infallible;
+    }
+
+    impl EncodableWithImpl<BigEndian> for Cover<&u64> {
+        fn encode_impl(self) -> Result<Vec<u8>, Infallible> {
+            Ok(self.0.to_be_bytes().to_vec())
+        }
+    }
+
+    impl DecodableWithImpl<BigEndian> for Cover<u64> {
+        fn decode_impl(data: &[u8]) -> Result<Self, FixedIntDecodeError> {
+            let bytes = data
+                .try_into()
+                .map_err(|_| FixedIntDecodeError::InvalidLength {
+                    expected: 8,
+                    actual: data.len(),
+                })?;
+            Ok(Cover(u64::from_be_bytes(bytes)))
+        }
+    }
+
+    fn populate(storage: &mut TestStorage, n: u64) {
+        let old = Map::<u32, Item<u64, TestEncoding>>::new(0);
+        let mut access = old.access(&mut *storage);
+        for i in 0..n {
+            access.entry_mut(&(i as u32)).set(&(i * 10)).unwrap();
+        }
+
+        // something under another prefix, which must be left alone
+        Item::<u64, TestEncoding>::new(1)
+            .access(storage)
+            .set(&7)
+            .unwrap();
+    }
+
+    #[test]
+    fn in_batches() {
+        let mut storage = TestStorage::new();
+        populate(&mut storage, 5);
+
+        let cursor =
+            reencode::<u64, TestEncoding, BigEndian, _>(&mut storage, &[0], 2, None).unwrap();
+        assert_eq!(cursor, Some([&[0][..], &crate::key!(&1u32)].concat()));
+
+        let cursor =
+            reencode::<u64, TestEncoding, BigEndian, _>(&mut storage, &[0], 2, cursor.as_deref())
+                .unwrap();
+        assert!(cursor.is_some());
+
+        let cursor =
+            reencode::<u64, TestEncoding, BigEndian, _>(&mut storage, &[0], 2, cursor.as_deref())
+                .unwrap();
+        assert_eq!(cursor, None);
+
+        let new = Map::<u32, Item<u64, BigEndian>>::new(0);
+        let access = new.access(&storage);
+        for i in 0..5u32 {
+            assert_eq!(access.entry(&i).get(), Ok(Some(i as u64 * 10)));
+        }
+
+        assert_eq!(storage.get(&[1]), Some(7u64.to_le_bytes().to_vec()));
+    }
+
+    #[test]
+    fn exact_batch_is_done() {
+        let mut storage = TestStorage::new();
+        populate(&mut storage, 2);
+
+        assert_eq!(
+            reencode::<u64, TestEncoding, BigEndian, _>(&mut storage, &[0], 2, None),
+            Ok(None)
+        );
+        assert_eq!(
+            reencode::<u64, TestEncoding, BigEndian, _>(&mut storage, &[2], 2, None),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn keeps_metadata() {
+        let mut storage = TestStorage::new();
+
+        let old = Column::<u64, TestEncoding>::new(0);
+        old.access(&mut storage).push(&1).unwrap();
+        old.access(&mut storage).push(&2).unwrap();
+
+        reencode::<u64, TestEncoding, BigEndian, _>(&mut storage, &[0], 10, None).unwrap();
+
+        let new = Column::<u64, BigEndian>::new(0);
+        let mut access = new.access(&mut storage);
+        assert_eq!(access.len(), Ok(2));
+        access.push(&3).unwrap();
+        assert_eq!(access.get(2), Ok(Some(3)));
+        assert_eq!(access.get(0), Ok(Some(1)));
+    }
+
+    #[test]
+    fn failing_value() {
+        let mut storage = TestStorage::new();
+        populate(&mut storage, 3);
+
+        let corrupted = [&[0][..], &crate::key!(&1u32)].concat();
+        storage.set(&corrupted, &[1, 2, 3]);
+
+        assert_eq!(
+            reencode::<u64, TestEncoding, BigEndian, _>(&mut storage, &[0], 10, None),
+            Err(ReencodeError::Decode {
+                key: corrupted,
+                source: TestDecodeError::InvalidLength(3),
+            })
+        );
+
+        // nothing from the batch was written
+        let old = Map::<u32, Item<u64, TestEncoding>>::new(0);
+        assert_eq!(old.access(&storage).entry(&0).get(), Ok(Some(0)));
+    }
+}
@@ -0,0 +1,220 @@
+//! Readable prefixes for top-level containers, with [`prefix!`].
+//!
+//! Single-byte prefixes like `Map::new(3)` are compact, but they say nothing about what's
+//! stored under them, and two modules picking the same number is easy. [`prefix!`] builds
+//! longer prefixes from names instead, for the `new_with_prefix` constructors of [`Item`],
+//! [`Map`] and [`Column`]:
+//!
+//! - `prefix!("balances")` is the bytes of the name, like the namespaces of
+//!   `cw-storage-plus`,
+//! - `prefix!(hash "balances")` is a 4-byte hash of the name, so that all the prefixes have
+//!   the same width, whatever the names.
+//!
+//! Both are evaluated at compile time, so the name has to be a literal or a constant. For
+//! plain names, the `new_str` constructors take the name directly: `Map::new_str("balances")`
+//! is `Map::new_with_prefix(prefix!("balances"))`.
+//!
+//! Whichever constructor a container is made with, its keys are the prefix followed by the
+//! container's own keys: `Map::new(3)` and `Map::new_with_prefix(&[3])` are the same map.
+//!
+//! # Mixing styles
+//! A prefix only keeps containers apart if it isn't a prefix of another one. Names in the
+//! same style are safe from that as long as the names differ and, for plain names, no name
+//! starts with another one (`"balance"` and `"balances"` collide). Across styles, a
+//! single-byte prefix collides with every longer prefix starting with that byte:
+//! `Map::new(b'b')` and `Map::new_with_prefix(prefix!("balances"))` share their keys, and so can
+//! `Map::new(211)` and `Map::new_with_prefix(prefix!(hash "balances"))`. Better to stick to one
+//! style per contract; either way, [`assert_unique_prefixes`] rejects such collisions at
+//! compile time, and the [`audit`](crate::audit) registry reports them at runtime.
+//!
+//! # Hashed prefixes
+//! The hash is the 32-bit [FNV-1a] hash of the UTF-8 bytes of the name, in big-endian order.
+//! Since the prefix decides where the data lives, this is part of the storage layout: a name
+//! hashes to the same bytes in every version of this crate. Names hashing to a prefix starting
+//! with `255`, the byte reserved for metadata, are rejected at compile time.
+//!
+//! # Example
+//! ```
+//! # use storey_testing::backend::TestStorage;
+//! # use storey_testing::encoding::TestEncoding;
+//! use storey::containers::{Item, Map};
+//! use storey::prefix;
+//! use storey::storage::Storage as _;
+//!
+//! const CONFIG: Item<u64, TestEncoding> = Item::new_with_prefix(prefix!("config"));
+//! const BALANCES: Map<String, Item<u64, TestEncoding>> =
+//!     Map::new_with_prefix(prefix!(hash "balances"));
+//!
+//! let mut storage = TestStorage::new();
+//! CONFIG.access(&mut storage).set(&1).unwrap();
+//! BALANCES.access(&mut storage).entry_mut("alice").set(&5).unwrap();
+//!
+//! assert_eq!(storage.get(b"config"), Some(1u64.to_le_bytes().to_vec()));
+//! assert_eq!(
+//!     storage.get(&[211, 84, 197, 72, 5, b'a', b'l', b'i', b'c', b'e']),
+//!     Some(5u64.to_le_bytes().to_vec())
+//! );
+//! ```
+//!
+//! [`Item`]: crate::containers::Item
+//! [`Map`]: crate::containers::Map
+//! [`Column`]: crate::containers::Column
+//! [`assert_unique_prefixes`]: crate::containers::assert_unique_prefixes
+//! [FNV-1a]: http://www.isthe.com/chongo/tech/comp/fnv/index.html
+//! [`prefix!`]: crate::prefix!
+
+/// Builds a container prefix from a name, at compile time. See the
+/// [module docs](mod@crate::prefix).
+///
+/// `prefix!("name")` gives the bytes of the name, and `prefix!(hash "name")` a 4-byte hash of
+/// it, both as a `&'static [u8]`.
+///
+/// An empty name doesn't compile, and neither does a name hashing to a reserved prefix:
+///
+/// ```compile_fail
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::Item;
+///
+/// // hashes to [255, 100, 88, 202]
+/// const ITEM: Item<u64, TestEncoding> = Item::new_with_prefix(storey::prefix!(hash "item167"));
+/// ```
+#[macro_export]
+macro_rules! prefix {
+    (hash $name:expr) => {{
+        const PREFIX: &[u8] = &$crate::prefix::hashed($name);
+        PREFIX
+    }};
+    ($name:expr) => {{
+        const PREFIX: &[u8] = $crate::prefix::named($name);
+        PREFIX
+    }};
+}
+
+#[doc(hidden)]
+pub const fn named(name: &'static str) -> &'static [u8] {
+    if name.is_empty() {
+        panic!("a prefix name can't be empty");
+    }
+    // UTF-8 never contains the reserved byte 255
+    name.as_bytes()
+}
+
+#[doc(hidden)]
+pub const fn hashed(name: &str) -> [u8; 4] {
+    const OFFSET_BASIS: u32 = 0x811c9dc5;
+    const PRIME: u32 = 0x01000193;
+
+    if name.is_empty() {
+        panic!("a prefix name can't be empty");
+    }
+
+    let bytes = name.as_bytes();
+    let mut hash = OFFSET_BASIS;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u32;
+        hash = hash.wrapping_mul(PRIME);
+        i += 1;
+    }
+
+    let prefix = hash.to_be_bytes();
+    if prefix[0] == 255 {
+        panic!("the name hashes to a prefix starting with the reserved byte 255");
+    }
+    prefix
+}
+
+/// The prefix of a top-level container: a byte given to `new`, or the bytes given to
+/// `new_with_prefix`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Prefix {
+    Byte(u8),
+    Bytes(&'static [u8]),
+}
+
+impl Prefix {
+    /// A prefix given to `new_with_prefix`, checked like [`StorageBranch::try_new`] does.
+    ///
+    /// A one-byte prefix is the same as the one `new` makes.
+    ///
+    /// [`StorageBranch::try_new`]: crate::storage::StorageBranch::try_new
+    pub(crate) const fn bytes(prefix: &'static [u8]) -> Self {
+        match prefix {
+            [] => panic!("a container prefix can't be empty"),
+            [255, ..] => panic!("a container prefix can't start with the reserved byte 255"),
+            [byte] => Self::Byte(*byte),
+            _ => Self::Bytes(prefix),
+        }
+    }
+
+    /// The prefix, if it's a single byte.
+    ///
+    /// # Panics
+    /// If the prefix is longer.
+    pub(crate) const fn byte(&self) -> u8 {
+        match self {
+            Self::Byte(byte) => *byte,
+            Self::Bytes(_) => panic!("the container has a multi-byte prefix"),
+        }
+    }
+
+    pub(crate) const fn as_slice(&self) -> &[u8] {
+        match self {
+            Self::Byte(byte) => core::slice::from_ref(byte),
+            Self::Bytes(bytes) => bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_prefixes() {
+        assert_eq!(prefix!("balances"), b"balances");
+        assert_eq!(prefix!("a"), b"a");
+        assert_eq!(prefix!("ünï"), "ünï".as_bytes());
+
+        const NAME: &str = "config";
+        assert_eq!(prefix!(NAME), b"config");
+    }
+
+    #[test]
+    fn hashed_prefixes() {
+        // the reference test vectors of 32-bit FNV-1a
+        assert_eq!(prefix!(hash "a"), [0xe4, 0x0c, 0x29, 0x2c]);
+        assert_eq!(prefix!(hash "foobar"), [0xbf, 0x9c, 0xf9, 0x68]);
+
+        assert_eq!(prefix!(hash "balances"), [211, 84, 197, 72]);
+        assert_eq!(prefix!(hash "config"), [35, 255, 102, 199]);
+        assert_eq!(prefix!(hash "owner"), [245, 103, 76, 212]);
+        assert_eq!(prefix!(hash "total_supply"), [96, 235, 14, 61]);
+        assert_eq!(prefix!(hash "allowances"), [237, 36, 128, 146]);
+    }
+
+    #[test]
+    #[should_panic(expected = "reserved byte 255")]
+    fn reserved_hash() {
+        hashed("item167");
+    }
+
+    #[test]
+    fn prefix_bytes() {
+        assert_eq!(Prefix::Byte(3).as_slice(), [3]);
+        assert_eq!(Prefix::bytes(b"foo").as_slice(), b"foo");
+        assert_eq!(Prefix::Byte(3).byte(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "multi-byte prefix")]
+    fn byte_of_multi_byte_prefix() {
+        Prefix::bytes(b"foo").byte();
+    }
+
+    #[test]
+    #[should_panic(expected = "reserved byte 255")]
+    fn reserved_prefix() {
+        Prefix::bytes(&[255, 0]);
+    }
+}
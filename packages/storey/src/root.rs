@@ -0,0 +1,129 @@
+//! Declaring a contract's top-level containers in one place, with [`root!`].
+//!
+//! [`root!`] declares every container as a `const`, and assigns the prefixes that aren't given
+//! explicitly: the first container gets `0`, and every other one the prefix after the one
+//! before it, like the discriminants of a Rust enum. Prefixes only depend on the order of the
+//! declarations, so they're the same in every build. Since they're part of the stored state,
+//! new containers should go at the end, or get an explicit prefix.
+//!
+//! Next to the containers, [`root!`] declares a `LAYOUT` constant: a [`RootLayout`] listing the
+//! containers with their prefixes, for tools that need to know where things are stored. Two
+//! containers with overlapping prefixes are a compile error, as with
+//! [`declare_containers!`](crate::declare_containers).
+//!
+//! # Example
+//! ```
+//! mod state {
+//!     # use storey_testing::encoding::TestEncoding;
+//!     use storey::containers::{Column, Item, Map};
+//!
+//!     storey::root! {
+//!         /// The contract's configuration.
+//!         pub CONFIG: Item<u64, TestEncoding>,
+//!         pub BALANCES: Map<String, Item<u64, TestEncoding>>,
+//!         // a container that was removed used to live under 2
+//!         pub HISTORY: Column<u64, TestEncoding> = 3,
+//!         pub CLAIMS: Map<u32, Item<u64, TestEncoding>>,
+//!     }
+//! }
+//!
+//! assert_eq!(state::CONFIG.prefix(), 0);
+//! assert_eq!(state::BALANCES.prefix(), 1);
+//! assert_eq!(state::HISTORY.prefix(), 3);
+//! assert_eq!(state::CLAIMS.prefix(), 4);
+//!
+//! let claims = &state::LAYOUT.containers[3];
+//! assert_eq!(claims.name, "CLAIMS");
+//! assert_eq!(claims.prefix, [4]);
+//! assert_eq!(state::LAYOUT.by_prefix(&[3]).unwrap().name, "HISTORY");
+//! ```
+//!
+//! [`root!`]: crate::root!
+
+/// The top-level containers declared with [`root!`](crate::root!), in declaration order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RootLayout {
+    pub containers: &'static [RootEntry],
+}
+
+impl RootLayout {
+    /// The container with the given `prefix`, if there's one.
+    pub fn by_prefix(&self, prefix: &[u8]) -> Option<&RootEntry> {
+        self.containers.iter().find(|entry| entry.prefix == prefix)
+    }
+}
+
+/// A top-level container in a [`RootLayout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RootEntry {
+    /// The name of the container's `const`.
+    pub name: &'static str,
+
+    /// The prefix of all the container's keys.
+    pub prefix: &'static [u8],
+
+    /// The type of the container, as written in the declaration.
+    pub container: &'static str,
+}
+
+/// Declares top-level containers as `const`s, assigning the prefixes that aren't given, and a
+/// `LAYOUT` constant listing them. See the [module docs](mod@crate::root).
+///
+/// Every entry is the name of a container and its type, optionally followed by `= prefix`.
+/// Attributes like doc comments and a visibility can go before the name. The types need a
+/// `const fn new(prefix)`, which [`Item`], [`Map`] and [`Column`] all have.
+///
+/// A prefix past `255` doesn't compile, and neither do two containers with the same prefix:
+///
+/// ```compile_fail
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::Item;
+///
+/// storey::root! {
+///     CONFIG: Item<u64, TestEncoding> = 1,
+///     OWNER: Item<u64, TestEncoding> = 0,
+///     // gets 1, like `CONFIG`
+///     ADMIN: Item<u64, TestEncoding>,
+/// }
+/// ```
+///
+/// [`Item`]: crate::containers::Item
+/// [`Map`]: crate::containers::Map
+/// [`Column`]: crate::containers::Column
+#[macro_export]
+macro_rules! root {
+    // the containers, each with the prefix after the previous one unless given
+    (@consts [$($prev:ident)?]) => {};
+    (@consts [$($prev:ident)?]
+        $(#[$attr:meta])* $vis:vis $name:ident: $ty:ty $(= $prefix:expr)?
+        $(, $($rest:tt)*)?
+    ) => {
+        $(#[$attr])*
+        $vis const $name: $ty = <$ty>::new($crate::root!(@prefix [$($prev)?] $($prefix)?));
+
+        $($crate::root!(@consts [$name] $($rest)*);)?
+    };
+
+    (@prefix [$($prev:ident)?] $prefix:expr) => { $prefix };
+    (@prefix []) => { 0 };
+    (@prefix [$prev:ident]) => { $prev.prefix() + 1 };
+
+    ($($(#[$attr:meta])* $vis:vis $name:ident: $ty:ty $(= $prefix:expr)?),* $(,)?) => {
+        $crate::root!(@consts [] $($(#[$attr])* $vis $name: $ty $(= $prefix)?,)*);
+
+        /// The top-level containers, and their prefixes.
+        pub const LAYOUT: $crate::root::RootLayout = $crate::root::RootLayout {
+            containers: &[$(
+                $crate::root::RootEntry {
+                    name: ::core::stringify!($name),
+                    prefix: &[$name.prefix()],
+                    container: ::core::stringify!($ty),
+                }
+            ),*],
+        };
+
+        const _: () = $crate::containers::assert_unique_prefixes(&[$($name.prefix_bytes()),*]);
+    };
+}
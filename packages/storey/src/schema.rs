@@ -0,0 +1,171 @@
+//! JSON Schema descriptions of containers, for indexers and frontends.
+//!
+//! A container definition like `Map<String, Item<Position, E>>` says everything there is to
+//! know about what ends up in storage: the key structure, and the type of the values. This
+//! module turns that into a machine-readable [`SchemaDocument`], with the JSON Schema of every
+//! key and value type (generated by [`schemars`]) and a short textual description of the keys.
+//!
+//! The schemas describe the Rust types, not the bytes in storage. Keys are always stored in the
+//! binary layout described in [`containers`](crate::containers), and values in whatever the
+//! container's encoding produces. A consumer that wants to read raw storage still needs to
+//! know the encoding, but gets the shape of the decoded data from here.
+//!
+//! # Example
+//! ```
+//! # use storey_testing::encoding::TestEncoding;
+//! use storey::containers::{Item, Map};
+//!
+//! let balances = Map::<String, Item<u64, TestEncoding>>::new(0);
+//! let schema = balances.schema();
+//!
+//! assert_eq!(schema.prefix, [0]);
+//! assert_eq!(schema.key_structure, "String");
+//! ```
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::containers::{Column, Item, Map};
+
+/// A container that can describe its keys and values as JSON Schema.
+///
+/// This is implemented for all the containers in this crate, as long as the types of their keys
+/// and values implement [`JsonSchema`].
+pub trait StorableSchema {
+    /// Describe the container, adding the definitions of any named types to `gen`.
+    fn container_schema(gen: &mut SchemaGenerator) -> ContainerSchema;
+}
+
+/// The description of a container, and of the containers nested in it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "container", rename_all = "snake_case")]
+pub enum ContainerSchema {
+    /// A single value, stored under the container's own key.
+    Item { value: Schema },
+
+    /// A list of values, keyed by their `u32` index.
+    Column { value: Schema },
+
+    /// Entries keyed by `key`, each of them a container of its own.
+    Map {
+        /// The name of the key type, as reported by [`JsonSchema::schema_name`].
+        key_name: String,
+        key: Schema,
+        value: Box<ContainerSchema>,
+    },
+}
+
+impl ContainerSchema {
+    /// A short description of the key structure, e.g. `String / uint32 / index` for a
+    /// `Map<String, Map<u32, Column<_, _>>>`.
+    ///
+    /// Every map contributes the name of its key type, and a column contributes `index`. An
+    /// item doesn't manage any keys, so the result is empty for a lone [`Item`].
+    pub fn key_structure(&self) -> String {
+        let mut segments = Vec::new();
+        let mut current = self;
+
+        loop {
+            match current {
+                ContainerSchema::Item { .. } => break,
+                ContainerSchema::Column { .. } => {
+                    segments.push("index");
+                    break;
+                }
+                ContainerSchema::Map {
+                    key_name, value, ..
+                } => {
+                    segments.push(key_name);
+                    current = value;
+                }
+            }
+        }
+
+        segments.join(" / ")
+    }
+}
+
+/// The full description of a top-level container.
+///
+/// This is what the `schema` methods of the containers return. It serializes to a
+/// self-contained JSON document: the schemas in `container` refer to named types through
+/// `#/definitions/...`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaDocument {
+    /// The storage prefix of the container.
+    pub prefix: Vec<u8>,
+
+    /// See [`ContainerSchema::key_structure`].
+    pub key_structure: String,
+
+    pub container: ContainerSchema,
+
+    /// The definitions of the named types used in `container`.
+    pub definitions: schemars::Map<String, Schema>,
+}
+
+impl SchemaDocument {
+    /// Describe the container `S`, stored under `prefix`.
+    pub fn new<S: StorableSchema>(prefix: &[u8]) -> Self {
+        let mut gen = SchemaGenerator::default();
+        let container = S::container_schema(&mut gen);
+
+        Self {
+            prefix: prefix.to_vec(),
+            key_structure: container.key_structure(),
+            container,
+            definitions: gen.take_definitions(),
+        }
+    }
+}
+
+impl<T: JsonSchema, E> StorableSchema for Item<T, E> {
+    fn container_schema(gen: &mut SchemaGenerator) -> ContainerSchema {
+        ContainerSchema::Item {
+            value: gen.subschema_for::<T>(),
+        }
+    }
+}
+
+impl<T: JsonSchema, E> StorableSchema for Column<T, E> {
+    fn container_schema(gen: &mut SchemaGenerator) -> ContainerSchema {
+        ContainerSchema::Column {
+            value: gen.subschema_for::<T>(),
+        }
+    }
+}
+
+impl<K: JsonSchema, V: StorableSchema> StorableSchema for Map<K, V> {
+    fn container_schema(gen: &mut SchemaGenerator) -> ContainerSchema {
+        ContainerSchema::Map {
+            key_name: K::schema_name(),
+            key: gen.subschema_for::<K>(),
+            value: Box::new(V::container_schema(gen)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use storey_testing::encoding::TestEncoding;
+
+    #[test]
+    fn key_structure() {
+        let schema = Map::<String, Map<u32, Column<u64, TestEncoding>>>::new(0).schema();
+        assert_eq!(schema.key_structure, "String / uint32 / index");
+
+        let schema = Item::<u64, TestEncoding>::new(0).schema();
+        assert_eq!(schema.key_structure, "");
+        assert_eq!(
+            schema.container,
+            ContainerSchema::Item {
+                value: SchemaGenerator::default().subschema_for::<u64>()
+            }
+        );
+        assert!(schema.definitions.is_empty());
+    }
+}
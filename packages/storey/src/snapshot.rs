@@ -0,0 +1,494 @@
+//! Snapshots of raw storage, and the differences between them.
+//!
+//! [`capture`] copies every key-value pair under a set of prefixes out of a backend, and
+//! [`diff`] compares two such snapshots, listing the keys that were added, removed or changed.
+//! This is meant for verifying migrations: capture the state before and after running one,
+//! and check the diff is exactly what the migration was supposed to do.
+//!
+//! Diffs are in terms of raw keys and values. [`Diff::decode`] decodes the part of a diff that
+//! belongs to a container, given the container's type, into typed keys and values.
+//!
+//! Snapshots and diffs are sorted by raw key, so they're deterministic. With the `serde`
+//! feature enabled, they can be serialized (with keys and values as hex strings), so they can
+//! be compared against golden files.
+//!
+//! # Example
+//! ```
+//! # use storey_testing::backend::TestStorage;
+//! # use storey_testing::encoding::TestEncoding;
+//! use storey::containers::{Item, Map};
+//! use storey::snapshot::{capture, diff, DecodedChange, DecodedEntry};
+//!
+//! const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new(0);
+//!
+//! let mut storage = TestStorage::new();
+//! BALANCES.access(&mut storage).entry_mut("alice").set(&100).unwrap();
+//! BALANCES.access(&mut storage).entry_mut("bob").set(&5).unwrap();
+//!
+//! let before = capture(&storage, &[&[0]]);
+//!
+//! // the migration
+//! BALANCES.access(&mut storage).entry_mut("alice").set(&90).unwrap();
+//! BALANCES.access(&mut storage).entry_mut("carol").set(&10).unwrap();
+//!
+//! let after = capture(&storage, &[&[0]]);
+//!
+//! let decoded = diff(&before, &after)
+//!     .decode::<Map<String, Item<u64, TestEncoding>>>(&[0])
+//!     .unwrap();
+//!
+//! assert_eq!(
+//!     decoded.added,
+//!     [DecodedEntry { key: ("carol".to_string(), ()), value: 10 }]
+//! );
+//! assert_eq!(decoded.removed, []);
+//! assert_eq!(
+//!     decoded.changed,
+//!     [DecodedChange { key: ("alice".to_string(), ()), old: 100, new: 90 }]
+//! );
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crate::containers::Storable;
+use crate::storage::{IterableStorage, StorageBranch};
+
+/// Copy every key-value pair under `prefixes` out of `storage`.
+///
+/// The prefixes are raw key prefixes. They may overlap, and the empty prefix captures the
+/// whole storage.
+pub fn capture<S: IterableStorage>(storage: &S, prefixes: &[&[u8]]) -> Snapshot {
+    let mut entries = BTreeMap::new();
+
+    for prefix in prefixes {
+        let branch = StorageBranch::new(storage, prefix.to_vec());
+        for (key, value) in branch.pairs(None, None) {
+            entries.insert([prefix, &key[..]].concat(), value);
+        }
+    }
+
+    Snapshot {
+        entries: entries
+            .into_iter()
+            .map(|(key, value)| Entry { key, value })
+            .collect(),
+    }
+}
+
+/// Compare two snapshots.
+///
+/// Keys in both snapshots with the same value aren't part of the diff. Keys outside of the
+/// prefixes a snapshot was captured under are absent from it as far as the diff is concerned,
+/// so both snapshots should usually be captured under the same prefixes.
+pub fn diff(before: &Snapshot, after: &Snapshot) -> Diff {
+    let mut diff = Diff::default();
+
+    let mut before = before.entries.iter().peekable();
+    let mut after = after.entries.iter().peekable();
+
+    loop {
+        let ordering = match (before.peek(), after.peek()) {
+            (Some(old), Some(new)) => old.key.cmp(&new.key),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => break,
+        };
+
+        match ordering {
+            Ordering::Less => diff.removed.extend(before.next().cloned()),
+            Ordering::Greater => diff.added.extend(after.next().cloned()),
+            Ordering::Equal => {
+                let (old, new) = (before.next().unwrap(), after.next().unwrap());
+                if old.value != new.value {
+                    diff.changed.push(Change {
+                        key: old.key.clone(),
+                        old: old.value.clone(),
+                        new: new.value.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    diff
+}
+
+/// The key-value pairs under a set of prefixes, sorted by key. See [`capture`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "Vec<Entry>", into = "Vec<Entry>")
+)]
+pub struct Snapshot {
+    entries: Vec<Entry>,
+}
+
+impl Snapshot {
+    /// The entries, sorted by key.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Get the value under a raw key.
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.entries
+            .binary_search_by(|entry| entry.key.as_slice().cmp(key))
+            .ok()
+            .map(|i| self.entries[i].value.as_slice())
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Sorts the entries. If a key is there more than once, the last entry for it wins.
+impl From<Vec<Entry>> for Snapshot {
+    fn from(entries: Vec<Entry>) -> Self {
+        let entries: BTreeMap<_, _> = entries
+            .into_iter()
+            .map(|entry| (entry.key, entry.value))
+            .collect();
+
+        Self {
+            entries: entries
+                .into_iter()
+                .map(|(key, value)| Entry { key, value })
+                .collect(),
+        }
+    }
+}
+
+impl From<Snapshot> for Vec<Entry> {
+    fn from(snapshot: Snapshot) -> Self {
+        snapshot.entries
+    }
+}
+
+/// A raw key-value pair.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entry {
+    #[cfg_attr(feature = "serde", serde(with = "crate::hex"))]
+    pub key: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::hex"))]
+    pub value: Vec<u8>,
+}
+
+/// A raw key whose value changed.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Change {
+    #[cfg_attr(feature = "serde", serde(with = "crate::hex"))]
+    pub key: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::hex"))]
+    pub old: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::hex"))]
+    pub new: Vec<u8>,
+}
+
+/// The differences between two snapshots. See [`diff`].
+///
+/// Each list is sorted by key.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diff {
+    /// The entries only in the second snapshot.
+    pub added: Vec<Entry>,
+
+    /// The entries only in the first snapshot.
+    pub removed: Vec<Entry>,
+
+    /// The keys in both snapshots, with different values.
+    pub changed: Vec<Change>,
+}
+
+impl Diff {
+    /// Whether the snapshots were the same.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Decode the part of the diff under `prefix` as entries of the container `C`.
+    ///
+    /// `prefix` is the raw prefix the container is stored under, e.g. `[0]` for a top-level
+    /// `Map::new(0)`. Keys outside of it are left out. Keys are decoded with
+    /// [`Storable::decode_key`] (after stripping the prefix) and values with
+    /// [`Storable::decode_value`], just like in iteration over the container. Container
+    /// metadata (like the length of a [`Column`](crate::containers::Column)) lives in a
+    /// separate namespace, so it's never under the prefix.
+    ///
+    /// The first key or value that fails to decode is reported as an error, with its raw key.
+    pub fn decode<C: Storable>(
+        &self,
+        prefix: &[u8],
+    ) -> Result<DecodedDiff<C::Key, C::Value>, DecodeDiffErrorFor<C>> {
+        let decode_key = |key: &[u8]| {
+            C::decode_key(&key[prefix.len()..]).map_err(|source| DecodeDiffError::Key {
+                key: key.to_vec(),
+                source,
+            })
+        };
+        let decode_value = |key: &[u8], value: &[u8]| {
+            C::decode_value(value).map_err(|source| DecodeDiffError::Value {
+                key: key.to_vec(),
+                source,
+            })
+        };
+        let decode_entries = |entries: &[Entry]| {
+            entries
+                .iter()
+                .filter(|entry| entry.key.starts_with(prefix))
+                .map(|entry| {
+                    Ok(DecodedEntry {
+                        key: decode_key(&entry.key)?,
+                        value: decode_value(&entry.key, &entry.value)?,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(DecodedDiff {
+            added: decode_entries(&self.added)?,
+            removed: decode_entries(&self.removed)?,
+            changed: self
+                .changed
+                .iter()
+                .filter(|change| change.key.starts_with(prefix))
+                .map(|change| {
+                    Ok(DecodedChange {
+                        key: decode_key(&change.key)?,
+                        old: decode_value(&change.key, &change.old)?,
+                        new: decode_value(&change.key, &change.new)?,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
+/// A [`Diff`] decoded through a container. See [`Diff::decode`].
+///
+/// Each list is in the order of the raw keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodedDiff<K, V> {
+    /// The entries only in the second snapshot.
+    pub added: Vec<DecodedEntry<K, V>>,
+
+    /// The entries only in the first snapshot.
+    pub removed: Vec<DecodedEntry<K, V>>,
+
+    /// The keys in both snapshots, with different values.
+    pub changed: Vec<DecodedChange<K, V>>,
+}
+
+/// A decoded key-value pair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodedEntry<K, V> {
+    pub key: K,
+    pub value: V,
+}
+
+/// A decoded key whose value changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodedChange<K, V> {
+    pub key: K,
+    pub old: V,
+    pub new: V,
+}
+
+type DecodeDiffErrorFor<C> =
+    DecodeDiffError<<C as Storable>::KeyDecodeError, <C as Storable>::ValueDecodeError>;
+
+/// An error decoding a diff with [`Diff::decode`].
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
+pub enum DecodeDiffError<K, V> {
+    #[error("failed to decode raw key {key:?}: {source}")]
+    Key { key: Vec<u8>, source: K },
+
+    #[error("failed to decode the value under raw key {key:?}: {source}")]
+    Value { key: Vec<u8>, source: V },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::{Column, Item, Map};
+    use crate::storage::StorageMut as _;
+
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
+
+    fn entry(key: &[u8], value: &[u8]) -> Entry {
+        Entry {
+            key: key.to_vec(),
+            value: value.to_vec(),
+        }
+    }
+
+    #[test]
+    fn capture_under_prefixes() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0, 1], &[1]);
+        storage.set(&[1, 1], &[2]);
+        storage.set(&[1, 2], &[3]);
+        storage.set(&[2, 1], &[4]);
+
+        let snapshot = capture(&storage, &[&[2], &[0]]);
+        assert_eq!(
+            snapshot.entries(),
+            [entry(&[0, 1], &[1]), entry(&[2, 1], &[4])]
+        );
+        assert_eq!(snapshot.get(&[2, 1]), Some(&[4][..]));
+        assert_eq!(snapshot.get(&[1, 1]), None);
+
+        // overlapping prefixes don't duplicate entries
+        let snapshot = capture(&storage, &[&[1], &[1, 2]]);
+        assert_eq!(
+            snapshot.entries(),
+            [entry(&[1, 1], &[2]), entry(&[1, 2], &[3])]
+        );
+
+        assert_eq!(capture(&storage, &[&[]]).len(), 4);
+        assert!(capture(&storage, &[]).is_empty());
+    }
+
+    #[test]
+    fn diff_snapshots() {
+        let before = Snapshot::from(vec![
+            entry(&[3], &[3]),
+            entry(&[1], &[1]),
+            entry(&[2], &[2]),
+        ]);
+        let after = Snapshot::from(vec![
+            entry(&[2], &[20]),
+            entry(&[3], &[3]),
+            entry(&[4], &[4]),
+        ]);
+
+        let diff = diff(&before, &after);
+        assert_eq!(
+            diff,
+            Diff {
+                added: vec![entry(&[4], &[4])],
+                removed: vec![entry(&[1], &[1])],
+                changed: vec![Change {
+                    key: vec![2],
+                    old: vec![2],
+                    new: vec![20],
+                }],
+            }
+        );
+
+        assert!(super::diff(&before, &before).is_empty());
+        assert_eq!(super::diff(&Snapshot::default(), &after).added.len(), 3);
+        assert_eq!(super::diff(&after, &Snapshot::default()).removed.len(), 3);
+    }
+
+    #[test]
+    fn decode_through_container() {
+        let map = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+        let column = Column::<u64, TestEncoding>::new(1);
+
+        let mut storage = TestStorage::new();
+        map.access(&mut storage)
+            .entry_mut("alice")
+            .entry_mut(&1)
+            .set(&10)
+            .unwrap();
+        map.access(&mut storage)
+            .entry_mut("bob")
+            .entry_mut(&1)
+            .set(&20)
+            .unwrap();
+        column.access(&mut storage).push(&7).unwrap();
+
+        let before = capture(&storage, &[&[]]);
+
+        map.access(&mut storage)
+            .entry_mut("alice")
+            .entry_mut(&1)
+            .set(&11)
+            .unwrap();
+        map.access(&mut storage)
+            .entry_mut("alice")
+            .entry_mut(&2)
+            .set(&12)
+            .unwrap();
+        storage.remove(&[&[0][..], &crate::key!("bob", 1u32)].concat());
+        column.access(&mut storage).push(&8).unwrap();
+
+        let diff = diff(&before, &capture(&storage, &[&[]]));
+
+        let decoded = diff
+            .decode::<Map<String, Map<u32, Item<u64, TestEncoding>>>>(&[0])
+            .unwrap();
+        assert_eq!(
+            decoded,
+            DecodedDiff {
+                added: vec![DecodedEntry {
+                    key: ("alice".to_string(), (2, ())),
+                    value: 12,
+                }],
+                removed: vec![DecodedEntry {
+                    key: ("bob".to_string(), (1, ())),
+                    value: 20,
+                }],
+                changed: vec![DecodedChange {
+                    key: ("alice".to_string(), (1, ())),
+                    old: 10,
+                    new: 11,
+                }],
+            }
+        );
+
+        let decoded = diff.decode::<Column<u64, TestEncoding>>(&[1]).unwrap();
+        assert_eq!(decoded.added, [DecodedEntry { key: 1, value: 8 }]);
+        assert!(decoded.removed.is_empty() && decoded.changed.is_empty());
+
+        // the column's metadata isn't made of column entries
+        let err = diff
+            .decode::<Column<u64, TestEncoding>>(&[255])
+            .unwrap_err();
+        assert!(matches!(err, DecodeDiffError::Key { key, .. } if key == [255, 1, 0]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize() {
+        let diff = diff(
+            &Snapshot::from(vec![entry(&[0, 1], &[0xab])]),
+            &Snapshot::from(vec![entry(&[0, 1], &[0xcd]), entry(&[0, 2], &[])]),
+        );
+
+        let json = serde_json::to_string(&diff).unwrap();
+        assert_eq!(
+            json,
+            r#"{"added":[{"key":"0002","value":""}],"removed":[],"changed":[{"key":"0001","old":"ab","new":"cd"}]}"#
+        );
+        assert_eq!(serde_json::from_str::<Diff>(&json).unwrap(), diff);
+
+        // snapshots are sorted when deserialized
+        let snapshot: Snapshot =
+            serde_json::from_str(r#"[{"key":"02","value":"00"},{"key":"01","value":"00"}]"#)
+                .unwrap();
+        assert_eq!(snapshot.entries()[0].key, [1]);
+        assert_eq!(
+            serde_json::to_string(&snapshot).unwrap(),
+            r#"[{"key":"01","value":"00"},{"key":"02","value":"00"}]"#
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Counting the keys under a prefix, and the bytes they take up.
+//!
+//! [`measure`] goes over every entry under a raw key prefix once, and returns [`PrefixStats`]:
+//! the number of keys, the total size of the keys and values, and the size of the largest
+//! value. [`IterableAccessor::stats`] does the same for a container.
+//!
+//! [`measure_grouped`] splits the stats by the first key byte after the prefix. Measured
+//! from the root of a backend (with the empty prefix), that's a breakdown per top-level
+//! container, which helps find the containers taking up the most space.
+//!
+//! # Example
+//! ```
+//! # use storey_testing::backend::TestStorage;
+//! use storey::containers::{IterableAccessor as _, Item, Map};
+//! use storey::encoding::RawEncoding;
+//! use storey::stats::measure;
+//!
+//! const NAMES: Map<String, Item<String, RawEncoding>> = Map::new(0);
+//!
+//! let mut storage = TestStorage::new();
+//! NAMES.access(&mut storage).entry_mut("a").set(&"alice".to_string()).unwrap();
+//! NAMES.access(&mut storage).entry_mut("b").set(&"bob".to_string()).unwrap();
+//!
+//! let stats = measure(&storage, &[0]);
+//! assert_eq!(stats.keys, 2);
+//! assert_eq!(stats.key_bytes, 6); // prefix, length and the key, for both
+//! assert_eq!(stats.max_value_len, 5);
+//!
+//! // the container's own prefix isn't part of its keys
+//! assert_eq!(NAMES.access(&storage).stats().key_bytes, 4);
+//! ```
+//!
+//! [`IterableAccessor::stats`]: crate::containers::IterableAccessor::stats
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::storage::{prefix_end, IterableStorage};
+
+/// Measure the entries under a raw key `prefix`, in a single iteration.
+///
+/// Keys are measured as `storage` sees them. For a backend that's the full raw key, prefix
+/// included. Through a [`StorageBranch`](crate::storage::StorageBranch), the branch's prefix
+/// isn't part of the keys, so it isn't counted either.
+pub fn measure<S: IterableStorage>(storage: &S, prefix: &[u8]) -> PrefixStats {
+    pairs(storage, prefix).fold(PrefixStats::default(), |mut stats, (key, value)| {
+        stats.add(&key, &value);
+        stats
+    })
+}
+
+/// Measure the entries under a raw key `prefix`, grouped by the first key byte after it.
+///
+/// Keys that are exactly `prefix` don't belong to any group, and aren't counted. Summing up
+/// the groups gives the same stats as [`measure`] otherwise.
+pub fn measure_grouped<S: IterableStorage>(
+    storage: &S,
+    prefix: &[u8],
+) -> BTreeMap<u8, PrefixStats> {
+    let mut groups = BTreeMap::<u8, PrefixStats>::new();
+
+    for (key, value) in pairs(storage, prefix) {
+        if let Some(&group) = key.get(prefix.len()) {
+            groups.entry(group).or_default().add(&key, &value);
+        }
+    }
+
+    groups
+}
+
+fn pairs<'a, S: IterableStorage>(
+    storage: &'a S,
+    prefix: &[u8],
+) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a {
+    storage.pairs(Some(prefix), prefix_end(prefix).as_deref())
+}
+
+/// The number and size of the entries under a prefix. See [`measure`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrefixStats {
+    /// The number of keys.
+    pub keys: u64,
+
+    /// The total length of the keys, in bytes.
+    pub key_bytes: u64,
+
+    /// The total length of the values, in bytes.
+    pub value_bytes: u64,
+
+    /// The length of the largest value, in bytes.
+    pub max_value_len: u64,
+}
+
+impl PrefixStats {
+    /// The total length of the keys and values, in bytes.
+    pub fn total_bytes(&self) -> u64 {
+        self.key_bytes + self.value_bytes
+    }
+
+    fn add(&mut self, key: &[u8], value: &[u8]) {
+        self.keys += 1;
+        self.key_bytes += key.len() as u64;
+        self.value_bytes += value.len() as u64;
+        self.max_value_len = self.max_value_len.max(value.len() as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::{Item, IterableAccessor as _, Map};
+    use crate::encoding::RawEncoding;
+    use crate::storage::StorageMut as _;
+
+    use storey_testing::backend::TestStorage;
+
+    const NESTED: Map<String, Map<String, Item<String, RawEncoding>>> = Map::new(1);
+
+    fn seed() -> TestStorage {
+        let mut storage = TestStorage::new();
+
+        // values are stored as-is, so [1, 1, 'a', 2, 'x', 'y'] -> "foo" is 6 + 3 bytes
+        let entries = [("a", "xy", "foo"), ("a", "z", "longer"), ("bc", "x", "")];
+        for (outer, inner, value) in entries {
+            NESTED
+                .access(&mut storage)
+                .entry_mut(outer)
+                .entry_mut(inner)
+                .set(&value.to_string())
+                .unwrap();
+        }
+
+        // something outside of the map
+        storage.set(&[2], &[0; 10]);
+
+        storage
+    }
+
+    #[test]
+    fn measure_prefix() {
+        let storage = seed();
+
+        assert_eq!(
+            measure(&storage, &[1]),
+            PrefixStats {
+                keys: 3,
+                key_bytes: 6 + 5 + 6,
+                value_bytes: 3 + 6,
+                max_value_len: 6,
+            }
+        );
+        assert_eq!(measure(&storage, &[1]).total_bytes(), 26);
+
+        // the first outer entry only
+        assert_eq!(
+            measure(&storage, &[1, 1, b'a']),
+            PrefixStats {
+                keys: 2,
+                key_bytes: 6 + 5,
+                value_bytes: 9,
+                max_value_len: 6,
+            }
+        );
+
+        assert_eq!(measure(&storage, &[]).keys, 4);
+        assert_eq!(measure(&storage, &[3]), PrefixStats::default());
+    }
+
+    #[test]
+    fn measure_through_accessors() {
+        let storage = seed();
+
+        // keys are relative to the accessor's branch
+        let access = NESTED.access(&storage);
+        assert_eq!(
+            access.stats(),
+            PrefixStats {
+                keys: 3,
+                key_bytes: 5 + 4 + 5,
+                value_bytes: 9,
+                max_value_len: 6,
+            }
+        );
+
+        assert_eq!(
+            access.entry("bc").stats(),
+            PrefixStats {
+                keys: 1,
+                key_bytes: 2,
+                value_bytes: 0,
+                max_value_len: 0,
+            }
+        );
+        assert_eq!(access.entry("nope").stats(), PrefixStats::default());
+    }
+
+    #[test]
+    fn grouped() {
+        let storage = seed();
+
+        let groups = measure_grouped(&storage, &[]);
+        assert_eq!(groups.keys().copied().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(groups[&1], measure(&storage, &[1]));
+        assert_eq!(groups[&2].value_bytes, 10);
+
+        // under the map, the first byte is the length of the outer key
+        let groups = measure_grouped(&storage, &[1]);
+        assert_eq!(groups[&1].keys, 2);
+        assert_eq!(groups[&2].keys, 1);
+
+        // the prefix itself isn't in a group
+        let mut storage = storage;
+        storage.set(&[1], &[0]);
+        assert_eq!(measure(&storage, &[1]).keys, 4);
+        assert_eq!(
+            measure_grouped(&storage, &[1])
+                .values()
+                .map(|stats| stats.keys)
+                .sum::<u64>(),
+            3
+        );
+    }
+}
@@ -1,16 +1,53 @@
-use crate::storage::{IterableStorage, RevIterableStorage, Storage, StorageMut};
+use crate::storage::{ComposedKey, Storage, StorageMut};
+#[cfg(feature = "iteration")]
+use crate::storage::{IterableStorage, RemoveRangeStorage, RevIterableStorage};
+use alloc::vec::Vec;
 
-/// A type representing a storage namespace created by applying a prefix to all keys.
+/// A storage namespace created by applying a prefix to all keys.
 ///
-/// This type implements the [`Storage`] and [`StorageMut`] traits, making the fact a prefix
-/// is applied transparent to the user.
+/// This type implements the [`Storage`] and [`StorageMut`] traits (as well as the iteration
+/// traits), making the fact a prefix is applied transparent to the user. Every container
+/// accesses storage through one: the accessor of a [`Map`] with prefix `0` holds a branch with
+/// the prefix `[0]`, and each entry of that map is a branch nested in it.
 ///
-/// You don't need to be aware of this type unless implementing a custom container.
+/// You don't need to be aware of this type unless implementing a custom container, or
+/// accessing prefixed raw storage directly.
 ///
-/// # Example
+/// # Keys
+/// A key `k` accessed through a branch with prefix `p` is stored as `p ++ k` in the storage
+/// the branch wraps. Metadata keys are composed the same way, and then put in the metadata
+/// namespace of the wrapped storage. Keys returned by iteration have the prefix stripped.
+///
+/// # Iteration
+/// Iteration bounds are composed with the prefix the same way keys are: `start` becomes
+/// `p ++ start`, and `end` becomes `p ++ end`. A missing bound is replaced with the bound of
+/// the whole namespace - `p` for the start, and the first key that doesn't start with `p` for
+/// the end. Iteration never leaves the namespace.
+///
+/// # Nesting
+/// Since a branch is a storage itself, branches can be nested, and the prefixes concatenate.
+/// [`sub_branch`](Self::sub_branch) does the same thing without the extra layer of
+/// indirection: a sub-branch with prefix `q` of a branch with prefix `p` is a branch with
+/// prefix `p ++ q` over the same storage.
+///
+/// Nested branches don't concatenate the key at every level. Each one puts its prefix in front
+/// of a [`ComposedKey`] and passes it on, and the full key is only put together once, by the
+/// backend.
+///
+/// # Owned and borrowed storage
+/// A branch holds whatever it's given, but the storage traits are only implemented for
+/// branches over a reference: `StorageBranch<&S>` can read, and `StorageBranch<&mut S>` can
+/// read and write. To use an owned backend, pass a reference to it, or get the backend back
+/// with [`into_inner`](Self::into_inner).
+///
+/// Prefixes aren't checked by [`new`](Self::new). [`try_new`](Self::try_new) rejects prefixes
+/// that can't be right for a branch created directly over a backend.
+///
+/// # Examples
 /// ```
-/// # use mocks::backend::TestStorage;
-/// use storey::storage::{Storage as _, StorageMut as _, StorageBranch};
+/// # use storey_testing::backend::TestStorage;
+/// use storey::storage::{Storage as _, StorageMut as _};
+/// use storey::StorageBranch;
 ///
 /// let mut storage = TestStorage::new();
 /// let mut branch = StorageBranch::new(&mut storage, b"foo".to_vec());
@@ -20,57 +57,278 @@ use crate::storage::{IterableStorage, RevIterableStorage, Storage, StorageMut};
 /// assert_eq!(branch.get(b"bar"), Some(b"baz".to_vec()));
 /// assert_eq!(storage.get(b"foobar"), Some(b"baz".to_vec()));
 /// ```
+///
+/// A container built on the public API only - a set of byte strings:
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// use storey::storage::{IterableStorage, Storage, StorageMut};
+/// use storey::StorageBranch;
+///
+/// struct ByteSet {
+///     prefix: u8,
+/// }
+///
+/// impl ByteSet {
+///     fn access<S>(&self, storage: S) -> StorageBranch<S> {
+///         StorageBranch::try_new(storage, vec![self.prefix]).expect("valid prefix")
+///     }
+/// }
+///
+/// fn insert(set: &mut impl StorageMut, member: &[u8]) {
+///     set.set(member, &[]);
+/// }
+///
+/// fn contains(set: &impl Storage, member: &[u8]) -> bool {
+///     set.has(member)
+/// }
+///
+/// fn members(set: &impl IterableStorage) -> Vec<Vec<u8>> {
+///     set.keys(None, None).collect()
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// let fruits = ByteSet { prefix: 0 };
+/// let vegetables = ByteSet { prefix: 1 };
+///
+/// insert(&mut fruits.access(&mut storage), b"pear");
+/// insert(&mut fruits.access(&mut storage), b"apple");
+/// insert(&mut vegetables.access(&mut storage), b"leek");
+///
+/// assert!(contains(&fruits.access(&storage), b"pear"));
+/// assert!(!contains(&fruits.access(&storage), b"leek"));
+/// assert_eq!(members(&fruits.access(&storage)), [b"apple".to_vec(), b"pear".to_vec()]);
+///
+/// // one set per user, nested in a branch
+/// let per_user = ByteSet { prefix: 2 };
+/// let mut access = per_user.access(&mut storage);
+/// insert(&mut access.sub_branch_mut(b"alice/"), b"x");
+/// insert(&mut access.sub_branch_mut(b"bob/"), b"y");
+///
+/// assert_eq!(members(&access.sub_branch(b"alice/")), [b"x".to_vec()]);
+/// assert_eq!(members(&access), [b"alice/x".to_vec(), b"bob/y".to_vec()]);
+/// ```
+///
+/// [`Map`]: crate::containers::Map
 pub struct StorageBranch<S> {
     backend: S,
     prefix: Vec<u8>,
 }
 
+/// An error returned by [`StorageBranch::try_new`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum InvalidPrefix {
+    /// The prefix is empty, so the branch would be the whole storage.
+    #[error("empty prefix")]
+    Empty,
+
+    /// The prefix starts with the byte reserved for the metadata namespace of a backend.
+    #[error("prefix starting with the reserved byte 255")]
+    Reserved,
+}
+
 impl<S> StorageBranch<S> {
     /// Creates a new `StorageBranch` instance given a prefix.
     pub fn new(backend: S, prefix: Vec<u8>) -> Self {
         Self { backend, prefix }
     }
+
+    /// Creates a new `StorageBranch` instance given a prefix, checking the prefix.
+    ///
+    /// The prefix must be non-empty, and must not start with `255`, which a backend uses for
+    /// its metadata namespace. The latter doesn't apply to branches nested in another branch,
+    /// which should be created with [`sub_branch`](Self::sub_branch) instead.
+    pub fn try_new(backend: S, prefix: Vec<u8>) -> Result<Self, InvalidPrefix> {
+        match prefix.first() {
+            None => Err(InvalidPrefix::Empty),
+            Some(255) => Err(InvalidPrefix::Reserved),
+            Some(_) => Ok(Self::new(backend, prefix)),
+        }
+    }
+
+    /// The prefix applied to keys.
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    /// Get the wrapped storage back.
+    pub fn into_inner(self) -> S {
+        self.backend
+    }
+}
+
+impl<'s, S> StorageBranch<&'s S> {
+    /// A branch nested in this one, with `prefix` appended to this branch's prefix.
+    pub fn sub_branch(&self, prefix: &[u8]) -> StorageBranch<&'s S> {
+        StorageBranch::new(self.backend, [&self.prefix[..], prefix].concat())
+    }
+}
+
+impl<S> StorageBranch<&mut S> {
+    /// A read-only branch nested in this one, with `prefix` appended to this branch's prefix.
+    pub fn sub_branch(&self, prefix: &[u8]) -> StorageBranch<&S> {
+        StorageBranch::new(&*self.backend, [&self.prefix[..], prefix].concat())
+    }
+
+    /// A branch nested in this one, with `prefix` appended to this branch's prefix.
+    pub fn sub_branch_mut(&mut self, prefix: &[u8]) -> StorageBranch<&mut S> {
+        StorageBranch::new(&mut *self.backend, [&self.prefix[..], prefix].concat())
+    }
 }
 
 impl<S: Storage> Storage for StorageBranch<&S> {
     fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
-        self.backend.get(&[&self.prefix[..], key].concat())
+        self.get_composed(&ComposedKey::new(key))
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        self.has_composed(&ComposedKey::new(key))
     }
 
     fn get_meta(&self, key: &[u8]) -> Option<Vec<u8>> {
-        self.backend.get_meta(&[&self.prefix[..], key].concat())
+        self.get_meta_composed(&ComposedKey::new(key))
+    }
+
+    fn has_meta(&self, key: &[u8]) -> bool {
+        self.has_meta_composed(&ComposedKey::new(key))
+    }
+
+    fn get_composed(&self, key: &ComposedKey) -> Option<Vec<u8>> {
+        self.backend.get_composed(&key.with_prefix(&self.prefix))
+    }
+
+    fn has_composed(&self, key: &ComposedKey) -> bool {
+        self.backend.has_composed(&key.with_prefix(&self.prefix))
+    }
+
+    fn get_meta_composed(&self, key: &ComposedKey) -> Option<Vec<u8>> {
+        self.backend
+            .get_meta_composed(&key.with_prefix(&self.prefix))
+    }
+
+    fn has_meta_composed(&self, key: &ComposedKey) -> bool {
+        self.backend
+            .has_meta_composed(&key.with_prefix(&self.prefix))
+    }
+
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        let keys: Vec<_> = keys
+            .iter()
+            .map(|key| [&self.prefix[..], key].concat())
+            .collect();
+
+        self.backend
+            .get_many(&keys.iter().map(Vec::as_slice).collect::<Vec<_>>())
+    }
+
+    fn key_prefix(&self) -> Vec<u8> {
+        [self.backend.key_prefix(), self.prefix.clone()].concat()
     }
 }
 
 impl<S: Storage> Storage for StorageBranch<&mut S> {
     fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
-        self.backend.get(&[&self.prefix[..], key].concat())
+        self.get_composed(&ComposedKey::new(key))
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        self.has_composed(&ComposedKey::new(key))
     }
 
     fn get_meta(&self, key: &[u8]) -> Option<Vec<u8>> {
-        self.backend.get_meta(&[&self.prefix[..], key].concat())
+        self.get_meta_composed(&ComposedKey::new(key))
+    }
+
+    fn has_meta(&self, key: &[u8]) -> bool {
+        self.has_meta_composed(&ComposedKey::new(key))
+    }
+
+    fn get_composed(&self, key: &ComposedKey) -> Option<Vec<u8>> {
+        self.backend.get_composed(&key.with_prefix(&self.prefix))
+    }
+
+    fn has_composed(&self, key: &ComposedKey) -> bool {
+        self.backend.has_composed(&key.with_prefix(&self.prefix))
+    }
+
+    fn get_meta_composed(&self, key: &ComposedKey) -> Option<Vec<u8>> {
+        self.backend
+            .get_meta_composed(&key.with_prefix(&self.prefix))
+    }
+
+    fn has_meta_composed(&self, key: &ComposedKey) -> bool {
+        self.backend
+            .has_meta_composed(&key.with_prefix(&self.prefix))
+    }
+
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        let keys: Vec<_> = keys
+            .iter()
+            .map(|key| [&self.prefix[..], key].concat())
+            .collect();
+
+        self.backend
+            .get_many(&keys.iter().map(Vec::as_slice).collect::<Vec<_>>())
+    }
+
+    fn key_prefix(&self) -> Vec<u8> {
+        [self.backend.key_prefix(), self.prefix.clone()].concat()
     }
 }
 
 impl<S: StorageMut> StorageMut for StorageBranch<&mut S> {
     fn set(&mut self, key: &[u8], value: &[u8]) {
-        self.backend.set(&[&self.prefix[..], key].concat(), value)
+        self.set_composed(&ComposedKey::new(key), value)
     }
 
     fn remove(&mut self, key: &[u8]) {
-        self.backend.remove(&[&self.prefix[..], key].concat())
+        self.remove_composed(&ComposedKey::new(key))
     }
 
     fn set_meta(&mut self, key: &[u8], value: &[u8]) {
-        self.backend
-            .set_meta(&[&self.prefix[..], key].concat(), value)
+        self.set_meta_composed(&ComposedKey::new(key), value)
     }
 
     fn remove_meta(&mut self, key: &[u8]) {
-        self.backend.remove_meta(&[&self.prefix[..], key].concat())
+        self.remove_meta_composed(&ComposedKey::new(key))
+    }
+
+    fn set_composed(&mut self, key: &ComposedKey, value: &[u8]) {
+        self.backend
+            .set_composed(&key.with_prefix(&self.prefix), value)
+    }
+
+    fn remove_composed(&mut self, key: &ComposedKey) {
+        self.backend.remove_composed(&key.with_prefix(&self.prefix))
+    }
+
+    fn set_meta_composed(&mut self, key: &ComposedKey, value: &[u8]) {
+        self.backend
+            .set_meta_composed(&key.with_prefix(&self.prefix), value)
+    }
+
+    fn remove_meta_composed(&mut self, key: &ComposedKey) {
+        self.backend
+            .remove_meta_composed(&key.with_prefix(&self.prefix))
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<S: RemoveRangeStorage> RemoveRangeStorage for StorageBranch<&mut S> {
+    fn remove_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>) {
+        let (start, end) = sub_bounds(&self.prefix, start, end);
+
+        self.backend.remove_range(start.as_deref(), end.as_deref())
+    }
+
+    fn remove_meta_range(&mut self, start: Option<&[u8]>, end: Option<&[u8]>) {
+        let (start, end) = sub_bounds(&self.prefix, start, end);
+
+        self.backend
+            .remove_meta_range(start.as_deref(), end.as_deref())
     }
 }
 
+#[cfg(feature = "iteration")]
 impl<S: IterableStorage> IterableStorage for StorageBranch<&S> {
     type KeysIterator<'a> = BranchKeysIter<S::KeysIterator<'a>> where Self: 'a;
     type ValuesIterator<'a> = S::ValuesIterator<'a> where Self: 'a;
@@ -110,6 +368,7 @@ impl<S: IterableStorage> IterableStorage for StorageBranch<&S> {
     }
 }
 
+#[cfg(feature = "iteration")]
 impl<S: IterableStorage> IterableStorage for StorageBranch<&mut S> {
     type KeysIterator<'a> = BranchKeysIter<S::KeysIterator<'a>> where Self: 'a;
     type ValuesIterator<'a> = S::ValuesIterator<'a> where Self: 'a;
@@ -149,6 +408,7 @@ impl<S: IterableStorage> IterableStorage for StorageBranch<&mut S> {
     }
 }
 
+#[cfg(feature = "iteration")]
 impl<S: RevIterableStorage> RevIterableStorage for StorageBranch<&S> {
     type RevKeysIterator<'a> = BranchKeysIter<S::RevKeysIterator<'a>> where Self: 'a;
     type RevValuesIterator<'a> = S::RevValuesIterator<'a> where Self: 'a;
@@ -200,6 +460,7 @@ impl<S: RevIterableStorage> RevIterableStorage for StorageBranch<&S> {
     }
 }
 
+#[cfg(feature = "iteration")]
 impl<S: RevIterableStorage> RevIterableStorage for StorageBranch<&mut S> {
     type RevKeysIterator<'a> = BranchKeysIter<S::RevKeysIterator<'a>> where Self: 'a;
     type RevValuesIterator<'a> = S::RevValuesIterator<'a> where Self: 'a;
@@ -251,6 +512,7 @@ impl<S: RevIterableStorage> RevIterableStorage for StorageBranch<&mut S> {
     }
 }
 
+#[cfg(feature = "iteration")]
 fn sub_bounds(
     prefix: &[u8],
     start: Option<&[u8]>,
@@ -265,23 +527,32 @@ fn sub_bounds(
                     .map(|s| [prefix, s].concat())
                     .unwrap_or(prefix.to_vec()),
             ),
-            Some(end.map(|e| [prefix, e].concat()).unwrap_or_else(|| {
-                let mut pref = prefix.to_vec();
-                if let Some(x) = pref.last_mut() {
-                    *x += 1;
-                }
-                pref
-            })),
+            end.map(|e| [prefix, e].concat())
+                .or_else(|| prefix_end(prefix)),
         )
     }
 }
 
+/// The first key that comes after all the keys starting with `prefix`, or `None` if there's no
+/// such key (the prefix is all `255`s).
+#[cfg(feature = "iteration")]
+pub(crate) fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let last = prefix.iter().rposition(|&b| b != 255)?;
+
+    let mut end = prefix[..=last].to_vec();
+    end[last] += 1;
+    Some(end)
+}
+
 /// An iterator over the keys of a `StorageBranch`.
+#[cfg(feature = "iteration")]
+#[derive(Debug, Clone)]
 pub struct BranchKeysIter<I> {
     inner: I,
     prefix_len: usize,
 }
 
+#[cfg(feature = "iteration")]
 impl<I> Iterator for BranchKeysIter<I>
 where
     I: Iterator<Item = Vec<u8>>,
@@ -294,11 +565,14 @@ where
 }
 
 /// An iterator over the key-value pairs of a `StorageBranch`.
+#[cfg(feature = "iteration")]
+#[derive(Debug, Clone)]
 pub struct BranchKVIter<I> {
     inner: I,
     prefix_len: usize,
 }
 
+#[cfg(feature = "iteration")]
 impl<I> Iterator for BranchKVIter<I>
 where
     I: Iterator<Item = (Vec<u8>, Vec<u8>)>,
@@ -317,7 +591,7 @@ where
 mod tests {
     use super::*;
 
-    use mocks::backend::TestStorage;
+    use storey_testing::backend::TestStorage;
 
     #[test]
     fn storage_branch() {
@@ -436,4 +710,153 @@ mod tests {
         assert_eq!(storage.get(b"foobar"), None);
         assert_eq!(storage.get(b"fooqux"), None);
     }
+
+    #[test]
+    fn sub_bounds_trailing_max_bytes() {
+        assert_eq!(
+            sub_bounds(&[0, 255], None, None),
+            (Some(vec![0, 255]), Some(vec![1]))
+        );
+
+        assert_eq!(
+            sub_bounds(&[3, 255, 255], Some(&[1]), None),
+            (Some(vec![3, 255, 255, 1]), Some(vec![4]))
+        );
+
+        assert_eq!(
+            sub_bounds(&[255, 255], None, None),
+            (Some(vec![255, 255]), None)
+        );
+    }
+
+    #[test]
+    fn iteration_stays_in_namespace() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0, 254, 9], b"before");
+        storage.set(&[0, 255], b"a");
+        storage.set(&[0, 255, 255, 1], b"b");
+        storage.set(&[1], b"after");
+
+        let branch = StorageBranch::new(&storage, vec![0, 255]);
+        assert_eq!(
+            branch.pairs(None, None).collect::<Vec<_>>(),
+            [(vec![], b"a".to_vec()), (vec![255, 1], b"b".to_vec())]
+        );
+        assert_eq!(
+            branch.rev_keys(None, None).collect::<Vec<_>>(),
+            [vec![255, 1], vec![]]
+        );
+    }
+
+    #[test]
+    fn all_max_prefix_iterates_to_the_end() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0, 255, 254, 255], b"before");
+        storage.set(&[0, 255, 255], b"a");
+        storage.set(&[0, 255, 255, 0], b"b");
+        storage.set(&[0, 255, 255, 255, 255], b"c");
+
+        // the backend's root has no key after [0, 255, 255, ..], but branches under [0] do
+        let outer = StorageBranch::new(&storage, vec![0]);
+        let branch = StorageBranch::new(&outer, vec![255, 255]);
+        assert_eq!(
+            branch.keys(None, None).collect::<Vec<_>>(),
+            [vec![], vec![0], vec![255, 255]]
+        );
+        assert_eq!(
+            branch.rev_values(None, None).collect::<Vec<_>>(),
+            [b"c".to_vec(), b"b".to_vec(), b"a".to_vec()]
+        );
+        assert_eq!(
+            branch.keys(Some(&[1]), None).collect::<Vec<_>>(),
+            [vec![255, 255]]
+        );
+
+        // a prefix of only 255s has no end at all
+        let mut storage = TestStorage::new();
+        storage.set(&[254, 255], b"before");
+        storage.set(&[255, 255], b"a");
+        storage.set(&[255, 255, 255], b"b");
+        let branch = StorageBranch::new(&storage, vec![255, 255]);
+        assert_eq!(
+            branch.pairs(None, None).collect::<Vec<_>>(),
+            [(vec![], b"a".to_vec()), (vec![255], b"b".to_vec())]
+        );
+    }
+
+    #[test]
+    fn nested_branches_carry() {
+        let mut storage = TestStorage::new();
+        storage.set(&[1, 254, 255], b"before");
+        storage.set(&[1, 255, 255, 0], b"a");
+        storage.set(&[1, 255, 255, 255], b"b");
+        storage.set(&[2], b"after");
+
+        // neither prefix ends in 255 on its own, but [1, 255] + [255] carries into [2]
+        let outer = StorageBranch::new(&storage, vec![1, 255]);
+        let inner = StorageBranch::new(&outer, vec![255]);
+        assert_eq!(
+            inner.pairs(None, None).collect::<Vec<_>>(),
+            [(vec![0], b"a".to_vec()), (vec![255], b"b".to_vec())]
+        );
+        assert_eq!(
+            inner.rev_keys(None, None).collect::<Vec<_>>(),
+            [vec![255], vec![0]]
+        );
+        assert_eq!(
+            outer
+                .sub_branch(&[255])
+                .keys(None, None)
+                .collect::<Vec<_>>(),
+            [vec![0], vec![255]]
+        );
+    }
+
+    #[test]
+    fn try_new() {
+        let storage = TestStorage::new();
+
+        assert!(StorageBranch::try_new(&storage, vec![0]).is_ok());
+        assert_eq!(
+            StorageBranch::try_new(&storage, vec![]).err(),
+            Some(InvalidPrefix::Empty)
+        );
+        assert_eq!(
+            StorageBranch::try_new(&storage, vec![255, 0]).err(),
+            Some(InvalidPrefix::Reserved)
+        );
+    }
+
+    #[test]
+    fn sub_branch() {
+        let mut storage = TestStorage::new();
+        let mut branch = StorageBranch::new(&mut storage, b"foo".to_vec());
+
+        branch.sub_branch_mut(b"bar").set(b"baz", b"1");
+        branch.sub_branch_mut(b"bar").set_meta(b"baz", b"2");
+        assert_eq!(branch.sub_branch(b"bar").prefix(), b"foobar");
+        assert_eq!(branch.sub_branch(b"bar").get(b"baz"), Some(b"1".to_vec()));
+        assert_eq!(branch.get(b"barbaz"), Some(b"1".to_vec()));
+
+        let nested = StorageBranch::new(&branch, b"bar".to_vec());
+        assert_eq!(nested.get_meta(b"baz"), Some(b"2".to_vec()));
+
+        assert_eq!(storage.get(b"foobarbaz"), Some(b"1".to_vec()));
+        assert_eq!(storage.get_meta(b"foobarbaz"), Some(b"2".to_vec()));
+    }
+
+    #[test]
+    fn key_prefix() {
+        let mut storage = TestStorage::new();
+        assert_eq!(storage.key_prefix(), b"");
+
+        let mut branch = StorageBranch::new(&mut storage, b"foo".to_vec());
+        assert_eq!(branch.key_prefix(), b"foo");
+        assert_eq!(branch.sub_branch_mut(b"bar").key_prefix(), b"foobar");
+
+        // a branch of a branch only knows its own prefix, but its keys get both
+        let nested = StorageBranch::new(&branch, b"bar".to_vec());
+        assert_eq!(nested.prefix(), b"bar");
+        assert_eq!(nested.key_prefix(), b"foobar");
+    }
 }
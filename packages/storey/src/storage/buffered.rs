@@ -0,0 +1,304 @@
+#[cfg(feature = "iteration")]
+use alloc::collections::btree_map;
+use alloc::vec::Vec;
+#[cfg(feature = "iteration")]
+use core::cmp::Ordering;
+#[cfg(feature = "iteration")]
+use core::iter::Rev;
+
+use super::transaction::Overlay;
+#[cfg(feature = "iteration")]
+use super::transaction::{overlay_range, MergeIter, Project};
+#[cfg(feature = "iteration")]
+use crate::storage::{IterableStorage, RevIterableStorage};
+use crate::storage::{StorageBackend, StorageBackendMut};
+
+/// A storage backend batching writes to another backend.
+///
+/// Sets and removes are buffered in memory, and reads (including iteration) see the buffered
+/// state. [`flush`](Self::flush) applies everything to the base backend in a single
+/// [batch](StorageBackendMut::write_batch). Only the last write to each key is kept, so the
+/// base sees at most one write per key per flush.
+///
+/// This is meant for backends where every call is expensive, like a remote key-value store or
+/// a host boundary with a fixed cost per call. Unlike a [`Transaction`](super::Transaction),
+/// it's not a way to discard writes: buffered writes must be flushed before the wrapper is
+/// dropped. In debug builds, dropping it with unflushed writes panics.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::Column;
+/// use storey::storage::Buffered;
+///
+/// let mut storage = TestStorage::new();
+/// let column = Column::<u64, TestEncoding>::new(0);
+///
+/// {
+///     let mut buffered = Buffered::new(&mut storage);
+///     for i in 0..100 {
+///         column.access(&mut buffered).push(&i).unwrap();
+///     }
+///     buffered.flush();
+/// }
+///
+/// assert_eq!(column.access(&storage).len().unwrap(), 100);
+/// ```
+pub struct Buffered<S> {
+    base: S,
+    pending: Overlay,
+}
+
+impl<S> Buffered<S> {
+    /// Start buffering writes to `base`.
+    pub fn new(base: S) -> Self {
+        Self {
+            base,
+            pending: Overlay::new(),
+        }
+    }
+
+    /// Check if there are any writes that weren't flushed yet.
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
+
+impl<B: StorageBackendMut> Buffered<&mut B> {
+    /// Apply the buffered writes to the base backend.
+    pub fn flush(&mut self) {
+        self.base.write_batch(core::mem::take(&mut self.pending));
+    }
+}
+
+impl<S> Drop for Buffered<S> {
+    fn drop(&mut self) {
+        // without `std`, there's no telling whether this runs during a panic, so no check
+        #[cfg(feature = "std")]
+        if cfg!(debug_assertions) && self.has_pending() && !std::thread::panicking() {
+            panic!(
+                "`Buffered` dropped with {} unflushed writes",
+                self.pending.len()
+            );
+        }
+    }
+}
+
+impl<B: StorageBackend + ?Sized> StorageBackend for Buffered<&mut B> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.pending.get(key) {
+            Some(value) => value.clone(),
+            None => self.base.get(key),
+        }
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        match self.pending.get(key) {
+            Some(value) => value.is_some(),
+            None => self.base.has(key),
+        }
+    }
+}
+
+impl<B: StorageBackend + ?Sized> StorageBackendMut for Buffered<&mut B> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.pending.insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.pending.insert(key.to_vec(), None);
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<B: IterableStorage + ?Sized> IterableStorage for Buffered<&mut B> {
+    type KeysIterator<'a>
+        = core::iter::Map<Self::PairsIterator<'a>, Project>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = core::iter::Map<Self::PairsIterator<'a>, Project>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = MergeIter<'a, B::PairsIterator<'a>, btree_map::Range<'a, Vec<u8>, Option<Vec<u8>>>>
+    where
+        Self: 'a;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        self.pairs(start, end).map(|(k, _)| k)
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        self.pairs(start, end).map(|(_, v)| v)
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        MergeIter::new(
+            self.base.pairs(start, end),
+            overlay_range(&self.pending, start, end),
+            Ordering::Less,
+        )
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<B: RevIterableStorage + ?Sized> RevIterableStorage for Buffered<&mut B> {
+    type RevKeysIterator<'a>
+        = core::iter::Map<Self::RevPairsIterator<'a>, Project>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = core::iter::Map<Self::RevPairsIterator<'a>, Project>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        =
+        MergeIter<'a, B::RevPairsIterator<'a>, Rev<btree_map::Range<'a, Vec<u8>, Option<Vec<u8>>>>>
+    where
+        Self: 'a;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        self.rev_pairs(start, end).map(|(k, _)| k)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        self.rev_pairs(start, end).map(|(_, v)| v)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        MergeIter::new(
+            self.base.rev_pairs(start, end),
+            overlay_range(&self.pending, start, end).rev(),
+            Ordering::Greater,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+
+    use super::*;
+
+    use crate::containers::{Column, Item, IterableAccessor as _, Map};
+
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
+
+    /// Counts the write calls reaching the backend, per key.
+    #[derive(Default)]
+    struct Counting {
+        inner: TestStorage,
+        writes: BTreeMap<Vec<u8>, usize>,
+    }
+
+    impl StorageBackend for Counting {
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            self.inner.get(key)
+        }
+    }
+
+    impl StorageBackendMut for Counting {
+        fn set(&mut self, key: &[u8], value: &[u8]) {
+            *self.writes.entry(key.to_vec()).or_default() += 1;
+            self.inner.set(key, value)
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            *self.writes.entry(key.to_vec()).or_default() += 1;
+            self.inner.remove(key)
+        }
+    }
+
+    #[test]
+    fn one_write_per_key() {
+        let mut storage = Counting::default();
+
+        let column = Column::<u64, TestEncoding>::new(0);
+        let item = Item::<u64, TestEncoding>::new(1);
+
+        let mut buffered = Buffered::new(&mut storage);
+        for i in 0..10 {
+            column.access(&mut buffered).push(&i).unwrap();
+            item.access(&mut buffered).set(&i).unwrap();
+        }
+        column.access(&mut buffered).remove(3).unwrap();
+
+        // reads see the buffered state
+        assert_eq!(column.access(&buffered).len(), Ok(9));
+        assert_eq!(item.access(&buffered).get(), Ok(Some(9)));
+        assert!(buffered.base.writes.is_empty());
+
+        buffered.flush();
+        assert!(!buffered.has_pending());
+        drop(buffered);
+
+        // 10 column entries, the column's length and its next index, and the item
+        assert_eq!(storage.writes.len(), 13);
+        assert!(storage.writes.values().all(|&count| count == 1));
+
+        assert_eq!(column.access(&storage.inner).len(), Ok(9));
+        assert_eq!(column.access(&storage.inner).get(3), Ok(None));
+        assert_eq!(item.access(&storage.inner).get(), Ok(Some(9)));
+    }
+
+    #[test]
+    fn iteration() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        map.access(&mut storage).entry_mut("a").set(&1).unwrap();
+        map.access(&mut storage).entry_mut("c").set(&3).unwrap();
+
+        let mut buffered = Buffered::new(&mut storage);
+        map.access(&mut buffered).entry_mut("b").set(&2).unwrap();
+        map.access(&mut buffered).entry_mut("c").set(&4).unwrap();
+        buffered.remove(&[&[0][..], &crate::key!("a")].concat());
+
+        assert_eq!(
+            map.access(&buffered)
+                .values()
+                .collect::<Result<Vec<_>, _>>(),
+            Ok(vec![2, 4])
+        );
+        assert_eq!(
+            buffered.rev_keys(None, None).collect::<Vec<_>>(),
+            [
+                [&[0][..], &crate::key!("c")].concat(),
+                [&[0][..], &crate::key!("b")].concat()
+            ]
+        );
+
+        buffered.flush();
+        drop(buffered);
+
+        assert_eq!(
+            map.access(&storage).values().collect::<Result<Vec<_>, _>>(),
+            Ok(vec![2, 4])
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "unflushed writes")]
+    fn unflushed_drop_panics() {
+        let mut storage = TestStorage::new();
+
+        let mut buffered = Buffered::new(&mut storage);
+        buffered.set(&[0], b"lost");
+    }
+}
@@ -0,0 +1,528 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+#[cfg(feature = "iteration")]
+use crate::storage::{IterableStorage, RevIterableStorage};
+use crate::storage::{StorageBackend, StorageBackendMut};
+
+/// A storage backend wrapper caching point reads in a bounded LRU cache.
+///
+/// The results of [`get`](StorageBackend::get) are cached, including lookups of keys that don't
+/// exist. When the cache is full, the least recently used entries are evicted first. Writes
+/// made through the wrapper invalidate the cached entries for the keys written. Writes made to
+/// the wrapped backend by other means aren't seen by the cache.
+///
+/// Iteration is not cached: it always goes to the wrapped backend, and doesn't fill the cache
+/// either. Caching ranges correctly would mean tracking which ranges were fully read, which
+/// isn't worth it for the access patterns this is meant for - a handful of hot keys read over
+/// and over.
+///
+/// [`get_many`](StorageBackend::get_many) answers what it can from the cache, and fetches the
+/// rest from the wrapped backend in a single batch.
+///
+/// [`stats`](Self::stats) gives the number of hits and misses, to help pick a capacity.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::Item;
+/// use storey::storage::{Cached, Capacity};
+///
+/// let item = Item::<u64, TestEncoding>::new(0);
+///
+/// let mut storage = TestStorage::new();
+/// item.access(&mut storage).set(&42).unwrap();
+///
+/// let cached = Cached::new(&storage, Capacity::Entries(128));
+/// for _ in 0..10 {
+///     assert_eq!(item.access(&cached).get().unwrap(), Some(42));
+/// }
+///
+/// assert_eq!(cached.stats().misses, 1);
+/// assert_eq!(cached.stats().hits, 9);
+/// ```
+pub struct Cached<S> {
+    inner: S,
+    cache: RefCell<Lru>,
+}
+
+/// The bound on the size of the cache of a [`Cached`] backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capacity {
+    /// The maximum number of entries.
+    Entries(usize),
+
+    /// The maximum total length of the keys and values cached. Entries bigger than this are
+    /// never cached.
+    Bytes(usize),
+}
+
+/// The hit and miss counters of a [`Cached`] backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The reads answered by the cache.
+    pub hits: u64,
+
+    /// The reads that went to the wrapped backend.
+    pub misses: u64,
+
+    /// The entries evicted to make room for new ones.
+    pub evictions: u64,
+}
+
+impl<S> Cached<S> {
+    /// Start caching reads from `inner`, keeping at most `capacity` in the cache.
+    pub fn new(inner: S, capacity: Capacity) -> Self {
+        Self {
+            inner,
+            cache: RefCell::new(Lru::new(capacity)),
+        }
+    }
+
+    /// Get the hit and miss counters.
+    pub fn stats(&self) -> CacheStats {
+        self.cache.borrow().stats
+    }
+
+    /// Get the number of entries in the cache.
+    pub fn len(&self) -> usize {
+        self.cache.borrow().entries.len()
+    }
+
+    /// Check if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Empty the cache. The counters are kept.
+    pub fn clear(&mut self) {
+        self.cache.get_mut().clear();
+    }
+
+    /// Get the wrapped backend back.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: StorageBackend> StorageBackend for Cached<S> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        if let Some(value) = self.cache.borrow_mut().get(key) {
+            return value;
+        }
+
+        let value = self.inner.get(key);
+        self.cache.borrow_mut().insert(key, value.clone());
+        value
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        if let Some(value) = self.cache.borrow_mut().get(key) {
+            return value.is_some();
+        }
+
+        // there's no value to cache, so misses go straight to the backend every time
+        self.inner.has(key)
+    }
+
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        let mut cache = self.cache.borrow_mut();
+
+        // the keys missing from the cache are fetched in a single batch, each of them once
+        let mut missing: BTreeMap<&[u8], usize> = BTreeMap::new();
+        let mut batch = Vec::new();
+        let cached: Vec<_> = keys
+            .iter()
+            .map(|&key| {
+                if missing.contains_key(key) {
+                    // answered by the batch already going to the backend
+                    cache.stats.hits += 1;
+                    return None;
+                }
+
+                let value = cache.get(key);
+                if value.is_none() {
+                    missing.insert(key, batch.len());
+                    batch.push(key);
+                }
+                value
+            })
+            .collect();
+
+        if batch.is_empty() {
+            return cached.into_iter().flatten().collect();
+        }
+
+        let fetched = self.inner.get_many(&batch);
+        for (key, value) in batch.iter().zip(&fetched) {
+            cache.insert(key, value.clone());
+        }
+
+        keys.iter()
+            .zip(cached)
+            .map(|(key, value)| value.unwrap_or_else(|| fetched[missing[key]].clone()))
+            .collect()
+    }
+}
+
+impl<S: StorageBackendMut> StorageBackendMut for Cached<S> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.cache.get_mut().invalidate(key);
+        self.inner.set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.cache.get_mut().invalidate(key);
+        self.inner.remove(key)
+    }
+
+    fn write_batch<I>(&mut self, batch: I)
+    where
+        I: IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
+    {
+        let batch: Vec<_> = batch.into_iter().collect();
+        let cache = self.cache.get_mut();
+        for (key, _) in &batch {
+            cache.invalidate(key);
+        }
+
+        self.inner.write_batch(batch)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<S: IterableStorage> IterableStorage for Cached<S> {
+    type KeysIterator<'a>
+        = S::KeysIterator<'a>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = S::ValuesIterator<'a>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = S::PairsIterator<'a>
+    where
+        Self: 'a;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        self.inner.keys(start, end)
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        self.inner.values(start, end)
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        self.inner.pairs(start, end)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<S: RevIterableStorage> RevIterableStorage for Cached<S> {
+    type RevKeysIterator<'a>
+        = S::RevKeysIterator<'a>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = S::RevValuesIterator<'a>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        = S::RevPairsIterator<'a>
+    where
+        Self: 'a;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        self.inner.rev_keys(start, end)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        self.inner.rev_values(start, end)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        self.inner.rev_pairs(start, end)
+    }
+}
+
+struct Lru {
+    capacity: Capacity,
+    // cached values (`None` for missing keys), with the tick of their last use
+    entries: BTreeMap<Vec<u8>, (Option<Vec<u8>>, u64)>,
+    // the keys by tick of last use, least recent first
+    recency: BTreeMap<u64, Vec<u8>>,
+    tick: u64,
+    bytes: usize,
+    stats: CacheStats,
+}
+
+impl Lru {
+    fn new(capacity: Capacity) -> Self {
+        Self {
+            capacity,
+            entries: BTreeMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            bytes: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    fn size(key: &[u8], value: &Option<Vec<u8>>) -> usize {
+        key.len() + value.as_ref().map_or(0, Vec::len)
+    }
+
+    fn get(&mut self, key: &[u8]) -> Option<Option<Vec<u8>>> {
+        let Some((value, tick)) = self.entries.get_mut(key) else {
+            self.stats.misses += 1;
+            return None;
+        };
+        self.stats.hits += 1;
+
+        let key = self
+            .recency
+            .remove(tick)
+            .expect("cache entries have a tick");
+        self.tick += 1;
+        *tick = self.tick;
+        self.recency.insert(self.tick, key);
+
+        Some(value.clone())
+    }
+
+    fn insert(&mut self, key: &[u8], value: Option<Vec<u8>>) {
+        let size = Self::size(key, &value);
+        let fits = match self.capacity {
+            Capacity::Entries(max) => max > 0,
+            Capacity::Bytes(max) => size <= max,
+        };
+        if !fits {
+            return;
+        }
+
+        self.invalidate(key);
+        while self.is_full(size) {
+            let oldest = *self
+                .recency
+                .keys()
+                .next()
+                .expect("a full cache has entries");
+            let key = self
+                .recency
+                .remove(&oldest)
+                .expect("the tick was just found");
+            let (value, _) = self.entries.remove(&key).expect("ticks have an entry");
+            self.bytes -= Self::size(&key, &value);
+            self.stats.evictions += 1;
+        }
+
+        self.tick += 1;
+        self.bytes += size;
+        self.recency.insert(self.tick, key.to_vec());
+        self.entries.insert(key.to_vec(), (value, self.tick));
+    }
+
+    // whether an entry of `size` bytes needs room to be made
+    fn is_full(&self, size: usize) -> bool {
+        match self.capacity {
+            Capacity::Entries(max) => self.entries.len() >= max,
+            Capacity::Bytes(max) => self.bytes + size > max,
+        }
+    }
+
+    fn invalidate(&mut self, key: &[u8]) {
+        if let Some((value, tick)) = self.entries.remove(key) {
+            self.recency.remove(&tick);
+            self.bytes -= Self::size(key, &value);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.bytes = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::storage::Metered;
+
+    use storey_testing::backend::{CountingStorage, TestStorage};
+
+    fn storage() -> Metered<TestStorage> {
+        let mut storage = TestStorage::new();
+        for i in 0..10 {
+            storage.set(&[i], &[i; 4]);
+        }
+        Metered::new(storage)
+    }
+
+    #[test]
+    fn invalidation_on_write() {
+        let mut storage = storage();
+        let mut cached = Cached::new(&mut storage, Capacity::Entries(4));
+
+        assert_eq!(cached.get(&[1]), Some(vec![1; 4]));
+        assert_eq!(cached.get(&[1]), Some(vec![1; 4]));
+
+        cached.set(&[1], b"new");
+        assert_eq!(cached.get(&[1]), Some(b"new".to_vec()));
+        assert_eq!(cached.get(&[1]), Some(b"new".to_vec()));
+
+        cached.remove(&[1]);
+        assert_eq!(cached.get(&[1]), None);
+        assert!(!cached.has(&[1]));
+
+        cached.write_batch([(vec![1], Some(b"batch".to_vec()))]);
+        assert_eq!(cached.get(&[1]), Some(b"batch".to_vec()));
+
+        assert_eq!(
+            cached.stats(),
+            CacheStats {
+                hits: 3,
+                misses: 4,
+                evictions: 0
+            }
+        );
+        drop(cached);
+        assert_eq!(storage.report().total.reads, 4);
+    }
+
+    #[test]
+    fn negative_lookups() {
+        let storage = storage();
+        let cached = Cached::new(&storage, Capacity::Entries(4));
+
+        for _ in 0..5 {
+            assert_eq!(cached.get(&[42]), None);
+            assert!(!cached.has(&[42]));
+        }
+
+        assert_eq!(storage.report().total.reads, 1);
+        assert_eq!(cached.stats().misses, 1);
+        assert_eq!(cached.stats().hits, 9);
+        assert_eq!(cached.len(), 1);
+    }
+
+    #[test]
+    fn lru_eviction() {
+        let storage = storage();
+        let cached = Cached::new(&storage, Capacity::Entries(3));
+
+        cached.get(&[0]);
+        cached.get(&[1]);
+        cached.get(&[2]);
+        // 0 is now the most recently used
+        cached.get(&[0]);
+        // evicts 1
+        cached.get(&[3]);
+        assert_eq!(cached.len(), 3);
+        assert_eq!(cached.stats().evictions, 1);
+
+        let reads = storage.report().total.reads;
+        cached.get(&[0]);
+        cached.get(&[2]);
+        cached.get(&[3]);
+        assert_eq!(storage.report().total.reads, reads);
+        cached.get(&[1]);
+        assert_eq!(storage.report().total.reads, reads + 1);
+    }
+
+    #[test]
+    fn byte_capacity() {
+        let mut storage = storage();
+        storage.set(&[20], &[0; 100]);
+
+        // each small entry is 5 bytes
+        let cached = Cached::new(&storage, Capacity::Bytes(12));
+        cached.get(&[0]);
+        cached.get(&[1]);
+        assert_eq!(cached.len(), 2);
+        cached.get(&[2]);
+        assert_eq!(cached.len(), 2);
+        assert_eq!(cached.stats().evictions, 1);
+
+        // too big to be cached at all
+        cached.get(&[20]);
+        cached.get(&[20]);
+        assert_eq!(cached.stats().hits, 0);
+        assert_eq!(cached.len(), 2);
+    }
+
+    #[test]
+    fn has() {
+        let mut storage = CountingStorage::new();
+        storage.set(&[0], &[0; 4]);
+        let cached = Cached::new(&storage, Capacity::Entries(16));
+
+        cached.get(&[0]);
+        storage.reset();
+
+        // cached reads answer without the backend, the rest are checked without reading
+        assert!(cached.has(&[0]));
+        assert!(!cached.has(&[1]));
+        assert_eq!(storage.checks(), 1);
+        assert_eq!(storage.gets(), 0);
+    }
+
+    #[test]
+    fn get_many() {
+        let mut storage = CountingStorage::new();
+        for i in 0..10 {
+            storage.set(&[i], &[i; 4]);
+        }
+        let cached = Cached::new(&storage, Capacity::Entries(16));
+
+        cached.get(&[1]);
+        storage.reset();
+
+        assert_eq!(
+            cached.get_many(&[&[2], &[1], &[42], &[2], &[3]]),
+            [
+                Some(vec![2; 4]),
+                Some(vec![1; 4]),
+                None,
+                Some(vec![2; 4]),
+                Some(vec![3; 4])
+            ]
+        );
+        // the misses are fetched in one batch, each key once
+        assert_eq!(storage.batches(), 1);
+        assert_eq!(storage.gets(), 0);
+        assert_eq!(cached.stats().misses, 1 + 3);
+        assert_eq!(cached.stats().hits, 2);
+
+        // everything is cached now
+        assert_eq!(cached.get_many(&[&[42], &[3]]), [None, Some(vec![3; 4])]);
+        assert_eq!(storage.batches(), 1);
+    }
+
+    #[test]
+    fn iteration_is_not_cached() {
+        let storage = storage();
+        let cached = Cached::new(&storage, Capacity::Entries(16));
+
+        assert_eq!(cached.keys(Some(&[3]), Some(&[6])).count(), 3);
+        assert_eq!(cached.rev_values(None, None).count(), 10);
+        assert!(cached.is_empty());
+        assert_eq!(cached.stats(), CacheStats::default());
+    }
+}
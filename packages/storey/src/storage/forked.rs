@@ -0,0 +1,428 @@
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+#[cfg(feature = "iteration")]
+use core::cmp::Ordering;
+#[cfg(feature = "iteration")]
+use core::iter::Peekable;
+
+use super::transaction::Overlay;
+#[cfg(feature = "iteration")]
+use super::transaction::Project;
+#[cfg(feature = "iteration")]
+use crate::storage::{IterableStorage, RevIterableStorage};
+use crate::storage::{StorageBackend, StorageBackendMut};
+
+/// A storage backend layering a local overlay backend over a base backend.
+///
+/// Writes only go to the overlay. Reads check the overlay first and fall back to the base,
+/// and removing a key masks it in the base too. The base is never written to, so it can be a
+/// read-only snapshot of some state, for example one fetched from a remote node, and the fork
+/// can be used to simulate changes to that state.
+///
+/// Unlike a [`Transaction`](super::Transaction), the overlay is a backend of its own, and there's
+/// nothing to commit. The set of removed keys is kept in memory by the fork.
+///
+/// When reading from the base is expensive, [`with_memoized_reads`](Self::with_memoized_reads)
+/// keeps the results of point reads from the base in memory, so that each key is only fetched
+/// once. Iteration always goes to both layers.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::{Item, Map};
+/// use storey::storage::Forked;
+///
+/// let balances = Map::<String, Item<u64, TestEncoding>>::new(0);
+///
+/// let mut snapshot = TestStorage::new();
+/// balances.access(&mut snapshot).entry_mut("alice").set(&10).unwrap();
+///
+/// let mut fork = Forked::new(&snapshot, TestStorage::new());
+/// balances.access(&mut fork).entry_mut("alice").set(&6).unwrap();
+/// balances.access(&mut fork).entry_mut("bob").set(&4).unwrap();
+///
+/// assert_eq!(balances.access(&fork).entry("alice").get().unwrap(), Some(6));
+/// assert_eq!(balances.access(&snapshot).entry("alice").get().unwrap(), Some(10));
+/// assert_eq!(balances.access(&snapshot).entry("bob").get().unwrap(), None);
+/// ```
+pub struct Forked<B, O> {
+    base: B,
+    overlay: O,
+    removed: BTreeSet<Vec<u8>>,
+    memo: Option<RefCell<Overlay>>,
+}
+
+impl<B, O> Forked<B, O> {
+    /// Fork `base`, writing to `overlay`.
+    pub fn new(base: B, overlay: O) -> Self {
+        Self {
+            base,
+            overlay,
+            removed: BTreeSet::new(),
+            memo: None,
+        }
+    }
+
+    /// Fork `base`, writing to `overlay`, and remember the point reads made from `base`.
+    ///
+    /// Changes made to `base` by other means while the fork exists might not be seen.
+    pub fn with_memoized_reads(base: B, overlay: O) -> Self {
+        Self {
+            memo: Some(RefCell::new(Overlay::new())),
+            ..Self::new(base, overlay)
+        }
+    }
+
+    /// Get a reference to the base backend.
+    pub fn base(&self) -> &B {
+        &self.base
+    }
+
+    /// Get a reference to the overlay backend.
+    pub fn overlay(&self) -> &O {
+        &self.overlay
+    }
+
+    /// Iterate over the keys removed from the fork, in order. These keys are masked in the base.
+    pub fn removed(&self) -> impl Iterator<Item = &[u8]> {
+        self.removed.iter().map(Vec::as_slice)
+    }
+
+    /// Get the base and the overlay back.
+    pub fn into_parts(self) -> (B, O) {
+        (self.base, self.overlay)
+    }
+}
+
+impl<B: StorageBackend, O> Forked<B, O> {
+    fn get_base(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let Some(memo) = &self.memo else {
+            return self.base.get(key);
+        };
+
+        if let Some(value) = memo.borrow().get(key) {
+            return value.clone();
+        }
+
+        let value = self.base.get(key);
+        memo.borrow_mut().insert(key.to_vec(), value.clone());
+        value
+    }
+
+    fn has_base(&self, key: &[u8]) -> bool {
+        let memoized =
+            (self.memo.as_ref()).and_then(|memo| memo.borrow().get(key).map(Option::is_some));
+
+        memoized.unwrap_or_else(|| self.base.has(key))
+    }
+}
+
+impl<B: StorageBackend, O: StorageBackend> StorageBackend for Forked<B, O> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        if self.removed.contains(key) {
+            return None;
+        }
+
+        self.overlay.get(key).or_else(|| self.get_base(key))
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        !self.removed.contains(key) && (self.overlay.has(key) || self.has_base(key))
+    }
+}
+
+impl<B: StorageBackend, O: StorageBackendMut> StorageBackendMut for Forked<B, O> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.removed.remove(key);
+        self.overlay.set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.removed.insert(key.to_vec());
+        self.overlay.remove(key)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<B: IterableStorage, O: IterableStorage> IterableStorage for Forked<B, O> {
+    type KeysIterator<'a>
+        = core::iter::Map<Self::PairsIterator<'a>, Project>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = core::iter::Map<Self::PairsIterator<'a>, Project>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = ForkIter<'a, B::PairsIterator<'a>, O::PairsIterator<'a>>
+    where
+        Self: 'a;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        self.pairs(start, end).map(|(k, _)| k)
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        self.pairs(start, end).map(|(_, v)| v)
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        ForkIter::new(
+            self.base.pairs(start, end),
+            self.overlay.pairs(start, end),
+            &self.removed,
+            Ordering::Less,
+        )
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<B: RevIterableStorage, O: RevIterableStorage> RevIterableStorage for Forked<B, O> {
+    type RevKeysIterator<'a>
+        = core::iter::Map<Self::RevPairsIterator<'a>, Project>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = core::iter::Map<Self::RevPairsIterator<'a>, Project>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        = ForkIter<'a, B::RevPairsIterator<'a>, O::RevPairsIterator<'a>>
+    where
+        Self: 'a;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        self.rev_pairs(start, end).map(|(k, _)| k)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        self.rev_pairs(start, end).map(|(_, v)| v)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        ForkIter::new(
+            self.base.rev_pairs(start, end),
+            self.overlay.rev_pairs(start, end),
+            &self.removed,
+            Ordering::Greater,
+        )
+    }
+}
+
+/// An iterator merging the pairs of the two layers of a [`Forked`] backend.
+///
+/// Both iterators must be sorted in the same direction. Overlay entries win over base entries
+/// with the same key, and removed keys are skipped in the base.
+#[cfg(feature = "iteration")]
+pub struct ForkIter<'a, B, O>
+where
+    B: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+    O: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+{
+    base: Peekable<B>,
+    overlay: Peekable<O>,
+    removed: &'a BTreeSet<Vec<u8>>,
+    // the ordering between a key and the keys that come after it
+    order: Ordering,
+}
+
+#[cfg(feature = "iteration")]
+impl<'a, B, O> ForkIter<'a, B, O>
+where
+    B: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+    O: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+{
+    fn new(base: B, overlay: O, removed: &'a BTreeSet<Vec<u8>>, order: Ordering) -> Self {
+        Self {
+            base: base.peekable(),
+            overlay: overlay.peekable(),
+            removed,
+            order,
+        }
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<B, O> Iterator for ForkIter<'_, B, O>
+where
+    B: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+    O: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+{
+    type Item = (Vec<u8>, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let from_base = match (self.base.peek(), self.overlay.peek()) {
+                (None, None) => return None,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (Some((base_key, _)), Some((overlay_key, _))) => {
+                    match base_key.cmp(overlay_key) {
+                        // shadowed by the overlay
+                        Ordering::Equal => {
+                            self.base.next();
+                            false
+                        }
+                        ordering => ordering == self.order,
+                    }
+                }
+            };
+
+            if !from_base {
+                return self.overlay.next();
+            }
+
+            let (key, value) = self.base.next()?;
+            if !self.removed.contains(&key) {
+                return Some((key, value));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::{Item, IterableAccessor as _, Map};
+    use crate::storage::Metered;
+
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
+
+    fn base() -> TestStorage {
+        let mut base = TestStorage::new();
+        base.set(&[0], b"base0");
+        base.set(&[1], b"base1");
+        base.set(&[3], b"base3");
+        base.set(&[5], b"base5");
+        base
+    }
+
+    #[test]
+    fn reads_fall_through() {
+        let base = base();
+        let mut fork = Forked::new(&base, TestStorage::new());
+
+        fork.set(&[1], b"fork1");
+        fork.set(&[2], b"fork2");
+        fork.remove(&[3]);
+        fork.remove(&[4]);
+
+        assert_eq!(fork.get(&[0]), Some(b"base0".to_vec()));
+        assert_eq!(fork.get(&[1]), Some(b"fork1".to_vec()));
+        assert_eq!(fork.get(&[2]), Some(b"fork2".to_vec()));
+        assert_eq!(fork.get(&[3]), None);
+        assert!(!fork.has(&[4]));
+        assert_eq!(fork.removed().collect::<Vec<_>>(), [&[3][..], &[4][..]]);
+
+        // a removed key can be set again
+        fork.set(&[3], b"fork3");
+        assert_eq!(fork.get(&[3]), Some(b"fork3".to_vec()));
+
+        let (_, overlay) = fork.into_parts();
+        assert_eq!(
+            base.pairs(None, None).collect::<Vec<_>>(),
+            self::base().pairs(None, None).collect::<Vec<_>>()
+        );
+        assert_eq!(overlay.get(&[0]), None);
+        assert_eq!(overlay.get(&[3]), Some(b"fork3".to_vec()));
+    }
+
+    #[test]
+    fn iteration() {
+        let base = base();
+        let mut fork = Forked::new(&base, TestStorage::new());
+
+        fork.set(&[1], b"fork1");
+        fork.set(&[2], b"fork2");
+        fork.set(&[6], b"fork6");
+        fork.remove(&[3]);
+        fork.remove(&[0]);
+
+        assert_eq!(
+            fork.pairs(None, None).collect::<Vec<_>>(),
+            [
+                (vec![1], b"fork1".to_vec()),
+                (vec![2], b"fork2".to_vec()),
+                (vec![5], b"base5".to_vec()),
+                (vec![6], b"fork6".to_vec()),
+            ]
+        );
+        assert_eq!(
+            fork.rev_keys(None, None).collect::<Vec<_>>(),
+            [vec![6], vec![5], vec![2], vec![1]]
+        );
+        assert_eq!(
+            fork.values(Some(&[2]), Some(&[6])).collect::<Vec<_>>(),
+            [b"fork2".to_vec(), b"base5".to_vec()]
+        );
+        assert_eq!(
+            fork.rev_values(Some(&[0]), Some(&[3])).collect::<Vec<_>>(),
+            [b"fork2".to_vec(), b"fork1".to_vec()]
+        );
+    }
+
+    #[test]
+    fn memoized_reads() {
+        let base = Metered::new(base());
+        let fork = Forked::with_memoized_reads(&base, TestStorage::new());
+
+        for _ in 0..3 {
+            assert_eq!(fork.get(&[0]), Some(b"base0".to_vec()));
+            assert_eq!(fork.get(&[9]), None);
+        }
+        assert_eq!(base.report().total.reads, 2);
+
+        let fork = Forked::new(&base, TestStorage::new());
+        for _ in 0..3 {
+            assert_eq!(fork.get(&[0]), Some(b"base0".to_vec()));
+        }
+        assert_eq!(base.report().total.reads, 5);
+    }
+
+    #[test]
+    fn containers() {
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+
+        let mut base = TestStorage::new();
+        map.access(&mut base).entry_mut("a").set(&1).unwrap();
+        map.access(&mut base).entry_mut("b").set(&2).unwrap();
+        map.access(&mut base).entry_mut("c").set(&3).unwrap();
+
+        let mut fork = Forked::new(&base, TestStorage::new());
+        map.access(&mut fork).entry_mut("b").set(&20).unwrap();
+        map.access(&mut fork).entry_mut("d").set(&4).unwrap();
+        fork.remove(&[&[0][..], &crate::key!("a")].concat());
+
+        assert_eq!(
+            map.access(&fork)
+                .pairs()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            [
+                (("b".to_string(), ()), 20),
+                (("c".to_string(), ()), 3),
+                (("d".to_string(), ()), 4)
+            ]
+        );
+        assert_eq!(
+            map.access(&base).values().collect::<Result<Vec<_>, _>>(),
+            Ok(vec![1, 2, 3])
+        );
+    }
+}
@@ -0,0 +1,434 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+#[cfg(feature = "iteration")]
+use crate::storage::{IterableStorage, RevIterableStorage};
+use crate::storage::{StorageBackend, StorageBackendMut};
+
+/// A storage backend wrapper counting the operations made on the backend it wraps, and the
+/// bytes moved by them.
+///
+/// The counters are available through [`report`](Self::report). Besides the totals, they can
+/// be broken down by container: [`with_prefixes`](Self::with_prefixes) registers the prefixes
+/// of the top-level containers to keep separate counters for. Metadata of a container (like
+/// the length of a column) is counted towards the container.
+///
+/// It can wrap a backend, or a (mutable) reference to one.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::{Column, Item};
+/// use storey::storage::Metered;
+///
+/// let item = Item::<u64, TestEncoding>::new(0);
+/// let column = Column::<u64, TestEncoding>::new(1);
+///
+/// let mut metered = Metered::with_prefixes(TestStorage::new(), [0, 1]);
+/// item.access(&mut metered).set(&1).unwrap();
+/// column.access(&mut metered).push(&1).unwrap();
+///
+/// let report = metered.report();
+/// assert_eq!(report.prefixes[&0].writes, 1);
+/// // the value, and the column's metadata
+/// assert_eq!(report.prefixes[&1].writes, 3);
+/// assert_eq!(report.total.writes, 4);
+/// ```
+pub struct Metered<S> {
+    inner: S,
+    report: RefCell<Report>,
+}
+
+/// The counters collected by [`Metered`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// The counters for all operations.
+    pub total: Counters,
+
+    /// The counters for the operations under each registered prefix.
+    pub prefixes: BTreeMap<u8, Counters>,
+}
+
+/// Operation and byte counters.
+///
+/// Bytes are counted in the direction they travel: the keys and values coming out of the
+/// storage are read, the keys and values going in are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counters {
+    /// Point reads (`get` and `has`).
+    pub reads: u64,
+
+    /// Writes (`set`).
+    pub writes: u64,
+
+    /// Removals.
+    pub removes: u64,
+
+    /// Iterations started.
+    pub iterations: u64,
+
+    /// Items pulled from iterators.
+    pub iteration_steps: u64,
+
+    /// Bytes of the keys read, and of the keys yielded by iteration.
+    pub key_bytes_read: u64,
+
+    /// Bytes of the values read, including the ones yielded by iteration.
+    pub value_bytes_read: u64,
+
+    /// Bytes of the keys written or removed.
+    pub key_bytes_written: u64,
+
+    /// Bytes of the values written.
+    pub value_bytes_written: u64,
+}
+
+impl<S> Metered<S> {
+    /// Start metering the operations made on `inner`.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            report: RefCell::new(Report::default()),
+        }
+    }
+
+    /// Start metering the operations made on `inner`, with separate counters for each of
+    /// `prefixes`.
+    pub fn with_prefixes(inner: S, prefixes: impl IntoIterator<Item = u8>) -> Self {
+        let report = Report {
+            total: Counters::default(),
+            prefixes: prefixes
+                .into_iter()
+                .map(|prefix| (prefix, Counters::default()))
+                .collect(),
+        };
+
+        Self {
+            inner,
+            report: RefCell::new(report),
+        }
+    }
+
+    /// Get the counters collected so far.
+    pub fn report(&self) -> Report {
+        self.report.borrow().clone()
+    }
+
+    /// Set all the counters back to zero.
+    pub fn reset(&self) {
+        let mut report = self.report.borrow_mut();
+        report.total = Counters::default();
+        report
+            .prefixes
+            .values_mut()
+            .for_each(|c| *c = Counters::default());
+    }
+
+    /// Get the wrapped backend back.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn count(&self, key: Option<&[u8]>, f: impl Fn(&mut Counters)) {
+        count(&self.report, key.and_then(container_prefix), f)
+    }
+
+    #[cfg(feature = "iteration")]
+    fn meter_iter<I: Iterator>(
+        &self,
+        inner: I,
+        start: Option<&[u8]>,
+        measure: Measure<I::Item>,
+    ) -> MeteredIter<'_, I> {
+        let prefix = start.and_then(container_prefix);
+        count(&self.report, prefix, |c| c.iterations += 1);
+
+        MeteredIter {
+            inner,
+            report: &self.report,
+            prefix,
+            measure,
+        }
+    }
+}
+
+/// The prefix of the top-level container owning `key`, looking through the metadata
+/// namespace.
+fn container_prefix(key: &[u8]) -> Option<u8> {
+    match key {
+        [255, prefix, ..] => Some(*prefix),
+        [prefix, ..] => Some(*prefix),
+        [] => None,
+    }
+}
+
+fn count(report: &RefCell<Report>, prefix: Option<u8>, f: impl Fn(&mut Counters)) {
+    let mut report = report.borrow_mut();
+    f(&mut report.total);
+    if let Some(counters) = prefix.and_then(|prefix| report.prefixes.get_mut(&prefix)) {
+        f(counters);
+    }
+}
+
+fn len(bytes: usize) -> u64 {
+    bytes as u64
+}
+
+impl<S: StorageBackend> StorageBackend for Metered<S> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let value = self.inner.get(key);
+        self.count(Some(key), |c| {
+            c.reads += 1;
+            c.key_bytes_read += len(key.len());
+            c.value_bytes_read += len(value.as_ref().map_or(0, Vec::len));
+        });
+        value
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        self.count(Some(key), |c| {
+            c.reads += 1;
+            c.key_bytes_read += len(key.len());
+        });
+        self.inner.has(key)
+    }
+}
+
+impl<S: StorageBackendMut> StorageBackendMut for Metered<S> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.count(Some(key), |c| {
+            c.writes += 1;
+            c.key_bytes_written += len(key.len());
+            c.value_bytes_written += len(value.len());
+        });
+        self.inner.set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.count(Some(key), |c| {
+            c.removes += 1;
+            c.key_bytes_written += len(key.len());
+        });
+        self.inner.remove(key)
+    }
+
+    fn write_batch<I>(&mut self, batch: I)
+    where
+        I: IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
+    {
+        let batch: Vec<_> = batch.into_iter().collect();
+        for (key, value) in &batch {
+            self.count(Some(key), |c| {
+                match value {
+                    Some(value) => {
+                        c.writes += 1;
+                        c.value_bytes_written += len(value.len());
+                    }
+                    None => c.removes += 1,
+                }
+                c.key_bytes_written += len(key.len());
+            });
+        }
+
+        self.inner.write_batch(batch)
+    }
+}
+
+// The key and value lengths of an item yielded by iteration.
+#[cfg(feature = "iteration")]
+type Measure<T> = fn(&T) -> (usize, usize);
+
+#[cfg(feature = "iteration")]
+const KEY: Measure<Vec<u8>> = |key| (key.len(), 0);
+#[cfg(feature = "iteration")]
+const VALUE: Measure<Vec<u8>> = |value| (0, value.len());
+#[cfg(feature = "iteration")]
+const PAIR: Measure<(Vec<u8>, Vec<u8>)> = |(key, value)| (key.len(), value.len());
+
+#[cfg(feature = "iteration")]
+impl<S: IterableStorage> IterableStorage for Metered<S> {
+    type KeysIterator<'a>
+        = MeteredIter<'a, S::KeysIterator<'a>>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = MeteredIter<'a, S::ValuesIterator<'a>>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = MeteredIter<'a, S::PairsIterator<'a>>
+    where
+        Self: 'a;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        self.meter_iter(self.inner.keys(start, end), start, KEY)
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        self.meter_iter(self.inner.values(start, end), start, VALUE)
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        self.meter_iter(self.inner.pairs(start, end), start, PAIR)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<S: RevIterableStorage> RevIterableStorage for Metered<S> {
+    type RevKeysIterator<'a>
+        = MeteredIter<'a, S::RevKeysIterator<'a>>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = MeteredIter<'a, S::RevValuesIterator<'a>>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        = MeteredIter<'a, S::RevPairsIterator<'a>>
+    where
+        Self: 'a;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        self.meter_iter(self.inner.rev_keys(start, end), start, KEY)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        self.meter_iter(self.inner.rev_values(start, end), start, VALUE)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        self.meter_iter(self.inner.rev_pairs(start, end), start, PAIR)
+    }
+}
+
+/// An iterator counting the items pulled from it into the report of a [`Metered`].
+///
+/// The items are counted towards the container the iteration started in.
+#[cfg(feature = "iteration")]
+pub struct MeteredIter<'a, I: Iterator> {
+    inner: I,
+    report: &'a RefCell<Report>,
+    prefix: Option<u8>,
+    measure: Measure<I::Item>,
+}
+
+#[cfg(feature = "iteration")]
+impl<I: Iterator> Iterator for MeteredIter<'_, I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+
+        let (key_len, value_len) = (self.measure)(&item);
+        count(self.report, self.prefix, |c| {
+            c.iteration_steps += 1;
+            c.key_bytes_read += len(key_len);
+            c.value_bytes_read += len(value_len);
+        });
+
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::{Column, Item, IterableAccessor as _, Map};
+
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
+
+    #[test]
+    fn scripted() {
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let column = Column::<u64, TestEncoding>::new(1);
+        let item = Item::<u64, TestEncoding>::new(2);
+
+        let mut storage = TestStorage::new();
+        let mut metered = Metered::with_prefixes(&mut storage, [0, 1]);
+
+        // 2 writes, keys of 5 bytes
+        map.access(&mut metered).entry_mut("foo").set(&1).unwrap();
+        map.access(&mut metered).entry_mut("bar").set(&2).unwrap();
+        // 2 pushes: each reads and writes the 3 byte length and next index metadata keys, and
+        // writes a 5 byte entry
+        column.access(&mut metered).push(&10).unwrap();
+        column.access(&mut metered).push(&20).unwrap();
+        // 1 write, 1 read of the 1 byte key
+        item.access(&mut metered).set(&7).unwrap();
+        item.access(&metered).get().unwrap();
+        // a hit and a miss
+        map.access(&metered).entry("foo").get().unwrap();
+        map.access(&metered).entry("baz").get().unwrap();
+        // 2 items, 5 byte keys
+        assert_eq!(map.access(&metered).keys().count(), 2);
+        // stopped after 1 item
+        let column_access = column.access(&metered);
+        column_access.values().next().unwrap().unwrap();
+
+        let report = metered.report();
+
+        assert_eq!(
+            report.prefixes[&0],
+            Counters {
+                reads: 2,
+                writes: 2,
+                removes: 0,
+                iterations: 1,
+                iteration_steps: 2,
+                key_bytes_read: 5 + 5 + 5 + 5,
+                value_bytes_read: 8,
+                key_bytes_written: 5 + 5,
+                value_bytes_written: 8 + 8,
+            }
+        );
+        assert_eq!(
+            report.prefixes[&1],
+            Counters {
+                reads: 4,
+                writes: 6,
+                removes: 0,
+                iterations: 1,
+                iteration_steps: 1,
+                key_bytes_read: 4 * 3,
+                value_bytes_read: 4 + 4 + 8,
+                key_bytes_written: 2 * (5 + 3 + 3),
+                value_bytes_written: 2 * (8 + 4 + 4),
+            }
+        );
+        assert!(!report.prefixes.contains_key(&2));
+        assert_eq!(
+            report.total,
+            Counters {
+                reads: 2 + 4 + 1,
+                writes: 2 + 6 + 1,
+                removes: 0,
+                iterations: 2,
+                iteration_steps: 3,
+                key_bytes_read: 20 + 12 + 1,
+                value_bytes_read: 8 + 16 + 8,
+                key_bytes_written: 10 + 22 + 1,
+                value_bytes_written: 16 + 32 + 8,
+            }
+        );
+
+        metered.reset();
+        assert_eq!(metered.report().total, Counters::default());
+        assert_eq!(metered.report().prefixes[&0], Counters::default());
+    }
+}
@@ -2,20 +2,57 @@
 //!
 //! [`StorageBackend`] and [`StorageBackendMut`] are for accessing the fundamental binary
 //! key-value storage. You only need to interact with them if you're integrating `storey` with
-//! a new storage backend.
+//! a new storage backend. [`DynStorage`] is a dyn-compatible equivalent of those (and the
+//! iteration traits), for choosing a backend at runtime. A `BTreeMap<Vec<u8>, Vec<u8>>` is a
+//! backend out of the box, and so is a `HashMap` of bytes (with `std`), minus iteration.
 //!
 //! [`Storage`] and [`StorageMut`] provide a common interface for any binary storage type,
 //! including a storage backend or a storage branch (namespace). Similarly, [`RevIterableStorage`]
-//! and [`IterableStorage`] represent binary storage types that provide iteration. These traits
-//! are something you might be interested in if you're implementing a new container.
+//! and [`IterableStorage`] represent binary storage types that provide iteration, and
+//! [`RemoveRangeStorage`] ones that can remove a range of keys at once. These traits are
+//! something you might be interested in if you're implementing a new container.
 //!
 //! [`StorageBranch`] is a storage namespace. It can be used to divide a backend's key namespace
 //! into smaller namespaces. This is a fundamental building block for the hierarchy of storage
-//! containers. You only need to be aware of it if you're implementing a new container.
+//! containers. You only need to be aware of it if you're implementing a new container, or
+//! accessing prefixed raw storage directly. It's also exported from the crate root.
+//!
+//! [`Transaction`] buffers writes to a storage backend, so that they can be committed or
+//! rolled back together. [`Forked`] layers a writable overlay over a base backend that's never
+//! written to. [`Buffered`] batches writes to a backend where each call is
+//! expensive, and [`Cached`] caches reads from one. [`ReadOnly`] is a view of a backend that can't be written to. [`Traced`] records
+//! the calls made to a backend, for debugging, and [`Metered`] counts them. [`Recording`]
+//! collects the keys read and written, for building witnesses. [`Shared`] lets several
+//! threads read and write one backend, with the `std` feature.
 
 mod branch;
+mod buffered;
+mod cached;
+mod forked;
+mod metered;
+mod read_only;
+mod recording;
+#[cfg(feature = "std")]
+mod shared;
+mod traced;
+mod transaction;
 
-pub use branch::StorageBranch;
+#[cfg(feature = "iteration")]
+pub(crate) use branch::prefix_end;
+pub use branch::{InvalidPrefix, StorageBranch};
+pub use buffered::Buffered;
+pub use cached::{CacheStats, Cached, Capacity};
+pub use forked::Forked;
+pub use metered::{Counters, Metered, Report};
+pub use read_only::ReadOnly;
+pub use recording::{Accesses, ReadRange, Recording};
+#[cfg(feature = "std")]
+pub use shared::Shared;
+pub use storey_storage::{ComposedKey, Storage, StorageBackend, StorageBackendMut, StorageMut};
+#[cfg(feature = "iteration")]
 pub use storey_storage::{
-    IterableStorage, RevIterableStorage, Storage, StorageBackend, StorageBackendMut, StorageMut,
+    DynIterableStorage, DynRevIterableStorage, DynStorage, IterableStorage, RemoveRangeStorage,
+    RevIterableStorage,
 };
+pub use traced::{IterKind, Op, Traced};
+pub use transaction::{Savepoint, Transaction};
@@ -0,0 +1,175 @@
+use crate::storage::StorageBackend;
+#[cfg(feature = "iteration")]
+use crate::storage::{IterableStorage, RevIterableStorage};
+use alloc::vec::Vec;
+
+/// A read-only view of a storage backend.
+///
+/// This only implements the read and iteration traits, so containers accessed through it can
+/// be read and iterated over, but any attempt to write fails to compile. That's a way to make
+/// sure code that must not write (like a query handler) doesn't.
+///
+/// It can wrap a backend, or a reference to one.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::{Item, IterableAccessor as _, Map};
+/// use storey::storage::ReadOnly;
+///
+/// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+///
+/// let mut storage = TestStorage::new();
+/// map.access(&mut storage).entry_mut("foo").set(&1337).unwrap();
+///
+/// let read_only = ReadOnly::new(&storage);
+/// assert_eq!(map.access(&read_only).entry("foo").get(), Ok(Some(1337)));
+/// assert_eq!(map.access(&read_only).keys().count(), 1);
+/// ```
+///
+/// Writing doesn't compile:
+/// ```compile_fail
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// # use storey::containers::{Item, Map};
+/// # use storey::storage::ReadOnly;
+/// # let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+/// # let storage = TestStorage::new();
+/// let mut read_only = ReadOnly::new(&storage);
+/// map.access(&mut read_only).entry_mut("foo").set(&1337).unwrap();
+/// ```
+pub struct ReadOnly<S>(S);
+
+impl<S> ReadOnly<S> {
+    /// Wrap `storage` in a read-only view.
+    pub fn new(storage: S) -> Self {
+        Self(storage)
+    }
+
+    /// Get the wrapped storage back.
+    pub fn into_inner(self) -> S {
+        self.0
+    }
+}
+
+impl<S: StorageBackend> From<S> for ReadOnly<S> {
+    fn from(storage: S) -> Self {
+        Self::new(storage)
+    }
+}
+
+impl<S: StorageBackend> StorageBackend for ReadOnly<S> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.get(key)
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        self.0.has(key)
+    }
+
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        self.0.get_many(keys)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<S: IterableStorage> IterableStorage for ReadOnly<S> {
+    type KeysIterator<'a>
+        = S::KeysIterator<'a>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = S::ValuesIterator<'a>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = S::PairsIterator<'a>
+    where
+        Self: 'a;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        self.0.keys(start, end)
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        self.0.values(start, end)
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        self.0.pairs(start, end)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<S: RevIterableStorage> RevIterableStorage for ReadOnly<S> {
+    type RevKeysIterator<'a>
+        = S::RevKeysIterator<'a>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = S::RevValuesIterator<'a>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        = S::RevPairsIterator<'a>
+    where
+        Self: 'a;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        self.0.rev_keys(start, end)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        self.0.rev_values(start, end)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        self.0.rev_pairs(start, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::{Column, IterableAccessor as _};
+
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
+
+    #[test]
+    fn reads_and_iterates() {
+        let mut storage = TestStorage::new();
+
+        let column = Column::<u64, TestEncoding>::new(0);
+        column.access(&mut storage).push(&1).unwrap();
+        column.access(&mut storage).push(&2).unwrap();
+
+        let read_only = ReadOnly::from(&storage);
+        let access = column.access(&read_only);
+
+        assert_eq!(access.len(), Ok(2));
+        assert_eq!(access.get(1), Ok(Some(2)));
+        assert_eq!(
+            access.values().collect::<Result<Vec<_>, _>>(),
+            Ok(vec![1, 2])
+        );
+        assert_eq!(
+            read_only.rev_keys(None, None).collect::<Vec<_>>(),
+            storage.rev_keys(None, None).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,375 @@
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+#[cfg(feature = "iteration")]
+use super::transaction::Project;
+#[cfg(feature = "iteration")]
+use crate::storage::{IterableStorage, RevIterableStorage};
+use crate::storage::{StorageBackend, StorageBackendMut};
+
+/// A storage backend wrapper recording the set of keys read and written.
+///
+/// This is meant for generating witnesses: after running some code against it,
+/// [`accesses`](Self::accesses) tells exactly which keys the code depended on and which ones it
+/// changed. Unlike [`Traced`](super::Traced), it doesn't keep a log of operations, just the
+/// (deduplicated) keys, and the bounds of the range reads made by iteration, so that a verifier
+/// can reproduce them.
+///
+/// Iterating over values only still records the keys visited. To do that, it reads the pairs
+/// from the wrapped backend.
+///
+/// It can wrap a backend, or a (mutable) reference to one.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::Item;
+/// use storey::storage::Recording;
+///
+/// let item = Item::<u64, TestEncoding>::new(0);
+///
+/// let mut recording = Recording::new(TestStorage::new());
+/// let value = item.access(&recording).get().unwrap().unwrap_or(0);
+/// item.access(&mut recording).set(&(value + 1)).unwrap();
+///
+/// let accesses = recording.accesses();
+/// assert_eq!(accesses.reads, [vec![0]]);
+/// assert_eq!(accesses.writes, [vec![0]]);
+/// assert!(accesses.ranges.is_empty());
+/// ```
+pub struct Recording<S> {
+    inner: S,
+    reads: RefCell<BTreeSet<Vec<u8>>>,
+    ranges: RefCell<BTreeSet<ReadRange>>,
+    writes: BTreeSet<Vec<u8>>,
+}
+
+/// The keys accessed through a [`Recording`], sorted and deduplicated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Accesses {
+    /// The keys read, either directly or by visiting them during iteration. Reads of missing
+    /// keys are included.
+    pub reads: Vec<Vec<u8>>,
+
+    /// The range reads made by iteration.
+    pub ranges: Vec<ReadRange>,
+
+    /// The keys written or removed.
+    pub writes: Vec<Vec<u8>>,
+}
+
+/// The bounds of a range read, as passed to the iteration methods.
+///
+/// The range is inclusive for `start` and exclusive for `end`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ReadRange {
+    pub start: Option<Vec<u8>>,
+    pub end: Option<Vec<u8>>,
+    pub reverse: bool,
+}
+
+impl<S> Recording<S> {
+    /// Start recording the keys accessed in `inner`.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            reads: RefCell::new(BTreeSet::new()),
+            ranges: RefCell::new(BTreeSet::new()),
+            writes: BTreeSet::new(),
+        }
+    }
+
+    /// Get the keys accessed so far.
+    pub fn accesses(&self) -> Accesses {
+        Accesses {
+            reads: self.reads.borrow().iter().cloned().collect(),
+            ranges: self.ranges.borrow().iter().cloned().collect(),
+            writes: self.writes.iter().cloned().collect(),
+        }
+    }
+
+    /// Get the wrapped backend back.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn read(&self, key: &[u8]) {
+        if !self.reads.borrow().contains(key) {
+            self.reads.borrow_mut().insert(key.to_vec());
+        }
+    }
+
+    #[cfg(feature = "iteration")]
+    fn record_iter<I>(
+        &self,
+        inner: I,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        reverse: bool,
+    ) -> RecordingIter<'_, I> {
+        self.ranges.borrow_mut().insert(ReadRange {
+            start: start.map(<[u8]>::to_vec),
+            end: end.map(<[u8]>::to_vec),
+            reverse,
+        });
+
+        RecordingIter {
+            inner,
+            reads: &self.reads,
+        }
+    }
+}
+
+impl<S: StorageBackend> StorageBackend for Recording<S> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.read(key);
+        self.inner.get(key)
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        self.read(key);
+        self.inner.has(key)
+    }
+
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        for key in keys {
+            self.read(key);
+        }
+        self.inner.get_many(keys)
+    }
+}
+
+impl<S: StorageBackendMut> StorageBackendMut for Recording<S> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.writes.insert(key.to_vec());
+        self.inner.set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.writes.insert(key.to_vec());
+        self.inner.remove(key)
+    }
+
+    fn write_batch<I>(&mut self, batch: I)
+    where
+        I: IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
+    {
+        let batch: Vec<_> = batch.into_iter().collect();
+        self.writes.extend(batch.iter().map(|(key, _)| key.clone()));
+
+        self.inner.write_batch(batch)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<S: IterableStorage> IterableStorage for Recording<S> {
+    type KeysIterator<'a>
+        = RecordingIter<'a, S::KeysIterator<'a>>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = core::iter::Map<RecordingIter<'a, S::PairsIterator<'a>>, Project>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = RecordingIter<'a, S::PairsIterator<'a>>
+    where
+        Self: 'a;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        self.record_iter(self.inner.keys(start, end), start, end, false)
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        self.pairs(start, end).map(|(_, v)| v)
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        self.record_iter(self.inner.pairs(start, end), start, end, false)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<S: RevIterableStorage> RevIterableStorage for Recording<S> {
+    type RevKeysIterator<'a>
+        = RecordingIter<'a, S::RevKeysIterator<'a>>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = core::iter::Map<RecordingIter<'a, S::RevPairsIterator<'a>>, Project>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        = RecordingIter<'a, S::RevPairsIterator<'a>>
+    where
+        Self: 'a;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        self.record_iter(self.inner.rev_keys(start, end), start, end, true)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        self.rev_pairs(start, end).map(|(_, v)| v)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        self.record_iter(self.inner.rev_pairs(start, end), start, end, true)
+    }
+}
+
+/// An item yielded by iteration, from which the key visited can be recorded.
+#[cfg(feature = "iteration")]
+pub trait Visited {
+    fn key(&self) -> &[u8];
+}
+
+#[cfg(feature = "iteration")]
+impl Visited for Vec<u8> {
+    fn key(&self) -> &[u8] {
+        self
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl Visited for (Vec<u8>, Vec<u8>) {
+    fn key(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// An iterator recording the keys it visits into the read set of a [`Recording`].
+#[cfg(feature = "iteration")]
+pub struct RecordingIter<'a, I> {
+    inner: I,
+    reads: &'a RefCell<BTreeSet<Vec<u8>>>,
+}
+
+#[cfg(feature = "iteration")]
+impl<I> Iterator for RecordingIter<'_, I>
+where
+    I: Iterator,
+    I::Item: Visited,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        self.reads.borrow_mut().insert(item.key().to_vec());
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::{BoundedIterableAccessor as _, Item, Map};
+
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
+
+    fn key(k: u32) -> Vec<u8> {
+        [&[0][..], &crate::key!(&k)].concat()
+    }
+
+    fn populated() -> TestStorage {
+        let mut storage = TestStorage::new();
+        let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+        for i in 0..10 {
+            map.access(&mut storage)
+                .entry_mut(&i)
+                .set(&(i as u64))
+                .unwrap();
+        }
+        storage
+    }
+
+    #[test]
+    fn point_reads() {
+        let storage = populated();
+        let recording = Recording::new(&storage);
+
+        let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+        let access = map.access(&recording);
+        assert_eq!(access.entry(&7).get(), Ok(Some(7)));
+        assert_eq!(access.entry(&2).get(), Ok(Some(2)));
+        assert_eq!(access.entry(&7).get(), Ok(Some(7)));
+        assert_eq!(access.entry(&42).get(), Ok(None));
+
+        assert_eq!(
+            recording.accesses(),
+            Accesses {
+                reads: vec![key(2), key(7), key(42)],
+                ranges: vec![],
+                writes: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn bounded_scan() {
+        let storage = populated();
+        let recording = Recording::new(&storage);
+
+        let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+        let values = map
+            .access(&recording)
+            .bounded_values(Some(3), Some(6))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, [3, 4, 5]);
+
+        let accesses = recording.accesses();
+        assert_eq!(accesses.reads, [key(3), key(4), key(5)]);
+        assert_eq!(
+            accesses.ranges,
+            [ReadRange {
+                start: Some(key(3)),
+                end: Some(key(6)),
+                reverse: false,
+            }]
+        );
+        assert!(accesses.writes.is_empty());
+
+        // the recorded range reproduces the read
+        assert_eq!(
+            storage
+                .keys(
+                    accesses.ranges[0].start.as_deref(),
+                    accesses.ranges[0].end.as_deref()
+                )
+                .collect::<Vec<_>>(),
+            accesses.reads
+        );
+    }
+
+    #[test]
+    fn write_to_read_key() {
+        let mut storage = populated();
+        let mut recording = Recording::new(&mut storage);
+
+        let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut recording);
+        let value = access.entry(&1).get().unwrap().unwrap();
+        access.entry_mut(&1).set(&(value + 1)).unwrap();
+        access.entry_mut(&20).set(&0).unwrap();
+
+        let accesses = recording.accesses();
+        assert_eq!(accesses.reads, [key(1)]);
+        assert_eq!(accesses.writes, [key(1), key(20)]);
+    }
+}
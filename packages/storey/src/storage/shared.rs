@@ -0,0 +1,313 @@
+use alloc::vec::Vec;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+#[cfg(feature = "iteration")]
+use crate::storage::{IterableStorage, RevIterableStorage};
+use crate::storage::{StorageBackend, StorageBackendMut};
+
+/// A storage backend shared between threads.
+///
+/// Clones of a `Shared` point to the same backend, behind a read-write lock. Any number of
+/// threads can read at the same time, while a write waits for the reads in progress and
+/// blocks new ones until it's done. Writes work through a shared reference too, so a `Shared`
+/// can be used for writing without being cloned.
+///
+/// Each operation takes the lock for its own duration only. To make several operations
+/// atomic with respect to other threads, take the lock for all of them with
+/// [`read`](Self::read) or [`write`](Self::write). Write batches are applied under a single
+/// lock; range removals aren't.
+///
+/// Iterators don't hold the lock: starting an iteration copies the entries in the range out
+/// under the read lock, and the iterator goes over that snapshot. This keeps a slow consumer
+/// from blocking writers, and makes it safe to write from the loop consuming the iterator, at
+/// the cost of reading the whole range up front even if the iterator is dropped early. Prefer
+/// bounded iteration on large ranges.
+///
+/// A panic in another thread while it held the lock doesn't make the storage unusable. The
+/// lock is held for single backend calls (or by the code holding the guard), so it's up to
+/// the backend to stay consistent in that case.
+///
+/// `Shared<S>` is [`Send`] and [`Sync`] if `S` is both.
+///
+/// # Example
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::Item;
+/// use storey::storage::Shared;
+///
+/// const ITEM: Item<u64, TestEncoding> = Item::new(0);
+///
+/// let storage = Shared::new(BTreeMap::new());
+///
+/// let writer = {
+///     let mut storage = storage.clone();
+///     std::thread::spawn(move || ITEM.access(&mut storage).set(&1337).unwrap())
+/// };
+/// writer.join().unwrap();
+///
+/// assert_eq!(ITEM.access(&storage).get(), Ok(Some(1337)));
+/// ```
+pub struct Shared<S> {
+    inner: Arc<RwLock<S>>,
+}
+
+impl<S> Shared<S> {
+    /// Share `inner` between threads.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(inner)),
+        }
+    }
+
+    /// Lock the backend for reading, until the guard is dropped.
+    pub fn read(&self) -> RwLockReadGuard<'_, S> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the backend for writing, until the guard is dropped.
+    ///
+    /// The guard dereferences to the backend, so containers can be accessed through it.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # use storey_testing::encoding::TestEncoding;
+    /// use storey::containers::Column;
+    /// use storey::storage::Shared;
+    ///
+    /// let column = Column::<u64, TestEncoding>::new(0);
+    /// let storage = Shared::new(BTreeMap::new());
+    ///
+    /// // no other thread can see the column with only one of the values
+    /// let mut guard = storage.write();
+    /// column.access(&mut *guard).push(&1).unwrap();
+    /// column.access(&mut *guard).push(&2).unwrap();
+    /// ```
+    pub fn write(&self) -> RwLockWriteGuard<'_, S> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the backend back, if this is the last clone of it.
+    pub fn try_into_inner(self) -> Result<S, Self> {
+        Arc::try_unwrap(self.inner)
+            .map(|lock| lock.into_inner().unwrap_or_else(PoisonError::into_inner))
+            .map_err(|inner| Self { inner })
+    }
+}
+
+impl<S> Clone for Shared<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<S: StorageBackend> StorageBackend for Shared<S> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.read().get(key)
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        self.read().has(key)
+    }
+
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        self.read().get_many(keys)
+    }
+}
+
+impl<S: StorageBackendMut> StorageBackendMut for Shared<S> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        (&*self).set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        (&*self).remove(key)
+    }
+
+    fn write_batch<I>(&mut self, batch: I)
+    where
+        I: IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
+    {
+        (&*self).write_batch(batch)
+    }
+}
+
+impl<S: StorageBackendMut> StorageBackendMut for &Shared<S> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.write().set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.write().remove(key)
+    }
+
+    fn write_batch<I>(&mut self, batch: I)
+    where
+        I: IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
+    {
+        // collected first, so that no user code runs under the lock
+        let batch: Vec<_> = batch.into_iter().collect();
+        self.write().write_batch(batch)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<S: IterableStorage> IterableStorage for Shared<S> {
+    type KeysIterator<'a>
+        = alloc::vec::IntoIter<Vec<u8>>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = alloc::vec::IntoIter<Vec<u8>>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = alloc::vec::IntoIter<(Vec<u8>, Vec<u8>)>
+    where
+        Self: 'a;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        let snapshot: Vec<_> = self.read().keys(start, end).collect();
+        snapshot.into_iter()
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        let snapshot: Vec<_> = self.read().values(start, end).collect();
+        snapshot.into_iter()
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        let snapshot: Vec<_> = self.read().pairs(start, end).collect();
+        snapshot.into_iter()
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<S: RevIterableStorage> RevIterableStorage for Shared<S> {
+    type RevKeysIterator<'a>
+        = alloc::vec::IntoIter<Vec<u8>>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = alloc::vec::IntoIter<Vec<u8>>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        = alloc::vec::IntoIter<(Vec<u8>, Vec<u8>)>
+    where
+        Self: 'a;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        let snapshot: Vec<_> = self.read().rev_keys(start, end).collect();
+        snapshot.into_iter()
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        let snapshot: Vec<_> = self.read().rev_values(start, end).collect();
+        snapshot.into_iter()
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        let snapshot: Vec<_> = self.read().rev_pairs(start, end).collect();
+        snapshot.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::collections::BTreeMap;
+    use std::thread;
+
+    use crate::containers::{Column, IterableAccessor as _};
+
+    use storey_testing::encoding::TestEncoding;
+
+    type Backend = BTreeMap<Vec<u8>, Vec<u8>>;
+
+    #[test]
+    fn send_and_sync() {
+        fn check<T: Send + Sync>() {}
+        check::<Shared<Backend>>();
+    }
+
+    #[test]
+    fn iteration_doesnt_hold_the_lock() {
+        let storage = Shared::new(Backend::new());
+        (&storage).set(&[1], &[1]);
+        (&storage).set(&[2], &[2]);
+
+        for key in storage.keys(None, None) {
+            (&storage).remove(&key);
+        }
+        assert_eq!(storage.read().len(), 0);
+    }
+
+    #[test]
+    fn concurrent_readers_and_writer() {
+        const PUSHES: u64 = 200;
+        const COLUMN: Column<u64, TestEncoding> = Column::new(0);
+        let storage = Shared::new(Backend::new());
+
+        let writer = {
+            let storage = storage.clone();
+            thread::spawn(move || {
+                for i in 0..PUSHES {
+                    // the value and the length are written under the same lock
+                    let mut guard = storage.write();
+                    COLUMN.access(&mut *guard).push(&i).unwrap();
+                }
+            })
+        };
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let storage = storage.clone();
+                thread::spawn(move || loop {
+                    let guard = storage.read();
+                    let access = COLUMN.access(&*guard);
+                    let len = access.len().unwrap();
+                    let values = access.values().collect::<Result<Vec<_>, _>>().unwrap();
+                    assert_eq!(values, (0..len as u64).collect::<Vec<_>>());
+                    drop(guard);
+
+                    // reads outside of a guard see a consistent prefix too
+                    let values = COLUMN
+                        .access(&storage)
+                        .values()
+                        .collect::<Result<Vec<_>, _>>()
+                        .unwrap();
+                    assert_eq!(values, (0..values.len() as u64).collect::<Vec<_>>());
+
+                    if len == PUSHES as u32 {
+                        break;
+                    }
+                })
+            })
+            .collect();
+
+        writer.join().unwrap();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        assert_eq!(COLUMN.access(&storage).len(), Ok(PUSHES as u32));
+        assert!(storage.try_into_inner().is_ok());
+    }
+}
@@ -0,0 +1,384 @@
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+#[cfg(feature = "iteration")]
+use crate::storage::{IterableStorage, RevIterableStorage};
+use crate::storage::{StorageBackend, StorageBackendMut};
+
+/// A storage backend wrapper recording every call made to the backend it wraps.
+///
+/// Each read, write and iteration is recorded as an [`Op`], with the raw key and the length
+/// of the value, so that the storage traffic caused by some code can be inspected afterwards.
+/// Iterations are recorded when they start, and count the items actually pulled from them.
+///
+/// It can wrap a backend, or a (mutable) reference to one.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::{Item, Map};
+/// use storey::storage::{Op, Traced};
+///
+/// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+///
+/// let mut storage = TestStorage::new();
+/// let mut traced = Traced::new(&mut storage);
+///
+/// map.access(&mut traced).entry_mut("foo").set(&1337).unwrap();
+/// map.access(&traced).entry("bar").get().unwrap();
+///
+/// let ops = traced.take_ops();
+/// assert_eq!(ops.len(), 2);
+/// assert!(matches!(ops[0], Op::Set { value_len: 8, .. }));
+/// assert!(matches!(ops[1], Op::Get { value_len: None, .. }));
+/// ```
+pub struct Traced<S> {
+    inner: S,
+    ops: RefCell<Vec<Op>>,
+}
+
+/// A call recorded by [`Traced`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    /// A read, with the length of the value found (if any).
+    Get {
+        key: Vec<u8>,
+        value_len: Option<usize>,
+    },
+
+    /// An existence check.
+    Has { key: Vec<u8>, found: bool },
+
+    /// A write.
+    Set { key: Vec<u8>, value_len: usize },
+
+    /// A removal.
+    Remove { key: Vec<u8> },
+
+    /// An iteration, with the number of items pulled from it so far.
+    Iter {
+        kind: IterKind,
+        reverse: bool,
+        start: Option<Vec<u8>>,
+        end: Option<Vec<u8>>,
+        items: usize,
+    },
+}
+
+/// What an [`Op::Iter`] iterated over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IterKind {
+    Keys,
+    Values,
+    Pairs,
+}
+
+impl<S> Traced<S> {
+    /// Start recording the calls made to `inner`.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            ops: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Get the calls recorded so far.
+    pub fn ops(&self) -> Vec<Op> {
+        self.ops.borrow().clone()
+    }
+
+    /// Get the calls recorded so far, and clear the log.
+    pub fn take_ops(&self) -> Vec<Op> {
+        self.ops.take()
+    }
+
+    /// Get the wrapped backend back.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn record(&self, op: Op) {
+        self.ops.borrow_mut().push(op);
+    }
+
+    #[cfg(feature = "iteration")]
+    fn trace_iter<I: Iterator>(
+        &self,
+        inner: I,
+        kind: IterKind,
+        reverse: bool,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> TracedIter<'_, I> {
+        let mut ops = self.ops.borrow_mut();
+        ops.push(Op::Iter {
+            kind,
+            reverse,
+            start: start.map(<[u8]>::to_vec),
+            end: end.map(<[u8]>::to_vec),
+            items: 0,
+        });
+
+        TracedIter {
+            inner,
+            ops: &self.ops,
+            index: ops.len() - 1,
+        }
+    }
+}
+
+impl<S: StorageBackend> StorageBackend for Traced<S> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let value = self.inner.get(key);
+        self.record(Op::Get {
+            key: key.to_vec(),
+            value_len: value.as_ref().map(Vec::len),
+        });
+        value
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        let found = self.inner.has(key);
+        self.record(Op::Has {
+            key: key.to_vec(),
+            found,
+        });
+        found
+    }
+}
+
+impl<S: StorageBackendMut> StorageBackendMut for Traced<S> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.record(Op::Set {
+            key: key.to_vec(),
+            value_len: value.len(),
+        });
+        self.inner.set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.record(Op::Remove { key: key.to_vec() });
+        self.inner.remove(key)
+    }
+
+    fn write_batch<I>(&mut self, batch: I)
+    where
+        I: IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
+    {
+        let batch: Vec<_> = batch.into_iter().collect();
+        for (key, value) in &batch {
+            self.record(match value {
+                Some(value) => Op::Set {
+                    key: key.clone(),
+                    value_len: value.len(),
+                },
+                None => Op::Remove { key: key.clone() },
+            });
+        }
+
+        self.inner.write_batch(batch)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<S: IterableStorage> IterableStorage for Traced<S> {
+    type KeysIterator<'a>
+        = TracedIter<'a, S::KeysIterator<'a>>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = TracedIter<'a, S::ValuesIterator<'a>>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = TracedIter<'a, S::PairsIterator<'a>>
+    where
+        Self: 'a;
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        let inner = self.inner.keys(start, end);
+        self.trace_iter(inner, IterKind::Keys, false, start, end)
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        let inner = self.inner.values(start, end);
+        self.trace_iter(inner, IterKind::Values, false, start, end)
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        let inner = self.inner.pairs(start, end);
+        self.trace_iter(inner, IterKind::Pairs, false, start, end)
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<S: RevIterableStorage> RevIterableStorage for Traced<S> {
+    type RevKeysIterator<'a>
+        = TracedIter<'a, S::RevKeysIterator<'a>>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = TracedIter<'a, S::RevValuesIterator<'a>>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        = TracedIter<'a, S::RevPairsIterator<'a>>
+    where
+        Self: 'a;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        let inner = self.inner.rev_keys(start, end);
+        self.trace_iter(inner, IterKind::Keys, true, start, end)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        let inner = self.inner.rev_values(start, end);
+        self.trace_iter(inner, IterKind::Values, true, start, end)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        let inner = self.inner.rev_pairs(start, end);
+        self.trace_iter(inner, IterKind::Pairs, true, start, end)
+    }
+}
+
+/// An iterator counting the items pulled from it into the log of a [`Traced`].
+#[cfg(feature = "iteration")]
+pub struct TracedIter<'a, I> {
+    inner: I,
+    ops: &'a RefCell<Vec<Op>>,
+    index: usize,
+}
+
+#[cfg(feature = "iteration")]
+impl<I: Iterator> Iterator for TracedIter<'_, I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+
+        // the log might have been taken in the meantime
+        if let Some(Op::Iter { items, .. }) = self.ops.borrow_mut().get_mut(self.index) {
+            *items += 1;
+        }
+
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::{Column, Item, IterableAccessor as _, Map};
+
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
+
+    #[test]
+    fn map_write_read() {
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let foo = [&[0][..], &crate::key!("foo")].concat();
+        let bar = [&[0][..], &crate::key!("bar")].concat();
+
+        let mut storage = TestStorage::new();
+        let mut traced = Traced::new(&mut storage);
+
+        let mut access = map.access(&mut traced);
+        access.entry_mut("foo").set(&1337).unwrap();
+        access.entry_mut("bar").set(&42).unwrap();
+        assert_eq!(access.entry("foo").get(), Ok(Some(1337)));
+        assert_eq!(access.entry("baz").get(), Ok(None));
+
+        let access = map.access(&traced);
+        let mut keys = access.keys();
+        keys.next().unwrap().unwrap();
+        drop(keys);
+
+        assert_eq!(
+            traced.take_ops(),
+            [
+                Op::Set {
+                    key: foo.clone(),
+                    value_len: 8
+                },
+                Op::Set {
+                    key: bar,
+                    value_len: 8
+                },
+                Op::Get {
+                    key: foo,
+                    value_len: Some(8)
+                },
+                Op::Get {
+                    key: [&[0][..], &crate::key!("baz")].concat(),
+                    value_len: None
+                },
+                Op::Iter {
+                    kind: IterKind::Keys,
+                    reverse: false,
+                    start: Some(vec![0]),
+                    end: Some(vec![1]),
+                    items: 1
+                }
+            ]
+        );
+        assert!(traced.ops().is_empty());
+    }
+
+    #[test]
+    fn column_traffic() {
+        let column = Column::<u64, TestEncoding>::new(0);
+
+        let mut traced = Traced::new(TestStorage::new());
+        column.access(&mut traced).push(&1).unwrap();
+        column.access(&mut traced).push(&2).unwrap();
+        traced.take_ops();
+
+        column.access(&mut traced).remove(0).unwrap();
+        let values = column
+            .access(&traced)
+            .values()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, [2]);
+
+        // the removal decrements the length kept in the column's metadata
+        assert_eq!(
+            traced.ops(),
+            [
+                Op::Remove {
+                    key: vec![0, 0, 0, 0, 0]
+                },
+                Op::Get {
+                    key: vec![255, 0, 1],
+                    value_len: Some(4)
+                },
+                Op::Set {
+                    key: vec![255, 0, 1],
+                    value_len: 4
+                },
+                Op::Iter {
+                    kind: IterKind::Values,
+                    reverse: false,
+                    start: Some(vec![0]),
+                    end: Some(vec![1]),
+                    items: 1
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,697 @@
+#[cfg(feature = "iteration")]
+use alloc::collections::btree_map;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+#[cfg(feature = "iteration")]
+use core::cmp::Ordering;
+#[cfg(feature = "iteration")]
+use core::iter::{Peekable, Rev};
+#[cfg(feature = "iteration")]
+use core::ops::Bound;
+
+#[cfg(feature = "iteration")]
+use crate::storage::{IterableStorage, RevIterableStorage};
+use crate::storage::{StorageBackend, StorageBackendMut};
+
+/// A storage backend buffering writes to another backend until they're committed.
+///
+/// Sets and removes only go to an in-memory overlay. Reads (including iteration) see the
+/// overlay first and the base storage second, so the transaction observes its own writes.
+/// Nothing reaches the base until [`commit`](Self::commit) is called; dropping the transaction
+/// or calling [`rollback`](Self::rollback) discards everything.
+///
+/// This makes it possible to run a fallible sequence of container operations and keep either
+/// all of their writes, or none.
+///
+/// The base storage is borrowed mutably for the whole lifetime of the transaction. Since a
+/// transaction is a storage backend itself, transactions can be nested.
+///
+/// For finer-grained control, [`savepoint`](Self::savepoint) marks a point within the
+/// transaction that can later be rolled back to, without losing the writes made before it.
+///
+/// # Example
+/// ```
+/// # use storey_testing::backend::TestStorage;
+/// # use storey_testing::encoding::TestEncoding;
+/// use storey::containers::{Item, Map};
+/// use storey::storage::Transaction;
+///
+/// let balances = Map::<String, Item<u64, TestEncoding>>::new(0);
+///
+/// let mut storage = TestStorage::new();
+/// balances.access(&mut storage).entry_mut("alice").set(&10).unwrap();
+///
+/// fn transfer(
+///     balances: &Map<String, Item<u64, TestEncoding>>,
+///     storage: &mut TestStorage,
+///     amount: u64,
+/// ) -> Result<(), &'static str> {
+///     let mut tx = Transaction::new(storage);
+///     let mut access = balances.access(&mut tx);
+///
+///     // credit first, debit second - a failing debit must undo the credit
+///     let bob = access.entry("bob").get().unwrap().unwrap_or(0);
+///     access.entry_mut("bob").set(&(bob + amount)).unwrap();
+///
+///     let alice = access.entry("alice").get().unwrap().unwrap_or(0);
+///     let alice = alice.checked_sub(amount).ok_or("insufficient funds")?;
+///     access.entry_mut("alice").set(&alice).unwrap();
+///
+///     tx.commit();
+///     Ok(())
+/// }
+///
+/// assert_eq!(transfer(&balances, &mut storage, 15), Err("insufficient funds"));
+/// assert_eq!(balances.access(&storage).entry("bob").get().unwrap(), None);
+///
+/// transfer(&balances, &mut storage, 4).unwrap();
+/// assert_eq!(balances.access(&storage).entry("bob").get().unwrap(), Some(4));
+/// assert_eq!(balances.access(&storage).entry("alice").get().unwrap(), Some(6));
+/// ```
+pub struct Transaction<S> {
+    base: S,
+    overlay: Overlay,
+    // One undo log per active savepoint, oldest first.
+    savepoints: Vec<(u64, UndoLog)>,
+    next_savepoint: u64,
+}
+
+// `None` marks a removed key.
+pub(super) type Overlay = BTreeMap<Vec<u8>, Option<Vec<u8>>>;
+
+// The overlay entries (`None` if there was no entry) from before the first write to each key
+// since a savepoint was created.
+type UndoLog = BTreeMap<Vec<u8>, Option<Option<Vec<u8>>>>;
+
+/// A point within a [`Transaction`] that can be rolled back to.
+///
+/// Created by [`Transaction::savepoint`], and consumed by [`Transaction::release`] or
+/// [`Transaction::rollback_to`].
+#[must_use = "a savepoint should be released or rolled back to"]
+#[derive(Debug)]
+pub struct Savepoint(u64);
+
+impl<S> Transaction<S> {
+    /// Start a transaction on top of `base`.
+    pub fn new(base: S) -> Self {
+        Self {
+            base,
+            overlay: BTreeMap::new(),
+            savepoints: Vec::new(),
+            next_savepoint: 0,
+        }
+    }
+
+    /// Create a savepoint.
+    ///
+    /// Savepoints nest: a savepoint created while another one is active belongs to it, and is
+    /// released or rolled back together with it.
+    ///
+    /// # Example
+    /// ```
+    /// # use storey_testing::backend::TestStorage;
+    /// use storey::storage::{StorageBackend as _, StorageBackendMut as _, Transaction};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let mut tx = Transaction::new(&mut storage);
+    /// tx.set(b"outer", b"kept");
+    ///
+    /// // try strategy A
+    /// let savepoint = tx.savepoint();
+    /// tx.set(b"strategy", b"A");
+    /// let failed = true;
+    ///
+    /// if failed {
+    ///     // fall back to strategy B
+    ///     tx.rollback_to(savepoint);
+    ///     tx.set(b"fallback", b"B");
+    /// } else {
+    ///     tx.release(savepoint);
+    /// }
+    ///
+    /// assert_eq!(tx.get(b"outer"), Some(b"kept".to_vec()));
+    /// assert_eq!(tx.get(b"strategy"), None);
+    /// assert_eq!(tx.get(b"fallback"), Some(b"B".to_vec()));
+    /// ```
+    pub fn savepoint(&mut self) -> Savepoint {
+        let id = self.next_savepoint;
+        self.next_savepoint += 1;
+        self.savepoints.push((id, BTreeMap::new()));
+
+        Savepoint(id)
+    }
+
+    /// Keep the writes made since `savepoint`, merging them into the enclosing level.
+    ///
+    /// Savepoints created after `savepoint` are released too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `savepoint` is no longer active, because an enclosing savepoint was released or
+    /// rolled back already.
+    pub fn release(&mut self, savepoint: Savepoint) {
+        let index = self.savepoint_index(savepoint);
+
+        let released = self.savepoints.split_off(index);
+        if let Some((_, parent)) = self.savepoints.last_mut() {
+            // the oldest entry is the one from before the enclosing level started writing
+            for (_, log) in released {
+                for (key, previous) in log {
+                    parent.entry(key).or_insert(previous);
+                }
+            }
+        }
+    }
+
+    /// Discard the writes made since `savepoint`, keeping the ones made before it.
+    ///
+    /// Savepoints created after `savepoint` are discarded too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `savepoint` is no longer active, because an enclosing savepoint was released or
+    /// rolled back already.
+    pub fn rollback_to(&mut self, savepoint: Savepoint) {
+        let index = self.savepoint_index(savepoint);
+
+        for (_, log) in self.savepoints.drain(index..).rev() {
+            for (key, previous) in log {
+                match previous {
+                    Some(value) => self.overlay.insert(key, value),
+                    None => self.overlay.remove(&key),
+                };
+            }
+        }
+    }
+
+    fn savepoint_index(&self, savepoint: Savepoint) -> usize {
+        self.savepoints
+            .iter()
+            .position(|(id, _)| *id == savepoint.0)
+            .expect("the savepoint is no longer active")
+    }
+
+    fn write(&mut self, key: &[u8], value: Option<Vec<u8>>) {
+        if let Some((_, log)) = self.savepoints.last_mut() {
+            if !log.contains_key(key) {
+                log.insert(key.to_vec(), self.overlay.get(key).cloned());
+            }
+        }
+
+        self.overlay.insert(key.to_vec(), value);
+    }
+
+    /// Discard all the writes made in the transaction.
+    ///
+    /// This is the same as dropping the transaction, but makes the intent explicit.
+    pub fn rollback(self) {}
+}
+
+impl<B: StorageBackendMut> Transaction<&mut B> {
+    /// Write everything from the transaction to the base storage, as a single
+    /// [batch](StorageBackendMut::write_batch).
+    ///
+    /// Writes made since savepoints that are still active are committed too.
+    pub fn commit(self) {
+        self.base.write_batch(self.overlay);
+    }
+}
+
+impl<B: StorageBackend + ?Sized> StorageBackend for Transaction<&mut B> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.overlay.get(key) {
+            Some(value) => value.clone(),
+            None => self.base.get(key),
+        }
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        match self.overlay.get(key) {
+            Some(value) => value.is_some(),
+            None => self.base.has(key),
+        }
+    }
+}
+
+impl<B: StorageBackend + ?Sized> StorageBackendMut for Transaction<&mut B> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.write(key, Some(value.to_vec()));
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.write(key, None);
+    }
+}
+
+#[cfg(feature = "iteration")]
+pub(super) type Project = fn((Vec<u8>, Vec<u8>)) -> Vec<u8>;
+
+#[cfg(feature = "iteration")]
+impl<B: IterableStorage + ?Sized> IterableStorage for Transaction<&mut B> {
+    type KeysIterator<'a>
+        = core::iter::Map<Self::PairsIterator<'a>, Project>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = core::iter::Map<Self::PairsIterator<'a>, Project>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = MergeIter<'a, B::PairsIterator<'a>, btree_map::Range<'a, Vec<u8>, Option<Vec<u8>>>>
+    where
+        Self: 'a;
+
+    // Keys and values are both merged by key, so they both need the base's pairs.
+
+    fn keys<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::KeysIterator<'a> {
+        self.pairs(start, end).map(|(k, _)| k)
+    }
+
+    fn values<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::ValuesIterator<'a> {
+        self.pairs(start, end).map(|(_, v)| v)
+    }
+
+    fn pairs<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>) -> Self::PairsIterator<'a> {
+        MergeIter::new(
+            self.base.pairs(start, end),
+            overlay_range(&self.overlay, start, end),
+            Ordering::Less,
+        )
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<B: RevIterableStorage + ?Sized> RevIterableStorage for Transaction<&mut B> {
+    type RevKeysIterator<'a>
+        = core::iter::Map<Self::RevPairsIterator<'a>, Project>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = core::iter::Map<Self::RevPairsIterator<'a>, Project>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        =
+        MergeIter<'a, B::RevPairsIterator<'a>, Rev<btree_map::Range<'a, Vec<u8>, Option<Vec<u8>>>>>
+    where
+        Self: 'a;
+
+    fn rev_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevKeysIterator<'a> {
+        self.rev_pairs(start, end).map(|(k, _)| k)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        self.rev_pairs(start, end).map(|(_, v)| v)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        MergeIter::new(
+            self.base.rev_pairs(start, end),
+            overlay_range(&self.overlay, start, end).rev(),
+            Ordering::Greater,
+        )
+    }
+}
+
+/// The entries of `overlay` between `start` (inclusive) and `end` (exclusive).
+#[cfg(feature = "iteration")]
+pub(super) fn overlay_range<'a>(
+    overlay: &'a Overlay,
+    start: Option<&[u8]>,
+    end: Option<&[u8]>,
+) -> btree_map::Range<'a, Vec<u8>, Option<Vec<u8>>> {
+    let start = start.map_or(Bound::Unbounded, Bound::Included);
+    let end = end.map_or(Bound::Unbounded, Bound::Excluded);
+
+    // `BTreeMap::range` panics on inverted bounds, while storage iteration is just empty
+    match (start, end) {
+        (Bound::Included(start), Bound::Excluded(end)) if start > end => {
+            overlay.range::<[u8], _>((Bound::Included(end), Bound::Excluded(end)))
+        }
+        bounds => overlay.range::<[u8], _>(bounds),
+    }
+}
+
+/// An iterator merging the pairs of a base storage with an overlay of buffered writes, like
+/// the one of a [`Transaction`].
+///
+/// Both iterators must be sorted in the same direction. Overlay entries win over base entries
+/// with the same key, and removed keys are skipped.
+#[cfg(feature = "iteration")]
+pub struct MergeIter<'a, I, O>
+where
+    I: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+    O: Iterator<Item = (&'a Vec<u8>, &'a Option<Vec<u8>>)>,
+{
+    base: Peekable<I>,
+    overlay: Peekable<O>,
+    // the ordering between a key and the keys that come after it
+    order: Ordering,
+}
+
+#[cfg(feature = "iteration")]
+impl<'a, I, O> MergeIter<'a, I, O>
+where
+    I: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+    O: Iterator<Item = (&'a Vec<u8>, &'a Option<Vec<u8>>)>,
+{
+    pub(super) fn new(base: I, overlay: O, order: Ordering) -> Self {
+        Self {
+            base: base.peekable(),
+            overlay: overlay.peekable(),
+            order,
+        }
+    }
+}
+
+#[cfg(feature = "iteration")]
+impl<'a, I, O> Iterator for MergeIter<'a, I, O>
+where
+    I: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+    O: Iterator<Item = (&'a Vec<u8>, &'a Option<Vec<u8>>)>,
+{
+    type Item = (Vec<u8>, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match (self.base.peek(), self.overlay.peek()) {
+                (None, None) => return None,
+                (Some(_), None) => return self.base.next(),
+                (None, Some(_)) => {}
+                (Some((base_key, _)), Some((overlay_key, _))) => {
+                    match base_key.cmp(overlay_key) {
+                        // shadowed by the overlay
+                        Ordering::Equal => {
+                            self.base.next();
+                        }
+                        ordering if ordering == self.order => return self.base.next(),
+                        _ => {}
+                    }
+                }
+            }
+
+            let (key, value) = self.overlay.next()?;
+            if let Some(value) = value {
+                return Some((key.clone(), value.clone()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::{Column, Item, IterableAccessor as _, Map};
+
+    use storey_testing::backend::TestStorage;
+    use storey_testing::encoding::TestEncoding;
+
+    fn pairs(storage: &impl IterableStorage) -> Vec<(Vec<u8>, Vec<u8>)> {
+        storage.pairs(None, None).collect()
+    }
+
+    fn rev_pairs(storage: &impl RevIterableStorage) -> Vec<(Vec<u8>, Vec<u8>)> {
+        storage.rev_pairs(None, None).collect()
+    }
+
+    #[test]
+    fn reads_through_overlay() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0], b"base");
+        storage.set(&[1], b"base");
+
+        let mut tx = Transaction::new(&mut storage);
+        tx.set(&[1], b"overlay");
+        tx.set(&[2], b"overlay");
+        tx.remove(&[0]);
+
+        assert_eq!(tx.get(&[0]), None);
+        assert_eq!(tx.get(&[1]), Some(b"overlay".to_vec()));
+        assert_eq!(tx.get(&[2]), Some(b"overlay".to_vec()));
+
+        tx.rollback();
+        assert_eq!(
+            pairs(&storage),
+            [(vec![0], b"base".to_vec()), (vec![1], b"base".to_vec())]
+        );
+    }
+
+    #[test]
+    fn commit() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0], b"base");
+        storage.set(&[1], b"base");
+
+        let mut tx = Transaction::new(&mut storage);
+        tx.set(&[1], b"overlay");
+        tx.set(&[2], b"overlay");
+        tx.remove(&[0]);
+        tx.commit();
+
+        assert_eq!(
+            pairs(&storage),
+            [
+                (vec![1], b"overlay".to_vec()),
+                (vec![2], b"overlay".to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn drop_discards() {
+        let mut storage = TestStorage::new();
+
+        {
+            let mut tx = Transaction::new(&mut storage);
+            tx.set(&[0], b"overlay");
+        }
+
+        assert_eq!(storage.get(&[0]), None);
+    }
+
+    #[test]
+    fn interleaved_iteration() {
+        let mut storage = TestStorage::new();
+        for key in [1, 3, 5, 7] {
+            storage.set(&[key], b"base");
+        }
+
+        let mut tx = Transaction::new(&mut storage);
+        for key in [0, 2, 3, 8] {
+            tx.set(&[key], b"overlay");
+        }
+        tx.remove(&[5]);
+        tx.remove(&[6]); // not in the base
+
+        let expected = vec![
+            (vec![0], b"overlay".to_vec()),
+            (vec![1], b"base".to_vec()),
+            (vec![2], b"overlay".to_vec()),
+            (vec![3], b"overlay".to_vec()),
+            (vec![7], b"base".to_vec()),
+            (vec![8], b"overlay".to_vec()),
+        ];
+        assert_eq!(pairs(&tx), expected);
+        assert_eq!(
+            rev_pairs(&tx),
+            expected.iter().cloned().rev().collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            tx.keys(Some(&[2]), Some(&[8])).collect::<Vec<_>>(),
+            [vec![2], vec![3], vec![7]]
+        );
+        assert_eq!(
+            tx.rev_values(Some(&[1]), Some(&[3])).collect::<Vec<_>>(),
+            [b"overlay".to_vec(), b"base".to_vec()]
+        );
+        assert_eq!(tx.keys(Some(&[5]), Some(&[3])).count(), 0);
+    }
+
+    #[test]
+    fn removed_then_rewritten() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0], b"base");
+        storage.set(&[1], b"base");
+
+        let mut tx = Transaction::new(&mut storage);
+        tx.remove(&[1]);
+        assert_eq!(pairs(&tx), [(vec![0], b"base".to_vec())]);
+
+        tx.set(&[1], b"rewritten");
+        assert_eq!(
+            pairs(&tx),
+            [
+                (vec![0], b"base".to_vec()),
+                (vec![1], b"rewritten".to_vec())
+            ]
+        );
+
+        tx.remove(&[1]);
+        assert_eq!(rev_pairs(&tx), [(vec![0], b"base".to_vec())]);
+
+        tx.set(&[1], b"rewritten again");
+        tx.commit();
+        assert_eq!(storage.get(&[1]), Some(b"rewritten again".to_vec()));
+    }
+
+    #[test]
+    fn containers() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let column = Column::<u64, TestEncoding>::new(1);
+        map.access(&mut storage).entry_mut("a").set(&1).unwrap();
+        map.access(&mut storage).entry_mut("c").set(&3).unwrap();
+        column.access(&mut storage).push(&10).unwrap();
+
+        let mut tx = Transaction::new(&mut storage);
+        map.access(&mut tx).entry_mut("b").set(&2).unwrap();
+        tx.remove(&[&[0][..], &crate::key!("c")].concat());
+        column.access(&mut tx).push(&20).unwrap();
+
+        assert_eq!(
+            map.access(&tx).values().collect::<Result<Vec<_>, _>>(),
+            Ok(vec![1, 2])
+        );
+        assert_eq!(column.access(&tx).len(), Ok(2));
+
+        // the base is untouched until the commit, metadata included
+        tx.commit();
+        assert_eq!(
+            map.access(&storage).values().collect::<Result<Vec<_>, _>>(),
+            Ok(vec![1, 2])
+        );
+        assert_eq!(column.access(&storage).len(), Ok(2));
+        assert_eq!(column.access(&storage).get(1), Ok(Some(20)));
+    }
+
+    #[test]
+    fn savepoints() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0], b"base");
+
+        let mut tx = Transaction::new(&mut storage);
+        tx.set(&[1], b"tx");
+
+        let first = tx.savepoint();
+        tx.set(&[2], b"first");
+        tx.set(&[1], b"first");
+
+        let second = tx.savepoint();
+        tx.set(&[3], b"second");
+        tx.remove(&[2]);
+        tx.remove(&[0]);
+
+        let third = tx.savepoint();
+        tx.set(&[4], b"third");
+        tx.set(&[3], b"third");
+
+        assert_eq!(
+            pairs(&tx),
+            [
+                (vec![1], b"first".to_vec()),
+                (vec![3], b"third".to_vec()),
+                (vec![4], b"third".to_vec())
+            ]
+        );
+
+        // rolling back the middle level discards the innermost one too
+        tx.rollback_to(second);
+
+        let expected = vec![
+            (vec![0], b"base".to_vec()),
+            (vec![1], b"first".to_vec()),
+            (vec![2], b"first".to_vec()),
+        ];
+        assert_eq!(pairs(&tx), expected);
+        assert_eq!(
+            rev_pairs(&tx),
+            expected.iter().cloned().rev().collect::<Vec<_>>()
+        );
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            tx.release(third);
+        }));
+        assert!(result.is_err());
+
+        tx.release(first);
+        tx.commit();
+        assert_eq!(
+            pairs(&storage),
+            [
+                (vec![0], b"base".to_vec()),
+                (vec![1], b"first".to_vec()),
+                (vec![2], b"first".to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn released_savepoint_rolls_back_with_parent() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0], b"base");
+
+        let mut tx = Transaction::new(&mut storage);
+
+        let outer = tx.savepoint();
+        tx.set(&[1], b"outer");
+
+        let inner = tx.savepoint();
+        tx.set(&[1], b"inner");
+        tx.set(&[0], b"inner");
+        tx.release(inner);
+
+        assert_eq!(tx.get(&[0]), Some(b"inner".to_vec()));
+        assert_eq!(tx.get(&[1]), Some(b"inner".to_vec()));
+
+        tx.rollback_to(outer);
+        assert_eq!(pairs(&tx), [(vec![0], b"base".to_vec())]);
+
+        // savepoints can be created again after the stack was unwound
+        let again = tx.savepoint();
+        tx.set(&[2], b"again");
+        tx.release(again);
+        tx.commit();
+
+        assert_eq!(storage.get(&[2]), Some(b"again".to_vec()));
+    }
+
+    #[test]
+    fn nested() {
+        let mut storage = TestStorage::new();
+
+        let mut outer = Transaction::new(&mut storage);
+        outer.set(&[0], b"outer");
+
+        let mut inner = Transaction::new(&mut outer);
+        inner.set(&[1], b"inner");
+        inner.remove(&[0]);
+        assert_eq!(pairs(&inner), [(vec![1], b"inner".to_vec())]);
+        inner.rollback();
+
+        let mut inner = Transaction::new(&mut outer);
+        inner.set(&[1], b"inner");
+        inner.commit();
+
+        assert_eq!(outer.get(&[1]), Some(b"inner".to_vec()));
+        outer.commit();
+        assert_eq!(
+            pairs(&storage),
+            [(vec![0], b"outer".to_vec()), (vec![1], b"inner".to_vec())]
+        );
+    }
+}
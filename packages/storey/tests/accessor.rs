@@ -0,0 +1,111 @@
+// What `accessor!` expands to: wrappers delegating to the accessors under other names.
+
+use std::mem::size_of;
+
+use storey::containers::{Item, ItemAccess, Map, MapAccess};
+use storey::storage::StorageBranch;
+use storey_testing::backend::TestStorage;
+use storey_testing::encoding::{fail_decode, TestEncoding};
+
+const OWNER: Item<u64, TestEncoding> = Item::new(0);
+const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new(1);
+
+storey::accessor! {
+    /// The owner of the contract.
+    pub struct Owner(OWNER: Item<u64, TestEncoding>) {
+        pub fn get = get;
+        pub fn is_set = exists;
+        pub fn transfer = set;
+        pub fn bump = update;
+    }
+}
+
+mod private {
+    use super::*;
+
+    storey::accessor! {
+        pub(crate) struct Balances(BALANCES: Map<String, Item<u64, TestEncoding>>) {
+            /// The balance of `owner`.
+            pub(crate) fn of = get;
+            pub(crate) fn holds = has;
+            pub(crate) fn set_balance = set;
+            pub(crate) fn adjust = update;
+            #[allow(dead_code)]
+            fn private_of = get;
+        }
+    }
+}
+
+use private::Balances;
+
+storey::accessor! {
+    struct Empty(OWNER: Item<u64, TestEncoding>) {}
+}
+
+#[test]
+fn item() {
+    let mut storage = TestStorage::new();
+
+    assert_eq!(Owner::new(&storage).get(), Ok(None));
+    assert!(!Owner::new(&storage).is_set());
+
+    let mut owner = Owner::new(&mut storage);
+    owner.transfer(&7).unwrap();
+    assert_eq!(owner.bump(|n| n.unwrap() + 1), Ok(8));
+    assert_eq!(owner.get(), Ok(Some(8)));
+
+    // the same data as through the container
+    assert_eq!(OWNER.access(&storage).get(), Ok(Some(8)));
+    assert!(Owner::new(&storage).is_set());
+}
+
+#[test]
+fn map() {
+    let mut storage = TestStorage::new();
+
+    let mut balances = Balances::new(&mut storage);
+    balances.set_balance("alice", &5).unwrap();
+    assert_eq!(balances.adjust("alice", |b| b.unwrap_or(0) + 2), Ok(7));
+    assert_eq!(balances.adjust("bob", |b| b.unwrap_or(0) + 2), Ok(2));
+
+    let balances = Balances::new(&storage);
+    assert_eq!(balances.of("alice"), Ok(Some(7)));
+    assert_eq!(balances.of(&"bob".to_string()), Ok(Some(2)));
+    assert_eq!(balances.of("carol"), Ok(None));
+    assert!(balances.holds("alice"));
+    assert!(!balances.holds("carol"));
+
+    assert_eq!(BALANCES.access(&storage).entry("alice").get(), Ok(Some(7)));
+}
+
+#[test]
+fn update_errors() {
+    let mut storage = TestStorage::new();
+    Balances::new(&mut storage)
+        .set_balance("alice", &5)
+        .unwrap();
+
+    fail_decode();
+    let result = Balances::new(&mut storage).adjust("alice", |b| b.unwrap() + 1);
+    assert!(matches!(result, Err(storey::Error::Decode(_))));
+
+    // nothing was written
+    assert_eq!(Balances::new(&storage).of("alice"), Ok(Some(5)));
+}
+
+#[test]
+fn zero_sized_wrappers() {
+    // the wrappers are the accessors, and nothing else
+    assert_eq!(
+        size_of::<Owner<&TestStorage>>(),
+        size_of::<ItemAccess<TestEncoding, u64, StorageBranch<&TestStorage>>>()
+    );
+    assert_eq!(
+        size_of::<Balances<&mut TestStorage>>(),
+        size_of::<MapAccess<String, Item<u64, TestEncoding>, StorageBranch<&mut TestStorage>>>()
+    );
+    assert_eq!(
+        size_of::<Empty<&TestStorage>>(),
+        size_of::<Owner<&TestStorage>>()
+    );
+}
@@ -0,0 +1,176 @@
+// The `prefix-audit` registry: containers record their prefixes when accessed, and overlapping
+// ones are reported.
+
+use std::panic::catch_unwind;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use storey::audit::{assert_no_prefix_collisions, clear, register};
+use storey::containers::{Column, Item, Map};
+use storey::prefix;
+use storey_testing::backend::TestStorage;
+use storey_testing::encoding::TestEncoding;
+
+// the registry is global, so the tests can't run concurrently
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn fresh_registry() -> MutexGuard<'static, ()> {
+    let guard = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    clear();
+    guard
+}
+
+fn report() -> Option<String> {
+    catch_unwind(assert_no_prefix_collisions)
+        .err()
+        .map(|payload| *payload.downcast::<String>().unwrap())
+}
+
+#[test]
+fn distinct_prefixes() {
+    let _guard = fresh_registry();
+    let mut storage = TestStorage::new();
+
+    let item = Item::<u64, TestEncoding>::new(0);
+    let map = Map::<String, Item<u64, TestEncoding>>::new(1);
+    let column = Column::<u64, TestEncoding>::new(2);
+
+    item.access(&mut storage).set(&1).unwrap();
+    map.access(&mut storage).entry_mut("foo").set(&2).unwrap();
+    column.access(&mut storage).push(&3).unwrap();
+
+    assert_eq!(report(), None);
+}
+
+#[test]
+fn same_container_accessed_twice() {
+    let _guard = fresh_registry();
+    let mut storage = TestStorage::new();
+
+    let item = Item::<u64, TestEncoding>::new(0);
+    item.access(&mut storage).set(&1).unwrap();
+    item.access(&storage).get().unwrap();
+
+    assert_eq!(report(), None);
+}
+
+#[test]
+fn equal_prefixes() {
+    let _guard = fresh_registry();
+    let mut storage = TestStorage::new();
+
+    let item = Item::<u64, TestEncoding>::new(3);
+    let map = Map::<String, Item<u64, TestEncoding>>::new(3);
+
+    item.access(&mut storage).set(&1).unwrap();
+    map.access(&mut storage).entry_mut("foo").set(&2).unwrap();
+
+    let report = report().unwrap();
+    assert!(report.starts_with("containers with overlapping prefixes:\n  `"));
+    assert!(report.contains("`storey::containers::item::Item<u64, "));
+    assert!(report.contains("`storey::containers::map::Map<"));
+    assert!(report.contains(&format!(" under [3] (accessed at {}:", file!())));
+    assert_eq!(report.lines().count(), 2);
+}
+
+#[test]
+fn byte_prefix_overlap() {
+    let _guard = fresh_registry();
+
+    let line = line!();
+    register(&[1], "outer");
+    register(&[1, 2], "inner");
+    register(&[2], "unrelated");
+    register(&[1, 3], "sibling");
+
+    let report = report().unwrap();
+    let lines: Vec<_> = report.lines().skip(1).collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with(&format!(
+        "  `outer` under [1] (accessed at {}:{}:",
+        file!(),
+        line + 1
+    )));
+    assert!(lines[0].contains(&format!(
+        ") and `inner` under [1, 2] (accessed at {}:{}:",
+        file!(),
+        line + 2
+    )));
+    assert!(lines[1].starts_with("  `outer` under [1] "));
+    assert!(lines[1].contains(") and `sibling` under [1, 3] "));
+}
+
+#[test]
+fn prefix_styles() {
+    let _guard = fresh_registry();
+    let mut storage = TestStorage::new();
+
+    // `b'b'` is the first byte of "balances", 211 the first byte of its hash
+    let by_byte = Item::<u64, TestEncoding>::new(b'b');
+    let by_hash_byte = Item::<u64, TestEncoding>::new(211);
+    let by_name = Map::<String, Item<u64, TestEncoding>>::new_with_prefix(prefix!("balances"));
+    let by_hash = Map::<String, Item<u64, TestEncoding>>::new_with_prefix(prefix!(hash "balances"));
+
+    by_byte.access(&mut storage).set(&1).unwrap();
+    by_hash_byte.access(&mut storage).set(&1).unwrap();
+    by_name
+        .access(&mut storage)
+        .entry_mut("foo")
+        .set(&2)
+        .unwrap();
+    by_hash
+        .access(&mut storage)
+        .entry_mut("foo")
+        .set(&2)
+        .unwrap();
+
+    let report = report().unwrap();
+    let lines: Vec<_> = report.lines().skip(1).collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains(" under [98] "));
+    assert!(lines[0].contains(" under [98, 97, 108, 97, 110, 99, 101, 115] "));
+    assert!(lines[1].contains(" under [211] "));
+    assert!(lines[1].contains(" under [211, 84, 197, 72] "));
+}
+
+#[test]
+fn string_prefixes() {
+    let _guard = fresh_registry();
+    let mut storage = TestStorage::new();
+
+    // "balance" is a prefix of "balances", and `b'c'` the first byte of "config"
+    let balance = Item::<u64, TestEncoding>::new_str("balance");
+    let balances = Map::<String, Item<u64, TestEncoding>>::new_str("balances");
+    let config = Item::<u64, TestEncoding>::new_str("config");
+    let by_byte = Column::<u64, TestEncoding>::new(b'c');
+    let control = Item::<u64, TestEncoding>::new(0);
+
+    balance.access(&mut storage).set(&1).unwrap();
+    balances
+        .access(&mut storage)
+        .entry_mut("foo")
+        .set(&2)
+        .unwrap();
+    config.access(&mut storage).set(&3).unwrap();
+    by_byte.access(&mut storage).push(&4).unwrap();
+    control.access(&mut storage).set(&5).unwrap();
+
+    let report = report().unwrap();
+    let lines: Vec<_> = report.lines().skip(1).collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains(" under [98, 97, 108, 97, 110, 99, 101] "));
+    assert!(lines[0].contains(" under [98, 97, 108, 97, 110, 99, 101, 115] "));
+    assert!(lines[1].contains(" under [99] "));
+    assert!(lines[1].contains(" under [99, 111, 110, 102, 105, 103] "));
+}
+
+#[test]
+fn cleared() {
+    let _guard = fresh_registry();
+
+    register(&[4], "a");
+    register(&[4], "b");
+    assert!(report().is_some());
+
+    clear();
+    assert_eq!(report(), None);
+}
@@ -0,0 +1,10 @@
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/compile_fail/*.rs");
+    #[cfg(feature = "derive")]
+    t.compile_fail("tests/compile_fail/derive/*.rs");
+    // the errors list the key types implementing a trait, and `cosmwasm-std` adds some
+    #[cfg(not(feature = "cosmwasm-std"))]
+    t.compile_fail("tests/compile_fail/keys/*.rs");
+}
@@ -0,0 +1,13 @@
+use storey::containers::{Item, Map};
+use storey_testing::encoding::TestEncoding;
+
+const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new(0);
+
+storey::accessor! {
+    struct Balances(BALANCES: Map<String, Item<u64, TestEncoding>>) {
+        fn of = get;
+        fn forget = remove;
+    }
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use storey::containers::{BoundedIterableAccessor as _, Item, Map};
+
+use storey_testing::backend::TestStorage;
+use storey_testing::encoding::TestEncoding;
+
+fn main() {
+    let storage = TestStorage::new();
+    let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+
+    // string keys are length-prefixed, so their byte order isn't their natural order
+    let _ = map
+        .access(&storage)
+        .bounded_keys(Some("a".to_string()), Some("b".to_string()));
+}
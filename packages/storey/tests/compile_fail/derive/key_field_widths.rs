@@ -0,0 +1,17 @@
+use storey::containers::{Key, OrderPreservingKey, OwnedKey};
+
+#[derive(Key, OwnedKey)]
+struct VariableNotLast {
+    #[storey(variable)]
+    name: String,
+    id: u32,
+}
+
+#[derive(Key, OwnedKey, OrderPreservingKey)]
+struct OrderedVariable {
+    id: u32,
+    #[storey(variable)]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+use storey::containers::{Item, Map};
+use storey::meta::StorageSchema;
+use storey_testing::encoding::TestEncoding;
+
+#[derive(StorageSchema)]
+struct Overlapping {
+    #[storey(prefix = "bal")]
+    balances: Map<String, Item<u64, TestEncoding>>,
+    #[storey(prefix = "balances")]
+    balances_v2: Map<String, Item<u64, TestEncoding>>,
+}
+
+#[derive(StorageSchema)]
+struct AfterName {
+    #[storey(prefix = "config")]
+    config: Item<u64, TestEncoding>,
+    owner: Item<u64, TestEncoding>,
+}
+
+#[derive(StorageSchema)]
+struct EmptyName {
+    #[storey(prefix = "")]
+    config: Item<u64, TestEncoding>,
+}
+
+fn main() {}
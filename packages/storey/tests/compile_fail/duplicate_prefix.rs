@@ -0,0 +1,10 @@
+use storey::containers::{Column, Item, Map};
+use storey_testing::encoding::TestEncoding;
+
+storey::declare_containers! {
+    CONFIG: Item<u64, TestEncoding> = 0,
+    BALANCES: Map<String, Item<u64, TestEncoding>> = 1,
+    HISTORY: Column<u64, TestEncoding> = 1,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use storey::containers::{Item, Map};
+use storey_testing::encoding::TestEncoding;
+
+// only the last component of a composite key may be of variable width, or decoding couldn't
+// tell where it ends
+const BY_NAME_THEN_ID: Map<(String, u32), Item<u64, TestEncoding>> = Map::new(0);
+const BY_ID_NAME_ID: Map<(u32, String, u32), Item<u64, TestEncoding>> = Map::new(1);
+const BY_NAMES: Map<[String; 2], Item<u64, TestEncoding>> = Map::new(2);
+
+fn main() {}
@@ -0,0 +1,17 @@
+use storey::migrate::StepOutcome;
+use storey_testing::backend::TestStorage;
+
+fn step(_: &mut TestStorage, _: Option<&[u8]>) -> Result<StepOutcome, ()> {
+    Ok(StepOutcome::Done)
+}
+
+storey::migration! {
+    storage: TestStorage,
+    error: (),
+    steps: [
+        1 -> 2: step,
+        3 -> 4: step,
+    ],
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use storey::containers::{assert_unique_prefixes, Item, Map};
+use storey_testing::encoding::TestEncoding;
+
+const CONFIG: Item<u64, TestEncoding> = Item::new_str("config");
+const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new_str("bal");
+const BALANCES_V2: Map<String, Item<u64, TestEncoding>> = Map::new_str("balances");
+
+const _: () = assert_unique_prefixes(&[
+    CONFIG.prefix_bytes(),
+    BALANCES.prefix_bytes(),
+    BALANCES_V2.prefix_bytes(),
+]);
+
+fn main() {}
@@ -0,0 +1,15 @@
+use storey::containers::{Item, Map};
+use storey::storage::ReadOnly;
+use storey_testing::backend::TestStorage;
+use storey_testing::encoding::TestEncoding;
+
+fn main() {
+    let storage = TestStorage::new();
+    let mut read_only = ReadOnly::new(&storage);
+
+    let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    map.access(&mut read_only)
+        .entry_mut("foo")
+        .set(&1337)
+        .unwrap();
+}
@@ -0,0 +1,9 @@
+use storey::containers::Item;
+use storey_testing::encoding::TestEncoding;
+
+storey::root! {
+    CONFIG: Item<u64, TestEncoding> = 255,
+    OWNER: Item<u64, TestEncoding>,
+}
+
+fn main() {}
@@ -1,8 +1,10 @@
-use storey::containers::{Column, Item, IterableAccessor as _, Map};
+use std::convert::Infallible;
+
+use storey::containers::{Column, Item, IterableAccessor as _, Key, Map, OwnedKey};
 
-use mocks::backend::TestStorage;
-use mocks::encoding::TestEncoding;
 use storey_storage::Storage as _;
+use storey_testing::backend::TestStorage;
+use storey_testing::encoding::TestEncoding;
 
 #[test]
 fn map_of_map() {
@@ -67,3 +69,90 @@ fn map_of_column() {
         ]
     );
 }
+
+#[test]
+fn key_macro_matches_nested_entries() {
+    let mut storage = TestStorage::new();
+
+    let map = Map::<String, Map<i32, Map<String, Item<u64, TestEncoding>>>>::new(0);
+    let owner = String::from("owner");
+
+    map.access(&mut storage)
+        .entry_mut(&owner)
+        .entry_mut(&-5)
+        .entry_mut("")
+        .set(&1337)
+        .unwrap();
+
+    let raw = [&[0][..], &storey::key!(owner, -5i32, "")].concat();
+    assert_eq!(
+        raw,
+        [0, 5, 111, 119, 110, 101, 114, 4, 127, 255, 255, 251, 0]
+    );
+    assert_eq!(storage.get(&raw), Some(1337u64.to_le_bytes().to_vec()));
+
+    // a prefix of the components is a prefix of the raw key
+    assert!(raw.starts_with(&[&[0][..], &storey::key!(&owner)].concat()));
+
+    assert_eq!(storey::key!(), Vec::<u8>::new());
+}
+
+/// A key stored as that many zero bytes, so the encoding of a smaller one is a prefix of the
+/// encoding of a bigger one.
+#[derive(Debug, PartialEq)]
+struct Tally(usize);
+
+impl Key for Tally {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.resize(buf.len() + self.0, 0);
+    }
+}
+
+impl OwnedKey for Tally {
+    type Error = Infallible;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Tally(bytes.len()))
+    }
+}
+
+#[test]
+fn outer_keys_need_not_be_prefix_free() {
+    let mut storage = TestStorage::new();
+
+    // every level is length-prefixed, so the outer key ends where its length says it does
+    let map = Map::<Tally, Map<Tally, Item<u64, TestEncoding>>>::new(0);
+    let mut access = map.access(&mut storage);
+
+    access
+        .entry_mut(&Tally(1))
+        .entry_mut(&Tally(2))
+        .set(&12)
+        .unwrap();
+    access
+        .entry_mut(&Tally(2))
+        .entry_mut(&Tally(1))
+        .set(&21)
+        .unwrap();
+    access
+        .entry_mut(&Tally(3))
+        .entry_mut(&Tally(0))
+        .set(&30)
+        .unwrap();
+
+    assert_eq!(
+        storage.get(&[0, 1, 0, 2, 0, 0]),
+        Some(12u64.to_le_bytes().to_vec())
+    );
+    assert_eq!(
+        map.access(&storage)
+            .pairs()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        [
+            ((Tally(1), (Tally(2), ())), 12),
+            ((Tally(2), (Tally(1), ())), 21),
+            ((Tally(3), (Tally(0), ())), 30),
+        ]
+    );
+}
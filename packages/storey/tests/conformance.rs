@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, HashMap};
+
+use storey::containers::{Column, Item, IterableAccessor as _, Map};
+use storey::storage::{
+    Buffered, Cached, Capacity, Forked, IterableStorage as _, Metered, Recording,
+    StorageBackendMut as _, StorageBranch, Traced, Transaction,
+};
+use storey_testing::backend::TestStorage;
+use storey_testing::conformance::{check_get_many, check_iteration, check_remove_range};
+use storey_testing::encoding::TestEncoding;
+
+#[test]
+fn test_storage() {
+    check_iteration(&mut TestStorage::new());
+}
+
+#[test]
+fn std_maps() {
+    check_iteration(&mut BTreeMap::new());
+    check_remove_range(&mut BTreeMap::new());
+    check_get_many(&mut BTreeMap::new());
+    check_get_many(&mut HashMap::new());
+}
+
+#[test]
+fn containers_over_std_maps() {
+    let mut storage = BTreeMap::new();
+    let map = Map::<String, Column<u64, TestEncoding>>::new(0);
+    map.access(&mut storage).entry_mut("foo").push(&1).unwrap();
+    map.access(&mut storage).entry_mut("foo").push(&2).unwrap();
+    map.access(&mut storage).entry_mut("bar").push(&3).unwrap();
+
+    let access = map.access(&storage);
+    assert_eq!(
+        access.keys().map(|k| k.unwrap()).collect::<Vec<_>>(),
+        [
+            ("bar".to_string(), 0),
+            ("foo".to_string(), 0),
+            ("foo".to_string(), 1)
+        ]
+    );
+    assert_eq!(
+        access.entry("foo").values().collect::<Result<Vec<_>, _>>(),
+        Ok(vec![1, 2])
+    );
+
+    // no iteration, but point reads and writes work
+    let mut storage = HashMap::new();
+    let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    map.access(&mut storage)
+        .entry_mut("foo")
+        .set(&1337)
+        .unwrap();
+    assert_eq!(map.access(&storage).entry("foo").get(), Ok(Some(1337)));
+    assert_eq!(map.access(&storage).entry("bar").get(), Ok(None));
+}
+
+#[test]
+fn transaction() {
+    let mut storage = TestStorage::new();
+    check_iteration(&mut Transaction::new(&mut storage));
+}
+
+#[test]
+fn committed_transaction() {
+    let mut storage = TestStorage::new();
+    let mut tx = Transaction::new(&mut storage);
+    check_iteration(&mut tx);
+    tx.commit();
+
+    // the writes reached the base, and checking again overwrites them with the same values
+    check_iteration(&mut storage);
+}
+
+#[test]
+fn buffered() {
+    let mut storage = TestStorage::new();
+    let mut buffered = Buffered::new(&mut storage);
+    check_iteration(&mut buffered);
+    buffered.flush();
+}
+
+#[test]
+fn forked() {
+    let base = TestStorage::new();
+    check_iteration(&mut Forked::new(&base, TestStorage::new()));
+}
+
+#[test]
+fn wrappers() {
+    check_iteration(&mut Cached::new(TestStorage::new(), Capacity::Entries(4)));
+    check_iteration(&mut Metered::new(TestStorage::new()));
+    check_iteration(&mut Recording::new(TestStorage::new()));
+    check_iteration(&mut Traced::new(TestStorage::new()));
+}
+
+#[test]
+fn remove_range() {
+    check_remove_range(&mut TestStorage::new());
+
+    let mut storage = TestStorage::new();
+    check_remove_range(&mut Transaction::new(&mut storage));
+
+    let mut storage = TestStorage::new();
+    let mut buffered = Buffered::new(&mut storage);
+    check_remove_range(&mut buffered);
+    buffered.flush();
+
+    let base = TestStorage::new();
+    check_remove_range(&mut Forked::new(&base, TestStorage::new()));
+
+    check_remove_range(&mut Cached::new(TestStorage::new(), Capacity::Entries(4)));
+    check_remove_range(&mut Traced::new(TestStorage::new()));
+}
+
+#[test]
+fn get_many() {
+    let mut storage = TestStorage::new();
+    check_get_many(&mut Transaction::new(&mut storage));
+
+    let mut storage = TestStorage::new();
+    let mut buffered = Buffered::new(&mut storage);
+    check_get_many(&mut buffered);
+    buffered.flush();
+
+    let base = TestStorage::new();
+    check_get_many(&mut Forked::new(&base, TestStorage::new()));
+
+    check_get_many(&mut Cached::new(TestStorage::new(), Capacity::Entries(4)));
+    check_get_many(&mut Metered::new(TestStorage::new()));
+    check_get_many(&mut Recording::new(TestStorage::new()));
+    check_get_many(&mut Traced::new(TestStorage::new()));
+}
+
+#[test]
+fn branch_remove_range() {
+    let mut storage = TestStorage::new();
+    storage.set(&[6, 255], b"before");
+    storage.set(&[8], b"after");
+
+    check_remove_range(&mut StorageBranch::new(&mut storage, vec![7]));
+
+    assert_eq!(
+        storage.pairs(None, None).collect::<Vec<_>>(),
+        [
+            (vec![6, 255], b"before".to_vec()),
+            (vec![8], b"after".to_vec())
+        ]
+    );
+}
@@ -0,0 +1,102 @@
+// Containers print their prefix and accessors the raw prefix of their keys, so that neither
+// the key, value and encoding types nor the storage have to be `Debug`.
+
+use std::collections::BTreeMap;
+
+use storey::containers::{Column, Item, IterableAccessor as _, Map};
+use storey_testing::backend::TestStorage;
+use storey_testing::encoding::TestEncoding;
+
+// `TestEncoding` is neither `Debug` nor `Clone`
+type Value = Item<u64, TestEncoding>;
+
+#[test]
+fn containers() {
+    let item = Value::new(0);
+    let map = Map::<String, Map<u32, Value>>::new(1);
+    let column = Column::<u64, TestEncoding>::new_with_prefix(b"balances");
+
+    assert_eq!(format!("{item:?}"), "Item { prefix: [0] }");
+    assert_eq!(format!("{map:?}"), "Map { prefix: [1] }");
+    assert_eq!(
+        format!("{column:?}"),
+        "Column { prefix: [98, 97, 108, 97, 110, 99, 101, 115] }"
+    );
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Containers {
+        item: Value,
+        column: Column<u64, TestEncoding>,
+    }
+
+    let containers = Containers { item, column };
+    let copy = containers;
+    assert_eq!(copy, containers);
+    assert_ne!(Value::new(1), item);
+}
+
+#[test]
+fn equal_prefixes() {
+    // containers are equal when they're stored under the same prefix
+    assert_eq!(Value::new(3), Value::new_with_prefix(&[3]));
+    assert_ne!(
+        Map::<String, Value>::new(3),
+        Map::<String, Value>::new_with_prefix(&[3, 0])
+    );
+}
+
+#[test]
+fn accessors() {
+    let mut storage = TestStorage::new();
+    let item = Value::new(0);
+    let map = Map::<String, Map<String, Column<u64, TestEncoding>>>::new(1);
+
+    assert_eq!(
+        format!("{:?}", item.access(&storage)),
+        "ItemAccess { prefix: [0] }"
+    );
+    assert_eq!(
+        format!("{:?}", map.access(&mut storage)),
+        "MapAccess { prefix: [1] }"
+    );
+
+    // nested accessors show the whole prefix, not only the part their map added
+    let access = map.access(&storage);
+    let inner = access.entry("ab");
+    assert_eq!(format!("{inner:?}"), "MapAccess { prefix: [1, 2, 97, 98] }");
+    assert_eq!(
+        format!("{:?}", inner.entry("c")),
+        "ColumnAccess { prefix: [1, 2, 97, 98, 1, 99] }"
+    );
+
+    let mut access = map.access(&mut storage);
+    let mut inner = access.entry_mut("ab");
+    assert_eq!(
+        format!("{:?}", inner.entry_mut("c")),
+        "ColumnAccess { prefix: [1, 2, 97, 98, 1, 99] }"
+    );
+}
+
+#[test]
+fn iterators() {
+    let mut storage = BTreeMap::<Vec<u8>, Vec<u8>>::new();
+    let column = Column::<u64, TestEncoding>::new(0);
+
+    let mut access = column.access(&mut storage);
+    access.push(&1).unwrap();
+    access.push(&2).unwrap();
+
+    let access = column.access(&storage);
+    let mut pairs = access.pairs();
+    assert_eq!(pairs.next().unwrap().unwrap(), (0, 1));
+
+    // a clone carries on from where the iterator is
+    let rest = pairs.clone().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(rest, [(1, 2)]);
+    assert_eq!(pairs.next().unwrap().unwrap(), (1, 2));
+
+    let keys = access.keys();
+    assert_eq!(keys.clone().count(), 2);
+    assert!(format!("{keys:?}").starts_with("StorableKeys { inner: "));
+    assert!(format!("{:?}", access.values()).starts_with("StorableValues { inner: "));
+}
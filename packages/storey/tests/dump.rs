@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use std::io::Cursor;
+
+use storey::containers::{Column, Item, IterableAccessor as _, Map};
+use storey::dump::{
+    export, export_iter, import, import_stream, read_json_lines, write_json_lines, Dump,
+    ImportError, OnExisting,
+};
+use storey::encoding::JsonEncoding;
+use storey::storage::{IterableStorage as _, Transaction};
+use storey_testing::backend::TestStorage;
+
+const BALANCES: Map<String, Item<u64, JsonEncoding>> = Map::new(0);
+const HISTORY: Column<String, JsonEncoding> = Column::new(1);
+
+fn seed(storage: &mut TestStorage) {
+    for (name, balance) in [("alice", 100), ("bob", 5), ("carol", 0)] {
+        BALANCES
+            .access(&mut *storage)
+            .entry_mut(name)
+            .set(&balance)
+            .unwrap();
+        HISTORY
+            .access(&mut *storage)
+            .push(&format!("opened {name}"))
+            .unwrap();
+    }
+}
+
+fn balances<S: storey::storage::IterableStorage>(storage: &S) -> Vec<(String, u64)> {
+    BALANCES
+        .access(storage)
+        .pairs()
+        .map(|pair| pair.map(|((name, ()), balance)| (name, balance)))
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+#[test]
+fn round_trip_through_json() {
+    let mut source = TestStorage::new();
+    seed(&mut source);
+
+    // the whole storage, container metadata included
+    let json = serde_json::to_string(&export(&source, &[&[]])).unwrap();
+    let dump: Dump = serde_json::from_str(&json).unwrap();
+
+    let mut target = BTreeMap::new();
+    import(&mut target, &dump, OnExisting::Error).unwrap();
+
+    assert_eq!(balances(&target), balances(&source));
+    assert_eq!(HISTORY.access(&target).len().unwrap(), 3);
+    assert_eq!(
+        HISTORY.access(&target).get(1).unwrap(),
+        Some("opened bob".to_string())
+    );
+
+    // and back, from the other backend type
+    let mut back = TestStorage::new();
+    import(&mut back, &export(&target, &[&[]]), OnExisting::Error).unwrap();
+    assert_eq!(
+        back.pairs(None, None).collect::<Vec<_>>(),
+        source.pairs(None, None).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn round_trip_through_json_lines() {
+    let mut source = TestStorage::new();
+    seed(&mut source);
+
+    let mut file = Vec::new();
+    let written = write_json_lines(&mut file, export_iter(&source, &[&[0]])).unwrap();
+    assert_eq!(written, 3);
+    assert_eq!(String::from_utf8_lossy(&file).lines().count(), 3);
+
+    let mut target = BTreeMap::new();
+    let imported = import_stream(
+        &mut target,
+        read_json_lines(Cursor::new(&file)),
+        OnExisting::Error,
+    )
+    .unwrap();
+    assert_eq!(imported, 3);
+    assert_eq!(balances(&target), balances(&source));
+
+    // only the balances were exported
+    assert_eq!(HISTORY.access(&target).len().unwrap(), 0);
+}
+
+#[test]
+fn existing_keys() {
+    let mut source = TestStorage::new();
+    seed(&mut source);
+    let dump = export(&source, &[&[0]]);
+
+    let mut target = BTreeMap::new();
+    BALANCES
+        .access(&mut target)
+        .entry_mut("bob")
+        .set(&1000)
+        .unwrap();
+    BALANCES
+        .access(&mut target)
+        .entry_mut("dave")
+        .set(&7)
+        .unwrap();
+
+    let err = import(&mut target, &dump, OnExisting::Error).unwrap_err();
+    assert!(matches!(err, ImportError::Exists { .. }));
+    assert_eq!(
+        balances(&target),
+        [("bob".to_string(), 1000), ("dave".to_string(), 7)]
+    );
+
+    import(&mut target, &dump, OnExisting::Overwrite).unwrap();
+    assert_eq!(
+        balances(&target),
+        [
+            ("bob".to_string(), 5),
+            ("dave".to_string(), 7),
+            ("alice".to_string(), 100),
+            ("carol".to_string(), 0),
+        ]
+    );
+}
+
+#[test]
+fn streaming_import_in_a_transaction() {
+    let mut source = TestStorage::new();
+    seed(&mut source);
+
+    let mut file = Vec::new();
+    write_json_lines(&mut file, export_iter(&source, &[&[0]])).unwrap();
+    file.extend_from_slice(b"not an entry\n");
+
+    let mut target = BTreeMap::new();
+    let mut tx = Transaction::new(&mut target);
+    let err = import_stream(
+        &mut tx,
+        read_json_lines(Cursor::new(&file)),
+        OnExisting::Error,
+    )
+    .unwrap_err();
+    assert!(
+        matches!(err, ImportError::Source(err) if err.kind() == std::io::ErrorKind::InvalidData)
+    );
+
+    // the transaction is dropped without being committed
+    drop(tx);
+    assert!(target.is_empty());
+}
@@ -0,0 +1,69 @@
+use storey::containers::{BoundedIterableAccessor as _, Item, IterableAccessor as _, Map};
+use storey::storage::{
+    Cached, Capacity, DynStorage, RevIterableStorage as _, StorageBackend as _, Transaction,
+};
+use storey_testing::backend::TestStorage;
+use storey_testing::conformance::{check_get_many, check_iteration, check_remove_range};
+use storey_testing::encoding::TestEncoding;
+
+fn open(cached: bool) -> Box<dyn DynStorage> {
+    if cached {
+        Box::new(Cached::new(TestStorage::new(), Capacity::Entries(8)))
+    } else {
+        Box::new(TestStorage::new())
+    }
+}
+
+#[test]
+fn map_over_boxed_backend() {
+    for cached in [false, true] {
+        let mut storage = open(cached);
+
+        let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+        for i in [0, 1, 3, 4] {
+            access.entry_mut(&i).set(&(u64::from(i) * 10)).unwrap();
+        }
+
+        let access = map.access(&storage);
+        assert_eq!(access.entry(&3).get(), Ok(Some(30)));
+        assert_eq!(
+            access.pairs().collect::<Result<Vec<_>, _>>().unwrap(),
+            [((0, ()), 0), ((1, ()), 10), ((3, ()), 30), ((4, ()), 40)]
+        );
+        assert_eq!(
+            access
+                .bounded_values(Some(1), Some(4))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            [10, 30]
+        );
+        assert_eq!(access.get_many(&[&4, &2]), Ok(vec![Some(40), None]));
+
+        assert_eq!(storage.rev_keys(None, None).count(), 4);
+    }
+}
+
+#[test]
+fn boxed_wrapper_of_borrowed_backend() {
+    let mut base = TestStorage::new();
+
+    {
+        let mut storage: Box<dyn DynStorage + '_> = Box::new(Transaction::new(&mut base));
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        map.access(&mut storage)
+            .entry_mut("foo")
+            .set(&1337)
+            .unwrap();
+        // dropped without committing
+    }
+
+    assert_eq!(base.get(&[0, 3, b'f', b'o', b'o']), None);
+}
+
+#[test]
+fn conformance() {
+    check_iteration(&mut open(false));
+    check_remove_range(&mut open(false));
+    check_get_many(&mut open(true));
+}
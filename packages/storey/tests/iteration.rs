@@ -1,7 +1,10 @@
-use storey::containers::{Item, IterableAccessor as _, Map};
+use storey::containers::{
+    Column, Item, ItemKeyDecodeError, IterableAccessor as _, KVDecodeError, Map, MapKeyDecodeError,
+};
 
-use mocks::backend::TestStorage;
-use mocks::encoding::TestEncoding;
+use storey_storage::StorageMut as _;
+use storey_testing::backend::TestStorage;
+use storey_testing::encoding::{fail_decode, TestDecodeError, TestEncoding};
 
 #[test]
 fn map_of_map_iteration() {
@@ -44,3 +47,243 @@ fn map_of_map_iteration() {
         ]
     );
 }
+
+#[test]
+fn nested_entries_with_max_bytes() {
+    let mut storage = TestStorage::new();
+
+    let map = Map::<Vec<u8>, Map<u32, Item<u64, TestEncoding>>>::new(0);
+    let mut access = map.access(&mut storage);
+
+    // the entries' raw prefixes end in one, several, or only 255s (after the length)
+    for (outer, inner, value) in [
+        (vec![1, 254], 1, 1),
+        (vec![1, 255], 2, 2),
+        (vec![1, 255], u32::MAX, 3),
+        (vec![255, 255], 4, 4),
+        (vec![255, 255], u32::MAX, 5),
+        (vec![255], 6, 6),
+    ] {
+        access
+            .entry_mut(&outer)
+            .entry_mut(&inner)
+            .set(&value)
+            .unwrap();
+    }
+
+    let values = |outer: &[u8]| {
+        access
+            .entry(&outer.to_vec())
+            .values()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    };
+    assert_eq!(values(&[1, 254]), [1]);
+    assert_eq!(values(&[1, 255]), [2, 3]);
+    assert_eq!(values(&[255, 255]), [4, 5]);
+    assert_eq!(values(&[255]), [6]);
+    assert_eq!(
+        access
+            .entry(&vec![255, 255])
+            .keys()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        [(4, ()), (u32::MAX, ())]
+    );
+
+    // the innermost prefix for ([255, 255], u32::MAX) is all 255s after the lengths
+    assert_eq!(
+        access
+            .entry(&vec![255, 255])
+            .entry(&u32::MAX)
+            .get()
+            .unwrap(),
+        Some(5)
+    );
+    assert_eq!(access.values().count(), 6);
+}
+
+#[test]
+fn iteration_errors_propagate() {
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn total(storage: &TestStorage) -> Result<u64, Error> {
+        let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
+
+        let mut total = 0;
+        for pair in map.access(storage).pairs() {
+            let (_, value) = pair?;
+            total += value;
+        }
+        Ok(total)
+    }
+
+    let mut storage = TestStorage::new();
+
+    let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
+    let mut access = map.access(&mut storage);
+    access.entry_mut("foo").entry_mut("bar").set(&1337).unwrap();
+    access.entry_mut("foo").entry_mut("baz").set(&42).unwrap();
+
+    assert_eq!(total(&storage).unwrap(), 1379);
+
+    // corrupt one of the values
+    storage.set(
+        &[&[0][..], &storey::key!("foo", "baz")].concat(),
+        &[1, 2, 3],
+    );
+
+    assert_eq!(
+        total(&storage).unwrap_err().to_string(),
+        "failed to decode value: invalid length, expected 8 bytes, got 3"
+    );
+}
+
+#[test]
+fn corrupted_nested_keys() {
+    let mut storage = TestStorage::new();
+
+    let map = Map::<String, Map<String, Map<String, Item<u64, TestEncoding>>>>::new(0);
+    map.access(&mut storage)
+        .entry_mut("foo")
+        .entry_mut("bar")
+        .entry_mut("baz")
+        .set(&1)
+        .unwrap();
+
+    // the innermost key claims 4 bytes, but only 3 follow
+    storage.set(
+        &[
+            0, 3, b'f', b'o', b'o', 3, b'b', b'a', b'r', 4, b'q', b'u', b'x',
+        ],
+        &[0; 8],
+    );
+    // a trailing byte where the item's empty key should be
+    storage.set(&[0, 3, b'q', b'u', b'x', 1, b'a', 1, b'b', 0], &[0; 8]);
+
+    let errors = map
+        .access(&storage)
+        .keys()
+        .filter_map(Result::err)
+        .collect::<Vec<_>>();
+    assert_eq!(errors.len(), 2);
+
+    assert_eq!(
+        errors[0].key,
+        [3, b'f', b'o', b'o', 3, b'b', b'a', b'r', 4, b'q', b'u', b'x']
+    );
+    assert_eq!(errors[0].offset, 8);
+    assert_eq!(errors[0].component, 2);
+    assert_eq!(
+        errors[0].source,
+        MapKeyDecodeError::Inner(MapKeyDecodeError::Inner(MapKeyDecodeError::KeyTooShort(4)))
+    );
+    assert_eq!(
+        errors[0].to_string(),
+        "invalid key 03666f6f0362617204717578 at byte 8 (component 2): sub key decode error: \
+         sub key decode error: key too short, expected 4 bytes after length prefix"
+    );
+
+    assert_eq!(errors[1].offset, 8);
+    assert_eq!(errors[1].component, 3);
+    assert_eq!(
+        errors[1].source,
+        MapKeyDecodeError::Inner(MapKeyDecodeError::Inner(MapKeyDecodeError::Inner(
+            ItemKeyDecodeError
+        )))
+    );
+
+    // pairs report the same errors
+    let error = map.access(&storage).pairs().find_map(Result::err).unwrap();
+    assert_eq!(error, KVDecodeError::Key(errors[0].clone()));
+}
+
+#[test]
+fn injected_failures() {
+    let mut storage = TestStorage::new();
+
+    let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    map.access(&mut storage).entry_mut("foo").set(&1).unwrap();
+    map.access(&mut storage).entry_mut("bar").set(&2).unwrap();
+
+    storage.fail_next_get();
+    assert_eq!(
+        map.access(&storage).entry("foo").get(),
+        Err(storey::Error::Decode(TestDecodeError::InvalidLength(0)))
+    );
+    assert_eq!(map.access(&storage).entry("foo").get(), Ok(Some(1)));
+
+    fail_decode();
+    let values = map.access(&storage).values().collect::<Vec<_>>();
+    assert_eq!(values.len(), 2);
+    assert!(values[0].is_err());
+    assert_eq!(values[1], Ok(1));
+}
+
+#[test]
+fn dump() {
+    let mut storage = TestStorage::new();
+
+    let map = Map::<String, Map<(u32, String), Item<u64, TestEncoding>>>::new(0);
+    let mut access = map.access(&mut storage);
+    access
+        .entry_mut("alice")
+        .entry_mut(&(7, "x".to_string()))
+        .set(&100)
+        .unwrap();
+    access
+        .entry_mut("bob")
+        .entry_mut(&(1, "".to_string()))
+        .set(&5)
+        .unwrap();
+
+    let column = Column::<u64, TestEncoding>::new(1);
+    column.access(&mut storage).push(&3).unwrap();
+    column.access(&mut storage).push(&4).unwrap();
+
+    // in raw key order, so shorter strings first
+    assert_eq!(
+        map.access(&storage).dump(),
+        "\"bob\" / (1, \"\") => 5\n\
+         \"alice\" / (7, \"x\") => 100\n"
+    );
+    assert_eq!(
+        column.access(&storage).dump_entries(),
+        [
+            ("0".to_string(), "3".to_string()),
+            ("1".to_string(), "4".to_string())
+        ]
+    );
+    assert_eq!(
+        Map::<String, Item<u64, TestEncoding>>::new(2)
+            .access(&storage)
+            .dump(),
+        ""
+    );
+}
+
+#[test]
+fn dump_corrupted_entries() {
+    let mut storage = TestStorage::new();
+
+    let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
+    map.access(&mut storage)
+        .entry_mut("foo")
+        .entry_mut("bar")
+        .set(&1337)
+        .unwrap();
+
+    // a nested key that's not UTF-8, and a value that's too short
+    storage.set(&[0, 3, b'f', b'o', b'o', 1, 0xff], &1u64.to_le_bytes());
+    storage.set(&[0, 3, b'f', b'o', b'o', 1, b'a'], &[1, 2, 3]);
+
+    // the entries that don't decode are still there, and the rest is unaffected
+    assert_eq!(
+        map.access(&storage).dump_entries(),
+        [
+            ("\"foo\" / \"a\"".to_string(), "<invalid value 010203: invalid length, expected 8 bytes, got 3>".to_string()),
+            ("<invalid key 03666f6f01ff at byte 4 (component 1): sub key decode error: invalid UTF8>".to_string(), "1".to_string()),
+            ("\"foo\" / \"bar\"".to_string(), "1337".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,345 @@
+//! Property tests for map key encodings.
+//!
+//! Every key type registered with `key_properties!` is checked for:
+//! - injectivity: distinct keys encode to distinct bytes,
+//! - round-tripping: decoding an encoded key yields the original key,
+//! - prefix-freeness: within a composite key, no component's (length-prefixed) encoding is a
+//!   prefix of another's, so components can't bleed into each other,
+//! - and, for `OrderPreservingKey` types, that byte order matches the key's own order.
+//!
+//! New key impls should be registered here.
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use storey::containers::{Key, OrderPreservingKey, OwnedKey};
+
+fn encode<K: Key>(key: &K) -> Vec<u8> {
+    let mut buf = Vec::new();
+    key.encode(&mut buf);
+    buf
+}
+
+fn assert_order_preserving<K: OrderPreservingKey + Ord>(a: &K, b: &K) -> Result<(), TestCaseError> {
+    prop_assert_eq!(a.cmp(b), encode(a).cmp(&encode(b)));
+    prop_assert_eq!(a.cmp(b), storey::key!(a).cmp(&storey::key!(b)));
+    Ok(())
+}
+
+macro_rules! key_properties {
+    ($name:ident: $t:ty = $strategy:expr) => {
+        key_properties!(@impl $name: $t = $strategy, {});
+    };
+    ($name:ident: $t:ty = $strategy:expr, ordered) => {
+        key_properties!(@impl $name: $t = $strategy, {
+            #[test]
+            fn order_preserving(a in $strategy, b in $strategy) {
+                assert_order_preserving::<$t>(&a, &b)?;
+            }
+        });
+    };
+    (@impl $name:ident: $t:ty = $strategy:expr, { $($extra:tt)* }) => {
+        mod $name {
+            use super::*;
+
+            proptest! {
+                #[test]
+                fn injective(a in $strategy, b in $strategy) {
+                    prop_assert_eq!(a == b, encode::<$t>(&a) == encode::<$t>(&b));
+                }
+
+                #[test]
+                fn roundtrip(key in $strategy) {
+                    let bytes = encode::<$t>(&key);
+                    prop_assert!(<$t as OwnedKey>::from_bytes(&bytes) == Ok(key));
+                }
+
+                #[test]
+                fn prefix_free(a in $strategy, b in $strategy) {
+                    let (a_raw, b_raw) = (storey::key!(a), storey::key!(b));
+                    prop_assert_eq!(a == b, a_raw.starts_with(&b_raw));
+                    prop_assert_eq!(a == b, b_raw.starts_with(&a_raw));
+
+                    // the same has to hold with a component following it
+                    let followed = storey::key!(a, 0u8);
+                    prop_assert_eq!(a == b, followed.starts_with(&b_raw));
+                }
+
+                $($extra)*
+            }
+        }
+    };
+}
+
+/// Strings of at most 240 bytes, so they fit the one-byte length prefix.
+fn string() -> impl Strategy<Value = String> {
+    "\\PC{0,60}"
+}
+
+fn bytes() -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..=255)
+}
+
+key_properties!(string_key: String = string());
+key_properties!(bytes_key: Vec<u8> = bytes());
+
+key_properties!(u8_key: u8 = any::<u8>(), ordered);
+key_properties!(u16_key: u16 = any::<u16>(), ordered);
+key_properties!(u32_key: u32 = any::<u32>(), ordered);
+key_properties!(u64_key: u64 = any::<u64>(), ordered);
+key_properties!(u128_key: u128 = any::<u128>(), ordered);
+key_properties!(i8_key: i8 = any::<i8>(), ordered);
+key_properties!(i16_key: i16 = any::<i16>(), ordered);
+key_properties!(i32_key: i32 = any::<i32>(), ordered);
+key_properties!(i64_key: i64 = any::<i64>(), ordered);
+key_properties!(i128_key: i128 = any::<i128>(), ordered);
+key_properties!(usize_key: usize = any::<usize>(), ordered);
+key_properties!(isize_key: isize = any::<isize>(), ordered);
+
+key_properties!(u16_array_key: [u16; 3] = any::<[u16; 3]>(), ordered);
+key_properties!(i64_array_key: [i64; 2] = any::<[i64; 2]>(), ordered);
+key_properties!(nested_array_key: [[i8; 2]; 2] = any::<[[i8; 2]; 2]>(), ordered);
+
+key_properties!(int_pair_key: (u32, i8) = any::<(u32, i8)>(), ordered);
+key_properties!(int_triple_key: (i16, [u8; 2], u64) = any::<(i16, [u8; 2], u64)>(), ordered);
+key_properties!(int_string_key: (u32, String) = (any::<u32>(), string()));
+key_properties!(mixed_triple_key: (i64, u8, Vec<u8>) = (any::<i64>(), any::<u8>(), vec(any::<u8>(), 0..=200)));
+
+#[cfg(feature = "cosmwasm-std")]
+mod cosmwasm {
+    use super::*;
+
+    use cosmwasm_std::{
+        Addr, CanonicalAddr, Decimal, Decimal256, Timestamp, Uint128, Uint256, Uint64,
+    };
+    use storey::containers::InvalidKeyLength;
+
+    /// Any value, with zero and the maximum value coming up often.
+    fn with_edges<T: Clone + std::fmt::Debug + 'static>(
+        min: T,
+        max: T,
+        any: impl Strategy<Value = T> + 'static,
+    ) -> impl Strategy<Value = T> {
+        prop_oneof![1 => Just(min), 1 => Just(max), 8 => any]
+    }
+
+    fn uint64() -> impl Strategy<Value = Uint64> {
+        with_edges(
+            Uint64::zero(),
+            Uint64::MAX,
+            any::<u64>().prop_map(Uint64::new),
+        )
+    }
+
+    fn uint128() -> impl Strategy<Value = Uint128> {
+        with_edges(
+            Uint128::zero(),
+            Uint128::MAX,
+            any::<u128>().prop_map(Uint128::new),
+        )
+    }
+
+    fn uint256() -> impl Strategy<Value = Uint256> {
+        let any = any::<[u8; 32]>().prop_map(Uint256::from_be_bytes);
+        with_edges(Uint256::zero(), Uint256::MAX, any)
+    }
+
+    /// Decimals of every magnitude: the atomics are shifted right by a random number of bits, so
+    /// tiny fractions come up as often as huge numbers.
+    fn decimal() -> impl Strategy<Value = Decimal> {
+        let any =
+            (any::<u128>(), 0..128u32).prop_map(|(atomics, shift)| Decimal::raw(atomics >> shift));
+        with_edges(Decimal::zero(), Decimal::MAX, any)
+    }
+
+    fn decimal256() -> impl Strategy<Value = Decimal256> {
+        let any = (any::<[u8; 32]>(), 0..256u32)
+            .prop_map(|(atomics, shift)| Decimal256::new(Uint256::from_be_bytes(atomics) >> shift));
+        with_edges(Decimal256::zero(), Decimal256::MAX, any)
+    }
+
+    key_properties!(addr_key: Addr = string().prop_map(Addr::unchecked));
+    key_properties!(canonical_addr_key: CanonicalAddr = vec(any::<u8>(), 0..=64).prop_map(CanonicalAddr::from));
+    key_properties!(uint64_key: Uint64 = uint64(), ordered);
+    key_properties!(uint128_key: Uint128 = uint128(), ordered);
+    key_properties!(uint256_key: Uint256 = uint256(), ordered);
+    key_properties!(timestamp_key: Timestamp = any::<u64>().prop_map(Timestamp::from_nanos), ordered);
+    key_properties!(decimal_key: Decimal = decimal(), ordered);
+    key_properties!(decimal256_key: Decimal256 = decimal256(), ordered);
+
+    #[test]
+    fn uint_keys_have_a_strict_length() {
+        assert_eq!(
+            Uint64::from_bytes(&[0; 9]),
+            Err(InvalidKeyLength {
+                expected: 8,
+                actual: 9
+            })
+        );
+        assert_eq!(
+            Uint128::from_bytes(&[0; 8]),
+            Err(InvalidKeyLength {
+                expected: 16,
+                actual: 8
+            })
+        );
+        assert_eq!(
+            Uint256::from_bytes(&[0; 31]),
+            Err(InvalidKeyLength {
+                expected: 32,
+                actual: 31
+            })
+        );
+        assert_eq!(Uint256::from_bytes(&[0xff; 32]), Ok(Uint256::MAX));
+    }
+
+    #[test]
+    fn timestamps_are_stored_like_u64() {
+        let time = Timestamp::from_nanos(1_700_000_000_000_000_000);
+        assert_eq!(storey::key!(time), storey::key!(time.nanos()));
+    }
+
+    #[test]
+    fn decimals_are_stored_as_atomics() {
+        let price = Decimal::percent(150);
+        assert_eq!(
+            storey::key!(price),
+            storey::key!(Uint128::new(1_500_000_000_000_000_000))
+        );
+
+        let price = Decimal256::percent(150);
+        assert_eq!(storey::key!(price), storey::key!(price.atomics()));
+
+        // the smallest step is kept exactly
+        let tiny = Decimal::raw(1);
+        assert_eq!(Decimal::from_bytes(&encode(&tiny)), Ok(tiny));
+        assert!(encode(&tiny) > encode(&Decimal::zero()));
+    }
+}
+
+#[cfg(feature = "derive")]
+mod derived {
+    use super::*;
+
+    use std::convert::Infallible;
+
+    use storey::containers::{FixedSizeKey, InvalidUtf8, MapKeyDecodeError, StructKeyDecodeError};
+
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Key, OwnedKey, OrderPreservingKey)]
+    struct PoolKey {
+        denom_a_id: u32,
+        denom_b_id: u32,
+    }
+
+    fn pool_key() -> impl Strategy<Value = PoolKey> {
+        any::<(u32, u32)>().prop_map(|(denom_a_id, denom_b_id)| PoolKey {
+            denom_a_id,
+            denom_b_id,
+        })
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Key, OwnedKey, OrderPreservingKey)]
+    struct Signed(i16, [u8; 3], i64);
+
+    fn signed() -> impl Strategy<Value = Signed> {
+        any::<(i16, [u8; 3], i64)>().prop_map(|(a, b, c)| Signed(a, b, c))
+    }
+
+    /// A derived key as a field of another one.
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Key, OwnedKey, OrderPreservingKey)]
+    struct Nested {
+        pool: PoolKey,
+        epoch: u8,
+    }
+
+    fn nested() -> impl Strategy<Value = Nested> {
+        (pool_key(), any::<u8>()).prop_map(|(pool, epoch)| Nested { pool, epoch })
+    }
+
+    #[derive(Debug, Clone, PartialEq, Key, OwnedKey)]
+    struct Named {
+        owner_id: u64,
+        #[storey(variable)]
+        name: String,
+    }
+
+    fn named() -> impl Strategy<Value = Named> {
+        (any::<u64>(), string()).prop_map(|(owner_id, name)| Named { owner_id, name })
+    }
+
+    #[derive(Debug, Clone, PartialEq, Key, OwnedKey)]
+    struct OnlyBytes(#[storey(variable)] Vec<u8>);
+
+    key_properties!(pool_key_key: PoolKey = pool_key(), ordered);
+    key_properties!(signed_key: Signed = signed(), ordered);
+    key_properties!(nested_key: Nested = nested(), ordered);
+    key_properties!(named_key: Named = named());
+    key_properties!(only_bytes_key: OnlyBytes = bytes().prop_map(OnlyBytes));
+
+    proptest! {
+        #[test]
+        fn encoded_like_a_tuple(key in signed()) {
+            prop_assert_eq!(encode(&key), encode(&(key.0, key.1, key.2)));
+        }
+    }
+
+    #[test]
+    fn widths() {
+        assert_eq!(PoolKey::SIZE, 8);
+        assert_eq!(PoolKey::WIDTH, Some(8));
+        assert_eq!(Nested::SIZE, 9);
+        assert_eq!(Signed::WIDTH, Some(13));
+        assert_eq!(Named::WIDTH, None);
+    }
+
+    #[test]
+    fn wrong_lengths() {
+        assert_eq!(
+            PoolKey::from_bytes(&[0; 9]),
+            Err(StructKeyDecodeError::InvalidLength {
+                expected: 8,
+                actual: 9
+            })
+        );
+        assert_eq!(
+            PoolKey::from_bytes(&[0; 7]),
+            Err(StructKeyDecodeError::InvalidLength {
+                expected: 8,
+                actual: 7
+            })
+        );
+        assert_eq!(
+            Named::from_bytes(&[0; 7]),
+            Err(StructKeyDecodeError::TooShort {
+                expected: 8,
+                actual: 7
+            })
+        );
+        assert_eq!(
+            Named::from_bytes(&[0; 8]),
+            Ok(Named {
+                owner_id: 0,
+                name: String::new()
+            })
+        );
+    }
+
+    #[test]
+    fn failing_field() {
+        let mut bytes = vec![0; 8];
+        bytes.push(0xff);
+
+        let error = Named::from_bytes(&bytes).unwrap_err();
+        assert_eq!(
+            error,
+            StructKeyDecodeError::Field {
+                field: "name",
+                source: InvalidUtf8.into()
+            }
+        );
+        assert_eq!(error.to_string(), "field `name`: invalid UTF8");
+        assert_eq!(
+            MapKeyDecodeError::<Infallible>::from(error),
+            MapKeyDecodeError::InvalidUtf8
+        );
+    }
+}
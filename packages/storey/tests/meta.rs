@@ -0,0 +1,169 @@
+// The metadata `StorageSchema` and `container_meta!` generate.
+
+use storey::containers::{Column, Item, Map};
+use storey::encoding::RawEncoding;
+use storey::meta::{
+    container_meta, ContainerEntry, ContainerMeta, KeyMeta, StorageSchema, ValueMeta,
+};
+use storey_testing::encoding::TestEncoding;
+
+#[allow(dead_code)]
+struct Foo {
+    bar: u32,
+}
+
+// the derive needs the containers written out, so no type aliases
+#[allow(dead_code, clippy::type_complexity)]
+#[derive(StorageSchema)]
+struct State {
+    config: Item<Foo, RawEncoding>,
+    #[storey(prefix = 3)]
+    positions: Map<String, Map<u64, Item<Foo, TestEncoding>>>,
+    history: Column<(u32, String), RawEncoding>,
+    #[storey(prefix = 1)]
+    owners: Map<[u8; 20], Item<std::vec::Vec<u8>, RawEncoding>>,
+    pairs: Map<(u16, String), Map<i32, Item<Vec<u8>, RawEncoding>>>,
+    #[storey(prefix = "votes")]
+    votes: Column<u64, RawEncoding>,
+}
+
+const FOO: ValueMeta = ValueMeta {
+    type_name: "Foo",
+    encoding: "test",
+};
+
+#[test]
+fn nested_map() {
+    const META: ContainerMeta = container_meta!(Map<String, Map<u64, Item<Foo, TestEncoding>>>);
+
+    assert_eq!(
+        META,
+        ContainerMeta::Map {
+            key: KeyMeta {
+                type_name: "String",
+                width: None,
+            },
+            value: &ContainerMeta::Map {
+                key: KeyMeta {
+                    type_name: "u64",
+                    width: Some(8),
+                },
+                value: &ContainerMeta::Item { value: FOO },
+            },
+        }
+    );
+
+    assert_eq!(META.value(), Some(&FOO));
+    let keys: Vec<_> = META.keys().iter().map(|key| key.type_name).collect();
+    assert_eq!(keys, ["String", "u64"]);
+}
+
+#[test]
+fn derived() {
+    const fn key(type_name: &'static str, width: Option<usize>) -> KeyMeta {
+        KeyMeta { type_name, width }
+    }
+    const fn raw(type_name: &'static str) -> ValueMeta {
+        ValueMeta {
+            type_name,
+            encoding: "raw",
+        }
+    }
+    const EXPECTED: &[ContainerEntry] = &[
+        ContainerEntry {
+            name: "config",
+            prefix: &[0],
+            container: ContainerMeta::Item { value: raw("Foo") },
+        },
+        ContainerEntry {
+            name: "positions",
+            prefix: &[3],
+            container: container_meta!(Map<String, Map<u64, Item<Foo, TestEncoding>>>),
+        },
+        ContainerEntry {
+            name: "history",
+            prefix: &[4],
+            container: ContainerMeta::Column {
+                value: raw("(u32, String)"),
+            },
+        },
+        ContainerEntry {
+            name: "owners",
+            prefix: &[1],
+            container: ContainerMeta::Map {
+                key: key("[u8; 20]", Some(20)),
+                value: &ContainerMeta::Item {
+                    value: raw("std::vec::Vec<u8>"),
+                },
+            },
+        },
+        ContainerEntry {
+            name: "pairs",
+            prefix: &[2],
+            container: ContainerMeta::Map {
+                key: key("(u16, String)", None),
+                value: &ContainerMeta::Map {
+                    key: key("i32", Some(4)),
+                    value: &ContainerMeta::Item {
+                        value: raw("Vec<u8>"),
+                    },
+                },
+            },
+        },
+        ContainerEntry {
+            name: "votes",
+            prefix: b"votes",
+            container: ContainerMeta::Column { value: raw("u64") },
+        },
+    ];
+
+    assert_eq!(State::CONTAINERS, EXPECTED);
+}
+
+#[test]
+fn widths() {
+    let width = |meta: ContainerMeta| meta.keys()[0].width;
+
+    assert_eq!(
+        width(container_meta!(Map<(u16, [u8; 4]), Item<u64, TestEncoding>>)),
+        Some(6)
+    );
+    assert_eq!(
+        width(container_meta!(Map<(u8, u16, u32), Item<u64, TestEncoding>>)),
+        Some(7)
+    );
+    assert_eq!(
+        width(container_meta!(Map<usize, Item<u64, TestEncoding>>)),
+        Some(8)
+    );
+    assert_eq!(
+        width(container_meta!(Map<Vec<u8>, Item<u64, TestEncoding>>)),
+        None
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialized() {
+    let json = serde_json::to_value(State::CONTAINERS[1]).unwrap();
+
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "name": "positions",
+            "prefix": [3],
+            "container": {
+                "kind": "map",
+                "key": { "type_name": "String", "width": null },
+                "value": {
+                    "kind": "map",
+                    "key": { "type_name": "u64", "width": 8 },
+                    "value": {
+                        "kind": "item",
+                        "value": { "type_name": "Foo", "encoding": "test" },
+                    },
+                },
+            },
+        })
+    );
+}
@@ -0,0 +1,250 @@
+// Running the steps declared with `migration!`: in order, only the ones needed, and resuming
+// steps that take several calls.
+
+use storey::containers::{Item, IterableAccessor as _, Map};
+use storey::migrate::{MigrationError, MigrationOutcome, StepOutcome, STORAGE_VERSION};
+use storey::storage::{IterableStorage as _, Storage as _, StorageMut as _};
+use storey_testing::backend::TestStorage;
+use storey_testing::encoding::TestEncoding;
+
+// version 1: balances by name
+const BALANCES_V1: Map<String, Item<u64, TestEncoding>> = Map::new(0);
+// version 2: balances by id, and a name for every id
+const BALANCES: Map<u32, Item<u64, TestEncoding>> = Map::new(1);
+const NAMES: Map<u32, Item<u64, TestEncoding>> = Map::new(2);
+// version 3: a total
+const TOTAL: Item<u64, TestEncoding> = Item::new(3);
+
+#[derive(Debug, PartialEq, Eq)]
+struct Failed;
+
+/// Moves the balances two at a time, giving them ids in the order of the raw keys.
+fn balances_by_id(storage: &mut TestStorage, cursor: Option<&[u8]>) -> Result<StepOutcome, Failed> {
+    let next_id = cursor.map_or(0, |cursor| u32::from_be_bytes(cursor.try_into().unwrap()));
+
+    let batch: Vec<_> = BALANCES_V1
+        .access(&*storage)
+        .pairs()
+        .take(2)
+        .collect::<Result<_, _>>()
+        .map_err(|_| Failed)?;
+
+    let mut id = next_id;
+    for ((name, ()), balance) in &batch {
+        BALANCES
+            .access(&mut *storage)
+            .entry_mut(&id)
+            .set(balance)
+            .unwrap();
+        NAMES
+            .access(&mut *storage)
+            .entry_mut(&id)
+            .set(&(name.len() as u64))
+            .unwrap();
+        storage.remove(&[&[0][..], &storey::key!(name)].concat());
+        id += 1;
+    }
+
+    if BALANCES_V1.access(&*storage).keys().next().is_some() {
+        Ok(StepOutcome::InProgress(id.to_be_bytes().to_vec()))
+    } else {
+        Ok(StepOutcome::Done)
+    }
+}
+
+fn total(storage: &mut TestStorage, _: Option<&[u8]>) -> Result<StepOutcome, Failed> {
+    let total = BALANCES
+        .access(&*storage)
+        .values()
+        .map(|balance| balance.unwrap())
+        .sum();
+    TOTAL.access(storage).set(&total).unwrap();
+    Ok(StepOutcome::Done)
+}
+
+fn failing(_: &mut TestStorage, _: Option<&[u8]>) -> Result<StepOutcome, Failed> {
+    Err(Failed)
+}
+
+mod app {
+    use super::*;
+
+    storey::migration! {
+        storage: TestStorage,
+        error: Failed,
+        steps: [
+            1 -> 2: balances_by_id,
+            2 -> 3: total,
+        ],
+    }
+}
+
+mod broken {
+    use super::*;
+
+    storey::migration! {
+        storage: TestStorage,
+        error: Failed,
+        steps: [
+            0 -> 1: failing,
+        ],
+    }
+}
+
+fn version_1(balances: &[(&str, u64)]) -> TestStorage {
+    let mut storage = TestStorage::new();
+    STORAGE_VERSION.access(&mut storage).set(&1).unwrap();
+    for (name, balance) in balances {
+        BALANCES_V1
+            .access(&mut storage)
+            .entry_mut(*name)
+            .set(balance)
+            .unwrap();
+    }
+    storage
+}
+
+fn version(storage: &TestStorage) -> Option<u32> {
+    STORAGE_VERSION.access(storage).get().unwrap()
+}
+
+#[test]
+fn latest_version() {
+    assert_eq!(app::LATEST_VERSION, 3);
+    assert_eq!(broken::LATEST_VERSION, 1);
+}
+
+#[test]
+fn across_two_versions() {
+    let mut storage = version_1(&[("amy", 5), ("bob", 7)]);
+
+    assert_eq!(
+        app::run_migrations(&mut storage, 3),
+        Ok(MigrationOutcome::UpToDate(3))
+    );
+    assert_eq!(version(&storage), Some(3));
+    assert_eq!(BALANCES.access(&storage).entry(&0).get(), Ok(Some(5)));
+    assert_eq!(BALANCES.access(&storage).entry(&1).get(), Ok(Some(7)));
+    assert_eq!(TOTAL.access(&storage).get(), Ok(Some(12)));
+
+    // nothing more to do
+    assert_eq!(
+        app::run_migrations(&mut storage, 3),
+        Ok(MigrationOutcome::UpToDate(3))
+    );
+}
+
+#[test]
+fn up_to_the_target() {
+    let mut storage = version_1(&[("alice", 5)]);
+
+    assert_eq!(
+        app::run_migrations(&mut storage, 2),
+        Ok(MigrationOutcome::UpToDate(2))
+    );
+    assert_eq!(version(&storage), Some(2));
+    assert_eq!(TOTAL.access(&storage).get(), Ok(None));
+
+    assert_eq!(
+        app::run_migrations(&mut storage, 3),
+        Ok(MigrationOutcome::UpToDate(3))
+    );
+    assert_eq!(TOTAL.access(&storage).get(), Ok(Some(5)));
+}
+
+#[test]
+fn resuming_a_chunked_step() {
+    let mut storage = version_1(&[("amy", 1), ("bob", 2), ("cat", 3), ("dan", 4), ("eve", 5)]);
+
+    for _ in 0..2 {
+        assert_eq!(
+            app::run_migrations(&mut storage, 3),
+            Ok(MigrationOutcome::InProgress { from: 1, to: 2 })
+        );
+        // the version only moves once the step is over
+        assert_eq!(version(&storage), Some(1));
+    }
+    assert_eq!(BALANCES.access(&storage).keys().count(), 4);
+
+    // the last chunk, and the next step in the same call
+    assert_eq!(
+        app::run_migrations(&mut storage, 3),
+        Ok(MigrationOutcome::UpToDate(3))
+    );
+    assert_eq!(
+        BALANCES
+            .access(&storage)
+            .pairs()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        [
+            ((0, ()), 1),
+            ((1, ()), 2),
+            ((2, ()), 3),
+            ((3, ()), 4),
+            ((4, ()), 5)
+        ]
+    );
+    assert_eq!(BALANCES_V1.access(&storage).keys().count(), 0);
+    assert_eq!(TOTAL.access(&storage).get(), Ok(Some(15)));
+
+    // the cursor is gone, and only the version is left under the reserved prefix
+    assert_eq!(
+        storage.get(&[STORAGE_VERSION.prefix()]),
+        Some(3u32.to_le_bytes().to_vec())
+    );
+    assert_eq!(storage.keys(Some(&[255]), None).count(), 0);
+}
+
+#[test]
+fn unversioned_storage() {
+    let mut storage = TestStorage::new();
+
+    // version 0, which no step of `app` starts at
+    assert_eq!(
+        app::run_migrations(&mut storage, 3),
+        Err(MigrationError::NoPath {
+            current: 0,
+            target: 3
+        })
+    );
+    assert_eq!(version(&storage), None);
+}
+
+#[test]
+fn failing_step() {
+    let mut storage = TestStorage::new();
+
+    assert_eq!(
+        broken::run_migrations(&mut storage, 1),
+        Err(MigrationError::Step {
+            from: 0,
+            to: 1,
+            source: Failed
+        })
+    );
+    assert_eq!(version(&storage), None);
+}
+
+#[test]
+fn bad_targets() {
+    let mut storage = version_1(&[]);
+
+    assert_eq!(
+        app::run_migrations(&mut storage, 0),
+        Err(MigrationError::Downgrade {
+            current: 1,
+            target: 0
+        })
+    );
+    assert_eq!(
+        app::run_migrations(&mut storage, 4),
+        Err(MigrationError::NoPath {
+            current: 1,
+            target: 4
+        })
+    );
+    // nothing ran
+    assert_eq!(version(&storage), Some(1));
+    assert_eq!(BALANCES.access(&storage).keys().count(), 0);
+}
@@ -0,0 +1,294 @@
+//! Model tests: random sequences of operations are applied both to a container and to a
+//! `BTreeMap` standing in for it, and the two have to agree after every step.
+//!
+//! Each container under test implements [`Harness`], which says how to run the operations
+//! against it and in which order it iterates. New containers can be checked by adding a
+//! `Harness` impl and a `proptest!` case running it.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use storey::containers::{BoundedIterableAccessor as _, Item, IterableAccessor as _, Map};
+use storey_testing::backend::TestStorage;
+use storey_testing::encoding::TestEncoding;
+
+#[derive(Debug, Clone)]
+enum Op<K> {
+    Set(K, u64),
+    Remove(K),
+    Get(K),
+    Range(Option<K>, Option<K>),
+    Clear,
+}
+
+fn ops<K: Debug + Clone>(
+    key: impl Strategy<Value = K> + Clone,
+) -> impl Strategy<Value = Vec<Op<K>>> {
+    let op = prop_oneof![
+        8 => (key.clone(), any::<u64>()).prop_map(|(k, v)| Op::Set(k, v)),
+        3 => key.clone().prop_map(Op::Remove),
+        3 => key.clone().prop_map(Op::Get),
+        3 => (proptest::option::of(key.clone()), proptest::option::of(key))
+            .prop_map(|(start, end)| Op::Range(start, end)),
+        1 => Just(Op::Clear),
+    ];
+    vec(op, 0..64)
+}
+
+/// A container under test, with the keys of its reference model.
+trait Harness {
+    type Key: Debug + Clone + Ord;
+
+    fn set(storage: &mut TestStorage, key: &Self::Key, value: u64);
+
+    fn remove(storage: &mut TestStorage, key: &Self::Key);
+
+    fn get(storage: &TestStorage, key: &Self::Key) -> Option<u64>;
+
+    fn clear(storage: &mut TestStorage);
+
+    /// Every entry, in the order the container iterates in.
+    fn entries(storage: &TestStorage) -> Vec<(Self::Key, u64)>;
+
+    /// The entries from `start` (inclusive) to `end` (exclusive), or `None` if the container
+    /// can't iterate over a range.
+    fn range(
+        storage: &TestStorage,
+        start: Option<&Self::Key>,
+        end: Option<&Self::Key>,
+    ) -> Option<Vec<(Self::Key, u64)>>;
+
+    /// The order the container iterates in, which is the order of the raw keys.
+    fn order(a: &Self::Key, b: &Self::Key) -> Ordering;
+}
+
+/// The entries of the model, in the order the container should iterate in.
+fn sorted<H: Harness>(model: &BTreeMap<H::Key, u64>) -> Vec<(H::Key, u64)> {
+    let mut entries: Vec<_> = model.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    entries.sort_by(|(a, _), (b, _)| H::order(a, b));
+    entries
+}
+
+fn run<H: Harness>(ops: Vec<Op<H::Key>>) -> Result<(), TestCaseError> {
+    let mut storage = TestStorage::new();
+    let mut model = BTreeMap::new();
+
+    for op in ops {
+        match &op {
+            Op::Set(key, value) => {
+                H::set(&mut storage, key, *value);
+                model.insert(key.clone(), *value);
+            }
+            Op::Remove(key) => {
+                H::remove(&mut storage, key);
+                model.remove(key);
+            }
+            Op::Get(key) => {
+                prop_assert_eq!(H::get(&storage, key), model.get(key).copied(), "{:?}", op);
+            }
+            Op::Range(start, end) => {
+                if let Some(actual) = H::range(&storage, start.as_ref(), end.as_ref()) {
+                    let in_range = |key: &H::Key| {
+                        start.as_ref().map_or(true, |s| H::order(key, s).is_ge())
+                            && end.as_ref().map_or(true, |e| H::order(key, e).is_lt())
+                    };
+                    let mut expected = sorted::<H>(&model);
+                    expected.retain(|(key, _)| in_range(key));
+                    prop_assert_eq!(actual, expected, "{:?}", op);
+                }
+            }
+            Op::Clear => {
+                H::clear(&mut storage);
+                model.clear();
+            }
+        }
+
+        prop_assert_eq!(H::entries(&storage), sorted::<H>(&model), "after {:?}", op);
+    }
+
+    Ok(())
+}
+
+/// String keys are length-prefixed, so a map iterates over them shortest first.
+fn string_order(a: &str, b: &str) -> Ordering {
+    (a.len(), a).cmp(&(b.len(), b))
+}
+
+fn string_key() -> impl Strategy<Value = String> + Clone {
+    "[ab€]{0,3}"
+}
+
+struct StringMap;
+
+const STRING_MAP: Map<String, Item<u64, TestEncoding>> = Map::new(0);
+
+impl Harness for StringMap {
+    type Key = String;
+
+    fn set(storage: &mut TestStorage, key: &String, value: u64) {
+        STRING_MAP
+            .access(storage)
+            .entry_mut(key)
+            .set(&value)
+            .unwrap();
+    }
+
+    fn remove(storage: &mut TestStorage, key: &String) {
+        STRING_MAP.access(storage).entry_mut(key).remove();
+    }
+
+    fn get(storage: &TestStorage, key: &String) -> Option<u64> {
+        STRING_MAP.access(storage).entry(key).get().unwrap()
+    }
+
+    fn clear(storage: &mut TestStorage) {
+        STRING_MAP.access(storage).clear();
+    }
+
+    fn entries(storage: &TestStorage) -> Vec<(String, u64)> {
+        STRING_MAP
+            .access(storage)
+            .pairs()
+            .map(|pair| pair.map(|((key, ()), value)| (key, value)))
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    fn range(
+        _: &TestStorage,
+        _: Option<&String>,
+        _: Option<&String>,
+    ) -> Option<Vec<(String, u64)>> {
+        // string keys don't sort like their raw encoding
+        None
+    }
+
+    fn order(a: &String, b: &String) -> Ordering {
+        string_order(a, b)
+    }
+}
+
+struct IntMap;
+
+const INT_MAP: Map<u32, Item<u64, TestEncoding>> = Map::new(0);
+
+impl Harness for IntMap {
+    type Key = u32;
+
+    fn set(storage: &mut TestStorage, key: &u32, value: u64) {
+        INT_MAP.access(storage).entry_mut(key).set(&value).unwrap();
+    }
+
+    fn remove(storage: &mut TestStorage, key: &u32) {
+        INT_MAP.access(storage).entry_mut(key).remove();
+    }
+
+    fn get(storage: &TestStorage, key: &u32) -> Option<u64> {
+        INT_MAP.access(storage).entry(key).get().unwrap()
+    }
+
+    fn clear(storage: &mut TestStorage) {
+        INT_MAP.access(storage).clear();
+    }
+
+    fn entries(storage: &TestStorage) -> Vec<(u32, u64)> {
+        Self::range(storage, None, None).unwrap()
+    }
+
+    fn range(
+        storage: &TestStorage,
+        start: Option<&u32>,
+        end: Option<&u32>,
+    ) -> Option<Vec<(u32, u64)>> {
+        let pairs = INT_MAP
+            .access(storage)
+            .bounded_pairs(start.copied(), end.copied())
+            .map(|pair| pair.map(|((key, ()), value)| (key, value)))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        Some(pairs)
+    }
+
+    fn order(a: &u32, b: &u32) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+struct NestedMap;
+
+const NESTED_MAP: Map<String, Map<String, Item<u64, TestEncoding>>> = Map::new(0);
+
+impl Harness for NestedMap {
+    type Key = (String, String);
+
+    fn set(storage: &mut TestStorage, (outer, inner): &(String, String), value: u64) {
+        NESTED_MAP
+            .access(storage)
+            .entry_mut(outer)
+            .entry_mut(inner)
+            .set(&value)
+            .unwrap();
+    }
+
+    fn remove(storage: &mut TestStorage, (outer, inner): &(String, String)) {
+        NESTED_MAP
+            .access(storage)
+            .entry_mut(outer)
+            .entry_mut(inner)
+            .remove();
+    }
+
+    fn get(storage: &TestStorage, (outer, inner): &(String, String)) -> Option<u64> {
+        NESTED_MAP
+            .access(storage)
+            .entry(outer)
+            .entry(inner)
+            .get()
+            .unwrap()
+    }
+
+    fn clear(storage: &mut TestStorage) {
+        NESTED_MAP.access(storage).clear();
+    }
+
+    fn entries(storage: &TestStorage) -> Vec<((String, String), u64)> {
+        NESTED_MAP
+            .access(storage)
+            .pairs()
+            .map(|pair| pair.map(|((outer, (inner, ())), value)| ((outer, inner), value)))
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    fn range(
+        _: &TestStorage,
+        _: Option<&(String, String)>,
+        _: Option<&(String, String)>,
+    ) -> Option<Vec<((String, String), u64)>> {
+        None
+    }
+
+    fn order(a: &(String, String), b: &(String, String)) -> Ordering {
+        string_order(&a.0, &b.0).then_with(|| string_order(&a.1, &b.1))
+    }
+}
+
+proptest! {
+    #[test]
+    fn string_map(ops in ops(string_key())) {
+        run::<StringMap>(ops)?;
+    }
+
+    #[test]
+    fn int_map(ops in ops(prop_oneof![0u32..16, any::<u32>()])) {
+        run::<IntMap>(ops)?;
+    }
+
+    #[test]
+    fn nested_map(ops in ops((string_key(), string_key()))) {
+        run::<NestedMap>(ops)?;
+    }
+}
@@ -0,0 +1,155 @@
+// A container made with `new(b)` and one made with `new_with_prefix(&[b])` are the same
+// container: data written through one is read back through the other. String prefixes are
+// their UTF-8 bytes.
+
+use storey::containers::{assert_unique_prefixes, Column, Item, IterableAccessor as _, Map};
+use storey::dump::export;
+use storey::prefix;
+use storey::storage::IterableStorage as _;
+use storey_testing::backend::TestStorage;
+use storey_testing::encoding::TestEncoding;
+
+fn raw_pairs(storage: &TestStorage) -> Vec<(Vec<u8>, Vec<u8>)> {
+    storage.pairs(None, None).collect()
+}
+
+#[test]
+fn items() {
+    let by_byte = Item::<u64, TestEncoding>::new(3);
+    let by_slice = Item::<u64, TestEncoding>::new_with_prefix(&[3]);
+
+    let mut storage = TestStorage::new();
+    by_byte.access(&mut storage).set(&1337).unwrap();
+    assert_eq!(by_slice.access(&storage).get().unwrap(), Some(1337));
+
+    let mut other = TestStorage::new();
+    by_slice.access(&mut other).set(&1337).unwrap();
+    assert_eq!(raw_pairs(&other), raw_pairs(&storage));
+
+    assert_eq!(by_slice.prefix(), 3);
+    assert_eq!(by_slice, by_byte);
+}
+
+#[test]
+fn maps() {
+    let by_byte = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(3);
+    let by_slice = Map::<String, Map<String, Item<u64, TestEncoding>>>::new_with_prefix(&[3]);
+
+    let mut storage = TestStorage::new();
+    let mut access = by_byte.access(&mut storage);
+    access.entry_mut("alice").entry_mut("bob").set(&5).unwrap();
+    access.entry_mut("carol").entry_mut("dave").set(&7).unwrap();
+
+    let access = by_slice.access(&storage);
+    assert_eq!(access.entry("alice").entry("bob").get().unwrap(), Some(5));
+    let pairs = access.pairs().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(
+        pairs,
+        [
+            (("alice".to_string(), ("bob".to_string(), ())), 5),
+            (("carol".to_string(), ("dave".to_string(), ())), 7),
+        ]
+    );
+
+    let mut other = TestStorage::new();
+    let mut access = by_slice.access(&mut other);
+    access.entry_mut("alice").entry_mut("bob").set(&5).unwrap();
+    access.entry_mut("carol").entry_mut("dave").set(&7).unwrap();
+    assert_eq!(raw_pairs(&other), raw_pairs(&storage));
+
+    assert_eq!(by_slice.prefix(), 3);
+}
+
+#[test]
+fn columns() {
+    let by_byte = Column::<u64, TestEncoding>::new(3);
+    let by_slice = Column::<u64, TestEncoding>::new_with_prefix(&[3]);
+
+    let mut storage = TestStorage::new();
+    by_byte.access(&mut storage).push(&1).unwrap();
+    by_byte.access(&mut storage).push(&2).unwrap();
+
+    // the metadata is shared too, so pushing carries on from the last index
+    let mut access = by_slice.access(&mut storage);
+    assert_eq!(access.len().unwrap(), 2);
+    assert_eq!(access.push(&3).unwrap(), 2);
+    assert_eq!(by_byte.access(&storage).get(2).unwrap(), Some(3));
+
+    let mut other = TestStorage::new();
+    for value in [1, 2, 3] {
+        by_slice.access(&mut other).push(&value).unwrap();
+    }
+    assert_eq!(raw_pairs(&other), raw_pairs(&storage));
+
+    assert_eq!(by_slice.prefix(), 3);
+}
+
+#[test]
+fn string_prefixes() {
+    let config = Item::<u64, TestEncoding>::new_str("config");
+    let balances = Map::<String, Item<u64, TestEncoding>>::new_str("balances");
+    let allowances = Map::<String, Map<String, Item<u64, TestEncoding>>>::new_str("allowances");
+    let flag = Item::<u64, TestEncoding>::new(0);
+
+    let mut storage = TestStorage::new();
+    config.access(&mut storage).set(&1).unwrap();
+    balances
+        .access(&mut storage)
+        .entry_mut("alice")
+        .set(&100)
+        .unwrap();
+    allowances
+        .access(&mut storage)
+        .entry_mut("alice")
+        .entry_mut("bob")
+        .set(&5)
+        .unwrap();
+    flag.access(&mut storage).set(&2).unwrap();
+
+    // the names show up in a raw dump, with the length bytes of map keys between the parts
+    let keys: Vec<_> = export(&storage, &[b""])
+        .entries
+        .into_iter()
+        .map(|entry| String::from_utf8(entry.key).unwrap())
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            "\0",
+            "allowances\u{5}alice\u{3}bob",
+            "balances\u{5}alice",
+            "config"
+        ]
+    );
+
+    // the single-byte container sits apart, and the string ones are the named prefixes
+    assert_eq!(flag.access(&storage).get().unwrap(), Some(2));
+    assert_eq!(config, Item::new_with_prefix(prefix!("config")));
+    assert_eq!(balances.prefix_bytes(), b"balances");
+    assert_eq!(Item::<u64, TestEncoding>::new_str("c"), Item::new(b'c'));
+
+    // none of them starts with another, so they pass the compile-time check
+    const _: () = assert_unique_prefixes(&[
+        Item::<u64, TestEncoding>::new_str("config").prefix_bytes(),
+        Map::<String, Item<u64, TestEncoding>>::new_str("balances").prefix_bytes(),
+        Map::<String, Item<u64, TestEncoding>>::new_str("allowances").prefix_bytes(),
+        Item::<u64, TestEncoding>::new(0).prefix_bytes(),
+    ]);
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_constructors() {
+    assert_eq!(
+        Item::<u64, TestEncoding>::with_prefix(b"config"),
+        Item::<u64, TestEncoding>::new_with_prefix(b"config")
+    );
+    assert_eq!(
+        Map::<String, Item<u64, TestEncoding>>::with_prefix(&[3]),
+        Map::<String, Item<u64, TestEncoding>>::new(3)
+    );
+    assert_eq!(
+        Column::<u64, TestEncoding>::with_prefix(b"history").prefix_bytes(),
+        b"history"
+    );
+}
@@ -0,0 +1,156 @@
+// What `root!` expands to: the containers with their prefixes, and the `LAYOUT` constant.
+
+use storey::containers::{Column, Item, Map};
+use storey::root::{RootEntry, RootLayout};
+use storey_testing::backend::TestStorage;
+use storey_testing::encoding::TestEncoding;
+
+mod state {
+    use super::*;
+
+    storey::root! {
+        /// The configuration.
+        pub CONFIG: Item<u64, TestEncoding>,
+        pub BALANCES: Map<String, Item<u64, TestEncoding>>,
+        pub HISTORY: Column<u64, TestEncoding> = 5,
+        pub CLAIMS: Map<u32, Map<String, Item<u64, TestEncoding>>>,
+        pub(crate) OWNER: Item<u64, TestEncoding> = 2,
+        LAST: Item<u64, TestEncoding>,
+    }
+
+    pub fn last() -> Item<u64, TestEncoding> {
+        LAST
+    }
+}
+
+mod single {
+    use super::*;
+
+    storey::root! { pub ONLY: Item<u64, TestEncoding> = 9 }
+}
+
+mod empty {
+    storey::root! {}
+}
+
+#[test]
+fn prefixes() {
+    assert_eq!(state::CONFIG.prefix(), 0);
+    assert_eq!(state::BALANCES.prefix(), 1);
+    assert_eq!(state::HISTORY.prefix(), 5);
+    assert_eq!(state::CLAIMS.prefix(), 6);
+    assert_eq!(state::OWNER.prefix(), 2);
+    assert_eq!(state::last().prefix(), 3);
+
+    assert_eq!(single::ONLY.prefix(), 9);
+}
+
+#[test]
+fn containers_work() {
+    let mut storage = TestStorage::new();
+
+    state::CONFIG.access(&mut storage).set(&1).unwrap();
+    state::BALANCES
+        .access(&mut storage)
+        .entry_mut("alice")
+        .set(&2)
+        .unwrap();
+    state::HISTORY.access(&mut storage).push(&3).unwrap();
+
+    assert_eq!(state::CONFIG.access(&storage).get().unwrap(), Some(1));
+    assert_eq!(
+        state::BALANCES
+            .access(&storage)
+            .entry("alice")
+            .get()
+            .unwrap(),
+        Some(2)
+    );
+    assert_eq!(state::HISTORY.access(&storage).get(0).unwrap(), Some(3));
+}
+
+#[test]
+fn layout() {
+    let entry = |name, prefix, container| RootEntry {
+        name,
+        prefix,
+        container,
+    };
+
+    assert_eq!(
+        state::LAYOUT.containers,
+        [
+            entry("CONFIG", &[0], "Item<u64, TestEncoding>"),
+            entry("BALANCES", &[1], "Map<String, Item<u64, TestEncoding>>"),
+            entry("HISTORY", &[5], "Column<u64, TestEncoding>"),
+            entry(
+                "CLAIMS",
+                &[6],
+                "Map<u32, Map<String, Item<u64, TestEncoding>>>"
+            ),
+            entry("OWNER", &[2], "Item<u64, TestEncoding>"),
+            entry("LAST", &[3], "Item<u64, TestEncoding>"),
+        ]
+    );
+    assert_eq!(state::LAYOUT.by_prefix(&[5]).unwrap().name, "HISTORY");
+    assert_eq!(state::LAYOUT.by_prefix(&[4]), None);
+
+    assert_eq!(empty::LAYOUT, RootLayout { containers: &[] });
+}
+
+// The document tools get, which shouldn't change without a reason.
+#[cfg(feature = "serde")]
+#[test]
+fn layout_document() {
+    let document = serde_json::to_string_pretty(&state::LAYOUT).unwrap();
+
+    assert_eq!(
+        document,
+        r#"{
+  "containers": [
+    {
+      "name": "CONFIG",
+      "prefix": [
+        0
+      ],
+      "container": "Item<u64, TestEncoding>"
+    },
+    {
+      "name": "BALANCES",
+      "prefix": [
+        1
+      ],
+      "container": "Map<String, Item<u64, TestEncoding>>"
+    },
+    {
+      "name": "HISTORY",
+      "prefix": [
+        5
+      ],
+      "container": "Column<u64, TestEncoding>"
+    },
+    {
+      "name": "CLAIMS",
+      "prefix": [
+        6
+      ],
+      "container": "Map<u32, Map<String, Item<u64, TestEncoding>>>"
+    },
+    {
+      "name": "OWNER",
+      "prefix": [
+        2
+      ],
+      "container": "Item<u64, TestEncoding>"
+    },
+    {
+      "name": "LAST",
+      "prefix": [
+        3
+      ],
+      "container": "Item<u64, TestEncoding>"
+    }
+  ]
+}"#
+    );
+}
@@ -0,0 +1,62 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use storey::containers::{Column, Item, Map};
+use storey::encoding::MsgPackEncoding;
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct Position {
+    owner: String,
+    shares: u64,
+    tags: Vec<String>,
+}
+
+#[test]
+fn map_of_items() {
+    let positions = Map::<String, Item<Position, MsgPackEncoding>>::new(3);
+
+    assert_eq!(
+        serde_json::to_value(positions.schema()).unwrap(),
+        json!({
+            "prefix": [3],
+            "key_structure": "String",
+            "container": {
+                "container": "map",
+                "key_name": "String",
+                "key": { "type": "string" },
+                "value": {
+                    "container": "item",
+                    "value": { "$ref": "#/definitions/Position" }
+                }
+            },
+            "definitions": {
+                "Position": {
+                    "type": "object",
+                    "required": ["owner", "shares", "tags"],
+                    "properties": {
+                        "owner": { "type": "string" },
+                        "shares": { "type": "integer", "format": "uint64", "minimum": 0.0 },
+                        "tags": { "type": "array", "items": { "type": "string" } }
+                    }
+                }
+            }
+        })
+    );
+}
+
+#[test]
+fn named_prefixes() {
+    let config = Item::<String, MsgPackEncoding>::new_str("config");
+    let positions = Map::<String, Item<Position, MsgPackEncoding>>::new_str("positions");
+    let history = Column::<u64, MsgPackEncoding>::new_str("history");
+
+    assert_eq!(config.schema().prefix, b"config");
+    assert_eq!(positions.schema().prefix, b"positions");
+    assert_eq!(history.schema().prefix, b"history");
+
+    // the bytes, not a string, since prefixes don't have to be UTF-8
+    assert_eq!(
+        serde_json::to_value(config.schema()).unwrap()["prefix"],
+        json!(b"config")
+    );
+}
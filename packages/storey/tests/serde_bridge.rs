@@ -0,0 +1,67 @@
+// A plain `serde` type, stored with different encodings without implementing any of the
+// encoding traits for it.
+
+use serde::{Deserialize, Serialize};
+use storey::containers::{Column, Item, IterableAccessor as _, Map};
+use storey::encoding::{JsonEncoding, MsgPackEncoding};
+
+use storey_storage::Storage as _;
+use storey_testing::backend::TestStorage;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Position {
+    owner: String,
+    shares: u64,
+    tags: Vec<String>,
+}
+
+fn position() -> Position {
+    Position {
+        owner: "alice".to_string(),
+        shares: 7,
+        tags: vec!["early".to_string()],
+    }
+}
+
+#[test]
+fn two_encodings() {
+    let mut storage = TestStorage::new();
+
+    let json = Item::<Position, JsonEncoding>::new(0);
+    let msgpack = Item::<Position, MsgPackEncoding>::new(1);
+
+    json.access(&mut storage).set(&position()).unwrap();
+    msgpack.access(&mut storage).set(&position()).unwrap();
+
+    assert_eq!(json.access(&storage).get().unwrap(), Some(position()));
+    assert_eq!(msgpack.access(&storage).get().unwrap(), Some(position()));
+
+    assert_eq!(
+        storage.get(&[0]).unwrap(),
+        br#"{"owner":"alice","shares":7,"tags":["early"]}"#
+    );
+    assert_eq!(
+        storage.get(&[1]).unwrap(),
+        rmp_serde::to_vec_named(&position()).unwrap()
+    );
+}
+
+#[test]
+fn nested_containers() {
+    let mut storage = TestStorage::new();
+
+    let positions = Map::<String, Column<Position, MsgPackEncoding>>::new(0);
+
+    let mut access = positions.access(&mut storage);
+    access.entry_mut("pool-1").push(&position()).unwrap();
+    access.entry_mut("pool-1").push(&position()).unwrap();
+
+    assert_eq!(
+        access
+            .entry("pool-1")
+            .values()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        vec![position(), position()]
+    );
+}
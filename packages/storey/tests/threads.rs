@@ -0,0 +1,91 @@
+// Containers are `Send` and `Sync` whatever they store, and accessors and iterators are
+// whenever their storage is.
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::thread;
+
+use storey::containers::{
+    Column, ColumnAccess, Hashed, Item, ItemAccess, IterableAccessor as _, Map, MapAccess,
+};
+use storey::StorageBranch;
+use storey_testing::encoding::TestEncoding;
+
+type Backend = BTreeMap<Vec<u8>, Vec<u8>>;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+fn assert_value_send_sync<T: Send + Sync>(_: &T) {}
+
+#[test]
+fn containers() {
+    assert_send_sync::<Item<u64, TestEncoding>>();
+    assert_send_sync::<Map<String, Column<u64, TestEncoding>>>();
+    assert_send_sync::<Map<Hashed<str, ()>, Item<u64, TestEncoding>>>();
+
+    // not even the types they store have to be thread-safe
+    assert_send_sync::<Item<Rc<u64>, Cell<u8>>>();
+    assert_send_sync::<Map<Rc<str>, Column<Cell<u64>, Rc<()>>>>();
+    assert_send_sync::<Hashed<Rc<str>, Cell<u8>>>();
+}
+
+#[test]
+fn accessors() {
+    type Branch<'a> = StorageBranch<&'a Backend>;
+    type BranchMut<'a> = StorageBranch<&'a mut Backend>;
+
+    assert_send_sync::<ItemAccess<TestEncoding, Rc<u64>, Branch>>();
+    assert_send_sync::<ColumnAccess<TestEncoding, Rc<u64>, BranchMut>>();
+    assert_send_sync::<MapAccess<Rc<str>, Item<Cell<u64>, TestEncoding>, Branch>>();
+
+    let mut storage = Backend::new();
+    let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
+    let column = Column::<u64, TestEncoding>::new(1);
+
+    assert_value_send_sync(&map.access(&storage));
+    assert_value_send_sync(&map.access(&storage).entry("foo"));
+    assert_value_send_sync(&map.access(&mut storage).entry_mut("foo"));
+    assert_value_send_sync(&column.access(&mut storage));
+}
+
+#[test]
+fn iterators() {
+    let storage = Backend::new();
+    let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
+    let column = Column::<u64, TestEncoding>::new(1);
+
+    let access = map.access(&storage);
+    assert_value_send_sync(&access.pairs());
+    assert_value_send_sync(&access.keys());
+    assert_value_send_sync(&access.values());
+    assert_value_send_sync(&column.access(&storage).pairs());
+}
+
+#[test]
+fn across_threads() {
+    let mut storage = Backend::new();
+    let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+
+    let mut access = map.access(&mut storage);
+    thread::scope(|s| {
+        s.spawn(|| {
+            access.entry_mut("foo").set(&1337).unwrap();
+            access.entry_mut("bar").set(&42).unwrap();
+        });
+    });
+
+    let access = map.access(&storage);
+    let mut pairs = access.pairs();
+    let first = pairs.next().unwrap().unwrap();
+
+    // the iterator carries on in another thread, while this one reads too
+    let rest = thread::scope(|s| {
+        let rest = s.spawn(move || pairs.collect::<Result<Vec<_>, _>>().unwrap());
+        assert_eq!(access.entry("foo").get().unwrap(), Some(1337));
+        rest.join().unwrap()
+    });
+
+    assert_eq!(first, (("bar".to_string(), ()), 42));
+    assert_eq!(rest, [(("foo".to_string(), ()), 1337)]);
+}